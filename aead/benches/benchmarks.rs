@@ -186,3 +186,67 @@ fn bench_x_cha_cha20_poly1305_decrypt_fail(b: &mut Bencher) {
     let (a, ct) = setup_failure(tink_aead::x_cha_cha20_poly1305_key_template());
     b.iter(|| a.decrypt(&ct, AAD).unwrap_err());
 }
+
+/// Number of keys in the keyset for the large-keyset benchmarks.
+const LARGE_KEYSET_SIZE: usize = 10_000;
+
+/// Create an AEAD for a keyset with [`LARGE_KEYSET_SIZE`] AES-128-GCM keys, together with a
+/// ciphertext produced by the first (oldest) key and one produced by the last (primary) key.
+fn setup_large_keyset() -> (Box<dyn tink_core::Aead>, Vec<u8>, Vec<u8>) {
+    tink_aead::init();
+    let kt = tink_aead::aes128_gcm_key_template();
+    let mut ksm = tink_core::keyset::Manager::new();
+    ksm.rotate(&kt).unwrap();
+    let oldest_ct = tink_aead::new(&ksm.handle().unwrap())
+        .unwrap()
+        .encrypt(MSG, AAD)
+        .unwrap();
+    for _ in 1..LARGE_KEYSET_SIZE {
+        ksm.rotate(&kt).unwrap();
+    }
+    let a = tink_aead::new(&ksm.handle().unwrap()).unwrap();
+    let primary_ct = a.encrypt(MSG, AAD).unwrap();
+    (a, oldest_ct, primary_ct)
+}
+
+#[bench]
+fn bench_large_keyset_decrypt_oldest(b: &mut Bencher) {
+    let (a, ct, _) = setup_large_keyset();
+    b.iter(|| a.decrypt(&ct, AAD).unwrap());
+}
+
+#[bench]
+fn bench_large_keyset_decrypt_primary(b: &mut Bencher) {
+    let (a, _, ct) = setup_large_keyset();
+    b.iter(|| a.decrypt(&ct, AAD).unwrap());
+}
+
+/// Number of keys in the keyset for the startup benchmarks, representative of an archival
+/// keyset that has been rotated many times.
+const ARCHIVAL_KEYSET_SIZE: usize = 50_000;
+
+/// Create a handle for a keyset with [`ARCHIVAL_KEYSET_SIZE`] AES-128-GCM keys, using the given
+/// key parsing policy.
+fn setup_archival_keyset(parsing: tink_core::keyset::KeyParsing) -> tink_core::keyset::Handle {
+    tink_aead::init();
+    let kt = tink_aead::aes128_gcm_key_template();
+    let mut ksm = tink_core::keyset::Manager::new();
+    for _ in 0..ARCHIVAL_KEYSET_SIZE {
+        ksm.rotate(&kt).unwrap();
+    }
+    let mut kh = ksm.handle().unwrap();
+    kh.set_key_parsing(parsing);
+    kh
+}
+
+#[bench]
+fn bench_archival_keyset_new_eager(b: &mut Bencher) {
+    let kh = setup_archival_keyset(tink_core::keyset::KeyParsing::Eager);
+    b.iter(|| tink_aead::new(&kh).unwrap());
+}
+
+#[bench]
+fn bench_archival_keyset_new_lazy(b: &mut Bencher) {
+    let kh = setup_archival_keyset(tink_core::keyset::KeyParsing::Lazy);
+    b.iter(|| tink_aead::new(&kh).unwrap());
+}
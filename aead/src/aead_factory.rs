@@ -16,13 +16,83 @@
 
 //! Provides an implementation of AEAD using a set of underlying implementations.
 
-use tink_core::{utils::wrap_err, TinkError};
+use tink_core::{primitiveset::TypedEntry, utils::wrap_err, TinkError};
 
 /// Returns a [`tink_core::Aead`] primitive from the given keyset handle.
 pub fn new(h: &tink_core::keyset::Handle) -> Result<Box<dyn tink_core::Aead>, TinkError> {
     new_with_key_manager(h, None)
 }
 
+/// Return a [`tink_core::Aead`] primitive from the given keyset handle, whose KMS envelope keys
+/// pass the caller's associated data to the remote KMS as specified, so that the KMS can enforce
+/// policy tied to the data's context.
+///
+/// Fails if the keyset contains a KMS envelope key whose KMS client does not
+/// [support](tink_core::registry::KmsClient::supports_associated_data) associated data.  Keys of
+/// other types are unaffected.
+pub fn new_with_kms_associated_data(
+    h: &tink_core::keyset::Handle,
+    associated_data: crate::KmsAssociatedData,
+) -> Result<Box<dyn tink_core::Aead>, TinkError> {
+    new_with_key_manager(
+        h,
+        Some(std::sync::Arc::new(
+            crate::KmsEnvelopeAeadKeyManager::new_with_associated_data(associated_data),
+        )),
+    )
+}
+
+/// Return a [`tink_core::Aead`] primitive from the given keyset handle, whose KMS envelope keys
+/// only use data encryption keys permitted by `policy`.
+///
+/// Fails if the keyset contains a KMS envelope key whose DEK template is not permitted.  Keys of
+/// other types are unaffected.
+pub fn new_with_kms_dek_policy(
+    h: &tink_core::keyset::Handle,
+    policy: crate::KmsEnvelopeDekPolicy,
+) -> Result<Box<dyn tink_core::Aead>, TinkError> {
+    new_with_key_manager(
+        h,
+        Some(std::sync::Arc::new(
+            crate::KmsEnvelopeAeadKeyManager::new_with_dek_policy(policy),
+        )),
+    )
+}
+
+/// Return a [`tink_core::Aead`] primitive from the given keyset handle, whose AES-GCM keys absorb
+/// associated data supplied to
+/// [`encrypt_with_aad_reader`](tink_core::Aead::encrypt_with_aad_reader) and
+/// [`decrypt_with_aad_reader`](tink_core::Aead::decrypt_with_aad_reader) incrementally (as
+/// ChaCha20-Poly1305 and XChaCha20-Poly1305 keys always do), rather than reading it into memory.
+///
+/// The primitives for these keys hold an additional (zeroized on drop) copy of the raw key.
+pub fn new_with_aad_reader(
+    h: &tink_core::keyset::Handle,
+) -> Result<Box<dyn tink_core::Aead>, TinkError> {
+    new_with_key_manager(
+        h,
+        Some(std::sync::Arc::new(
+            crate::AesGcmKeyManager::new_with_aad_reader(),
+        )),
+    )
+}
+
+/// Return a [`tink_core::Aead`] primitive for a single key, bypassing keyset wrapping.
+///
+/// The primitive's ciphertexts have no output prefix, and none of the keyset-level protections
+/// apply; prefer [`new`] with a keyset [`Handle`](tink_core::keyset::Handle) unless exactly one
+/// raw key is available.  Registered gatekeepers are consulted before the primitive is created.
+pub fn unwrapped_aead_from_key_data(
+    kd: &tink_proto::KeyData,
+) -> Result<Box<dyn tink_core::Aead>, TinkError> {
+    match tink_core::registry::unwrapped_primitive_from_key_data(kd)
+        .map_err(|e| wrap_err("aead::factory: cannot create primitive from key", e))?
+    {
+        tink_core::Primitive::Aead(p) => Ok(p),
+        _ => Err("aead::factory: not an AEAD primitive".into()),
+    }
+}
+
 /// Return a [`tink_core::Aead`] primitive from the given keyset handle and custom key
 /// manager.
 fn new_with_key_manager(
@@ -46,6 +116,9 @@ struct WrappedAead {
 
 impl WrappedAead {
     fn new(ps: tink_core::primitiveset::PrimitiveSet) -> Result<WrappedAead, TinkError> {
+        if !ps.usage.allows_encrypt() && !ps.usage.allows_decrypt() {
+            return Err("aead::factory: keyset usage does not permit AEAD".into());
+        }
         let entry = match &ps.primary {
             None => return Err("aead::factory: no primary primitive".into()),
             Some(p) => p,
@@ -68,47 +141,143 @@ impl WrappedAead {
     }
 }
 
-impl tink_core::Aead for WrappedAead {
-    fn encrypt(&self, pt: &[u8], aad: &[u8]) -> Result<Vec<u8>, TinkError> {
-        let primary = self
-            .ps
+impl WrappedAead {
+    /// Check that the primary key may be used to encrypt `pt_len` bytes, and return it.
+    fn primary_for_encrypt(
+        &self,
+        op: &tink_core::trace::Operation,
+        pt_len: usize,
+    ) -> Result<&TypedEntry<Box<dyn tink_core::Aead>>, TinkError> {
+        if !self.ps.usage.allows_encrypt() {
+            op.failed("encryption not permitted");
+            return Err("aead::factory: encryption not permitted".into());
+        }
+        self.ps.check_primary_validity().map_err(|e| {
+            op.failed("primary key not valid");
+            e
+        })?;
+        self.ps.record_primary_usage(pt_len).map_err(|e| {
+            op.failed("primary key usage limit reached");
+            e
+        })?;
+        self.ps
             .primary
             .as_ref()
-            .ok_or_else(|| TinkError::new("no primary"))?;
-
-        let ct = primary.primitive.encrypt(pt, aad)?;
-
-        let mut ret = Vec::with_capacity(primary.prefix.len() + ct.len());
-        ret.extend_from_slice(&primary.prefix);
-        ret.extend_from_slice(&ct);
-        Ok(ret)
+            .ok_or_else(|| TinkError::new("no primary"))
     }
 
-    fn decrypt(&self, ct: &[u8], aad: &[u8]) -> Result<Vec<u8>, TinkError> {
-        // try non-raw keys
+    /// Return the entries that may have produced `ct`, together with the ciphertext that each
+    /// should decrypt: keys whose prefix matches first, then raw keys.
+    fn decrypt_candidates<'a>(
+        &'a self,
+        ct: &'a [u8],
+    ) -> Vec<(&'a TypedEntry<Box<dyn tink_core::Aead>>, &'a [u8])> {
+        let mut candidates = Vec::new();
         let prefix_size = tink_core::cryptofmt::NON_RAW_PREFIX_SIZE;
         if ct.len() > prefix_size {
             let prefix = &ct[..prefix_size];
             let ct_no_prefix = &ct[prefix_size..];
             if let Some(entries) = self.ps.entries_for_prefix(prefix) {
-                for entry in entries {
-                    if let Ok(pt) = entry.primitive.decrypt(ct_no_prefix, aad) {
-                        return Ok(pt);
-                    }
-                }
+                candidates.extend(entries.iter().map(|entry| (entry, ct_no_prefix)));
             }
         }
-
-        // try raw keys
         if let Some(entries) = self.ps.raw_entries() {
-            for entry in entries {
-                if let Ok(pt) = entry.primitive.decrypt(ct, aad) {
-                    return Ok(pt);
-                }
+            candidates.extend(entries.iter().map(|entry| (entry, ct)));
+        }
+        candidates
+    }
+}
+
+impl tink_core::Aead for WrappedAead {
+    fn encrypt(&self, pt: &[u8], aad: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let op = tink_core::trace::operation("aead", "encrypt", pt.len());
+        let primary = self.primary_for_encrypt(&op, pt.len())?;
+
+        let ct = primary.primitive.encrypt(pt, aad).map_err(|e| {
+            op.failed("encryption failed");
+            e
+        })?;
+
+        let mut ret = Vec::with_capacity(primary.prefix.len() + ct.len());
+        ret.extend_from_slice(&primary.prefix);
+        ret.extend_from_slice(&ct);
+        op.succeeded(primary.key_id, ret.len());
+        Ok(ret)
+    }
+
+    fn decrypt(&self, ct: &[u8], aad: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let op = tink_core::trace::operation("aead", "decrypt", ct.len());
+        if !self.ps.usage.allows_decrypt() {
+            op.failed("decryption not permitted");
+            return Err("aead::factory: decryption not permitted".into());
+        }
+        for (entry, ct) in self.decrypt_candidates(ct) {
+            if let Ok(pt) = entry.primitive.decrypt(ct, aad) {
+                op.succeeded(entry.key_id, pt.len());
+                return Ok(pt);
             }
+            op.key_failed(entry.key_id);
         }
 
         // nothing worked
+        op.failed("no key could decrypt");
         Err("aead::decrypt: decryption failed".into())
     }
+
+    fn encrypt_with_aad_reader(
+        &self,
+        pt: &[u8],
+        aad: &mut dyn std::io::Read,
+    ) -> Result<Vec<u8>, TinkError> {
+        let op = tink_core::trace::operation("aead", "encrypt", pt.len());
+        let primary = self.primary_for_encrypt(&op, pt.len())?;
+
+        let ct = primary
+            .primitive
+            .encrypt_with_aad_reader(pt, aad)
+            .map_err(|e| {
+                op.failed("encryption failed");
+                e
+            })?;
+
+        let mut ret = Vec::with_capacity(primary.prefix.len() + ct.len());
+        ret.extend_from_slice(&primary.prefix);
+        ret.extend_from_slice(&ct);
+        op.succeeded(primary.key_id, ret.len());
+        Ok(ret)
+    }
+
+    /// Decrypt `ct` with the associated data read from `aad`.  The associated data can only be
+    /// read once, so it is only streamed when a single key in the keyset could have produced the
+    /// ciphertext; otherwise it is read into memory so that each candidate key can be tried.
+    fn decrypt_with_aad_reader(
+        &self,
+        ct: &[u8],
+        aad: &mut dyn std::io::Read,
+    ) -> Result<Vec<u8>, TinkError> {
+        let candidates = self.decrypt_candidates(ct);
+        if candidates.len() != 1 {
+            let mut data = Vec::new();
+            aad.read_to_end(&mut data)
+                .map_err(|e| wrap_err("aead::factory: failed to read associated data", e))?;
+            return self.decrypt(ct, &data);
+        }
+        let op = tink_core::trace::operation("aead", "decrypt", ct.len());
+        if !self.ps.usage.allows_decrypt() {
+            op.failed("decryption not permitted");
+            return Err("aead::factory: decryption not permitted".into());
+        }
+        let (entry, ct) = candidates[0];
+        match entry.primitive.decrypt_with_aad_reader(ct, aad) {
+            Ok(pt) => {
+                op.succeeded(entry.key_id, pt.len());
+                Ok(pt)
+            }
+            Err(_) => {
+                op.key_failed(entry.key_id);
+                op.failed("no key could decrypt");
+                Err("aead::decrypt: decryption failed".into())
+            }
+        }
+    }
 }
@@ -17,6 +17,7 @@
 //! This module contains pre-generated [`KeyTemplate`]s for AEAD keys. One can use these templates
 //! to generate new Keysets.
 
+use tink_core::TinkError;
 use tink_proto::{prost::Message, HashType, KeyTemplate, OutputPrefixType};
 
 /// Return a [`KeyTemplate`] that generates an AES-GCM key with the following parameters:
@@ -40,6 +41,22 @@ pub fn aes256_gcm_no_prefix_key_template() -> KeyTemplate {
     create_aes_gcm_key_template(32, OutputPrefixType::Raw)
 }
 
+/// Return a [`KeyTemplate`] that generates a key-committing AES-GCM key with the following
+/// parameters:
+///   - Key size: 16 bytes
+///   - Output prefix type: TINK
+pub fn aes128_gcm_committing_key_template() -> KeyTemplate {
+    create_aes_gcm_committing_key_template(16, OutputPrefixType::Tink)
+}
+
+/// Return a [`KeyTemplate`] that generates a key-committing AES-GCM key with the following
+/// parameters:
+///   - Key size: 32 bytes
+///   - Output prefix type: TINK
+pub fn aes256_gcm_committing_key_template() -> KeyTemplate {
+    create_aes_gcm_committing_key_template(32, OutputPrefixType::Tink)
+}
+
 /// Return a [`KeyTemplate`] that generates an AES-GCM-SIV key with the following parameters:
 ///   - Key size: 16 bytes
 ///   - Output prefix type: TINK
@@ -132,17 +149,23 @@ pub fn kms_envelope_aead_key_template(uri: &str, dek_t: KeyTemplate) -> KeyTempl
 
 /// Return an AES-GCM key template with the given key size in bytes.
 fn create_aes_gcm_key_template(key_size: u32, output_prefix_type: OutputPrefixType) -> KeyTemplate {
-    let format = tink_proto::AesGcmKeyFormat {
-        version: crate::AES_GCM_KEY_VERSION,
-        key_size,
-    };
-    let mut serialized_format = Vec::new();
-    format.encode(&mut serialized_format).unwrap(); // safe: proto-encode
-    KeyTemplate {
-        type_url: crate::AES_GCM_TYPE_URL.to_string(),
-        value: serialized_format,
-        output_prefix_type: output_prefix_type as i32,
-    }
+    AesGcmTemplateBuilder::new()
+        .key_size(key_size)
+        .prefix(output_prefix_type)
+        .build()
+        .unwrap() // safe: valid parameters
+}
+
+/// Return a key-committing AES-GCM key template with the given key size in bytes.
+fn create_aes_gcm_committing_key_template(
+    key_size: u32,
+    output_prefix_type: OutputPrefixType,
+) -> KeyTemplate {
+    AesGcmCommittingTemplateBuilder::new()
+        .key_size(key_size)
+        .prefix(output_prefix_type)
+        .build()
+        .unwrap() // safe: valid parameters
 }
 
 /// Return an AES-GCM-SIV key template with the given key size in bytes.
@@ -150,17 +173,11 @@ fn create_aes_gcm_siv_key_template(
     key_size: u32,
     output_prefix_type: OutputPrefixType,
 ) -> KeyTemplate {
-    let format = tink_proto::AesGcmSivKeyFormat {
-        version: crate::AES_GCM_SIV_KEY_VERSION,
-        key_size,
-    };
-    let mut serialized_format = Vec::new();
-    format.encode(&mut serialized_format).unwrap(); // safe: proto-encode
-    KeyTemplate {
-        type_url: crate::AES_GCM_SIV_TYPE_URL.to_string(),
-        value: serialized_format,
-        output_prefix_type: output_prefix_type as i32,
-    }
+    AesGcmSivTemplateBuilder::new()
+        .key_size(key_size)
+        .prefix(output_prefix_type)
+        .build()
+        .unwrap() // safe: valid parameters
 }
 
 /// Return an AES-CTR-HMAC key template with the given parameters.
@@ -171,25 +188,258 @@ fn create_aes_ctr_hmac_aead_key_template(
     tag_size: u32,
     hash: HashType,
 ) -> KeyTemplate {
-    let format = tink_proto::AesCtrHmacAeadKeyFormat {
-        aes_ctr_key_format: Some(tink_proto::AesCtrKeyFormat {
-            params: Some(tink_proto::AesCtrParams { iv_size }),
-            key_size: aes_key_size,
-        }),
-        hmac_key_format: Some(tink_proto::HmacKeyFormat {
-            version: crate::AES_CTR_HMAC_AEAD_KEY_VERSION,
-            params: Some(tink_proto::HmacParams {
-                hash: hash as i32,
-                tag_size,
-            }),
-            key_size: hmac_key_size,
-        }),
-    };
+    AesCtrHmacAeadTemplateBuilder::new()
+        .aes_key_size(aes_key_size)
+        .iv_size(iv_size)
+        .hmac_key_size(hmac_key_size)
+        .tag_size(tag_size)
+        .hash(hash)
+        .build()
+        .unwrap() // safe: valid parameters
+}
+
+/// Serialize a key format into a [`KeyTemplate`], checking the output prefix type.
+fn serialize_template<T: Message>(
+    type_url: &str,
+    format: &T,
+    output_prefix_type: OutputPrefixType,
+) -> Result<KeyTemplate, TinkError> {
+    if output_prefix_type == OutputPrefixType::UnknownPrefix {
+        return Err("invalid output prefix type".into());
+    }
     let mut serialized_format = Vec::new();
     format.encode(&mut serialized_format).unwrap(); // safe: proto-encode
-    KeyTemplate {
+    Ok(KeyTemplate {
+        type_url: type_url.to_string(),
         value: serialized_format,
-        type_url: crate::AES_CTR_HMAC_AEAD_TYPE_URL.to_string(),
-        output_prefix_type: OutputPrefixType::Tink as i32,
+        output_prefix_type: output_prefix_type as i32,
+    })
+}
+
+/// Builder for AES-GCM [`KeyTemplate`]s, whose parameters are checked when the template is built.
+/// Defaults to a 16-byte key with TINK output prefix.
+#[derive(Clone, Copy, Debug)]
+pub struct AesGcmTemplateBuilder {
+    key_size: u32,
+    prefix: OutputPrefixType,
+}
+
+impl Default for AesGcmTemplateBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AesGcmTemplateBuilder {
+    /// Create a builder with the default parameters.
+    pub fn new() -> Self {
+        Self {
+            key_size: 16,
+            prefix: OutputPrefixType::Tink,
+        }
+    }
+
+    /// Set the key size in bytes (16, 24 or 32).
+    pub fn key_size(mut self, key_size: u32) -> Self {
+        self.key_size = key_size;
+        self
+    }
+
+    /// Set the output prefix type.
+    pub fn prefix(mut self, prefix: OutputPrefixType) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    /// Build the [`KeyTemplate`], or return an error if the parameters are invalid.
+    pub fn build(&self) -> Result<KeyTemplate, TinkError> {
+        let format = tink_proto::AesGcmKeyFormat {
+            version: crate::AES_GCM_KEY_VERSION,
+            key_size: self.key_size,
+        };
+        crate::aes_gcm_key_manager::validate_key_format(&format)?;
+        serialize_template(crate::AES_GCM_TYPE_URL, &format, self.prefix)
+    }
+}
+
+/// Builder for key-committing AES-GCM [`KeyTemplate`]s, whose parameters are checked when the
+/// template is built.  Defaults to a 16-byte key with TINK output prefix.
+#[derive(Clone, Copy, Debug)]
+pub struct AesGcmCommittingTemplateBuilder {
+    key_size: u32,
+    prefix: OutputPrefixType,
+}
+
+impl Default for AesGcmCommittingTemplateBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AesGcmCommittingTemplateBuilder {
+    /// Create a builder with the default parameters.
+    pub fn new() -> Self {
+        Self {
+            key_size: 16,
+            prefix: OutputPrefixType::Tink,
+        }
+    }
+
+    /// Set the key size in bytes (16 or 32).
+    pub fn key_size(mut self, key_size: u32) -> Self {
+        self.key_size = key_size;
+        self
+    }
+
+    /// Set the output prefix type.
+    pub fn prefix(mut self, prefix: OutputPrefixType) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    /// Build the [`KeyTemplate`], or return an error if the parameters are invalid.
+    pub fn build(&self) -> Result<KeyTemplate, TinkError> {
+        let format = tink_proto::AesGcmCommittingKeyFormat {
+            version: crate::AES_GCM_COMMITTING_KEY_VERSION,
+            key_size: self.key_size,
+        };
+        crate::aes_gcm_committing_key_manager::validate_key_format(&format)?;
+        serialize_template(crate::AES_GCM_COMMITTING_TYPE_URL, &format, self.prefix)
+    }
+}
+
+/// Builder for AES-GCM-SIV [`KeyTemplate`]s, whose parameters are checked when the template is
+/// built.  Defaults to a 16-byte key with TINK output prefix.
+#[derive(Clone, Copy, Debug)]
+pub struct AesGcmSivTemplateBuilder {
+    key_size: u32,
+    prefix: OutputPrefixType,
+}
+
+impl Default for AesGcmSivTemplateBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AesGcmSivTemplateBuilder {
+    /// Create a builder with the default parameters.
+    pub fn new() -> Self {
+        Self {
+            key_size: 16,
+            prefix: OutputPrefixType::Tink,
+        }
+    }
+
+    /// Set the key size in bytes (16, 24 or 32).
+    pub fn key_size(mut self, key_size: u32) -> Self {
+        self.key_size = key_size;
+        self
+    }
+
+    /// Set the output prefix type.
+    pub fn prefix(mut self, prefix: OutputPrefixType) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    /// Build the [`KeyTemplate`], or return an error if the parameters are invalid.
+    pub fn build(&self) -> Result<KeyTemplate, TinkError> {
+        let format = tink_proto::AesGcmSivKeyFormat {
+            version: crate::AES_GCM_SIV_KEY_VERSION,
+            key_size: self.key_size,
+        };
+        crate::aes_gcm_siv_key_manager::validate_key_format(&format)?;
+        serialize_template(crate::AES_GCM_SIV_TYPE_URL, &format, self.prefix)
+    }
+}
+
+/// Builder for AES-CTR-HMAC-AEAD [`KeyTemplate`]s, whose parameters are checked when the template
+/// is built.  Defaults to the parameters of [`aes128_ctr_hmac_sha256_key_template`].
+#[derive(Clone, Copy, Debug)]
+pub struct AesCtrHmacAeadTemplateBuilder {
+    aes_key_size: u32,
+    iv_size: u32,
+    hmac_key_size: u32,
+    tag_size: u32,
+    hash: HashType,
+    prefix: OutputPrefixType,
+}
+
+impl Default for AesCtrHmacAeadTemplateBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AesCtrHmacAeadTemplateBuilder {
+    /// Create a builder with the default parameters.
+    pub fn new() -> Self {
+        Self {
+            aes_key_size: 16,
+            iv_size: 16,
+            hmac_key_size: 32,
+            tag_size: 16,
+            hash: HashType::Sha256,
+            prefix: OutputPrefixType::Tink,
+        }
+    }
+
+    /// Set the AES key size in bytes (16 or 32).
+    pub fn aes_key_size(mut self, aes_key_size: u32) -> Self {
+        self.aes_key_size = aes_key_size;
+        self
+    }
+
+    /// Set the AES-CTR IV size in bytes (12 to 16).
+    pub fn iv_size(mut self, iv_size: u32) -> Self {
+        self.iv_size = iv_size;
+        self
+    }
+
+    /// Set the HMAC key size in bytes (at least 16).
+    pub fn hmac_key_size(mut self, hmac_key_size: u32) -> Self {
+        self.hmac_key_size = hmac_key_size;
+        self
+    }
+
+    /// Set the HMAC tag size in bytes (at least 10, and at most the hash output size).
+    pub fn tag_size(mut self, tag_size: u32) -> Self {
+        self.tag_size = tag_size;
+        self
+    }
+
+    /// Set the HMAC hash function.
+    pub fn hash(mut self, hash: HashType) -> Self {
+        self.hash = hash;
+        self
+    }
+
+    /// Set the output prefix type.
+    pub fn prefix(mut self, prefix: OutputPrefixType) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    /// Build the [`KeyTemplate`], or return an error if the parameters are invalid.
+    pub fn build(&self) -> Result<KeyTemplate, TinkError> {
+        let format = tink_proto::AesCtrHmacAeadKeyFormat {
+            aes_ctr_key_format: Some(tink_proto::AesCtrKeyFormat {
+                params: Some(tink_proto::AesCtrParams {
+                    iv_size: self.iv_size,
+                }),
+                key_size: self.aes_key_size,
+            }),
+            hmac_key_format: Some(tink_proto::HmacKeyFormat {
+                version: crate::AES_CTR_HMAC_AEAD_KEY_VERSION,
+                params: Some(tink_proto::HmacParams {
+                    hash: self.hash as i32,
+                    tag_size: self.tag_size,
+                }),
+                key_size: self.hmac_key_size,
+            }),
+        };
+        crate::aes_ctr_hmac_aead_key_manager::validate_key_format(&format)?;
+        serialize_template(crate::AES_CTR_HMAC_AEAD_TYPE_URL, &format, self.prefix)
     }
 }
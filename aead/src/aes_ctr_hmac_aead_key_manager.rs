@@ -155,14 +155,15 @@ fn validate_hmac_key(
         .params
         .as_ref()
         .ok_or_else(|| TinkError::new("AesCtrHmacAeadKeyManager: no HMAC params"))?;
-    let hash = HashType::from_i32(hmac_params.hash)
-        .ok_or_else(|| TinkError::new("AesCtrHmacAeadKeyManager: unknown hash"))?;
+    let hash = hmac_params
+        .try_hash()
+        .map_err(|e| wrap_err("AesCtrHmacAeadKeyManager: unknown hash", e))?;
 
     Ok((hmac_key, hmac_params, hash))
 }
 
 /// Validate the given [`tink_proto::AesCtrHmacAeadKeyFormat`].
-fn validate_key_format(
+pub(crate) fn validate_key_format(
     format: &tink_proto::AesCtrHmacAeadKeyFormat,
 ) -> Result<(tink_proto::AesCtrKeyFormat, tink_proto::HmacKeyFormat), TinkError> {
     // Validate AesCtrKeyFormat.
@@ -203,12 +204,12 @@ fn validate_key_format(
         .into());
     }
 
-    let tag_size = match HashType::from_i32(hmac_params.hash) {
-        Some(HashType::Sha1) => 20,
-        Some(HashType::Sha224) => 28,
-        Some(HashType::Sha256) => 32,
-        Some(HashType::Sha384) => 48,
-        Some(HashType::Sha512) => 64,
+    let tag_size = match hmac_params.try_hash() {
+        Ok(HashType::Sha1) => 20,
+        Ok(HashType::Sha224) => 28,
+        Ok(HashType::Sha256) => 32,
+        Ok(HashType::Sha384) => 48,
+        Ok(HashType::Sha512) => 64,
         _ => {
             return Err(format!(
                 "AesCtrHmacAeadKeyManager: invalid HmacParams: hash_type {:?} not supported",
@@ -29,7 +29,17 @@ pub const AES_GCM_TYPE_URL: &str = "type.googleapis.com/google.crypto.tink.AesGc
 /// It generates new [`AesGcmKey`](tink_proto::AesGcmKey) keys and produces new instances of
 /// [`subtle::AesGcm`].
 #[derive(Default)]
-pub(crate) struct AesGcmKeyManager {}
+pub(crate) struct AesGcmKeyManager {
+    aad_reader: bool,
+}
+
+impl AesGcmKeyManager {
+    /// Return a key manager whose primitives are [`subtle::AesGcmWithAadReader`] instances,
+    /// which absorb associated data supplied from a reader incrementally.
+    pub(crate) fn new_with_aad_reader() -> Self {
+        AesGcmKeyManager { aad_reader: true }
+    }
+}
 
 impl tink_core::registry::KeyManager for AesGcmKeyManager {
     /// Create a [`subtle::AesGcm`] for the given serialized [`tink_proto::AesGcmKey`].
@@ -40,8 +50,13 @@ impl tink_core::registry::KeyManager for AesGcmKeyManager {
         let key = tink_proto::AesGcmKey::decode(serialized_key)
             .map_err(|e| wrap_err("AesGcmKeyManager: invalid key", e))?;
         validate_key(&key)?;
-        match subtle::AesGcm::new(&key.key_value) {
-            Ok(p) => Ok(tink_core::Primitive::Aead(Box::new(p))),
+        let p: Result<Box<dyn tink_core::Aead>, TinkError> = if self.aad_reader {
+            subtle::AesGcmWithAadReader::new(&key.key_value).map(|p| Box::new(p) as _)
+        } else {
+            subtle::AesGcm::new(&key.key_value).map(|p| Box::new(p) as _)
+        };
+        match p {
+            Ok(p) => Ok(tink_core::Primitive::Aead(p)),
             Err(e) => Err(wrap_err("AesGcmKeyManager: cannot create new primitive", e)),
         }
     }
@@ -67,6 +82,38 @@ impl tink_core::registry::KeyManager for AesGcmKeyManager {
         Ok(sk)
     }
 
+    fn supports_derivation(&self) -> bool {
+        true
+    }
+
+    /// Derive a new key according to specification the given serialized
+    /// [`tink_proto::AesGcmKeyFormat`], reading the key value from `pseudorandomness`.
+    fn derive_key(
+        &self,
+        serialized_key_format: &[u8],
+        pseudorandomness: &mut dyn std::io::Read,
+    ) -> Result<Vec<u8>, TinkError> {
+        if serialized_key_format.is_empty() {
+            return Err("AesGcmKeyManager: invalid key format".into());
+        }
+        let key_format = tink_proto::AesGcmKeyFormat::decode(serialized_key_format)
+            .map_err(|e| wrap_err("AesGcmKeyManager: invalid key format", e))?;
+        validate_key_format(&key_format)
+            .map_err(|e| wrap_err("AesGcmKeyManager: invalid key format", e))?;
+        let mut key_value = vec![0; key_format.key_size as usize];
+        pseudorandomness
+            .read_exact(&mut key_value)
+            .map_err(|e| wrap_err("AesGcmKeyManager: not enough pseudorandomness", e))?;
+        let key = tink_proto::AesGcmKey {
+            version: AES_GCM_KEY_VERSION,
+            key_value,
+        };
+        let mut sk = Vec::new();
+        key.encode(&mut sk)
+            .map_err(|e| wrap_err("AesGcmKeyManager: failed to encode new key", e))?;
+        Ok(sk)
+    }
+
     fn type_url(&self) -> &'static str {
         AES_GCM_TYPE_URL
     }
@@ -80,11 +127,11 @@ fn validate_key(key: &tink_proto::AesGcmKey) -> Result<(), TinkError> {
     tink_core::keyset::validate_key_version(key.version, AES_GCM_KEY_VERSION)
         .map_err(|e| wrap_err("AesGcmKeyManager", e))?;
     let key_size = key.key_value.len();
-    crate::subtle::validate_aes_key_size(key_size).map_err(|e| wrap_err("AesGcmKeyManager", e))
+    crate::subtle::validate_aes_gcm_key_size(key_size).map_err(|e| wrap_err("AesGcmKeyManager", e))
 }
 
 /// Validate the given [`tink_proto::AesGcmKeyFormat`].
-fn validate_key_format(format: &tink_proto::AesGcmKeyFormat) -> Result<(), TinkError> {
-    crate::subtle::validate_aes_key_size(format.key_size as usize)
+pub(crate) fn validate_key_format(format: &tink_proto::AesGcmKeyFormat) -> Result<(), TinkError> {
+    crate::subtle::validate_aes_gcm_key_size(format.key_size as usize)
         .map_err(|e| wrap_err("AesGcmKeyManager", e))
 }
@@ -83,11 +83,14 @@ fn validate_key(key: &tink_proto::AesGcmSivKey) -> Result<(), TinkError> {
     tink_core::keyset::validate_key_version(key.version, AES_GCM_SIV_KEY_VERSION)
         .map_err(|e| wrap_err("AesGcmSivKeyManager", e))?;
     let key_size = key.key_value.len();
-    crate::subtle::validate_aes_key_size(key_size).map_err(|e| wrap_err("AesGcmSivKeyManager", e))
+    crate::subtle::validate_aes_gcm_key_size(key_size)
+        .map_err(|e| wrap_err("AesGcmSivKeyManager", e))
 }
 
 /// Validate the given [`tink_proto::AesGcmSivKeyFormat`].
-fn validate_key_format(format: &tink_proto::AesGcmSivKeyFormat) -> Result<(), TinkError> {
-    crate::subtle::validate_aes_key_size(format.key_size as usize)
+pub(crate) fn validate_key_format(
+    format: &tink_proto::AesGcmSivKeyFormat,
+) -> Result<(), TinkError> {
+    crate::subtle::validate_aes_gcm_key_size(format.key_size as usize)
         .map_err(|e| wrap_err("AesGcmSivKeyManager", e))
 }
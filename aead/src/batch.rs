@@ -0,0 +1,242 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Provide batch AEAD operations that process many records in parallel.
+
+use std::{
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::{mpsc, Arc, Mutex},
+    thread,
+};
+use tink_core::{keyset::Handle, utils::wrap_err, TinkError};
+
+/// A unit of work submitted to a [`BatchExecutor`].
+pub type BatchJob = Box<dyn FnOnce() + Send>;
+
+/// `BatchExecutor` runs the jobs that make up a batch operation.
+pub trait BatchExecutor: Send + Sync {
+    /// Run all of `jobs`, possibly concurrently, and return once every job has finished.
+    fn run(&self, jobs: Vec<BatchJob>);
+
+    /// Return the number of jobs that the executor can usefully run at once; a batch is split
+    /// into this many jobs.
+    fn parallelism(&self) -> usize {
+        1
+    }
+}
+
+/// `SequentialExecutor` runs each job in turn on the calling thread.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SequentialExecutor;
+
+impl BatchExecutor for SequentialExecutor {
+    fn run(&self, jobs: Vec<BatchJob>) {
+        for job in jobs {
+            job();
+        }
+    }
+}
+
+/// `ThreadPoolExecutor` runs jobs on a fixed set of worker threads, which are stopped when the
+/// executor is dropped.
+///
+/// Jobs must not themselves submit work to the same executor, as this can deadlock.
+pub struct ThreadPoolExecutor {
+    sender: Mutex<Option<mpsc::Sender<BatchJob>>>,
+    workers: Vec<thread::JoinHandle<()>>,
+}
+
+impl ThreadPoolExecutor {
+    /// Create a thread pool with `threads` worker threads (at least one).
+    pub fn new(threads: usize) -> Self {
+        let (sender, receiver) = mpsc::channel::<BatchJob>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..threads.max(1))
+            .map(|_| {
+                let receiver = receiver.clone();
+                thread::spawn(move || loop {
+                    let job = match receiver.lock() {
+                        Ok(receiver) => receiver.recv(),
+                        Err(_) => return,
+                    };
+                    match job {
+                        Ok(job) => job(),
+                        Err(_) => return,
+                    }
+                })
+            })
+            .collect();
+        ThreadPoolExecutor {
+            sender: Mutex::new(Some(sender)),
+            workers,
+        }
+    }
+}
+
+impl BatchExecutor for ThreadPoolExecutor {
+    fn run(&self, jobs: Vec<BatchJob>) {
+        let sender = match self.sender.lock() {
+            Ok(sender) => sender.clone(),
+            Err(_) => None,
+        };
+        let (done_tx, done_rx) = mpsc::channel();
+        let mut pending = 0;
+        for job in jobs {
+            let done_tx = done_tx.clone();
+            let job: BatchJob = Box::new(move || {
+                // A panicking job must not take the worker thread down with it.
+                let _ = catch_unwind(AssertUnwindSafe(job));
+                let _ = done_tx.send(());
+            });
+            match &sender {
+                Some(sender) => match sender.send(job) {
+                    Ok(()) => pending += 1,
+                    Err(mpsc::SendError(job)) => job(),
+                },
+                None => job(),
+            }
+        }
+        for _ in 0..pending {
+            if done_rx.recv().is_err() {
+                break;
+            }
+        }
+    }
+
+    fn parallelism(&self) -> usize {
+        self.workers.len()
+    }
+}
+
+impl Drop for ThreadPoolExecutor {
+    fn drop(&mut self) {
+        if let Ok(mut sender) = self.sender.lock() {
+            sender.take();
+        }
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// `BatchAead` encrypts and decrypts batches of records with the AEAD primitive for a keyset,
+/// splitting each batch between the jobs of a [`BatchExecutor`].  Each job uses its own AEAD
+/// primitive created from the keyset handle, so the primitives do not need to be shared between
+/// threads.  The results of a batch operation are in the same order as its inputs.
+///
+/// Records are copied into each job, so that executors can run jobs on threads that outlive the
+/// call.
+pub struct BatchAead {
+    handle: Handle,
+    executor: Arc<dyn BatchExecutor>,
+}
+
+/// Result of processing one record in a job, with any error rendered as a string so that it can
+/// be returned from another thread.
+type RecordResult = Result<Vec<u8>, String>;
+
+impl BatchAead {
+    /// Run `op` on each `(input, aad)` record using the AEAD primitive for the keyset.
+    fn process<F>(&self, records: &[(&[u8], &[u8])], op: F) -> Vec<Result<Vec<u8>, TinkError>>
+    where
+        F: Fn(&dyn tink_core::Aead, &[u8], &[u8]) -> Result<Vec<u8>, TinkError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        if records.is_empty() {
+            return Vec::new();
+        }
+        let op = Arc::new(op);
+        let jobs_count = self.executor.parallelism().max(1).min(records.len());
+        let chunk_size = (records.len() + jobs_count - 1) / jobs_count;
+        let (result_tx, result_rx) = mpsc::channel::<(usize, Vec<RecordResult>)>();
+        let jobs: Vec<BatchJob> = records
+            .chunks(chunk_size)
+            .enumerate()
+            .map(|(index, chunk)| {
+                let chunk: Vec<(Vec<u8>, Vec<u8>)> = chunk
+                    .iter()
+                    .map(|(input, aad)| (input.to_vec(), aad.to_vec()))
+                    .collect();
+                let handle = self.handle.clone();
+                let op = op.clone();
+                let result_tx = result_tx.clone();
+                Box::new(move || {
+                    let results = match crate::new(&handle) {
+                        Ok(a) => chunk
+                            .iter()
+                            .map(|(input, aad)| {
+                                op(a.as_ref(), input, aad).map_err(|e| e.to_string())
+                            })
+                            .collect(),
+                        Err(e) => vec![Err(e.to_string()); chunk.len()],
+                    };
+                    let _ = result_tx.send((index, results));
+                }) as BatchJob
+            })
+            .collect();
+        let chunks_count = jobs.len();
+        drop(result_tx);
+        self.executor.run(jobs);
+
+        let mut chunks: Vec<Option<Vec<RecordResult>>> = vec![None; chunks_count];
+        for (index, results) in result_rx.try_iter() {
+            chunks[index] = Some(results);
+        }
+        chunks
+            .into_iter()
+            .zip(records.chunks(chunk_size))
+            .flat_map(|(results, chunk)| match results {
+                Some(results) => results
+                    .into_iter()
+                    .map(|r| r.map_err(|e| TinkError::new(&e)))
+                    .collect::<Vec<_>>(),
+                None => chunk
+                    .iter()
+                    .map(|_| Err("BatchAead: job did not complete".into()))
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Encrypt each `(plaintext, aad)` record, returning the ciphertexts in the same order.
+    /// Fails if any record cannot be encrypted.
+    pub fn encrypt_batch(&self, records: &[(&[u8], &[u8])]) -> Result<Vec<Vec<u8>>, TinkError> {
+        self.process(records, |a, pt, aad| a.encrypt(pt, aad))
+            .into_iter()
+            .enumerate()
+            .map(|(i, r)| r.map_err(|e| wrap_err(&format!("BatchAead: record {}", i), e)))
+            .collect()
+    }
+
+    /// Decrypt each `(ciphertext, aad)` record, returning the result for each record in the same
+    /// order, so that a record that fails to decrypt does not affect the others.
+    pub fn decrypt_batch(&self, records: &[(&[u8], &[u8])]) -> Vec<Result<Vec<u8>, TinkError>> {
+        self.process(records, |a, ct, aad| a.decrypt(ct, aad))
+    }
+}
+
+/// Return a [`BatchAead`] for the given keyset handle, which runs batch operations using
+/// `executor`.
+pub fn new_batch(h: &Handle, executor: Arc<dyn BatchExecutor>) -> Result<BatchAead, TinkError> {
+    // Check up front that the keyset yields an AEAD primitive.
+    crate::new(h).map_err(|e| wrap_err("aead::new_batch", e))?;
+    Ok(BatchAead {
+        handle: h.clone(),
+        executor,
+    })
+}
@@ -61,6 +61,32 @@ impl tink_core::registry::KeyManager for ChaCha20Poly1305KeyManager {
         Ok(sk)
     }
 
+    fn supports_derivation(&self) -> bool {
+        true
+    }
+
+    /// Derive a new key, reading the key value from `pseudorandomness` and ignoring the
+    /// specification in the given serialized key format because the key size and other params are
+    /// fixed.
+    fn derive_key(
+        &self,
+        _serialized_key_format: &[u8],
+        pseudorandomness: &mut dyn std::io::Read,
+    ) -> Result<Vec<u8>, TinkError> {
+        let mut key_value = vec![0; subtle::CHA_CHA20_KEY_SIZE];
+        pseudorandomness
+            .read_exact(&mut key_value)
+            .map_err(|e| wrap_err("ChaCha20Poly1305KeyManager: not enough pseudorandomness", e))?;
+        let key = tink_proto::ChaCha20Poly1305Key {
+            version: CHA_CHA20_POLY1305_KEY_VERSION,
+            key_value,
+        };
+        let mut sk = Vec::new();
+        key.encode(&mut sk)
+            .map_err(|e| wrap_err("ChaCha20Poly1305KeyManager: failed to encode new key", e))?;
+        Ok(sk)
+    }
+
     fn type_url(&self) -> &'static str {
         CHA_CHA20_POLY1305_TYPE_URL
     }
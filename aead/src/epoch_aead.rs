@@ -0,0 +1,133 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Provide an AEAD wrapper that binds ciphertexts to a key epoch.
+
+use std::convert::TryInto;
+use tink_core::{utils::wrap_err, TinkError};
+
+/// Size of the little-endian epoch that starts each ciphertext.
+pub const EPOCH_SIZE: usize = 8;
+
+/// `EpochAead` wraps an AEAD so that each ciphertext is bound to a caller-supplied 64-bit epoch.
+///
+/// Ciphertexts consist of the epoch (8 bytes, little-endian) followed by the output of the
+/// wrapped AEAD, which is computed with associated data `aad || epoch`, with the epoch also in
+/// 8-byte little-endian encoding.  Changing the stored epoch therefore makes decryption fail.
+///
+/// Decryption rejects ciphertexts whose epoch is below the configured minimum epoch, so that
+/// old ciphertexts can be detected and re-encrypted.
+pub struct EpochAead {
+    inner: Box<dyn tink_core::Aead>,
+    epoch: u64,
+    min_epoch: u64,
+}
+
+/// Manual implementation of [`Clone`] relying on the trait bounds for
+/// primitives to provide `.box_clone()` methods.
+impl Clone for EpochAead {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.box_clone(),
+            epoch: self.epoch,
+            min_epoch: self.min_epoch,
+        }
+    }
+}
+
+/// Return the associated data passed to the wrapped AEAD for the given epoch.
+fn epoch_aad(aad: &[u8], epoch: u64) -> Vec<u8> {
+    let mut ret = Vec::with_capacity(aad.len() + EPOCH_SIZE);
+    ret.extend_from_slice(aad);
+    ret.extend_from_slice(&epoch.to_le_bytes());
+    ret
+}
+
+impl EpochAead {
+    /// Create an [`EpochAead`] that encrypts under `epoch` and accepts ciphertexts of any epoch.
+    pub fn new(inner: Box<dyn tink_core::Aead>, epoch: u64) -> EpochAead {
+        EpochAead {
+            inner,
+            epoch,
+            min_epoch: 0,
+        }
+    }
+
+    /// Set the minimum epoch that decryption accepts.
+    pub fn with_min_epoch(mut self, min_epoch: u64) -> EpochAead {
+        self.min_epoch = min_epoch;
+        self
+    }
+
+    /// Return the epoch that new ciphertexts are bound to.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Return the epoch that `ct` claims to be bound to, without authenticating it.
+    pub fn ciphertext_epoch(ct: &[u8]) -> Result<u64, TinkError> {
+        if ct.len() < EPOCH_SIZE {
+            return Err("EpochAead: ciphertext too short".into());
+        }
+        Ok(u64::from_le_bytes(ct[..EPOCH_SIZE].try_into().unwrap())) // safe: checked len
+    }
+
+    /// Encrypt `pt` with `aad` as additional authenticated data, binding the ciphertext to
+    /// `epoch` rather than the configured epoch.
+    pub fn encrypt_with_epoch(
+        &self,
+        pt: &[u8],
+        aad: &[u8],
+        epoch: u64,
+    ) -> Result<Vec<u8>, TinkError> {
+        let ct = self
+            .inner
+            .encrypt(pt, &epoch_aad(aad, epoch))
+            .map_err(|e| wrap_err("EpochAead: encryption failed", e))?;
+        let mut ret = Vec::with_capacity(EPOCH_SIZE + ct.len());
+        ret.extend_from_slice(&epoch.to_le_bytes());
+        ret.extend_from_slice(&ct);
+        Ok(ret)
+    }
+
+    /// Decrypt `ct` with `aad` as additional authenticated data, returning the plaintext and
+    /// the epoch the ciphertext was bound to.  Fails if the epoch is below the minimum epoch.
+    pub fn decrypt_with_epoch(&self, ct: &[u8], aad: &[u8]) -> Result<(Vec<u8>, u64), TinkError> {
+        let epoch = Self::ciphertext_epoch(ct)?;
+        if epoch < self.min_epoch {
+            return Err(format!(
+                "EpochAead: ciphertext epoch {} is older than minimum epoch {}",
+                epoch, self.min_epoch
+            )
+            .into());
+        }
+        let pt = self
+            .inner
+            .decrypt(&ct[EPOCH_SIZE..], &epoch_aad(aad, epoch))
+            .map_err(|e| wrap_err("EpochAead: decryption failed", e))?;
+        Ok((pt, epoch))
+    }
+}
+
+impl tink_core::Aead for EpochAead {
+    fn encrypt(&self, pt: &[u8], aad: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.encrypt_with_epoch(pt, aad, self.epoch)
+    }
+
+    fn decrypt(&self, ct: &[u8], aad: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.decrypt_with_epoch(ct, aad).map(|(pt, _)| pt)
+    }
+}
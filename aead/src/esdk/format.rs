@@ -0,0 +1,389 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Parsing and serialization of AWS Encryption SDK message headers.
+
+use std::collections::BTreeMap;
+use tink_core::{utils::wrap_err, TinkError};
+use tink_proto::HashType;
+
+/// Message format version 1, used by the non-committing algorithm suites.
+pub(crate) const VERSION_1: u8 = 0x01;
+/// Message format version 2, used by the key-committing algorithm suites.
+pub(crate) const VERSION_2: u8 = 0x02;
+/// Message type for customer authenticated encrypted data (version 1 only).
+const TYPE_CUSTOMER_AED: u8 = 0x80;
+/// Content type for a body that is a single encrypted block.
+pub(crate) const CONTENT_NON_FRAMED: u8 = 0x01;
+/// Content type for a body that is split into frames.
+pub(crate) const CONTENT_FRAMED: u8 = 0x02;
+/// Size of the message ID for version 1 messages.
+const MESSAGE_ID_SIZE_V1: usize = 16;
+/// Size of the message ID for version 2 messages.
+pub(crate) const MESSAGE_ID_SIZE_V2: usize = 32;
+/// Size of the key commitment held in version 2 headers.
+pub(crate) const COMMITMENT_SIZE: usize = 32;
+
+/// Algorithm suites defined by the AWS Encryption SDK.
+///
+/// Each suite uses AES-GCM with a 12-byte IV and a 16-byte tag for the message content; the
+/// suites differ in key size, in how the content key is derived from the data key, and in
+/// whether messages are signed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlgorithmSuite {
+    Aes128GcmIv12Tag16NoKdf = 0x0014,
+    Aes192GcmIv12Tag16NoKdf = 0x0046,
+    Aes256GcmIv12Tag16NoKdf = 0x0078,
+    Aes128GcmIv12Tag16HkdfSha256 = 0x0114,
+    Aes192GcmIv12Tag16HkdfSha256 = 0x0146,
+    Aes256GcmIv12Tag16HkdfSha256 = 0x0178,
+    Aes128GcmIv12Tag16HkdfSha256EcdsaP256 = 0x0214,
+    Aes192GcmIv12Tag16HkdfSha384EcdsaP384 = 0x0346,
+    Aes256GcmIv12Tag16HkdfSha384EcdsaP384 = 0x0378,
+    Aes256GcmHkdfSha512CommitKey = 0x0478,
+    Aes256GcmHkdfSha512CommitKeyEcdsaP384 = 0x0578,
+}
+
+/// Curve used for message signatures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum SignatureCurve {
+    P256,
+    P384,
+}
+
+impl AlgorithmSuite {
+    /// Return the algorithm suite with the given identifier, if known.
+    pub fn from_id(id: u16) -> Option<Self> {
+        use AlgorithmSuite::*;
+        Some(match id {
+            0x0014 => Aes128GcmIv12Tag16NoKdf,
+            0x0046 => Aes192GcmIv12Tag16NoKdf,
+            0x0078 => Aes256GcmIv12Tag16NoKdf,
+            0x0114 => Aes128GcmIv12Tag16HkdfSha256,
+            0x0146 => Aes192GcmIv12Tag16HkdfSha256,
+            0x0178 => Aes256GcmIv12Tag16HkdfSha256,
+            0x0214 => Aes128GcmIv12Tag16HkdfSha256EcdsaP256,
+            0x0346 => Aes192GcmIv12Tag16HkdfSha384EcdsaP384,
+            0x0378 => Aes256GcmIv12Tag16HkdfSha384EcdsaP384,
+            0x0478 => Aes256GcmHkdfSha512CommitKey,
+            0x0578 => Aes256GcmHkdfSha512CommitKeyEcdsaP384,
+            _ => return None,
+        })
+    }
+
+    /// Return the identifier of the algorithm suite.
+    pub fn id(&self) -> u16 {
+        *self as u16
+    }
+
+    /// Return the size in bytes of the data key and of the AES key.
+    pub fn key_size(&self) -> usize {
+        match self.id() & 0xff {
+            0x14 => 16,
+            0x46 => 24,
+            _ => 32,
+        }
+    }
+
+    /// Indicate whether the suite commits to the data key, which requires message format
+    /// version 2.
+    pub fn is_committing(&self) -> bool {
+        matches!(
+            self,
+            AlgorithmSuite::Aes256GcmHkdfSha512CommitKey
+                | AlgorithmSuite::Aes256GcmHkdfSha512CommitKeyEcdsaP384
+        )
+    }
+
+    /// Return the message format version used with this suite.
+    pub(crate) fn message_version(&self) -> u8 {
+        if self.is_committing() {
+            VERSION_2
+        } else {
+            VERSION_1
+        }
+    }
+
+    /// Return the size of the message ID used with this suite.
+    pub(crate) fn message_id_size(&self) -> usize {
+        if self.is_committing() {
+            MESSAGE_ID_SIZE_V2
+        } else {
+            MESSAGE_ID_SIZE_V1
+        }
+    }
+
+    /// Return the hash function used to derive the content key, if any.
+    pub(crate) fn kdf_hash(&self) -> Option<HashType> {
+        match self.id() >> 8 {
+            0x00 => None,
+            0x01 | 0x02 => Some(HashType::Sha256),
+            0x03 => Some(HashType::Sha384),
+            _ => Some(HashType::Sha512),
+        }
+    }
+
+    /// Return the curve used to sign messages, if any.
+    pub(crate) fn signature_curve(&self) -> Option<SignatureCurve> {
+        match self.id() >> 8 {
+            0x02 => Some(SignatureCurve::P256),
+            0x03 | 0x05 => Some(SignatureCurve::P384),
+            _ => None,
+        }
+    }
+}
+
+/// An encrypted copy of the data key, as produced by a single key provider.
+#[derive(Clone, Debug)]
+pub(crate) struct EncryptedDataKey {
+    pub provider_id: String,
+    pub provider_info: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+}
+
+/// The authenticated part of a message header.
+#[derive(Clone, Debug)]
+pub(crate) struct Header {
+    pub suite: AlgorithmSuite,
+    pub message_id: Vec<u8>,
+    pub encryption_context: BTreeMap<String, String>,
+    pub encrypted_data_keys: Vec<EncryptedDataKey>,
+    pub content_type: u8,
+    pub frame_length: u32,
+    /// Key commitment; only present for version 2 messages.
+    pub commitment: Vec<u8>,
+}
+
+/// Sequential reader over an encoded message.
+pub(crate) struct Parser<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Parser { data, pos: 0 }
+    }
+
+    /// Return the current offset into the data.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Indicate whether all of the data has been consumed.
+    pub fn is_empty(&self) -> bool {
+        self.pos == self.data.len()
+    }
+
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], TinkError> {
+        match self.pos.checked_add(len) {
+            Some(end) if end <= self.data.len() => {
+                let result = &self.data[self.pos..end];
+                self.pos = end;
+                Ok(result)
+            }
+            _ => Err("esdk: message truncated".into()),
+        }
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, TinkError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, TinkError> {
+        let b = self.read_bytes(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, TinkError> {
+        let mut b = [0u8; 4];
+        b.copy_from_slice(self.read_bytes(4)?);
+        Ok(u32::from_be_bytes(b))
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, TinkError> {
+        let mut b = [0u8; 8];
+        b.copy_from_slice(self.read_bytes(8)?);
+        Ok(u64::from_be_bytes(b))
+    }
+
+    /// Read a field preceded by its 2-byte length.
+    pub fn read_field(&mut self) -> Result<&'a [u8], TinkError> {
+        let len = self.read_u16()?;
+        self.read_bytes(len as usize)
+    }
+
+    /// Read a UTF-8 string preceded by its 2-byte length.
+    fn read_string(&mut self) -> Result<String, TinkError> {
+        String::from_utf8(self.read_field()?.to_vec())
+            .map_err(|e| wrap_err("esdk: invalid string", e))
+    }
+}
+
+/// Append a field preceded by its 2-byte length.
+fn write_field(out: &mut Vec<u8>, data: &[u8]) -> Result<(), TinkError> {
+    if data.len() > u16::MAX as usize {
+        return Err("esdk: field too long".into());
+    }
+    out.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    out.extend_from_slice(data);
+    Ok(())
+}
+
+/// Serialize an encryption context, with entries sorted by key.  An empty context
+/// serializes to nothing at all.
+pub(crate) fn serialize_context(context: &BTreeMap<String, String>) -> Result<Vec<u8>, TinkError> {
+    let mut out = Vec::new();
+    if context.is_empty() {
+        return Ok(out);
+    }
+    if context.len() > u16::MAX as usize {
+        return Err("esdk: too many encryption context entries".into());
+    }
+    out.extend_from_slice(&(context.len() as u16).to_be_bytes());
+    for (k, v) in context {
+        write_field(&mut out, k.as_bytes())?;
+        write_field(&mut out, v.as_bytes())?;
+    }
+    Ok(out)
+}
+
+/// Parse a serialized encryption context.
+fn parse_context(data: &[u8]) -> Result<BTreeMap<String, String>, TinkError> {
+    let mut context = BTreeMap::new();
+    if data.is_empty() {
+        return Ok(context);
+    }
+    let mut p = Parser::new(data);
+    let count = p.read_u16()?;
+    if count == 0 {
+        return Err("esdk: invalid encryption context".into());
+    }
+    for _ in 0..count {
+        let k = p.read_string()?;
+        let v = p.read_string()?;
+        if context.insert(k, v).is_some() {
+            return Err("esdk: duplicate encryption context key".into());
+        }
+    }
+    if !p.is_empty() {
+        return Err("esdk: invalid encryption context".into());
+    }
+    Ok(context)
+}
+
+impl Header {
+    /// Parse the authenticated part of a header.
+    pub fn parse(p: &mut Parser) -> Result<Header, TinkError> {
+        let version = p.read_u8()?;
+        match version {
+            VERSION_1 => {
+                if p.read_u8()? != TYPE_CUSTOMER_AED {
+                    return Err("esdk: unsupported message type".into());
+                }
+            }
+            VERSION_2 => {}
+            v => return Err(format!("esdk: unsupported message format version {}", v).into()),
+        }
+        let suite_id = p.read_u16()?;
+        let suite = AlgorithmSuite::from_id(suite_id)
+            .ok_or_else(|| format!("esdk: unknown algorithm suite {:#06x}", suite_id))?;
+        if suite.message_version() != version {
+            return Err(format!(
+                "esdk: algorithm suite {:?} not valid in version {} message",
+                suite, version
+            )
+            .into());
+        }
+        let message_id = p.read_bytes(suite.message_id_size())?.to_vec();
+        let encryption_context = parse_context(p.read_field()?)?;
+
+        let edk_count = p.read_u16()?;
+        if edk_count == 0 {
+            return Err("esdk: no encrypted data keys".into());
+        }
+        let mut encrypted_data_keys = Vec::with_capacity(edk_count as usize);
+        for _ in 0..edk_count {
+            encrypted_data_keys.push(EncryptedDataKey {
+                provider_id: p.read_string()?,
+                provider_info: p.read_field()?.to_vec(),
+                ciphertext: p.read_field()?.to_vec(),
+            });
+        }
+
+        let content_type = p.read_u8()?;
+        if version == VERSION_1 {
+            if p.read_bytes(4)? != &[0u8; 4][..] {
+                return Err("esdk: invalid reserved field".into());
+            }
+            if p.read_u8()? as usize != crate::subtle::AES_GCM_IV_SIZE {
+                return Err("esdk: invalid IV length".into());
+            }
+        }
+        let frame_length = p.read_u32()?;
+        match content_type {
+            CONTENT_NON_FRAMED if version == VERSION_1 && frame_length == 0 => {}
+            CONTENT_FRAMED if frame_length > 0 => {}
+            _ => return Err("esdk: invalid content type or frame length".into()),
+        }
+        let commitment = if version == VERSION_2 {
+            p.read_bytes(COMMITMENT_SIZE)?.to_vec()
+        } else {
+            Vec::new()
+        };
+
+        Ok(Header {
+            suite,
+            message_id,
+            encryption_context,
+            encrypted_data_keys,
+            content_type,
+            frame_length,
+            commitment,
+        })
+    }
+
+    /// Serialize the authenticated part of the header.
+    pub fn serialize(&self) -> Result<Vec<u8>, TinkError> {
+        let version = self.suite.message_version();
+        let mut out = vec![version];
+        if version == VERSION_1 {
+            out.push(TYPE_CUSTOMER_AED);
+        }
+        out.extend_from_slice(&self.suite.id().to_be_bytes());
+        out.extend_from_slice(&self.message_id);
+        write_field(&mut out, &serialize_context(&self.encryption_context)?)?;
+        if self.encrypted_data_keys.len() > u16::MAX as usize {
+            return Err("esdk: too many encrypted data keys".into());
+        }
+        out.extend_from_slice(&(self.encrypted_data_keys.len() as u16).to_be_bytes());
+        for edk in &self.encrypted_data_keys {
+            write_field(&mut out, edk.provider_id.as_bytes())?;
+            write_field(&mut out, &edk.provider_info)?;
+            write_field(&mut out, &edk.ciphertext)?;
+        }
+        out.push(self.content_type);
+        if version == VERSION_1 {
+            out.extend_from_slice(&[0u8; 4]);
+            out.push(crate::subtle::AES_GCM_IV_SIZE as u8);
+        }
+        out.extend_from_slice(&self.frame_length.to_be_bytes());
+        if version == VERSION_2 {
+            out.extend_from_slice(&self.commitment);
+        }
+        Ok(out)
+    }
+}
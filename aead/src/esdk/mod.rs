@@ -0,0 +1,438 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Interoperability with the [AWS Encryption SDK message
+//! format](https://docs.aws.amazon.com/encryption-sdk/latest/developer-guide/message-format.html).
+//!
+//! Messages produced by the AWS Encryption SDK hold one or more copies of a data key, each
+//! encrypted by a key provider.  Each copy is decrypted here with a KMS client from the
+//! [`tink_core::registry`], by converting the key provider ID and key provider info into a
+//! key URI of the form `<provider ID>://<provider info>`.  For the AWS KMS keyring (provider ID
+//! `aws-kms`) this gives the `aws-kms://arn:...` URIs handled by `tink-awskms`.
+//!
+//! As the key provider fields come from the (unauthenticated) message header, decryption only
+//! uses copies whose key URI is in an explicit allowlist supplied by the caller, as in the
+//! "strict mode" of the AWS Encryption SDK.
+//!
+//! The message's encryption context is passed to the KMS via
+//! [`KmsClient::get_aead_with_context`](tink_core::registry::KmsClient::get_aead_with_context).
+//!
+//! Messages are processed entirely in memory.
+
+use std::{collections::BTreeMap, convert::TryFrom};
+use tink_core::{registry, subtle::compute_hkdf, utils::wrap_err, TinkError};
+
+mod format;
+pub use format::AlgorithmSuite;
+use format::*;
+
+/// Encryption context key holding the public key for signed algorithm suites.
+pub const PUBLIC_KEY_CONTEXT_KEY: &str = "aws-crypto-public-key";
+/// Prefix for encryption context keys reserved by the AWS Encryption SDK.
+const RESERVED_CONTEXT_PREFIX: &str = "aws-crypto-";
+/// Frame length used when encrypting.
+pub const FRAME_LENGTH: u32 = 4096;
+
+const SINGLE_BLOCK_AAD: &[u8] = b"AWSKMSEncryptionClient Single Block";
+const FRAME_AAD: &[u8] = b"AWSKMSEncryptionClient Frame";
+const FINAL_FRAME_AAD: &[u8] = b"AWSKMSEncryptionClient Final Frame";
+/// Sequence number marker that introduces the final frame.
+const FINAL_FRAME_MARKER: u32 = 0xffff_ffff;
+/// HKDF info labels for the key-committing algorithm suites.
+const DERIVE_KEY_LABEL: &[u8] = b"DERIVEKEY";
+const COMMIT_KEY_LABEL: &[u8] = b"COMMITKEY";
+
+/// Result of decrypting a message.
+#[derive(Clone, Debug)]
+pub struct DecryptedMessage {
+    pub plaintext: Vec<u8>,
+    /// Encryption context of the message, including any entries added by the AWS Encryption
+    /// SDK itself (such as [`PUBLIC_KEY_CONTEXT_KEY`]).
+    pub encryption_context: BTreeMap<String, String>,
+    pub algorithm_suite: AlgorithmSuite,
+}
+
+/// Derive the content key (and, for committing suites, the key commitment) from a data key.
+fn derive_keys(header: &Header, data_key: &[u8]) -> Result<(Vec<u8>, Vec<u8>), TinkError> {
+    let suite = header.suite;
+    match suite.kdf_hash() {
+        None => Ok((data_key.to_vec(), Vec::new())),
+        Some(hash) if suite.is_committing() => {
+            let mut info = suite.id().to_be_bytes().to_vec();
+            info.extend_from_slice(DERIVE_KEY_LABEL);
+            let key = compute_hkdf(hash, data_key, &header.message_id, &info, suite.key_size())?;
+            let commitment = compute_hkdf(
+                hash,
+                data_key,
+                &header.message_id,
+                COMMIT_KEY_LABEL,
+                COMMITMENT_SIZE,
+            )?;
+            Ok((key, commitment))
+        }
+        Some(hash) => {
+            let mut info = suite.id().to_be_bytes().to_vec();
+            info.extend_from_slice(&header.message_id);
+            let key = compute_hkdf(hash, data_key, &[], &info, suite.key_size())?;
+            Ok((key, Vec::new()))
+        }
+    }
+}
+
+/// Build the additional data for a block of message content.
+fn content_aad(message_id: &[u8], label: &[u8], seq: u32, len: u64) -> Vec<u8> {
+    let mut aad = Vec::with_capacity(message_id.len() + label.len() + 12);
+    aad.extend_from_slice(message_id);
+    aad.extend_from_slice(label);
+    aad.extend_from_slice(&seq.to_be_bytes());
+    aad.extend_from_slice(&len.to_be_bytes());
+    aad
+}
+
+/// Build the IV for a block of message content, which is the big-endian sequence number.
+fn content_iv(seq: u32) -> Vec<u8> {
+    let mut iv = vec![0u8; crate::subtle::AES_GCM_IV_SIZE];
+    iv[8..].copy_from_slice(&seq.to_be_bytes());
+    iv
+}
+
+/// Decrypt the data key from the first encrypted data key whose key URI is in `key_uris` and
+/// that a registered KMS client can handle.
+fn decrypt_data_key(key_uris: &[&str], header: &Header) -> Result<Vec<u8>, TinkError> {
+    let mut last_err = TinkError::new("no encrypted data keys");
+    for edk in &header.encrypted_data_keys {
+        let info = match std::str::from_utf8(&edk.provider_info) {
+            Ok(info) => info,
+            Err(e) => {
+                last_err = wrap_err("invalid key provider info", e);
+                continue;
+            }
+        };
+        let key_uri = format!("{}://{}", edk.provider_id, info);
+        if !key_uris.contains(&key_uri.as_str()) {
+            last_err = format!("key URI {} not allowed", key_uri).into();
+            continue;
+        }
+        let result = registry::get_kms_client(&key_uri)
+            .and_then(|client| client.get_aead_with_context(&key_uri, &header.encryption_context))
+            .and_then(|aead| aead.decrypt(&edk.ciphertext, &[]));
+        match result {
+            Ok(data_key) if data_key.len() == header.suite.key_size() => return Ok(data_key),
+            Ok(_) => last_err = TinkError::new("data key has wrong size"),
+            Err(e) => last_err = e,
+        }
+    }
+    Err(wrap_err("esdk: failed to decrypt data key", last_err))
+}
+
+/// Decrypt a message in the AWS Encryption SDK message format, using one of the copies of the
+/// data key that are encrypted under a key in `key_uris`.  Copies encrypted under any other key
+/// are ignored.
+pub fn decrypt(key_uris: &[&str], message: &[u8]) -> Result<DecryptedMessage, TinkError> {
+    let mut p = Parser::new(message);
+    let header = Header::parse(&mut p)?;
+    let header_len = p.position();
+    let suite = header.suite;
+
+    let data_key = decrypt_data_key(key_uris, &header)?;
+    let (key, commitment) = derive_keys(&header, &data_key)?;
+    if suite.is_committing()
+        && !tink_core::subtle::constant_time_compare(&commitment, &header.commitment)
+    {
+        return Err("esdk: key commitment mismatch".into());
+    }
+    let cipher = crate::subtle::AesGcm::new(&key)?;
+
+    // Check the header authentication tag, computed over an empty plaintext.
+    let header_iv = if suite.message_version() == VERSION_1 {
+        p.read_bytes(crate::subtle::AES_GCM_IV_SIZE)?.to_vec()
+    } else {
+        vec![0u8; crate::subtle::AES_GCM_IV_SIZE]
+    };
+    let header_tag = p.read_bytes(crate::subtle::AES_GCM_TAG_SIZE)?;
+    cipher
+        .decrypt_with_iv(&header_iv, header_tag, &message[..header_len])
+        .map_err(|e| wrap_err("esdk: header authentication failed", e))?;
+
+    let mut plaintext = Vec::new();
+    if header.content_type == CONTENT_NON_FRAMED {
+        let iv = p.read_bytes(crate::subtle::AES_GCM_IV_SIZE)?;
+        let len = p.read_u64()?;
+        let ct_len = usize::try_from(len)
+            .ok()
+            .and_then(|l| l.checked_add(crate::subtle::AES_GCM_TAG_SIZE))
+            .ok_or_else(|| TinkError::new("esdk: content too long"))?;
+        let ct = p.read_bytes(ct_len)?;
+        let aad = content_aad(&header.message_id, SINGLE_BLOCK_AAD, 1, len);
+        plaintext = cipher
+            .decrypt_with_iv(iv, ct, &aad)
+            .map_err(|e| wrap_err("esdk: decryption failed", e))?;
+    } else {
+        let mut seq: u32 = 1;
+        loop {
+            let marker = p.read_u32()?;
+            let final_frame = marker == FINAL_FRAME_MARKER;
+            let frame_seq = if final_frame { p.read_u32()? } else { marker };
+            if frame_seq != seq {
+                return Err("esdk: unexpected frame sequence number".into());
+            }
+            let iv = p.read_bytes(crate::subtle::AES_GCM_IV_SIZE)?;
+            if iv != content_iv(seq).as_slice() {
+                return Err("esdk: unexpected frame IV".into());
+            }
+            let len = if final_frame {
+                p.read_u32()?
+            } else {
+                header.frame_length
+            };
+            if len > header.frame_length {
+                return Err("esdk: final frame too long".into());
+            }
+            let ct_len = (len as usize)
+                .checked_add(crate::subtle::AES_GCM_TAG_SIZE)
+                .ok_or_else(|| TinkError::new("esdk: frame too long"))?;
+            let ct = p.read_bytes(ct_len)?;
+            let label = if final_frame {
+                FINAL_FRAME_AAD
+            } else {
+                FRAME_AAD
+            };
+            let aad = content_aad(&header.message_id, label, seq, len as u64);
+            let pt = cipher
+                .decrypt_with_iv(iv, ct, &aad)
+                .map_err(|e| wrap_err("esdk: decryption failed", e))?;
+            plaintext.extend_from_slice(&pt);
+            if final_frame {
+                break;
+            }
+            seq = seq
+                .checked_add(1)
+                .filter(|s| *s != FINAL_FRAME_MARKER)
+                .ok_or_else(|| TinkError::new("esdk: too many frames"))?;
+        }
+    }
+
+    if let Some(curve) = suite.signature_curve() {
+        let signed_len = p.position();
+        let signature = p.read_field()?;
+        let public_key = header
+            .encryption_context
+            .get(PUBLIC_KEY_CONTEXT_KEY)
+            .ok_or_else(|| TinkError::new("esdk: missing public key for signed message"))?;
+        let public_key = base64::decode(public_key)
+            .map_err(|e| wrap_err("esdk: invalid public key encoding", e))?;
+        verify(curve, &public_key, &message[..signed_len], signature)?;
+    }
+    if !p.is_empty() {
+        return Err("esdk: trailing data after message".into());
+    }
+
+    Ok(DecryptedMessage {
+        plaintext,
+        encryption_context: header.encryption_context,
+        algorithm_suite: suite,
+    })
+}
+
+/// Encrypt `plaintext` into a message in the AWS Encryption SDK message format, with a copy of
+/// the data key encrypted under each of `key_uris` (via registered KMS clients).  Only the
+/// key-committing algorithm suites are supported for encryption.
+///
+/// The encryption context is bound to the message but is not secret; keys beginning with
+/// `aws-crypto-` are reserved.
+pub fn encrypt(
+    key_uris: &[&str],
+    plaintext: &[u8],
+    encryption_context: &BTreeMap<String, String>,
+    suite: AlgorithmSuite,
+) -> Result<Vec<u8>, TinkError> {
+    if !suite.is_committing() {
+        return Err("esdk: encryption requires a key-committing algorithm suite".into());
+    }
+    if key_uris.is_empty() {
+        return Err("esdk: no key URIs".into());
+    }
+    if encryption_context
+        .keys()
+        .any(|k| k.starts_with(RESERVED_CONTEXT_PREFIX))
+    {
+        return Err("esdk: encryption context uses reserved key".into());
+    }
+    let mut encryption_context = encryption_context.clone();
+    let signing_key = match suite.signature_curve() {
+        Some(curve) => {
+            let (signing_key, public_key) = new_signing_key(curve)?;
+            encryption_context.insert(
+                PUBLIC_KEY_CONTEXT_KEY.to_string(),
+                base64::encode(&public_key),
+            );
+            Some(signing_key)
+        }
+        None => None,
+    };
+
+    let data_key = tink_core::subtle::random::get_random_bytes(suite.key_size());
+    let mut encrypted_data_keys = Vec::with_capacity(key_uris.len());
+    for key_uri in key_uris {
+        let (provider_id, provider_info) = key_uri
+            .split_once("://")
+            .ok_or_else(|| format!("esdk: key URI {} has no scheme", key_uri))?;
+        let aead = registry::get_kms_client(key_uri)?
+            .get_aead_with_context(key_uri, &encryption_context)?;
+        encrypted_data_keys.push(EncryptedDataKey {
+            provider_id: provider_id.to_string(),
+            provider_info: provider_info.as_bytes().to_vec(),
+            ciphertext: aead
+                .encrypt(&data_key, &[])
+                .map_err(|e| wrap_err("esdk: failed to encrypt data key", e))?,
+        });
+    }
+
+    let mut header = Header {
+        suite,
+        message_id: tink_core::subtle::random::get_random_bytes(MESSAGE_ID_SIZE_V2),
+        encryption_context,
+        encrypted_data_keys,
+        content_type: CONTENT_FRAMED,
+        frame_length: FRAME_LENGTH,
+        commitment: Vec::new(),
+    };
+    let (key, commitment) = derive_keys(&header, &data_key)?;
+    header.commitment = commitment;
+    let cipher = crate::subtle::AesGcm::new(&key)?;
+
+    let mut out = header.serialize()?;
+    let header_tag = cipher.encrypt_with_iv(&[0u8; crate::subtle::AES_GCM_IV_SIZE], &[], &out)?;
+    out.extend_from_slice(&header_tag);
+
+    let mut chunks = plaintext.chunks(FRAME_LENGTH as usize).peekable();
+    let mut seq: u32 = 1;
+    loop {
+        let chunk = chunks.next().unwrap_or(&[]);
+        // The last frame is always a final frame, even when the plaintext length is an exact
+        // multiple of the frame length.
+        let final_frame = chunks.peek().is_none();
+        let iv = content_iv(seq);
+        if final_frame {
+            out.extend_from_slice(&FINAL_FRAME_MARKER.to_be_bytes());
+            out.extend_from_slice(&seq.to_be_bytes());
+            out.extend_from_slice(&iv);
+            out.extend_from_slice(&(chunk.len() as u32).to_be_bytes());
+        } else {
+            out.extend_from_slice(&seq.to_be_bytes());
+            out.extend_from_slice(&iv);
+        }
+        let label = if final_frame {
+            FINAL_FRAME_AAD
+        } else {
+            FRAME_AAD
+        };
+        let aad = content_aad(&header.message_id, label, seq, chunk.len() as u64);
+        out.extend_from_slice(&cipher.encrypt_with_iv(&iv, chunk, &aad)?);
+        if final_frame {
+            break;
+        }
+        seq = seq
+            .checked_add(1)
+            .filter(|s| *s != FINAL_FRAME_MARKER)
+            .ok_or_else(|| TinkError::new("esdk: plaintext too long"))?;
+    }
+
+    if let Some(signing_key) = signing_key {
+        let signature = signing_key.sign(&out)?;
+        out.extend_from_slice(&(signature.len() as u16).to_be_bytes());
+        out.extend_from_slice(&signature);
+    }
+    Ok(out)
+}
+
+/// Private key for signing messages.
+enum SigningKey {
+    P256(p256::ecdsa::SigningKey),
+    P384(p384::ecdsa::SigningKey),
+}
+
+impl SigningKey {
+    /// Return a DER-encoded signature over `data`.
+    fn sign(&self, data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        use ecdsa::signature::Signer;
+        Ok(match self {
+            SigningKey::P256(key) => {
+                let signature: p256::ecdsa::Signature = key
+                    .try_sign(data)
+                    .map_err(|e| wrap_err("esdk: signing failed", e))?;
+                signature.to_der().as_bytes().to_vec()
+            }
+            SigningKey::P384(key) => {
+                let signature: p384::ecdsa::Signature = key
+                    .try_sign(data)
+                    .map_err(|e| wrap_err("esdk: signing failed", e))?;
+                signature.to_der().as_bytes().to_vec()
+            }
+        })
+    }
+}
+
+/// Generate a signing key, returning it together with its compressed public key.
+fn new_signing_key(curve: SignatureCurve) -> Result<(SigningKey, Vec<u8>), TinkError> {
+    // Retry on the (vanishingly unlikely) chance that the random scalar is out of range.
+    for _ in 0..10 {
+        match curve {
+            SignatureCurve::P256 => {
+                let bytes = tink_core::subtle::random::get_random_bytes(32);
+                if let Ok(key) = p256::ecdsa::SigningKey::from_bytes(&bytes) {
+                    let public_key = key.verifying_key().to_encoded_point(true);
+                    return Ok((SigningKey::P256(key), public_key.as_bytes().to_vec()));
+                }
+            }
+            SignatureCurve::P384 => {
+                let bytes = tink_core::subtle::random::get_random_bytes(48);
+                if let Ok(key) = p384::ecdsa::SigningKey::from_bytes(&bytes) {
+                    let public_key = key.verifying_key().to_encoded_point(true);
+                    return Ok((SigningKey::P384(key), public_key.as_bytes().to_vec()));
+                }
+            }
+        }
+    }
+    Err("esdk: failed to generate signing key".into())
+}
+
+/// Verify a DER-encoded signature over `data` with a SEC1-encoded public key.
+fn verify(
+    curve: SignatureCurve,
+    public_key: &[u8],
+    data: &[u8],
+    signature: &[u8],
+) -> Result<(), TinkError> {
+    use ecdsa::signature::Verifier;
+    match curve {
+        SignatureCurve::P256 => {
+            let key = p256::ecdsa::VerifyingKey::from_sec1_bytes(public_key)
+                .map_err(|e| wrap_err("esdk: invalid public key", e))?;
+            let signature = p256::ecdsa::Signature::from_der(signature)
+                .map_err(|e| wrap_err("esdk: invalid signature", e))?;
+            key.verify(data, &signature)
+        }
+        SignatureCurve::P384 => {
+            let key = p384::ecdsa::VerifyingKey::from_sec1_bytes(public_key)
+                .map_err(|e| wrap_err("esdk: invalid public key", e))?;
+            let signature = p384::ecdsa::Signature::from_der(signature)
+                .map_err(|e| wrap_err("esdk: invalid signature", e))?;
+            key.verify(data, &signature)
+        }
+    }
+    .map_err(|e| wrap_err("esdk: signature verification failed", e))
+}
@@ -0,0 +1,106 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Provide an AEAD that falls back to older keysets for decryption.
+
+use tink_core::{keyset::Handle, utils::wrap_err, TinkError};
+
+/// `FallbackAead` encrypts with a primary keyset, and decrypts by trying the primary keyset and
+/// then each of a list of fallback keysets in turn (for example, previous keyset generations
+/// that are no longer rotated into the primary keyset).  This allows data to be migrated across
+/// keyset boundaries without retry loops in the application.
+///
+/// Keysets are identified by their index: 0 for the primary keyset, and `i + 1` for the `i`-th
+/// fallback keyset.  [`decrypt_with_keyset_index`](FallbackAead::decrypt_with_keyset_index)
+/// reports which keyset decrypted a ciphertext, so that callers can re-encrypt data that still
+/// relies on a fallback keyset.
+pub struct FallbackAead {
+    primary: Box<dyn tink_core::Aead>,
+    fallbacks: Vec<Box<dyn tink_core::Aead>>,
+}
+
+/// Manual implementation of [`Clone`] relying on the trait bounds for
+/// primitives to provide `.box_clone()` methods.
+impl Clone for FallbackAead {
+    fn clone(&self) -> Self {
+        Self {
+            primary: self.primary.box_clone(),
+            fallbacks: self.fallbacks.iter().map(|a| a.box_clone()).collect(),
+        }
+    }
+}
+
+impl FallbackAead {
+    /// Create a [`FallbackAead`] from the given AEAD primitives.
+    pub fn new(
+        primary: Box<dyn tink_core::Aead>,
+        fallbacks: Vec<Box<dyn tink_core::Aead>>,
+    ) -> FallbackAead {
+        FallbackAead { primary, fallbacks }
+    }
+
+    /// Create a [`FallbackAead`] from the AEAD primitives of the given keyset handles.
+    pub fn from_handles(primary: &Handle, fallbacks: &[Handle]) -> Result<FallbackAead, TinkError> {
+        let primary =
+            crate::new(primary).map_err(|e| wrap_err("FallbackAead: invalid primary keyset", e))?;
+        let fallbacks = fallbacks
+            .iter()
+            .enumerate()
+            .map(|(i, h)| {
+                crate::new(h).map_err(|e| {
+                    wrap_err(&format!("FallbackAead: invalid fallback keyset {}", i), e)
+                })
+            })
+            .collect::<Result<Vec<_>, TinkError>>()?;
+        Ok(FallbackAead::new(primary, fallbacks))
+    }
+
+    /// Return the number of keysets, including the primary keyset.
+    pub fn keyset_count(&self) -> usize {
+        1 + self.fallbacks.len()
+    }
+
+    /// Decrypt `ct` with `aad` as additional authenticated data, returning the plaintext and the
+    /// index of the keyset that decrypted it (0 for the primary keyset).
+    pub fn decrypt_with_keyset_index(
+        &self,
+        ct: &[u8],
+        aad: &[u8],
+    ) -> Result<(Vec<u8>, usize), TinkError> {
+        std::iter::once(&self.primary)
+            .chain(self.fallbacks.iter())
+            .enumerate()
+            .find_map(|(i, a)| a.decrypt(ct, aad).ok().map(|pt| (pt, i)))
+            .ok_or_else(|| {
+                format!(
+                    "FallbackAead: decryption failed with all {} keysets",
+                    self.keyset_count()
+                )
+                .into()
+            })
+    }
+}
+
+impl tink_core::Aead for FallbackAead {
+    /// Encrypt with the primary keyset.
+    fn encrypt(&self, pt: &[u8], aad: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.primary.encrypt(pt, aad)
+    }
+
+    fn decrypt(&self, ct: &[u8], aad: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.decrypt_with_keyset_index(ct, aad).map(|(pt, _)| pt)
+    }
+}
@@ -21,10 +21,43 @@ use tink_core::{utils::wrap_err, TinkError};
 
 const LEN_DEK: usize = 4;
 
+/// `KmsAssociatedData` determines whether, and how, a [`KmsEnvelopeAead`] passes the caller's
+/// associated data to the remote KMS when wrapping and unwrapping the data encryption key (DEK).
+///
+/// Forwarding the associated data lets the KMS enforce policy tied to the data's context (for
+/// example AWS KMS encryption context conditions), but only works with KMS clients whose
+/// backends authenticate additional data (see
+/// [`KmsClient::supports_associated_data`](tink_core::registry::KmsClient::supports_associated_data)).
+/// Ciphertexts produced with forwarding enabled can only be decrypted with the same setting.
+///
+/// This is the same KMS-side associated data that binds the AEADs returned by
+/// [`KmsClient::get_aead_with_context`](tink_core::registry::KmsClient::get_aead_with_context)
+/// to their encryption context.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KmsAssociatedData {
+    /// The DEK is wrapped with empty associated data; the caller's associated data only
+    /// authenticates the payload.  This is the ciphertext format used by other Tink
+    /// implementations.
+    Omit,
+    /// The caller's associated data is passed to the KMS unchanged.
+    Forward,
+    /// The SHA-256 digest of the caller's associated data is passed to the KMS, which bounds the
+    /// size of KMS requests and keeps the associated data itself out of KMS audit logs.
+    Sha256,
+}
+
+impl Default for KmsAssociatedData {
+    fn default() -> Self {
+        KmsAssociatedData::Omit
+    }
+}
+
 /// `KmsEnvelopeAead` represents an instance of Envelope AEAD.
 pub struct KmsEnvelopeAead {
     dek_template: tink_proto::KeyTemplate,
     remote: Box<dyn tink_core::Aead>,
+    associated_data: KmsAssociatedData,
+    dek_policy: Option<crate::KmsEnvelopeDekPolicy>,
 }
 
 /// Manual implementation of [`Clone`] relying on the trait bounds for
@@ -34,6 +67,8 @@ impl Clone for KmsEnvelopeAead {
         Self {
             dek_template: self.dek_template.clone(),
             remote: self.remote.box_clone(),
+            associated_data: self.associated_data,
+            dek_policy: self.dek_policy.clone(),
         }
     }
 }
@@ -43,6 +78,42 @@ impl KmsEnvelopeAead {
         KmsEnvelopeAead {
             dek_template: kt,
             remote,
+            associated_data: KmsAssociatedData::Omit,
+            dek_policy: None,
+        }
+    }
+
+    /// Restrict the DEKs used for encryption and accepted for decryption to those permitted by
+    /// `policy`.  Fails if the policy does not permit the DEK template.
+    pub fn with_dek_policy(
+        mut self,
+        policy: crate::KmsEnvelopeDekPolicy,
+    ) -> Result<Self, TinkError> {
+        policy
+            .check_template(&self.dek_template)
+            .map_err(|e| wrap_err("KmsEnvelopeAead", e))?;
+        self.dek_policy = Some(policy);
+        Ok(self)
+    }
+
+    /// Configure how the caller's associated data is passed to the remote KMS.  The remote
+    /// [`Aead`](tink_core::Aead) must authenticate its additional data for this to have any
+    /// effect.
+    pub fn with_associated_data(mut self, associated_data: KmsAssociatedData) -> Self {
+        self.associated_data = associated_data;
+        self
+    }
+
+    /// Return the associated data to use for the remote KMS operation.
+    fn remote_aad(&self, aad: &[u8]) -> Result<Vec<u8>, TinkError> {
+        match self.associated_data {
+            KmsAssociatedData::Omit => Ok(Vec::new()),
+            KmsAssociatedData::Forward => Ok(aad.to_vec()),
+            KmsAssociatedData::Sha256 => {
+                let mut hash_func = tink_core::subtle::get_hash_func(tink_proto::HashType::Sha256)
+                    .ok_or_else(|| TinkError::new("KmsEnvelopeAead: no SHA-256"))?;
+                tink_core::subtle::compute_hash(&mut hash_func, aad)
+            }
         }
     }
 }
@@ -51,12 +122,11 @@ impl tink_core::Aead for KmsEnvelopeAead {
     fn encrypt(&self, pt: &[u8], aad: &[u8]) -> Result<Vec<u8>, TinkError> {
         // Create a new key for each encryption operation.
         let dek = tink_core::registry::new_key(&self.dek_template)?;
-        let encrypted_dek = self.remote.encrypt(&dek, &[])?;
+        let encrypted_dek = self.remote.encrypt(&dek, &self.remote_aad(aad)?)?;
 
-        let primitive = match tink_core::registry::primitive(&self.dek_template.type_url, &dek)? {
-            tink_core::Primitive::Aead(p) => p,
-            _ => return Err("KmsEnvelopeAead: failed to convert AEAD primitive".into()),
-        };
+        let primitive = tink_core::registry::primitive(&self.dek_template.type_url, &dek)?
+            .into_aead()
+            .map_err(|e| wrap_err("KmsEnvelopeAead: failed to convert AEAD primitive", e))?;
         let payload = primitive.encrypt(pt, aad)?;
         build_cipher_text(&encrypted_dek, &payload)
     }
@@ -81,15 +151,21 @@ impl tink_core::Aead for KmsEnvelopeAead {
         let payload = &ct[ed..];
 
         // Decrypt the DEK.
-        let dek = self.remote.decrypt(encrypted_dek, &[])?;
+        let dek = self.remote.decrypt(encrypted_dek, &self.remote_aad(aad)?)?;
+
+        // Check the DEK before it is interpreted according to the DEK template.
+        if let Some(policy) = &self.dek_policy {
+            policy
+                .check_dek(&self.dek_template.type_url, &dek)
+                .map_err(|e| wrap_err("KmsEnvelopeAead", e))?;
+        }
 
         // Get an AEAD primitive corresponding to the DEK.
         let p = tink_core::registry::primitive(&self.dek_template.type_url, &dek)
             .map_err(|e| wrap_err("KmsEnvelopeAead", e))?;
-        let primitive = match p {
-            tink_core::Primitive::Aead(p) => p,
-            _ => return Err("KmsEnvelopeAead: failed to convert AEAD primitive".into()),
-        };
+        let primitive = p
+            .into_aead()
+            .map_err(|e| wrap_err("KmsEnvelopeAead: failed to convert AEAD primitive", e))?;
 
         // Decrypt the payload.
         primitive.decrypt(payload, aad)
@@ -29,7 +29,30 @@ pub const KMS_ENVELOPE_AEAD_TYPE_URL: &str =
 /// It generates new [`KmsEnvelopeAeadKey`](tink_proto::KmsEnvelopeAeadKey) keys and produces new
 /// instances of [`KmsEnvelopeAead`](crate::KmsEnvelopeAead).
 #[derive(Default)]
-pub(crate) struct KmsEnvelopeAeadKeyManager {}
+pub(crate) struct KmsEnvelopeAeadKeyManager {
+    /// How produced primitives pass associated data to the KMS.
+    associated_data: crate::KmsAssociatedData,
+    /// Restriction on the DEKs of produced primitives.
+    dek_policy: Option<crate::KmsEnvelopeDekPolicy>,
+}
+
+impl KmsEnvelopeAeadKeyManager {
+    /// Create a key manager whose primitives pass associated data to the KMS as specified.
+    pub(crate) fn new_with_associated_data(associated_data: crate::KmsAssociatedData) -> Self {
+        Self {
+            associated_data,
+            dek_policy: None,
+        }
+    }
+
+    /// Create a key manager whose primitives only use DEKs permitted by `dek_policy`.
+    pub(crate) fn new_with_dek_policy(dek_policy: crate::KmsEnvelopeDekPolicy) -> Self {
+        Self {
+            associated_data: crate::KmsAssociatedData::Omit,
+            dek_policy: Some(dek_policy),
+        }
+    }
+}
 
 impl tink_core::registry::KeyManager for KmsEnvelopeAeadKeyManager {
     /// Create a [`crate::KmsEnvelopeAead`] for the given serialized
@@ -46,18 +69,30 @@ impl tink_core::registry::KeyManager for KmsEnvelopeAeadKeyManager {
             .ok_or_else(|| TinkError::new("KmsEnvelopeAeadKeyManager: missing URI"))?;
         let uri = key_params.kek_uri;
         let kms_client = tink_core::registry::get_kms_client(&uri)?;
+        if self.associated_data != crate::KmsAssociatedData::Omit
+            && !kms_client.supports_associated_data(&uri)
+        {
+            return Err(
+                "KmsEnvelopeAeadKeyManager: KMS client does not support associated data".into(),
+            );
+        }
         let backend = kms_client
             .get_aead(&uri)
             .map_err(|e| wrap_err("KmsEnvelopeAeadKeyManager: invalid aead backend", e))?;
 
-        Ok(tink_core::Primitive::Aead(Box::new(
-            crate::KmsEnvelopeAead::new(
-                key_params.dek_template.ok_or_else(|| {
-                    TinkError::new("KmsEnvelopeAeadKeyManager: missing DEK template")
-                })?,
-                backend,
-            ),
-        )))
+        let mut aead = crate::KmsEnvelopeAead::new(
+            key_params
+                .dek_template
+                .ok_or_else(|| TinkError::new("KmsEnvelopeAeadKeyManager: missing DEK template"))?,
+            backend,
+        )
+        .with_associated_data(self.associated_data);
+        if let Some(policy) = &self.dek_policy {
+            aead = aead
+                .with_dek_policy(policy.clone())
+                .map_err(|e| wrap_err("KmsEnvelopeAeadKeyManager", e))?;
+        }
+        Ok(tink_core::Primitive::Aead(Box::new(aead)))
     }
 
     /// Create a new key according to specification the given serialized
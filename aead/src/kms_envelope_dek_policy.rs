@@ -0,0 +1,194 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Restriction of the data encryption keys (DEKs) used by [`KmsEnvelopeAead`](crate::KmsEnvelopeAead).
+
+use tink_core::{utils::wrap_err, TinkError};
+use tink_proto::{prost::Message, KeyTemplate};
+
+/// The parameters of a DEK that a [`KmsEnvelopeDekPolicy`] compares, which can be determined both
+/// from a DEK template and from a DEK.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum DekShape {
+    AesGcm {
+        key_size: usize,
+    },
+    AesGcmSiv {
+        key_size: usize,
+    },
+    AesGcmCommitting {
+        key_size: usize,
+    },
+    AesCtrHmac {
+        aes_key_size: usize,
+        iv_size: u32,
+        hmac_key_size: usize,
+        hash: i32,
+        tag_size: u32,
+    },
+    ChaCha20Poly1305,
+    XChaCha20Poly1305,
+}
+
+/// `KmsEnvelopeDekPolicy` restricts the DEK templates that a
+/// [`KmsEnvelopeAead`](crate::KmsEnvelopeAead) accepts to an allowlist, for example to permit
+/// only AES-256-GCM.
+///
+/// The policy is checked against the DEK template when the AEAD is created, and against each DEK
+/// when it is unwrapped during decryption, so that a ciphertext carrying a DEK of a different
+/// type or size (which the DEK template would otherwise be used to interpret) is rejected.
+///
+/// Templates are compared by key type and key parameters; their output prefix types are
+/// ignored, as DEKs are always used without a prefix.  Only the AEAD key types of this crate are
+/// supported.
+#[derive(Clone, Debug)]
+pub struct KmsEnvelopeDekPolicy {
+    allowed: Vec<DekShape>,
+}
+
+impl KmsEnvelopeDekPolicy {
+    /// Create a policy that permits DEKs matching any of the `allowed` templates.
+    pub fn new(allowed: &[KeyTemplate]) -> Result<Self, TinkError> {
+        let allowed = allowed
+            .iter()
+            .map(|kt| {
+                template_shape(kt)
+                    .map_err(|e| wrap_err("KmsEnvelopeDekPolicy: invalid template", e))
+            })
+            .collect::<Result<Vec<_>, TinkError>>()?;
+        Ok(KmsEnvelopeDekPolicy { allowed })
+    }
+
+    /// Check that the policy permits DEKs generated from `kt`.
+    pub fn check_template(&self, kt: &KeyTemplate) -> Result<(), TinkError> {
+        let shape = template_shape(kt)
+            .map_err(|e| wrap_err("KmsEnvelopeDekPolicy: DEK template not permitted", e))?;
+        self.check(&shape)
+            .map_err(|e| wrap_err("KmsEnvelopeDekPolicy: DEK template not permitted", e))
+    }
+
+    /// Check that the policy permits the serialized DEK `dek` of type `type_url`.
+    pub(crate) fn check_dek(&self, type_url: &str, dek: &[u8]) -> Result<(), TinkError> {
+        let shape = key_shape(type_url, dek)
+            .map_err(|e| wrap_err("KmsEnvelopeDekPolicy: invalid DEK", e))?;
+        self.check(&shape)
+            .map_err(|e| wrap_err("KmsEnvelopeDekPolicy: DEK not permitted", e))
+    }
+
+    fn check(&self, shape: &DekShape) -> Result<(), TinkError> {
+        if self.allowed.contains(shape) {
+            Ok(())
+        } else {
+            Err(format!("{:?} is not in the allowlist", shape).into())
+        }
+    }
+}
+
+/// Return the shape of the DEKs generated from `kt`.
+fn template_shape(kt: &KeyTemplate) -> Result<DekShape, TinkError> {
+    let value = &kt.value[..];
+    let invalid = |e| wrap_err("invalid key format", e);
+    Ok(match kt.type_url.as_str() {
+        crate::AES_GCM_TYPE_URL => DekShape::AesGcm {
+            key_size: tink_proto::AesGcmKeyFormat::decode(value)
+                .map_err(invalid)?
+                .key_size as usize,
+        },
+        crate::AES_GCM_SIV_TYPE_URL => DekShape::AesGcmSiv {
+            key_size: tink_proto::AesGcmSivKeyFormat::decode(value)
+                .map_err(invalid)?
+                .key_size as usize,
+        },
+        crate::AES_GCM_COMMITTING_TYPE_URL => DekShape::AesGcmCommitting {
+            key_size: tink_proto::AesGcmCommittingKeyFormat::decode(value)
+                .map_err(invalid)?
+                .key_size as usize,
+        },
+        crate::AES_CTR_HMAC_AEAD_TYPE_URL => {
+            let format = tink_proto::AesCtrHmacAeadKeyFormat::decode(value).map_err(invalid)?;
+            let aes = format
+                .aes_ctr_key_format
+                .ok_or_else(|| TinkError::new("missing AES-CTR key format"))?;
+            let hmac = format
+                .hmac_key_format
+                .ok_or_else(|| TinkError::new("missing HMAC key format"))?;
+            let aes_params = aes.params.unwrap_or_default();
+            let hmac_params = hmac.params.unwrap_or_default();
+            DekShape::AesCtrHmac {
+                aes_key_size: aes.key_size as usize,
+                iv_size: aes_params.iv_size,
+                hmac_key_size: hmac.key_size as usize,
+                hash: hmac_params.hash,
+                tag_size: hmac_params.tag_size,
+            }
+        }
+        crate::CHA_CHA20_POLY1305_TYPE_URL => DekShape::ChaCha20Poly1305,
+        crate::X_CHA_CHA20_POLY1305_TYPE_URL => DekShape::XChaCha20Poly1305,
+        other => return Err(format!("unsupported DEK type {}", other).into()),
+    })
+}
+
+/// Return the shape of the serialized key `dek` of type `type_url`.
+fn key_shape(type_url: &str, dek: &[u8]) -> Result<DekShape, TinkError> {
+    let invalid = |e| wrap_err("invalid key", e);
+    Ok(match type_url {
+        crate::AES_GCM_TYPE_URL => DekShape::AesGcm {
+            key_size: tink_proto::AesGcmKey::decode(dek)
+                .map_err(invalid)?
+                .key_value
+                .len(),
+        },
+        crate::AES_GCM_SIV_TYPE_URL => DekShape::AesGcmSiv {
+            key_size: tink_proto::AesGcmSivKey::decode(dek)
+                .map_err(invalid)?
+                .key_value
+                .len(),
+        },
+        crate::AES_GCM_COMMITTING_TYPE_URL => DekShape::AesGcmCommitting {
+            key_size: tink_proto::AesGcmCommittingKey::decode(dek)
+                .map_err(invalid)?
+                .key_value
+                .len(),
+        },
+        crate::AES_CTR_HMAC_AEAD_TYPE_URL => {
+            let key = tink_proto::AesCtrHmacAeadKey::decode(dek).map_err(invalid)?;
+            let aes = key
+                .aes_ctr_key
+                .ok_or_else(|| TinkError::new("missing AES-CTR key"))?;
+            let hmac = key
+                .hmac_key
+                .ok_or_else(|| TinkError::new("missing HMAC key"))?;
+            let aes_params = aes.params.unwrap_or_default();
+            let hmac_params = hmac.params.unwrap_or_default();
+            DekShape::AesCtrHmac {
+                aes_key_size: aes.key_value.len(),
+                iv_size: aes_params.iv_size,
+                hmac_key_size: hmac.key_value.len(),
+                hash: hmac_params.hash,
+                tag_size: hmac_params.tag_size,
+            }
+        }
+        crate::CHA_CHA20_POLY1305_TYPE_URL => {
+            tink_proto::ChaCha20Poly1305Key::decode(dek).map_err(invalid)?;
+            DekShape::ChaCha20Poly1305
+        }
+        crate::X_CHA_CHA20_POLY1305_TYPE_URL => {
+            tink_proto::XChaCha20Poly1305Key::decode(dek).map_err(invalid)?;
+            DekShape::XChaCha20Poly1305
+        }
+        other => return Err(format!("unsupported DEK type {}", other).into()),
+    })
+}
@@ -19,6 +19,7 @@
 //! AEAD encryption assures the confidentiality and authenticity of the data. This primitive is CPA
 //! secure.
 
+#![cfg_attr(docsrs, feature(doc_cfg))]
 #![deny(broken_intra_doc_links)]
 
 use std::sync::Once;
@@ -30,16 +31,31 @@ mod aead_key_templates;
 pub use aead_key_templates::*;
 mod aes_ctr_hmac_aead_key_manager;
 pub use aes_ctr_hmac_aead_key_manager::*;
+mod aes_gcm_committing_key_manager;
+pub use aes_gcm_committing_key_manager::*;
 mod aes_gcm_key_manager;
 pub use aes_gcm_key_manager::*;
 mod aes_gcm_siv_key_manager;
 pub use aes_gcm_siv_key_manager::*;
+mod batch;
+pub use batch::*;
 mod chacha20poly1305_key_manager;
 pub use chacha20poly1305_key_manager::*;
+mod epoch_aead;
+pub use epoch_aead::*;
+#[cfg(feature = "esdk")]
+#[cfg_attr(docsrs, doc(cfg(feature = "esdk")))]
+pub mod esdk;
+mod fallback_aead;
+pub use fallback_aead::*;
 mod kms_envelope_aead;
 pub use kms_envelope_aead::*;
 mod kms_envelope_aead_key_manager;
 pub use kms_envelope_aead_key_manager::*;
+mod kms_envelope_dek_policy;
+pub use kms_envelope_dek_policy::*;
+mod prefix_migration;
+pub use prefix_migration::*;
 mod xchacha20poly1305_key_manager;
 pub use xchacha20poly1305_key_manager::*;
 
@@ -59,6 +75,8 @@ pub fn init() {
             .expect("tink_aead::init() failed"); // safe: init
         register_key_manager(std::sync::Arc::new(AesGcmKeyManager::default()))
             .expect("tink_aead::init() failed"); // safe: init
+        register_key_manager(std::sync::Arc::new(AesGcmCommittingKeyManager::default()))
+            .expect("tink_aead::init() failed"); // safe: init
         register_key_manager(std::sync::Arc::new(AesGcmSivKeyManager::default()))
             .expect("tink_aead::init() failed"); // safe: init
         register_key_manager(std::sync::Arc::new(ChaCha20Poly1305KeyManager::default()))
@@ -74,6 +92,14 @@ pub fn init() {
             "AES256_GCM_NO_PREFIX",
             aes256_gcm_no_prefix_key_template,
         );
+        tink_core::registry::register_template_generator(
+            "AES128_GCM_COMMITTING",
+            aes128_gcm_committing_key_template,
+        );
+        tink_core::registry::register_template_generator(
+            "AES256_GCM_COMMITTING",
+            aes256_gcm_committing_key_template,
+        );
         tink_core::registry::register_template_generator(
             "AES128_GCM_SIV",
             aes128_gcm_siv_key_template,
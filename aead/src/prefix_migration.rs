@@ -0,0 +1,208 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Migration of AEAD ciphertexts from other output prefix types to the TINK prefix.
+
+use std::{cell::Cell, collections::HashMap};
+use tink_core::{
+    cryptofmt::{LEGACY_START_BYTE, NON_RAW_PREFIX_SIZE, TINK_START_BYTE},
+    keyset::Handle,
+    utils::wrap_err,
+    KeyId, TinkError,
+};
+use tink_proto::OutputPrefixType;
+
+/// The output prefix format of a ciphertext, as detected by a [`PrefixMigrator`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CiphertextPrefix {
+    /// Prefixed for a key with [`OutputPrefixType::Tink`].
+    Tink,
+    /// Prefixed for a key with [`OutputPrefixType::Legacy`].
+    Legacy,
+    /// Prefixed for a key with [`OutputPrefixType::Crunchy`].
+    Crunchy,
+    /// Not recognizably prefixed for any key in the keyset, as for keys with
+    /// [`OutputPrefixType::Raw`].
+    Raw,
+}
+
+/// The result of migrating a single ciphertext with a [`PrefixMigrator`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MigratedCiphertext {
+    /// The migrated ciphertext.  This is the original ciphertext if it already had the TINK
+    /// prefix.
+    pub ciphertext: Vec<u8>,
+    /// The prefix format of the original ciphertext.
+    pub source: CiphertextPrefix,
+}
+
+impl MigratedCiphertext {
+    /// Return whether the ciphertext was re-encrypted.
+    pub fn reencrypted(&self) -> bool {
+        self.source != CiphertextPrefix::Tink
+    }
+}
+
+/// Counts of the ciphertexts processed by a [`PrefixMigrator`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PrefixMigrationStats {
+    /// Ciphertexts that already had the TINK prefix, and were left unchanged.
+    pub unchanged: u64,
+    /// Ciphertexts with the LEGACY prefix that were re-encrypted.
+    pub from_legacy: u64,
+    /// Ciphertexts with the CRUNCHY prefix that were re-encrypted.
+    pub from_crunchy: u64,
+    /// Ciphertexts without a recognized prefix that were re-encrypted.
+    pub from_raw: u64,
+    /// Ciphertexts that could not be migrated.
+    pub failed: u64,
+}
+
+/// `PrefixMigrator` re-encrypts AEAD ciphertexts that were produced by keys with the LEGACY,
+/// CRUNCHY or RAW output prefix types under the primary key of a keyset, which must have the
+/// TINK output prefix type.  This allows data encrypted by older systems to be migrated to the
+/// TINK prefix format by rewriting it in place, without changes to the applications that read
+/// it (which can keep using a keyset holding both the old and new keys).
+///
+/// Every ciphertext is decrypted, so the migration also checks the integrity of the data.
+/// Ciphertexts that already have the TINK prefix are left unchanged, even if they were
+/// encrypted under a key other than the primary key.
+///
+/// Records are processed one at a time, so large data sets can be migrated as a stream with
+/// [`PrefixMigrator::migrate_iter`].
+pub struct PrefixMigrator {
+    aead: Box<dyn tink_core::Aead>,
+    prefix_types: HashMap<KeyId, Vec<OutputPrefixType>>,
+    stats: Cell<PrefixMigrationStats>,
+}
+
+impl PrefixMigrator {
+    /// Create a migrator for the keys in `h`, which must be able to decrypt the ciphertexts to
+    /// be migrated, and whose primary key must have the TINK output prefix type.
+    pub fn new(h: &Handle) -> Result<PrefixMigrator, TinkError> {
+        let info = h.keyset_info();
+        let primary = info
+            .key_info
+            .iter()
+            .find(|k| k.key_id == info.primary_key_id)
+            .ok_or_else(|| TinkError::new("PrefixMigrator: no primary key"))?;
+        if primary.output_prefix_type != OutputPrefixType::Tink as i32 {
+            return Err("PrefixMigrator: primary key must have the TINK output prefix type".into());
+        }
+        let mut prefix_types: HashMap<KeyId, Vec<OutputPrefixType>> = HashMap::new();
+        for key in &info.key_info {
+            if let Ok(t) = key.try_output_prefix_type() {
+                prefix_types.entry(key.key_id).or_default().push(t);
+            }
+        }
+        Ok(PrefixMigrator {
+            aead: crate::new(h).map_err(|e| wrap_err("PrefixMigrator", e))?,
+            prefix_types,
+            stats: Cell::new(PrefixMigrationStats::default()),
+        })
+    }
+
+    /// Migrate a single `ciphertext` with associated data `aad`, re-encrypting it (with the same
+    /// associated data) unless it already has the TINK prefix.
+    pub fn migrate(&self, ciphertext: &[u8], aad: &[u8]) -> Result<MigratedCiphertext, TinkError> {
+        let result = self.migrate_one(ciphertext, aad);
+        let mut stats = self.stats.get();
+        match &result {
+            Ok(m) => match m.source {
+                CiphertextPrefix::Tink => stats.unchanged += 1,
+                CiphertextPrefix::Legacy => stats.from_legacy += 1,
+                CiphertextPrefix::Crunchy => stats.from_crunchy += 1,
+                CiphertextPrefix::Raw => stats.from_raw += 1,
+            },
+            Err(_) => stats.failed += 1,
+        }
+        self.stats.set(stats);
+        result
+    }
+
+    /// Migrate a batch of `(ciphertext, aad)` records, returning the result for each record in
+    /// order.  A failure for one record does not affect the others.
+    pub fn migrate_batch(
+        &self,
+        records: &[(&[u8], &[u8])],
+    ) -> Vec<Result<MigratedCiphertext, TinkError>> {
+        records
+            .iter()
+            .map(|(ct, aad)| self.migrate(ct, aad))
+            .collect()
+    }
+
+    /// Lazily migrate a sequence of `(ciphertext, aad)` records, returning the result for each
+    /// record in order.  Only one record is held in memory at a time.
+    pub fn migrate_iter<'a, I>(
+        &'a self,
+        records: I,
+    ) -> impl Iterator<Item = Result<MigratedCiphertext, TinkError>> + 'a
+    where
+        I: IntoIterator<Item = (Vec<u8>, Vec<u8>)>,
+        I::IntoIter: 'a,
+    {
+        records
+            .into_iter()
+            .map(move |(ct, aad)| self.migrate(&ct, &aad))
+    }
+
+    /// Return the counts of ciphertexts processed so far.
+    pub fn stats(&self) -> PrefixMigrationStats {
+        self.stats.get()
+    }
+
+    fn migrate_one(&self, ciphertext: &[u8], aad: &[u8]) -> Result<MigratedCiphertext, TinkError> {
+        let plaintext = self
+            .aead
+            .decrypt(ciphertext, aad)
+            .map_err(|e| wrap_err("PrefixMigrator: decryption failed", e))?;
+        let source = self.detect_prefix(ciphertext);
+        if source == CiphertextPrefix::Tink {
+            return Ok(MigratedCiphertext {
+                ciphertext: ciphertext.to_vec(),
+                source,
+            });
+        }
+        let ciphertext = self
+            .aead
+            .encrypt(&plaintext, aad)
+            .map_err(|e| wrap_err("PrefixMigrator: encryption failed", e))?;
+        Ok(MigratedCiphertext { ciphertext, source })
+    }
+
+    /// Determine the prefix format of a ciphertext from its first bytes.  A ciphertext whose
+    /// prefix matches keys with more than one prefix type is attributed to the first of TINK,
+    /// LEGACY or CRUNCHY that matches.
+    fn detect_prefix(&self, ciphertext: &[u8]) -> CiphertextPrefix {
+        if ciphertext.len() < NON_RAW_PREFIX_SIZE {
+            return CiphertextPrefix::Raw;
+        }
+        let key_id =
+            KeyId::from_be_bytes([ciphertext[1], ciphertext[2], ciphertext[3], ciphertext[4]]);
+        let types = match self.prefix_types.get(&key_id) {
+            Some(types) => types,
+            None => return CiphertextPrefix::Raw,
+        };
+        let has = |t: OutputPrefixType| types.contains(&t);
+        match ciphertext[0] {
+            TINK_START_BYTE if has(OutputPrefixType::Tink) => CiphertextPrefix::Tink,
+            LEGACY_START_BYTE if has(OutputPrefixType::Legacy) => CiphertextPrefix::Legacy,
+            LEGACY_START_BYTE if has(OutputPrefixType::Crunchy) => CiphertextPrefix::Crunchy,
+            _ => CiphertextPrefix::Raw,
+        }
+    }
+}
@@ -0,0 +1,270 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Support for supplying associated data incrementally, for AEAD constructions whose
+//! authenticator can absorb the associated data a block at a time.
+//!
+//! [`AesGcmWithAadReader`](crate::subtle::AesGcmWithAadReader),
+//! [`ChaCha20Poly1305`](crate::subtle::ChaCha20Poly1305) and
+//! [`XChaCha20Poly1305`](crate::subtle::XChaCha20Poly1305) override the
+//! [`tink_core::Aead`] methods that take associated data from an [`std::io::Read`], so that very
+//! large associated data never needs to be held in memory.  The ciphertexts produced and
+//! accepted are identical to those of the slice-based methods.
+
+use aes::cipher::{
+    consts::U16, generic_array::GenericArray, BlockCipher, BlockEncrypt, BlockSizeUser, KeyInit,
+    KeyIvInit, StreamCipher, StreamCipherSeek,
+};
+use ghash::{universal_hash::UniversalHash, GHash};
+use poly1305::Poly1305;
+use std::io::Read;
+use tink_core::{utils::wrap_err, TinkError};
+
+/// Size of the blocks absorbed by GHASH and Poly1305.
+const MAC_BLOCK_SIZE: usize = 16;
+/// Size of the buffer used when reading associated data.
+const AAD_BUFFER_SIZE: usize = 256 * MAC_BLOCK_SIZE;
+/// Size of the GCM IV supported here.
+const GCM_IV_SIZE: usize = 12;
+
+/// `AadChunks` presents a sequence of byte slices as a single [`std::io::Read`], for supplying
+/// associated data in pieces to
+/// [`encrypt_with_aad_reader`](tink_core::Aead::encrypt_with_aad_reader) and
+/// [`decrypt_with_aad_reader`](tink_core::Aead::decrypt_with_aad_reader).
+pub struct AadChunks<'a, I: Iterator<Item = &'a [u8]>> {
+    chunks: I,
+    current: &'a [u8],
+}
+
+impl<'a, I: Iterator<Item = &'a [u8]>> AadChunks<'a, I> {
+    /// Return an [`AadChunks`] that yields the concatenation of `chunks`.
+    pub fn new<T: IntoIterator<IntoIter = I>>(chunks: T) -> Self {
+        AadChunks {
+            chunks: chunks.into_iter(),
+            current: &[],
+        }
+    }
+}
+
+impl<'a, I: Iterator<Item = &'a [u8]>> Read for AadChunks<'a, I> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.current.is_empty() {
+            match self.chunks.next() {
+                Some(chunk) => self.current = chunk,
+                None => return Ok(0),
+            }
+        }
+        let n = std::cmp::min(buf.len(), self.current.len());
+        buf[..n].copy_from_slice(&self.current[..n]);
+        self.current = &self.current[n..];
+        Ok(n)
+    }
+}
+
+/// Feed everything readable from `aad` into `mac`, zero-padding the final partial block, and
+/// return the number of bytes absorbed.
+fn absorb_aad<U: UniversalHash<BlockSize = U16>>(
+    mac: &mut U,
+    aad: &mut dyn Read,
+) -> Result<u64, TinkError> {
+    let mut buf = [0u8; AAD_BUFFER_SIZE];
+    let mut filled = 0;
+    let mut total = 0u64;
+    loop {
+        let n = match aad.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(wrap_err("failed to read associated data", e)),
+        };
+        filled += n;
+        total += n as u64;
+        let whole = filled - filled % MAC_BLOCK_SIZE;
+        if whole == 0 {
+            continue;
+        }
+        mac.update_padded(&buf[..whole]);
+        buf.copy_within(whole..filled, 0);
+        filled -= whole;
+    }
+    mac.update_padded(&buf[..filled]);
+    Ok(total)
+}
+
+/// Build the final block of the GCM authenticator, holding the bit lengths of the associated
+/// data and ciphertext.
+fn gcm_length_block(aad_len: u64, ct_len: usize) -> GenericArray<u8, U16> {
+    let mut block = GenericArray::default();
+    block[..8].copy_from_slice(&(aad_len * 8).to_be_bytes());
+    block[8..].copy_from_slice(&((ct_len as u64) * 8).to_be_bytes());
+    block
+}
+
+/// Build the final block of the Poly1305 authenticator, holding the byte lengths of the
+/// associated data and ciphertext.
+fn poly1305_length_block(aad_len: u64, ct_len: usize) -> GenericArray<u8, U16> {
+    let mut block = GenericArray::default();
+    block[..8].copy_from_slice(&aad_len.to_le_bytes());
+    block[8..].copy_from_slice(&(ct_len as u64).to_le_bytes());
+    block
+}
+
+/// Compute the GCM tag for ciphertext `ct` under `key` and the 96-bit `iv`.
+fn gcm_tag<C>(key: &[u8], iv: &[u8], ct: &[u8], aad: &mut dyn Read) -> Result<[u8; 16], TinkError>
+where
+    C: BlockCipher + BlockEncrypt + KeyInit + BlockSizeUser<BlockSize = U16>,
+{
+    let cipher = C::new_from_slice(key).map_err(|_| TinkError::new("invalid AES key size"))?;
+    let mut h = GenericArray::default();
+    cipher.encrypt_block(&mut h);
+    let mut mac = GHash::new(&h);
+    let aad_len = absorb_aad(&mut mac, aad)?;
+    mac.update_padded(ct);
+    mac.update(&[gcm_length_block(aad_len, ct.len())]);
+    let mut tag = gcm_counter_block(iv, 1)?;
+    cipher.encrypt_block(&mut tag);
+    let mut out = [0u8; 16];
+    for (o, (t, s)) in out.iter_mut().zip(tag.iter().zip(mac.finalize().iter())) {
+        *o = t ^ s;
+    }
+    Ok(out)
+}
+
+/// Return the GCM counter block for a 96-bit `iv` and the given counter value.
+fn gcm_counter_block(iv: &[u8], counter: u32) -> Result<GenericArray<u8, U16>, TinkError> {
+    if iv.len() != GCM_IV_SIZE {
+        return Err("invalid GCM IV size".into());
+    }
+    let mut block = GenericArray::default();
+    block[..GCM_IV_SIZE].copy_from_slice(iv);
+    block[GCM_IV_SIZE..].copy_from_slice(&counter.to_be_bytes());
+    Ok(block)
+}
+
+/// Apply the GCM keystream for `key` and the 96-bit `iv` to `data`.
+fn gcm_ctr<C>(key: &[u8], iv: &[u8], data: &mut [u8]) -> Result<(), TinkError>
+where
+    C: BlockCipher + BlockEncrypt + KeyInit + BlockSizeUser<BlockSize = U16>,
+{
+    let mut ctr = ctr::Ctr32BE::<C>::new_from_slices(key, &gcm_counter_block(iv, 2)?)
+        .map_err(|_| TinkError::new("invalid AES key size"))?;
+    ctr.apply_keystream(data);
+    Ok(())
+}
+
+/// AES-GCM encryption of `pt` with a 96-bit `iv`, returning the ciphertext followed by the tag.
+pub(crate) fn gcm_seal<C>(
+    key: &[u8],
+    iv: &[u8],
+    pt: &[u8],
+    aad: &mut dyn Read,
+) -> Result<Vec<u8>, TinkError>
+where
+    C: BlockCipher + BlockEncrypt + KeyInit + BlockSizeUser<BlockSize = U16>,
+{
+    let mut ct = Vec::with_capacity(pt.len() + MAC_BLOCK_SIZE);
+    ct.extend_from_slice(pt);
+    gcm_ctr::<C>(key, iv, &mut ct)?;
+    let tag = gcm_tag::<C>(key, iv, &ct, aad)?;
+    ct.extend_from_slice(&tag);
+    Ok(ct)
+}
+
+/// AES-GCM decryption of `ct` (the ciphertext followed by the tag) with a 96-bit `iv`.
+pub(crate) fn gcm_open<C>(
+    key: &[u8],
+    iv: &[u8],
+    ct: &[u8],
+    aad: &mut dyn Read,
+) -> Result<Vec<u8>, TinkError>
+where
+    C: BlockCipher + BlockEncrypt + KeyInit + BlockSizeUser<BlockSize = U16>,
+{
+    if ct.len() < MAC_BLOCK_SIZE {
+        return Err("ciphertext too short".into());
+    }
+    let (ct, tag) = ct.split_at(ct.len() - MAC_BLOCK_SIZE);
+    let computed = gcm_tag::<C>(key, iv, ct, aad)?;
+    if !tink_core::subtle::constant_time_compare(&computed, tag) {
+        return Err("aead::Error".into());
+    }
+    let mut pt = ct.to_vec();
+    gcm_ctr::<C>(key, iv, &mut pt)?;
+    Ok(pt)
+}
+
+/// Set up a ChaCha20 (or XChaCha20) keystream for RFC 8439 AEAD, returning the cipher
+/// positioned at block 1 together with the one-time Poly1305 authenticator.
+fn chacha_init<C>(key: &[u8], nonce: &[u8]) -> Result<(C, Poly1305), TinkError>
+where
+    C: KeyIvInit + StreamCipher + StreamCipherSeek,
+{
+    let mut cipher = C::new_from_slices(key, nonce)
+        .map_err(|_| TinkError::new("invalid ChaCha20 key or nonce size"))?;
+    let mut mac_key = poly1305::Key::default();
+    cipher.apply_keystream(&mut mac_key);
+    cipher.seek(64u64);
+    Ok((cipher, Poly1305::new(&mac_key)))
+}
+
+/// ChaCha20-Poly1305 (or XChaCha20-Poly1305) encryption of `pt`, returning the ciphertext
+/// followed by the tag.
+pub(crate) fn chacha_seal<C>(
+    key: &[u8],
+    nonce: &[u8],
+    pt: &[u8],
+    aad: &mut dyn Read,
+) -> Result<Vec<u8>, TinkError>
+where
+    C: KeyIvInit + StreamCipher + StreamCipherSeek,
+{
+    let (mut cipher, mut mac) = chacha_init::<C>(key, nonce)?;
+    let aad_len = absorb_aad(&mut mac, aad)?;
+    let mut ct = Vec::with_capacity(pt.len() + MAC_BLOCK_SIZE);
+    ct.extend_from_slice(pt);
+    cipher.apply_keystream(&mut ct);
+    mac.update_padded(&ct);
+    mac.update(&[poly1305_length_block(aad_len, ct.len())]);
+    ct.extend_from_slice(&mac.finalize());
+    Ok(ct)
+}
+
+/// ChaCha20-Poly1305 (or XChaCha20-Poly1305) decryption of `ct` (the ciphertext followed by the
+/// tag).
+pub(crate) fn chacha_open<C>(
+    key: &[u8],
+    nonce: &[u8],
+    ct: &[u8],
+    aad: &mut dyn Read,
+) -> Result<Vec<u8>, TinkError>
+where
+    C: KeyIvInit + StreamCipher + StreamCipherSeek,
+{
+    if ct.len() < MAC_BLOCK_SIZE {
+        return Err("ciphertext too short".into());
+    }
+    let (ct, tag) = ct.split_at(ct.len() - MAC_BLOCK_SIZE);
+    let (mut cipher, mut mac) = chacha_init::<C>(key, nonce)?;
+    let aad_len = absorb_aad(&mut mac, aad)?;
+    mac.update_padded(ct);
+    mac.update(&[poly1305_length_block(aad_len, ct.len())]);
+    if !tink_core::subtle::constant_time_compare(&mac.finalize(), tag) {
+        return Err("aead::Error".into());
+    }
+    let mut pt = ct.to_vec();
+    cipher.apply_keystream(&mut pt);
+    Ok(pt)
+}
@@ -23,3 +23,16 @@ pub fn validate_aes_key_size(size_in_bytes: usize) -> Result<(), tink_core::Tink
         _ => Err(format!("invalid AES key size; want 16 or 32, got {}", size_in_bytes).into()),
     }
 }
+
+/// Check if the given key size is a valid AES key size for AES-GCM or AES-GCM-SIV, which
+/// additionally accept 192-bit keys.
+pub fn validate_aes_gcm_key_size(size_in_bytes: usize) -> Result<(), tink_core::TinkError> {
+    match size_in_bytes {
+        16 | 24 | 32 => Ok(()),
+        _ => Err(format!(
+            "invalid AES key size; want 16, 24 or 32, got {}",
+            size_in_bytes
+        )
+        .into()),
+    }
+}
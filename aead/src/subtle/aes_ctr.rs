@@ -24,6 +24,7 @@ use tink_core::{utils::wrap_err, TinkError};
 /// The minimum IV size that this implementation supports.
 pub const AES_CTR_MIN_IV_SIZE: usize = 12;
 
+/// The AES block size, which is also the maximum IV size that this implementation supports.
 pub const AES_BLOCK_SIZE_IN_BYTES: usize = 16;
 
 type Aes128Ctr = ::ctr::Ctr64BE<aes::Aes128>;
@@ -36,7 +37,14 @@ enum AesCtrVariant {
     Aes256([u8; 32]),
 }
 
-/// `AesCtr` is an implementation of AEAD interface.
+/// `AesCtr` is an implementation of [`IndCpaCipher`] using AES in counter mode.
+///
+/// Each encryption uses a fresh random IV of `iv_size` bytes.  The initial counter block is the
+/// IV followed by zero bytes, and the counter occupies the final 8 bytes of the block as a
+/// big-endian integer (so a single message can be up to 2^68 bytes long with a 12-byte IV).  The
+/// ciphertext is the IV followed by the encrypted plaintext.
+///
+/// With random IVs of the minimum size, a key should not be used for more than 2^32 messages.
 #[derive(Clone)]
 pub struct AesCtr {
     key: AesCtrVariant,
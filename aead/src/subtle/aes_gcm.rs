@@ -16,46 +16,171 @@
 
 //! AES-GCM based implementation of the [`tink_core::Aead`] trait.
 
+use crate::subtle::{gcm_open, gcm_seal};
+use aes::{Aes128, Aes192, Aes256};
 use aes_gcm::{
     aead::{consts::U12, generic_array::GenericArray, Aead, Payload},
     KeyInit,
 };
+use std::{
+    io::Read,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
 use tink_core::{utils::wrap_err, TinkError};
+use zeroize::Zeroizing;
 
 /// The only IV size that this implementation supports.
 pub const AES_GCM_IV_SIZE: usize = 12;
 /// The only tag size that this implementation supports.
 pub const AES_GCM_TAG_SIZE: usize = 16;
+/// Default bound on the number of encryptions with random IVs performed with a single key, to
+/// keep the probability of an IV collision below 2^-32 (NIST SP 800-38D section 8.3).
+pub const AES_GCM_DEFAULT_MAX_INVOCATIONS: u64 = 1 << 32;
 /// The maximum supported plaintext size.
 const MAX_AES_GCM_PLAINTEXT_SIZE: u64 = (1 << 36) - 32;
 
+/// AES-GCM with a 192-bit key, which the `aes-gcm` crate does not provide an alias for.
+type Aes192Gcm = aes_gcm::AesGcm<Aes192, U12>;
+
 #[derive(Clone)]
 enum AesGcmVariant {
     Aes128(Box<aes_gcm::Aes128Gcm>),
+    Aes192(Box<Aes192Gcm>),
     Aes256(Box<aes_gcm::Aes256Gcm>),
 }
 
 /// `AesGcm` is an implementation of the [`tink_core::Aead`] trait.
+///
+/// An `AesGcm` instance may optionally enforce a bound on the number of encryptions it performs
+/// (see [`AesGcm::with_invocation_limit`]).  Clones of the instance share the same count.
 #[derive(Clone)]
 pub struct AesGcm {
     key: AesGcmVariant,
+    limit: Option<Arc<InvocationLimit>>,
+}
+
+/// Count of encryptions performed with a key, together with the bound on that count.
+struct InvocationLimit {
+    max: u64,
+    count: AtomicU64,
 }
 
 impl AesGcm {
     /// Return an [`AesGcm`] instance.
-    /// The key argument should be the AES key, either 16 or 32 bytes to select
-    /// AES-128 or AES-256.
+    /// The key argument should be the AES key, either 16, 24 or 32 bytes to select
+    /// AES-128, AES-192 or AES-256.
     pub fn new(key: &[u8]) -> Result<AesGcm, TinkError> {
         let key = match key.len() {
             16 => AesGcmVariant::Aes128(Box::new(aes_gcm::Aes128Gcm::new(
-                GenericArray::from_slice(key),
+                GenericArray::from_slice(key), // safe: length matched
             ))),
+            24 => AesGcmVariant::Aes192(Box::new(Aes192Gcm::new(GenericArray::from_slice(key)))), // safe: length matched
             32 => AesGcmVariant::Aes256(Box::new(aes_gcm::Aes256Gcm::new(
-                GenericArray::from_slice(key),
+                GenericArray::from_slice(key), // safe: length matched
             ))),
-            l => return Err(format!("AesGcm: invalid AES key size {} (want 16, 32)", l).into()),
+            l => {
+                return Err(format!("AesGcm: invalid AES key size {} (want 16, 24, 32)", l).into())
+            }
         };
-        Ok(AesGcm { key })
+        Ok(AesGcm { key, limit: None })
+    }
+
+    /// Return an [`AesGcm`] instance for the given key that refuses to perform more than
+    /// [`AES_GCM_DEFAULT_MAX_INVOCATIONS`] encryptions.
+    pub fn new_with_default_limit(key: &[u8]) -> Result<AesGcm, TinkError> {
+        Ok(Self::new(key)?.with_invocation_limit(AES_GCM_DEFAULT_MAX_INVOCATIONS))
+    }
+
+    /// Return a copy of this instance that refuses to perform more than `max` encryptions with
+    /// random IVs; once the bound is reached, [`encrypt`](tink_core::Aead::encrypt) fails and the
+    /// key should be rotated.  Decryption is unaffected.  The count starts from zero, and is
+    /// shared by any clones of the returned instance.
+    ///
+    /// For keys in a keyset, the equivalent bound can be applied with
+    /// [`Handle::set_key_usage_limit`](tink_core::keyset::Handle::set_key_usage_limit).
+    pub fn with_invocation_limit(&self, max: u64) -> AesGcm {
+        AesGcm {
+            key: self.key.clone(),
+            limit: Some(Arc::new(InvocationLimit {
+                max,
+                count: AtomicU64::new(0),
+            })),
+        }
+    }
+
+    /// Return the number of encryptions performed so far, if an invocation limit is in force.
+    pub fn invocations(&self) -> Option<u64> {
+        self.limit
+            .as_ref()
+            .map(|limit| limit.count.load(Ordering::SeqCst))
+    }
+
+    /// Count an encryption with a random IV against the invocation limit, if any.
+    pub(crate) fn record_invocation(&self) -> Result<(), TinkError> {
+        if let Some(limit) = &self.limit {
+            limit
+                .count
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                    if n < limit.max {
+                        Some(n + 1)
+                    } else {
+                        None
+                    }
+                })
+                .map_err(|_| TinkError::new("AesGcm: invocation limit reached, rotate the key"))?;
+        }
+        Ok(())
+    }
+
+    /// Encrypt `pt` with `aad` as additional authenticated data, using the given IV.  The
+    /// resulting ciphertext does not include the IV.
+    pub(crate) fn encrypt_with_iv(
+        &self,
+        iv: &[u8],
+        pt: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, TinkError> {
+        if pt.len() as u64 > max_pt_size() {
+            return Err("AesGcm: plaintext too long".into());
+        }
+        if iv.len() != AES_GCM_IV_SIZE {
+            return Err("AesGcm: invalid IV size".into());
+        }
+        let iv = GenericArray::from_slice(iv); // safe: length checked
+        let payload = Payload { msg: pt, aad };
+        match &self.key {
+            AesGcmVariant::Aes128(key) => key.encrypt(iv, payload),
+            AesGcmVariant::Aes192(key) => key.encrypt(iv, payload),
+            AesGcmVariant::Aes256(key) => key.encrypt(iv, payload),
+        }
+        .map_err(|e| wrap_err("AesGcm", e))
+    }
+
+    /// Decrypt `ct` (which does not include the IV) with `aad` as the additional authenticated
+    /// data, using the given IV.
+    pub(crate) fn decrypt_with_iv(
+        &self,
+        iv: &[u8],
+        ct: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, TinkError> {
+        if iv.len() != AES_GCM_IV_SIZE {
+            return Err("AesGcm: invalid IV size".into());
+        }
+        if ct.len() < AES_GCM_TAG_SIZE {
+            return Err("AesGcm: ciphertext too short".into());
+        }
+        let iv = GenericArray::from_slice(iv); // safe: length checked
+        let payload = Payload { msg: ct, aad };
+        match &self.key {
+            AesGcmVariant::Aes128(key) => key.decrypt(iv, payload),
+            AesGcmVariant::Aes192(key) => key.decrypt(iv, payload),
+            AesGcmVariant::Aes256(key) => key.decrypt(iv, payload),
+        }
+        .map_err(|e| wrap_err("AesGcm", e))
     }
 }
 
@@ -65,14 +190,85 @@ impl tink_core::Aead for AesGcm {
     ///
     /// Note: AES-GCM implementation of crypto library always returns ciphertext with 128-bit tag.
     fn encrypt(&self, pt: &[u8], aad: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.record_invocation()?;
+        let iv = new_iv();
+        let ct = self.encrypt_with_iv(&iv, pt, aad)?;
+        let mut ret = Vec::with_capacity(iv.len() + ct.len());
+        ret.extend_from_slice(&iv);
+        ret.extend_from_slice(&ct);
+        Ok(ret)
+    }
+
+    /// Decrypt `ct` with `aad` as the additional authenticated data.
+    fn decrypt(&self, ct: &[u8], aad: &[u8]) -> Result<Vec<u8>, TinkError> {
+        if ct.len() < AES_GCM_IV_SIZE + AES_GCM_TAG_SIZE {
+            return Err("AesGcm: ciphertext too short".into());
+        }
+        self.decrypt_with_iv(&ct[..AES_GCM_IV_SIZE], &ct[AES_GCM_IV_SIZE..], aad)
+    }
+}
+
+/// `AesGcmWithAadReader` is an implementation of the [`tink_core::Aead`] trait that absorbs
+/// associated data supplied through
+/// [`encrypt_with_aad_reader`](tink_core::Aead::encrypt_with_aad_reader) and
+/// [`decrypt_with_aad_reader`](tink_core::Aead::decrypt_with_aad_reader) incrementally, rather
+/// than reading it all into memory first.  Its ciphertexts are identical to those of [`AesGcm`].
+///
+/// The incremental construction needs the raw AES key, which is held (shared between clones) in
+/// memory that is zeroized when the last clone is dropped.
+#[derive(Clone)]
+pub struct AesGcmWithAadReader {
+    aead: AesGcm,
+    raw_key: Arc<Zeroizing<Vec<u8>>>,
+}
+
+impl AesGcmWithAadReader {
+    /// Return an [`AesGcmWithAadReader`] instance.
+    /// The key argument should be the AES key, either 16, 24 or 32 bytes to select
+    /// AES-128, AES-192 or AES-256.
+    pub fn new(key: &[u8]) -> Result<AesGcmWithAadReader, TinkError> {
+        Ok(AesGcmWithAadReader {
+            aead: AesGcm::new(key)?,
+            raw_key: Arc::new(Zeroizing::new(key.to_vec())),
+        })
+    }
+
+    /// Return a copy of this instance that refuses to perform more than `max` encryptions with
+    /// random IVs, as for [`AesGcm::with_invocation_limit`].
+    pub fn with_invocation_limit(&self, max: u64) -> AesGcmWithAadReader {
+        AesGcmWithAadReader {
+            aead: self.aead.with_invocation_limit(max),
+            raw_key: self.raw_key.clone(),
+        }
+    }
+
+    /// Return the number of encryptions performed so far, if an invocation limit is in force.
+    pub fn invocations(&self) -> Option<u64> {
+        self.aead.invocations()
+    }
+}
+
+impl tink_core::Aead for AesGcmWithAadReader {
+    fn encrypt(&self, pt: &[u8], aad: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.aead.encrypt(pt, aad)
+    }
+
+    fn decrypt(&self, ct: &[u8], aad: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.aead.decrypt(ct, aad)
+    }
+
+    /// Encrypt `pt` with the associated data read from `aad`, producing the same ciphertext
+    /// format as [`encrypt`](tink_core::Aead::encrypt).
+    fn encrypt_with_aad_reader(&self, pt: &[u8], aad: &mut dyn Read) -> Result<Vec<u8>, TinkError> {
         if pt.len() as u64 > max_pt_size() {
             return Err("AesGcm: plaintext too long".into());
         }
+        self.aead.record_invocation()?;
         let iv = new_iv();
-        let payload = Payload { msg: pt, aad };
-        let ct = match &self.key {
-            AesGcmVariant::Aes128(key) => key.encrypt(&iv, payload),
-            AesGcmVariant::Aes256(key) => key.encrypt(&iv, payload),
+        let ct = match &self.aead.key {
+            AesGcmVariant::Aes128(_) => gcm_seal::<Aes128>(&self.raw_key, &iv, pt, aad),
+            AesGcmVariant::Aes192(_) => gcm_seal::<Aes192>(&self.raw_key, &iv, pt, aad),
+            AesGcmVariant::Aes256(_) => gcm_seal::<Aes256>(&self.raw_key, &iv, pt, aad),
         }
         .map_err(|e| wrap_err("AesGcm", e))?;
         let mut ret = Vec::with_capacity(iv.len() + ct.len());
@@ -81,29 +277,25 @@ impl tink_core::Aead for AesGcm {
         Ok(ret)
     }
 
-    /// Decrypt `ct` with `aad` as the additional authenticated data.
-    fn decrypt(&self, ct: &[u8], aad: &[u8]) -> Result<Vec<u8>, TinkError> {
+    /// Decrypt `ct` with the associated data read from `aad`.
+    fn decrypt_with_aad_reader(&self, ct: &[u8], aad: &mut dyn Read) -> Result<Vec<u8>, TinkError> {
         if ct.len() < AES_GCM_IV_SIZE + AES_GCM_TAG_SIZE {
             return Err("AesGcm: ciphertext too short".into());
         }
-        let iv = GenericArray::from_slice(&ct[..AES_GCM_IV_SIZE]);
-        let payload = Payload {
-            msg: &ct[AES_GCM_IV_SIZE..],
-            aad,
-        };
-        let pt = match &self.key {
-            AesGcmVariant::Aes128(key) => key.decrypt(iv, payload),
-            AesGcmVariant::Aes256(key) => key.decrypt(iv, payload),
+        let (iv, ct) = ct.split_at(AES_GCM_IV_SIZE);
+        match &self.aead.key {
+            AesGcmVariant::Aes128(_) => gcm_open::<Aes128>(&self.raw_key, iv, ct, aad),
+            AesGcmVariant::Aes192(_) => gcm_open::<Aes192>(&self.raw_key, iv, ct, aad),
+            AesGcmVariant::Aes256(_) => gcm_open::<Aes256>(&self.raw_key, iv, ct, aad),
         }
-        .map_err(|e| wrap_err("AesGcm", e))?;
-        Ok(pt)
+        .map_err(|e| wrap_err("AesGcm", e))
     }
 }
 
 /// Create a new IV for encryption.
 fn new_iv() -> GenericArray<u8, U12> {
     let iv = tink_core::subtle::random::get_random_bytes(AES_GCM_IV_SIZE);
-    *GenericArray::<u8, U12>::from_slice(&iv)
+    *GenericArray::<u8, U12>::from_slice(&iv) // safe: AES_GCM_IV_SIZE bytes
 }
 
 /// Maximum plaintext size.
@@ -0,0 +1,108 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Key-committing AEAD based on AES-GCM, implementing the [`tink_core::Aead`] trait.
+
+use super::{AesGcm, AES_GCM_IV_SIZE, AES_GCM_TAG_SIZE};
+use tink_core::{utils::wrap_err, Mac, TinkError};
+use tink_mac::subtle::Hmac;
+use tink_proto::HashType;
+
+/// The size of the key commitment included in each ciphertext.
+pub const AES_GCM_COMMITMENT_SIZE: usize = 32;
+/// Label for deriving the per-message encryption key.
+const ENCRYPTION_KEY_LABEL: u8 = 0x01;
+/// Label for deriving the per-message key commitment.
+const COMMITMENT_LABEL: u8 = 0x02;
+
+/// `AesGcmCommitting` is an implementation of the [`tink_core::Aead`] trait that is key
+/// committing: a ciphertext can only be decrypted under the key that produced it.
+///
+/// For each message a random IV is generated, and an AES-GCM key and a key commitment are
+/// derived from the main key and the IV:
+///
+///   enc_key = HMAC-SHA256(key, 0x01 || iv)[..key_size]
+///   commitment = HMAC-SHA256(key, 0x02 || iv)
+///
+/// The ciphertext consists of iv || commitment || AES-GCM(enc_key, iv, pt, aad), and the
+/// commitment is checked before any decryption is attempted.
+#[derive(Clone)]
+pub struct AesGcmCommitting {
+    hmac: Hmac,
+    key_size: usize,
+}
+
+impl AesGcmCommitting {
+    /// Return an [`AesGcmCommitting`] instance.
+    /// The key argument should be the main key, either 16 or 32 bytes to select
+    /// AES-128 or AES-256 for the per-message keys.
+    pub fn new(key: &[u8]) -> Result<AesGcmCommitting, TinkError> {
+        let key_size = key.len();
+        super::validate_aes_key_size(key_size).map_err(|e| wrap_err("AesGcmCommitting", e))?;
+        let hmac = Hmac::new(HashType::Sha256, key, AES_GCM_COMMITMENT_SIZE)
+            .map_err(|e| wrap_err("AesGcmCommitting", e))?;
+        Ok(AesGcmCommitting { hmac, key_size })
+    }
+
+    /// Compute the HMAC of `label || iv`.
+    fn derive(&self, label: u8, iv: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let mut input = Vec::with_capacity(1 + iv.len());
+        input.push(label);
+        input.extend_from_slice(iv);
+        self.hmac.compute_mac(&input)
+    }
+
+    /// Return the AES-GCM primitive for the given IV.
+    fn new_cipher(&self, iv: &[u8]) -> Result<AesGcm, TinkError> {
+        let enc_key = self.derive(ENCRYPTION_KEY_LABEL, iv)?;
+        AesGcm::new(&enc_key[..self.key_size])
+    }
+}
+
+impl tink_core::Aead for AesGcmCommitting {
+    /// Encrypt `pt` with `aad` as additional authenticated data.  The resulting ciphertext
+    /// consists of the IV, the key commitment and the AES-GCM ciphertext.
+    fn encrypt(&self, pt: &[u8], aad: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let iv = tink_core::subtle::random::get_random_bytes(AES_GCM_IV_SIZE);
+        let cipher = self.new_cipher(&iv)?;
+        let commitment = self.derive(COMMITMENT_LABEL, &iv)?;
+        let ct = cipher.encrypt_with_iv(&iv, pt, aad)?;
+
+        let mut ret = Vec::with_capacity(iv.len() + commitment.len() + ct.len());
+        ret.extend_from_slice(&iv);
+        ret.extend_from_slice(&commitment);
+        ret.extend_from_slice(&ct);
+        Ok(ret)
+    }
+
+    /// Decrypt `ct` with `aad` as the additional authenticated data.
+    fn decrypt(&self, ct: &[u8], aad: &[u8]) -> Result<Vec<u8>, TinkError> {
+        if ct.len() < AES_GCM_IV_SIZE + AES_GCM_COMMITMENT_SIZE + AES_GCM_TAG_SIZE {
+            return Err("AesGcmCommitting: ciphertext too short".into());
+        }
+        let iv = &ct[..AES_GCM_IV_SIZE];
+        let commitment = &ct[AES_GCM_IV_SIZE..AES_GCM_IV_SIZE + AES_GCM_COMMITMENT_SIZE];
+        let mut input = Vec::with_capacity(1 + iv.len());
+        input.push(COMMITMENT_LABEL);
+        input.extend_from_slice(iv);
+        self.hmac
+            .verify_mac(commitment, &input)
+            .map_err(|_| TinkError::new("AesGcmCommitting: key commitment mismatch"))?;
+
+        let cipher = self.new_cipher(iv)?;
+        cipher.decrypt_with_iv(iv, &ct[AES_GCM_IV_SIZE + AES_GCM_COMMITMENT_SIZE..], aad)
+    }
+}
@@ -16,6 +16,7 @@
 
 //! AES-GCM-SIV based implementation of the [`tink_core::Aead`] trait.
 
+use aes::Aes192;
 use aes_gcm_siv::{
     aead::{consts::U12, generic_array::GenericArray, Aead, Payload},
     KeyInit,
@@ -27,9 +28,13 @@ pub const AES_GCM_SIV_NONCE_SIZE: usize = 12;
 /// The only tag size that this implementation supports.
 pub const AES_GCM_SIV_TAG_SIZE: usize = 16;
 
+/// AES-GCM-SIV with a 192-bit key, which the `aes-gcm-siv` crate does not provide an alias for.
+type Aes192GcmSiv = aes_gcm_siv::AesGcmSiv<Aes192>;
+
 #[derive(Clone)]
 enum AesGcmSivVariant {
     Aes128(Box<aes_gcm_siv::Aes128GcmSiv>),
+    Aes192(Box<Aes192GcmSiv>),
     Aes256(Box<aes_gcm_siv::Aes256GcmSiv>),
 }
 
@@ -41,17 +46,25 @@ pub struct AesGcmSiv {
 
 impl AesGcmSiv {
     /// Return an [`AesGcmSiv`] instance.
-    /// The key argument should be the AES key, either 16 or 32 bytes to select
-    /// AES-128 or AES-256.
+    /// The key argument should be the AES key, either 16, 24 or 32 bytes to select
+    /// AES-128, AES-192 or AES-256.
     pub fn new(key: &[u8]) -> Result<AesGcmSiv, TinkError> {
         let key = match key.len() {
             16 => AesGcmSivVariant::Aes128(Box::new(aes_gcm_siv::Aes128GcmSiv::new(
-                GenericArray::from_slice(key),
+                GenericArray::from_slice(key), // safe: length matched
             ))),
+            24 => {
+                // safe: length matched
+                AesGcmSivVariant::Aes192(Box::new(Aes192GcmSiv::new(GenericArray::from_slice(key))))
+            }
             32 => AesGcmSivVariant::Aes256(Box::new(aes_gcm_siv::Aes256GcmSiv::new(
-                GenericArray::from_slice(key),
+                GenericArray::from_slice(key), // safe: length matched
             ))),
-            l => return Err(format!("AesGcmSiv: invalid AES key size {} (want 16, 32)", l).into()),
+            l => {
+                return Err(
+                    format!("AesGcmSiv: invalid AES key size {} (want 16, 24, 32)", l).into(),
+                )
+            }
         };
         Ok(AesGcmSiv { key })
     }
@@ -74,6 +87,7 @@ impl tink_core::Aead for AesGcmSiv {
         let payload = Payload { msg: pt, aad };
         let ct = match &self.key {
             AesGcmSivVariant::Aes128(key) => key.encrypt(&iv, payload),
+            AesGcmSivVariant::Aes192(key) => key.encrypt(&iv, payload),
             AesGcmSivVariant::Aes256(key) => key.encrypt(&iv, payload),
         }
         .map_err(|e| wrap_err("AesGcmSiv", e))?;
@@ -95,13 +109,14 @@ impl tink_core::Aead for AesGcmSiv {
             return Err("AesGcmSiv: additional-data too long".into());
         }
 
-        let iv = GenericArray::from_slice(&ct[..AES_GCM_SIV_NONCE_SIZE]);
+        let iv = GenericArray::from_slice(&ct[..AES_GCM_SIV_NONCE_SIZE]); // safe: length checked
         let payload = Payload {
             msg: &ct[AES_GCM_SIV_NONCE_SIZE..],
             aad,
         };
         let pt = match &self.key {
             AesGcmSivVariant::Aes128(key) => key.decrypt(iv, payload),
+            AesGcmSivVariant::Aes192(key) => key.decrypt(iv, payload),
             AesGcmSivVariant::Aes256(key) => key.decrypt(iv, payload),
         }
         .map_err(|e| wrap_err("AesGcmSiv", e))?;
@@ -112,5 +127,5 @@ impl tink_core::Aead for AesGcmSiv {
 /// Create a new IV for encryption.
 fn new_iv() -> GenericArray<u8, U12> {
     let iv = tink_core::subtle::random::get_random_bytes(AES_GCM_SIV_NONCE_SIZE);
-    *GenericArray::<u8, U12>::from_slice(&iv)
+    *GenericArray::<u8, U12>::from_slice(&iv) // safe: AES_GCM_SIV_NONCE_SIZE bytes
 }
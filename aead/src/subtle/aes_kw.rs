@@ -0,0 +1,246 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! AES Key Wrap (RFC 3394) and AES Key Wrap with Padding (RFC 5649).
+
+use aes::{
+    cipher::{generic_array::GenericArray, BlockDecrypt, BlockEncrypt, KeyInit},
+    Aes128, Aes192, Aes256,
+};
+use tink_core::{subtle::constant_time_compare, TinkError};
+
+/// Default initial value for AES-KW (RFC 3394 section 2.2.3.1).
+const KW_IV: [u8; 8] = [0xa6; 8];
+/// Constant part of the alternative initial value for AES-KWP (RFC 5649 section 3).
+const KWP_IV_PREFIX: [u8; 4] = [0xa6, 0x59, 0x59, 0xa6];
+/// Size of the semiblocks that the wrapping operates on.
+const SEMIBLOCK_SIZE: usize = 8;
+/// Minimum size in bytes of key material wrapped with AES-KW.
+pub const AES_KW_MIN_KEY_SIZE: usize = 16;
+/// Maximum size in bytes of key material wrapped with AES-KW or AES-KWP.  This is far larger than
+/// any symmetric key, while keeping the cost of unwrapping invalid input bounded.
+pub const AES_KW_MAX_KEY_SIZE: usize = 4096;
+
+#[derive(Clone)]
+enum AesKwVariant {
+    Aes128(Box<Aes128>),
+    Aes192(Box<Aes192>),
+    Aes256(Box<Aes256>),
+}
+
+impl AesKwVariant {
+    fn new(kek: &[u8]) -> Result<Self, TinkError> {
+        Ok(match kek.len() {
+            16 => AesKwVariant::Aes128(Box::new(Aes128::new(GenericArray::from_slice(kek)))), // safe: length matched
+            24 => AesKwVariant::Aes192(Box::new(Aes192::new(GenericArray::from_slice(kek)))), // safe: length matched
+            32 => AesKwVariant::Aes256(Box::new(Aes256::new(GenericArray::from_slice(kek)))), // safe: length matched
+            l => return Err(format!("AesKw: invalid KEK size {}", l).into()),
+        })
+    }
+
+    fn encrypt_block(&self, block: &mut [u8; 16]) {
+        let block = GenericArray::from_mut_slice(block);
+        match self {
+            AesKwVariant::Aes128(c) => c.encrypt_block(block),
+            AesKwVariant::Aes192(c) => c.encrypt_block(block),
+            AesKwVariant::Aes256(c) => c.encrypt_block(block),
+        }
+    }
+
+    fn decrypt_block(&self, block: &mut [u8; 16]) {
+        let block = GenericArray::from_mut_slice(block);
+        match self {
+            AesKwVariant::Aes128(c) => c.decrypt_block(block),
+            AesKwVariant::Aes192(c) => c.decrypt_block(block),
+            AesKwVariant::Aes256(c) => c.decrypt_block(block),
+        }
+    }
+
+    /// The wrapping function W of RFC 3394 section 2.2.1, for initial value `iv` and plaintext
+    /// of at least two semiblocks.
+    fn wrap(&self, iv: [u8; 8], plaintext: &[u8]) -> Vec<u8> {
+        let n = plaintext.len() / SEMIBLOCK_SIZE;
+        let mut out = Vec::with_capacity(plaintext.len() + SEMIBLOCK_SIZE);
+        out.extend_from_slice(&iv);
+        out.extend_from_slice(plaintext);
+        let mut block = [0u8; 16];
+        block[..8].copy_from_slice(&iv);
+        for j in 0..6 {
+            for i in 1..=n {
+                let r = &mut out[i * SEMIBLOCK_SIZE..(i + 1) * SEMIBLOCK_SIZE];
+                block[8..].copy_from_slice(r);
+                self.encrypt_block(&mut block);
+                r.copy_from_slice(&block[8..]);
+                let t = ((n * j + i) as u64).to_be_bytes();
+                for (a, t) in block[..8].iter_mut().zip(t.iter()) {
+                    *a ^= t;
+                }
+            }
+        }
+        out[..8].copy_from_slice(&block[..8]);
+        out
+    }
+
+    /// The unwrapping function W^-1 of RFC 3394 section 2.2.2, returning the recovered initial
+    /// value and plaintext.  The caller must check the initial value.
+    fn unwrap(&self, ciphertext: &[u8]) -> ([u8; 8], Vec<u8>) {
+        let n = ciphertext.len() / SEMIBLOCK_SIZE - 1;
+        let mut out = ciphertext[SEMIBLOCK_SIZE..].to_vec();
+        let mut block = [0u8; 16];
+        block[..8].copy_from_slice(&ciphertext[..8]);
+        for j in (0..6).rev() {
+            for i in (1..=n).rev() {
+                let t = ((n * j + i) as u64).to_be_bytes();
+                for (a, t) in block[..8].iter_mut().zip(t.iter()) {
+                    *a ^= t;
+                }
+                let r = &mut out[(i - 1) * SEMIBLOCK_SIZE..i * SEMIBLOCK_SIZE];
+                block[8..].copy_from_slice(r);
+                self.decrypt_block(&mut block);
+                r.copy_from_slice(&block[8..]);
+            }
+        }
+        let mut iv = [0u8; 8];
+        iv.copy_from_slice(&block[..8]);
+        (iv, out)
+    }
+}
+
+/// `AesKw` implements AES Key Wrap as specified in RFC 3394 (also NIST SP 800-38F "KW"), as used
+/// by HSMs and PKCS#11 `CKM_AES_KEY_WRAP` to exchange key material.  The key material must be a
+/// multiple of 8 bytes, between [`AES_KW_MIN_KEY_SIZE`] and [`AES_KW_MAX_KEY_SIZE`] bytes long;
+/// use [`AesKwp`] for other sizes.
+///
+/// Key wrapping is deterministic, and is only suitable for wrapping uniformly random key
+/// material.
+#[derive(Clone)]
+pub struct AesKw {
+    kek: AesKwVariant,
+}
+
+impl AesKw {
+    /// Return an [`AesKw`] instance for a 16, 24 or 32 byte key-encryption key.
+    pub fn new(kek: &[u8]) -> Result<AesKw, TinkError> {
+        Ok(AesKw {
+            kek: AesKwVariant::new(kek)?,
+        })
+    }
+}
+
+impl tink_core::KeyWrap for AesKw {
+    fn wrap_key(&self, key: &[u8]) -> Result<Vec<u8>, TinkError> {
+        if key.len() < AES_KW_MIN_KEY_SIZE
+            || key.len() > AES_KW_MAX_KEY_SIZE
+            || key.len() % SEMIBLOCK_SIZE != 0
+        {
+            return Err(format!("AesKw: invalid key size {}", key.len()).into());
+        }
+        Ok(self.kek.wrap(KW_IV, key))
+    }
+
+    fn unwrap_key(&self, wrapped: &[u8]) -> Result<Vec<u8>, TinkError> {
+        if wrapped.len() < AES_KW_MIN_KEY_SIZE + SEMIBLOCK_SIZE
+            || wrapped.len() > AES_KW_MAX_KEY_SIZE + SEMIBLOCK_SIZE
+            || wrapped.len() % SEMIBLOCK_SIZE != 0
+        {
+            return Err(format!("AesKw: invalid wrapped key size {}", wrapped.len()).into());
+        }
+        let (iv, key) = self.kek.unwrap(wrapped);
+        if !constant_time_compare(&iv, &KW_IV) {
+            return Err("AesKw: integrity check failed".into());
+        }
+        Ok(key)
+    }
+}
+
+/// `AesKwp` implements AES Key Wrap with Padding as specified in RFC 5649 (also NIST SP 800-38F
+/// "KWP", and PKCS#11 `CKM_AES_KEY_WRAP_KWP`), which wraps key material of any size from 1 to
+/// [`AES_KW_MAX_KEY_SIZE`] bytes.
+///
+/// Key wrapping is deterministic, and is only suitable for wrapping uniformly random key
+/// material.
+#[derive(Clone)]
+pub struct AesKwp {
+    kek: AesKwVariant,
+}
+
+impl AesKwp {
+    /// Return an [`AesKwp`] instance for a 16, 24 or 32 byte key-encryption key.
+    pub fn new(kek: &[u8]) -> Result<AesKwp, TinkError> {
+        Ok(AesKwp {
+            kek: AesKwVariant::new(kek)?,
+        })
+    }
+}
+
+impl tink_core::KeyWrap for AesKwp {
+    fn wrap_key(&self, key: &[u8]) -> Result<Vec<u8>, TinkError> {
+        if key.is_empty() || key.len() > AES_KW_MAX_KEY_SIZE {
+            return Err(format!("AesKwp: invalid key size {}", key.len()).into());
+        }
+        let mut iv = [0u8; 8];
+        iv[..4].copy_from_slice(&KWP_IV_PREFIX);
+        iv[4..].copy_from_slice(&(key.len() as u32).to_be_bytes());
+        let mut padded = key.to_vec();
+        padded.resize(
+            (key.len() + SEMIBLOCK_SIZE - 1) / SEMIBLOCK_SIZE * SEMIBLOCK_SIZE,
+            0,
+        );
+        if padded.len() == SEMIBLOCK_SIZE {
+            // A single semiblock is encrypted as one AES block (RFC 5649 section 4.1).
+            let mut block = [0u8; 16];
+            block[..8].copy_from_slice(&iv);
+            block[8..].copy_from_slice(&padded);
+            self.kek.encrypt_block(&mut block);
+            Ok(block.to_vec())
+        } else {
+            Ok(self.kek.wrap(iv, &padded))
+        }
+    }
+
+    fn unwrap_key(&self, wrapped: &[u8]) -> Result<Vec<u8>, TinkError> {
+        if wrapped.len() < 2 * SEMIBLOCK_SIZE
+            || wrapped.len() > AES_KW_MAX_KEY_SIZE + 2 * SEMIBLOCK_SIZE
+            || wrapped.len() % SEMIBLOCK_SIZE != 0
+        {
+            return Err(format!("AesKwp: invalid wrapped key size {}", wrapped.len()).into());
+        }
+        let (iv, mut key) = if wrapped.len() == 2 * SEMIBLOCK_SIZE {
+            let mut block = [0u8; 16];
+            block.copy_from_slice(wrapped);
+            self.kek.decrypt_block(&mut block);
+            let mut iv = [0u8; 8];
+            iv.copy_from_slice(&block[..8]);
+            (iv, block[8..].to_vec())
+        } else {
+            self.kek.unwrap(wrapped)
+        };
+
+        // Check the constant prefix, that the message length indicator is consistent with the
+        // length, and that the padding is zero, without revealing which check failed.
+        let mut ok = constant_time_compare(&iv[..4], &KWP_IV_PREFIX);
+        let mli = u32::from_be_bytes([iv[4], iv[5], iv[6], iv[7]]) as usize;
+        let padded_len = key.len();
+        ok &= mli <= padded_len && mli + SEMIBLOCK_SIZE > padded_len;
+        let padding = &key[std::cmp::min(mli, padded_len)..];
+        ok &= padding.iter().fold(0u8, |acc, b| acc | b) == 0;
+        if !ok {
+            return Err("AesKwp: integrity check failed".into());
+        }
+        key.truncate(mli);
+        Ok(key)
+    }
+}
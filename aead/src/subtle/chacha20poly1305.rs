@@ -16,10 +16,12 @@
 
 //! ChaCha20 Poly1305 implementation of AEAD.
 
+use crate::subtle::{chacha_open, chacha_seal};
 use chacha20poly1305::{
     aead::{Aead, Payload},
     KeyInit,
 };
+use std::io::Read;
 use tink_core::{utils::wrap_err, TinkError};
 
 /// Size of a ChaCh20 key in bytes.
@@ -44,7 +46,7 @@ impl ChaCha20Poly1305 {
         }
 
         Ok(ChaCha20Poly1305 {
-            key: chacha20poly1305::Key::clone_from_slice(key),
+            key: chacha20poly1305::Key::clone_from_slice(key), // safe: length checked
         })
     }
 }
@@ -76,7 +78,7 @@ impl tink_core::Aead for ChaCha20Poly1305 {
         }
 
         let cipher = chacha20poly1305::ChaCha20Poly1305::new(&self.key);
-        let n = chacha20poly1305::Nonce::from_slice(&ct[..CHA_CHA20_NONCE_SIZE]);
+        let n = chacha20poly1305::Nonce::from_slice(&ct[..CHA_CHA20_NONCE_SIZE]); // safe: length checked
         cipher
             .decrypt(
                 n,
@@ -87,10 +89,36 @@ impl tink_core::Aead for ChaCha20Poly1305 {
             )
             .map_err(|e| wrap_err("ChaCha20Poly1305", e))
     }
+
+    /// Encrypt `pt` with the associated data read from `aad`, producing the same ciphertext
+    /// format as [`encrypt`](tink_core::Aead::encrypt).
+    fn encrypt_with_aad_reader(&self, pt: &[u8], aad: &mut dyn Read) -> Result<Vec<u8>, TinkError> {
+        if pt.len() > (isize::MAX as usize) - CHA_CHA20_NONCE_SIZE - POLY1305_TAG_SIZE {
+            return Err("ChaCha20Poly1305: plaintext too long".into());
+        }
+        let n = new_nonce();
+        let ct = chacha_seal::<chacha20::ChaCha20>(&self.key, &n, pt, aad)
+            .map_err(|e| wrap_err("ChaCha20Poly1305", e))?;
+
+        let mut ret = Vec::with_capacity(n.len() + ct.len());
+        ret.extend_from_slice(&n);
+        ret.extend_from_slice(&ct);
+        Ok(ret)
+    }
+
+    /// Decrypt `ct` with the associated data read from `aad`.
+    fn decrypt_with_aad_reader(&self, ct: &[u8], aad: &mut dyn Read) -> Result<Vec<u8>, TinkError> {
+        if ct.len() < CHA_CHA20_NONCE_SIZE + POLY1305_TAG_SIZE {
+            return Err("ChaCha20Poly1305: ciphertext too short".into());
+        }
+        let (n, ct) = ct.split_at(CHA_CHA20_NONCE_SIZE);
+        chacha_open::<chacha20::ChaCha20>(&self.key, n, ct, aad)
+            .map_err(|e| wrap_err("ChaCha20Poly1305", e))
+    }
 }
 
 /// Create a new nonce for encryption.
 fn new_nonce() -> chacha20poly1305::Nonce {
     let iv = tink_core::subtle::random::get_random_bytes(CHA_CHA20_NONCE_SIZE);
-    *chacha20poly1305::Nonce::from_slice(&iv)
+    *chacha20poly1305::Nonce::from_slice(&iv) // safe: CHA_CHA20_NONCE_SIZE bytes
 }
@@ -23,6 +23,27 @@ use tink_core::{utils::wrap_err, TinkError};
 /// and additional authenticated data (aad). The MAC is computed over (aad ||
 /// ciphertext || size of aad). This implementation is based on
 /// <http://tools.ietf.org/html/draft-mcgrew-aead-aes-cbc-hmac-sha2-05>.
+///
+/// It can combine any [`IndCpaCipher`] with any [`tink_core::Mac`], for example to build the
+/// AES-CTR-HMAC AEAD from its parts:
+///
+/// ```
+/// use tink_aead::subtle::{AesCtr, EncryptThenAuthenticate};
+/// use tink_core::{subtle::random::get_random_bytes, Aead};
+///
+/// let ctr = AesCtr::new(&get_random_bytes(16), 16).unwrap();
+/// let mac = tink_mac::subtle::Hmac::new(
+///     tink_proto::HashType::Sha256,
+///     &get_random_bytes(32),
+///     16,
+/// )
+/// .unwrap();
+/// let aead = EncryptThenAuthenticate::new(Box::new(ctr), Box::new(mac), 16).unwrap();
+/// let ct = aead.encrypt(b"plaintext", b"associated data").unwrap();
+/// assert_eq!(aead.decrypt(&ct, b"associated data").unwrap(), b"plaintext");
+/// ```
+///
+/// The encryption and MAC keys must be independent.
 pub struct EncryptThenAuthenticate {
     ind_cpa_cipher: Box<dyn IndCpaCipher>,
     mac: Box<dyn tink_core::Mac>,
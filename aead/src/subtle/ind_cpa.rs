@@ -16,16 +16,20 @@
 
 //! Trait definition for `IndCpaCipher`.
 
-// `IndCpaCipher` provides an interface for symmetric key ciphers that are
-// indistinguishable against chosen-plaintext attacks. Said primitives do not
-// provide authentication, thus should not be used directly, but only to
-// construct safer primitives such as AEAD.
+/// `IndCpaCipher` provides an interface for symmetric key ciphers that are indistinguishable
+/// against chosen-plaintext attacks, such as [`AesCtr`](super::AesCtr).
+///
+/// Such ciphers do not provide authentication, so should not be used directly, but only to
+/// construct safer primitives such as [`EncryptThenAuthenticate`](super::EncryptThenAuthenticate).
+/// Implementations are responsible for their own IV (nonce) handling: the ciphertext must carry
+/// whatever is needed to decrypt it, and [`encrypt`](IndCpaCipher::encrypt) must not repeat an IV
+/// under the same key.
 pub trait IndCpaCipher: IndCpaCipherBoxClone {
-    // Encrypt plaintext. The resulting ciphertext is indistinguishable under
-    // chosen-plaintext attack. However, it does not have integrity protection.
+    /// Encrypt plaintext. The resulting ciphertext is indistinguishable under chosen-plaintext
+    /// attack. However, it does not have integrity protection.
     fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, tink_core::TinkError>;
 
-    // Decrypt ciphertext and returns the resulting plaintext.
+    /// Decrypt ciphertext and return the resulting plaintext.
     fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, tink_core::TinkError>;
 }
 
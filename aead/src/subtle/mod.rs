@@ -16,14 +16,20 @@
 
 //! Provides subtle implementations of the `tink_core::Aead` primitive.
 
+mod aad_reader;
+pub use self::aad_reader::*;
 mod aead;
 pub use self::aead::*;
 mod aes_ctr;
 pub use self::aes_ctr::*;
 mod aes_gcm;
 pub use self::aes_gcm::*;
+mod aes_gcm_committing;
+pub use self::aes_gcm_committing::*;
 mod aes_gcm_siv;
 pub use self::aes_gcm_siv::*;
+mod aes_kw;
+pub use self::aes_kw::*;
 mod chacha20poly1305;
 pub use self::chacha20poly1305::*;
 mod encrypt_then_authenticate;
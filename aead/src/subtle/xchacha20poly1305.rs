@@ -16,10 +16,12 @@
 
 //! XChaCha20 Poly1305 implementation of AEAD.
 
+use crate::subtle::{chacha_open, chacha_seal};
 use chacha20poly1305::{
     aead::{Aead, Payload},
     KeyInit,
 };
+use std::io::Read;
 use tink_core::{utils::wrap_err, TinkError};
 
 /// Size of an XChaCh20 key in bytes.
@@ -44,7 +46,7 @@ impl XChaCha20Poly1305 {
         }
 
         Ok(XChaCha20Poly1305 {
-            key: chacha20poly1305::Key::clone_from_slice(key),
+            key: chacha20poly1305::Key::clone_from_slice(key), // safe: length checked
         })
     }
 }
@@ -76,7 +78,7 @@ impl tink_core::Aead for XChaCha20Poly1305 {
         }
 
         let cipher = chacha20poly1305::XChaCha20Poly1305::new(&self.key);
-        let n = chacha20poly1305::XNonce::from_slice(&ct[..X_CHA_CHA20_NONCE_SIZE]);
+        let n = chacha20poly1305::XNonce::from_slice(&ct[..X_CHA_CHA20_NONCE_SIZE]); // safe: length checked
         cipher
             .decrypt(
                 n,
@@ -87,10 +89,36 @@ impl tink_core::Aead for XChaCha20Poly1305 {
             )
             .map_err(|e| wrap_err("XChaCha20Poly1305", e))
     }
+
+    /// Encrypt `pt` with the associated data read from `aad`, producing the same ciphertext
+    /// format as [`encrypt`](tink_core::Aead::encrypt).
+    fn encrypt_with_aad_reader(&self, pt: &[u8], aad: &mut dyn Read) -> Result<Vec<u8>, TinkError> {
+        if pt.len() > (isize::MAX as usize) - X_CHA_CHA20_NONCE_SIZE - POLY1305_TAG_SIZE {
+            return Err("XChaCha20Poly1305: plaintext too long".into());
+        }
+        let n = new_nonce();
+        let ct = chacha_seal::<chacha20::XChaCha20>(&self.key, &n, pt, aad)
+            .map_err(|e| wrap_err("XChaCha20Poly1305", e))?;
+
+        let mut ret = Vec::with_capacity(n.len() + ct.len());
+        ret.extend_from_slice(&n);
+        ret.extend_from_slice(&ct);
+        Ok(ret)
+    }
+
+    /// Decrypt `ct` with the associated data read from `aad`.
+    fn decrypt_with_aad_reader(&self, ct: &[u8], aad: &mut dyn Read) -> Result<Vec<u8>, TinkError> {
+        if ct.len() < X_CHA_CHA20_NONCE_SIZE + POLY1305_TAG_SIZE {
+            return Err("XChaCha20Poly1305: ciphertext too short".into());
+        }
+        let (n, ct) = ct.split_at(X_CHA_CHA20_NONCE_SIZE);
+        chacha_open::<chacha20::XChaCha20>(&self.key, n, ct, aad)
+            .map_err(|e| wrap_err("XChaCha20Poly1305", e))
+    }
 }
 
 /// Create a new nonce for encryption.
 fn new_nonce() -> chacha20poly1305::XNonce {
     let iv = tink_core::subtle::random::get_random_bytes(X_CHA_CHA20_NONCE_SIZE);
-    *chacha20poly1305::XNonce::from_slice(&iv)
+    *chacha20poly1305::XNonce::from_slice(&iv) // safe: X_CHA_CHA20_NONCE_SIZE bytes
 }
@@ -0,0 +1,140 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! This crate registers the key managers of all Tink primitive crates in a single call.
+
+#![deny(broken_intra_doc_links)]
+
+use tink_core::TinkError;
+
+/// The [upstream Tink](https://github.com/google/tink) version that this Rust
+/// port is based on.
+pub const UPSTREAM_VERSION: &str = "1.6.0";
+
+/// Description of a primitive crate: its name, initialization function and the type URLs that it
+/// registers key managers for.
+struct CrateInit {
+    name: &'static str,
+    init: fn(),
+    type_urls: &'static [&'static str],
+}
+
+const CRATES: &[CrateInit] = &[
+    CrateInit {
+        name: "tink_aead",
+        init: tink_aead::init,
+        type_urls: &[
+            tink_aead::AES_CTR_HMAC_AEAD_TYPE_URL,
+            tink_aead::AES_GCM_TYPE_URL,
+            tink_aead::AES_GCM_COMMITTING_TYPE_URL,
+            tink_aead::AES_GCM_SIV_TYPE_URL,
+            tink_aead::CHA_CHA20_POLY1305_TYPE_URL,
+            tink_aead::X_CHA_CHA20_POLY1305_TYPE_URL,
+            tink_aead::KMS_ENVELOPE_AEAD_TYPE_URL,
+        ],
+    },
+    CrateInit {
+        name: "tink_daead",
+        init: tink_daead::init,
+        type_urls: &[tink_daead::AES_SIV_TYPE_URL],
+    },
+    CrateInit {
+        name: "tink_mac",
+        init: tink_mac::init,
+        type_urls: &[tink_mac::HMAC_TYPE_URL, tink_mac::CMAC_TYPE_URL],
+    },
+    CrateInit {
+        name: "tink_prf",
+        init: tink_prf::init,
+        type_urls: &[
+            tink_prf::HMAC_PRF_TYPE_URL,
+            tink_prf::HKDF_PRF_TYPE_URL,
+            tink_prf::AES_CMAC_PRF_TYPE_URL,
+        ],
+    },
+    CrateInit {
+        name: "tink_signature",
+        init: tink_signature::init,
+        type_urls: &[
+            tink_signature::ECDSA_SIGNER_TYPE_URL,
+            tink_signature::ECDSA_VERIFIER_TYPE_URL,
+            tink_signature::ED25519_SIGNER_TYPE_URL,
+            tink_signature::ED25519_VERIFIER_TYPE_URL,
+        ],
+    },
+    CrateInit {
+        name: "tink_streaming_aead",
+        init: tink_streaming_aead::init,
+        type_urls: &[
+            tink_streaming_aead::AES_CTR_HMAC_TYPE_URL,
+            tink_streaming_aead::AES_GCM_HKDF_TYPE_URL,
+        ],
+    },
+    CrateInit {
+        name: "tink_hybrid",
+        init: tink_hybrid::init,
+        type_urls: &[
+            tink_hybrid::ECIES_AEAD_HKDF_PRIVATE_KEY_TYPE_URL,
+            tink_hybrid::ECIES_AEAD_HKDF_PUBLIC_KEY_TYPE_URL,
+        ],
+    },
+];
+
+/// Return the message carried by a panic payload.
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown error".to_string()
+    }
+}
+
+/// Initialize all of the Tink primitive crates, registering their key managers and key template
+/// generators.
+///
+/// Unlike the per-crate `init()` functions, conflicts are reported as errors: if a key manager
+/// for one of the Tink key types has already been registered by other code, the error identifies
+/// the crate whose initialization failed.  After initialization, every expected key type is
+/// checked to be available from the registry.
+///
+/// Calling this function more than once is harmless.
+pub fn init_all() -> Result<(), TinkError> {
+    for krate in CRATES {
+        std::panic::catch_unwind(krate.init).map_err(|e| {
+            TinkError::new(&format!(
+                "tink_all: {}::init() failed: {}",
+                krate.name,
+                panic_message(&*e)
+            ))
+        })?;
+        for type_url in krate.type_urls {
+            tink_core::registry::get_key_manager(type_url).map_err(|_| {
+                TinkError::new(&format!(
+                    "tink_all: {}::init() did not register key manager for {}",
+                    krate.name, type_url
+                ))
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Initialize all of the Tink primitive crates, panicking on failure.  See [`init_all`].
+pub fn init() {
+    init_all().expect("tink_all::init() failed"); // safe: init
+}
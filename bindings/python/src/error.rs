@@ -0,0 +1,42 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Python exception type.
+
+use pyo3::{create_exception, exceptions::PyException, PyErr};
+
+create_exception!(
+    tink_rust,
+    TinkError,
+    PyException,
+    "Error raised by Tink operations."
+);
+
+/// Convert a Tink error into a Python exception.
+pub(crate) fn to_py_err(e: tink_core::TinkError) -> PyErr {
+    TinkError::new_err(e.to_string())
+}
+
+/// Run `f` with the GIL released, converting any Tink error into a Python exception.
+pub(crate) fn allow_threads<T, F>(py: pyo3::Python, f: F) -> pyo3::PyResult<T>
+where
+    T: Send,
+    F: Send + FnOnce() -> Result<T, tink_core::TinkError>,
+{
+    // Tink errors may hold non-`Send` sources, so only the message leaves the closure.
+    py.allow_threads(|| f().map_err(|e| e.to_string()))
+        .map_err(TinkError::new_err)
+}
@@ -0,0 +1,97 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Adapters between Python binary file-like objects and Rust I/O traits.
+
+use pyo3::{prelude::*, types::PyBytes};
+use std::io;
+
+/// Convert a Python exception into an I/O error.
+fn to_io_err(e: PyErr) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, e.to_string())
+}
+
+/// `PyWriter` implements [`io::Write`] by calling the `write` and `flush` methods of a Python
+/// object.
+pub(crate) struct PyWriter {
+    file: PyObject,
+}
+
+impl PyWriter {
+    pub(crate) fn new(file: PyObject) -> Self {
+        PyWriter { file }
+    }
+}
+
+impl io::Write for PyWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Python::with_gil(|py| {
+            let written = self
+                .file
+                .call_method1(py, "write", (PyBytes::new(py, buf),))
+                .map_err(to_io_err)?;
+            // Raw streams return the number of bytes written (or `None` if they would block);
+            // buffered streams write everything.
+            if written.is_none(py) {
+                return Err(io::ErrorKind::WouldBlock.into());
+            }
+            written.extract::<usize>(py).map_err(to_io_err)
+        })
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Python::with_gil(|py| {
+            if self.file.as_ref(py).hasattr("flush").map_err(to_io_err)? {
+                self.file.call_method0(py, "flush").map_err(to_io_err)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// `PyReader` implements [`io::Read`] by calling the `read` method of a Python object.
+pub(crate) struct PyReader {
+    file: PyObject,
+}
+
+impl PyReader {
+    pub(crate) fn new(file: PyObject) -> Self {
+        PyReader { file }
+    }
+}
+
+impl io::Read for PyReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        Python::with_gil(|py| {
+            let data = self
+                .file
+                .call_method1(py, "read", (buf.len(),))
+                .map_err(to_io_err)?;
+            if data.is_none(py) {
+                return Err(io::ErrorKind::WouldBlock.into());
+            }
+            let data: &[u8] = data.extract(py).map_err(to_io_err)?;
+            if data.len() > buf.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "read returned too much data",
+                ));
+            }
+            buf[..data.len()].copy_from_slice(data);
+            Ok(data.len())
+        })
+    }
+}
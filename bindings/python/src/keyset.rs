@@ -0,0 +1,107 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Keyset handle class.
+
+use crate::to_py_err;
+use pyo3::prelude::*;
+use tink_core::{
+    keyset::{insecure, BinaryReader, Handle, JsonReader, Reader},
+    TinkError,
+};
+
+/// `KeysetHandle` provides access to a keyset, without exposing its key material.
+#[pyclass(module = "tink_rust", frozen)]
+#[derive(Clone)]
+pub struct KeysetHandle {
+    pub(crate) handle: Handle,
+}
+
+/// Read a keyset that has been encrypted with the KMS key identified by `kms_key_uri`, using a
+/// registered KMS client.
+fn read_encrypted<T: Reader>(
+    reader: &mut T,
+    kms_key_uri: &str,
+    associated_data: &[u8],
+) -> Result<Handle, TinkError> {
+    let main_key = tink_core::registry::get_kms_client(kms_key_uri)?.get_aead(kms_key_uri)?;
+    Handle::read_with_associated_data(reader, main_key, associated_data)
+}
+
+#[pymethods]
+impl KeysetHandle {
+    /// Generate a keyset holding a single new key, from the key template registered under
+    /// `template_name` (for example `"AES256_GCM"`).
+    #[staticmethod]
+    fn generate_new(template_name: &str) -> PyResult<Self> {
+        let generator = tink_core::registry::get_template_generator(template_name)
+            .ok_or_else(|| to_py_err(format!("unknown key template {}", template_name).into()))?;
+        let handle = Handle::new(&generator()).map_err(to_py_err)?;
+        Ok(KeysetHandle { handle })
+    }
+
+    /// Read a cleartext keyset in JSON format.
+    #[staticmethod]
+    fn read_cleartext_json(keyset: &str) -> PyResult<Self> {
+        let handle = insecure::read(&mut JsonReader::new(keyset.as_bytes())).map_err(to_py_err)?;
+        Ok(KeysetHandle { handle })
+    }
+
+    /// Read a cleartext keyset in binary format.
+    #[staticmethod]
+    fn read_cleartext_binary(keyset: &[u8]) -> PyResult<Self> {
+        let handle = insecure::read(&mut BinaryReader::new(keyset)).map_err(to_py_err)?;
+        Ok(KeysetHandle { handle })
+    }
+
+    /// Read a keyset in JSON format that has been encrypted with the KMS key identified by
+    /// `kms_key_uri`.
+    #[staticmethod]
+    #[pyo3(signature = (keyset, kms_key_uri, associated_data = b"".as_slice()))]
+    fn read_encrypted_json(
+        keyset: &str,
+        kms_key_uri: &str,
+        associated_data: &[u8],
+    ) -> PyResult<Self> {
+        let handle = read_encrypted(
+            &mut JsonReader::new(keyset.as_bytes()),
+            kms_key_uri,
+            associated_data,
+        )
+        .map_err(to_py_err)?;
+        Ok(KeysetHandle { handle })
+    }
+
+    /// Read a keyset in binary format that has been encrypted with the KMS key identified by
+    /// `kms_key_uri`.
+    #[staticmethod]
+    #[pyo3(signature = (keyset, kms_key_uri, associated_data = b"".as_slice()))]
+    fn read_encrypted_binary(
+        keyset: &[u8],
+        kms_key_uri: &str,
+        associated_data: &[u8],
+    ) -> PyResult<Self> {
+        let handle = read_encrypted(&mut BinaryReader::new(keyset), kms_key_uri, associated_data)
+            .map_err(to_py_err)?;
+        Ok(KeysetHandle { handle })
+    }
+
+    /// Return the ID of the primary key of the keyset.
+    #[getter]
+    fn primary_key_id(&self) -> u32 {
+        self.handle.keyset_info().primary_key_id
+    }
+}
@@ -0,0 +1,101 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Provide a [PyO3](https://pyo3.rs) Python module (`tink_rust`) for reading keysets and using
+//! the Aead, DeterministicAead, Mac and StreamingAead primitives.
+//!
+//! Tink primitives are not required to be `Send`, so the Python classes for primitives hold a
+//! [`tink_core::keyset::Handle`] and create the underlying primitive for each operation (which
+//! allows the GIL to be released while it runs).
+
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#![deny(broken_intra_doc_links)]
+
+use pyo3::prelude::*;
+
+mod error;
+pub use error::*;
+mod file_io;
+mod keyset;
+pub use keyset::*;
+mod primitives;
+pub use primitives::*;
+mod streaming;
+pub use streaming::*;
+
+/// Initialize the primitive crates exposed by the module, registering their key managers and
+/// key templates.
+fn init() {
+    tink_aead::init();
+    tink_daead::init();
+    tink_mac::init();
+    tink_streaming_aead::init();
+}
+
+/// Register a KMS client for AWS KMS keys with the given URI prefix, optionally using the
+/// credentials in the given file (otherwise the default credential chain is used).
+#[cfg(feature = "awskms")]
+#[cfg_attr(docsrs, doc(cfg(feature = "awskms")))]
+#[pyfunction]
+#[pyo3(signature = (uri_prefix, credentials_path = None))]
+fn register_aws_kms_client(uri_prefix: &str, credentials_path: Option<&str>) -> PyResult<()> {
+    let client = match credentials_path {
+        Some(path) => {
+            tink_awskms::AwsClient::new_with_credentials(uri_prefix, std::path::Path::new(path))
+        }
+        None => tink_awskms::AwsClient::new(uri_prefix),
+    }
+    .map_err(to_py_err)?;
+    tink_core::registry::register_kms_client(client);
+    Ok(())
+}
+
+/// Register a KMS client for GCP Cloud KMS keys with the given URI prefix, optionally using the
+/// credentials in the given file (otherwise the default credentials are used).
+#[cfg(feature = "gcpkms")]
+#[cfg_attr(docsrs, doc(cfg(feature = "gcpkms")))]
+#[pyfunction]
+#[pyo3(signature = (uri_prefix, credentials_path = None))]
+fn register_gcp_kms_client(uri_prefix: &str, credentials_path: Option<&str>) -> PyResult<()> {
+    let client = match credentials_path {
+        Some(path) => {
+            tink_gcpkms::GcpClient::new_with_credentials(uri_prefix, std::path::Path::new(path))
+        }
+        None => tink_gcpkms::GcpClient::new(uri_prefix),
+    }
+    .map_err(to_py_err)?;
+    tink_core::registry::register_kms_client(client);
+    Ok(())
+}
+
+/// The `tink_rust` Python module.
+#[pymodule]
+fn tink_rust(py: Python, m: &PyModule) -> PyResult<()> {
+    init();
+    m.add("TinkError", py.get_type::<TinkError>())?;
+    m.add_class::<KeysetHandle>()?;
+    m.add_class::<Aead>()?;
+    m.add_class::<DeterministicAead>()?;
+    m.add_class::<Mac>()?;
+    m.add_class::<StreamingAead>()?;
+    m.add_class::<EncryptingStream>()?;
+    m.add_class::<DecryptingStream>()?;
+    #[cfg(feature = "awskms")]
+    m.add_function(wrap_pyfunction!(register_aws_kms_client, m)?)?;
+    #[cfg(feature = "gcpkms")]
+    m.add_function(wrap_pyfunction!(register_gcp_kms_client, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,140 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Classes for the Aead, DeterministicAead and Mac primitives.
+
+use crate::{allow_threads, to_py_err, KeysetHandle};
+use pyo3::{prelude::*, types::PyBytes};
+use tink_core::keyset::Handle;
+
+/// `Aead` provides authenticated encryption with associated data.
+#[pyclass(module = "tink_rust", frozen)]
+pub struct Aead {
+    handle: Handle,
+}
+
+#[pymethods]
+impl Aead {
+    /// Create an `Aead` that uses the primary key of the given keyset.
+    #[new]
+    fn new(handle: &KeysetHandle) -> PyResult<Self> {
+        tink_aead::new(&handle.handle).map_err(to_py_err)?;
+        Ok(Aead {
+            handle: handle.handle.clone(),
+        })
+    }
+
+    /// Encrypt `plaintext`, authenticating `associated_data`.
+    fn encrypt<'p>(
+        &self,
+        py: Python<'p>,
+        plaintext: &[u8],
+        associated_data: &[u8],
+    ) -> PyResult<&'p PyBytes> {
+        let ct = allow_threads(py, || {
+            tink_aead::new(&self.handle)?.encrypt(plaintext, associated_data)
+        })?;
+        Ok(PyBytes::new(py, &ct))
+    }
+
+    /// Decrypt `ciphertext`, authenticating `associated_data`.
+    fn decrypt<'p>(
+        &self,
+        py: Python<'p>,
+        ciphertext: &[u8],
+        associated_data: &[u8],
+    ) -> PyResult<&'p PyBytes> {
+        let pt = allow_threads(py, || {
+            tink_aead::new(&self.handle)?.decrypt(ciphertext, associated_data)
+        })?;
+        Ok(PyBytes::new(py, &pt))
+    }
+}
+
+/// `DeterministicAead` provides deterministic authenticated encryption with associated data.
+#[pyclass(module = "tink_rust", frozen)]
+pub struct DeterministicAead {
+    handle: Handle,
+}
+
+#[pymethods]
+impl DeterministicAead {
+    /// Create a `DeterministicAead` that uses the primary key of the given keyset.
+    #[new]
+    fn new(handle: &KeysetHandle) -> PyResult<Self> {
+        tink_daead::new(&handle.handle).map_err(to_py_err)?;
+        Ok(DeterministicAead {
+            handle: handle.handle.clone(),
+        })
+    }
+
+    /// Deterministically encrypt `plaintext`, authenticating `associated_data`.
+    fn encrypt_deterministically<'p>(
+        &self,
+        py: Python<'p>,
+        plaintext: &[u8],
+        associated_data: &[u8],
+    ) -> PyResult<&'p PyBytes> {
+        let ct = allow_threads(py, || {
+            tink_daead::new(&self.handle)?.encrypt_deterministically(plaintext, associated_data)
+        })?;
+        Ok(PyBytes::new(py, &ct))
+    }
+
+    /// Deterministically decrypt `ciphertext`, authenticating `associated_data`.
+    fn decrypt_deterministically<'p>(
+        &self,
+        py: Python<'p>,
+        ciphertext: &[u8],
+        associated_data: &[u8],
+    ) -> PyResult<&'p PyBytes> {
+        let pt = allow_threads(py, || {
+            tink_daead::new(&self.handle)?.decrypt_deterministically(ciphertext, associated_data)
+        })?;
+        Ok(PyBytes::new(py, &pt))
+    }
+}
+
+/// `Mac` computes and verifies message authentication codes.
+#[pyclass(module = "tink_rust", frozen)]
+pub struct Mac {
+    handle: Handle,
+}
+
+#[pymethods]
+impl Mac {
+    /// Create a `Mac` that uses the keys of the given keyset.
+    #[new]
+    fn new(handle: &KeysetHandle) -> PyResult<Self> {
+        tink_mac::new(&handle.handle).map_err(to_py_err)?;
+        Ok(Mac {
+            handle: handle.handle.clone(),
+        })
+    }
+
+    /// Compute the MAC of `data`, using the primary key.
+    fn compute_mac<'p>(&self, py: Python<'p>, data: &[u8]) -> PyResult<&'p PyBytes> {
+        let mac = allow_threads(py, || tink_mac::new(&self.handle)?.compute_mac(data))?;
+        Ok(PyBytes::new(py, &mac))
+    }
+
+    /// Verify that `mac_value` is a valid MAC of `data`, raising `TinkError` if not.
+    fn verify_mac(&self, py: Python, mac_value: &[u8], data: &[u8]) -> PyResult<()> {
+        allow_threads(py, || {
+            tink_mac::new(&self.handle)?.verify_mac(mac_value, data)
+        })
+    }
+}
@@ -0,0 +1,220 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Classes for the StreamingAead primitive, wrapping Python binary file-like objects.
+
+use crate::{
+    file_io::{PyReader, PyWriter},
+    to_py_err, KeysetHandle, TinkError,
+};
+use pyo3::{prelude::*, types::PyBytes};
+use std::io::{Read, Write};
+use tink_core::{keyset::Handle, EncryptingWrite};
+
+/// Convert an I/O error into a Python exception.
+fn io_err(e: std::io::Error) -> PyErr {
+    TinkError::new_err(e.to_string())
+}
+
+/// `StreamingAead` provides authenticated encryption with associated data for streams of data.
+#[pyclass(module = "tink_rust", frozen)]
+pub struct StreamingAead {
+    handle: Handle,
+}
+
+#[pymethods]
+impl StreamingAead {
+    /// Create a `StreamingAead` that uses the keys of the given keyset.
+    #[new]
+    fn new(handle: &KeysetHandle) -> PyResult<Self> {
+        tink_streaming_aead::new(&handle.handle).map_err(to_py_err)?;
+        Ok(StreamingAead {
+            handle: handle.handle.clone(),
+        })
+    }
+
+    /// Return a stream that encrypts data written to it (authenticating `associated_data`), and
+    /// writes the ciphertext to `ciphertext_destination`, which must be a writable binary
+    /// file-like object.  The stream must be closed to complete the ciphertext.
+    fn new_encrypting_stream(
+        &self,
+        ciphertext_destination: PyObject,
+        associated_data: &[u8],
+    ) -> PyResult<EncryptingStream> {
+        let writer = tink_streaming_aead::new(&self.handle)
+            .and_then(|p| {
+                p.new_encrypting_writer(
+                    Box::new(PyWriter::new(ciphertext_destination)),
+                    associated_data,
+                )
+            })
+            .map_err(to_py_err)?;
+        Ok(EncryptingStream {
+            writer: Some(writer),
+        })
+    }
+
+    /// Return a stream that reads ciphertext from `ciphertext_source` (which must be a readable
+    /// binary file-like object) and returns the decrypted data, authenticating
+    /// `associated_data`.
+    fn new_decrypting_stream(
+        &self,
+        ciphertext_source: PyObject,
+        associated_data: &[u8],
+    ) -> PyResult<DecryptingStream> {
+        let reader = tink_streaming_aead::new(&self.handle)
+            .and_then(|p| {
+                p.new_decrypting_reader(Box::new(PyReader::new(ciphertext_source)), associated_data)
+            })
+            .map_err(to_py_err)?;
+        Ok(DecryptingStream {
+            reader: Some(reader),
+        })
+    }
+}
+
+/// `EncryptingStream` is a writable binary file-like object that encrypts the data written to
+/// it.
+#[pyclass(module = "tink_rust", unsendable)]
+pub struct EncryptingStream {
+    writer: Option<Box<dyn EncryptingWrite>>,
+}
+
+impl EncryptingStream {
+    fn writer(&mut self) -> PyResult<&mut Box<dyn EncryptingWrite>> {
+        self.writer
+            .as_mut()
+            .ok_or_else(|| pyo3::exceptions::PyValueError::new_err("write to closed stream"))
+    }
+}
+
+#[pymethods]
+impl EncryptingStream {
+    /// Encrypt and write `data`, returning the number of bytes written.
+    fn write(&mut self, data: &[u8]) -> PyResult<usize> {
+        self.writer()?.write_all(data).map_err(io_err)?;
+        Ok(data.len())
+    }
+
+    /// Flush any complete segments of ciphertext.
+    fn flush(&mut self) -> PyResult<()> {
+        self.writer()?.flush().map_err(io_err)
+    }
+
+    /// Complete the ciphertext.  The destination file-like object is not closed.
+    fn close(&mut self) -> PyResult<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.close().map_err(to_py_err)?;
+        }
+        Ok(())
+    }
+
+    #[getter]
+    fn closed(&self) -> bool {
+        self.writer.is_none()
+    }
+
+    fn writable(&self) -> bool {
+        true
+    }
+
+    fn readable(&self) -> bool {
+        false
+    }
+
+    fn __enter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __exit__(
+        &mut self,
+        _exc_type: PyObject,
+        _exc_value: PyObject,
+        _traceback: PyObject,
+    ) -> PyResult<bool> {
+        self.close()?;
+        Ok(false)
+    }
+}
+
+/// `DecryptingStream` is a readable binary file-like object that returns the decrypted data
+/// read from a ciphertext source.
+#[pyclass(module = "tink_rust", unsendable)]
+pub struct DecryptingStream {
+    reader: Option<Box<dyn Read>>,
+}
+
+#[pymethods]
+impl DecryptingStream {
+    /// Read and return up to `size` bytes of decrypted data, or all remaining data if `size` is
+    /// negative.  Returns empty bytes at the end of the stream.
+    #[pyo3(signature = (size = -1))]
+    fn read<'p>(&mut self, py: Python<'p>, size: isize) -> PyResult<&'p PyBytes> {
+        let reader = self
+            .reader
+            .as_mut()
+            .ok_or_else(|| pyo3::exceptions::PyValueError::new_err("read from closed stream"))?;
+        let mut data = Vec::new();
+        if size < 0 {
+            reader.read_to_end(&mut data).map_err(io_err)?;
+        } else {
+            data.resize(size as usize, 0);
+            let mut filled = 0;
+            // Keep reading until `size` bytes are available (or the stream ends), like a
+            // buffered Python stream.
+            while filled < data.len() {
+                match reader.read(&mut data[filled..]).map_err(io_err)? {
+                    0 => break,
+                    n => filled += n,
+                }
+            }
+            data.truncate(filled);
+        }
+        Ok(PyBytes::new(py, &data))
+    }
+
+    /// Close the stream.  The source file-like object is not closed.
+    fn close(&mut self) {
+        self.reader = None;
+    }
+
+    #[getter]
+    fn closed(&self) -> bool {
+        self.reader.is_none()
+    }
+
+    fn readable(&self) -> bool {
+        true
+    }
+
+    fn writable(&self) -> bool {
+        false
+    }
+
+    fn __enter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __exit__(
+        &mut self,
+        _exc_type: PyObject,
+        _exc_value: PyObject,
+        _traceback: PyObject,
+    ) -> PyResult<bool> {
+        self.close();
+        Ok(false)
+    }
+}
@@ -0,0 +1,113 @@
+# Copyright 2020 The Tink-Rust Authors
+#
+# Licensed under the Apache License, Version 2.0 (the "License");
+# you may not use this file except in compliance with the License.
+# You may obtain a copy of the License at
+#
+#      http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing, software
+# distributed under the License is distributed on an "AS IS" BASIS,
+# WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+# See the License for the specific language governing permissions and
+# limitations under the License.
+#
+#//////////////////////////////////////////////////////////////////////////////
+
+
+"""Tests for the tink_rust Python module; run with `maturin develop && pytest`."""
+
+import io
+
+import pytest
+
+import tink_rust
+
+# AES256-GCM keyset with key ID 42 and key bytes 0x00..0x1f.
+AES_GCM_KEYSET = """{
+  "primaryKeyId": 42,
+  "key": [
+    {
+      "keyData": {
+        "typeUrl": "type.googleapis.com/google.crypto.tink.AesGcmKey",
+        "keyMaterialType": "SYMMETRIC",
+        "value": "EiAAAQIDBAUGBwgJCgsMDQ4PEBESExQVFhcYGRobHB0eHw=="
+      },
+      "outputPrefixType": "TINK",
+      "keyId": 42,
+      "status": "ENABLED"
+    }
+  ]
+}"""
+
+
+def test_aead_round_trip():
+    handle = tink_rust.KeysetHandle.generate_new("AES256_GCM")
+    aead = tink_rust.Aead(handle)
+    ct = aead.encrypt(b"plaintext", b"aad")
+    assert ct != b"plaintext"
+    assert aead.decrypt(ct, b"aad") == b"plaintext"
+    with pytest.raises(tink_rust.TinkError):
+        aead.decrypt(ct, b"wrong aad")
+
+
+def test_read_cleartext_json():
+    handle = tink_rust.KeysetHandle.read_cleartext_json(AES_GCM_KEYSET)
+    assert handle.primary_key_id == 42
+    aead = tink_rust.Aead(handle)
+    ct = aead.encrypt(b"plaintext", b"")
+    # Ciphertext has a TINK output prefix: version byte then big-endian key ID.
+    assert ct[:5] == b"\x01\x00\x00\x00\x2a"
+    assert aead.decrypt(ct, b"") == b"plaintext"
+
+
+def test_read_invalid_keyset():
+    with pytest.raises(tink_rust.TinkError):
+        tink_rust.KeysetHandle.read_cleartext_json("{}")
+    with pytest.raises(tink_rust.TinkError):
+        tink_rust.KeysetHandle.generate_new("NO_SUCH_TEMPLATE")
+
+
+def test_wrong_primitive():
+    handle = tink_rust.KeysetHandle.generate_new("HMAC_SHA256_256BITTAG")
+    with pytest.raises(tink_rust.TinkError):
+        tink_rust.Aead(handle)
+
+
+def test_deterministic_aead():
+    handle = tink_rust.KeysetHandle.generate_new("AES256_SIV")
+    daead = tink_rust.DeterministicAead(handle)
+    ct1 = daead.encrypt_deterministically(b"plaintext", b"aad")
+    ct2 = daead.encrypt_deterministically(b"plaintext", b"aad")
+    assert ct1 == ct2
+    assert daead.decrypt_deterministically(ct1, b"aad") == b"plaintext"
+
+
+def test_mac():
+    handle = tink_rust.KeysetHandle.generate_new("HMAC_SHA256_256BITTAG")
+    mac = tink_rust.Mac(handle)
+    tag = mac.compute_mac(b"data")
+    mac.verify_mac(tag, b"data")
+    with pytest.raises(tink_rust.TinkError):
+        mac.verify_mac(tag, b"other data")
+
+
+def test_streaming_aead():
+    handle = tink_rust.KeysetHandle.generate_new("AES128_GCM_HKDF_4KB")
+    saead = tink_rust.StreamingAead(handle)
+    plaintext = bytes(range(256)) * 100
+
+    ct_buf = io.BytesIO()
+    with saead.new_encrypting_stream(ct_buf, b"aad") as enc:
+        assert enc.write(plaintext[:1000]) == 1000
+        enc.write(plaintext[1000:])
+    assert enc.closed
+
+    with saead.new_decrypting_stream(io.BytesIO(ct_buf.getvalue()), b"aad") as dec:
+        assert dec.read(10) == plaintext[:10]
+        assert dec.read() == plaintext[10:]
+        assert dec.read() == b""
+
+    dec = saead.new_decrypting_stream(io.BytesIO(ct_buf.getvalue()), b"wrong aad")
+    with pytest.raises(tink_rust.TinkError):
+        dec.read()
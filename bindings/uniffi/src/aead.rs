@@ -0,0 +1,90 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! AEAD bindings.
+
+use crate::{KeysetHandle, TinkFfiError};
+use std::sync::Arc;
+use tink_core::{keyset::Handle, TinkError};
+
+/// Source of the key material for an [`Aead`].
+enum AeadSource {
+    Keyset(Handle),
+    Kms(String),
+}
+
+/// `Aead` provides authenticated encryption with associated data, using either a keyset or a
+/// key held in a KMS.
+#[derive(uniffi::Object)]
+pub struct Aead {
+    source: AeadSource,
+}
+
+impl Aead {
+    /// Create the underlying [`tink_core::Aead`] primitive.
+    pub(crate) fn primitive(&self) -> Result<Box<dyn tink_core::Aead>, TinkError> {
+        match &self.source {
+            AeadSource::Keyset(handle) => tink_aead::new(handle),
+            AeadSource::Kms(key_uri) => {
+                tink_core::registry::get_kms_client(key_uri)?.get_aead(key_uri)
+            }
+        }
+    }
+}
+
+#[uniffi::export]
+impl Aead {
+    /// Create an `Aead` that uses the primary key of the given keyset.
+    #[uniffi::constructor]
+    pub fn new(handle: Arc<KeysetHandle>) -> Result<Arc<Self>, TinkFfiError> {
+        crate::init();
+        let aead = Aead {
+            source: AeadSource::Keyset(handle.handle.clone()),
+        };
+        // Check that the keyset holds AEAD keys.
+        aead.primitive()?;
+        Ok(Arc::new(aead))
+    }
+
+    /// Create an `Aead` that uses the key with the given URI, which must be supported by a
+    /// registered [`KmsClient`](tink_core::registry::KmsClient).
+    #[uniffi::constructor]
+    pub fn from_kms_uri(key_uri: String) -> Result<Arc<Self>, TinkFfiError> {
+        crate::init();
+        tink_core::registry::get_kms_client(&key_uri)?;
+        Ok(Arc::new(Aead {
+            source: AeadSource::Kms(key_uri),
+        }))
+    }
+
+    /// Encrypt `plaintext`, authenticating `associated_data`.
+    pub fn encrypt(
+        &self,
+        plaintext: Vec<u8>,
+        associated_data: Vec<u8>,
+    ) -> Result<Vec<u8>, TinkFfiError> {
+        Ok(self.primitive()?.encrypt(&plaintext, &associated_data)?)
+    }
+
+    /// Decrypt `ciphertext`, authenticating `associated_data`.
+    pub fn decrypt(
+        &self,
+        ciphertext: Vec<u8>,
+        associated_data: Vec<u8>,
+    ) -> Result<Vec<u8>, TinkFfiError> {
+        Ok(self.primitive()?.decrypt(&ciphertext, &associated_data)?)
+    }
+}
@@ -0,0 +1,43 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Error type for the bindings.
+
+use tink_core::TinkError;
+
+/// `TinkFfiError` is the error type returned across the FFI boundary.
+#[derive(Debug, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum TinkFfiError {
+    /// An error from Tink; the message includes any underlying cause.
+    Tink(String),
+}
+
+impl std::fmt::Display for TinkFfiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TinkFfiError::Tink(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for TinkFfiError {}
+
+impl std::convert::From<TinkError> for TinkFfiError {
+    fn from(e: TinkError) -> Self {
+        TinkFfiError::Tink(e.to_string())
+    }
+}
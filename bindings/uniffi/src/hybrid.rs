@@ -0,0 +1,81 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Hybrid encryption bindings.
+
+use crate::{KeysetHandle, TinkFfiError};
+use std::sync::Arc;
+use tink_core::keyset::Handle;
+
+/// `HybridEncrypt` encrypts data for the holder of the private keys corresponding to a public
+/// keyset.
+#[derive(uniffi::Object)]
+pub struct HybridEncrypt {
+    handle: Handle,
+}
+
+#[uniffi::export]
+impl HybridEncrypt {
+    /// Create a `HybridEncrypt` that uses the primary key of the given public keyset.
+    #[uniffi::constructor]
+    pub fn new(handle: Arc<KeysetHandle>) -> Result<Arc<Self>, TinkFfiError> {
+        crate::init();
+        // Check that the keyset holds hybrid encryption keys.
+        tink_hybrid::new_encrypt(&handle.handle)?;
+        Ok(Arc::new(HybridEncrypt {
+            handle: handle.handle.clone(),
+        }))
+    }
+
+    /// Encrypt `plaintext`, binding it to `context_info`.
+    pub fn encrypt(
+        &self,
+        plaintext: Vec<u8>,
+        context_info: Vec<u8>,
+    ) -> Result<Vec<u8>, TinkFfiError> {
+        Ok(tink_hybrid::new_encrypt(&self.handle)?.encrypt(&plaintext, &context_info)?)
+    }
+}
+
+/// `HybridDecrypt` decrypts data encrypted with the public keys corresponding to a private
+/// keyset.
+#[derive(uniffi::Object)]
+pub struct HybridDecrypt {
+    handle: Handle,
+}
+
+#[uniffi::export]
+impl HybridDecrypt {
+    /// Create a `HybridDecrypt` that uses the keys of the given private keyset.
+    #[uniffi::constructor]
+    pub fn new(handle: Arc<KeysetHandle>) -> Result<Arc<Self>, TinkFfiError> {
+        crate::init();
+        // Check that the keyset holds hybrid decryption keys.
+        tink_hybrid::new_decrypt(&handle.handle)?;
+        Ok(Arc::new(HybridDecrypt {
+            handle: handle.handle.clone(),
+        }))
+    }
+
+    /// Decrypt `ciphertext`, checking that it was bound to `context_info`.
+    pub fn decrypt(
+        &self,
+        ciphertext: Vec<u8>,
+        context_info: Vec<u8>,
+    ) -> Result<Vec<u8>, TinkFfiError> {
+        Ok(tink_hybrid::new_decrypt(&self.handle)?.decrypt(&ciphertext, &context_info)?)
+    }
+}
@@ -0,0 +1,106 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Keyset handle bindings.
+
+use crate::{Aead, TinkFfiError};
+use std::sync::Arc;
+use tink_core::keyset::{BinaryReader, BinaryWriter, Handle};
+
+/// `KeysetHandle` provides access to a keyset, without exposing its key material.
+#[derive(uniffi::Object)]
+pub struct KeysetHandle {
+    pub(crate) handle: Handle,
+}
+
+impl KeysetHandle {
+    fn wrap(handle: Handle) -> Arc<Self> {
+        Arc::new(KeysetHandle { handle })
+    }
+}
+
+#[uniffi::export]
+impl KeysetHandle {
+    /// Generate a keyset holding a single new key, from the key template registered under
+    /// `template_name` (for example `"AES256_GCM"`).
+    #[uniffi::constructor]
+    pub fn generate_new(template_name: String) -> Result<Arc<Self>, TinkFfiError> {
+        crate::init();
+        let generator = tink_core::registry::get_template_generator(&template_name)
+            .ok_or_else(|| TinkFfiError::Tink(format!("unknown key template {}", template_name)))?;
+        Ok(Self::wrap(Handle::new(&generator())?))
+    }
+
+    /// Read a keyset in binary format that has been encrypted with `main_key` (see
+    /// [`write_encrypted`](Self::write_encrypted)).
+    #[uniffi::constructor]
+    pub fn read_encrypted(
+        encrypted_keyset: Vec<u8>,
+        main_key: Arc<Aead>,
+        associated_data: Vec<u8>,
+    ) -> Result<Arc<Self>, TinkFfiError> {
+        crate::init();
+        let mut reader = BinaryReader::new(&encrypted_keyset[..]);
+        Ok(Self::wrap(Handle::read_with_associated_data(
+            &mut reader,
+            main_key.primitive()?,
+            &associated_data,
+        )?))
+    }
+
+    /// Read a cleartext keyset in binary format, which must not contain any secret key
+    /// material (for example, a public keyset).
+    #[uniffi::constructor]
+    pub fn read_no_secret(keyset: Vec<u8>) -> Result<Arc<Self>, TinkFfiError> {
+        crate::init();
+        let mut reader = BinaryReader::new(&keyset[..]);
+        Ok(Self::wrap(Handle::read_with_no_secrets(&mut reader)?))
+    }
+
+    /// Return the keyset in binary format, encrypted with `main_key`.
+    pub fn write_encrypted(
+        &self,
+        main_key: Arc<Aead>,
+        associated_data: Vec<u8>,
+    ) -> Result<Vec<u8>, TinkFfiError> {
+        let mut buf = Vec::new();
+        self.handle.write_with_associated_data(
+            &mut BinaryWriter::new(&mut buf),
+            main_key.primitive()?,
+            &associated_data,
+        )?;
+        Ok(buf)
+    }
+
+    /// Return the keyset in cleartext binary format, failing if it contains any secret key
+    /// material.
+    pub fn write_no_secret(&self) -> Result<Vec<u8>, TinkFfiError> {
+        let mut buf = Vec::new();
+        self.handle
+            .write_with_no_secrets(&mut BinaryWriter::new(&mut buf))?;
+        Ok(buf)
+    }
+
+    /// Return a handle for the public keys corresponding to the private keys in this keyset.
+    pub fn public_handle(&self) -> Result<Arc<KeysetHandle>, TinkFfiError> {
+        Ok(Self::wrap(self.handle.public()?))
+    }
+
+    /// Return the ID of the primary key of the keyset.
+    pub fn primary_key_id(&self) -> u32 {
+        self.handle.keyset_info().primary_key_id
+    }
+}
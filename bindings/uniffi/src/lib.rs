@@ -0,0 +1,42 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Provide [UniFFI](https://mozilla.github.io/uniffi-rs/) bindings for keyset handling, AEAD and
+//! hybrid encryption, for use from Kotlin and Swift.
+//!
+//! Tink primitives are not required to be `Send`/`Sync`, but objects exposed across the FFI
+//! boundary must be; the exposed objects therefore hold a [`tink_core::keyset::Handle`] (which
+//! is `Send + Sync`), and create the underlying primitive for each operation.
+
+#![deny(broken_intra_doc_links)]
+
+uniffi::setup_scaffolding!();
+
+mod aead;
+pub use aead::*;
+mod error;
+pub use error::*;
+mod hybrid;
+pub use hybrid::*;
+mod keyset;
+pub use keyset::*;
+
+/// Initialize the primitive crates exposed by these bindings, registering their key managers and
+/// key templates.
+fn init() {
+    tink_aead::init();
+    tink_hybrid::init();
+}
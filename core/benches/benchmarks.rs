@@ -0,0 +1,95 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+#![feature(test)]
+extern crate test;
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+use test::Bencher;
+use tink_core::{registry::KeyManager, TinkError};
+
+const THREADS: usize = 8;
+const LOOKUPS_PER_THREAD: usize = 1000;
+const TYPE_URL: &str = "type.googleapis.com/google.crypto.tink.BenchmarkKey";
+
+struct BenchKeyManager;
+
+impl KeyManager for BenchKeyManager {
+    fn primitive(&self, _serialized_key: &[u8]) -> Result<tink_core::Primitive, TinkError> {
+        Err("not implemented".into())
+    }
+    fn new_key(&self, _serialized_key_format: &[u8]) -> Result<Vec<u8>, TinkError> {
+        Err("not implemented".into())
+    }
+    fn type_url(&self) -> &'static str {
+        TYPE_URL
+    }
+    fn key_material_type(&self) -> tink_proto::key_data::KeyMaterialType {
+        tink_proto::key_data::KeyMaterialType::Symmetric
+    }
+}
+
+/// Run `lookup` concurrently from [`THREADS`] threads.
+fn run_concurrently<F>(lookup: F)
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    let lookup = Arc::new(lookup);
+    let handles: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let lookup = lookup.clone();
+            std::thread::spawn(move || {
+                for _ in 0..LOOKUPS_PER_THREAD {
+                    lookup();
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+#[bench]
+fn bench_registry_get_key_manager(b: &mut Bencher) {
+    let _ = tink_core::registry::register_key_manager(Arc::new(BenchKeyManager));
+    b.iter(|| tink_core::registry::get_key_manager(TYPE_URL).unwrap());
+}
+
+#[bench]
+fn bench_registry_get_key_manager_concurrent(b: &mut Bencher) {
+    let _ = tink_core::registry::register_key_manager(Arc::new(BenchKeyManager));
+    b.iter(|| {
+        run_concurrently(|| {
+            tink_core::registry::get_key_manager(TYPE_URL).unwrap();
+        })
+    });
+}
+
+/// Baseline for comparison: the same concurrent lookups against a map behind a `RwLock`.
+#[bench]
+fn bench_rwlock_map_get_concurrent(b: &mut Bencher) {
+    let mut map: HashMap<&'static str, Arc<dyn KeyManager>> = HashMap::new();
+    map.insert(TYPE_URL, Arc::new(BenchKeyManager));
+    let map = Arc::new(RwLock::new(map));
+    b.iter(|| {
+        let map = map.clone();
+        run_concurrently(move || {
+            map.read().unwrap().get(TYPE_URL).unwrap().clone();
+        })
+    });
+}
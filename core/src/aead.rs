@@ -21,6 +21,8 @@
 /// Implementations of this trait are secure against adaptive chosen ciphertext attacks.
 /// Encryption with additional data ensures authenticity and integrity of that data, but not
 /// its secrecy (see [RFC 5116](https://tools.ietf.org/html/rfc5116)).
+///
+/// Absent associated data is represented by an empty slice (see [`crate::associated_data`]).
 pub trait Aead: AeadBoxClone {
     /// Encrypt plaintext with `additional_data` as additional
     /// authenticated data. The resulting ciphertext allows for checking
@@ -40,6 +42,44 @@ pub trait Aead: AeadBoxClone {
         ciphertext: &[u8],
         additional_data: &[u8],
     ) -> Result<Vec<u8>, crate::TinkError>;
+
+    /// Encrypt plaintext, authenticating all of the data that can be read from
+    /// `additional_data`.  The ciphertext is the same as would be produced by
+    /// [`encrypt`](Aead::encrypt) given the associated data as a slice.
+    ///
+    /// The default implementation reads all of the associated data into memory; primitives
+    /// whose construction can absorb associated data incrementally override this so that very
+    /// large associated data never needs to be held in memory.
+    fn encrypt_with_aad_reader(
+        &self,
+        plaintext: &[u8],
+        additional_data: &mut dyn std::io::Read,
+    ) -> Result<Vec<u8>, crate::TinkError> {
+        let additional_data = read_associated_data(additional_data)?;
+        self.encrypt(plaintext, &additional_data)
+    }
+
+    /// Decrypt ciphertext, checking that it was authenticated with all of the data that can be
+    /// read from `additional_data`.
+    ///
+    /// The default implementation reads all of the associated data into memory; primitives
+    /// whose construction can absorb associated data incrementally override this.
+    fn decrypt_with_aad_reader(
+        &self,
+        ciphertext: &[u8],
+        additional_data: &mut dyn std::io::Read,
+    ) -> Result<Vec<u8>, crate::TinkError> {
+        let additional_data = read_associated_data(additional_data)?;
+        self.decrypt(ciphertext, &additional_data)
+    }
+}
+
+/// Read all of the associated data available from `r` into memory.
+fn read_associated_data(r: &mut dyn std::io::Read) -> Result<Vec<u8>, crate::TinkError> {
+    let mut data = Vec::new();
+    r.read_to_end(&mut data)
+        .map_err(|e| crate::utils::wrap_err("failed to read associated data", e))?;
+    Ok(data)
 }
 
 /// Trait bound to indicate that primitive trait objects should support cloning
@@ -0,0 +1,184 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Handling of absent (as opposed to empty) associated data.
+//!
+//! Some other Tink implementations accept a null value for associated data (or for the
+//! `context_info` of hybrid encryption), whereas the Rust primitive traits always take a byte
+//! slice.  For every Tink key type an absent value is equivalent to an empty one: AES-GCM skips
+//! the associated data, AES-SIV uses a single empty associated data element, and the HKDF-based
+//! schemes (streaming AEAD, ECIES) use empty HKDF info.  So a ciphertext produced elsewhere with
+//! null associated data can be decrypted here by passing `&[]`, and vice versa.
+//!
+//! This module makes that canonicalization explicit: [`canonicalize`] maps `None` to the empty
+//! slice, and the extension traits below provide `Option`-taking variants of the primitive
+//! operations for code that is ported from (or interoperates with) APIs that distinguish the
+//! two cases.
+
+use crate::{
+    Aead, DeterministicAead, EncryptingWrite, HybridDecrypt, HybridEncrypt, StreamingAead,
+    TinkError,
+};
+
+/// Return the canonical form of possibly-absent associated data, which is empty if absent.
+pub fn canonicalize(associated_data: Option<&[u8]>) -> &[u8] {
+    associated_data.unwrap_or(&[])
+}
+
+/// Variants of [`Aead`] operations that take optional associated data.
+pub trait AeadWithOptionalAd {
+    /// Encrypt `plaintext`, treating absent `additional_data` as empty.
+    fn encrypt_with_optional_ad(
+        &self,
+        plaintext: &[u8],
+        additional_data: Option<&[u8]>,
+    ) -> Result<Vec<u8>, TinkError>;
+
+    /// Decrypt `ciphertext`, treating absent `additional_data` as empty.
+    fn decrypt_with_optional_ad(
+        &self,
+        ciphertext: &[u8],
+        additional_data: Option<&[u8]>,
+    ) -> Result<Vec<u8>, TinkError>;
+}
+
+impl<T: Aead + ?Sized> AeadWithOptionalAd for T {
+    fn encrypt_with_optional_ad(
+        &self,
+        plaintext: &[u8],
+        additional_data: Option<&[u8]>,
+    ) -> Result<Vec<u8>, TinkError> {
+        self.encrypt(plaintext, canonicalize(additional_data))
+    }
+
+    fn decrypt_with_optional_ad(
+        &self,
+        ciphertext: &[u8],
+        additional_data: Option<&[u8]>,
+    ) -> Result<Vec<u8>, TinkError> {
+        self.decrypt(ciphertext, canonicalize(additional_data))
+    }
+}
+
+/// Variants of [`DeterministicAead`] operations that take optional associated data.
+pub trait DeterministicAeadWithOptionalAd {
+    /// Deterministically encrypt `plaintext`, treating absent `additional_data` as empty.
+    fn encrypt_deterministically_with_optional_ad(
+        &self,
+        plaintext: &[u8],
+        additional_data: Option<&[u8]>,
+    ) -> Result<Vec<u8>, TinkError>;
+
+    /// Deterministically decrypt `ciphertext`, treating absent `additional_data` as empty.
+    fn decrypt_deterministically_with_optional_ad(
+        &self,
+        ciphertext: &[u8],
+        additional_data: Option<&[u8]>,
+    ) -> Result<Vec<u8>, TinkError>;
+}
+
+impl<T: DeterministicAead + ?Sized> DeterministicAeadWithOptionalAd for T {
+    fn encrypt_deterministically_with_optional_ad(
+        &self,
+        plaintext: &[u8],
+        additional_data: Option<&[u8]>,
+    ) -> Result<Vec<u8>, TinkError> {
+        self.encrypt_deterministically(plaintext, canonicalize(additional_data))
+    }
+
+    fn decrypt_deterministically_with_optional_ad(
+        &self,
+        ciphertext: &[u8],
+        additional_data: Option<&[u8]>,
+    ) -> Result<Vec<u8>, TinkError> {
+        self.decrypt_deterministically(ciphertext, canonicalize(additional_data))
+    }
+}
+
+/// Variants of [`StreamingAead`] operations that take optional associated data.
+pub trait StreamingAeadWithOptionalAd {
+    /// Return an encrypting writer, treating absent `aad` as empty.
+    fn new_encrypting_writer_with_optional_ad(
+        &self,
+        w: Box<dyn std::io::Write>,
+        aad: Option<&[u8]>,
+    ) -> Result<Box<dyn EncryptingWrite>, TinkError>;
+
+    /// Return a decrypting reader, treating absent `aad` as empty.
+    fn new_decrypting_reader_with_optional_ad(
+        &self,
+        r: Box<dyn std::io::Read>,
+        aad: Option<&[u8]>,
+    ) -> Result<Box<dyn std::io::Read>, TinkError>;
+}
+
+impl<T: StreamingAead + ?Sized> StreamingAeadWithOptionalAd for T {
+    fn new_encrypting_writer_with_optional_ad(
+        &self,
+        w: Box<dyn std::io::Write>,
+        aad: Option<&[u8]>,
+    ) -> Result<Box<dyn EncryptingWrite>, TinkError> {
+        self.new_encrypting_writer(w, canonicalize(aad))
+    }
+
+    fn new_decrypting_reader_with_optional_ad(
+        &self,
+        r: Box<dyn std::io::Read>,
+        aad: Option<&[u8]>,
+    ) -> Result<Box<dyn std::io::Read>, TinkError> {
+        self.new_decrypting_reader(r, canonicalize(aad))
+    }
+}
+
+/// Variant of [`HybridEncrypt::encrypt`] that takes optional context info.
+pub trait HybridEncryptWithOptionalContext {
+    /// Encrypt `plaintext`, treating absent `context_info` as empty.
+    fn encrypt_with_optional_context(
+        &self,
+        plaintext: &[u8],
+        context_info: Option<&[u8]>,
+    ) -> Result<Vec<u8>, TinkError>;
+}
+
+impl<T: HybridEncrypt + ?Sized> HybridEncryptWithOptionalContext for T {
+    fn encrypt_with_optional_context(
+        &self,
+        plaintext: &[u8],
+        context_info: Option<&[u8]>,
+    ) -> Result<Vec<u8>, TinkError> {
+        self.encrypt(plaintext, canonicalize(context_info))
+    }
+}
+
+/// Variant of [`HybridDecrypt::decrypt`] that takes optional context info.
+pub trait HybridDecryptWithOptionalContext {
+    /// Decrypt `ciphertext`, treating absent `context_info` as empty.
+    fn decrypt_with_optional_context(
+        &self,
+        ciphertext: &[u8],
+        context_info: Option<&[u8]>,
+    ) -> Result<Vec<u8>, TinkError>;
+}
+
+impl<T: HybridDecrypt + ?Sized> HybridDecryptWithOptionalContext for T {
+    fn decrypt_with_optional_context(
+        &self,
+        ciphertext: &[u8],
+        context_info: Option<&[u8]>,
+    ) -> Result<Vec<u8>, TinkError> {
+        self.decrypt(ciphertext, canonicalize(context_info))
+    }
+}
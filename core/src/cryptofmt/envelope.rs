@@ -0,0 +1,147 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Optional self-describing envelope for ciphertexts.
+//!
+//! The envelope wraps standard Tink output in a small header, so that long-lived data records
+//! which kind of key produced it without any out-of-band metadata.  Nothing in Tink produces
+//! envelopes unless explicitly asked to, so the wire format of primitives is unchanged.
+
+use crate::TinkError;
+use std::convert::TryInto;
+
+/// Magic bytes at the start of a ciphertext envelope.  The first byte differs from the start
+/// bytes of the Tink and legacy output prefixes.
+pub const ENVELOPE_MAGIC: [u8; 4] = *b"TKEV";
+/// Current version of the ciphertext envelope format.
+pub const ENVELOPE_VERSION: u8 = 1;
+/// Size of the fixed part of the envelope header: magic, version byte, 2-byte template hint
+/// length and 4-byte associated data length.
+pub const ENVELOPE_HEADER_SIZE: usize = ENVELOPE_MAGIC.len() + 1 + 2 + 4;
+
+/// The components of a ciphertext envelope, as returned by [`decode_envelope`].
+///
+/// An encoded envelope consists of:
+///  - magic bytes ([`ENVELOPE_MAGIC`])
+///  - version byte ([`ENVELOPE_VERSION`])
+///  - template hint length (2 bytes, big-endian)
+///  - template hint (UTF-8)
+///  - length of the associated data used at encryption (4 bytes, big-endian)
+///  - ciphertext, as produced by the primitive (including any key prefix)
+///
+/// The header is not authenticated; the template hint is informational and should only be used
+/// to select candidate keys, never to bypass decryption.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CiphertextEnvelope<'a> {
+    /// Envelope format version.
+    pub version: u8,
+    /// Hint describing the key template that produced the ciphertext, e.g. a template name
+    /// such as "AES256_GCM" or a key type URL.
+    pub template_hint: &'a str,
+    /// Length of the associated data that was used at encryption.
+    pub aad_len: u32,
+    /// The wrapped ciphertext.
+    pub ciphertext: &'a [u8],
+}
+
+/// Wrap `ciphertext` in an envelope carrying `template_hint` and the length of the associated
+/// data used to produce it.
+pub fn encode_envelope(
+    template_hint: &str,
+    aad_len: usize,
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, TinkError> {
+    let hint_len: u16 = template_hint
+        .len()
+        .try_into()
+        .map_err(|_| TinkError::new("cryptofmt: template hint too long"))?;
+    let aad_len: u32 = aad_len
+        .try_into()
+        .map_err(|_| TinkError::new("cryptofmt: associated data too long"))?;
+    let mut ret = Vec::with_capacity(ENVELOPE_HEADER_SIZE + template_hint.len() + ciphertext.len());
+    ret.extend_from_slice(&ENVELOPE_MAGIC);
+    ret.push(ENVELOPE_VERSION);
+    ret.extend_from_slice(&hint_len.to_be_bytes());
+    ret.extend_from_slice(template_hint.as_bytes());
+    ret.extend_from_slice(&aad_len.to_be_bytes());
+    ret.extend_from_slice(ciphertext);
+    Ok(ret)
+}
+
+/// Indicate whether `data` starts with the envelope magic bytes.
+///
+/// Ciphertexts produced by keys with a Tink or legacy output prefix never start with the magic
+/// bytes, but RAW ciphertexts may do so by chance.
+pub fn is_envelope(data: &[u8]) -> bool {
+    data.starts_with(&ENVELOPE_MAGIC)
+}
+
+/// Parse a ciphertext envelope into its components, without decrypting anything.
+pub fn decode_envelope(data: &[u8]) -> Result<CiphertextEnvelope, TinkError> {
+    if data.len() < ENVELOPE_HEADER_SIZE {
+        return Err("cryptofmt: envelope too short".into());
+    }
+    if !is_envelope(data) {
+        return Err("cryptofmt: missing envelope magic".into());
+    }
+    let mut pos = ENVELOPE_MAGIC.len();
+    let version = data[pos];
+    if version != ENVELOPE_VERSION {
+        return Err(format!("cryptofmt: unsupported envelope version {}", version).into());
+    }
+    pos += 1;
+    let hint_len = u16::from_be_bytes([data[pos], data[pos + 1]]) as usize;
+    pos += 2;
+    if data.len() < ENVELOPE_HEADER_SIZE + hint_len {
+        return Err("cryptofmt: envelope too short".into());
+    }
+    let template_hint = std::str::from_utf8(&data[pos..pos + hint_len])
+        .map_err(|_| TinkError::new("cryptofmt: invalid template hint"))?;
+    pos += hint_len;
+    let aad_len = u32::from_be_bytes(data[pos..pos + 4].try_into().unwrap()); // safe: checked len
+    pos += 4;
+    Ok(CiphertextEnvelope {
+        version,
+        template_hint,
+        aad_len,
+        ciphertext: &data[pos..],
+    })
+}
+
+/// Encrypt `plaintext` with `aead` and wrap the result in an envelope carrying `template_hint`.
+pub fn encrypt_with_envelope(
+    aead: &dyn crate::Aead,
+    template_hint: &str,
+    plaintext: &[u8],
+    associated_data: &[u8],
+) -> Result<Vec<u8>, TinkError> {
+    let ct = aead.encrypt(plaintext, associated_data)?;
+    encode_envelope(template_hint, associated_data.len(), &ct)
+}
+
+/// Unwrap an envelope produced by [`encrypt_with_envelope`] and decrypt the ciphertext within
+/// it with `aead`.
+pub fn decrypt_envelope(
+    aead: &dyn crate::Aead,
+    data: &[u8],
+    associated_data: &[u8],
+) -> Result<Vec<u8>, TinkError> {
+    let envelope = decode_envelope(data)?;
+    if envelope.aad_len as usize != associated_data.len() {
+        return Err("cryptofmt: associated data length mismatch".into());
+    }
+    aead.decrypt(envelope.ciphertext, associated_data)
+}
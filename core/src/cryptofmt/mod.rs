@@ -19,6 +19,9 @@
 use crate::TinkError;
 use tink_proto::OutputPrefixType;
 
+mod envelope;
+pub use envelope::*;
+
 /// Prefix size of Tink and Legacy key types.
 pub const NON_RAW_PREFIX_SIZE: usize = 5;
 
@@ -42,7 +45,8 @@ pub const RAW_PREFIX: Vec<u8> = Vec::new();
 
 /// Generate the prefix of ciphertexts produced by the crypto primitive obtained from key.  The
 /// prefix can be either empty (for RAW-type prefix), or consists of a 1-byte indicator of the type
-/// of the prefix, followed by 4 bytes of the key ID in big endian encoding.
+/// of the prefix, followed by 4 bytes of the key ID in big endian encoding.  Keys may also use a
+/// [registered](crate::registry::register_custom_output_prefix) custom prefix type.
 pub fn output_prefix(key: &tink_proto::keyset::Key) -> Result<Vec<u8>, TinkError> {
     match OutputPrefixType::from_i32(key.output_prefix_type) {
         Some(OutputPrefixType::Legacy) | Some(OutputPrefixType::Crunchy) => Ok(
@@ -54,9 +58,30 @@ pub fn output_prefix(key: &tink_proto::keyset::Key) -> Result<Vec<u8>, TinkError
             key.key_id,
         )),
         Some(OutputPrefixType::Raw) => Ok(RAW_PREFIX),
-        Some(OutputPrefixType::UnknownPrefix) | None => {
+        Some(OutputPrefixType::UnknownPrefix) => {
+            Err("cryptofmt: unknown output prefix type".into())
+        }
+        None => match crate::registry::custom_output_prefix(key.output_prefix_type) {
+            Some(custom) => Ok(custom.encode(key.key_id)),
+            None => Err("cryptofmt: unknown output prefix type".into()),
+        },
+    }
+}
+
+/// Return the standard output prefix type whose behaviour the primitive wrappers follow for the
+/// given `output_prefix_type` value: the type itself for standard prefix types, or the type that
+/// a [registered](crate::registry::register_custom_output_prefix) custom prefix behaves as.
+pub fn effective_output_prefix_type(
+    output_prefix_type: i32,
+) -> Result<OutputPrefixType, TinkError> {
+    match OutputPrefixType::from_i32(output_prefix_type) {
+        Some(OutputPrefixType::UnknownPrefix) => {
             Err("cryptofmt: unknown output prefix type".into())
         }
+        Some(t) => Ok(t),
+        None => crate::registry::custom_output_prefix(output_prefix_type)
+            .map(|custom| custom.behaves_as)
+            .ok_or_else(|| "cryptofmt: unknown output prefix type".into()),
     }
 }
 
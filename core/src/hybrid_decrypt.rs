@@ -57,6 +57,43 @@ pub trait HybridDecrypt: HybridDecryptBoxClone {
     /// Decrypt `ciphertext` verifying the integrity of `context_info`.
     /// Returns resulting plaintext
     fn decrypt(&self, ciphertext: &[u8], context_info: &[u8]) -> Result<Vec<u8>, crate::TinkError>;
+
+    /// Return a wrapper around an underlying `std::io::Read`, such that any read-operation via
+    /// the wrapper results in decryption of ciphertext produced by
+    /// [`crate::HybridEncrypt::new_encrypting_writer`], verifying the integrity of
+    /// `context_info`.
+    ///
+    /// The default implementation returns an error, as only keys whose data encapsulation
+    /// mechanism is a streaming AEAD support this.
+    fn new_decrypting_reader(
+        &self,
+        _r: Box<dyn std::io::Read>,
+        _context_info: &[u8],
+    ) -> Result<Box<dyn std::io::Read>, crate::TinkError> {
+        Err("streaming hybrid decryption not supported".into())
+    }
+
+    /// Decrypt `ciphertext` using only the key with identifier `key_id`, verifying the integrity
+    /// of `context_info`.  This avoids trying every RAW key when the caller knows which key was
+    /// used to encrypt.
+    ///
+    /// Only primitives backed by a keyset (such as those created by `tink_hybrid::new_decrypt()`)
+    /// know their key identifiers; the default implementation returns an error.
+    fn decrypt_with_key_id(
+        &self,
+        _key_id: crate::KeyId,
+        _ciphertext: &[u8],
+        _context_info: &[u8],
+    ) -> Result<Vec<u8>, crate::TinkError> {
+        Err("decrypt_with_key_id not supported".into())
+    }
+
+    /// Return the identifiers of the keys with a RAW output prefix, which
+    /// [`decrypt`](HybridDecrypt::decrypt) has to try in turn.  The default implementation returns
+    /// an empty list.
+    fn raw_key_ids(&self) -> Vec<crate::KeyId> {
+        Vec::new()
+    }
 }
 
 /// Trait bound to indicate that primitive trait objects should support cloning
@@ -44,7 +44,8 @@
 ///
 /// `context_info` can be empty, but to ensure the correct decryption of a ciphertext
 /// the same value must be provided for the decryption operation as was used during encryption (cf.
-/// [`crate::HybridDecrypt`]).
+/// [`crate::HybridDecrypt`]).  An empty `context_info` is equivalent to the null value accepted by
+/// some other Tink implementations (see [`crate::associated_data`]).
 ///
 /// A concrete implementation of this trait can implement the binding of contextInfo to
 /// the ciphertext in various ways, for example:
@@ -57,6 +58,23 @@ pub trait HybridEncrypt: HybridEncryptBoxClone {
     /// Encrypt `plaintext` binding `context_info` to the resulting
     /// ciphertext. Returns resulting ciphertext.
     fn encrypt(&self, plaintext: &[u8], context_info: &[u8]) -> Result<Vec<u8>, crate::TinkError>;
+
+    /// Return a wrapper around an underlying `std::io::Write`, such that any write-operation via
+    /// the wrapper results in hybrid encryption of the written data, binding `context_info` to
+    /// the resulting ciphertext.  The key encapsulation is only performed once, and the data is
+    /// then encrypted with a streaming AEAD, so arbitrarily large plaintexts can be encrypted
+    /// without buffering.  The resulting ciphertext can only be decrypted with
+    /// [`crate::HybridDecrypt::new_decrypting_reader`].
+    ///
+    /// The default implementation returns an error, as only keys whose data encapsulation
+    /// mechanism is a streaming AEAD support this.
+    fn new_encrypting_writer(
+        &self,
+        _w: Box<dyn std::io::Write>,
+        _context_info: &[u8],
+    ) -> Result<Box<dyn crate::EncryptingWrite>, crate::TinkError> {
+        Err("streaming hybrid encryption not supported".into())
+    }
 }
 
 /// Trait bound to indicate that primitive trait objects should support cloning
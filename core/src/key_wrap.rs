@@ -0,0 +1,33 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Key wrapping.
+
+/// `KeyWrap` is the interface for key wrapping schemes, such as AES Key Wrap (RFC 3394), that
+/// encrypt raw key material under a key-encryption key (KEK).  These are used to exchange keys
+/// with systems such as HSMs that cannot read Tink keysets.
+///
+/// Unlike [`Aead`](crate::Aead), key wrapping takes no associated data, and the ciphertext format
+/// is fixed by the scheme so that the other party can unwrap it.  Implementations may hold the
+/// KEK locally, or delegate to an HSM that holds it.
+pub trait KeyWrap {
+    /// Wrap (encrypt and authenticate) the raw key material in `key`.
+    fn wrap_key(&self, key: &[u8]) -> Result<Vec<u8>, crate::TinkError>;
+
+    /// Unwrap `wrapped`, returning the raw key material, or an error if it is not a valid
+    /// wrapping under this KEK.
+    fn unwrap_key(&self, wrapped: &[u8]) -> Result<Vec<u8>, crate::TinkError>;
+}
@@ -0,0 +1,229 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Audit trail of key lifecycle events.
+
+use super::{Clock, SystemClock};
+use crate::{KeyId, TinkError};
+use std::{
+    convert::TryInto,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Version byte at the start of an encoded [`AuditTrail`].
+const AUDIT_TRAIL_VERSION: u8 = 1;
+
+/// The kind of a [`KeyEvent`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyEventKind {
+    /// The key was generated or derived and added to the keyset.
+    Created,
+    /// The key became the primary key, replacing the previous primary key `from` (0 if the keyset
+    /// had no primary key).
+    Rotated { from: KeyId },
+    /// The key was enabled.
+    Enabled,
+    /// The key was disabled.
+    Disabled,
+    /// The key material was destroyed.
+    Destroyed,
+    /// The key was removed from the keyset.
+    Deleted,
+}
+
+impl KeyEventKind {
+    fn code(&self) -> (u8, KeyId) {
+        match self {
+            KeyEventKind::Created => (0, 0),
+            KeyEventKind::Rotated { from } => (1, *from),
+            KeyEventKind::Enabled => (2, 0),
+            KeyEventKind::Disabled => (3, 0),
+            KeyEventKind::Destroyed => (4, 0),
+            KeyEventKind::Deleted => (5, 0),
+        }
+    }
+
+    fn from_code(code: u8, from: KeyId) -> Option<Self> {
+        match code {
+            0 => Some(KeyEventKind::Created),
+            1 => Some(KeyEventKind::Rotated { from }),
+            2 => Some(KeyEventKind::Enabled),
+            3 => Some(KeyEventKind::Disabled),
+            4 => Some(KeyEventKind::Destroyed),
+            5 => Some(KeyEventKind::Deleted),
+            _ => None,
+        }
+    }
+}
+
+/// A single lifecycle event for a key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyEvent {
+    pub key_id: KeyId,
+    pub kind: KeyEventKind,
+    /// Time of the event, with a resolution of nanoseconds since the Unix epoch.
+    pub time: SystemTime,
+    /// Free-form description of who performed the operation.
+    pub actor: String,
+}
+
+/// `AuditTrail` is an ordered record of the lifecycle events of the keys in a keyset.  It is kept
+/// alongside the keyset (rather than inside the [`Keyset`](tink_proto::Keyset) proto), and can
+/// be persisted as a metadata blob via [`encode`](AuditTrail::encode) and
+/// [`decode`](AuditTrail::decode).
+///
+/// The encoding is:
+///  - version byte (1)
+///  - number of events (4 bytes, big-endian)
+///  - for each event: key ID (4 bytes), kind (1 byte), rotated-from key ID (4 bytes), seconds
+///    (8 bytes) and nanoseconds (4 bytes) since the Unix epoch, actor length (4 bytes), actor
+///    (UTF-8); all integers big-endian.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AuditTrail {
+    pub events: Vec<KeyEvent>,
+}
+
+impl AuditTrail {
+    /// Return the events recorded for the given key.
+    pub fn events_for_key(&self, key_id: KeyId) -> Vec<&KeyEvent> {
+        self.events.iter().filter(|e| e.key_id == key_id).collect()
+    }
+
+    /// Encode the audit trail as a metadata blob.
+    pub fn encode(&self) -> Result<Vec<u8>, TinkError> {
+        let count: u32 = self
+            .events
+            .len()
+            .try_into()
+            .map_err(|_| TinkError::new("keyset::AuditTrail: too many events"))?;
+        let mut out = vec![AUDIT_TRAIL_VERSION];
+        out.extend_from_slice(&count.to_be_bytes());
+        for event in &self.events {
+            let (code, from) = event.kind.code();
+            let since_epoch = event.time.duration_since(UNIX_EPOCH).unwrap_or_default();
+            let actor_len: u32 = event
+                .actor
+                .len()
+                .try_into()
+                .map_err(|_| TinkError::new("keyset::AuditTrail: actor too long"))?;
+            out.extend_from_slice(&event.key_id.to_be_bytes());
+            out.push(code);
+            out.extend_from_slice(&from.to_be_bytes());
+            out.extend_from_slice(&since_epoch.as_secs().to_be_bytes());
+            out.extend_from_slice(&since_epoch.subsec_nanos().to_be_bytes());
+            out.extend_from_slice(&actor_len.to_be_bytes());
+            out.extend_from_slice(event.actor.as_bytes());
+        }
+        Ok(out)
+    }
+
+    /// Decode an audit trail from a metadata blob produced by [`encode`](AuditTrail::encode).
+    pub fn decode(data: &[u8]) -> Result<AuditTrail, TinkError> {
+        let mut r = Decoder(data);
+        if r.take(1)?[0] != AUDIT_TRAIL_VERSION {
+            return Err("keyset::AuditTrail: unknown version".into());
+        }
+        let count = r.u32()?;
+        let mut events = Vec::new();
+        for _ in 0..count {
+            let key_id = r.u32()?;
+            let code = r.take(1)?[0];
+            let from = r.u32()?;
+            let kind = KeyEventKind::from_code(code, from)
+                .ok_or_else(|| TinkError::new("keyset::AuditTrail: unknown event kind"))?;
+            let secs = r.u64()?;
+            let nanos = r.u32()?;
+            if nanos >= 1_000_000_000 {
+                return Err("keyset::AuditTrail: invalid timestamp".into());
+            }
+            let time = UNIX_EPOCH
+                .checked_add(Duration::new(secs, nanos))
+                .ok_or_else(|| TinkError::new("keyset::AuditTrail: invalid timestamp"))?;
+            let actor_len = r.u32()? as usize;
+            let actor = String::from_utf8(r.take(actor_len)?.to_vec())
+                .map_err(|_| TinkError::new("keyset::AuditTrail: invalid actor"))?;
+            events.push(KeyEvent {
+                key_id,
+                kind,
+                time,
+                actor,
+            });
+        }
+        if !r.0.is_empty() {
+            return Err("keyset::AuditTrail: trailing data".into());
+        }
+        Ok(AuditTrail { events })
+    }
+}
+
+/// Helper for decoding big-endian integers from a byte slice.
+struct Decoder<'a>(&'a [u8]);
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], TinkError> {
+        if self.0.len() < n {
+            return Err("keyset::AuditTrail: truncated data".into());
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<u32, TinkError> {
+        let mut b = [0u8; 4];
+        b.copy_from_slice(self.take(4)?);
+        Ok(u32::from_be_bytes(b))
+    }
+
+    fn u64(&mut self) -> Result<u64, TinkError> {
+        let mut b = [0u8; 8];
+        b.copy_from_slice(self.take(8)?);
+        Ok(u64::from_be_bytes(b))
+    }
+}
+
+/// Records events into an optional [`AuditTrail`] on behalf of a
+/// [`Manager`](super::Manager).
+pub(crate) struct AuditRecorder {
+    pub(crate) trail: Option<AuditTrail>,
+    pub(crate) actor: String,
+    pub(crate) clock: Arc<dyn Clock>,
+}
+
+impl Default for AuditRecorder {
+    fn default() -> Self {
+        AuditRecorder {
+            trail: None,
+            actor: String::new(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+impl AuditRecorder {
+    /// Record an event, if auditing is enabled.
+    pub(crate) fn record(&mut self, key_id: KeyId, kind: KeyEventKind) {
+        if let Some(trail) = &mut self.trail {
+            trail.events.push(KeyEvent {
+                key_id,
+                kind,
+                time: self.clock.now(),
+                actor: self.actor.clone(),
+            });
+        }
+    }
+}
@@ -0,0 +1,249 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Builder for keysets holding several keys.
+
+use super::Handle;
+use crate::{
+    registry::{CleartextLoad, CleartextSource},
+    utils::wrap_err,
+    KeyId, TinkError,
+};
+use tink_proto::{
+    key_data::KeyMaterialType, KeyData, KeyStatusType, KeyTemplate, OutputPrefixType,
+};
+
+/// `KeyIdStrategy` determines how the ID of a [`KeysetEntry`] is chosen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyIdStrategy {
+    /// A random ID that is not used by any other key in the keyset.
+    Random,
+    /// The given ID.
+    Fixed(KeyId),
+}
+
+/// Source of the key material for a [`KeysetEntry`].
+#[derive(Clone)]
+enum EntrySource {
+    Template(KeyTemplate),
+    KeyData(KeyData, OutputPrefixType),
+}
+
+/// `KeysetEntry` describes a key to be added to a keyset by a [`KeysetBuilder`].  By default the
+/// key is enabled, not primary, and has a random ID.
+#[derive(Clone)]
+pub struct KeysetEntry {
+    source: EntrySource,
+    status: KeyStatusType,
+    id: KeyIdStrategy,
+    primary: bool,
+}
+
+impl KeysetEntry {
+    /// Create an entry for a fresh key generated according to `kt`.
+    pub fn from_template(kt: &KeyTemplate) -> Self {
+        Self::new(EntrySource::Template(kt.clone()))
+    }
+
+    /// Create an entry for an existing key.  The key data is checked by creating its primitive
+    /// when the keyset is built, so the corresponding key manager must be registered.
+    ///
+    /// If the key data is not public key material, any registered
+    /// [`Gatekeeper`](crate::registry::Gatekeeper)s are consulted (with
+    /// [`CleartextSource::Import`]) when the keyset is built.
+    pub fn from_key_data(key_data: KeyData, output_prefix_type: OutputPrefixType) -> Self {
+        Self::new(EntrySource::KeyData(key_data, output_prefix_type))
+    }
+
+    /// Indicate whether the entry imports existing key material that is not public.
+    fn imports_secret(&self) -> bool {
+        match &self.source {
+            EntrySource::Template(_) => false,
+            EntrySource::KeyData(key_data, _) => {
+                key_data.key_material_type != KeyMaterialType::AsymmetricPublic as i32
+            }
+        }
+    }
+
+    fn new(source: EntrySource) -> Self {
+        KeysetEntry {
+            source,
+            status: KeyStatusType::Enabled,
+            id: KeyIdStrategy::Random,
+            primary: false,
+        }
+    }
+
+    /// Set the status of the key, which must be [`KeyStatusType::Enabled`] or
+    /// [`KeyStatusType::Disabled`].
+    pub fn with_status(mut self, status: KeyStatusType) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Give the key a fixed ID.
+    pub fn with_fixed_id(mut self, key_id: KeyId) -> Self {
+        self.id = KeyIdStrategy::Fixed(key_id);
+        self
+    }
+
+    /// Give the key a random ID (the default).
+    pub fn with_random_id(mut self) -> Self {
+        self.id = KeyIdStrategy::Random;
+        self
+    }
+
+    /// Make the key the primary key of the keyset.
+    pub fn make_primary(mut self) -> Self {
+        self.primary = true;
+        self
+    }
+}
+
+/// `KeysetBuilder` builds a keyset [`Handle`] from a list of [`KeysetEntry`]s, in the style of
+/// upstream Tink's `KeysetHandle.Builder`:
+///
+/// ```ignore
+/// let kt = tink_aead::aes128_gcm_key_template();
+/// let h = KeysetBuilder::new()
+///     .add_entry(KeysetEntry::from_template(&kt).with_fixed_id(1))
+///     .add_entry(KeysetEntry::from_template(&kt).with_fixed_id(2).make_primary())
+///     .add_entry(KeysetEntry::from_template(&kt).with_status(KeyStatusType::Disabled))
+///     .build()?;
+/// ```
+///
+/// Keys appear in the keyset in the order of their entries, and exactly one entry must be
+/// marked as primary.  Nothing is generated until [`build`](KeysetBuilder::build) is called.
+#[derive(Clone, Default)]
+pub struct KeysetBuilder {
+    entries: Vec<KeysetEntry>,
+}
+
+impl KeysetBuilder {
+    /// Create a builder with no entries.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an entry to the keyset.
+    pub fn add_entry(mut self, entry: KeysetEntry) -> Self {
+        self.entries.push(entry);
+        self
+    }
+
+    /// Return the number of entries added so far.
+    pub fn entry_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Generate or check the key material for each entry, and return a handle for the resulting
+    /// keyset.
+    ///
+    /// If any entry was created from key data that is not public key material, any registered
+    /// [`Gatekeeper`](crate::registry::Gatekeeper)s are consulted (with
+    /// [`CleartextSource::Import`]) before the handle is returned.
+    pub fn build(&self) -> Result<Handle, TinkError> {
+        let primaries = self.entries.iter().filter(|e| e.primary).count();
+        if primaries != 1 {
+            return Err(format!(
+                "KeysetBuilder: exactly one entry must be primary (found {})",
+                primaries
+            )
+            .into());
+        }
+
+        let mut fixed_ids = Vec::new();
+        for entry in &self.entries {
+            if let KeyIdStrategy::Fixed(key_id) = entry.id {
+                if fixed_ids.contains(&key_id) {
+                    return Err(format!("KeysetBuilder: duplicate key ID {}", key_id).into());
+                }
+                fixed_ids.push(key_id);
+            }
+        }
+
+        let mut ks = tink_proto::Keyset::default();
+        for (i, entry) in self.entries.iter().enumerate() {
+            let key = build_key(entry, &ks, &fixed_ids)
+                .map_err(|e| wrap_err(&format!("KeysetBuilder: entry {}", i), e))?;
+            if entry.primary {
+                ks.primary_key_id = key.key_id;
+            }
+            ks.key.push(key);
+        }
+        let h = Handle::from_keyset(ks)?;
+        if self.entries.iter().any(KeysetEntry::imports_secret) {
+            crate::registry::check_cleartext_load(&CleartextLoad {
+                source: CleartextSource::Import,
+                keyset_info: h.keyset_info(),
+            })?;
+        }
+        Ok(h)
+    }
+}
+
+impl Handle {
+    /// Return a [`KeysetBuilder`] for constructing a new keyset.
+    pub fn builder() -> KeysetBuilder {
+        KeysetBuilder::new()
+    }
+}
+
+/// Create the key for `entry`, given the keys built so far and all of the fixed key IDs.
+fn build_key(
+    entry: &KeysetEntry,
+    ks: &tink_proto::Keyset,
+    fixed_ids: &[KeyId],
+) -> Result<tink_proto::keyset::Key, TinkError> {
+    match entry.status {
+        KeyStatusType::Enabled => {}
+        KeyStatusType::Disabled if !entry.primary => {}
+        KeyStatusType::Disabled => return Err("primary key must be enabled".into()),
+        _ => return Err(format!("invalid key status {:?}", entry.status).into()),
+    }
+    let (key_data, output_prefix_type) = match &entry.source {
+        EntrySource::Template(kt) => {
+            // Custom output prefix types are allowed if registered.
+            crate::cryptofmt::effective_output_prefix_type(kt.output_prefix_type)?;
+            let key_data = crate::registry::new_key_data(kt)
+                .map_err(|e| wrap_err("cannot create KeyData", e))?;
+            (key_data, kt.output_prefix_type)
+        }
+        EntrySource::KeyData(key_data, output_prefix_type) => {
+            if *output_prefix_type == OutputPrefixType::UnknownPrefix {
+                return Err("unknown output prefix type".into());
+            }
+            crate::registry::primitive_from_key_data(key_data)
+                .map_err(|e| wrap_err("invalid key data", e))?;
+            (key_data.clone(), *output_prefix_type as i32)
+        }
+    };
+    let key_id = match entry.id {
+        KeyIdStrategy::Fixed(key_id) => key_id,
+        KeyIdStrategy::Random => loop {
+            let key_id = crate::subtle::random::get_random_uint32();
+            if !fixed_ids.contains(&key_id) && !ks.key.iter().any(|k| k.key_id == key_id) {
+                break key_id;
+            }
+        },
+    };
+    Ok(tink_proto::keyset::Key {
+        key_data: Some(key_data),
+        status: entry.status as i32,
+        key_id,
+        output_prefix_type,
+    })
+}
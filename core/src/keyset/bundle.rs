@@ -0,0 +1,313 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Compact, signed bundles of verification keys for distribution to caches.
+
+use super::Handle;
+use crate::{utils::wrap_err, KeyId, TinkError};
+use arc_swap::ArcSwapOption;
+use std::{
+    convert::TryInto,
+    sync::{Arc, Mutex},
+};
+use tink_proto::{key_data::KeyMaterialType, KeyData, KeyStatusType, Keyset, OutputPrefixType};
+
+/// Magic bytes at the start of every verification bundle.
+const BUNDLE_MAGIC: &[u8] = b"TVB";
+/// Current version of the verification bundle format.
+const BUNDLE_VERSION: u8 = 1;
+/// Prefix of the type URLs of Tink's own key types, which is omitted from bundles.
+const TINK_TYPE_URL_PREFIX: &str = "type.googleapis.com/google.crypto.tink.";
+
+/// A verification bundle whose signature has been checked; see
+/// [`Handle::verification_bundle`].
+///
+/// The bundle format is:
+/// - the magic bytes `TVB` and a version byte (1);
+/// - the serial number (8 bytes, big-endian), the primary key ID (4 bytes, big-endian) and the
+///   number of keys (2 bytes, big-endian);
+/// - for each key: its ID (4 bytes, big-endian), its output prefix type (1 byte), its type URL
+///   (1 length byte, then the URL with any `type.googleapis.com/google.crypto.tink.` prefix
+///   removed) and its serialized public key (2 length bytes, big-endian, then the key);
+/// - the signature over all of the above.
+///
+/// Only enabled keys are included, and all keys are public keys, so the key status and key
+/// material type are implied.
+pub struct VerificationBundle {
+    serial: u64,
+    handle: Handle,
+}
+
+impl VerificationBundle {
+    /// Parse `bundle`, checking its signature with `verifier`.
+    pub fn parse(bundle: &[u8], verifier: &dyn crate::Verifier) -> Result<Self, TinkError> {
+        let mut r = BundleReader { data: bundle };
+        if r.take(BUNDLE_MAGIC.len())? != BUNDLE_MAGIC {
+            return Err("verification bundle: not a verification bundle".into());
+        }
+        let version = r.u8()?;
+        if version != BUNDLE_VERSION {
+            return Err(format!("verification bundle: unsupported version {}", version).into());
+        }
+        let serial = u64::from_be_bytes(r.take(8)?.try_into().unwrap()); // safe: took 8
+        let primary_key_id = r.u32()?;
+        let key_count = r.u16()?;
+        let mut keys = Vec::with_capacity(key_count as usize);
+        for _ in 0..key_count {
+            let key_id = r.u32()?;
+            let output_prefix_type = r.u8()? as i32;
+            let type_url_len = r.u8()? as usize;
+            let type_url = std::str::from_utf8(r.take(type_url_len)?)
+                .map_err(|_| TinkError::new("verification bundle: invalid type URL"))?;
+            let value_len = r.u16()? as usize;
+            let value = r.take(value_len)?.to_vec();
+            keys.push(tink_proto::keyset::Key {
+                key_data: Some(KeyData {
+                    type_url: expand_type_url(type_url),
+                    value,
+                    key_material_type: KeyMaterialType::AsymmetricPublic as i32,
+                }),
+                status: KeyStatusType::Enabled as i32,
+                key_id,
+                output_prefix_type,
+            });
+        }
+        let body_len = bundle.len() - r.data.len();
+        verifier
+            .verify(r.data, &bundle[..body_len])
+            .map_err(|e| wrap_err("verification bundle: invalid signature", e))?;
+
+        let handle = Handle::new_with_no_secrets(Keyset {
+            primary_key_id,
+            key: keys,
+        })
+        .map_err(|e| wrap_err("verification bundle: invalid keyset", e))?;
+        Ok(VerificationBundle { serial, handle })
+    }
+
+    /// Return the serial number of the bundle.
+    pub fn serial(&self) -> u64 {
+        self.serial
+    }
+
+    /// Return a handle for the verification keys in the bundle.
+    pub fn handle(&self) -> &Handle {
+        &self.handle
+    }
+}
+
+impl Handle {
+    /// Return a verification bundle holding the public keys of the enabled keys in this keyset,
+    /// signed with `signer` and labelled with `serial`.  The keyset must hold only asymmetric
+    /// keys; for private keys, the corresponding public keys are included.
+    ///
+    /// A verification bundle is more compact than a serialized [`Keyset`], and can be checked
+    /// and loaded with [`VerificationBundle::parse`] or a [`VerificationBundleCache`].  The
+    /// serial number allows a cache to ignore bundles older than the one it holds; it would
+    /// typically be incremented whenever the keyset is rotated.
+    pub fn verification_bundle(
+        &self,
+        serial: u64,
+        signer: &dyn crate::Signer,
+    ) -> Result<Vec<u8>, TinkError> {
+        let has_private = self.keyset().key.iter().any(|key| {
+            key.key_data.as_ref().map(|kd| kd.key_material_type)
+                == Some(KeyMaterialType::AsymmetricPrivate as i32)
+        });
+        let public = if has_private {
+            self.public()
+                .map_err(|e| wrap_err("verification bundle", e))?
+        } else {
+            self.clone()
+        };
+        let ks = public.keyset();
+        let keys: Vec<_> = ks
+            .key
+            .iter()
+            .filter(|key| key.status == KeyStatusType::Enabled as i32)
+            .collect();
+        let key_count: u16 = keys
+            .len()
+            .try_into()
+            .map_err(|_| TinkError::new("verification bundle: too many keys"))?;
+
+        let mut bundle = Vec::new();
+        bundle.extend_from_slice(BUNDLE_MAGIC);
+        bundle.push(BUNDLE_VERSION);
+        bundle.extend_from_slice(&serial.to_be_bytes());
+        bundle.extend_from_slice(&ks.primary_key_id.to_be_bytes());
+        bundle.extend_from_slice(&key_count.to_be_bytes());
+        for key in keys {
+            let key_data = key
+                .key_data
+                .as_ref()
+                .ok_or_else(|| TinkError::new("verification bundle: no key data"))?;
+            if key_data.key_material_type != KeyMaterialType::AsymmetricPublic as i32 {
+                return Err(format!(
+                    "verification bundle: key {} is not an asymmetric key",
+                    key.key_id
+                )
+                .into());
+            }
+            if key.try_output_prefix_type().is_err()
+                || key.output_prefix_type == OutputPrefixType::UnknownPrefix as i32
+            {
+                return Err(format!(
+                    "verification bundle: key {} has an unsupported output prefix type",
+                    key.key_id
+                )
+                .into());
+            }
+            let type_url = compact_type_url(&key_data.type_url);
+            let type_url_len: u8 = type_url
+                .len()
+                .try_into()
+                .map_err(|_| TinkError::new("verification bundle: type URL too long"))?;
+            let value_len: u16 = key_data
+                .value
+                .len()
+                .try_into()
+                .map_err(|_| TinkError::new("verification bundle: key too large"))?;
+            bundle.extend_from_slice(&key.key_id.to_be_bytes());
+            bundle.push(key.output_prefix_type as u8);
+            bundle.push(type_url_len);
+            bundle.extend_from_slice(type_url.as_bytes());
+            bundle.extend_from_slice(&value_len.to_be_bytes());
+            bundle.extend_from_slice(&key_data.value);
+        }
+        let signature = signer
+            .sign(&bundle)
+            .map_err(|e| wrap_err("verification bundle: cannot sign", e))?;
+        bundle.extend_from_slice(&signature);
+        Ok(bundle)
+    }
+}
+
+/// `VerificationBundleCache` holds the most recent [`VerificationBundle`] loaded into it, and can
+/// be shared between threads.  Readers always see a complete bundle: a refresh replaces the
+/// cached bundle atomically, and only once the new bundle has been verified.
+pub struct VerificationBundleCache {
+    current: ArcSwapOption<VerificationBundle>,
+    // Serializes refreshes, so that the serial number check and the replacement are atomic.
+    refresh: Mutex<()>,
+}
+
+impl Default for VerificationBundleCache {
+    fn default() -> Self {
+        VerificationBundleCache::new()
+    }
+}
+
+impl VerificationBundleCache {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        VerificationBundleCache {
+            current: ArcSwapOption::new(None),
+            refresh: Mutex::new(()),
+        }
+    }
+
+    /// Verify `bundle` with `verifier` and, if its serial number is higher than that of the
+    /// cached bundle (or the cache is empty), replace the cached bundle with it.  Returns whether
+    /// the cached bundle was replaced.  A bundle with a lower serial number than the cached
+    /// bundle is rejected with an error, as it may be an attempt to roll back a key rotation.
+    pub fn refresh(
+        &self,
+        bundle: &[u8],
+        verifier: &dyn crate::Verifier,
+    ) -> Result<bool, TinkError> {
+        let bundle = VerificationBundle::parse(bundle, verifier)?;
+        let _guard = self
+            .refresh
+            .lock()
+            .map_err(|_| TinkError::new("verification bundle: cache lock poisoned"))?;
+        if let Some(current) = self.current.load_full() {
+            if bundle.serial < current.serial {
+                return Err(format!(
+                    "verification bundle: serial {} is older than cached serial {}",
+                    bundle.serial, current.serial
+                )
+                .into());
+            }
+            if bundle.serial == current.serial {
+                return Ok(false);
+            }
+        }
+        self.current.store(Some(Arc::new(bundle)));
+        Ok(true)
+    }
+
+    /// Return the cached bundle, if any.
+    pub fn current(&self) -> Option<Arc<VerificationBundle>> {
+        self.current.load_full()
+    }
+
+    /// Return the serial number of the cached bundle, if any.
+    pub fn serial(&self) -> Option<u64> {
+        self.current().map(|b| b.serial)
+    }
+
+    /// Return a handle for the verification keys in the cached bundle, if any.
+    pub fn handle(&self) -> Option<Handle> {
+        self.current().map(|b| b.handle.clone())
+    }
+}
+
+/// Remove the prefix shared by Tink's own type URLs.  Such type URLs never contain a `/` once
+/// the prefix is removed, which distinguishes them from other type URLs.
+fn compact_type_url(type_url: &str) -> &str {
+    match type_url.strip_prefix(TINK_TYPE_URL_PREFIX) {
+        Some(name) if !name.contains('/') => name,
+        _ => type_url,
+    }
+}
+
+/// Reverse [`compact_type_url`].
+fn expand_type_url(type_url: &str) -> String {
+    if type_url.contains('/') {
+        type_url.to_string()
+    } else {
+        format!("{}{}", TINK_TYPE_URL_PREFIX, type_url)
+    }
+}
+
+/// Reader for the fields of a verification bundle.
+struct BundleReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> BundleReader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], TinkError> {
+        if self.data.len() < n {
+            return Err("verification bundle: truncated".into());
+        }
+        let (field, rest) = self.data.split_at(n);
+        self.data = rest;
+        Ok(field)
+    }
+
+    fn u8(&mut self) -> Result<u8, TinkError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, TinkError> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into().unwrap())) // safe: took 2
+    }
+
+    fn u32(&mut self) -> Result<KeyId, TinkError> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap())) // safe: took 4
+    }
+}
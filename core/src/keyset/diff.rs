@@ -0,0 +1,145 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Comparison of keysets, for planning keyset rollouts.
+
+use crate::{KeyId, TinkError};
+use std::collections::HashMap;
+use tink_proto::{keyset_info::KeyInfo, KeyStatusType, KeysetInfo};
+
+/// A change in the status of a key that is present in both keysets being compared.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyStatusChange {
+    pub key_id: KeyId,
+    pub old_status: KeyStatusType,
+    pub new_status: KeyStatusType,
+}
+
+/// The differences between two keysets, as returned by [`diff`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KeysetDiff {
+    /// Keys present in the new keyset but not the old one.
+    pub added: Vec<KeyInfo>,
+    /// Keys present in the old keyset but not the new one.
+    pub removed: Vec<KeyInfo>,
+    /// Keys present in both keysets whose status differs.
+    pub status_changed: Vec<KeyStatusChange>,
+    /// The old and new primary key IDs, if the primary key changed.
+    pub primary_changed: Option<(KeyId, KeyId)>,
+}
+
+impl KeysetDiff {
+    /// Indicate whether the two keysets have the same keys, statuses and primary key.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.status_changed.is_empty()
+            && self.primary_changed.is_none()
+    }
+}
+
+/// Describe the differences between the `old` and `new` keysets.  Keys are matched by key ID,
+/// and the entries in each list of the result are sorted by key ID.
+pub fn diff(old: &KeysetInfo, new: &KeysetInfo) -> KeysetDiff {
+    let old_keys = index_keys(old);
+    let new_keys = index_keys(new);
+
+    let mut result = KeysetDiff::default();
+    for (key_id, new_key) in &new_keys {
+        match old_keys.get(key_id) {
+            None => result.added.push((*new_key).clone()),
+            Some(old_key) if old_key.status != new_key.status => {
+                result.status_changed.push(KeyStatusChange {
+                    key_id: *key_id,
+                    old_status: status(old_key),
+                    new_status: status(new_key),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    for (key_id, old_key) in &old_keys {
+        if !new_keys.contains_key(key_id) {
+            result.removed.push((*old_key).clone());
+        }
+    }
+    if old.primary_key_id != new.primary_key_id {
+        result.primary_changed = Some((old.primary_key_id, new.primary_key_id));
+    }
+
+    result.added.sort_by_key(|k| k.key_id);
+    result.removed.sort_by_key(|k| k.key_id);
+    result.status_changed.sort_by_key(|c| c.key_id);
+    result
+}
+
+/// A key whose ciphertexts (or MACs/signatures) could be handled with the old keyset but not with
+/// the new one, as returned by [`undecryptable_prefixes`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UndecryptablePrefix {
+    pub key_id: KeyId,
+    /// The output prefix of the key; empty for keys with a RAW output prefix.
+    pub prefix: Vec<u8>,
+}
+
+/// Return the keys that are enabled in the `old` keyset but are missing or not enabled in the
+/// `new` keyset, together with their output prefixes.  Data produced by these keys will no longer
+/// be decryptable (or verifiable) once the new keyset is deployed, unless another enabled key in
+/// the new keyset has the same key material.  The result is sorted by key ID.
+pub fn undecryptable_prefixes(
+    old: &KeysetInfo,
+    new: &KeysetInfo,
+) -> Result<Vec<UndecryptablePrefix>, TinkError> {
+    let new_keys = index_keys(new);
+    let mut result = Vec::new();
+    for old_key in &old.key_info {
+        if status(old_key) != KeyStatusType::Enabled {
+            continue;
+        }
+        let still_enabled = new_keys
+            .get(&old_key.key_id)
+            .map(|k| status(k) == KeyStatusType::Enabled)
+            .unwrap_or(false);
+        if !still_enabled {
+            result.push(UndecryptablePrefix {
+                key_id: old_key.key_id,
+                prefix: key_info_prefix(old_key)?,
+            });
+        }
+    }
+    result.sort_by_key(|p| p.key_id);
+    Ok(result)
+}
+
+/// Index the keys of a keyset by key ID.
+fn index_keys(info: &KeysetInfo) -> HashMap<KeyId, &KeyInfo> {
+    info.key_info.iter().map(|k| (k.key_id, k)).collect()
+}
+
+/// Return the status of a key, treating unrecognized values as unknown.
+fn status(key: &KeyInfo) -> KeyStatusType {
+    key.try_status().unwrap_or(KeyStatusType::UnknownStatus)
+}
+
+/// Return the output prefix of the key described by `key`.
+fn key_info_prefix(key: &KeyInfo) -> Result<Vec<u8>, TinkError> {
+    crate::cryptofmt::output_prefix(&tink_proto::keyset::Key {
+        key_data: None,
+        status: key.status,
+        key_id: key.key_id,
+        output_prefix_type: key.output_prefix_type,
+    })
+}
@@ -0,0 +1,96 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Fingerprints of public key material.
+
+use crate::{utils::wrap_err, TinkError};
+use sha2::{Digest, Sha256};
+use tink_proto::{key_data::KeyMaterialType, prost::Message, KeyData};
+
+/// Size in bytes of a public key fingerprint.
+pub const FINGERPRINT_SIZE: usize = 32;
+
+const ECDSA_PUBLIC_KEY_TYPE_URL: &str = "type.googleapis.com/google.crypto.tink.EcdsaPublicKey";
+const ED25519_PUBLIC_KEY_TYPE_URL: &str = "type.googleapis.com/google.crypto.tink.Ed25519PublicKey";
+const ECIES_PUBLIC_KEY_TYPE_URL: &str =
+    "type.googleapis.com/google.crypto.tink.EciesAeadHkdfPublicKey";
+
+/// Return the fingerprint of the public key held in `key_data`: the SHA-256 digest of a canonical
+/// serialization of the key type, its parameters and its public key material.
+///
+/// The fingerprint does not depend on the key ID, the key status, the output prefix type or the
+/// key version, so the same public key material has the same fingerprint in every keyset that
+/// holds it.  Curve point coordinates are normalized by removing leading zero bytes, so keys
+/// written by implementations that encode coordinates as signed big-endian integers have the
+/// same fingerprint as keys written by implementations that use fixed-size encodings.
+///
+/// Only ECDSA, Ed25519 and ECIES-AEAD-HKDF public keys are supported.
+pub fn public_key_fingerprint(key_data: &KeyData) -> Result<Vec<u8>, TinkError> {
+    if key_data.key_material_type != KeyMaterialType::AsymmetricPublic as i32 {
+        return Err("public_key_fingerprint: not a public key".into());
+    }
+    let mut hasher = Sha256::new();
+    add_field(&mut hasher, key_data.type_url.as_bytes());
+    match key_data.type_url.as_str() {
+        ECDSA_PUBLIC_KEY_TYPE_URL => {
+            let key = tink_proto::EcdsaPublicKey::decode(key_data.value.as_ref())
+                .map_err(|e| wrap_err("public_key_fingerprint: invalid key", e))?;
+            add_field(&mut hasher, &encode_params(&key.params)?);
+            add_field(&mut hasher, strip_leading_zeros(&key.x));
+            add_field(&mut hasher, strip_leading_zeros(&key.y));
+        }
+        ED25519_PUBLIC_KEY_TYPE_URL => {
+            let key = tink_proto::Ed25519PublicKey::decode(key_data.value.as_ref())
+                .map_err(|e| wrap_err("public_key_fingerprint: invalid key", e))?;
+            add_field(&mut hasher, &key.key_value);
+        }
+        ECIES_PUBLIC_KEY_TYPE_URL => {
+            let key = tink_proto::EciesAeadHkdfPublicKey::decode(key_data.value.as_ref())
+                .map_err(|e| wrap_err("public_key_fingerprint: invalid key", e))?;
+            add_field(&mut hasher, &encode_params(&key.params)?);
+            add_field(&mut hasher, strip_leading_zeros(&key.x));
+            add_field(&mut hasher, strip_leading_zeros(&key.y));
+        }
+        type_url => {
+            return Err(format!("public_key_fingerprint: unsupported key type {}", type_url).into())
+        }
+    }
+    Ok(hasher.finalize().to_vec())
+}
+
+/// Add a length-prefixed field to the canonical serialization.
+fn add_field(hasher: &mut Sha256, data: &[u8]) {
+    hasher.update((data.len() as u64).to_be_bytes());
+    hasher.update(data);
+}
+
+/// Serialize key parameters.  The encoding produced by `prost` emits fields in tag order and
+/// omits default values, so is deterministic for a given set of parameters.
+fn encode_params<T: Message>(params: &Option<T>) -> Result<Vec<u8>, TinkError> {
+    let params = params
+        .as_ref()
+        .ok_or_else(|| TinkError::new("public_key_fingerprint: missing key params"))?;
+    let mut buf = Vec::with_capacity(params.encoded_len());
+    params
+        .encode(&mut buf)
+        .map_err(|e| wrap_err("public_key_fingerprint: failed to encode params", e))?;
+    Ok(buf)
+}
+
+fn strip_leading_zeros(data: &[u8]) -> &[u8] {
+    let start = data.iter().position(|b| *b != 0).unwrap_or(data.len());
+    &data[start..]
+}
@@ -16,16 +16,61 @@
 
 //! Handle wrapper for keysets.
 
-use crate::{utils::wrap_err, TinkError};
-use std::sync::Arc;
+use crate::{
+    keyset::{
+        lazy::{key_primitive, lazy_primitive, LazySource},
+        primitive_cache::PrimitiveCache,
+        Clock, KeyParsing, KeyUsage, KeyValidity, KeyVersionPolicy, SystemClock, UsageCheck,
+        UsageCounters, UsageLimit, UsageStats, ValidityCheck,
+    },
+    utils::wrap_err,
+    KeyId, TinkError,
+};
+use std::{collections::HashMap, sync::Arc};
 use tink_proto::{key_data::KeyMaterialType, prost::Message, Keyset, KeysetInfo};
 
 /// `Handle` provides access to a [`Keyset`] protobuf, to limit the exposure
 /// of actual protocol buffers that hold sensitive key material.
+///
+/// A `Handle` may also hold in-memory annotations for the keys in the keyset, such as their
+/// [`KeyValidity`] periods.  These annotations are not included when the keyset is written out.
+///
+/// A `Handle` may be restricted to particular operations (see [`Handle::restrict`]), and may
+/// count and limit the use of its keys (see [`Handle::enable_usage_counters`]).
+///
+/// A `Handle` is `Send` and `Sync`, and cloning it is cheap because the parsed keyset is held
+/// behind an [`Arc`] and shared between clones; there is no need to wrap it in a `Mutex` to share
+/// it between threads (see [`Handle::into_shared`]).  The primitive set built by
+/// [`Handle::primitives`] is cached and shared between clones too.  As primitives are not required
+/// to be `Send`/`Sync`, the cache is kept per thread: each thread builds the primitives for a
+/// handle once, and later calls on that thread return a copy of them.  Changing the handle (for
+/// example with [`Handle::set_key_validity`]) starts a new cache.
+#[derive(Clone)]
 pub struct Handle {
-    ks: Keyset,
+    ks: Arc<Keyset>,
+    validity: HashMap<KeyId, KeyValidity>,
+    clock: Arc<dyn Clock>,
+    usage: KeyUsage,
+    limits: HashMap<KeyId, UsageLimit>,
+    counters: Option<Arc<UsageCounters>>,
+    version_policy: KeyVersionPolicy,
+    key_parsing: KeyParsing,
+    primitives: Arc<PrimitiveCache>,
 }
 
+/// Information about a key in a keyset, together with the fingerprint of its public key material;
+/// see [`Handle::keyset_info_with_fingerprints`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct FingerprintedKeyInfo {
+    pub info: tink_proto::keyset_info::KeyInfo,
+    /// SHA-256 fingerprint of the public key material, or `None` for keys without public key
+    /// material.
+    pub fingerprint: Option<Vec<u8>>,
+}
+
+/// A keyset [`Handle`] that can be shared between threads; see [`Handle::into_shared`].
+pub type SharedHandle = Arc<Handle>;
+
 impl Handle {
     /// Create a keyset handle that contains a single fresh key generated according
     /// to the given [`KeyTemplate`](tink_proto::KeyTemplate).
@@ -40,9 +85,7 @@ impl Handle {
     /// Create a new instance of [`Handle`] using the given [`Keyset`] which does not contain any
     /// secret key material.
     pub fn new_with_no_secrets(ks: Keyset) -> Result<Self, TinkError> {
-        let h = Handle {
-            ks: validate_keyset(ks)?,
-        };
+        let h = Handle::new_validated(ks)?;
         if h.has_secrets()? {
             // If you need to do this, you have to use `tink_core::keyset::insecure::read()`
             // instead.
@@ -72,11 +115,91 @@ impl Handle {
     {
         let encrypted_keyset = reader.read_encrypted()?;
         let ks = decrypt(&encrypted_keyset, master_key, associated_data)?;
-        Ok(Handle {
-            ks: validate_keyset(ks)?,
+        crate::trace::keyset_io("read_encrypted", &ks);
+        Handle::new_validated(ks)
+    }
+
+    /// Attempt to create a [`Handle`] from an encrypted keyset obtained via a
+    /// [`Reader`](crate::keyset::Reader), decrypting it with the key encryption key at `kek_uri`.
+    ///
+    /// The [`KmsClient`](crate::registry::KmsClient) for `kek_uri` is looked up in the registry;
+    /// an error is returned if no registered client supports the URI.
+    pub fn read_encrypted_with_kms<T>(reader: &mut T, kek_uri: &str) -> Result<Self, TinkError>
+    where
+        T: crate::keyset::Reader,
+    {
+        let client = crate::registry::get_kms_client(kek_uri).map_err(|e| {
+            wrap_err(
+                &format!("keyset::Handle: no KMS client registered for {}", kek_uri),
+                e,
+            )
+        })?;
+        let master_key = client.get_aead(kek_uri).map_err(|e| {
+            wrap_err(
+                &format!("keyset::Handle: cannot obtain AEAD for {}", kek_uri),
+                e,
+            )
+        })?;
+        Self::read(reader, master_key).map_err(|e| {
+            wrap_err(
+                &format!(
+                    "keyset::Handle: cannot read keyset encrypted with {}",
+                    kek_uri
+                ),
+                e,
+            )
         })
     }
 
+    /// Attempt to create a [`Handle`] from an encrypted keyset obtained via a
+    /// [`Reader`](crate::keyset::Reader), using the provided associated data and consulting the
+    /// given [`UnwrapCache`](crate::keyset::UnwrapCache).
+    ///
+    /// In [`UnwrapCacheMode::Fallback`](crate::keyset::UnwrapCacheMode::Fallback) mode the master
+    /// key is always invoked, and a cached keyset is only used if decryption fails.  In
+    /// [`UnwrapCacheMode::Eager`](crate::keyset::UnwrapCacheMode::Eager) mode a cached keyset is
+    /// used without invoking the master key.  Successful decryptions are stored in the cache.
+    /// Errors from the cache (such as a failing [`UnwrapStore`](crate::keyset::UnwrapStore)) are
+    /// logged (with the `tracing` feature) and otherwise ignored.
+    pub fn read_with_unwrap_cache<T>(
+        reader: &mut T,
+        master_key: Box<dyn crate::Aead>,
+        associated_data: &[u8],
+        cache: &crate::keyset::UnwrapCache,
+    ) -> Result<Self, TinkError>
+    where
+        T: crate::keyset::Reader,
+    {
+        let encrypted_keyset = reader.read_encrypted()?;
+        let ciphertext = &encrypted_keyset.encrypted_keyset;
+        // Failures of the cache itself are logged and otherwise ignored, so that a broken cache
+        // never prevents use of the master key.
+        if cache.mode() == crate::keyset::UnwrapCacheMode::Eager {
+            match cache.get(ciphertext, associated_data) {
+                Ok(Some(ks)) => return Handle::new_validated(ks),
+                Ok(None) => {}
+                Err(_) => crate::trace::unwrap_cache_failed("get"),
+            }
+        }
+        let ks = match decrypt(&encrypted_keyset, master_key, associated_data) {
+            Ok(ks) => {
+                if cache.insert(ciphertext, associated_data, &ks).is_err() {
+                    crate::trace::unwrap_cache_failed("insert");
+                }
+                ks
+            }
+            Err(e) => match cache.get(ciphertext, associated_data) {
+                Ok(Some(ks)) => ks,
+                Ok(None) => return Err(e),
+                Err(_) => {
+                    crate::trace::unwrap_cache_failed("get");
+                    return Err(e);
+                }
+            },
+        };
+        Handle::new_validated(ks)
+    }
+
     /// Attempt to create a [`Handle`] from a keyset obtained via a
     /// [`Reader`](crate::keyset::Reader).
     pub fn read_with_no_secrets<T>(reader: &mut T) -> Result<Self, TinkError>
@@ -84,7 +207,9 @@ impl Handle {
         T: crate::keyset::Reader,
     {
         let ks = reader.read()?;
-        Handle::new_with_no_secrets(ks)
+        let handle = Handle::new_with_no_secrets(ks)?;
+        crate::trace::keyset_io("read_no_secrets", handle.keyset());
+        Ok(handle)
     }
 
     /// Return a [`Handle`] of the public keys if the managed keyset contains private keys.
@@ -109,7 +234,17 @@ impl Handle {
             primary_key_id: self.ks.primary_key_id,
             key: pub_keys,
         };
-        Ok(Handle { ks })
+        Ok(Handle {
+            ks: Arc::new(ks),
+            validity: self.validity.clone(),
+            clock: self.clock.clone(),
+            usage: self.usage,
+            limits: self.limits.clone(),
+            counters: self.counters.clone(),
+            version_policy: self.version_policy,
+            key_parsing: self.key_parsing,
+            primitives: Arc::default(),
+        })
     }
 
     /// Encrypts and writes the enclosed [`Keyset`].
@@ -134,8 +269,13 @@ impl Handle {
     where
         T: super::Writer,
     {
+        if self.usage != KeyUsage::Unrestricted {
+            return Err("keyset::Handle: cannot export usage-restricted keyset".into());
+        }
         let encrypted = encrypt(&self.ks, master_key, associated_data)?;
-        writer.write_encrypted(&encrypted)
+        writer.write_encrypted(&encrypted)?;
+        crate::trace::keyset_io("write_encrypted", &self.ks);
+        Ok(())
     }
 
     /// Export the keyset in `h` to the given [`Writer`](super::Writer) returning an error if the
@@ -145,9 +285,12 @@ impl Handle {
         T: super::Writer,
     {
         if self.has_secrets()? {
+            crate::trace::keyset_io_failed("write_no_secrets", "keyset contains secret keys");
             Err("exporting unencrypted secret key material is forbidden".into())
         } else {
-            w.write(&self.ks)
+            w.write(&self.ks)?;
+            crate::trace::keyset_io("write_no_secrets", &self.ks);
+            Ok(())
         }
     }
 
@@ -157,6 +300,9 @@ impl Handle {
     ///
     /// The returned set is usually later "wrapped" into a class that implements the corresponding
     /// [`Primitive`](crate::Primitive) interface.
+    ///
+    /// The set is built once per thread for a handle and its clones, and copies of it are returned
+    /// subsequently (until the handle is changed or the registered key managers change).
     pub fn primitives(&self) -> Result<crate::primitiveset::PrimitiveSet, TinkError> {
         self.primitives_with_key_manager(None)
     }
@@ -173,14 +319,35 @@ impl Handle {
     ///
     /// The returned set is usually later "wrapped" into a class that implements the corresponding
     /// [`Primitive`](crate::Primitive)-interface.
+    ///
+    /// If the handle is [restricted](Handle::restrict), the primitives in the set only perform the
+    /// permitted operations, and keys whose primitives only perform forbidden operations give an
+    /// error.
+    ///
+    /// Without a custom key manager, the set is cached as for [`Handle::primitives`].
     pub fn primitives_with_key_manager(
         &self,
         km: Option<Arc<dyn crate::registry::KeyManager>>,
+    ) -> Result<crate::primitiveset::PrimitiveSet, TinkError> {
+        match km {
+            None => PrimitiveCache::get_or_build(&self.primitives, || self.build_primitives(None)),
+            Some(km) => self.build_primitives(Some(km)),
+        }
+    }
+
+    /// Build the set of primitives for the enabled keys in the keyset.
+    fn build_primitives(
+        &self,
+        km: Option<Arc<dyn crate::registry::KeyManager>>,
     ) -> Result<crate::primitiveset::PrimitiveSet, TinkError> {
         super::validate(&self.ks)
             .map_err(|e| wrap_err("primitives_with_key_manager: invalid keyset", e))?;
         let mut primitive_set = crate::primitiveset::PrimitiveSet::new();
-        for key in &self.ks.key {
+        primitive_set.usage = self.usage;
+        // With lazy parsing, the first primitive created for each key type shows which kind of
+        // primitive the remaining keys of that type produce.
+        let mut kinds: HashMap<&str, crate::Primitive> = HashMap::new();
+        for (index, key) in self.ks.key.iter().enumerate() {
             if key.status != tink_proto::KeyStatusType::Enabled as i32 {
                 continue;
             }
@@ -188,27 +355,220 @@ impl Handle {
                 .key_data
                 .as_ref()
                 .ok_or_else(|| TinkError::new("primitives_with_key_manager: no key_data"))?;
-            let primitive = match &km {
-                Some(km) if km.does_support(&key_data.type_url) => km.primitive(&key_data.value),
-                Some(_) | None => crate::registry::primitive_from_key_data(key_data),
-            }
-            .map_err(|e| {
-                wrap_err(
-                    "primitives_with_key_manager: cannot get primitive from key",
-                    e,
-                )
-            })?;
+            let is_primary = key.key_id == self.ks.primary_key_id;
+            let like = match self.key_parsing {
+                KeyParsing::Lazy if !is_primary => kinds.get(key_data.type_url.as_str()),
+                KeyParsing::Lazy | KeyParsing::Eager => None,
+            };
+            let (primitive, deferred) = match like {
+                Some(like) => (
+                    lazy_primitive(
+                        like,
+                        LazySource {
+                            ks: self.ks.clone(),
+                            index,
+                            km: km.clone(),
+                            version_policy: self.version_policy,
+                            prefix_type: crate::cryptofmt::effective_output_prefix_type(
+                                key.output_prefix_type,
+                            )
+                            .map_err(|e| wrap_err("primitives_with_key_manager", e))?,
+                        },
+                    ),
+                    true,
+                ),
+                None => {
+                    let primitive = key_primitive(key_data, km.as_ref(), self.version_policy)
+                        .map_err(|e| {
+                            wrap_err(
+                                "primitives_with_key_manager: cannot get primitive from key",
+                                e,
+                            )
+                        })?;
+                    if self.key_parsing == KeyParsing::Lazy {
+                        kinds
+                            .entry(&key_data.type_url)
+                            .or_insert_with(|| primitive.clone());
+                    }
+                    (primitive, false)
+                }
+            };
+            // Enforce the usage restriction on the primitives themselves, so that it also applies
+            // to callers that use the primitive set directly.
+            let primitive = super::restrict_primitive(primitive, self.usage)
+                .map_err(|e| wrap_err("primitives_with_key_manager", e))?;
 
             let entry = primitive_set
                 .add(primitive, key)
                 .map_err(|e| wrap_err("primitives_with_key_manager: cannot add primitive", e))?;
-            if key.key_id == self.ks.primary_key_id {
+            if !deferred {
+                crate::trace::primitive_created(
+                    key.key_id,
+                    &key_data.type_url,
+                    entry.prefix_type,
+                    is_primary,
+                );
+            }
+            if is_primary {
                 primitive_set.primary = Some(entry.clone());
+                primitive_set.primary_validity =
+                    self.validity
+                        .get(&key.key_id)
+                        .map(|validity| ValidityCheck {
+                            validity: *validity,
+                            clock: self.clock.clone(),
+                        });
+                primitive_set.primary_usage = self.counters.as_ref().map(|counters| UsageCheck {
+                    key_id: key.key_id,
+                    limit: self.limits.get(&key.key_id).copied(),
+                    counters: counters.clone(),
+                });
             }
         }
         Ok(primitive_set)
     }
 
+    /// Annotate the key with the given ID with a validity period.  Primitives subsequently
+    /// created from this handle will refuse to create new ciphertexts, MACs or signatures with
+    /// the key if it is primary and the current time is outside the validity period; decryption
+    /// and verification are unaffected.
+    ///
+    /// The annotation is process-local: it is held only by this handle (and handles derived from
+    /// it), is not part of the keyset, and so is not persisted when the keyset is written out.  It
+    /// needs to be applied again after the keyset is read back.
+    pub fn set_key_validity(
+        &mut self,
+        key_id: KeyId,
+        validity: KeyValidity,
+    ) -> Result<(), TinkError> {
+        if !self.ks.key.iter().any(|k| k.key_id == key_id) {
+            return Err(format!("keyset::Handle: key {} not found", key_id).into());
+        }
+        self.validity.insert(key_id, validity);
+        self.primitives = Arc::default();
+        Ok(())
+    }
+
+    /// Return the validity period annotation for the key with the given ID, if any.
+    pub fn key_validity(&self, key_id: KeyId) -> Option<KeyValidity> {
+        self.validity.get(&key_id).copied()
+    }
+
+    /// Enable counting of key usage for primitives subsequently created from this handle.  The
+    /// counts are shared with clones of the handle made after this call.  Each operation that
+    /// creates a new ciphertext, MAC or signature with the primary key is counted, together with
+    /// the length of its input; streaming operations are counted without their length.  Counting
+    /// is off by default.
+    ///
+    /// The counts are process-local: they are held in memory, are not part of the keyset, and so
+    /// are not persisted when the keyset is written out.  Applications that need limits to hold
+    /// across restarts must record [`key_usage_stats`](Handle::key_usage_stats) themselves.
+    pub fn enable_usage_counters(&mut self) {
+        if self.counters.is_none() {
+            self.counters = Some(Arc::new(UsageCounters::default()));
+            self.primitives = Arc::default();
+        }
+    }
+
+    /// Return the usage so far of the key with the given ID, or `None` if usage counting is not
+    /// enabled (see [`Handle::enable_usage_counters`]).
+    pub fn key_usage_stats(&self, key_id: KeyId) -> Option<UsageStats> {
+        self.counters.as_ref().map(|c| c.stats(key_id))
+    }
+
+    /// Limit the use of the key with the given ID, enabling usage counting if necessary.
+    /// Primitives subsequently created from this handle will refuse to create new ciphertexts,
+    /// MACs or signatures with the key once the limit would be exceeded; decryption and
+    /// verification are unaffected.  For example, [`UsageLimit::aes_gcm`] enforces the NIST
+    /// limit on invocations of AES-GCM with random nonces.
+    ///
+    /// As with the usage counts, the limit is process-local and is not persisted with the keyset.
+    pub fn set_key_usage_limit(
+        &mut self,
+        key_id: KeyId,
+        limit: UsageLimit,
+    ) -> Result<(), TinkError> {
+        if !self.ks.key.iter().any(|k| k.key_id == key_id) {
+            return Err(format!("keyset::Handle: key {} not found", key_id).into());
+        }
+        self.enable_usage_counters();
+        self.limits.insert(key_id, limit);
+        self.primitives = Arc::default();
+        Ok(())
+    }
+
+    /// Return the usage limit annotation for the key with the given ID, if any.
+    pub fn key_usage_limit(&self, key_id: KeyId) -> Option<UsageLimit> {
+        self.limits.get(&key_id).copied()
+    }
+
+    /// Return a new [`Handle`] for the same keyset, whose primitives are restricted to the
+    /// operations permitted by `usage`.  A handle that is already restricted cannot be given a
+    /// different restriction, and a restricted handle cannot be written out (in encrypted form)
+    /// or used to create a [`Manager`](crate::keyset::Manager) with fewer restrictions.
+    pub fn restrict(&self, usage: KeyUsage) -> Result<Self, TinkError> {
+        if self.usage != KeyUsage::Unrestricted && self.usage != usage {
+            return Err("keyset::Handle: cannot change usage restriction".into());
+        }
+        Ok(Handle {
+            ks: self.ks.clone(),
+            validity: self.validity.clone(),
+            clock: self.clock.clone(),
+            usage,
+            limits: self.limits.clone(),
+            counters: self.counters.clone(),
+            version_policy: self.version_policy,
+            key_parsing: self.key_parsing,
+            primitives: Arc::default(),
+        })
+    }
+
+    /// Consume the `Handle` and return a [`SharedHandle`], suitable for storing in state that is
+    /// shared between threads (for example, the application state of a web framework).  Clones of
+    /// the `SharedHandle` share the keyset and its cached primitives.
+    pub fn into_shared(self) -> SharedHandle {
+        Arc::new(self)
+    }
+
+    /// Return the operations permitted for primitives created from this handle.
+    pub fn usage(&self) -> KeyUsage {
+        self.usage
+    }
+
+    /// Set the [`Clock`] used to check key validity periods for primitives subsequently created
+    /// from this handle.  By default the system time is used.
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+        self.primitives = Arc::default();
+    }
+
+    /// Set the [`KeyVersionPolicy`] for keys with a higher version than their key manager
+    /// supports, for primitives subsequently created from this handle.  By default such keys are
+    /// rejected.
+    pub fn set_key_version_policy(&mut self, policy: KeyVersionPolicy) {
+        self.version_policy = policy;
+        self.primitives = Arc::default();
+    }
+
+    /// Return the [`KeyVersionPolicy`] for primitives created from this handle.
+    pub fn key_version_policy(&self) -> KeyVersionPolicy {
+        self.version_policy
+    }
+
+    /// Set when the key material of each key is parsed, for primitives subsequently created from
+    /// this handle.  By default ([`KeyParsing::Eager`]) every enabled key is parsed when the
+    /// primitive is created; [`KeyParsing::Lazy`] defers parsing of non-primary keys until they
+    /// are first used, which speeds up creating primitives from very large keysets.
+    pub fn set_key_parsing(&mut self, parsing: KeyParsing) {
+        self.key_parsing = parsing;
+        self.primitives = Arc::default();
+    }
+
+    /// Return the [`KeyParsing`] policy for primitives created from this handle.
+    pub fn key_parsing(&self) -> KeyParsing {
+        self.key_parsing
+    }
+
     /// Check if the keyset handle contains any key material considered secret.  Both symmetric keys
     /// and the private key of an asymmetric crypto system are considered secret keys. Also
     /// returns true when encountering any errors.
@@ -217,13 +577,13 @@ impl Handle {
         for k in &self.ks.key {
             match &k.key_data {
                 None => return Err("invalid keyset".into()),
-                Some(kd) => match KeyMaterialType::from_i32(kd.key_material_type) {
-                    Some(KeyMaterialType::UnknownKeymaterial) => result = true,
-                    Some(KeyMaterialType::Symmetric) => result = true,
-                    Some(KeyMaterialType::AsymmetricPrivate) => result = true,
-                    Some(KeyMaterialType::AsymmetricPublic) => {}
-                    Some(KeyMaterialType::Remote) => {}
-                    None => return Err("invalid key material type".into()),
+                Some(kd) => match kd.try_key_material_type() {
+                    Ok(KeyMaterialType::UnknownKeymaterial) => result = true,
+                    Ok(KeyMaterialType::Symmetric) => result = true,
+                    Ok(KeyMaterialType::AsymmetricPrivate) => result = true,
+                    Ok(KeyMaterialType::AsymmetricPublic) => {}
+                    Ok(KeyMaterialType::Remote) => {}
+                    Err(_) => return Err("invalid key material type".into()),
                 },
             }
         }
@@ -236,9 +596,42 @@ impl Handle {
         get_keyset_info(&self.ks)
     }
 
+    /// Return the [`KeyInfo`](tink_proto::keyset_info::KeyInfo) entries of the managed keyset,
+    /// each annotated with the fingerprint of its public key material (see
+    /// [`public_key_fingerprint`](super::public_key_fingerprint)).  For private keys, the
+    /// fingerprint is that of the corresponding public key, so a private keyset and the public
+    /// keyset derived from it have matching fingerprints.  Keys that have no public key material
+    /// (symmetric keys, and destroyed keys) have no fingerprint.
+    pub fn keyset_info_with_fingerprints(&self) -> Result<Vec<FingerprintedKeyInfo>, TinkError> {
+        let mut result = Vec::with_capacity(self.ks.key.len());
+        for key in &self.ks.key {
+            let fingerprint = match &key.key_data {
+                Some(kd) if kd.key_material_type == KeyMaterialType::AsymmetricPublic as i32 => {
+                    Some(super::public_key_fingerprint(kd)?)
+                }
+                Some(kd) if kd.key_material_type == KeyMaterialType::AsymmetricPrivate as i32 => {
+                    let pub_key_data =
+                        public_key_data(kd).map_err(|e| wrap_err("keyset::Handle", e))?;
+                    Some(super::public_key_fingerprint(&pub_key_data)?)
+                }
+                _ => None,
+            };
+            result.push(FingerprintedKeyInfo {
+                info: get_key_info(key),
+                fingerprint,
+            });
+        }
+        Ok(result)
+    }
+
+    /// Return a reference to the enclosed [`Keyset`].
+    pub(crate) fn keyset(&self) -> &Keyset {
+        &self.ks
+    }
+
     /// Consume the `Handle` and return the enclosed [`Keyset`].
     pub(crate) fn into_inner(self) -> Keyset {
-        self.ks
+        Arc::try_unwrap(self.ks).unwrap_or_else(|ks| (*ks).clone())
     }
 
     /// Return a copy of the enclosed [`Keyset`]; for internal
@@ -246,14 +639,27 @@ impl Handle {
     #[cfg(feature = "insecure")]
     #[cfg_attr(docsrs, doc(cfg(feature = "insecure")))]
     pub(crate) fn clone_keyset(&self) -> Keyset {
-        self.ks.clone()
+        (*self.ks).clone()
     }
 
     /// Create a `Handle` from a [`Keyset`].  Implemented as a standalone method rather than
     /// as an `impl` of the `From` trait so visibility can be restricted.
     pub(crate) fn from_keyset(ks: Keyset) -> Result<Self, TinkError> {
+        Handle::new_validated(ks)
+    }
+
+    /// Create a `Handle` with no annotations from a [`Keyset`], after checking that it is valid.
+    fn new_validated(ks: Keyset) -> Result<Self, TinkError> {
         Ok(Handle {
-            ks: validate_keyset(ks)?,
+            ks: Arc::new(validate_keyset(ks)?),
+            validity: HashMap::new(),
+            clock: Arc::new(SystemClock),
+            usage: KeyUsage::Unrestricted,
+            limits: HashMap::new(),
+            counters: None,
+            version_policy: KeyVersionPolicy::Reject,
+            key_parsing: KeyParsing::Eager,
+            primitives: Arc::default(),
         })
     }
 }
@@ -264,9 +670,9 @@ fn validate_keyset(ks: Keyset) -> Result<Keyset, TinkError> {
         match &k.key_data {
             None if k.status == tink_proto::KeyStatusType::Destroyed as i32 => {}
             None => return Err("invalid keyset".into()),
-            Some(kd) => match KeyMaterialType::from_i32(kd.key_material_type) {
-                Some(_) => {}
-                None => return Err("invalid key material type".into()),
+            Some(kd) => match kd.try_key_material_type() {
+                Ok(_) => {}
+                Err(_) => return Err("invalid key material type".into()),
             },
         }
     }
@@ -274,7 +680,9 @@ fn validate_keyset(ks: Keyset) -> Result<Keyset, TinkError> {
 }
 
 /// Extract the public key data corresponding to private key data.
-fn public_key_data(priv_key_data: &tink_proto::KeyData) -> Result<tink_proto::KeyData, TinkError> {
+pub(crate) fn public_key_data(
+    priv_key_data: &tink_proto::KeyData,
+) -> Result<tink_proto::KeyData, TinkError> {
     if priv_key_data.key_material_type
         != tink_proto::key_data::KeyMaterialType::AsymmetricPrivate as i32
     {
@@ -300,8 +708,25 @@ fn decrypt(
 ) -> Result<Keyset, TinkError> {
     let decrypted = master_key
         .decrypt(&encrypted_keyset.encrypted_keyset, associated_data)
-        .map_err(|e| wrap_err("keyset::Handle: decryption failed", e))?;
-    Keyset::decode(&decrypted[..]).map_err(|_| TinkError::new("keyset::Handle:: invalid keyset"))
+        .map_err(|e| {
+            crate::trace::keyset_io_failed("read_encrypted", "decryption failed");
+            wrap_err("keyset::Handle: decryption failed", e)
+        })?;
+    decode_keyset(&decrypted)
+}
+
+/// Parse a serialized [`Keyset`].
+pub(super) fn decode_keyset(serialized: &[u8]) -> Result<Keyset, TinkError> {
+    Keyset::decode(serialized).map_err(|_| TinkError::new("keyset::Handle:: invalid keyset"))
+}
+
+/// Serialize a [`Keyset`].
+pub(super) fn encode_keyset(keyset: &Keyset) -> Result<Vec<u8>, TinkError> {
+    let mut serialized_keyset = vec![];
+    keyset
+        .encode(&mut serialized_keyset)
+        .map_err(|e| wrap_err("keyset::Handle: invalid keyset", e))?;
+    Ok(serialized_keyset)
 }
 
 /// Encrypt a keyset with a master key.
@@ -310,10 +735,7 @@ fn encrypt(
     master_key: Box<dyn crate::Aead>,
     associated_data: &[u8],
 ) -> Result<tink_proto::EncryptedKeyset, TinkError> {
-    let mut serialized_keyset = vec![];
-    keyset
-        .encode(&mut serialized_keyset)
-        .map_err(|e| wrap_err("keyset::Handle: invalid keyset", e))?;
+    let serialized_keyset = encode_keyset(keyset)?;
     let encrypted = master_key
         .encrypt(&serialized_keyset, associated_data)
         .map_err(|e| wrap_err("keyset::Handle: encrypted failed", e))?;
@@ -24,39 +24,77 @@ fn keyset_handle(ks: tink_proto::Keyset) -> Result<super::Handle, TinkError> {
 }
 
 /// Return the key material contained in a [`Handle`](super::Handle).
+///
+/// The material of a [restricted](super::Handle::restrict) handle would allow the restriction to
+/// be removed, so the key data of its keys is stripped, apart from public keys.
 pub fn keyset_material(h: &super::Handle) -> tink_proto::Keyset {
-    h.clone_keyset()
+    let mut ks = h.clone_keyset();
+    if h.usage() != super::KeyUsage::Unrestricted {
+        for key in &mut ks.key {
+            let public = key.key_data.as_ref().map_or(false, |kd| {
+                kd.key_material_type
+                    == tink_proto::key_data::KeyMaterialType::AsymmetricPublic as i32
+            });
+            if !public {
+                key.key_data = None;
+            }
+        }
+    }
+    ks
 }
 
 /// Create a new instance of [`Handle`](super::Handle) using the given
 /// [`Keyset`](tink_proto::Keyset).
+///
+/// Any registered [`Gatekeeper`](crate::registry::Gatekeeper)s are consulted before the handle is
+/// returned.
 pub fn new_handle(ks: tink_proto::Keyset) -> Result<super::Handle, TinkError> {
     if ks.key.is_empty() {
-        Err("insecure: invalid keyset".into())
-    } else {
-        keyset_handle(ks)
+        return Err("insecure: invalid keyset".into());
     }
+    let h = keyset_handle(ks)?;
+    crate::registry::check_cleartext_load(&crate::registry::CleartextLoad {
+        source: crate::registry::CleartextSource::Insecure,
+        keyset_info: h.keyset_info(),
+    })?;
+    Ok(h)
 }
 
 /// Create a [`Handle`](super::Handle) from a cleartext keyset obtained via `r`.
+///
+/// Any registered [`Gatekeeper`](crate::registry::Gatekeeper)s are consulted before the handle is
+/// returned.
 pub fn read<T>(r: &mut T) -> Result<super::Handle, TinkError>
 where
     T: super::Reader,
 {
     let ks = r.read()?;
     if ks.key.is_empty() {
-        Err("insecure: invalid keyset".into())
-    } else {
-        keyset_handle(ks)
+        return Err("insecure: invalid keyset".into());
     }
+    let h = keyset_handle(ks)?;
+    crate::registry::check_cleartext_load(&crate::registry::CleartextLoad {
+        source: crate::registry::CleartextSource::Insecure,
+        keyset_info: h.keyset_info(),
+    })?;
+    crate::trace::keyset_io("read_cleartext", h.keyset());
+    Ok(h)
 }
 
 /// Exports the keyset from `h` to the given writer `w` without encrypting it.
 /// Storing secret key material in an unencrypted fashion is dangerous. If feasible, you should use
 /// [`super::Handle::write()`] instead.
+///
+/// Fails for [restricted](super::Handle::restrict) handles, as for [`super::Handle::write()`].
 pub fn write<T>(h: &super::Handle, w: &mut T) -> Result<(), TinkError>
 where
     T: super::Writer,
 {
-    w.write(&keyset_material(h))
+    if h.usage() != super::KeyUsage::Unrestricted {
+        return Err("insecure: cannot export usage-restricted keyset".into());
+    }
+    let ks = keyset_material(h);
+    w.write(&ks)?;
+    crate::trace::keyset_io("write_cleartext", &ks);
+    Ok(())
 }
@@ -17,20 +17,64 @@
 //! JSON I/O for keysets (requires activation of the `json` feature).
 
 use crate::{utils::wrap_err, TinkError};
-use serde::Deserialize;
+use serde::de::DeserializeOwned;
 use std::io::{Read, Write};
 
+/// `JsonParsing` determines how strictly a [`JsonReader`] parses binary fields.
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JsonParsing {
+    /// Binary fields must be in standard, padded base64, as written by [`JsonWriter`].
+    Strict,
+    /// Binary fields may also be in URL-safe base64 (as produced by some `gcloud` and `tinkey`
+    /// flows), may omit padding, and may contain whitespace (such as line breaks).
+    Tolerant,
+}
+
+impl Default for JsonParsing {
+    fn default() -> Self {
+        JsonParsing::Strict
+    }
+}
+
 /// `JsonReader` deserializes a keyset from JSON format.
 #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
 pub struct JsonReader<T: Read> {
     r: T,
+    parsing: JsonParsing,
 }
 
 impl<T: Read> JsonReader<T> {
-    /// Return a new [`JsonReader`] that will read from `r`.
+    /// Return a new [`JsonReader`] that will read from `r`, with [`JsonParsing::Strict`]
+    /// parsing.
     #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
     pub fn new(r: T) -> Self {
-        JsonReader { r }
+        JsonReader {
+            r,
+            parsing: JsonParsing::default(),
+        }
+    }
+
+    /// Set how strictly binary fields are parsed.
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn with_parsing(mut self, parsing: JsonParsing) -> Self {
+        self.parsing = parsing;
+        self
+    }
+
+    fn parse<K: DeserializeOwned>(&mut self) -> Result<K, TinkError> {
+        match self.parsing {
+            JsonParsing::Strict => {
+                let mut de = serde_json::Deserializer::from_reader(&mut self.r);
+                K::deserialize(&mut de).map_err(|e| wrap_err("failed to parse", e))
+            }
+            JsonParsing::Tolerant => {
+                let mut value: serde_json::Value = serde_json::from_reader(&mut self.r)
+                    .map_err(|e| wrap_err("failed to parse", e))?;
+                normalize_binary_fields(&mut value, None);
+                K::deserialize(value).map_err(|e| wrap_err("failed to parse", e))
+            }
+        }
     }
 }
 
@@ -38,17 +82,58 @@ impl<T: Read> super::Reader for JsonReader<T> {
     /// Return a (cleartext) [`Keyset`](tink_proto::Keyset) object from the underlying
     /// [`std::io::Read`].
     fn read(&mut self) -> Result<tink_proto::Keyset, TinkError> {
-        let mut de = serde_json::Deserializer::from_reader(&mut self.r);
-        tink_proto::Keyset::deserialize(&mut de).map_err(|e| wrap_err("failed to parse", e))
+        self.parse()
     }
 
     /// Return an [`EncryptedKeyset`](tink_proto::EncryptedKeyset) object from the underlying
     /// [`std::io::Read`].
     fn read_encrypted(&mut self) -> Result<tink_proto::EncryptedKeyset, TinkError> {
-        let mut de = serde_json::Deserializer::from_reader(&mut self.r);
-        tink_proto::EncryptedKeyset::deserialize(&mut de)
-            .map_err(|e| wrap_err("failed to parse", e))
+        self.parse()
+    }
+}
+
+/// Rewrite the binary fields of a JSON (encrypted) keyset into standard, padded base64.  The
+/// binary fields are `keyData.value` and `encryptedKeyset`.
+fn normalize_binary_fields(value: &mut serde_json::Value, parent: Option<&str>) {
+    match value {
+        serde_json::Value::Object(fields) => {
+            for (name, field) in fields.iter_mut() {
+                let binary = match (parent, name.as_str()) {
+                    (Some("keyData"), "value") => true,
+                    (None, "encryptedKeyset") => true,
+                    _ => false,
+                };
+                match field {
+                    serde_json::Value::String(s) if binary => *s = normalize_base64(s),
+                    _ => normalize_binary_fields(field, Some(name)),
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                normalize_binary_fields(item, parent);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Convert (possibly unpadded) standard or URL-safe base64 containing whitespace into standard,
+/// padded base64.  Invalid input is left invalid, to be rejected by the decoder.
+fn normalize_base64(s: &str) -> String {
+    let mut out: String = s
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && *c != '=')
+        .map(|c| match c {
+            '-' => '+',
+            '_' => '/',
+            c => c,
+        })
+        .collect();
+    while out.len() % 4 != 0 {
+        out.push('=');
     }
+    out
 }
 
 /// `JsonWriter` serializes a keyset into JSON format.
@@ -0,0 +1,365 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Deferred creation of the primitives for the keys in a keyset.
+
+use crate::{keyset::KeyVersionPolicy, registry::KeyManager, utils::wrap_err, KeyId, TinkError};
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+use tink_proto::{KeyData, Keyset, OutputPrefixType};
+
+/// Policy for when the key material of each key in a keyset is parsed into a primitive, as
+/// primitives are created from a [`Handle`](crate::keyset::Handle).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyParsing {
+    /// Parse every enabled key when the primitive is created, so that any invalid key material
+    /// is reported immediately.
+    Eager,
+    /// Parse only the primary key (and one key of each key type) when the primitive is created,
+    /// and defer parsing the remaining keys until they are first used.  This reduces the time
+    /// taken to create a primitive from a keyset with very many keys, but invalid key material
+    /// in a non-primary key is only reported when an operation first needs that key.
+    Lazy,
+}
+
+impl Default for KeyParsing {
+    fn default() -> Self {
+        KeyParsing::Eager
+    }
+}
+
+/// Create the primitive for `key_data`, using the given key manager (if it supports the key
+/// type) or else the registered key manager.
+pub(crate) fn key_primitive(
+    key_data: &KeyData,
+    km: Option<&Arc<dyn KeyManager>>,
+    version_policy: KeyVersionPolicy,
+) -> Result<crate::Primitive, TinkError> {
+    super::with_key_version_policy(version_policy, || match km {
+        Some(km) if km.does_support(&key_data.type_url) => km.primitive(&key_data.value),
+        Some(_) | None => crate::registry::primitive_from_key_data(key_data),
+    })
+}
+
+/// The information needed to create the primitive for a key on first use.
+pub(crate) struct LazySource {
+    pub(crate) ks: Arc<Keyset>,
+    pub(crate) index: usize,
+    pub(crate) km: Option<Arc<dyn KeyManager>>,
+    pub(crate) version_policy: KeyVersionPolicy,
+    pub(crate) prefix_type: OutputPrefixType,
+}
+
+impl LazySource {
+    fn key_id(&self) -> KeyId {
+        self.ks.key[self.index].key_id
+    }
+
+    fn primitive(&self) -> Result<crate::Primitive, TinkError> {
+        let key_data = self.ks.key[self.index]
+            .key_data
+            .as_ref()
+            .ok_or_else(|| TinkError::new("no key_data"))?;
+        let p = key_primitive(key_data, self.km.as_ref(), self.version_policy)?;
+        crate::trace::primitive_created(self.key_id(), &key_data.type_url, self.prefix_type, false);
+        Ok(p)
+    }
+}
+
+struct LazyState<P: ?Sized> {
+    source: LazySource,
+    extract: fn(crate::Primitive) -> Option<Box<P>>,
+    primitive: RefCell<Option<Rc<P>>>,
+}
+
+/// A primitive of type `P` that is only created from its key when first used.  Clones share the
+/// created primitive.
+pub(crate) struct Lazy<P: ?Sized> {
+    state: Rc<LazyState<P>>,
+}
+
+impl<P: ?Sized> Clone for Lazy<P> {
+    fn clone(&self) -> Self {
+        Lazy {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<P: ?Sized> Lazy<P> {
+    fn new(source: LazySource, extract: fn(crate::Primitive) -> Option<Box<P>>) -> Self {
+        Lazy {
+            state: Rc::new(LazyState {
+                source,
+                extract,
+                primitive: RefCell::new(None),
+            }),
+        }
+    }
+
+    /// Return the primitive, creating it if this is its first use.  Failures are not cached.
+    fn get(&self) -> Result<Rc<P>, TinkError> {
+        if let Some(p) = self.state.primitive.borrow().as_ref() {
+            return Ok(p.clone());
+        }
+        let key_id = self.state.source.key_id();
+        let p = self
+            .state
+            .source
+            .primitive()
+            .map_err(|e| wrap_err(&format!("cannot get primitive from key {}", key_id), e))?;
+        let p: Rc<P> = (self.state.extract)(p)
+            .ok_or_else(|| {
+                TinkError::new(&format!("unexpected primitive type for key {}", key_id))
+            })?
+            .into();
+        *self.state.primitive.borrow_mut() = Some(p.clone());
+        Ok(p)
+    }
+}
+
+/// Return a primitive of the same kind as `like` that is created from `source` on first use.
+pub(crate) fn lazy_primitive(like: &crate::Primitive, source: LazySource) -> crate::Primitive {
+    use crate::Primitive;
+    match like {
+        Primitive::Aead(_) => Primitive::Aead(Box::new(Lazy::new(source, |p| match p {
+            Primitive::Aead(p) => Some(p),
+            _ => None,
+        }))),
+        Primitive::DeterministicAead(_) => {
+            Primitive::DeterministicAead(Box::new(Lazy::new(source, |p| match p {
+                Primitive::DeterministicAead(p) => Some(p),
+                _ => None,
+            })))
+        }
+        Primitive::HybridDecrypt(_) => {
+            Primitive::HybridDecrypt(Box::new(Lazy::new(source, |p| match p {
+                Primitive::HybridDecrypt(p) => Some(p),
+                _ => None,
+            })))
+        }
+        Primitive::HybridEncrypt(_) => {
+            Primitive::HybridEncrypt(Box::new(Lazy::new(source, |p| match p {
+                Primitive::HybridEncrypt(p) => Some(p),
+                _ => None,
+            })))
+        }
+        Primitive::Mac(_) => Primitive::Mac(Box::new(Lazy::new(source, |p| match p {
+            Primitive::Mac(p) => Some(p),
+            _ => None,
+        }))),
+        Primitive::Prf(_) => Primitive::Prf(Box::new(Lazy::new(source, |p| match p {
+            Primitive::Prf(p) => Some(p),
+            _ => None,
+        }))),
+        Primitive::Signer(_) => Primitive::Signer(Box::new(Lazy::new(source, |p| match p {
+            Primitive::Signer(p) => Some(p),
+            _ => None,
+        }))),
+        Primitive::StreamingAead(_) => {
+            Primitive::StreamingAead(Box::new(Lazy::new(source, |p| match p {
+                Primitive::StreamingAead(p) => Some(p),
+                _ => None,
+            })))
+        }
+        Primitive::StreamingPrf(_) => {
+            Primitive::StreamingPrf(Box::new(Lazy::new(source, |p| match p {
+                Primitive::StreamingPrf(p) => Some(p),
+                _ => None,
+            })))
+        }
+        Primitive::Verifier(_) => Primitive::Verifier(Box::new(Lazy::new(source, |p| match p {
+            Primitive::Verifier(p) => Some(p),
+            _ => None,
+        }))),
+    }
+}
+
+// Each primitive trait is implemented by forwarding every method (including those with default
+// implementations, which the underlying primitive may override) to the created primitive.
+
+impl crate::Aead for Lazy<dyn crate::Aead> {
+    fn encrypt(&self, plaintext: &[u8], additional_data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.get()?.encrypt(plaintext, additional_data)
+    }
+
+    fn decrypt(&self, ciphertext: &[u8], additional_data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.get()?.decrypt(ciphertext, additional_data)
+    }
+
+    fn encrypt_with_aad_reader(
+        &self,
+        plaintext: &[u8],
+        additional_data: &mut dyn std::io::Read,
+    ) -> Result<Vec<u8>, TinkError> {
+        self.get()?
+            .encrypt_with_aad_reader(plaintext, additional_data)
+    }
+
+    fn decrypt_with_aad_reader(
+        &self,
+        ciphertext: &[u8],
+        additional_data: &mut dyn std::io::Read,
+    ) -> Result<Vec<u8>, TinkError> {
+        self.get()?
+            .decrypt_with_aad_reader(ciphertext, additional_data)
+    }
+}
+
+impl crate::DeterministicAead for Lazy<dyn crate::DeterministicAead> {
+    fn encrypt_deterministically(
+        &self,
+        plaintext: &[u8],
+        additional_data: &[u8],
+    ) -> Result<Vec<u8>, TinkError> {
+        self.get()?
+            .encrypt_deterministically(plaintext, additional_data)
+    }
+
+    fn decrypt_deterministically(
+        &self,
+        ciphertext: &[u8],
+        additional_data: &[u8],
+    ) -> Result<Vec<u8>, TinkError> {
+        self.get()?
+            .decrypt_deterministically(ciphertext, additional_data)
+    }
+}
+
+impl crate::HybridDecrypt for Lazy<dyn crate::HybridDecrypt> {
+    fn decrypt(&self, ciphertext: &[u8], context_info: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.get()?.decrypt(ciphertext, context_info)
+    }
+
+    fn new_decrypting_reader(
+        &self,
+        r: Box<dyn std::io::Read>,
+        context_info: &[u8],
+    ) -> Result<Box<dyn std::io::Read>, TinkError> {
+        self.get()?.new_decrypting_reader(r, context_info)
+    }
+
+    fn decrypt_with_key_id(
+        &self,
+        key_id: KeyId,
+        ciphertext: &[u8],
+        context_info: &[u8],
+    ) -> Result<Vec<u8>, TinkError> {
+        self.get()?
+            .decrypt_with_key_id(key_id, ciphertext, context_info)
+    }
+
+    fn raw_key_ids(&self) -> Vec<KeyId> {
+        self.get().map(|p| p.raw_key_ids()).unwrap_or_default()
+    }
+}
+
+impl crate::HybridEncrypt for Lazy<dyn crate::HybridEncrypt> {
+    fn encrypt(&self, plaintext: &[u8], context_info: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.get()?.encrypt(plaintext, context_info)
+    }
+
+    fn new_encrypting_writer(
+        &self,
+        w: Box<dyn std::io::Write>,
+        context_info: &[u8],
+    ) -> Result<Box<dyn crate::EncryptingWrite>, TinkError> {
+        self.get()?.new_encrypting_writer(w, context_info)
+    }
+}
+
+impl crate::Mac for Lazy<dyn crate::Mac> {
+    fn compute_mac(&self, data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.get()?.compute_mac(data)
+    }
+
+    fn verify_mac(&self, mac: &[u8], data: &[u8]) -> Result<(), TinkError> {
+        self.get()?.verify_mac(mac, data)
+    }
+
+    fn verify_mac_with_key_id(&self, mac: &[u8], data: &[u8]) -> Result<KeyId, TinkError> {
+        self.get()?.verify_mac_with_key_id(mac, data)
+    }
+}
+
+impl crate::Prf for Lazy<dyn crate::Prf> {
+    fn compute_prf(&self, input: &[u8], output_length: usize) -> Result<Vec<u8>, TinkError> {
+        self.get()?.compute_prf(input, output_length)
+    }
+}
+
+impl crate::Signer for Lazy<dyn crate::Signer> {
+    fn sign(&self, data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.get()?.sign(data)
+    }
+}
+
+impl crate::StreamingAead for Lazy<dyn crate::StreamingAead> {
+    fn new_encrypting_writer(
+        &self,
+        w: Box<dyn std::io::Write>,
+        aad: &[u8],
+    ) -> Result<Box<dyn crate::EncryptingWrite>, TinkError> {
+        self.get()?.new_encrypting_writer(w, aad)
+    }
+
+    fn new_decrypting_reader(
+        &self,
+        r: Box<dyn std::io::Read>,
+        aad: &[u8],
+    ) -> Result<Box<dyn std::io::Read>, TinkError> {
+        self.get()?.new_decrypting_reader(r, aad)
+    }
+
+    fn new_encrypting_writer_with_segment_aad(
+        &self,
+        w: Box<dyn std::io::Write>,
+        aad: &[u8],
+        segment_aad: &[u8],
+    ) -> Result<Box<dyn crate::EncryptingWrite>, TinkError> {
+        self.get()?
+            .new_encrypting_writer_with_segment_aad(w, aad, segment_aad)
+    }
+
+    fn new_decrypting_reader_with_segment_aad(
+        &self,
+        r: Box<dyn std::io::Read>,
+        aad: &[u8],
+        segment_aad: &[u8],
+    ) -> Result<Box<dyn std::io::Read>, TinkError> {
+        self.get()?
+            .new_decrypting_reader_with_segment_aad(r, aad, segment_aad)
+    }
+}
+
+impl crate::StreamingPrf for Lazy<dyn crate::StreamingPrf> {
+    fn compute_prf(&self, input: &[u8]) -> Result<Box<dyn std::io::Read>, TinkError> {
+        self.get()?.compute_prf(input)
+    }
+}
+
+impl crate::Verifier for Lazy<dyn crate::Verifier> {
+    fn verify(&self, signature: &[u8], data: &[u8]) -> Result<(), TinkError> {
+        self.get()?.verify(signature, data)
+    }
+
+    fn verify_batch(&self, items: &[(&[u8], &[u8])]) -> Result<(), TinkError> {
+        self.get()?.verify_batch(items)
+    }
+
+    fn verify_with_key_id(&self, signature: &[u8], data: &[u8]) -> Result<KeyId, TinkError> {
+        self.get()?.verify_with_key_id(signature, data)
+    }
+}
@@ -16,15 +16,23 @@
 
 //! Utilities for managing keys in a keyset.
 
-use crate::{utils::wrap_err, KeyId, TinkError};
-use rand::Rng;
+use super::{AuditRecorder, AuditTrail, Clock, KeyEventKind, KeysetStore, StoredKeyset};
+use crate::{keyset::KeyUsage, utils::wrap_err, KeyId, TinkError};
+use std::sync::Arc;
 use tink_proto::{KeyStatusType, OutputPrefixType};
 
 /// Manager manages a [`Keyset`](tink_proto::Keyset)-proto, with convenience methods that rotate,
 /// disable, enable or destroy keys. Note: It is not thread-safe.
-#[derive(Default)]
 pub struct Manager {
     ks: tink_proto::Keyset,
+    usage: KeyUsage,
+    audit: AuditRecorder,
+}
+
+impl Default for Manager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Manager {
@@ -32,14 +40,93 @@ impl Manager {
     pub fn new() -> Self {
         Self {
             ks: tink_proto::Keyset::default(),
+            usage: KeyUsage::Unrestricted,
+            audit: AuditRecorder::default(),
         }
     }
 
-    /// Create a new instance from the given [`Handle`](super::Handle).
+    /// Create a new instance from the given [`Handle`](super::Handle).  Any usage restriction
+    /// on the handle also applies to handles created by the manager.
     pub fn new_from_handle(kh: super::Handle) -> Self {
+        let usage = kh.usage();
         Self {
             ks: kh.into_inner(),
+            usage,
+            audit: AuditRecorder::default(),
+        }
+    }
+
+    /// Create a new instance from the keyset held in `store`, decrypting it with `master_key`.
+    /// If the store holds an [`AuditTrail`], recording of key events is enabled and continues
+    /// from the stored trail.
+    pub fn new_from_store(
+        store: &dyn KeysetStore,
+        master_key: Box<dyn crate::Aead>,
+    ) -> Result<Self, TinkError> {
+        let stored = store
+            .load()
+            .map_err(|e| wrap_err("keyset::Manager: cannot load keyset", e))?;
+        let mut mem = super::MemReaderWriter {
+            keyset: None,
+            encrypted_keyset: Some(stored.encrypted_keyset),
+        };
+        // The metadata is bound to the keyset as associated data, so it cannot be modified
+        // independently of the keyset.
+        let kh = super::Handle::read_with_associated_data(&mut mem, master_key, &stored.metadata)?;
+        let mut manager = Self::new_from_handle(kh);
+        if !stored.metadata.is_empty() {
+            manager.audit.trail = Some(AuditTrail::decode(&stored.metadata)?);
         }
+        Ok(manager)
+    }
+
+    /// Encrypt the managed keyset with `master_key` and save it to `store`, together with the
+    /// [`AuditTrail`] (if enabled).
+    pub fn save_to_store(
+        &self,
+        store: &dyn KeysetStore,
+        master_key: Box<dyn crate::Aead>,
+    ) -> Result<(), TinkError> {
+        let metadata = match &self.audit.trail {
+            Some(trail) => trail.encode()?,
+            None => Vec::new(),
+        };
+        let mut mem = super::MemReaderWriter::default();
+        self.handle()?
+            .write_with_associated_data(&mut mem, master_key, &metadata)?;
+        let encrypted_keyset = mem
+            .encrypted_keyset
+            .ok_or_else(|| TinkError::new("keyset::Manager: no encrypted keyset"))?;
+        store
+            .save(&StoredKeyset {
+                encrypted_keyset,
+                metadata,
+            })
+            .map_err(|e| wrap_err("keyset::Manager: cannot save keyset", e))
+    }
+
+    /// Start recording key lifecycle events in an [`AuditTrail`], attributing subsequent events
+    /// to `actor`.  Any existing trail is kept.
+    pub fn enable_audit_trail(&mut self, actor: &str) {
+        if self.audit.trail.is_none() {
+            self.audit.trail = Some(AuditTrail::default());
+        }
+        self.set_audit_actor(actor);
+    }
+
+    /// Set the actor that subsequent key events are attributed to.
+    pub fn set_audit_actor(&mut self, actor: &str) {
+        self.audit.actor = actor.to_string();
+    }
+
+    /// Set the clock used to timestamp key events.
+    pub fn set_audit_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.audit.clock = clock;
+    }
+
+    /// Return the recorded key events, if auditing is enabled.
+    pub fn audit_trail(&self) -> Option<&AuditTrail> {
+        self.audit.trail.as_ref()
     }
 
     /// Generate a fresh key using the given key template and set the new key as the primary key.
@@ -58,30 +145,91 @@ impl Manager {
     ) -> Result<KeyId, TinkError> {
         let key_data = crate::registry::new_key_data(kt)
             .map_err(|e| wrap_err("keyset::Manager: cannot create KeyData", e))?;
+        self.add_key_data(kt, key_data, as_primary)
+    }
+
+    /// Derive a key using the given key template, taking the key material from
+    /// `pseudorandomness`, and optionally set the new key as the primary key. Returns the key ID
+    /// of the added key.  Only some key types support derivation.
+    pub fn add_derived(
+        &mut self,
+        kt: &tink_proto::KeyTemplate,
+        pseudorandomness: &mut dyn std::io::Read,
+        as_primary: bool,
+    ) -> Result<KeyId, TinkError> {
+        let key_data = crate::registry::derive_key_data(kt, pseudorandomness)
+            .map_err(|e| wrap_err("keyset::Manager: cannot derive KeyData", e))?;
+        self.add_key_data(kt, key_data, as_primary)
+    }
+
+    /// Add a key holding the given key data, with the output prefix type of the given key
+    /// template.
+    fn add_key_data(
+        &mut self,
+        kt: &tink_proto::KeyTemplate,
+        key_data: tink_proto::KeyData,
+        as_primary: bool,
+    ) -> Result<KeyId, TinkError> {
+        // Custom output prefix types are allowed if registered.
+        crate::cryptofmt::effective_output_prefix_type(kt.output_prefix_type)
+            .map_err(|e| wrap_err("keyset::Manager", e))?;
+        Ok(self.push_key(key_data, kt.output_prefix_type, as_primary))
+    }
+
+    /// Add a key holding key material that was imported from another format (such as a PKCS#8
+    /// private key), and optionally set the new key as the primary key.  The key data is checked
+    /// by creating its primitive, so the corresponding key manager must be registered.  Returns
+    /// the key ID of the added key.
+    ///
+    /// Callers that import secret key material should consult any registered
+    /// [`Gatekeeper`](crate::registry::Gatekeeper)s with
+    /// [`CleartextSource::Import`](crate::registry::CleartextSource::Import).
+    pub fn add_imported_key_data(
+        &mut self,
+        key_data: tink_proto::KeyData,
+        output_prefix_type: OutputPrefixType,
+        as_primary: bool,
+    ) -> Result<KeyId, TinkError> {
+        if output_prefix_type == OutputPrefixType::UnknownPrefix {
+            return Err("keyset::Manager: unknown output prefix type".into());
+        }
+        crate::registry::primitive_from_key_data(&key_data)
+            .map_err(|e| wrap_err("keyset::Manager: invalid imported key", e))?;
+        Ok(self.push_key(key_data, output_prefix_type as i32, as_primary))
+    }
+
+    /// Add an enabled key holding `key_data` to the keyset.
+    fn push_key(
+        &mut self,
+        key_data: tink_proto::KeyData,
+        output_prefix_type: i32,
+        as_primary: bool,
+    ) -> KeyId {
         let key_id = self.new_key_id();
-        let output_prefix_type = match OutputPrefixType::from_i32(kt.output_prefix_type) {
-            None | Some(OutputPrefixType::UnknownPrefix) => {
-                return Err("keyset::Manager: unknown output prefix type".into())
-            }
-            Some(p) => p,
-        };
         let key = tink_proto::keyset::Key {
             key_data: Some(key_data),
             status: tink_proto::KeyStatusType::Enabled as i32,
             key_id,
-            output_prefix_type: output_prefix_type as i32,
+            output_prefix_type,
         };
         self.ks.key.push(key);
+        self.audit.record(key_id, KeyEventKind::Created);
         if as_primary {
             // Set the new key as the primary key
+            self.audit.record(
+                key_id,
+                KeyEventKind::Rotated {
+                    from: self.ks.primary_key_id,
+                },
+            );
             self.ks.primary_key_id = key_id;
         }
-        Ok(key_id)
+        key_id
     }
 
     /// Create a new [`Handle`](super::Handle) for the managed keyset.
     pub fn handle(&self) -> Result<super::Handle, TinkError> {
-        super::Handle::from_keyset(self.ks.clone())
+        super::Handle::from_keyset(self.ks.clone())?.restrict(self.usage)
     }
 
     /// Sets the status of the specified key to [`KeyStatusType::Enabled`].  Succeeds only if before
@@ -90,14 +238,16 @@ impl Manager {
     pub fn enable(&mut self, key_id: KeyId) -> Result<(), TinkError> {
         for key in &mut self.ks.key {
             if key.key_id == key_id {
-                return match KeyStatusType::from_i32(key.status) {
-                    Some(KeyStatusType::Enabled) | Some(KeyStatusType::Disabled) => {
+                return match key.try_status() {
+                    Ok(KeyStatusType::Enabled) | Ok(KeyStatusType::Disabled) => {
                         key.status = KeyStatusType::Enabled as i32;
+                        self.audit.record(key_id, KeyEventKind::Enabled);
                         Ok(())
                     }
                     _ => Err(format!(
                         "Cannot enable key with key_id {} and status {}",
-                        key_id, key.status
+                        key_id,
+                        status_name(key.status)
                     )
                     .into()),
                 };
@@ -115,14 +265,16 @@ impl Manager {
         }
         for key in &mut self.ks.key {
             if key.key_id == key_id {
-                return match KeyStatusType::from_i32(key.status) {
-                    Some(KeyStatusType::Enabled) | Some(KeyStatusType::Disabled) => {
+                return match key.try_status() {
+                    Ok(KeyStatusType::Enabled) | Ok(KeyStatusType::Disabled) => {
                         key.status = KeyStatusType::Disabled as i32;
+                        self.audit.record(key_id, KeyEventKind::Disabled);
                         Ok(())
                     }
                     _ => Err(format!(
                         "Cannot disable key with key_id {} and status {}",
-                        key_id, key.status
+                        key_id,
+                        status_name(key.status)
                     )
                     .into()),
                 };
@@ -141,17 +293,19 @@ impl Manager {
         }
         for key in &mut self.ks.key {
             if key.key_id == key_id {
-                return match KeyStatusType::from_i32(key.status) {
-                    Some(KeyStatusType::Enabled)
-                    | Some(KeyStatusType::Disabled)
-                    | Some(KeyStatusType::Destroyed) => {
+                return match key.try_status() {
+                    Ok(KeyStatusType::Enabled)
+                    | Ok(KeyStatusType::Disabled)
+                    | Ok(KeyStatusType::Destroyed) => {
                         key.key_data = None;
                         key.status = KeyStatusType::Destroyed as i32;
+                        self.audit.record(key_id, KeyEventKind::Destroyed);
                         Ok(())
                     }
                     _ => Err(format!(
                         "Cannot destroy key with key_id {} and status {}",
-                        key_id, key.status
+                        key_id,
+                        status_name(key.status)
                     )
                     .into()),
                 };
@@ -160,6 +314,54 @@ impl Manager {
         Err(format!("Key {} not found", key_id).into())
     }
 
+    /// Return the status of the specified key.
+    pub fn key_status(&self, key_id: KeyId) -> Result<KeyStatusType, TinkError> {
+        let key = self
+            .ks
+            .key
+            .iter()
+            .find(|k| k.key_id == key_id)
+            .ok_or_else(|| TinkError::new(&format!("Key {} not found", key_id)))?;
+        key.try_status().map_err(|_| {
+            format!(
+                "Key {} has invalid status {}",
+                key_id,
+                status_name(key.status)
+            )
+            .into()
+        })
+    }
+
+    /// Set the status of the specified key, failing (and leaving the keyset unchanged) if the
+    /// transition is not permitted:
+    ///
+    /// | from \ to   | `Enabled` | `Disabled`     | `Destroyed`    |
+    /// |-------------|-----------|----------------|----------------|
+    /// | `Enabled`   | yes       | if not primary | if not primary |
+    /// | `Disabled`  | yes       | yes            | if not primary |
+    /// | `Destroyed` | no        | no             | if not primary |
+    ///
+    /// `Destroyed` is terminal, as the key material has been removed, and the primary key must
+    /// remain `Enabled`; make another key primary first with [`set_primary`](Self::set_primary).
+    /// Setting a key to `Destroyed` removes its key material, as for [`destroy`](Self::destroy).
+    pub fn set_key_status(
+        &mut self,
+        key_id: KeyId,
+        status: KeyStatusType,
+    ) -> Result<(), TinkError> {
+        match status {
+            KeyStatusType::Enabled => self.enable(key_id),
+            KeyStatusType::Disabled => self.disable(key_id),
+            KeyStatusType::Destroyed => self.destroy(key_id),
+            KeyStatusType::UnknownStatus => Err(format!(
+                "Cannot set status of key with key_id {} to {}",
+                key_id,
+                status_name(status as i32)
+            )
+            .into()),
+        }
+    }
+
     /// Removes the specifed key from the managed keyset.  Succeeds only if the specified key is not
     /// primary.  After deletion the keyset contains one key fewer.
     pub fn delete(&mut self, key_id: KeyId) -> Result<(), TinkError> {
@@ -176,6 +378,7 @@ impl Manager {
         match idx {
             Some(i) => {
                 self.ks.key.remove(i);
+                self.audit.record(key_id, KeyEventKind::Deleted);
                 Ok(())
             }
             None => Err(format!("Key {} not found", key_id).into()),
@@ -186,14 +389,17 @@ impl Manager {
     pub fn set_primary(&mut self, key_id: KeyId) -> Result<(), TinkError> {
         for key in &self.ks.key {
             if key.key_id == key_id {
-                return match KeyStatusType::from_i32(key.status) {
-                    Some(KeyStatusType::Enabled) => {
+                return match key.try_status() {
+                    Ok(KeyStatusType::Enabled) => {
+                        let from = self.ks.primary_key_id;
                         self.ks.primary_key_id = key_id;
+                        self.audit.record(key_id, KeyEventKind::Rotated { from });
                         Ok(())
                     }
                     _ => Err(format!(
                         "The candidate (key_id {}) for the primary key must be Enabled (status {})",
-                        key_id, key.status
+                        key_id,
+                        status_name(key.status)
                     )
                     .into()),
                 };
@@ -209,10 +415,8 @@ impl Manager {
 
     /// Generate a key id that has not been used by any key in the [`Keyset`](tink_proto::Keyset).
     fn new_key_id(&self) -> KeyId {
-        let mut rng = rand::thread_rng();
-
         loop {
-            let ret = rng.gen::<u32>();
+            let ret = crate::subtle::random::get_random_uint32();
             if self.ks.key.iter().any(|x| x.key_id == ret) {
                 continue;
             }
@@ -220,3 +424,11 @@ impl Manager {
         }
     }
 }
+
+/// Describe a (possibly invalid) key status for error messages.
+fn status_name(status: i32) -> String {
+    match KeyStatusType::from_i32(status) {
+        Some(KeyStatusType::UnknownStatus) | None => format!("UnknownStatus ({})", status),
+        Some(s) => format!("{:?}", s),
+    }
+}
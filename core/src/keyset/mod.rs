@@ -16,8 +16,18 @@
 
 //! Provide methods to generate, read, write or validate keysets.
 
+mod audit;
+pub use audit::*;
 mod binary_io;
 pub use binary_io::*;
+mod builder;
+pub use builder::*;
+mod bundle;
+pub use bundle::*;
+mod diff;
+pub use diff::*;
+mod fingerprint;
+pub use fingerprint::*;
 mod handle;
 pub use handle::*;
 #[cfg(feature = "json")]
@@ -25,14 +35,39 @@ pub use handle::*;
 mod json_io;
 #[cfg(feature = "json")]
 pub use json_io::*;
+mod lazy;
+pub use lazy::*;
 mod manager;
 pub use manager::*;
 mod mem_io;
 pub use mem_io::*;
+#[cfg(feature = "password")]
+#[cfg_attr(docsrs, doc(cfg(feature = "password")))]
+mod password;
+#[cfg(feature = "password")]
+pub use password::*;
+mod primitive_cache;
 mod reader;
 pub use reader::*;
+mod redact;
+pub use redact::*;
+mod reloading;
+pub use reloading::*;
+mod self_test;
+pub use self_test::*;
+mod store;
+pub use store::*;
+mod unwrap_cache;
+pub use unwrap_cache::*;
+mod usage;
+pub use usage::*;
+mod usage_limit;
+pub use usage_limit::*;
 mod validation;
 pub use validation::*;
+mod validity;
+pub use validity::*;
+mod wrapped_key;
 mod writer;
 pub use writer::*;
 
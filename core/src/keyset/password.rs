@@ -0,0 +1,217 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Password-based keyset encryption.
+
+use crate::{keyset::Handle, subtle::random::get_random_bytes, utils::wrap_err, TinkError};
+use chacha20poly1305::{
+    aead::{Aead, Payload},
+    ChaCha20Poly1305, KeyInit,
+};
+
+/// Version byte at the start of a password-encrypted keyset.
+const PASSWORD_FORMAT_VERSION: u8 = 1;
+/// Identifier for the Argon2id key derivation function.
+const KDF_ARGON2ID: u8 = 1;
+const SALT_SIZE: usize = 16;
+const NONCE_SIZE: usize = 12;
+const KEY_SIZE: usize = 32;
+/// Size of the header: version, KDF identifier, three big-endian `u32` cost parameters and salt.
+const HEADER_SIZE: usize = 2 + 3 * 4 + SALT_SIZE;
+
+/// Maximum Argon2id memory cost (in KiB) accepted when reading a password-encrypted keyset.
+pub const PASSWORD_MAX_M_COST_KIB: u32 = 1024 * 1024;
+/// Maximum Argon2id iteration count accepted when reading a password-encrypted keyset.
+pub const PASSWORD_MAX_T_COST: u32 = 16;
+/// Maximum Argon2id parallelism accepted when reading a password-encrypted keyset.
+pub const PASSWORD_MAX_P_COST: u32 = 16;
+
+/// Argon2id parameters used to derive a keyset-encryption key from a password.  The
+/// parameters are stored alongside the encrypted keyset, so need not be supplied on reading.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PasswordKdfParams {
+    /// Memory size in KiB.
+    pub m_cost_kib: u32,
+    /// Number of iterations.
+    pub t_cost: u32,
+    /// Degree of parallelism.
+    pub p_cost: u32,
+}
+
+impl Default for PasswordKdfParams {
+    /// Return the parameters recommended by OWASP for Argon2id: 19 MiB of memory, 2 iterations
+    /// and a parallelism of 1.
+    fn default() -> Self {
+        PasswordKdfParams {
+            m_cost_kib: 19 * 1024,
+            t_cost: 2,
+            p_cost: 1,
+        }
+    }
+}
+
+impl PasswordKdfParams {
+    fn argon2(&self) -> Result<argon2::Argon2<'static>, TinkError> {
+        if self.m_cost_kib > PASSWORD_MAX_M_COST_KIB
+            || self.t_cost > PASSWORD_MAX_T_COST
+            || self.p_cost > PASSWORD_MAX_P_COST
+        {
+            return Err("password: KDF parameters exceed limits".into());
+        }
+        let params = argon2::Params::new(self.m_cost_kib, self.t_cost, self.p_cost, Some(KEY_SIZE))
+            .map_err(|e| wrap_err("password: invalid KDF parameters", e))?;
+        Ok(argon2::Argon2::new(
+            argon2::Algorithm::Argon2id,
+            argon2::Version::V0x13,
+            params,
+        ))
+    }
+
+    fn derive_key(&self, password: &[u8], salt: &[u8]) -> Result<ChaCha20Poly1305, TinkError> {
+        let mut key = [0u8; KEY_SIZE];
+        self.argon2()?
+            .hash_password_into(password, salt, &mut key)
+            .map_err(|e| wrap_err("password: key derivation failed", e))?;
+        ChaCha20Poly1305::new_from_slice(&key).map_err(|e| wrap_err("password: invalid key", e))
+    }
+}
+
+/// An [`Aead`](crate::Aead) whose key is derived from a password.  Each encryption uses a fresh
+/// salt, and produces a header holding the KDF parameters and salt, followed by a nonce and the
+/// ChaCha20-Poly1305 ciphertext; the header is also authenticated.
+#[derive(Clone)]
+struct PasswordAead {
+    password: Vec<u8>,
+    /// Parameters used for encryption; decryption uses the parameters in the header.
+    params: PasswordKdfParams,
+}
+
+impl crate::Aead for PasswordAead {
+    fn encrypt(&self, plaintext: &[u8], additional_data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let salt = get_random_bytes(SALT_SIZE);
+        let cipher = self.params.derive_key(&self.password, &salt)?;
+
+        let mut out = Vec::with_capacity(HEADER_SIZE + NONCE_SIZE + plaintext.len() + 16);
+        out.push(PASSWORD_FORMAT_VERSION);
+        out.push(KDF_ARGON2ID);
+        out.extend_from_slice(&self.params.m_cost_kib.to_be_bytes());
+        out.extend_from_slice(&self.params.t_cost.to_be_bytes());
+        out.extend_from_slice(&self.params.p_cost.to_be_bytes());
+        out.extend_from_slice(&salt);
+        let aad = [&out[..], additional_data].concat();
+        let nonce = get_random_bytes(NONCE_SIZE);
+        let ct = cipher
+            .encrypt(
+                chacha20poly1305::Nonce::from_slice(&nonce), // safe: NONCE_SIZE bytes
+                Payload {
+                    msg: plaintext,
+                    aad: &aad,
+                },
+            )
+            .map_err(|e| wrap_err("password: encryption failed", e))?;
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&ct);
+        Ok(out)
+    }
+
+    fn decrypt(&self, ciphertext: &[u8], additional_data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        if ciphertext.len() < HEADER_SIZE + NONCE_SIZE {
+            return Err("password: ciphertext too short".into());
+        }
+        let (header, rest) = ciphertext.split_at(HEADER_SIZE);
+        if header[0] != PASSWORD_FORMAT_VERSION {
+            return Err("password: unsupported format version".into());
+        }
+        if header[1] != KDF_ARGON2ID {
+            return Err("password: unsupported KDF".into());
+        }
+        let be_u32 = |offset: usize| {
+            let mut b = [0u8; 4];
+            b.copy_from_slice(&header[offset..offset + 4]);
+            u32::from_be_bytes(b)
+        };
+        let params = PasswordKdfParams {
+            m_cost_kib: be_u32(2),
+            t_cost: be_u32(6),
+            p_cost: be_u32(10),
+        };
+        let salt = &header[14..];
+        let cipher = params.derive_key(&self.password, salt)?;
+        let (nonce, ct) = rest.split_at(NONCE_SIZE);
+        let aad = [header, additional_data].concat();
+        cipher
+            .decrypt(
+                chacha20poly1305::Nonce::from_slice(nonce), // safe: length checked
+                Payload { msg: ct, aad: &aad },
+            )
+            .map_err(|_| TinkError::new("password: decryption failed"))
+    }
+}
+
+impl Handle {
+    /// Encrypt the enclosed keyset with a key derived from `password` using Argon2id with the
+    /// default [`PasswordKdfParams`], and write it.
+    ///
+    /// This is intended for development and for small deployments without access to a KMS; the
+    /// security of the keyset depends on the strength of the password.
+    pub fn write_with_password<T>(&self, writer: &mut T, password: &[u8]) -> Result<(), TinkError>
+    where
+        T: super::Writer,
+    {
+        self.write_with_password_params(writer, password, &PasswordKdfParams::default())
+    }
+
+    /// Encrypt the enclosed keyset with a key derived from `password` using Argon2id with the
+    /// given parameters, and write it.
+    pub fn write_with_password_params<T>(
+        &self,
+        writer: &mut T,
+        password: &[u8],
+        params: &PasswordKdfParams,
+    ) -> Result<(), TinkError>
+    where
+        T: super::Writer,
+    {
+        params.argon2()?;
+        let master_key = PasswordAead {
+            password: password.to_vec(),
+            params: *params,
+        };
+        self.write(writer, Box::new(master_key))
+    }
+
+    /// Attempt to create a [`Handle`] from a keyset written by
+    /// [`write_with_password`](Handle::write_with_password), using the KDF parameters stored
+    /// with the keyset (up to the `PASSWORD_MAX_*` limits).
+    ///
+    /// Any registered [`Gatekeeper`](crate::registry::Gatekeeper)s are consulted before the
+    /// handle is returned.
+    pub fn read_with_password<T>(reader: &mut T, password: &[u8]) -> Result<Self, TinkError>
+    where
+        T: crate::keyset::Reader,
+    {
+        let master_key = PasswordAead {
+            password: password.to_vec(),
+            params: PasswordKdfParams::default(),
+        };
+        let h = Self::read(reader, Box::new(master_key))?;
+        crate::registry::check_cleartext_load(&crate::registry::CleartextLoad {
+            source: crate::registry::CleartextSource::Password,
+            keyset_info: h.keyset_info(),
+        })?;
+        Ok(h)
+    }
+}
@@ -0,0 +1,84 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Caching of the primitive sets built from a keyset handle.
+
+use crate::{primitiveset::PrimitiveSet, registry::KeyManagerMap, TinkError};
+use std::{
+    cell::RefCell,
+    sync::{Arc, Weak},
+};
+
+/// `PrimitiveCache` identifies the primitive sets cached for a [`Handle`](super::Handle) and its
+/// clones.  It is shared (behind an [`Arc`]) between clones of a handle, and replaced whenever the
+/// handle is changed in a way that affects its primitives.
+///
+/// Primitives are not required to be `Send`, so the cached sets themselves are held per thread:
+/// each thread builds the primitive set for a handle once, and subsequently gets a copy of it.
+#[derive(Debug, Default)]
+pub(crate) struct PrimitiveCache {
+    // Ensure that each instance has a distinct address.
+    _unique: u8,
+}
+
+/// A primitive set cached on the current thread.
+struct CacheEntry {
+    cache: Weak<PrimitiveCache>,
+    // The key managers that the primitives were created with.
+    key_managers: Arc<KeyManagerMap>,
+    primitives: PrimitiveSet,
+}
+
+thread_local! {
+    static CACHED: RefCell<Vec<CacheEntry>> = RefCell::new(Vec::new());
+}
+
+impl PrimitiveCache {
+    /// Return a copy of the primitive set cached on this thread for `cache`, or build it with
+    /// `build` (and cache it) if there is none.  A cached set is discarded if the registered key
+    /// managers have changed since it was built.
+    pub(crate) fn get_or_build<F>(cache: &Arc<Self>, build: F) -> Result<PrimitiveSet, TinkError>
+    where
+        F: FnOnce() -> Result<PrimitiveSet, TinkError>,
+    {
+        let key_managers = crate::registry::key_managers();
+        let cached = CACHED.with(|c| {
+            let mut entries = c.borrow_mut();
+            // Drop the sets of handles that no longer exist, or that have stale key managers.
+            entries.retain(|e| {
+                e.cache.strong_count() > 0 && Arc::ptr_eq(&e.key_managers, &key_managers)
+            });
+            entries
+                .iter()
+                .find(|e| std::ptr::eq(e.cache.as_ptr(), Arc::as_ptr(cache)))
+                .map(|e| e.primitives.clone())
+        });
+        if let Some(primitives) = cached {
+            return Ok(primitives);
+        }
+
+        // Build outside of the borrow, as building may create primitives from other handles.
+        let primitives = build()?;
+        CACHED.with(|c| {
+            c.borrow_mut().push(CacheEntry {
+                cache: Arc::downgrade(cache),
+                key_managers,
+                primitives: primitives.clone(),
+            })
+        });
+        Ok(primitives)
+    }
+}
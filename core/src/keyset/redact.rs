@@ -0,0 +1,183 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Debug output for key-bearing protocol buffer messages that omits key material.
+
+use std::fmt;
+use tink_proto::prost::Message;
+
+/// `Redacted` wraps a reference to a key-bearing protocol buffer message, and provides a
+/// [`Debug`](fmt::Debug) implementation that omits any key material.  The derived `Debug`
+/// implementations of [`tink_proto`] messages include key bytes, so use this (via
+/// [`RedactedDebug::redacted`]) when logging them:
+///
+/// ```
+/// use tink_core::keyset::RedactedDebug;
+/// let key_data = tink_proto::KeyData {
+///     type_url: "type.googleapis.com/google.crypto.tink.AesGcmKey".to_string(),
+///     value: vec![0x42; 34],
+///     key_material_type: tink_proto::key_data::KeyMaterialType::Symmetric as i32,
+/// };
+/// assert_eq!(
+///     format!("{:?}", key_data.redacted()),
+///     "KeyData { type_url: \"type.googleapis.com/google.crypto.tink.AesGcmKey\", \
+///      key_material_type: Symmetric, value: <redacted, 34 bytes> }"
+/// );
+/// ```
+///
+/// Type URLs, key IDs, statuses and the sizes of key material are still shown.  Public keys are
+/// redacted in the same way as private and symmetric keys.
+pub struct Redacted<'a, T: ?Sized>(pub &'a T);
+
+/// Trait for messages that have a [`Redacted`] [`Debug`](fmt::Debug) representation.
+pub trait RedactedDebug {
+    /// Return a wrapper whose [`Debug`](fmt::Debug) output omits key material.
+    fn redacted(&self) -> Redacted<'_, Self> {
+        Redacted(self)
+    }
+}
+
+/// Placeholder for redacted bytes, showing only their length.
+struct RedactedBytes(usize);
+
+impl fmt::Debug for RedactedBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<redacted, {} bytes>", self.0)
+    }
+}
+
+/// Shows an enum value by name where it is known, and by number otherwise.
+struct EnumValue<T: fmt::Debug>(Result<T, i32>);
+
+impl<T: fmt::Debug> fmt::Debug for EnumValue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Ok(v) => write!(f, "{:?}", v),
+            Err(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+fn enum_value<T: fmt::Debug, E>(v: Result<T, E>, raw: i32) -> EnumValue<T> {
+    EnumValue(v.map_err(|_| raw))
+}
+
+impl RedactedDebug for tink_proto::KeyData {}
+
+impl fmt::Debug for Redacted<'_, tink_proto::KeyData> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kd = self.0;
+        f.debug_struct("KeyData")
+            .field("type_url", &kd.type_url)
+            .field(
+                "key_material_type",
+                &enum_value(kd.try_key_material_type(), kd.key_material_type),
+            )
+            .field("value", &RedactedBytes(kd.value.len()))
+            .finish()
+    }
+}
+
+impl RedactedDebug for tink_proto::keyset::Key {}
+
+impl fmt::Debug for Redacted<'_, tink_proto::keyset::Key> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let key = self.0;
+        f.debug_struct("Key")
+            .field("key_data", &key.key_data.as_ref().map(Redacted))
+            .field("status", &enum_value(key.try_status(), key.status))
+            .field("key_id", &key.key_id)
+            .field(
+                "output_prefix_type",
+                &enum_value(key.try_output_prefix_type(), key.output_prefix_type),
+            )
+            .finish()
+    }
+}
+
+impl RedactedDebug for tink_proto::Keyset {}
+
+impl fmt::Debug for Redacted<'_, tink_proto::Keyset> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Keyset")
+            .field("primary_key_id", &self.0.primary_key_id)
+            .field("key", &self.0.key.iter().map(Redacted).collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// Implement [`RedactedDebug`] for key messages, showing only the message name and the size of
+/// its encoding.
+macro_rules! redacted_key_messages {
+    ($($name:ident),* $(,)?) => {
+        $(
+            impl RedactedDebug for tink_proto::$name {}
+
+            impl fmt::Debug for Redacted<'_, tink_proto::$name> {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    write!(f, "{} {:?}", stringify!($name), RedactedBytes(self.0.encoded_len()))
+                }
+            }
+        )*
+    };
+}
+
+redacted_key_messages!(
+    AesCmacKey,
+    AesCmacPrfKey,
+    AesCtrHmacAeadKey,
+    AesCtrHmacStreamingKey,
+    AesCtrKey,
+    AesEaxKey,
+    AesGcmCommittingKey,
+    AesGcmHkdfStreamingKey,
+    AesGcmKey,
+    AesGcmSivKey,
+    AesSivKey,
+    AgeX25519PrivateKey,
+    AgeX25519PublicKey,
+    AndroidKeystoreAesGcmKey,
+    AndroidKeystoreEcdsaPrivateKey,
+    ChaCha20Poly1305Key,
+    EcdsaPrivateKey,
+    EcdsaPublicKey,
+    EciesAeadHkdfPrivateKey,
+    EciesAeadHkdfPublicKey,
+    Ed25519PrivateKey,
+    Ed25519PublicKey,
+    HkdfPrfKey,
+    HmacKey,
+    HmacPrfKey,
+    HpkePrivateKey,
+    HpkePublicKey,
+    JwtEcdsaPrivateKey,
+    JwtEcdsaPublicKey,
+    JwtHmacKey,
+    JwtRsaSsaPkcs1PrivateKey,
+    JwtRsaSsaPkcs1PublicKey,
+    JwtRsaSsaPssPrivateKey,
+    JwtRsaSsaPssPublicKey,
+    KmsAeadKey,
+    KmsEnvelopeAeadKey,
+    KmsSignerPrivateKey,
+    PrfBasedDeriverKey,
+    RsaSsaPkcs1PrivateKey,
+    RsaSsaPkcs1PublicKey,
+    RsaSsaPssPrivateKey,
+    RsaSsaPssPublicKey,
+    SecureEnclaveEcdsaPrivateKey,
+    XChaCha20Poly1305Key,
+);
@@ -0,0 +1,260 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Keyset handles that follow the keyset held in a [`KeysetStore`].
+
+use super::{Clock, Handle, KeysetStore, StoredKeyset, SystemClock};
+use crate::{utils::wrap_err, TinkError};
+use arc_swap::ArcSwap;
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+/// `ReloadingHandle` holds a keyset [`Handle`] for the keyset in a [`KeysetStore`], and re-reads
+/// the store when the keyset expires (after a time-to-live) or when it is told that the stored
+/// keyset has changed, so that long-running services pick up key rotations without restarting.
+///
+/// A `ReloadingHandle` can be shared between threads.  A re-read only replaces the handle if the
+/// stored keyset has changed, and the replacement is atomic: callers see either the old handle or
+/// the new one, and are not blocked while another thread reads the store or decrypts the keyset.
+/// Primitives that follow the current handle are available from [`ReloadingHandle::primitive`].
+///
+/// If a re-read triggered by expiry or by a change notification fails (for example because the
+/// store is unavailable), the previous handle remains in use and the re-read is retried once the
+/// time-to-live has elapsed again; the failure is available from
+/// [`ReloadingHandle::last_error`].
+pub struct ReloadingHandle {
+    store: Box<dyn KeysetStore + Send + Sync>,
+    master_key: Box<dyn crate::Aead + Send + Sync>,
+    ttl: Option<Duration>,
+    clock: Arc<dyn Clock>,
+    current: ArcSwap<Loaded>,
+    status: Mutex<ReloadStatus>,
+    // Serializes re-reads, so that the change check and the replacement are atomic.
+    reload: Mutex<()>,
+}
+
+/// A keyset read from the store, and the handle for it.
+struct Loaded {
+    handle: Handle,
+    stored: StoredKeyset,
+    generation: u64,
+}
+
+struct ReloadStatus {
+    loaded_at: SystemTime,
+    stale: bool,
+    last_error: Option<String>,
+}
+
+impl ReloadingHandle {
+    /// Create a handle for the keyset in `store`, decrypting it with `master_key`.  The keyset is
+    /// read immediately, and re-read on access once `ttl` has elapsed since the last read; with
+    /// a `ttl` of `None` the keyset is only re-read when requested.
+    pub fn new(
+        store: Box<dyn KeysetStore + Send + Sync>,
+        master_key: Box<dyn crate::Aead + Send + Sync>,
+        ttl: Option<Duration>,
+    ) -> Result<Self, TinkError> {
+        Self::new_with_clock(store, master_key, ttl, Arc::new(SystemClock))
+    }
+
+    /// Create a handle as for [`ReloadingHandle::new`], measuring the time-to-live with `clock`.
+    pub fn new_with_clock(
+        store: Box<dyn KeysetStore + Send + Sync>,
+        master_key: Box<dyn crate::Aead + Send + Sync>,
+        ttl: Option<Duration>,
+        clock: Arc<dyn Clock>,
+    ) -> Result<Self, TinkError> {
+        let stored = store
+            .load()
+            .map_err(|e| wrap_err("ReloadingHandle: cannot load keyset", e))?;
+        let handle = decrypt(&stored, master_key.box_clone())?;
+        let loaded_at = clock.now();
+        Ok(ReloadingHandle {
+            store,
+            master_key,
+            ttl,
+            clock,
+            current: ArcSwap::from_pointee(Loaded {
+                handle,
+                stored,
+                generation: 0,
+            }),
+            status: Mutex::new(ReloadStatus {
+                loaded_at,
+                stale: false,
+                last_error: None,
+            }),
+            reload: Mutex::new(()),
+        })
+    }
+
+    /// Return the current handle, first re-reading the store if the keyset has expired or been
+    /// marked as changed.
+    pub fn handle(&self) -> Result<Handle, TinkError> {
+        Ok(self.current()?.handle.clone())
+    }
+
+    /// Return the generation of the current handle, which starts at zero and increases each time
+    /// the handle is replaced by a changed keyset.  Does not re-read the store.
+    pub fn generation(&self) -> Result<u64, TinkError> {
+        Ok(self.current.load().generation)
+    }
+
+    /// Record that the stored keyset has (or may have) changed, typically in response to a
+    /// notification from the store.  The store is re-read on the next access.
+    pub fn notify_changed(&self) {
+        if let Ok(mut status) = self.status.lock() {
+            status.stale = true;
+        }
+    }
+
+    /// Re-read the store now, returning whether the keyset had changed.  Any failure is
+    /// returned, and leaves the current handle in place.
+    pub fn reload(&self) -> Result<bool, TinkError> {
+        let _guard = self
+            .reload
+            .lock()
+            .map_err(|_| TinkError::new("ReloadingHandle: lock poisoned"))?;
+        self.reload_now()
+    }
+
+    /// Return a description of the failure of the most recent re-read, if it failed.
+    pub fn last_error(&self) -> Option<String> {
+        self.status().ok().and_then(|s| s.last_error.clone())
+    }
+
+    /// Return a primitive that follows the current handle, creating a fresh primitive with `new`
+    /// (for example `tink_aead::new`) whenever the handle has been replaced.
+    pub fn primitive<P>(
+        &self,
+        new: fn(&Handle) -> Result<P, TinkError>,
+    ) -> ReloadingPrimitive<'_, P> {
+        ReloadingPrimitive {
+            source: self,
+            new,
+            current: RefCell::new(None),
+        }
+    }
+
+    fn status(&self) -> Result<std::sync::MutexGuard<'_, ReloadStatus>, TinkError> {
+        self.status
+            .lock()
+            .map_err(|_| TinkError::new("ReloadingHandle: lock poisoned"))
+    }
+
+    /// Indicate whether the keyset has expired or been marked as changed.
+    fn due(&self) -> Result<bool, TinkError> {
+        let status = self.status()?;
+        let expired = match self.ttl {
+            Some(ttl) => match self.clock.now().duration_since(status.loaded_at) {
+                Ok(elapsed) => elapsed >= ttl,
+                // The clock has gone backwards; re-read rather than trust the timestamp.
+                Err(_) => true,
+            },
+            None => false,
+        };
+        Ok(status.stale || expired)
+    }
+
+    /// Return the current keyset, re-reading the store if due.
+    fn current(&self) -> Result<Arc<Loaded>, TinkError> {
+        if self.due()? {
+            let _guard = self
+                .reload
+                .lock()
+                .map_err(|_| TinkError::new("ReloadingHandle: lock poisoned"))?;
+            // Another thread may have re-read the store while this one waited for the lock.  A
+            // failure keeps the previous keyset, and is retried once the time-to-live has elapsed
+            // again (or on the next change notification).
+            if self.due()? {
+                self.reload_now().ok();
+            }
+        }
+        Ok(self.current.load_full())
+    }
+
+    /// Re-read the store, replacing the current keyset if it has changed, and record the outcome.
+    /// Must be called with the `reload` lock held.  The store is read and the keyset decrypted
+    /// without holding any lock that readers need.
+    fn reload_now(&self) -> Result<bool, TinkError> {
+        // Clear the flag first, so that a notification that arrives during the re-read is not
+        // lost.
+        self.status()?.stale = false;
+        let result = self.load_changed();
+        let mut status = self.status()?;
+        status.loaded_at = self.clock.now();
+        status.last_error = result.as_ref().err().map(|e| format!("{}", e));
+        result
+    }
+
+    fn load_changed(&self) -> Result<bool, TinkError> {
+        let stored = self
+            .store
+            .load()
+            .map_err(|e| wrap_err("ReloadingHandle: cannot load keyset", e))?;
+        let current = self.current.load_full();
+        if stored == current.stored {
+            return Ok(false);
+        }
+        let handle = decrypt(&stored, self.master_key.box_clone())?;
+        self.current.store(Arc::new(Loaded {
+            handle,
+            stored,
+            generation: current.generation + 1,
+        }));
+        Ok(true)
+    }
+}
+
+/// A primitive created from the current handle of a [`ReloadingHandle`], and re-created when
+/// the handle is replaced; see [`ReloadingHandle::primitive`].
+pub struct ReloadingPrimitive<'a, P> {
+    source: &'a ReloadingHandle,
+    new: fn(&Handle) -> Result<P, TinkError>,
+    current: RefCell<Option<(u64, Rc<P>)>>,
+}
+
+impl<P> ReloadingPrimitive<'_, P> {
+    /// Return the primitive for the current handle, re-reading the store first if due.  A
+    /// primitive that is already in use remains valid (for the keyset it was created from).
+    pub fn get(&self) -> Result<Rc<P>, TinkError> {
+        let loaded = self.source.current()?;
+        if let Some((g, p)) = &*self.current.borrow() {
+            if *g == loaded.generation {
+                return Ok(p.clone());
+            }
+        }
+        let p = Rc::new((self.new)(&loaded.handle)?);
+        *self.current.borrow_mut() = Some((loaded.generation, p.clone()));
+        Ok(p)
+    }
+}
+
+/// Decrypt the keyset in `stored`, binding its metadata as for
+/// [`Manager::save_to_store`](super::Manager::save_to_store).
+fn decrypt(stored: &StoredKeyset, master_key: Box<dyn crate::Aead>) -> Result<Handle, TinkError> {
+    let mut mem = super::MemReaderWriter {
+        keyset: None,
+        encrypted_keyset: Some(stored.encrypted_keyset.clone()),
+    };
+    Handle::read_with_associated_data(&mut mem, master_key, &stored.metadata)
+        .map_err(|e| wrap_err("ReloadingHandle: cannot decrypt keyset", e))
+}
@@ -0,0 +1,200 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Startup self-tests for the keys in a keyset.
+
+use super::Handle;
+use crate::{utils::wrap_err, KeyId, Primitive, TinkError};
+use std::{
+    cell::RefCell,
+    io::{Read, Write},
+    rc::Rc,
+};
+use tink_proto::KeyStatusType;
+
+/// Data that is encrypted, MACed or signed during a self-test.
+const SELF_TEST_DATA: &[u8] = b"tink self-test data";
+/// Associated data (or context info) used during a self-test.
+const SELF_TEST_AAD: &[u8] = b"tink self-test";
+
+/// The result of a self-test of a single key in a keyset; see [`Handle::self_test`].
+#[derive(Debug)]
+pub struct KeySelfTest {
+    pub key_id: KeyId,
+    pub type_url: String,
+    /// `Ok` if the key passed its self-test, otherwise the reason it failed.
+    pub result: Result<(), TinkError>,
+}
+
+impl Handle {
+    /// Construct a primitive for every enabled key in the keyset and check that it works,
+    /// returning the result for each key.  This allows a service to fail fast at startup if a
+    /// keyset refers to an unsupported key type or holds corrupted key material.
+    ///
+    /// Keys are checked with a round trip where possible: data is encrypted and decrypted, MACs
+    /// and signatures are computed and verified (using the corresponding public key for private
+    /// keys), and PRF outputs are checked to be deterministic.  Keys whose primitive cannot be
+    /// checked on its own (public encryption and verification keys) are only constructed.
+    ///
+    /// Self-tests use the registered key managers, and ignore any restrictions, validity periods
+    /// or usage limits attached to the handle; they are not counted towards usage limits.
+    pub fn self_test(&self) -> Vec<KeySelfTest> {
+        self.keyset()
+            .key
+            .iter()
+            .filter(|key| key.status == KeyStatusType::Enabled as i32)
+            .map(|key| {
+                let type_url = key
+                    .key_data
+                    .as_ref()
+                    .map(|kd| kd.type_url.clone())
+                    .unwrap_or_default();
+                let result = match &key.key_data {
+                    Some(kd) => self_test_key(kd),
+                    None => Err("no key data".into()),
+                };
+                KeySelfTest {
+                    key_id: key.key_id,
+                    type_url,
+                    result,
+                }
+            })
+            .collect()
+    }
+
+    /// Run [`self_test`](Handle::self_test), returning an error that describes the first key to
+    /// fail, if any.
+    pub fn check_self_test(&self) -> Result<(), TinkError> {
+        match self.self_test().into_iter().find(|t| t.result.is_err()) {
+            Some(KeySelfTest {
+                key_id,
+                type_url,
+                result: Err(e),
+            }) => Err(wrap_err(
+                &format!(
+                    "keyset::Handle: self-test failed for key {} ({})",
+                    key_id, type_url
+                ),
+                e,
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Check the primitive for a single key.
+fn self_test_key(kd: &tink_proto::KeyData) -> Result<(), TinkError> {
+    let primitive = crate::registry::primitive_from_key_data(kd)
+        .map_err(|e| wrap_err("cannot create primitive", e))?;
+    match primitive {
+        Primitive::Aead(p) => {
+            let ct = p.encrypt(SELF_TEST_DATA, SELF_TEST_AAD)?;
+            check_round_trip(&p.decrypt(&ct, SELF_TEST_AAD)?)
+        }
+        Primitive::DeterministicAead(p) => {
+            let ct = p.encrypt_deterministically(SELF_TEST_DATA, SELF_TEST_AAD)?;
+            if p.encrypt_deterministically(SELF_TEST_DATA, SELF_TEST_AAD)? != ct {
+                return Err("encryption is not deterministic".into());
+            }
+            check_round_trip(&p.decrypt_deterministically(&ct, SELF_TEST_AAD)?)
+        }
+        Primitive::Mac(p) => {
+            let tag = p.compute_mac(SELF_TEST_DATA)?;
+            p.verify_mac(&tag, SELF_TEST_DATA)
+        }
+        Primitive::Prf(p) => {
+            let out = p.compute_prf(SELF_TEST_DATA, 16)?;
+            if p.compute_prf(SELF_TEST_DATA, 16)? != out {
+                return Err("PRF is not deterministic".into());
+            }
+            Ok(())
+        }
+        Primitive::Signer(p) => {
+            let signature = p.sign(SELF_TEST_DATA)?;
+            match public_primitive(kd)? {
+                Primitive::Verifier(v) => v.verify(&signature, SELF_TEST_DATA),
+                _ => Err("public key is not a verification key".into()),
+            }
+        }
+        Primitive::HybridDecrypt(p) => match public_primitive(kd)? {
+            Primitive::HybridEncrypt(e) => {
+                let ct = e.encrypt(SELF_TEST_DATA, SELF_TEST_AAD)?;
+                check_round_trip(&p.decrypt(&ct, SELF_TEST_AAD)?)
+            }
+            _ => Err("public key is not an encryption key".into()),
+        },
+        Primitive::StreamingAead(p) => {
+            let buf = SharedBuf::default();
+            let mut w = p.new_encrypting_writer(Box::new(buf.clone()), SELF_TEST_AAD)?;
+            w.write_all(SELF_TEST_DATA)
+                .map_err(|e| wrap_err("write failed", e))?;
+            w.close()?;
+            let ct = buf.0.borrow().clone();
+            let mut r =
+                p.new_decrypting_reader(Box::new(std::io::Cursor::new(ct)), SELF_TEST_AAD)?;
+            let mut pt = Vec::new();
+            r.read_to_end(&mut pt)
+                .map_err(|e| wrap_err("read failed", e))?;
+            check_round_trip(&pt)
+        }
+        Primitive::StreamingPrf(p) => {
+            let mut out = [0u8; 16];
+            p.compute_prf(SELF_TEST_DATA)?
+                .read_exact(&mut out)
+                .map_err(|e| wrap_err("read failed", e))?;
+            let mut again = [0u8; 16];
+            p.compute_prf(SELF_TEST_DATA)?
+                .read_exact(&mut again)
+                .map_err(|e| wrap_err("read failed", e))?;
+            if out != again {
+                return Err("PRF is not deterministic".into());
+            }
+            Ok(())
+        }
+        Primitive::HybridEncrypt(_) | Primitive::Verifier(_) => Ok(()),
+    }
+}
+
+/// Return the primitive for the public key corresponding to a private key.
+fn public_primitive(kd: &tink_proto::KeyData) -> Result<Primitive, TinkError> {
+    let pub_kd = super::handle::public_key_data(kd)?;
+    crate::registry::primitive_from_key_data(&pub_kd)
+        .map_err(|e| wrap_err("cannot create public key primitive", e))
+}
+
+/// Check that a round trip recovered the self-test data.
+fn check_round_trip(pt: &[u8]) -> Result<(), TinkError> {
+    if pt == SELF_TEST_DATA {
+        Ok(())
+    } else {
+        Err("round trip produced different data".into())
+    }
+}
+
+/// Writer that collects its output in a buffer that is shared between clones.
+#[derive(Clone, Default)]
+struct SharedBuf(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedBuf {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Storage for encrypted keysets together with their metadata.
+
+use crate::TinkError;
+use std::sync::Mutex;
+
+/// An encrypted keyset together with an (unencrypted) metadata blob, as held by a
+/// [`KeysetStore`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StoredKeyset {
+    pub encrypted_keyset: tink_proto::EncryptedKeyset,
+    /// Metadata kept alongside the keyset, such as an encoded [`AuditTrail`](super::AuditTrail).
+    /// Empty if there is no metadata.
+    pub metadata: Vec<u8>,
+}
+
+/// `KeysetStore` persists an encrypted keyset along with its metadata.
+pub trait KeysetStore {
+    /// Load the stored keyset.
+    fn load(&self) -> Result<StoredKeyset, TinkError>;
+
+    /// Store `stored`, replacing any existing keyset.
+    fn save(&self, stored: &StoredKeyset) -> Result<(), TinkError>;
+}
+
+/// `MemKeysetStore` implements [`KeysetStore`] with in-memory storage (typically for testing).
+#[derive(Debug, Default)]
+pub struct MemKeysetStore {
+    stored: Mutex<Option<StoredKeyset>>,
+}
+
+impl KeysetStore for MemKeysetStore {
+    fn load(&self) -> Result<StoredKeyset, TinkError> {
+        self.stored
+            .lock()
+            .map_err(|_| TinkError::new("MemKeysetStore: lock poisoned"))?
+            .clone()
+            .ok_or_else(|| "no keyset available".into())
+    }
+
+    fn save(&self, stored: &StoredKeyset) -> Result<(), TinkError> {
+        *self
+            .stored
+            .lock()
+            .map_err(|_| TinkError::new("MemKeysetStore: lock poisoned"))? = Some(stored.clone());
+        Ok(())
+    }
+}
@@ -0,0 +1,303 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Caching of unwrapped (decrypted) keysets.
+
+use crate::{utils::wrap_err, TinkError};
+use sha2::Digest;
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use zeroize::Zeroizing;
+
+/// Length of the expiry timestamp prefix on persisted cache entries.
+const EXPIRY_LEN: usize = 8;
+
+/// `UnwrapCacheMode` determines when an [`UnwrapCache`] is consulted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnwrapCacheMode {
+    /// Only use a cached keyset when the master key fails to decrypt the encrypted keyset
+    /// (e.g. because a KMS is unavailable).
+    Fallback,
+    /// Use a cached keyset if present, without invoking the master key at all.
+    Eager,
+}
+
+/// `UnwrapStore` provides persistent storage for [`UnwrapCache`] entries, so that cached keysets
+/// survive a process restart.  All data handed to the store is encrypted with the local key of
+/// the cache.
+pub trait UnwrapStore: Send + Sync {
+    /// Return the data stored under `id`, if any.
+    fn load(&self, id: &[u8]) -> Result<Option<Vec<u8>>, TinkError>;
+
+    /// Store `data` under `id`, replacing any existing entry.
+    fn store(&mut self, id: &[u8], data: &[u8]) -> Result<(), TinkError>;
+}
+
+/// `MemUnwrapStore` implements [`UnwrapStore`] with in-memory storage (typically for testing).
+#[derive(Default)]
+pub struct MemUnwrapStore {
+    entries: HashMap<Vec<u8>, Vec<u8>>,
+}
+
+impl MemUnwrapStore {
+    /// Return the number of stored entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Indicate whether the store is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Return an iterator over the stored `(id, data)` pairs, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8], &[u8])> {
+        self.entries
+            .iter()
+            .map(|(id, data)| (id.as_slice(), data.as_slice()))
+    }
+}
+
+impl UnwrapStore for MemUnwrapStore {
+    fn load(&self, id: &[u8]) -> Result<Option<Vec<u8>>, TinkError> {
+        Ok(self.entries.get(id).cloned())
+    }
+
+    fn store(&mut self, id: &[u8], data: &[u8]) -> Result<(), TinkError> {
+        self.entries.insert(id.to_vec(), data.to_vec());
+        Ok(())
+    }
+}
+
+/// An in-memory cache entry.  The keyset is held serialized, and encrypted under the local key
+/// when the cache has one; either way the data is zeroized when the entry is evicted or dropped.
+struct CacheEntry {
+    expiry: Instant,
+    data: Zeroizing<Vec<u8>>,
+}
+
+/// Persistent storage for an [`UnwrapCache`], together with the local key that protects it.
+struct PersistentStore {
+    store: Box<dyn UnwrapStore>,
+    local_key: Box<dyn crate::Aead>,
+}
+
+/// `UnwrapCache` remembers the result of decrypting encrypted keysets with a (typically
+/// KMS-backed) master key for a bounded time-to-live, for use with
+/// [`Handle::read_with_unwrap_cache`](super::Handle::read_with_unwrap_cache).
+///
+/// Entries are keyed by a digest of the encrypted keyset and its associated data, so a cached
+/// keyset is only ever returned for exactly the same ciphertext that produced it.  In-memory
+/// entries are encrypted under the local key of a cache created with
+/// [`UnwrapCache::new_with_store`], and are zeroized when they expire, when the cache is
+/// [cleared](UnwrapCache::clear) and when it is dropped.
+pub struct UnwrapCache {
+    mode: UnwrapCacheMode,
+    ttl: Duration,
+    entries: Mutex<HashMap<Vec<u8>, CacheEntry>>,
+    persistent: Option<Mutex<PersistentStore>>,
+}
+
+impl UnwrapCache {
+    /// Create an in-memory cache whose entries are valid for `ttl`.
+    pub fn new(mode: UnwrapCacheMode, ttl: Duration) -> Self {
+        UnwrapCache {
+            mode,
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+            persistent: None,
+        }
+    }
+
+    /// Create a cache whose entries are additionally persisted to `store`, encrypted under
+    /// `local_key`.  The `local_key` should not depend on the availability of the master key (for
+    /// example, it may be held in a local keychain or HSM).
+    pub fn new_with_store(
+        mode: UnwrapCacheMode,
+        ttl: Duration,
+        store: Box<dyn UnwrapStore>,
+        local_key: Box<dyn crate::Aead>,
+    ) -> Self {
+        UnwrapCache {
+            mode,
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+            persistent: Some(Mutex::new(PersistentStore { store, local_key })),
+        }
+    }
+
+    /// Return the mode of the cache.
+    pub fn mode(&self) -> UnwrapCacheMode {
+        self.mode
+    }
+
+    /// Remove all in-memory entries from the cache.  Persisted entries are unaffected, but will
+    /// expire after their time-to-live.
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+
+    /// Return the cached keyset for the given encrypted keyset, if present and not expired.
+    /// An expired in-memory entry is removed.
+    pub(crate) fn get(
+        &self,
+        encrypted_keyset: &[u8],
+        associated_data: &[u8],
+    ) -> Result<Option<tink_proto::Keyset>, TinkError> {
+        let id = cache_id(encrypted_keyset, associated_data);
+        let cached = {
+            let mut entries = self
+                .entries
+                .lock()
+                .map_err(|_| TinkError::new("UnwrapCache: lock poisoned"))?;
+            match entries.get(&id) {
+                Some(entry) if entry.expiry > Instant::now() => Some(entry.data.clone()),
+                Some(_) => {
+                    entries.remove(&id);
+                    None
+                }
+                None => None,
+            }
+        };
+        let persistent = match &self.persistent {
+            Some(p) => p
+                .lock()
+                .map_err(|_| TinkError::new("UnwrapCache: lock poisoned"))?,
+            None => {
+                return match cached {
+                    Some(serialized) => Ok(Some(super::handle::decode_keyset(&serialized)?)),
+                    None => Ok(None),
+                }
+            }
+        };
+        if let Some(ct) = cached {
+            let serialized = Zeroizing::new(
+                persistent
+                    .local_key
+                    .decrypt(&ct, &id)
+                    .map_err(|e| wrap_err("UnwrapCache: cannot decrypt cached entry", e))?,
+            );
+            return Ok(Some(super::handle::decode_keyset(&serialized)?));
+        }
+        let data = match persistent.store.load(&id)? {
+            Some(data) => data,
+            None => return Ok(None),
+        };
+        if data.len() < EXPIRY_LEN {
+            return Err("UnwrapCache: invalid persisted entry".into());
+        }
+        let (expiry, ct) = data.split_at(EXPIRY_LEN);
+        let expiry_secs = u64::from_be_bytes(expiry.try_into().unwrap()); // safe: length checked
+        if expiry_secs <= unix_now()? {
+            return Ok(None);
+        }
+        let serialized = Zeroizing::new(
+            persistent
+                .local_key
+                .decrypt(ct, &entry_ad(&id, expiry))
+                .map_err(|e| wrap_err("UnwrapCache: cannot decrypt persisted entry", e))?,
+        );
+        let keyset = super::handle::decode_keyset(&serialized)?;
+        Ok(Some(keyset))
+    }
+
+    /// Store the keyset resulting from decryption of the given encrypted keyset, removing any
+    /// expired in-memory entries.
+    pub(crate) fn insert(
+        &self,
+        encrypted_keyset: &[u8],
+        associated_data: &[u8],
+        keyset: &tink_proto::Keyset,
+    ) -> Result<(), TinkError> {
+        let id = cache_id(encrypted_keyset, associated_data);
+        let serialized = Zeroizing::new(super::handle::encode_keyset(keyset)?);
+        let mut persistent = match &self.persistent {
+            Some(p) => Some(
+                p.lock()
+                    .map_err(|_| TinkError::new("UnwrapCache: lock poisoned"))?,
+            ),
+            None => None,
+        };
+        let data = match &persistent {
+            Some(persistent) => Zeroizing::new(
+                persistent
+                    .local_key
+                    .encrypt(&serialized, &id)
+                    .map_err(|e| wrap_err("UnwrapCache: cannot encrypt entry", e))?,
+            ),
+            None => serialized.clone(),
+        };
+        {
+            let mut entries = self
+                .entries
+                .lock()
+                .map_err(|_| TinkError::new("UnwrapCache: lock poisoned"))?;
+            let now = Instant::now();
+            // Evicted entries are zeroized as they are dropped.
+            entries.retain(|_, entry| entry.expiry > now);
+            entries.insert(
+                id.clone(),
+                CacheEntry {
+                    expiry: now + self.ttl,
+                    data,
+                },
+            );
+        }
+        if let Some(persistent) = &mut persistent {
+            let expiry = (unix_now()? + self.ttl.as_secs()).to_be_bytes();
+            let ct = persistent
+                .local_key
+                .encrypt(&serialized, &entry_ad(&id, &expiry))
+                .map_err(|e| wrap_err("UnwrapCache: cannot encrypt entry", e))?;
+            let mut data = Vec::with_capacity(EXPIRY_LEN + ct.len());
+            data.extend_from_slice(&expiry);
+            data.extend_from_slice(&ct);
+            persistent.store.store(&id, &data)?;
+        }
+        Ok(())
+    }
+}
+
+/// Return the cache identifier for an encrypted keyset and its associated data.
+fn cache_id(encrypted_keyset: &[u8], associated_data: &[u8]) -> Vec<u8> {
+    let mut hasher = sha2::Sha256::new();
+    hasher.update((encrypted_keyset.len() as u64).to_be_bytes());
+    hasher.update(encrypted_keyset);
+    hasher.update(associated_data);
+    hasher.finalize().to_vec()
+}
+
+/// Return the associated data used to protect a persisted entry, binding the ciphertext to both
+/// its identifier and its expiry time.
+fn entry_ad(id: &[u8], expiry: &[u8]) -> Vec<u8> {
+    let mut ad = Vec::with_capacity(id.len() + expiry.len());
+    ad.extend_from_slice(id);
+    ad.extend_from_slice(expiry);
+    ad
+}
+
+fn unix_now() -> Result<u64, TinkError> {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .map_err(|e| wrap_err("UnwrapCache: invalid system time", e))
+}
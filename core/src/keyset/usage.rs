@@ -0,0 +1,297 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Restrictions on the operations permitted for a keyset.
+
+use crate::TinkError;
+
+/// `KeyUsage` restricts the operations that primitives created from a
+/// [`Handle`](crate::keyset::Handle) may perform.  The restriction is checked when the primitive
+/// is created (for primitive types that only perform a forbidden operation) and again on each
+/// operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyUsage {
+    /// All operations are permitted.
+    Unrestricted,
+    /// Only encryption is permitted (AEAD, deterministic AEAD, hybrid and streaming AEAD).
+    EncryptOnly,
+    /// Only decryption is permitted (AEAD, deterministic AEAD, hybrid and streaming AEAD).
+    DecryptOnly,
+    /// Only verification is permitted (MAC and signature).
+    VerifyOnly,
+}
+
+impl KeyUsage {
+    /// Indicate whether encryption is permitted.
+    pub fn allows_encrypt(&self) -> bool {
+        matches!(self, KeyUsage::Unrestricted | KeyUsage::EncryptOnly)
+    }
+
+    /// Indicate whether decryption is permitted.
+    pub fn allows_decrypt(&self) -> bool {
+        matches!(self, KeyUsage::Unrestricted | KeyUsage::DecryptOnly)
+    }
+
+    /// Indicate whether computation of MACs, signatures and PRFs is permitted.
+    pub fn allows_compute(&self) -> bool {
+        matches!(self, KeyUsage::Unrestricted)
+    }
+
+    /// Indicate whether verification of MACs and signatures is permitted.
+    pub fn allows_verify(&self) -> bool {
+        matches!(self, KeyUsage::Unrestricted | KeyUsage::VerifyOnly)
+    }
+}
+
+/// Return `p` restricted to the operations permitted by `usage`.  Primitives that only perform
+/// forbidden operations are rejected, and those that perform both permitted and forbidden
+/// operations are wrapped so that the forbidden operations fail.
+pub(crate) fn restrict_primitive(
+    p: crate::Primitive,
+    usage: KeyUsage,
+) -> Result<crate::Primitive, TinkError> {
+    use crate::Primitive;
+    if usage == KeyUsage::Unrestricted {
+        return Ok(p);
+    }
+    match p {
+        Primitive::Aead(p) => {
+            if !usage.allows_encrypt() && !usage.allows_decrypt() {
+                return Err("usage restriction does not permit AEAD".into());
+            }
+            Ok(Primitive::Aead(Box::new(Restricted { inner: p, usage })))
+        }
+        Primitive::DeterministicAead(p) => {
+            if !usage.allows_encrypt() && !usage.allows_decrypt() {
+                return Err("usage restriction does not permit deterministic AEAD".into());
+            }
+            Ok(Primitive::DeterministicAead(Box::new(Restricted {
+                inner: p,
+                usage,
+            })))
+        }
+        Primitive::StreamingAead(p) => {
+            if !usage.allows_encrypt() && !usage.allows_decrypt() {
+                return Err("usage restriction does not permit streaming AEAD".into());
+            }
+            Ok(Primitive::StreamingAead(Box::new(Restricted {
+                inner: p,
+                usage,
+            })))
+        }
+        Primitive::Mac(p) => {
+            if !usage.allows_compute() && !usage.allows_verify() {
+                return Err("usage restriction does not permit MAC".into());
+            }
+            Ok(Primitive::Mac(Box::new(Restricted { inner: p, usage })))
+        }
+        Primitive::HybridEncrypt(_) if !usage.allows_encrypt() => {
+            Err("usage restriction: encryption not permitted".into())
+        }
+        Primitive::HybridDecrypt(_) if !usage.allows_decrypt() => {
+            Err("usage restriction: decryption not permitted".into())
+        }
+        Primitive::Signer(_) if !usage.allows_compute() => {
+            Err("usage restriction: signing not permitted".into())
+        }
+        Primitive::Verifier(_) if !usage.allows_verify() => {
+            Err("usage restriction: verification not permitted".into())
+        }
+        Primitive::Prf(_) | Primitive::StreamingPrf(_) if !usage.allows_compute() => {
+            Err("usage restriction: PRF computation not permitted".into())
+        }
+        p => Ok(p),
+    }
+}
+
+/// A primitive whose operations are checked against a [`KeyUsage`] before being forwarded.
+struct Restricted<P: ?Sized> {
+    inner: Box<P>,
+    usage: KeyUsage,
+}
+
+impl<P: ?Sized> Restricted<P> {
+    fn check_encrypt(&self) -> Result<(), TinkError> {
+        if self.usage.allows_encrypt() {
+            Ok(())
+        } else {
+            Err("usage restriction: encryption not permitted".into())
+        }
+    }
+
+    fn check_decrypt(&self) -> Result<(), TinkError> {
+        if self.usage.allows_decrypt() {
+            Ok(())
+        } else {
+            Err("usage restriction: decryption not permitted".into())
+        }
+    }
+}
+
+impl Clone for Restricted<dyn crate::Aead> {
+    fn clone(&self) -> Self {
+        Restricted {
+            inner: self.inner.box_clone(),
+            usage: self.usage,
+        }
+    }
+}
+
+impl crate::Aead for Restricted<dyn crate::Aead> {
+    fn encrypt(&self, plaintext: &[u8], additional_data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.check_encrypt()?;
+        self.inner.encrypt(plaintext, additional_data)
+    }
+
+    fn decrypt(&self, ciphertext: &[u8], additional_data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.check_decrypt()?;
+        self.inner.decrypt(ciphertext, additional_data)
+    }
+
+    fn encrypt_with_aad_reader(
+        &self,
+        plaintext: &[u8],
+        additional_data: &mut dyn std::io::Read,
+    ) -> Result<Vec<u8>, TinkError> {
+        self.check_encrypt()?;
+        self.inner
+            .encrypt_with_aad_reader(plaintext, additional_data)
+    }
+
+    fn decrypt_with_aad_reader(
+        &self,
+        ciphertext: &[u8],
+        additional_data: &mut dyn std::io::Read,
+    ) -> Result<Vec<u8>, TinkError> {
+        self.check_decrypt()?;
+        self.inner
+            .decrypt_with_aad_reader(ciphertext, additional_data)
+    }
+}
+
+impl Clone for Restricted<dyn crate::DeterministicAead> {
+    fn clone(&self) -> Self {
+        Restricted {
+            inner: self.inner.box_clone(),
+            usage: self.usage,
+        }
+    }
+}
+
+impl crate::DeterministicAead for Restricted<dyn crate::DeterministicAead> {
+    fn encrypt_deterministically(
+        &self,
+        plaintext: &[u8],
+        additional_data: &[u8],
+    ) -> Result<Vec<u8>, TinkError> {
+        self.check_encrypt()?;
+        self.inner
+            .encrypt_deterministically(plaintext, additional_data)
+    }
+
+    fn decrypt_deterministically(
+        &self,
+        ciphertext: &[u8],
+        additional_data: &[u8],
+    ) -> Result<Vec<u8>, TinkError> {
+        self.check_decrypt()?;
+        self.inner
+            .decrypt_deterministically(ciphertext, additional_data)
+    }
+}
+
+impl Clone for Restricted<dyn crate::StreamingAead> {
+    fn clone(&self) -> Self {
+        Restricted {
+            inner: self.inner.box_clone(),
+            usage: self.usage,
+        }
+    }
+}
+
+impl crate::StreamingAead for Restricted<dyn crate::StreamingAead> {
+    fn new_encrypting_writer(
+        &self,
+        w: Box<dyn std::io::Write>,
+        aad: &[u8],
+    ) -> Result<Box<dyn crate::EncryptingWrite>, TinkError> {
+        self.check_encrypt()?;
+        self.inner.new_encrypting_writer(w, aad)
+    }
+
+    fn new_decrypting_reader(
+        &self,
+        r: Box<dyn std::io::Read>,
+        aad: &[u8],
+    ) -> Result<Box<dyn std::io::Read>, TinkError> {
+        self.check_decrypt()?;
+        self.inner.new_decrypting_reader(r, aad)
+    }
+
+    fn new_encrypting_writer_with_segment_aad(
+        &self,
+        w: Box<dyn std::io::Write>,
+        aad: &[u8],
+        segment_aad: &[u8],
+    ) -> Result<Box<dyn crate::EncryptingWrite>, TinkError> {
+        self.check_encrypt()?;
+        self.inner
+            .new_encrypting_writer_with_segment_aad(w, aad, segment_aad)
+    }
+
+    fn new_decrypting_reader_with_segment_aad(
+        &self,
+        r: Box<dyn std::io::Read>,
+        aad: &[u8],
+        segment_aad: &[u8],
+    ) -> Result<Box<dyn std::io::Read>, TinkError> {
+        self.check_decrypt()?;
+        self.inner
+            .new_decrypting_reader_with_segment_aad(r, aad, segment_aad)
+    }
+}
+
+impl Clone for Restricted<dyn crate::Mac> {
+    fn clone(&self) -> Self {
+        Restricted {
+            inner: self.inner.box_clone(),
+            usage: self.usage,
+        }
+    }
+}
+
+impl crate::Mac for Restricted<dyn crate::Mac> {
+    fn compute_mac(&self, data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        if !self.usage.allows_compute() {
+            return Err("usage restriction: MAC computation not permitted".into());
+        }
+        self.inner.compute_mac(data)
+    }
+
+    fn verify_mac(&self, mac: &[u8], data: &[u8]) -> Result<(), TinkError> {
+        if !self.usage.allows_verify() {
+            return Err("usage restriction: MAC verification not permitted".into());
+        }
+        self.inner.verify_mac(mac, data)
+    }
+
+    fn verify_mac_with_key_id(&self, mac: &[u8], data: &[u8]) -> Result<crate::KeyId, TinkError> {
+        if !self.usage.allows_verify() {
+            return Err("usage restriction: MAC verification not permitted".into());
+        }
+        self.inner.verify_mac_with_key_id(mac, data)
+    }
+}
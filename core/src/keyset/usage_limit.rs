@@ -0,0 +1,139 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Per-key usage counters and usage limits.
+
+use crate::{KeyId, TinkError};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// `UsageStats` records how much a key has been used to create new ciphertexts, MACs or
+/// signatures.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UsageStats {
+    /// Number of operations performed with the key.
+    pub operations: u64,
+    /// Total length of the input data processed by those operations.
+    pub bytes: u64,
+}
+
+/// `UsageLimit` bounds how much a key may be used to create new ciphertexts, MACs or signatures.
+/// Once the bound would be exceeded, further operations with the key fail; decryption and
+/// verification are unaffected.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UsageLimit {
+    /// Maximum number of operations; `None` means no limit.
+    pub max_operations: Option<u64>,
+    /// Maximum total length of input data; `None` means no limit.
+    pub max_bytes: Option<u64>,
+}
+
+impl UsageLimit {
+    /// Limit for AES-GCM keys used with random 96-bit nonces: at most 2^32 invocations per key,
+    /// as required by NIST SP 800-38D section 8.3.
+    pub fn aes_gcm() -> Self {
+        UsageLimit {
+            max_operations: Some(1 << 32),
+            max_bytes: None,
+        }
+    }
+
+    /// Indicate whether one more operation on `bytes` bytes of input is permitted, given the
+    /// usage so far.
+    fn allows(&self, stats: &UsageStats, bytes: u64) -> bool {
+        if let Some(max) = self.max_operations {
+            if stats.operations >= max {
+                return false;
+            }
+        }
+        if let Some(max) = self.max_bytes {
+            if stats.bytes.saturating_add(bytes) > max {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// `UsageCounters` holds the usage counters for the keys of a keyset.  It is shared between a
+/// [`Handle`](crate::keyset::Handle), its clones and the primitives created from them, so that
+/// usage is counted across all of them.
+#[derive(Default)]
+pub struct UsageCounters {
+    keys: Mutex<HashMap<KeyId, UsageStats>>,
+}
+
+impl UsageCounters {
+    /// Return the usage so far of the key with the given ID.
+    pub fn stats(&self, key_id: KeyId) -> UsageStats {
+        let keys = match self.keys.lock() {
+            Ok(keys) => keys,
+            Err(p) => p.into_inner(),
+        };
+        keys.get(&key_id).copied().unwrap_or_default()
+    }
+
+    /// Record an operation on `bytes` bytes of input with the key with the given ID, or return an
+    /// error (without recording anything) if that would exceed the given usage limit.
+    pub fn record(
+        &self,
+        key_id: KeyId,
+        bytes: usize,
+        limit: Option<&UsageLimit>,
+    ) -> Result<(), TinkError> {
+        let mut keys = self
+            .keys
+            .lock()
+            .map_err(|_| TinkError::new("usage counters poisoned"))?;
+        let stats = keys.entry(key_id).or_default();
+        let bytes = bytes as u64;
+        if let Some(limit) = limit {
+            if !limit.allows(stats, bytes) {
+                return Err("primary key has reached its usage limit".into());
+            }
+        }
+        stats.operations += 1;
+        stats.bytes = stats.bytes.saturating_add(bytes);
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for UsageCounters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "UsageCounters")
+    }
+}
+
+/// `UsageCheck` combines the ID and [`UsageLimit`] of a primary key with the [`UsageCounters`] its
+/// usage is recorded in.  It is attached to a [`PrimitiveSet`](crate::primitiveset::PrimitiveSet) so that primitive
+/// wrappers can count the use of the primary key, and refuse to use it beyond its limit.
+#[derive(Clone, Debug)]
+pub struct UsageCheck {
+    pub key_id: KeyId,
+    pub limit: Option<UsageLimit>,
+    pub counters: Arc<UsageCounters>,
+}
+
+impl UsageCheck {
+    /// Record an operation on `bytes` bytes of input, or return an error if the key has reached
+    /// its usage limit.
+    pub fn record(&self, bytes: usize) -> Result<(), TinkError> {
+        self.counters
+            .record(self.key_id, bytes, self.limit.as_ref())
+    }
+}
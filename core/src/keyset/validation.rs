@@ -17,18 +17,62 @@
 //! Keyset validation functions.
 
 use crate::TinkError;
+use std::cell::Cell;
+
+/// Policy for keys whose version is higher than the key manager handling them was built for,
+/// as can happen when a fleet with mixed library versions rolls forward to a new key version.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyVersionPolicy {
+    /// Reject keys with a higher version.
+    Reject,
+    /// Accept keys with a higher version, ignoring any fields that the key manager does not know
+    /// about, and emit a warning (via the [`trace`](crate::trace) module).
+    WarnAndAccept,
+}
+
+impl Default for KeyVersionPolicy {
+    fn default() -> Self {
+        KeyVersionPolicy::Reject
+    }
+}
+
+thread_local! {
+    static KEY_VERSION_POLICY: Cell<KeyVersionPolicy> = Cell::new(KeyVersionPolicy::Reject);
+}
+
+/// Run `f` with the given [`KeyVersionPolicy`] applied to any [`validate_key_version`] checks
+/// that it performs on the current thread.  Primitives created from a
+/// [`Handle`](crate::keyset::Handle) use the handle's policy (see
+/// [`Handle::set_key_version_policy`](crate::keyset::Handle::set_key_version_policy)).
+pub fn with_key_version_policy<T, F: FnOnce() -> T>(policy: KeyVersionPolicy, f: F) -> T {
+    /// Restore the previous policy on exit, even if `f` panics.
+    struct Restore(KeyVersionPolicy);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            KEY_VERSION_POLICY.with(|p| p.set(self.0));
+        }
+    }
+    let _restore = Restore(KEY_VERSION_POLICY.with(|p| p.replace(policy)));
+    f()
+}
 
 /// Check whether the given version is valid. The version is valid
-/// only if it is the range [0..max_expected].
+/// only if it is the range [0..max_expected], unless the current [`KeyVersionPolicy`] (see
+/// [`with_key_version_policy`]) accepts higher versions.
 pub fn validate_key_version(version: u32, max_expected: u32) -> Result<(), TinkError> {
-    if version > max_expected {
-        Err(format!(
+    if version <= max_expected {
+        return Ok(());
+    }
+    match KEY_VERSION_POLICY.with(|p| p.get()) {
+        KeyVersionPolicy::Reject => Err(format!(
             "key has version {}; only keys with version in range [0..{}] are supported",
             version, max_expected
         )
-        .into())
-    } else {
-        Ok(())
+        .into()),
+        KeyVersionPolicy::WarnAndAccept => {
+            crate::trace::newer_key_version_accepted(version, max_expected);
+            Ok(())
+        }
     }
 }
 
@@ -80,6 +124,7 @@ pub fn validate_key(key: &tink_proto::keyset::Key) -> Result<(), TinkError> {
         && key.output_prefix_type != tink_proto::OutputPrefixType::Legacy as i32
         && key.output_prefix_type != tink_proto::OutputPrefixType::Raw as i32
         && key.output_prefix_type != tink_proto::OutputPrefixType::Crunchy as i32
+        && crate::registry::custom_output_prefix(key.output_prefix_type).is_none()
     {
         Err(format!("key {} has unknown prefix", key.key_id).into())
     } else if key.status != tink_proto::KeyStatusType::Enabled as i32
@@ -0,0 +1,131 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Validity periods for keys.
+
+use crate::TinkError;
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+/// `Clock` is a source of the current time, used when checking key validity periods.
+pub trait Clock: Send + Sync {
+    /// Return the current time.
+    fn now(&self) -> SystemTime;
+}
+
+/// `SystemClock` implements [`Clock`] using the system time.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// `FixedClock` implements [`Clock`] with an explicitly controlled time (typically for testing).
+#[derive(Debug)]
+pub struct FixedClock {
+    now: Mutex<SystemTime>,
+}
+
+impl FixedClock {
+    /// Create a clock that reports the given time.
+    pub fn new(now: SystemTime) -> Self {
+        FixedClock {
+            now: Mutex::new(now),
+        }
+    }
+
+    /// Set the time reported by the clock.
+    pub fn set(&self, now: SystemTime) {
+        if let Ok(mut t) = self.now.lock() {
+            *t = now;
+        }
+    }
+
+    /// Move the time reported by the clock forward.
+    pub fn advance(&self, d: Duration) {
+        if let Ok(mut t) = self.now.lock() {
+            *t += d;
+        }
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> SystemTime {
+        match self.now.lock() {
+            Ok(t) => *t,
+            Err(p) => *p.into_inner(),
+        }
+    }
+}
+
+/// `KeyValidity` describes the period during which a key may be used to create new ciphertexts,
+/// MACs or signatures.  Keys outside their validity period may still be used for decryption and
+/// verification.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct KeyValidity {
+    /// Time before which the key is not yet valid; `None` means no lower bound.
+    pub not_before: Option<SystemTime>,
+    /// Time after which the key is no longer valid; `None` means no upper bound.
+    pub not_after: Option<SystemTime>,
+}
+
+impl KeyValidity {
+    /// Indicate whether the validity period includes the given time.
+    pub fn is_valid_at(&self, t: SystemTime) -> bool {
+        if let Some(not_before) = self.not_before {
+            if t < not_before {
+                return false;
+            }
+        }
+        if let Some(not_after) = self.not_after {
+            if t > not_after {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// `ValidityCheck` combines a [`KeyValidity`] with the [`Clock`] it is checked against.  It is
+/// attached to a [`PrimitiveSet`](crate::primitiveset::PrimitiveSet) so that primitive wrappers
+/// can refuse to use an expired primary key.
+#[derive(Clone)]
+pub struct ValidityCheck {
+    pub validity: KeyValidity,
+    pub clock: Arc<dyn Clock>,
+}
+
+impl ValidityCheck {
+    /// Return an error if the current time is outside the validity period.
+    pub fn check(&self) -> Result<(), TinkError> {
+        if self.validity.is_valid_at(self.clock.now()) {
+            Ok(())
+        } else {
+            Err("primary key is outside its validity period".into())
+        }
+    }
+}
+
+impl std::fmt::Debug for ValidityCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ValidityCheck({:?})", self.validity)
+    }
+}
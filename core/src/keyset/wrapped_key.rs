@@ -0,0 +1,220 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Exchange of raw key material wrapped under a key-encryption key.
+
+use super::{Handle, Manager};
+use crate::{
+    registry::{CleartextLoad, CleartextSource},
+    utils::wrap_err,
+    KeyId, KeyWrap, TinkError,
+};
+use tink_proto::{prost::Message, KeyTemplate, OutputPrefixType};
+
+const AES_GCM_TYPE_URL: &str = "type.googleapis.com/google.crypto.tink.AesGcmKey";
+const AES_GCM_SIV_TYPE_URL: &str = "type.googleapis.com/google.crypto.tink.AesGcmSivKey";
+const AES_SIV_TYPE_URL: &str = "type.googleapis.com/google.crypto.tink.AesSivKey";
+const CHA_CHA20_POLY1305_TYPE_URL: &str =
+    "type.googleapis.com/google.crypto.tink.ChaCha20Poly1305Key";
+const X_CHA_CHA20_POLY1305_TYPE_URL: &str =
+    "type.googleapis.com/google.crypto.tink.XChaCha20Poly1305Key";
+const HMAC_TYPE_URL: &str = "type.googleapis.com/google.crypto.tink.HmacKey";
+
+/// Size in bytes of (X)ChaCha20-Poly1305 keys.
+const CHA_CHA20_KEY_SIZE: usize = 32;
+
+/// Methods for exchanging the raw key material of symmetric keys with systems (such as HSMs)
+/// that only accept keys wrapped under a key-encryption key, for example with AES Key Wrap.
+///
+/// The key types supported are AES-GCM, AES-GCM-SIV, ChaCha20-Poly1305, XChaCha20-Poly1305,
+/// AES-SIV and HMAC.  Only the key material is exchanged; the other party must agree on the
+/// algorithm and parameters, which are given by the [`KeyTemplate`] on import.
+impl Handle {
+    /// Return the raw key material of the key with ID `key_id`, wrapped under `kek`.  As with
+    /// [`Handle::write`], this fails for a handle whose usage is restricted (see
+    /// [`Handle::restrict`]).
+    pub fn export_wrapped_key(
+        &self,
+        key_id: KeyId,
+        kek: &dyn KeyWrap,
+    ) -> Result<Vec<u8>, TinkError> {
+        if self.usage() != super::KeyUsage::Unrestricted {
+            return Err("keyset: cannot export key from usage-restricted keyset".into());
+        }
+        let key = self
+            .keyset()
+            .key
+            .iter()
+            .find(|k| k.key_id == key_id)
+            .ok_or_else(|| TinkError::new(&format!("keyset: key {} not found", key_id)))?;
+        let key_data = key.key_data.as_ref().ok_or_else(|| {
+            TinkError::new(&format!("keyset: key {} has no key material", key_id))
+        })?;
+        let raw = raw_key_material(key_data)
+            .map_err(|e| wrap_err(&format!("keyset: cannot export key {}", key_id), e))?;
+        kek.wrap_key(&raw)
+            .map_err(|e| wrap_err("keyset: cannot wrap key", e))
+    }
+
+    /// Create a keyset [`Handle`] holding a single key, with the raw key material unwrapped from
+    /// `wrapped` with `kek` and the key type, parameters and output prefix type of `kt`.  The
+    /// size of the key material must match the template.
+    ///
+    /// The key manager for the key type must be registered.  Any registered
+    /// [`Gatekeeper`](crate::registry::Gatekeeper)s are consulted (with
+    /// [`CleartextSource::Import`]) before the handle is returned.
+    pub fn import_wrapped_key(
+        wrapped: &[u8],
+        kek: &dyn KeyWrap,
+        kt: &KeyTemplate,
+    ) -> Result<Handle, TinkError> {
+        let output_prefix_type = OutputPrefixType::from_i32(kt.output_prefix_type)
+            .ok_or_else(|| TinkError::new("keyset: unknown output prefix type"))?;
+        let raw = kek
+            .unwrap_key(wrapped)
+            .map_err(|e| wrap_err("keyset: cannot unwrap key", e))?;
+        let key_data =
+            key_data_from_raw(kt, raw).map_err(|e| wrap_err("keyset: cannot import key", e))?;
+
+        let mut ksm = Manager::new();
+        ksm.add_imported_key_data(key_data, output_prefix_type, true)?;
+        let h = ksm.handle()?;
+        crate::registry::check_cleartext_load(&CleartextLoad {
+            source: CleartextSource::Import,
+            keyset_info: h.keyset_info(),
+        })?;
+        Ok(h)
+    }
+}
+
+/// Return the raw key material held in `key_data`.
+fn raw_key_material(key_data: &tink_proto::KeyData) -> Result<Vec<u8>, TinkError> {
+    let value = key_data.value.as_slice();
+    let invalid = |e| wrap_err("invalid key", e);
+    Ok(match key_data.type_url.as_str() {
+        AES_GCM_TYPE_URL => {
+            tink_proto::AesGcmKey::decode(value)
+                .map_err(invalid)?
+                .key_value
+        }
+        AES_GCM_SIV_TYPE_URL => {
+            tink_proto::AesGcmSivKey::decode(value)
+                .map_err(invalid)?
+                .key_value
+        }
+        AES_SIV_TYPE_URL => {
+            tink_proto::AesSivKey::decode(value)
+                .map_err(invalid)?
+                .key_value
+        }
+        CHA_CHA20_POLY1305_TYPE_URL => {
+            tink_proto::ChaCha20Poly1305Key::decode(value)
+                .map_err(invalid)?
+                .key_value
+        }
+        X_CHA_CHA20_POLY1305_TYPE_URL => {
+            tink_proto::XChaCha20Poly1305Key::decode(value)
+                .map_err(invalid)?
+                .key_value
+        }
+        HMAC_TYPE_URL => {
+            tink_proto::HmacKey::decode(value)
+                .map_err(invalid)?
+                .key_value
+        }
+        other => return Err(format!("unsupported key type {}", other).into()),
+    })
+}
+
+/// Build the key data for a key of the type described by `kt`, holding `raw` key material.
+fn key_data_from_raw(kt: &KeyTemplate, raw: Vec<u8>) -> Result<tink_proto::KeyData, TinkError> {
+    let format = kt.value.as_slice();
+    let invalid = |e| wrap_err("invalid key format", e);
+    let check_size = |want: usize| {
+        if raw.len() == want {
+            Ok(())
+        } else {
+            Err(TinkError::new(&format!(
+                "key material has {} bytes, template requires {}",
+                raw.len(),
+                want
+            )))
+        }
+    };
+    let mut value = Vec::new();
+    let encoded = match kt.type_url.as_str() {
+        AES_GCM_TYPE_URL => {
+            let format = tink_proto::AesGcmKeyFormat::decode(format).map_err(invalid)?;
+            check_size(format.key_size as usize)?;
+            tink_proto::AesGcmKey {
+                version: 0,
+                key_value: raw,
+            }
+            .encode(&mut value)
+        }
+        AES_GCM_SIV_TYPE_URL => {
+            let format = tink_proto::AesGcmSivKeyFormat::decode(format).map_err(invalid)?;
+            check_size(format.key_size as usize)?;
+            tink_proto::AesGcmSivKey {
+                version: 0,
+                key_value: raw,
+            }
+            .encode(&mut value)
+        }
+        AES_SIV_TYPE_URL => {
+            let format = tink_proto::AesSivKeyFormat::decode(format).map_err(invalid)?;
+            check_size(format.key_size as usize)?;
+            tink_proto::AesSivKey {
+                version: 0,
+                key_value: raw,
+            }
+            .encode(&mut value)
+        }
+        CHA_CHA20_POLY1305_TYPE_URL => {
+            check_size(CHA_CHA20_KEY_SIZE)?;
+            tink_proto::ChaCha20Poly1305Key {
+                version: 0,
+                key_value: raw,
+            }
+            .encode(&mut value)
+        }
+        X_CHA_CHA20_POLY1305_TYPE_URL => {
+            check_size(CHA_CHA20_KEY_SIZE)?;
+            tink_proto::XChaCha20Poly1305Key {
+                version: 0,
+                key_value: raw,
+            }
+            .encode(&mut value)
+        }
+        HMAC_TYPE_URL => {
+            let format = tink_proto::HmacKeyFormat::decode(format).map_err(invalid)?;
+            check_size(format.key_size as usize)?;
+            tink_proto::HmacKey {
+                version: 0,
+                params: format.params,
+                key_value: raw,
+            }
+            .encode(&mut value)
+        }
+        other => return Err(format!("unsupported key type {}", other).into()),
+    };
+    encoded.map_err(|e| wrap_err("failed to encode key", e))?;
+    Ok(tink_proto::KeyData {
+        type_url: kt.type_url.clone(),
+        value,
+        key_material_type: tink_proto::key_data::KeyMaterialType::Symmetric as i32,
+    })
+}
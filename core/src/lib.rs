@@ -19,11 +19,14 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![deny(broken_intra_doc_links)]
 
+pub mod associated_data;
 pub mod cryptofmt;
 pub mod keyset;
+pub mod pipeline;
 pub mod primitiveset;
 pub mod registry;
 pub mod subtle;
+pub mod trace;
 pub mod utils;
 pub use utils::TinkError;
 
@@ -43,12 +46,16 @@ mod hybrid_decrypt;
 pub use hybrid_decrypt::*;
 mod hybrid_encrypt;
 pub use hybrid_encrypt::*;
+mod key_wrap;
+pub use key_wrap::*;
 mod mac;
 pub use mac::*;
 mod prf;
 pub use prf::*;
 mod signer;
 pub use signer::*;
+mod streaming_prf;
+pub use streaming_prf::*;
 mod streamingaead;
 pub use streamingaead::*;
 mod verifier;
@@ -64,6 +71,7 @@ pub enum Primitive {
     Prf(Box<dyn Prf>),
     Signer(Box<dyn Signer>),
     StreamingAead(Box<dyn StreamingAead>),
+    StreamingPrf(Box<dyn StreamingPrf>),
     Verifier(Box<dyn Verifier>),
 }
 
@@ -81,11 +89,68 @@ impl Clone for Primitive {
             Primitive::Prf(p) => Primitive::Prf(p.box_clone()),
             Primitive::Signer(p) => Primitive::Signer(p.box_clone()),
             Primitive::StreamingAead(p) => Primitive::StreamingAead(p.box_clone()),
+            Primitive::StreamingPrf(p) => Primitive::StreamingPrf(p.box_clone()),
             Primitive::Verifier(p) => Primitive::Verifier(p.box_clone()),
         }
     }
 }
 
+/// Generate fallible accessors for each variant of [`Primitive`].
+macro_rules! primitive_accessors {
+    { $( $variant:ident: $trait:ident => $into:ident, $as:ident );* $(;)? } => {
+        impl Primitive {
+            /// Return the name of the type of primitive.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $( Primitive::$variant(_) => stringify!($variant), )*
+                }
+            }
+
+            $(
+                #[doc = concat!("Convert into a [`", stringify!($trait),
+                                "`] primitive, or return an error for a different type of ",
+                                "primitive.")]
+                pub fn $into(self) -> Result<Box<dyn $trait>, TinkError> {
+                    match self {
+                        Primitive::$variant(p) => Ok(p),
+                        p => Err(format!(
+                            "expected {} primitive, got {}",
+                            stringify!($variant),
+                            p.name()
+                        )
+                        .into()),
+                    }
+                }
+
+                #[doc = concat!("Return a reference to the [`", stringify!($trait),
+                                "`] primitive, or `None` for a different type of primitive.")]
+                pub fn $as(&self) -> Option<&dyn $trait> {
+                    match self {
+                        Primitive::$variant(p) => Some(p.as_ref()),
+                        _ => None,
+                    }
+                }
+            )*
+        }
+    };
+}
+
+// Fallible conversions from the [`Primitive`] `enum` wrapper to specific primitive types.  (These
+// cannot be `TryFrom` implementations, as the infallible `From` implementations below imply a
+// conflicting blanket `TryFrom` implementation.)
+primitive_accessors! {
+    Aead: Aead => into_aead, as_aead;
+    DeterministicAead: DeterministicAead => into_deterministic_aead, as_deterministic_aead;
+    HybridDecrypt: HybridDecrypt => into_hybrid_decrypt, as_hybrid_decrypt;
+    HybridEncrypt: HybridEncrypt => into_hybrid_encrypt, as_hybrid_encrypt;
+    Mac: Mac => into_mac, as_mac;
+    Prf: Prf => into_prf, as_prf;
+    Signer: Signer => into_signer, as_signer;
+    StreamingAead: StreamingAead => into_streaming_aead, as_streaming_aead;
+    StreamingPrf: StreamingPrf => into_streaming_prf, as_streaming_prf;
+    Verifier: Verifier => into_verifier, as_verifier;
+}
+
 // Conversions from the [`Primitive`] `enum` wrapper to specific primitive types.  Will panic if the
 // wrong type is passed in.
 
@@ -161,6 +226,15 @@ impl From<Primitive> for Box<dyn StreamingAead> {
     }
 }
 
+impl From<Primitive> for Box<dyn StreamingPrf> {
+    fn from(p: Primitive) -> Box<dyn StreamingPrf> {
+        match p {
+            Primitive::StreamingPrf(p) => p,
+            _ => panic!("attempt to convert wrong primitive type"), // safe: precondition
+        }
+    }
+}
+
 impl From<Primitive> for Box<dyn Verifier> {
     fn from(p: Primitive) -> Box<dyn Verifier> {
         match p {
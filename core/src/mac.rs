@@ -33,6 +33,18 @@ pub trait Mac: MacBoxClone {
             Err("Invalid MAC".into())
         }
     }
+
+    /// Returns the identifier of the key that validated `mac` if it is a correct authentication
+    /// code for `data`, otherwise it returns an error.  Only primitives backed by a keyset (such as
+    /// those created by `tink_mac::new()`) know their key identifiers; the default implementation
+    /// returns an error.
+    fn verify_mac_with_key_id(
+        &self,
+        _mac: &[u8],
+        _data: &[u8],
+    ) -> Result<crate::KeyId, crate::TinkError> {
+        Err("verify_mac_with_key_id not supported".into())
+    }
 }
 
 /// Trait bound to indicate that primitive trait objects should support cloning
@@ -0,0 +1,191 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Ordered composition of primitives into a single [`Aead`] with a versioned on-wire layout.
+//!
+//! A [`Pipeline`] applies a sequence of [`Stage`]s in order on encryption and in reverse order on
+//! decryption.  The stages are normally created from keyset handles using the relevant factories
+//! (e.g. `tink_aead::new(&handle)` and `tink_mac::new(&handle)`), so key rotation is handled by
+//! each stage.
+//!
+//! The layout produced by version 1 of the pipeline is a single version byte followed by the
+//! output of the last stage, where each stage transforms the output of the previous one:
+//! - [`Stage::Transform`]: `forward(input)`.
+//! - [`Stage::Aead`]: `encrypt(input, stage_ad)`.
+//! - [`Stage::Mac`]: `tag_len (4 bytes, big-endian) || tag || input` where the tag is computed
+//!   over `stage_ad || input`.
+//!
+//! Here `stage_ad` is `version || stage_index || aad_len (8 bytes, big-endian) || aad`, which binds
+//! the additional data to every authenticating stage along with the stage's position.
+
+use crate::{utils::wrap_err, Aead, Mac, TinkError};
+use std::convert::TryFrom;
+
+/// Version of the on-wire layout produced by [`Pipeline`].
+pub const PIPELINE_VERSION: u8 = 1;
+/// Maximum number of stages in a [`Pipeline`].
+pub const MAX_PIPELINE_STAGES: usize = 255;
+const TAG_LEN_SIZE: usize = 4;
+
+/// `Transform` is an unkeyed, reversible transformation of data, such as compression, for use as
+/// a [`Stage`] of a [`Pipeline`].
+pub trait Transform: TransformBoxClone {
+    /// Transform data on the encryption path.
+    fn forward(&self, data: &[u8]) -> Result<Vec<u8>, TinkError>;
+    /// Undo [`forward`](Transform::forward) on the decryption path.
+    fn reverse(&self, data: &[u8]) -> Result<Vec<u8>, TinkError>;
+}
+
+/// Trait bound to indicate that transform trait objects should support cloning
+/// themselves as trait objects.
+pub trait TransformBoxClone {
+    fn box_clone(&self) -> Box<dyn Transform>;
+}
+
+/// Default implementation of the box-clone trait bound for any underlying
+/// concrete type that implements [`Clone`].
+impl<T> TransformBoxClone for T
+where
+    T: 'static + Transform + Clone,
+{
+    fn box_clone(&self) -> Box<dyn Transform> {
+        Box::new(self.clone())
+    }
+}
+
+/// A single step of a [`Pipeline`].
+pub enum Stage {
+    Transform(Box<dyn Transform>),
+    Aead(Box<dyn Aead>),
+    Mac(Box<dyn Mac>),
+}
+
+impl Clone for Stage {
+    fn clone(&self) -> Self {
+        match self {
+            Stage::Transform(t) => Stage::Transform(t.box_clone()),
+            Stage::Aead(a) => Stage::Aead(a.box_clone()),
+            Stage::Mac(m) => Stage::Mac(m.box_clone()),
+        }
+    }
+}
+
+/// `Pipeline` composes an ordered sequence of [`Stage`]s into a single [`Aead`].
+#[derive(Clone)]
+pub struct Pipeline {
+    stages: Vec<Stage>,
+}
+
+impl Pipeline {
+    /// Create a pipeline that applies `stages` in order on encryption.  At least one of the
+    /// stages must be a [`Stage::Aead`], so that the plaintext is encrypted, and the last stage
+    /// must be an authenticating stage ([`Stage::Aead`] or [`Stage::Mac`]), so that every byte of
+    /// the output is authenticated.
+    pub fn new(stages: Vec<Stage>) -> Result<Pipeline, TinkError> {
+        if stages.len() > MAX_PIPELINE_STAGES {
+            return Err(format!("Pipeline: at most {} stages allowed", MAX_PIPELINE_STAGES).into());
+        }
+        if !stages.iter().any(|s| matches!(s, Stage::Aead(_))) {
+            return Err("Pipeline: no AEAD stage".into());
+        }
+        if !matches!(stages.last(), Some(Stage::Aead(_)) | Some(Stage::Mac(_))) {
+            return Err("Pipeline: last stage is not an authenticating stage".into());
+        }
+        Ok(Pipeline { stages })
+    }
+
+    /// Create a pipeline that encrypts with `aead` and then authenticates the ciphertext with
+    /// `mac`.
+    pub fn encrypt_then_mac(aead: Box<dyn Aead>, mac: Box<dyn Mac>) -> Pipeline {
+        Pipeline {
+            stages: vec![Stage::Aead(aead), Stage::Mac(mac)],
+        }
+    }
+}
+
+/// Build the additional data for the stage at `index`.
+fn stage_ad(index: usize, additional_data: &[u8]) -> Vec<u8> {
+    let mut ad = Vec::with_capacity(2 + 8 + additional_data.len());
+    ad.push(PIPELINE_VERSION);
+    ad.push(index as u8);
+    ad.extend_from_slice(&(additional_data.len() as u64).to_be_bytes());
+    ad.extend_from_slice(additional_data);
+    ad
+}
+
+impl Aead for Pipeline {
+    fn encrypt(&self, plaintext: &[u8], additional_data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let mut data = plaintext.to_vec();
+        for (i, stage) in self.stages.iter().enumerate() {
+            data = match stage {
+                Stage::Transform(t) => t
+                    .forward(&data)
+                    .map_err(|e| wrap_err("Pipeline: transform failed", e))?,
+                Stage::Aead(a) => a.encrypt(&data, &stage_ad(i, additional_data))?,
+                Stage::Mac(m) => {
+                    let mut mac_input = stage_ad(i, additional_data);
+                    mac_input.extend_from_slice(&data);
+                    let tag = m.compute_mac(&mac_input)?;
+                    let tag_len = u32::try_from(tag.len()).map_err(|_| "Pipeline: tag too long")?;
+                    let mut out = Vec::with_capacity(TAG_LEN_SIZE + tag.len() + data.len());
+                    out.extend_from_slice(&tag_len.to_be_bytes());
+                    out.extend_from_slice(&tag);
+                    out.extend_from_slice(&data);
+                    out
+                }
+            };
+        }
+        let mut out = Vec::with_capacity(1 + data.len());
+        out.push(PIPELINE_VERSION);
+        out.extend_from_slice(&data);
+        Ok(out)
+    }
+
+    fn decrypt(&self, ciphertext: &[u8], additional_data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        match ciphertext.first() {
+            None => return Err("Pipeline: ciphertext too short".into()),
+            Some(&PIPELINE_VERSION) => {}
+            Some(v) => return Err(format!("Pipeline: unsupported version {}", v).into()),
+        }
+        let mut data = ciphertext[1..].to_vec();
+        for (i, stage) in self.stages.iter().enumerate().rev() {
+            data = match stage {
+                Stage::Transform(t) => t
+                    .reverse(&data)
+                    .map_err(|e| wrap_err("Pipeline: transform failed", e))?,
+                Stage::Aead(a) => a.decrypt(&data, &stage_ad(i, additional_data))?,
+                Stage::Mac(m) => {
+                    if data.len() < TAG_LEN_SIZE {
+                        return Err("Pipeline: ciphertext too short".into());
+                    }
+                    let mut tag_len = [0u8; TAG_LEN_SIZE];
+                    tag_len.copy_from_slice(&data[..TAG_LEN_SIZE]);
+                    let tag_len = u32::from_be_bytes(tag_len) as usize;
+                    if data.len() - TAG_LEN_SIZE < tag_len {
+                        return Err("Pipeline: ciphertext too short".into());
+                    }
+                    let (tag, rest) = data[TAG_LEN_SIZE..].split_at(tag_len);
+                    let mut mac_input = stage_ad(i, additional_data);
+                    mac_input.extend_from_slice(rest);
+                    m.verify_mac(tag, &mac_input)
+                        .map_err(|e| wrap_err("Pipeline: invalid MAC", e))?;
+                    rest.to_vec()
+                }
+            };
+        }
+        Ok(data)
+    }
+}
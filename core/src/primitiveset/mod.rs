@@ -61,6 +61,11 @@ impl Entry {
 /// the set is used, and upon decryption the ciphertext's prefix determines the
 /// id of the primitive from the set.
 ///
+/// Entries are indexed by their output prefix, so finding the candidate primitives for a
+/// ciphertext takes constant time regardless of the size of the keyset; only RAW entries (which
+/// share the empty prefix) have to be tried in turn.  Keys whose prefixes collide are kept
+/// together under the same prefix.
+///
 /// `PrimitiveSet` is public to allow its use in implementations of custom
 /// primitives.
 #[derive(Clone, Default)]
@@ -72,6 +77,17 @@ pub struct PrimitiveSet {
     // primitives sharing the prefix). This allows quickly retrieving the
     // primitives sharing some particular prefix.
     pub entries: HashMap<Vec<u8>, Vec<Entry>>,
+
+    // Validity period of the primary entry, if any.  This comes from the (in-memory)
+    // annotations of the keyset handle that the set was created from.
+    pub(crate) primary_validity: Option<crate::keyset::ValidityCheck>,
+
+    // Usage counter (and limit) of the primary entry, if any.  This comes from the (in-memory)
+    // annotations of the keyset handle that the set was created from.
+    pub(crate) primary_usage: Option<crate::keyset::UsageCheck>,
+
+    // Operations permitted for the primitives.
+    pub usage: crate::keyset::KeyUsage,
 }
 
 impl PrimitiveSet {
@@ -80,6 +96,9 @@ impl PrimitiveSet {
         PrimitiveSet {
             primary: None,
             entries: HashMap::new(),
+            primary_validity: None,
+            primary_usage: None,
+            usage: crate::keyset::KeyUsage::Unrestricted,
         }
     }
 
@@ -88,6 +107,16 @@ impl PrimitiveSet {
         self.entries_for_prefix(&crate::cryptofmt::RAW_PREFIX)
     }
 
+    /// Return the validity period check for the primary entry, if any.
+    pub fn primary_validity(&self) -> Option<&crate::keyset::ValidityCheck> {
+        self.primary_validity.as_ref()
+    }
+
+    /// Return the usage counter check for the primary entry, if any.
+    pub fn primary_usage(&self) -> Option<&crate::keyset::UsageCheck> {
+        self.primary_usage.as_ref()
+    }
+
     /// Return all primitives in the set that have the given prefix.
     pub fn entries_for_prefix(&self, prefix: &[u8]) -> Vec<Entry> {
         match self.entries.get(prefix) {
@@ -111,10 +140,10 @@ impl PrimitiveSet {
             key.key_id,
             p,
             &prefix,
-            tink_proto::OutputPrefixType::from_i32(key.output_prefix_type)
-                .ok_or_else(|| TinkError::new("invalid key prefix type"))?,
-            tink_proto::KeyStatusType::from_i32(key.status)
-                .ok_or_else(|| TinkError::new("invalid key status"))?,
+            crate::cryptofmt::effective_output_prefix_type(key.output_prefix_type)
+                .map_err(|e| wrap_err("primitiveset", e))?,
+            key.try_status()
+                .map_err(|e| wrap_err("invalid key status", e))?,
         );
         let retval = entry.clone();
         match self.entries.entry(prefix) {
@@ -159,6 +188,17 @@ pub struct TypedPrimitiveSet<P: From<crate::Primitive>> {
     // primitives sharing the prefix). This allows quickly retrieving the
     // primitives sharing some particular prefix.
     pub entries: HashMap<Vec<u8>, Vec<TypedEntry<P>>>,
+
+    // Validity period of the primary entry, if any.  This comes from the (in-memory)
+    // annotations of the keyset handle that the set was created from.
+    pub(crate) primary_validity: Option<crate::keyset::ValidityCheck>,
+
+    // Usage counter (and limit) of the primary entry, if any.  This comes from the (in-memory)
+    // annotations of the keyset handle that the set was created from.
+    pub(crate) primary_usage: Option<crate::keyset::UsageCheck>,
+
+    // Operations permitted for the primitives.
+    pub usage: crate::keyset::KeyUsage,
 }
 
 impl<P: From<crate::Primitive>> TypedPrimitiveSet<P> {
@@ -171,6 +211,43 @@ impl<P: From<crate::Primitive>> TypedPrimitiveSet<P> {
     pub fn entries_for_prefix(&self, prefix: &[u8]) -> Option<&Vec<TypedEntry<P>>> {
         self.entries.get(prefix)
     }
+
+    /// Return the primitive in the set for the key with the given ID, if any.
+    pub fn entry_for_key_id(&self, key_id: crate::KeyId) -> Option<&TypedEntry<P>> {
+        self.entries
+            .values()
+            .flat_map(|entries| entries.iter())
+            .find(|entry| entry.key_id == key_id)
+    }
+
+    /// Return the validity period check for the primary entry, if any.
+    pub fn primary_validity(&self) -> Option<&crate::keyset::ValidityCheck> {
+        self.primary_validity.as_ref()
+    }
+
+    /// Return the usage counter check for the primary entry, if any.
+    pub fn primary_usage(&self) -> Option<&crate::keyset::UsageCheck> {
+        self.primary_usage.as_ref()
+    }
+
+    /// Return an error if the primary entry has a validity period that does not include the
+    /// current time.
+    pub fn check_primary_validity(&self) -> Result<(), TinkError> {
+        match &self.primary_validity {
+            Some(v) => v.check(),
+            None => Ok(()),
+        }
+    }
+
+    /// Record an operation on `bytes` bytes of input with the primary entry, or return an error
+    /// if the primary entry has reached its usage limit.  Does nothing if usage counting is not
+    /// enabled.
+    pub fn record_primary_usage(&self, bytes: usize) -> Result<(), TinkError> {
+        match &self.primary_usage {
+            Some(u) => u.record(bytes),
+            None => Ok(()),
+        }
+    }
 }
 
 /// A `TypedPrimitiveSet` is [`Clone`]able if its constituent [`TypedEntry`] objects
@@ -184,6 +261,9 @@ where
         Self {
             primary: self.primary.as_ref().cloned(),
             entries: self.entries.clone(),
+            primary_validity: self.primary_validity.clone(),
+            primary_usage: self.primary_usage.clone(),
+            usage: self.usage,
         }
     }
 }
@@ -199,6 +279,9 @@ impl<P: From<crate::Primitive>> From<PrimitiveSet> for TypedPrimitiveSet<P> {
                 .into_iter()
                 .map(|(k, v)| (k, v.into_iter().map(TypedEntry::<P>::from).collect()))
                 .collect(),
+            primary_validity: ps.primary_validity,
+            primary_usage: ps.primary_usage,
+            usage: ps.usage,
         }
     }
 }
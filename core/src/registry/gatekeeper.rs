@@ -0,0 +1,50 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Trait definition for gatekeepers consulted before cleartext key material is loaded.
+
+/// Describes how cleartext key material is being loaded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CleartextSource {
+    /// An unencrypted keyset, read via `keyset::insecure::read` or created via
+    /// `keyset::insecure::new_handle`.
+    Insecure,
+    /// A password-encrypted keyset, read via `keyset::Handle::read_with_password`.
+    Password,
+    /// Private key material imported from another format, such as a PKCS#8 private key.
+    Import,
+    /// A single key used directly to create a primitive, bypassing keyset wrapping, via
+    /// [`unwrapped_primitive_from_key_data`](crate::registry::unwrapped_primitive_from_key_data).
+    SingleKey,
+}
+
+/// Information about a pending load of cleartext key material, passed to each [`Gatekeeper`].
+/// It does not include any secret key material.
+#[derive(Clone, Debug)]
+pub struct CleartextLoad {
+    /// How the key material is being loaded.
+    pub source: CleartextSource,
+    /// Information about the keyset being loaded.
+    pub keyset_info: tink_proto::KeysetInfo,
+}
+
+/// `Gatekeeper` is consulted before a keyset holding cleartext key material is returned to the
+/// caller, allowing deployments to enforce environmental checks (such as verifying TEE
+/// attestation, or that the host is on an allowlist) within the library.
+pub trait Gatekeeper: Send + Sync {
+    /// Return an error if the given load should not go ahead.
+    fn check(&self, load: &CleartextLoad) -> Result<(), crate::TinkError>;
+}
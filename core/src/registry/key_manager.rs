@@ -55,6 +55,40 @@ pub trait KeyManager: Send + Sync {
         })
     }
 
+    /// Indicate whether this `KeyManager` supports deriving keys from pseudorandomness via
+    /// [`derive_key`](KeyManager::derive_key).
+    fn supports_derivation(&self) -> bool {
+        false
+    }
+
+    /// Derive a new key according to specification in `serialized_key_format`, taking the key
+    /// material from `pseudorandomness` rather than generating it randomly.  The result is returned
+    /// as a serialized protocol buffer.  Key managers for key types that cannot be derived in this
+    /// way return an error (and also return false from
+    /// [`supports_derivation`](KeyManager::supports_derivation)).
+    fn derive_key(
+        &self,
+        _serialized_key_format: &[u8],
+        _pseudorandomness: &mut dyn std::io::Read,
+    ) -> Result<Vec<u8>, TinkError> {
+        Err(format!("key derivation not supported for {}", self.type_url()).into())
+    }
+
+    /// Derive a new [`KeyData`](tink_proto::KeyData) according to specification in
+    /// `serialized_key_format`, taking the key material from `pseudorandomness`.
+    fn derive_key_data(
+        &self,
+        serialized_key_format: &[u8],
+        pseudorandomness: &mut dyn std::io::Read,
+    ) -> Result<tink_proto::KeyData, TinkError> {
+        let serialized_key = self.derive_key(serialized_key_format, pseudorandomness)?;
+        Ok(tink_proto::KeyData {
+            type_url: self.type_url().to_string(),
+            value: serialized_key,
+            key_material_type: self.key_material_type() as i32,
+        })
+    }
+
     /// Indicate whether this `KeyManager` understands private key types.
     fn supports_private_keys(&self) -> bool {
         false
@@ -49,3 +49,15 @@ pub fn template_names() -> Vec<String> {
         .cloned()
         .collect()
 }
+
+/// Return a copy of the registered key template generators.
+#[cfg(feature = "test-registry")]
+pub(crate) fn template_generators() -> HashMap<String, KeyTemplateGenerator> {
+    TEMPLATE_GENERATORS.read().unwrap().clone() // safe: lock
+}
+
+/// Replace the registered key template generators.
+#[cfg(feature = "test-registry")]
+pub(crate) fn set_template_generators(generators: HashMap<String, KeyTemplateGenerator>) {
+    *TEMPLATE_GENERATORS.write().unwrap() = generators; // safe: lock
+}
@@ -16,11 +16,175 @@
 
 //! Trait definition for KMS clients.
 
+/// `KmsCapabilities` describes the optional operations that a [`KmsClient`] supports for a
+/// particular key URI, beyond providing an [`Aead`](crate::Aead) backend.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct KmsCapabilities {
+    /// The [`Aead`](crate::Aead) backends authenticate their additional data in the KMS; see
+    /// [`KmsClient::supports_associated_data`].
+    pub associated_data: bool,
+    /// [`KmsClient::generate_data_key`] is supported.
+    pub generate_data_key: bool,
+    /// [`KmsClient::get_signer`] and [`KmsClient::get_public_key_data`] are supported, as the key
+    /// is an asymmetric signing key.
+    pub sign: bool,
+}
+
+/// `DataKey` holds a data encryption key generated by a KMS, both in plaintext and encrypted
+/// under the KMS key.
+#[derive(Clone)]
+pub struct DataKey {
+    /// The key, for local use.
+    pub plaintext: Vec<u8>,
+    /// The key encrypted under the KMS key, for storage alongside the data it protects.  It can
+    /// be decrypted with the client's [`Aead`](crate::Aead) backend for the same key URI, using
+    /// empty additional data.
+    pub ciphertext: Vec<u8>,
+}
+
+impl std::fmt::Debug for DataKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DataKey")
+            .field("ciphertext", &self.ciphertext)
+            .finish_non_exhaustive()
+    }
+}
+
 /// `KmsClient` knows how to produce primitives backed by keys stored in remote KMS services.
+///
+/// Beyond [`get_aead`](KmsClient::get_aead), clients may support further operations where their
+/// KMS does; [`capabilities`](KmsClient::capabilities) reports which are available for a key.
 pub trait KmsClient: Send + Sync {
     /// Returns true if this client does support `key_uri`.
     fn supported(&self, key_uri: &str) -> bool;
 
     /// Get an [`Aead`](crate::Aead) backend by `key_uri`.
     fn get_aead(&self, key_uri: &str) -> Result<Box<dyn crate::Aead>, crate::TinkError>;
+
+    /// Returns true if the [`Aead`](crate::Aead) backends for `key_uri` pass their additional
+    /// data to the KMS, so that the KMS authenticates it (for example as AWS KMS encryption
+    /// context or GCP KMS additional authenticated data) and can apply policy based on it.
+    ///
+    /// The default implementation returns false.
+    fn supports_associated_data(&self, _key_uri: &str) -> bool {
+        false
+    }
+
+    /// Returns the optional operations supported for `key_uri`.
+    ///
+    /// The default implementation only reports [`supports_associated_data`]
+    /// (KmsClient::supports_associated_data).
+    fn capabilities(&self, key_uri: &str) -> KmsCapabilities {
+        KmsCapabilities {
+            associated_data: self.supports_associated_data(key_uri),
+            ..Default::default()
+        }
+    }
+
+    /// Generate a new random data encryption key of `key_size` bytes, returned both in plaintext
+    /// and encrypted under the KMS key at `key_uri` (for example with AWS KMS `GenerateDataKey`).
+    ///
+    /// The default implementation returns an error, for clients whose KMS has no such operation.
+    fn generate_data_key(
+        &self,
+        key_uri: &str,
+        _key_size: usize,
+    ) -> Result<DataKey, crate::TinkError> {
+        Err(format!(
+            "KMS client for {} does not support data key generation",
+            key_uri
+        )
+        .into())
+    }
+
+    /// Get a [`Signer`](crate::Signer) that signs with the asymmetric KMS key at `key_uri`.  The
+    /// signatures have no output prefix, and can be verified with the key from
+    /// [`get_public_key_data`](KmsClient::get_public_key_data).
+    ///
+    /// The default implementation returns an error, for clients whose KMS has no signing keys.
+    fn get_signer(&self, key_uri: &str) -> Result<Box<dyn crate::Signer>, crate::TinkError> {
+        Err(format!("KMS client for {} does not support signing", key_uri).into())
+    }
+
+    /// Get the public key corresponding to the asymmetric KMS key at `key_uri`, as Tink
+    /// [`KeyData`](tink_proto::KeyData) for a verification key type.
+    ///
+    /// The default implementation returns an error, for clients whose KMS has no signing keys.
+    fn get_public_key_data(&self, key_uri: &str) -> Result<tink_proto::KeyData, crate::TinkError> {
+        Err(format!("KMS client for {} does not support signing", key_uri).into())
+    }
+
+    /// Get an [`Aead`](crate::Aead) backed by `key_uri` whose ciphertexts are bound to
+    /// `context`, a set of key-value pairs passed to the KMS (for example, an AWS KMS encryption
+    /// context).  The returned primitive only accepts empty additional data.
+    ///
+    /// This is the structured form of the associated data reported by
+    /// [`supports_associated_data`](KmsClient::supports_associated_data): the default
+    /// implementation passes a length-prefixed encoding of `context` as the additional data of
+    /// the [`get_aead`](KmsClient::get_aead) backend, and returns an error for clients whose
+    /// backends do not authenticate additional data.  Clients whose KMS takes key-value pairs
+    /// natively should override it to pass `context` unchanged.
+    fn get_aead_with_context(
+        &self,
+        key_uri: &str,
+        context: &std::collections::BTreeMap<String, String>,
+    ) -> Result<Box<dyn crate::Aead>, crate::TinkError> {
+        if !self.supports_associated_data(key_uri) {
+            return Err(format!(
+                "KMS client for {} does not support encryption context",
+                key_uri
+            )
+            .into());
+        }
+        let mut context_aad = Vec::new();
+        for (k, v) in context {
+            for s in &[k, v] {
+                context_aad.extend_from_slice(&(s.len() as u32).to_be_bytes());
+                context_aad.extend_from_slice(s.as_bytes());
+            }
+        }
+        Ok(Box::new(ContextAead {
+            aead: self.get_aead(key_uri)?,
+            context_aad,
+        }))
+    }
+}
+
+/// AEAD that binds ciphertexts to a fixed encryption context, passed as additional data.
+struct ContextAead {
+    aead: Box<dyn crate::Aead>,
+    context_aad: Vec<u8>,
+}
+
+impl Clone for ContextAead {
+    fn clone(&self) -> Self {
+        ContextAead {
+            aead: self.aead.box_clone(),
+            context_aad: self.context_aad.clone(),
+        }
+    }
+}
+
+impl crate::Aead for ContextAead {
+    fn encrypt(
+        &self,
+        plaintext: &[u8],
+        additional_data: &[u8],
+    ) -> Result<Vec<u8>, crate::TinkError> {
+        if !additional_data.is_empty() {
+            return Err("additional data not supported with encryption context".into());
+        }
+        self.aead.encrypt(plaintext, &self.context_aad)
+    }
+
+    fn decrypt(
+        &self,
+        ciphertext: &[u8],
+        additional_data: &[u8],
+    ) -> Result<Vec<u8>, crate::TinkError> {
+        if !additional_data.is_empty() {
+            return Err("additional data not supported with encryption context".into());
+        }
+        self.aead.decrypt(ciphertext, &self.context_aad)
+    }
 }
@@ -27,54 +27,87 @@
 //! public though, to enable configurations with custom primitives and [`KeyManager`]s.
 
 use crate::TinkError;
+use arc_swap::ArcSwap;
 use lazy_static::lazy_static;
 use std::{
     collections::HashMap,
-    sync::{Arc, RwLock},
+    sync::{Arc, Mutex, RwLock},
 };
 
+mod gatekeeper;
+pub use gatekeeper::*;
 mod kms_client;
 pub use kms_client::*;
 mod key_manager;
 pub use key_manager::*;
 mod key_templates;
 pub use key_templates::*;
+mod output_prefix;
+pub use output_prefix::*;
+mod policy;
+pub use policy::*;
+#[cfg(feature = "test-registry")]
+mod snapshot;
+#[cfg(feature = "test-registry")]
+pub use snapshot::*;
+
+/// Map of key manager objects, indexed by type URL.
+pub(crate) type KeyManagerMap = HashMap<&'static str, Arc<dyn KeyManager>>;
 
 lazy_static! {
-    /// Global registry of key manager objects, indexed by type URL.
-    static ref KEY_MANAGERS: RwLock<HashMap<&'static str, Arc<dyn KeyManager>>> =
-        RwLock::new(HashMap::new());
+    /// Global registry of key manager objects, indexed by type URL.  The map itself is an
+    /// immutable snapshot, so lookups never block; registration replaces the snapshot with an
+    /// updated copy.
+    static ref KEY_MANAGERS: ArcSwap<KeyManagerMap> = ArcSwap::from_pointee(HashMap::new());
+    /// Lock serializing updates to `KEY_MANAGERS`.
+    static ref KEY_MANAGERS_UPDATE: Mutex<()> = Mutex::new(());
     /// Global list of KMS client objects.
     static ref KMS_CLIENTS: RwLock<Vec<Arc<dyn KmsClient>>> = RwLock::new(Vec::new());
+    /// Global list of gatekeepers for cleartext key material.
+    static ref GATEKEEPERS: RwLock<Vec<Arc<dyn Gatekeeper>>> = RwLock::new(Vec::new());
+    /// Global map of custom output prefix formats, indexed by output prefix type value.
+    static ref OUTPUT_PREFIXES: RwLock<HashMap<i32, CustomOutputPrefix>> =
+        RwLock::new(HashMap::new());
+    /// Global policy restricting the key templates used to generate new keys.
+    static ref TEMPLATE_POLICY: RwLock<Option<Arc<KeyTemplatePolicy>>> = RwLock::new(None);
 }
 
-/// Error message for global key manager registry lock.
-const MERR: &str = "global KEY_MANAGERS lock poisoned";
+/// Error message for global key manager registry update lock.
+const MERR: &str = "global KEY_MANAGERS_UPDATE lock poisoned";
 /// Error message for global KMS client list lock.
 const CERR: &str = "global KMS_CLIENTS lock poisoned";
+/// Error message for global gatekeeper list lock.
+const GERR: &str = "global GATEKEEPERS lock poisoned";
+/// Error message for global custom output prefix map lock.
+const PERR: &str = "global OUTPUT_PREFIXES lock poisoned";
+/// Error message for global key template policy lock.
+const TERR: &str = "global TEMPLATE_POLICY lock poisoned";
 
 /// Register the given key manager. Does not allow overwrite of existing key managers.
 pub fn register_key_manager<T>(km: Arc<T>) -> Result<(), TinkError>
 where
     T: 'static + KeyManager,
 {
-    let mut key_mgrs = KEY_MANAGERS.write().expect(MERR); // safe: lock
+    let _guard = KEY_MANAGERS_UPDATE.lock().expect(MERR); // safe: lock
 
     let type_url = km.type_url();
-    if key_mgrs.contains_key(type_url) {
+    let current = KEY_MANAGERS.load_full();
+    if current.contains_key(type_url) {
         return Err(format!(
             "registry::register_key_manager: type {} already registered",
             type_url
         )
         .into());
     }
+    let mut key_mgrs: KeyManagerMap = (*current).clone();
     key_mgrs.insert(type_url, km);
+    KEY_MANAGERS.store(Arc::new(key_mgrs));
     Ok(())
 }
 
 /// Return the key manager for the given `type_url` if it exists.
 pub fn get_key_manager(type_url: &str) -> Result<Arc<dyn KeyManager>, TinkError> {
-    let key_mgrs = KEY_MANAGERS.read().expect(MERR); // safe: lock
+    let key_mgrs = KEY_MANAGERS.load();
     let km = key_mgrs.get(type_url).ok_or_else(|| {
         TinkError::new(&format!(
             "registry::get_key_manager: unsupported key type: {}",
@@ -84,13 +117,42 @@ pub fn get_key_manager(type_url: &str) -> Result<Arc<dyn KeyManager>, TinkError>
     Ok(km.clone())
 }
 
+/// Return the current map of registered key managers.  A new map is installed whenever the set of
+/// key managers changes, so the identity of the returned [`Arc`] shows whether it has changed.
+pub(crate) fn key_managers() -> Arc<KeyManagerMap> {
+    KEY_MANAGERS.load_full()
+}
+
+/// Return the type URLs of all registered key managers, in sorted order.
+pub fn key_manager_type_urls() -> Vec<&'static str> {
+    let mut type_urls: Vec<&'static str> = KEY_MANAGERS.load().keys().copied().collect();
+    type_urls.sort_unstable();
+    type_urls
+}
+
 /// Generate a new [`KeyData`](tink_proto::KeyData) for the given key template.
 pub fn new_key_data(kt: &tink_proto::KeyTemplate) -> Result<tink_proto::KeyData, TinkError> {
+    check_key_template(kt)?;
     get_key_manager(&kt.type_url)?.new_key_data(&kt.value)
 }
 
+/// Derive a new [`KeyData`](tink_proto::KeyData) for the given key template, taking the key
+/// material from `pseudorandomness`.
+pub fn derive_key_data(
+    kt: &tink_proto::KeyTemplate,
+    pseudorandomness: &mut dyn std::io::Read,
+) -> Result<tink_proto::KeyData, TinkError> {
+    check_key_template(kt)?;
+    let km = get_key_manager(&kt.type_url)?;
+    if !km.supports_derivation() {
+        return Err(format!("key derivation not supported for {}", kt.type_url).into());
+    }
+    km.derive_key_data(&kt.value, pseudorandomness)
+}
+
 /// Generate a new key for the given key template as a serialized protobuf message.
 pub fn new_key(kt: &tink_proto::KeyTemplate) -> Result<Vec<u8>, TinkError> {
+    check_key_template(kt)?;
     get_key_manager(&kt.type_url)?.new_key(&kt.value)
 }
 
@@ -99,6 +161,33 @@ pub fn primitive_from_key_data(kd: &tink_proto::KeyData) -> Result<crate::Primit
     primitive(&kd.type_url, &kd.value)
 }
 
+/// Create a new primitive directly from a single key, without a keyset.
+///
+/// The returned primitive is not wrapped: it has no output prefix and none of the keyset-level
+/// features (key rotation, validity periods, usage limits, usage restrictions) apply.  This is
+/// intended for constrained environments that store exactly one key; everything else should use
+/// a [`Handle`](crate::keyset::Handle).
+///
+/// Any registered [`Gatekeeper`]s are consulted (with [`CleartextSource::SingleKey`]) before the
+/// primitive is created.
+pub fn unwrapped_primitive_from_key_data(
+    kd: &tink_proto::KeyData,
+) -> Result<crate::Primitive, TinkError> {
+    check_cleartext_load(&CleartextLoad {
+        source: CleartextSource::SingleKey,
+        keyset_info: tink_proto::KeysetInfo {
+            primary_key_id: 0,
+            key_info: vec![tink_proto::keyset_info::KeyInfo {
+                type_url: kd.type_url.clone(),
+                status: tink_proto::KeyStatusType::Enabled as i32,
+                key_id: 0,
+                output_prefix_type: tink_proto::OutputPrefixType::Raw as i32,
+            }],
+        },
+    })?;
+    primitive_from_key_data(kd)
+}
+
 /// Create a new primitive for the given serialized key using the [`KeyManager`]
 /// identified by the given `type_url`.
 pub fn primitive(type_url: &str, sk: &[u8]) -> Result<crate::Primitive, TinkError> {
@@ -133,3 +222,118 @@ pub fn get_kms_client(key_uri: &str) -> Result<Arc<dyn KmsClient>, TinkError> {
     }
     Err(format!("KMS client supporting {} not found", key_uri).into())
 }
+
+/// Register a [`Gatekeeper`] to be consulted before cleartext key material is loaded.
+pub fn register_gatekeeper<T>(g: T)
+where
+    T: 'static + Gatekeeper,
+{
+    let mut gatekeepers = GATEKEEPERS.write().expect(GERR); // safe: lock
+    gatekeepers.push(Arc::new(g));
+}
+
+/// Remove all registered gatekeepers (requires activation of the `test-registry` feature).
+#[cfg(feature = "test-registry")]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-registry")))]
+pub fn clear_gatekeepers() {
+    let mut gatekeepers = GATEKEEPERS.write().expect(GERR); // safe: lock
+    gatekeepers.clear();
+}
+
+/// Consult all registered gatekeepers about a load of cleartext key material, returning an
+/// error if any of them rejects it.  Code that loads cleartext keysets from other sources should
+/// call this before returning a [`Handle`](crate::keyset::Handle).
+pub fn check_cleartext_load(load: &CleartextLoad) -> Result<(), TinkError> {
+    let gatekeepers = GATEKEEPERS.read().expect(GERR); // safe: lock
+    for g in gatekeepers.iter() {
+        g.check(load)
+            .map_err(|e| crate::utils::wrap_err("registry: cleartext load rejected", e))?;
+    }
+    Ok(())
+}
+
+/// Install a [`KeyTemplatePolicy`] that all subsequent key generation must satisfy, replacing
+/// any previously installed policy.
+pub fn set_key_template_policy(policy: KeyTemplatePolicy) {
+    *TEMPLATE_POLICY.write().expect(TERR) = Some(Arc::new(policy)); // safe: lock
+}
+
+/// Remove the installed [`KeyTemplatePolicy`], if any, so that all key templates are permitted.
+pub fn clear_key_template_policy() {
+    *TEMPLATE_POLICY.write().expect(TERR) = None; // safe: lock
+}
+
+/// Return the installed [`KeyTemplatePolicy`], if any.
+pub fn key_template_policy() -> Option<Arc<KeyTemplatePolicy>> {
+    TEMPLATE_POLICY.read().expect(TERR).clone() // safe: lock
+}
+
+/// Return an error if the installed [`KeyTemplatePolicy`] does not permit the given key template
+/// to be used for generating keys.
+pub fn check_key_template(kt: &tink_proto::KeyTemplate) -> Result<(), TinkError> {
+    match key_template_policy() {
+        Some(policy) => policy.check(kt),
+        None => Ok(()),
+    }
+}
+
+/// Register an additional output prefix format.  Fails if the format's `output_prefix_type` is a
+/// standard prefix type or already registered, or if its prefixes could be confused with those
+/// of a standard or already registered prefix type.
+pub fn register_custom_output_prefix(prefix: CustomOutputPrefix) -> Result<(), TinkError> {
+    if tink_proto::OutputPrefixType::from_i32(prefix.output_prefix_type).is_some() {
+        return Err(format!(
+            "registry::register_custom_output_prefix: {} is a standard output prefix type",
+            prefix.output_prefix_type
+        )
+        .into());
+    }
+    if prefix.start_byte == crate::cryptofmt::TINK_START_BYTE
+        || prefix.start_byte == crate::cryptofmt::LEGACY_START_BYTE
+    {
+        return Err(format!(
+            "registry::register_custom_output_prefix: start byte {:#04x} is reserved",
+            prefix.start_byte
+        )
+        .into());
+    }
+    if prefix.behaves_as != tink_proto::OutputPrefixType::Tink
+        && prefix.behaves_as != tink_proto::OutputPrefixType::Legacy
+    {
+        return Err(
+            "registry::register_custom_output_prefix: must behave as TINK or LEGACY".into(),
+        );
+    }
+    let mut prefixes = OUTPUT_PREFIXES.write().expect(PERR); // safe: lock
+    for existing in prefixes.values() {
+        if existing.output_prefix_type == prefix.output_prefix_type {
+            return Err(format!(
+                "registry::register_custom_output_prefix: type {} already registered",
+                prefix.output_prefix_type
+            )
+            .into());
+        }
+        if existing.start_byte == prefix.start_byte {
+            return Err(format!(
+                "registry::register_custom_output_prefix: start byte {:#04x} already registered",
+                prefix.start_byte
+            )
+            .into());
+        }
+    }
+    prefixes.insert(prefix.output_prefix_type, prefix);
+    Ok(())
+}
+
+/// Return the registered custom output prefix format for the given `output_prefix_type` value,
+/// if any.
+pub fn custom_output_prefix(output_prefix_type: i32) -> Option<CustomOutputPrefix> {
+    let prefixes = OUTPUT_PREFIXES.read().expect(PERR); // safe: lock
+    prefixes.get(&output_prefix_type).copied()
+}
+
+/// Remove all registered custom output prefix formats.
+pub fn clear_custom_output_prefixes() {
+    let mut prefixes = OUTPUT_PREFIXES.write().expect(PERR); // safe: lock
+    prefixes.clear();
+}
@@ -0,0 +1,65 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Definition of additional output prefix formats.
+
+use crate::KeyId;
+use tink_proto::OutputPrefixType;
+
+/// `CustomOutputPrefix` describes an additional output prefix format, for interoperability with
+/// systems that mark their ciphertexts, MACs and signatures with a bespoke prefix byte.
+///
+/// Like a TINK prefix, a custom prefix is [`NON_RAW_PREFIX_SIZE`](crate::cryptofmt::NON_RAW_PREFIX_SIZE)
+/// bytes long: a 1-byte marker followed by the 4-byte big-endian key ID.  Once registered with
+/// [`register_custom_output_prefix`], keys whose `output_prefix_type` holds the custom value are
+/// accepted in keysets, and the primitive wrappers produce and recognize the custom prefix.
+///
+/// The custom value is not a value of [`OutputPrefixType`], so it is lost when a keyset is
+/// written in JSON format; use the binary format for keysets holding such keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CustomOutputPrefix {
+    /// The `output_prefix_type` value of keys that use this prefix.  It must not be a value of
+    /// [`OutputPrefixType`].
+    pub output_prefix_type: i32,
+    /// The first byte of the prefix.  It must differ from the first byte of TINK and
+    /// LEGACY/CRUNCHY prefixes, and from that of other registered custom prefixes.
+    pub start_byte: u8,
+    /// The standard prefix type whose behaviour the primitive wrappers follow for keys with this
+    /// prefix: either [`OutputPrefixType::Tink`], or [`OutputPrefixType::Legacy`] for systems
+    /// whose MACs and signatures cover the data followed by a zero byte.
+    pub behaves_as: OutputPrefixType,
+}
+
+impl CustomOutputPrefix {
+    /// Return the prefix for the key with the given ID.
+    pub fn encode(&self, key_id: KeyId) -> Vec<u8> {
+        let mut prefix = Vec::with_capacity(crate::cryptofmt::NON_RAW_PREFIX_SIZE);
+        prefix.push(self.start_byte);
+        prefix.extend_from_slice(&key_id.to_be_bytes());
+        prefix
+    }
+
+    /// Return the key ID from the prefix at the start of `data`, or `None` if `data` does not
+    /// start with this kind of prefix.
+    pub fn parse(&self, data: &[u8]) -> Option<KeyId> {
+        if data.len() < crate::cryptofmt::NON_RAW_PREFIX_SIZE || data[0] != self.start_byte {
+            return None;
+        }
+        let mut key_id = [0u8; 4];
+        key_id.copy_from_slice(&data[1..crate::cryptofmt::NON_RAW_PREFIX_SIZE]);
+        Some(KeyId::from_be_bytes(key_id))
+    }
+}
@@ -0,0 +1,174 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Organization-level policy restricting the key templates that may be used to generate keys.
+
+use crate::TinkError;
+use std::{collections::HashSet, fmt, sync::Arc};
+use tink_proto::KeyTemplate;
+
+/// Predicate identifying key templates that a [`KeyTemplatePolicy`] forbids.
+type DenyPredicate = Arc<dyn Fn(&KeyTemplate) -> bool + Send + Sync>;
+
+/// A rule forbidding some key templates, together with the reason reported when it applies.
+#[derive(Clone)]
+struct Denial {
+    reason: String,
+    matches: DenyPredicate,
+}
+
+/// `KeyTemplatePolicy` restricts which key templates may be used to generate new keys.  Once
+/// installed with [`set_key_template_policy`](crate::registry::set_key_template_policy), it is
+/// consulted by [`new_key_data`](crate::registry::new_key_data),
+/// [`new_key`](crate::registry::new_key) and [`derive_key_data`](crate::registry::derive_key_data)
+/// (and so by [`keyset::Manager`](crate::keyset::Manager) and
+/// [`keyset::Handle::new`](crate::keyset::Handle::new)).  Existing keys are unaffected.
+///
+/// A template is permitted if:
+///  - no allowlist entries have been added, or it matches an allowed type URL or an allowed
+///    template (comparing type URL and serialized key format, but not output prefix type), and
+///  - none of the deny rules matches it.
+///
+/// ```
+/// use tink_core::registry::KeyTemplatePolicy;
+/// use tink_proto::{prost::Message, AesGcmKeyFormat, HashType, HmacKeyFormat};
+///
+/// const AES_GCM_TYPE_URL: &str = "type.googleapis.com/google.crypto.tink.AesGcmKey";
+/// const HMAC_TYPE_URL: &str = "type.googleapis.com/google.crypto.tink.HmacKey";
+///
+/// let policy = KeyTemplatePolicy::new("example-org")
+///     .allow_type_url(AES_GCM_TYPE_URL)
+///     .allow_type_url(HMAC_TYPE_URL)
+///     .deny("AES-128 is forbidden", |kt| {
+///         kt.type_url == AES_GCM_TYPE_URL
+///             && AesGcmKeyFormat::decode(kt.value.as_ref()).map_or(true, |f| f.key_size < 32)
+///     })
+///     .deny("SHA-1 MACs are forbidden", |kt| {
+///         kt.type_url == HMAC_TYPE_URL
+///             && HmacKeyFormat::decode(kt.value.as_ref()).map_or(true, |f| {
+///                 f.params.map_or(true, |p| p.try_hash() == Ok(HashType::Sha1))
+///             })
+///     });
+/// ```
+#[derive(Clone)]
+pub struct KeyTemplatePolicy {
+    name: String,
+    allowed_type_urls: HashSet<String>,
+    allowed_templates: Vec<(String, KeyTemplate)>,
+    denials: Vec<Denial>,
+}
+
+impl KeyTemplatePolicy {
+    /// Create an empty policy, which permits all templates.  The `name` is included in the
+    /// errors reported for rejected templates.
+    pub fn new(name: &str) -> Self {
+        KeyTemplatePolicy {
+            name: name.to_string(),
+            allowed_type_urls: HashSet::new(),
+            allowed_templates: Vec::new(),
+            denials: Vec::new(),
+        }
+    }
+
+    /// Return the name of the policy.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Add all templates for the given key type to the allowlist.
+    pub fn allow_type_url(mut self, type_url: &str) -> Self {
+        self.allowed_type_urls.insert(type_url.to_string());
+        self
+    }
+
+    /// Add a specific template to the allowlist, identified by `label` in error messages.
+    pub fn allow_template(mut self, label: &str, template: KeyTemplate) -> Self {
+        self.allowed_templates.push((label.to_string(), template));
+        self
+    }
+
+    /// Add the template produced by the named template generator (see
+    /// [`register_template_generator`](crate::registry::register_template_generator)) to the
+    /// allowlist.  Fails if no generator with that name is registered.
+    pub fn allow_named_template(self, name: &str) -> Result<Self, TinkError> {
+        let generator = crate::registry::get_template_generator(name).ok_or_else(|| {
+            TinkError::new(&format!(
+                "KeyTemplatePolicy: unknown key template name {}",
+                name
+            ))
+        })?;
+        Ok(self.allow_template(name, generator()))
+    }
+
+    /// Add a rule forbidding the templates for which `matches` returns `true`, reporting
+    /// `reason` when it rejects a template.
+    pub fn deny<F>(mut self, reason: &str, matches: F) -> Self
+    where
+        F: Fn(&KeyTemplate) -> bool + Send + Sync + 'static,
+    {
+        self.denials.push(Denial {
+            reason: reason.to_string(),
+            matches: Arc::new(matches),
+        });
+        self
+    }
+
+    /// Return an error describing why `template` is not permitted by this policy, if it is not.
+    pub fn check(&self, template: &KeyTemplate) -> Result<(), TinkError> {
+        if !self.allowed_type_urls.is_empty() || !self.allowed_templates.is_empty() {
+            let allowed = self.allowed_type_urls.contains(&template.type_url)
+                || self.allowed_templates.iter().any(|(_, allowed)| {
+                    allowed.type_url == template.type_url && allowed.value == template.value
+                });
+            if !allowed {
+                return Err(format!(
+                    "key template policy '{}': template for {} is not in the allowlist",
+                    self.name, template.type_url
+                )
+                .into());
+            }
+        }
+        if let Some(denial) = self.denials.iter().find(|d| (d.matches)(template)) {
+            return Err(format!(
+                "key template policy '{}': template for {} rejected: {}",
+                self.name, template.type_url, denial.reason
+            )
+            .into());
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for KeyTemplatePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyTemplatePolicy")
+            .field("name", &self.name)
+            .field("allowed_type_urls", &self.allowed_type_urls)
+            .field(
+                "allowed_templates",
+                &self
+                    .allowed_templates
+                    .iter()
+                    .map(|(label, _)| label)
+                    .collect::<Vec<_>>(),
+            )
+            .field(
+                "denials",
+                &self.denials.iter().map(|d| &d.reason).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
@@ -0,0 +1,109 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Snapshot and restore of the global registry state, for hermetic tests (requires activation of
+//! the `test-registry` feature).
+
+use super::{
+    key_templates, CustomOutputPrefix, Gatekeeper, KeyManagerMap, KeyTemplatePolicy, KmsClient,
+    CERR, GATEKEEPERS, GERR, KEY_MANAGERS, KEY_MANAGERS_UPDATE, KMS_CLIENTS, MERR, OUTPUT_PREFIXES,
+    PERR, TEMPLATE_POLICY, TERR,
+};
+use lazy_static::lazy_static;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+lazy_static! {
+    /// Lock held by each [`RegistryGuard`], so that tests isolating the registry run one at a time.
+    static ref ISOLATION: Mutex<()> = Mutex::new(());
+}
+
+/// A copy of the global registry state: key managers, KMS clients, gatekeepers, key template
+/// generators, custom output prefixes and the key template policy.
+#[cfg_attr(docsrs, doc(cfg(feature = "test-registry")))]
+#[derive(Clone)]
+pub struct RegistrySnapshot {
+    key_managers: Arc<KeyManagerMap>,
+    kms_clients: Vec<Arc<dyn KmsClient>>,
+    gatekeepers: Vec<Arc<dyn Gatekeeper>>,
+    template_generators: std::collections::HashMap<String, key_templates::KeyTemplateGenerator>,
+    output_prefixes: std::collections::HashMap<i32, CustomOutputPrefix>,
+    template_policy: Option<Arc<KeyTemplatePolicy>>,
+}
+
+/// Take a snapshot of the global registry state.
+///
+/// The `init()` functions of the primitive crates only register their key managers once per
+/// process, so snapshots should be taken after calling them; restoring a snapshot from before
+/// an `init()` call leaves that crate's key managers unregistered.
+#[cfg_attr(docsrs, doc(cfg(feature = "test-registry")))]
+pub fn snapshot() -> RegistrySnapshot {
+    RegistrySnapshot {
+        key_managers: KEY_MANAGERS.load_full(),
+        kms_clients: KMS_CLIENTS.read().expect(CERR).clone(), // safe: lock
+        gatekeepers: GATEKEEPERS.read().expect(GERR).clone(), // safe: lock
+        template_generators: key_templates::template_generators(),
+        output_prefixes: OUTPUT_PREFIXES.read().expect(PERR).clone(), // safe: lock
+        template_policy: TEMPLATE_POLICY.read().expect(TERR).clone(), // safe: lock
+    }
+}
+
+/// Restore the global registry state to a previously taken snapshot, discarding any key
+/// managers, KMS clients, gatekeepers, key template generators, custom output prefixes and key
+/// template policy changes made since (including any made by other threads).
+#[cfg_attr(docsrs, doc(cfg(feature = "test-registry")))]
+pub fn restore(snapshot: RegistrySnapshot) {
+    {
+        let _guard = KEY_MANAGERS_UPDATE.lock().expect(MERR); // safe: lock
+        KEY_MANAGERS.store(snapshot.key_managers);
+    }
+    *KMS_CLIENTS.write().expect(CERR) = snapshot.kms_clients; // safe: lock
+    *GATEKEEPERS.write().expect(GERR) = snapshot.gatekeepers; // safe: lock
+    key_templates::set_template_generators(snapshot.template_generators);
+    *OUTPUT_PREFIXES.write().expect(PERR) = snapshot.output_prefixes; // safe: lock
+    *TEMPLATE_POLICY.write().expect(TERR) = snapshot.template_policy; // safe: lock
+}
+
+/// Guard returned by [`isolate`], which restores the registry state when dropped.
+#[cfg_attr(docsrs, doc(cfg(feature = "test-registry")))]
+pub struct RegistryGuard {
+    snapshot: Option<RegistrySnapshot>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl Drop for RegistryGuard {
+    fn drop(&mut self) {
+        if let Some(snapshot) = self.snapshot.take() {
+            restore(snapshot);
+        }
+    }
+}
+
+/// Take a snapshot of the global registry state, returning a guard that restores it when
+/// dropped.
+///
+/// Only one guard exists at a time, so tests that modify the registry under a guard are
+/// serialized with respect to each other, while other tests continue to run in parallel.  As
+/// for [`snapshot`], call the relevant `init()` functions first.
+#[cfg_attr(docsrs, doc(cfg(feature = "test-registry")))]
+pub fn isolate() -> RegistryGuard {
+    // A test that panics while holding the guard still restores the registry on unwind, so a
+    // poisoned lock carries no inconsistent state.
+    let lock = ISOLATION.lock().unwrap_or_else(|e| e.into_inner());
+    RegistryGuard {
+        snapshot: Some(snapshot()),
+        _lock: lock,
+    }
+}
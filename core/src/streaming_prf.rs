@@ -0,0 +1,52 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Streaming pseudo-random function.
+
+/// The `StreamingPrf` trait is an abstraction for an element of a pseudo random function family,
+/// selected by a key, whose output is delivered as a stream of bytes rather than a buffer of a
+/// fixed length.
+///
+/// It has the same properties as [`Prf`](crate::Prf): the output is deterministic for a given key
+/// and input, and is indistinguishable from random bytes.  Reading `n` bytes from the stream
+/// always gives the same bytes, regardless of how the reads are split up.
+///
+/// Typical uses are deriving key material or other secrets of a size that is not known up front.
+pub trait StreamingPrf: StreamingPrfBoxClone {
+    /// Compute the PRF selected by the underlying key on `input`, returning a reader that
+    /// produces the output stream.
+    ///
+    /// The stream may be of limited length (depending on the underlying algorithm), in which case
+    /// the reader reports end-of-file once all of the available output has been read.
+    fn compute_prf(&self, input: &[u8]) -> Result<Box<dyn std::io::Read>, crate::TinkError>;
+}
+
+/// Trait bound to indicate that primitive trait objects should support cloning
+/// themselves as trait objects.
+pub trait StreamingPrfBoxClone {
+    fn box_clone(&self) -> Box<dyn StreamingPrf>;
+}
+
+/// Default implementation of the box-clone trait bound for any underlying
+/// concrete type that implements [`Clone`].
+impl<T> StreamingPrfBoxClone for T
+where
+    T: 'static + StreamingPrf + Clone,
+{
+    fn box_clone(&self) -> Box<dyn StreamingPrf> {
+        Box::new(self.clone())
+    }
+}
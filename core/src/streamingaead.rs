@@ -46,6 +46,45 @@ pub trait StreamingAead: StreamingAeadBoxClone {
         r: Box<dyn std::io::Read>,
         aad: &[u8],
     ) -> Result<Box<dyn std::io::Read>, crate::TinkError>;
+
+    /// Return a wrapper around an underlying `std::io::Write` that behaves like the result of
+    /// [`new_encrypting_writer`](StreamingAead::new_encrypting_writer), except that
+    /// `segment_aad` is additionally authenticated as part of every segment of the ciphertext
+    /// (rather than only being used when setting up the stream).  The same `segment_aad` has to
+    /// be passed to
+    /// [`new_decrypting_reader_with_segment_aad`](StreamingAead::new_decrypting_reader_with_segment_aad)
+    /// for decryption.
+    ///
+    /// An empty `segment_aad` gives the same ciphertext format as `new_encrypting_writer`.
+    /// The default implementation only supports an empty `segment_aad`.
+    fn new_encrypting_writer_with_segment_aad(
+        &self,
+        w: Box<dyn std::io::Write>,
+        aad: &[u8],
+        segment_aad: &[u8],
+    ) -> Result<Box<dyn EncryptingWrite>, crate::TinkError> {
+        if !segment_aad.is_empty() {
+            return Err("per-segment associated data not supported".into());
+        }
+        self.new_encrypting_writer(w, aad)
+    }
+
+    /// Return a wrapper around an underlying `std::io::Read` that decrypts ciphertext produced
+    /// by [`new_encrypting_writer_with_segment_aad`](StreamingAead::new_encrypting_writer_with_segment_aad),
+    /// using `aad` and `segment_aad` as associated authenticated data.
+    ///
+    /// The default implementation only supports an empty `segment_aad`.
+    fn new_decrypting_reader_with_segment_aad(
+        &self,
+        r: Box<dyn std::io::Read>,
+        aad: &[u8],
+        segment_aad: &[u8],
+    ) -> Result<Box<dyn std::io::Read>, crate::TinkError> {
+        if !segment_aad.is_empty() {
+            return Err("per-segment associated data not supported".into());
+        }
+        self.new_decrypting_reader(r, aad)
+    }
 }
 
 /// Trait for an object that writes encrypted data.  Users must call `close()` to finish.
@@ -39,7 +39,77 @@ fn validate_hkdf_params(
     }
 }
 
-/// Extract a pseudorandom key.
+/// Invoke `$body` with `$h` bound to the digest type corresponding to `$hash`.
+macro_rules! with_hkdf_hash {
+    ($hash:expr, $h:ident => $body:expr) => {
+        match $hash {
+            HashType::Sha1 => {
+                type $h = sha1::Sha1;
+                $body
+            }
+            HashType::Sha224 => {
+                type $h = sha2::Sha224;
+                $body
+            }
+            HashType::Sha256 => {
+                type $h = sha2::Sha256;
+                $body
+            }
+            HashType::Sha384 => {
+                type $h = sha2::Sha384;
+                $body
+            }
+            HashType::Sha512 => {
+                type $h = sha2::Sha512;
+                $body
+            }
+            h => return Err(format!("hkdf: unsupported hash {:?}", h).into()),
+        }
+    };
+}
+
+/// Return the maximum number of bytes that HKDF can produce with the given hash.
+pub fn hkdf_max_output_size(hash_alg: HashType) -> Result<usize, TinkError> {
+    Ok(255 * super::get_hash_digest_size(hash_alg)?)
+}
+
+/// Perform the HKDF-Extract step of [RFC 5869](https://tools.ietf.org/html/rfc5869), returning
+/// a pseudorandom key of the digest size of `hash_alg`.
+///
+/// An empty `salt` is equivalent to a salt of digest-size zero bytes.
+pub fn hkdf_extract(hash_alg: HashType, ikm: &[u8], salt: &[u8]) -> Result<Vec<u8>, TinkError> {
+    Ok(with_hkdf_hash!(hash_alg, H => {
+        let (prk, _) = hkdf::Hkdf::<H>::extract(Some(salt), ikm);
+        prk.to_vec()
+    }))
+}
+
+/// Perform the HKDF-Expand step of [RFC 5869](https://tools.ietf.org/html/rfc5869), deriving
+/// `output_size` bytes from the pseudorandom key `prk`.
+///
+/// The `prk` must be at least as long as the digest size of `hash_alg`, and `output_size` can be
+/// at most [`hkdf_max_output_size`].
+pub fn hkdf_expand(
+    hash_alg: HashType,
+    prk: &[u8],
+    info: &[u8],
+    output_size: usize,
+) -> Result<Vec<u8>, TinkError> {
+    if output_size > hkdf_max_output_size(hash_alg).map_err(|e| wrap_err("hkdf", e))? {
+        return Err("hkdf: output size too big".into());
+    }
+    let mut okm = vec![0; output_size];
+    with_hkdf_hash!(hash_alg, H => {
+        let hk = hkdf::Hkdf::<H>::from_prk(prk)
+            .map_err(|_| TinkError::new("hkdf: pseudorandom key too short"))?;
+        hk.expand(info, &mut okm)
+            .map_err(|_| TinkError::new("hkdf: expand failed"))?;
+    });
+    Ok(okm)
+}
+
+/// Extract a pseudorandom key from `key` and `salt`, and expand it into `tag_size` bytes
+/// using `info`.  The `tag_size` must be at least 10 bytes.
 pub fn compute_hkdf(
     hash_alg: HashType,
     key: &[u8],
@@ -50,29 +120,6 @@ pub fn compute_hkdf(
     let key_size = key.len();
     validate_hkdf_params(hash_alg, key_size, tag_size).map_err(|e| wrap_err("hkdf", e))?;
 
-    let mut okm = vec![0; tag_size];
-    match hash_alg {
-        HashType::Sha1 => {
-            let prk = hkdf::Hkdf::<sha1::Sha1>::new(Some(salt), key);
-            prk.expand(info, &mut okm)
-                .map_err(|_| "compute of hkdf failed")?;
-        }
-        HashType::Sha256 => {
-            let prk = hkdf::Hkdf::<sha2::Sha256>::new(Some(salt), key);
-            prk.expand(info, &mut okm)
-                .map_err(|_| "compute of hkdf failed")?;
-        }
-        HashType::Sha384 => {
-            let prk = hkdf::Hkdf::<sha2::Sha384>::new(Some(salt), key);
-            prk.expand(info, &mut okm)
-                .map_err(|_| "compute of hkdf failed")?;
-        }
-        HashType::Sha512 => {
-            let prk = hkdf::Hkdf::<sha2::Sha512>::new(Some(salt), key);
-            prk.expand(info, &mut okm)
-                .map_err(|_| "compute of hkdf failed")?;
-        }
-        h => return Err(format!("hkdf: unsupported hash {:?}", h).into()),
-    }
-    Ok(okm)
+    let prk = hkdf_extract(hash_alg, key, salt)?;
+    hkdf_expand(hash_alg, &prk, info, tag_size)
 }
@@ -15,17 +15,138 @@
 ////////////////////////////////////////////////////////////////////////////////
 
 //! Utilities for random data.
+//!
+//! All randomness used by Tink (for key generation, key IDs, IVs and nonces) is obtained via
+//! this module, either directly or through [`TinkRng`].  With the `test-rng` feature enabled,
+//! [`with_test_rng`] replaces this randomness with a seeded deterministic generator, so that
+//! tests can produce reproducible keys and ciphertexts.
 
 use rand::{thread_rng, Rng};
 
+/// Fill `dest` with random bytes.
+pub fn fill_random_bytes(dest: &mut [u8]) {
+    #[cfg(feature = "test-rng")]
+    {
+        if test_rng::fill(dest) {
+            return;
+        }
+    }
+    thread_rng().fill(dest);
+}
+
 /// Return a vector of the given `size` filled with random bytes.
 pub fn get_random_bytes(size: usize) -> Vec<u8> {
     let mut data = vec![0u8; size];
-    thread_rng().fill(&mut data[..]);
+    fill_random_bytes(&mut data[..]);
     data
 }
 
 /// Randomly generate an unsigned 32-bit integer.
 pub fn get_random_uint32() -> u32 {
-    thread_rng().gen()
+    let mut data = [0u8; 4];
+    fill_random_bytes(&mut data);
+    u32::from_le_bytes(data)
+}
+
+/// Cryptographically secure random number generator that draws from [`fill_random_bytes`], for
+/// use with dependencies that take an RNG parameter.  Implements the RNG traits of both
+/// `rand_core` 0.5 (as re-exported by `rand` 0.7) and `rand_core` 0.6.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TinkRng;
+
+impl rand::RngCore for TinkRng {
+    fn next_u32(&mut self) -> u32 {
+        get_random_uint32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut data = [0u8; 8];
+        fill_random_bytes(&mut data);
+        u64::from_le_bytes(data)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        fill_random_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        fill_random_bytes(dest);
+        Ok(())
+    }
+}
+
+impl rand::CryptoRng for TinkRng {}
+
+impl rand_core::RngCore for TinkRng {
+    fn next_u32(&mut self) -> u32 {
+        rand::RngCore::next_u32(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand::RngCore::next_u64(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        fill_random_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        fill_random_bytes(dest);
+        Ok(())
+    }
+}
+
+impl rand_core::CryptoRng for TinkRng {}
+
+/// Run `f` with all randomness on the current thread drawn from a deterministic generator
+/// seeded with `seed`, restoring the previous source of randomness afterwards.
+///
+/// The override is per-thread, so tests running in parallel do not affect each other; work
+/// that `f` hands off to other threads uses normal randomness.  The generated values are
+/// stable for a given seed (ChaCha20 keyed by the seed), allowing golden outputs to be
+/// recorded.
+///
+/// This is for tests only: key material generated under `with_test_rng` is predictable.
+#[cfg(feature = "test-rng")]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-rng")))]
+pub fn with_test_rng<T, F: FnOnce() -> T>(seed: u64, f: F) -> T {
+    test_rng::with_seed(seed, f)
+}
+
+#[cfg(feature = "test-rng")]
+mod test_rng {
+    use rand::{RngCore, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+    use std::cell::RefCell;
+
+    thread_local! {
+        static RNG: RefCell<Option<ChaCha20Rng>> = RefCell::new(None);
+    }
+
+    /// Restores the previous generator when dropped, including on panic.
+    struct Restore(Option<ChaCha20Rng>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let prev = self.0.take();
+            RNG.with(|rng| *rng.borrow_mut() = prev);
+        }
+    }
+
+    pub(super) fn with_seed<T, F: FnOnce() -> T>(seed: u64, f: F) -> T {
+        let prev = RNG.with(|rng| rng.borrow_mut().replace(ChaCha20Rng::seed_from_u64(seed)));
+        let _restore = Restore(prev);
+        f()
+    }
+
+    /// Fill `dest` from the deterministic generator, if one is active on this thread.
+    pub(super) fn fill(dest: &mut [u8]) -> bool {
+        RNG.with(|rng| match rng.borrow_mut().as_mut() {
+            Some(rng) => {
+                rng.fill_bytes(dest);
+                true
+            }
+            None => false,
+        })
+    }
 }
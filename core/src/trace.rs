@@ -0,0 +1,165 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Structured logging of primitive operations and keyset I/O.
+//!
+//! With the `tracing` feature enabled, Tink emits [`tracing`](https://docs.rs/tracing) spans
+//! and events (under the `tink` target) describing primitive operations and keyset reads and
+//! writes, to help diagnose failures such as "decryption failed" in production.  Without the
+//! feature, the functions in this module do nothing.
+//!
+//! Events only ever include operation names, data lengths, key IDs, key statuses, output
+//! prefix types, key type URLs and fixed failure reasons.  This is guaranteed by the types
+//! accepted here: nothing in this module takes key material, plaintext, ciphertext,
+//! associated data or error messages, so none of these can reach the logs.
+//!
+//! Operations are logged at `DEBUG` level (with failures at `WARN`), and the individual keys
+//! tried during an operation at `TRACE` level.
+
+use crate::KeyId;
+use tink_proto::{Keyset, OutputPrefixType};
+
+/// Target used for all spans and events.
+#[cfg(feature = "tracing")]
+const TARGET: &str = "tink";
+
+/// A traced primitive operation, such as AEAD decryption.
+pub struct Operation {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+/// Start tracing an operation named `name` (e.g. "decrypt") of the given `primitive` (e.g.
+/// "aead"), whose input data is `input_len` bytes long.
+#[allow(unused_variables)]
+pub fn operation(primitive: &'static str, name: &'static str, input_len: usize) -> Operation {
+    Operation {
+        #[cfg(feature = "tracing")]
+        span: tracing::debug_span!(
+            target: TARGET,
+            "tink_operation",
+            primitive,
+            operation = name,
+            input_len
+        ),
+    }
+}
+
+#[allow(unused_variables)]
+impl Operation {
+    /// Record that the key with the given ID was tried, but did not work for this operation.
+    pub fn key_failed(&self, key_id: KeyId) {
+        #[cfg(feature = "tracing")]
+        tracing::trace!(target: TARGET, parent: &self.span, key_id, "key did not match");
+    }
+
+    /// Record that the operation succeeded with the key with the given ID, producing
+    /// `output_len` bytes of output.
+    pub fn succeeded(&self, key_id: KeyId, output_len: usize) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            target: TARGET,
+            parent: &self.span,
+            key_id,
+            output_len,
+            "operation succeeded"
+        );
+    }
+
+    /// Record that the operation failed, for the given reason.
+    pub fn failed(&self, reason: &'static str) {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(target: TARGET, parent: &self.span, reason, "operation failed");
+    }
+}
+
+/// Record that an [`UnwrapCache`](crate::keyset::UnwrapCache) operation (e.g. "get") failed,
+/// and that the failure was ignored.
+#[allow(unused_variables)]
+pub(crate) fn unwrap_cache_failed(operation: &'static str) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(
+        target: TARGET,
+        operation,
+        "unwrap cache operation failed, ignoring"
+    );
+}
+
+/// Record a successful keyset read or write operation (e.g. "read_encrypted").
+#[allow(unused_variables)]
+pub(crate) fn keyset_io(operation: &'static str, ks: &Keyset) {
+    #[cfg(feature = "tracing")]
+    {
+        let span = tracing::debug_span!(target: TARGET, "tink_keyset_io", operation);
+        tracing::debug!(
+            target: TARGET,
+            parent: &span,
+            primary_key_id = ks.primary_key_id,
+            key_count = ks.key.len(),
+            "keyset I/O succeeded"
+        );
+        for key in &ks.key {
+            let type_url = key.key_data.as_ref().map_or("", |kd| kd.type_url.as_str());
+            tracing::trace!(
+                target: TARGET,
+                parent: &span,
+                key_id = key.key_id,
+                type_url,
+                status = key.status,
+                output_prefix_type = key.output_prefix_type,
+                "keyset key"
+            );
+        }
+    }
+}
+
+/// Record a failed keyset read or write operation, for the given reason.
+#[allow(unused_variables)]
+pub(crate) fn keyset_io_failed(operation: &'static str, reason: &'static str) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(target: TARGET, operation, reason, "keyset I/O failed");
+}
+
+/// Record the acceptance of a key with a higher version than the key manager supports.
+#[allow(unused_variables)]
+pub(crate) fn newer_key_version_accepted(version: u32, max_supported: u32) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(
+        target: TARGET,
+        version,
+        max_supported,
+        "accepted key with unsupported version"
+    );
+}
+
+/// Record the creation of a primitive for a key, as part of building a primitive set.
+#[allow(unused_variables)]
+pub(crate) fn primitive_created(
+    key_id: KeyId,
+    type_url: &str,
+    prefix_type: OutputPrefixType,
+    primary: bool,
+) {
+    #[cfg(feature = "tracing")]
+    tracing::trace!(
+        target: TARGET,
+        key_id,
+        type_url,
+        output_prefix_type = ?prefix_type,
+        primary,
+        "primitive created"
+    );
+}
@@ -19,7 +19,12 @@
 //! Some of these utilities are not idiomatic Rust, but are included to make the process of
 //! translating code from other languages (e.g. Go) easier.
 
-use std::error::Error;
+use std::{
+    cell::{RefCell, RefMut},
+    error::Error,
+    io,
+    rc::Rc,
+};
 
 /// `Error` type for errors emitted by Tink. Note that errors from cryptographic
 /// operations are necessarily uninformative, to avoid information leakage.
@@ -62,6 +67,12 @@ impl std::convert::From<String> for TinkError {
     }
 }
 
+impl std::convert::From<tink_proto::UnknownEnumValue> for TinkError {
+    fn from(e: tink_proto::UnknownEnumValue) -> Self {
+        e.to_string().into()
+    }
+}
+
 /// Wrap an error with an additional message.  This utility is intended to help
 /// with porting Go code to Rust, to cover patterns like:
 ///
@@ -80,3 +91,83 @@ where
         src: Some(Box::new(src)),
     }
 }
+
+/// Wrapper around an [`io::Read`] trait object that stores a copy of all of the data
+/// read from the underlying object.
+struct CopyReader {
+    reader: Box<dyn io::Read>,
+    copying: bool,
+    read_pos: usize,
+    copied_data: Vec<u8>,
+}
+
+impl CopyReader {
+    fn new(reader: Box<dyn io::Read>) -> Self {
+        Self {
+            reader,
+            copying: true,
+            read_pos: 0,
+            copied_data: vec![],
+        }
+    }
+    fn rewind(&mut self) {
+        self.read_pos = 0;
+    }
+    fn stop_copying(&mut self) {
+        self.copying = false;
+        // Buffered data has been consumed, so drop it.
+        self.copied_data = vec![];
+        self.read_pos = 0;
+    }
+}
+
+impl io::Read for CopyReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.read_pos < self.copied_data.len() {
+            // Read from the buffered copy of the data.
+            let available_data = self.copied_data.len() - self.read_pos;
+            let n = std::cmp::min(buf.len(), available_data);
+            buf[..n].copy_from_slice(&self.copied_data[self.read_pos..self.read_pos + n]);
+            self.read_pos += n;
+            Ok(n)
+        } else {
+            // Read from the underlying object
+            let n = self.reader.read(buf)?;
+            if self.copying {
+                // Store a copy of the data read.
+                self.copied_data.extend_from_slice(&buf[..n]);
+                self.read_pos += n;
+            }
+            Ok(n)
+        }
+    }
+}
+
+/// Shareable reader that keeps a copy of the data read from an underlying reader, so that the
+/// same data can be re-read after a [`rewind`](SharedCopyReader::rewind).  This is used by
+/// decrypting readers that try each candidate key in turn on the start of a ciphertext stream.
+#[derive(Clone)]
+pub struct SharedCopyReader(Rc<RefCell<CopyReader>>);
+
+impl SharedCopyReader {
+    pub fn new(reader: Box<dyn io::Read>) -> Self {
+        Self(Rc::new(RefCell::new(CopyReader::new(reader))))
+    }
+    /// Arrange for subsequent reads to start again from the beginning of the copied data.
+    pub fn rewind(&mut self) {
+        let mut cr: RefMut<_> = self.0.borrow_mut();
+        cr.rewind();
+    }
+    /// Stop copying data, and drop any data copied so far.
+    pub fn stop_copying(&mut self) {
+        let mut cr: RefMut<_> = self.0.borrow_mut();
+        cr.stop_copying();
+    }
+}
+
+impl io::Read for SharedCopyReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut cr: RefMut<_> = self.0.borrow_mut();
+        cr.read(buf)
+    }
+}
@@ -24,6 +24,28 @@
 pub trait Verifier: VerifierBoxClone {
     /// Returns `Ok(())` if `signature` is a valid signature for `data`; otherwise returns an error.
     fn verify(&self, signature: &[u8], data: &[u8]) -> Result<(), crate::TinkError>;
+
+    /// Returns `Ok(())` if every `(signature, data)` pair in `items` holds a valid signature;
+    /// otherwise returns an error.  The default implementation verifies each pair in turn, but
+    /// implementations may verify the whole batch more efficiently.
+    fn verify_batch(&self, items: &[(&[u8], &[u8])]) -> Result<(), crate::TinkError> {
+        for (signature, data) in items {
+            self.verify(signature, data)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the identifier of the key that validated `signature` if it is a valid signature
+    /// for `data`; otherwise returns an error.  Only primitives backed by a keyset (such as those
+    /// created by `tink_signature::new_verifier()`) know their key identifiers; the default
+    /// implementation returns an error.
+    fn verify_with_key_id(
+        &self,
+        _signature: &[u8],
+        _data: &[u8],
+    ) -> Result<crate::KeyId, crate::TinkError> {
+        Err("verify_with_key_id not supported".into())
+    }
 }
 
 /// Trait bound to indicate that primitive trait objects should support cloning
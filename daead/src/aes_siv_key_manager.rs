@@ -57,13 +57,7 @@ impl KeyManager for AesSivKeyManager {
             // If a key format was provided, check it is valid.
             let key_format = tink_proto::AesSivKeyFormat::decode(serialized_key_format)
                 .map_err(|_| "AesSivKeyManager: invalid key format")?;
-            if key_format.key_size as usize != subtle::AES_SIV_KEY_SIZE {
-                return Err(format!(
-                    "AesSivKeyManager: key_format.key_size != {}",
-                    subtle::AES_SIV_KEY_SIZE
-                )
-                .into());
-            }
+            validate_key_format(&key_format)?;
         }
         let key = tink_proto::AesSivKey {
             version: AES_SIV_KEY_VERSION,
@@ -95,3 +89,15 @@ fn validate_key(key: &tink_proto::AesSivKey) -> Result<(), TinkError> {
         Ok(())
     }
 }
+
+/// Validate the given [`AesSivKeyFormat`](tink_proto::AesSivKeyFormat).
+pub(crate) fn validate_key_format(format: &tink_proto::AesSivKeyFormat) -> Result<(), TinkError> {
+    if format.key_size as usize != subtle::AES_SIV_KEY_SIZE {
+        return Err(format!(
+            "AesSivKeyManager: key_format.key_size != {}",
+            subtle::AES_SIV_KEY_SIZE
+        )
+        .into());
+    }
+    Ok(())
+}
@@ -51,6 +51,9 @@ impl WrappedDeterministicAead {
     fn new(
         ps: tink_core::primitiveset::PrimitiveSet,
     ) -> Result<WrappedDeterministicAead, TinkError> {
+        if !ps.usage.allows_encrypt() && !ps.usage.allows_decrypt() {
+            return Err("daead::factory: keyset usage does not permit deterministic AEAD".into());
+        }
         let entry = match &ps.primary {
             None => return Err("daead::factory: no primary primitive".into()),
             Some(p) => p,
@@ -75,21 +78,46 @@ impl WrappedDeterministicAead {
 
 impl tink_core::DeterministicAead for WrappedDeterministicAead {
     fn encrypt_deterministically(&self, pt: &[u8], aad: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let op = tink_core::trace::operation("daead", "encrypt", pt.len());
+        if !self.ps.usage.allows_encrypt() {
+            op.failed("encryption not permitted");
+            return Err("daead::factory: encryption not permitted".into());
+        }
+        self.ps.check_primary_validity().map_err(|e| {
+            op.failed("primary key not valid");
+            e
+        })?;
+        self.ps.record_primary_usage(pt.len()).map_err(|e| {
+            op.failed("primary key usage limit reached");
+            e
+        })?;
         let primary = self
             .ps
             .primary
             .as_ref()
             .ok_or_else(|| TinkError::new("no primary"))?;
 
-        let ct = primary.primitive.encrypt_deterministically(pt, aad)?;
+        let ct = primary
+            .primitive
+            .encrypt_deterministically(pt, aad)
+            .map_err(|e| {
+                op.failed("encryption failed");
+                e
+            })?;
 
         let mut ret = Vec::with_capacity(primary.prefix.len() + ct.len());
         ret.extend_from_slice(&primary.prefix);
         ret.extend_from_slice(&ct);
+        op.succeeded(primary.key_id, ret.len());
         Ok(ret)
     }
 
     fn decrypt_deterministically(&self, ct: &[u8], aad: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let op = tink_core::trace::operation("daead", "decrypt", ct.len());
+        if !self.ps.usage.allows_decrypt() {
+            op.failed("decryption not permitted");
+            return Err("daead::factory: decryption not permitted".into());
+        }
         // try non-raw keys
         let prefix_size = tink_core::cryptofmt::NON_RAW_PREFIX_SIZE;
         if ct.len() > prefix_size {
@@ -98,8 +126,10 @@ impl tink_core::DeterministicAead for WrappedDeterministicAead {
             if let Some(entries) = self.ps.entries_for_prefix(prefix) {
                 for entry in entries {
                     if let Ok(pt) = entry.primitive.decrypt_deterministically(ct_no_prefix, aad) {
+                        op.succeeded(entry.key_id, pt.len());
                         return Ok(pt);
                     }
+                    op.key_failed(entry.key_id);
                 }
             }
         }
@@ -108,12 +138,15 @@ impl tink_core::DeterministicAead for WrappedDeterministicAead {
         if let Some(entries) = self.ps.raw_entries() {
             for entry in entries {
                 if let Ok(pt) = entry.primitive.decrypt_deterministically(ct, aad) {
+                    op.succeeded(entry.key_id, pt.len());
                     return Ok(pt);
                 }
+                op.key_failed(entry.key_id);
             }
         }
 
         // nothing worked
+        op.failed("no key could decrypt");
         Err("daead::factory: decryption failed".into())
     }
 }
@@ -16,19 +16,65 @@
 
 //! This module contains pre-generated [`KeyTemplate`] instances for deterministic AEAD.
 
-use tink_proto::{prost::Message, KeyTemplate};
+use tink_core::TinkError;
+use tink_proto::{prost::Message, KeyTemplate, OutputPrefixType};
 
 /// Return a [`KeyTemplate`](tink_proto::KeyTemplate) that generates a AES-SIV key.
 pub fn aes_siv_key_template() -> KeyTemplate {
-    let format = tink_proto::AesSivKeyFormat {
-        key_size: 64,
-        version: crate::AES_SIV_KEY_VERSION,
-    };
-    let mut serialized_format = Vec::new();
-    format.encode(&mut serialized_format).unwrap(); // safe: proto-encode
-    KeyTemplate {
-        type_url: crate::AES_SIV_TYPE_URL.to_string(),
-        output_prefix_type: tink_proto::OutputPrefixType::Tink as i32,
-        value: serialized_format,
+    AesSivTemplateBuilder::new().build().unwrap() // safe: valid parameters
+}
+
+/// Builder for AES-SIV [`KeyTemplate`]s, whose parameters are checked when the template is built.
+/// Defaults to a 64-byte key with TINK output prefix.
+#[derive(Clone, Copy, Debug)]
+pub struct AesSivTemplateBuilder {
+    key_size: u32,
+    prefix: OutputPrefixType,
+}
+
+impl Default for AesSivTemplateBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AesSivTemplateBuilder {
+    /// Create a builder with the default parameters.
+    pub fn new() -> Self {
+        Self {
+            key_size: crate::subtle::AES_SIV_KEY_SIZE as u32,
+            prefix: OutputPrefixType::Tink,
+        }
+    }
+
+    /// Set the key size in bytes (only 64 is supported).
+    pub fn key_size(mut self, key_size: u32) -> Self {
+        self.key_size = key_size;
+        self
+    }
+
+    /// Set the output prefix type.
+    pub fn prefix(mut self, prefix: OutputPrefixType) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    /// Build the [`KeyTemplate`], or return an error if the parameters are invalid.
+    pub fn build(&self) -> Result<KeyTemplate, TinkError> {
+        if self.prefix == OutputPrefixType::UnknownPrefix {
+            return Err("invalid output prefix type".into());
+        }
+        let format = tink_proto::AesSivKeyFormat {
+            key_size: self.key_size,
+            version: crate::AES_SIV_KEY_VERSION,
+        };
+        crate::aes_siv_key_manager::validate_key_format(&format)?;
+        let mut serialized_format = Vec::new();
+        format.encode(&mut serialized_format).unwrap(); // safe: proto-encode
+        Ok(KeyTemplate {
+            type_url: crate::AES_SIV_TYPE_URL.to_string(),
+            output_prefix_type: self.prefix as i32,
+            value: serialized_format,
+        })
     }
 }
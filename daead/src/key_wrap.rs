@@ -0,0 +1,70 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Helpers for wrapping key material with a deterministic AEAD.
+
+use tink_core::{utils::wrap_err, TinkError};
+use tink_proto::prost::Message;
+
+/// Domain separation label included in the associated data of every wrapped key.
+const KEY_WRAP_LABEL: &[u8] = b"tink-key-wrap-v1";
+
+/// Build the associated data that binds a wrapped key to its type URL.
+fn key_wrap_ad(type_url: &str) -> Vec<u8> {
+    let mut ad = Vec::with_capacity(KEY_WRAP_LABEL.len() + type_url.len());
+    ad.extend_from_slice(KEY_WRAP_LABEL);
+    ad.extend_from_slice(type_url.as_bytes());
+    ad
+}
+
+/// Wrap `key_data` with the key-encryption primitive `kek`, which should be an AES-SIV
+/// [`tink_core::DeterministicAead`] (e.g. obtained from [`aes_siv_key_template`](crate::aes_siv_key_template)).
+///
+/// The serialized [`tink_proto::KeyData`] is encrypted with the key's type URL bound in as
+/// associated data, so that a wrapped key cannot be unwrapped as a key of a different type.
+/// Wrapping is deterministic: the same key always wraps to the same output.
+pub fn wrap_key(
+    kek: &dyn tink_core::DeterministicAead,
+    key_data: &tink_proto::KeyData,
+) -> Result<Vec<u8>, TinkError> {
+    if key_data.type_url.is_empty() {
+        return Err("key_wrap: missing type URL".into());
+    }
+    let mut serialized = Vec::new();
+    key_data
+        .encode(&mut serialized)
+        .map_err(|e| wrap_err("key_wrap: failed to encode key data", e))?;
+    kek.encrypt_deterministically(&serialized, &key_wrap_ad(&key_data.type_url))
+        .map_err(|e| wrap_err("key_wrap: failed to wrap key", e))
+}
+
+/// Unwrap a key produced by [`wrap_key`], checking that it was wrapped as a key of type
+/// `type_url`.
+pub fn unwrap_key(
+    kek: &dyn tink_core::DeterministicAead,
+    wrapped: &[u8],
+    type_url: &str,
+) -> Result<tink_proto::KeyData, TinkError> {
+    let serialized = kek
+        .decrypt_deterministically(wrapped, &key_wrap_ad(type_url))
+        .map_err(|e| wrap_err("key_wrap: failed to unwrap key", e))?;
+    let key_data = tink_proto::KeyData::decode(serialized.as_ref())
+        .map_err(|e| wrap_err("key_wrap: invalid key data", e))?;
+    if key_data.type_url != type_url {
+        return Err("key_wrap: type URL mismatch".into());
+    }
+    Ok(key_data)
+}
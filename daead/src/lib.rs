@@ -29,6 +29,8 @@ mod factory;
 pub use factory::*;
 mod key_templates;
 pub use key_templates::*;
+mod key_wrap;
+pub use key_wrap::*;
 
 pub mod subtle;
 
@@ -16,7 +16,11 @@
 
 //! Provides subtle implementations of the `DeterministicAEAD` primitive using AES-SIV.
 
-use aes_siv::{aead::generic_array::GenericArray, siv::Aes256Siv, KeyInit};
+use aes_siv::{
+    aead::generic_array::GenericArray,
+    siv::{Aes128Siv, Aes256Siv, CmacSiv},
+    KeyInit,
+};
 use std::{cell::RefCell, rc::Rc};
 use tink_core::{utils::wrap_err, TinkError};
 
@@ -43,16 +47,28 @@ const AES_BLOCK_SIZE: usize = 16;
 /// Since 192-bit AES keys are not supported by tink for voodoo reasons
 /// and RFC 5297 only supports same size encryption and MAC keys this
 /// implies that keys must be 64 bytes (2*256 bits) long.
+///
+/// For interoperability with other systems, [`AesSiv::new_compat`] also accepts the shorter
+/// key sizes of RFC 5297; such keys are not supported by the key manager.
 #[derive(Clone)]
 pub struct AesSiv {
     // Need to use interior mutability because `aes_siv::siv::Siv` operations
     // take a `&mut self` parameter.
-    cipher: Rc<RefCell<Aes256Siv>>,
+    cipher: Rc<RefCell<AesSivVariant>>,
+}
+
+enum AesSivVariant {
+    Aes128(Aes128Siv),
+    Aes192(CmacSiv<aes::Aes192>),
+    Aes256(Aes256Siv),
 }
 
 /// Key size in bytes.
 pub const AES_SIV_KEY_SIZE: usize = 64; // 512 bits
 
+/// Key sizes in bytes accepted by [`AesSiv::new_compat`].
+pub const AES_SIV_COMPAT_KEY_SIZES: &[usize] = &[32, 48, 64];
+
 impl AesSiv {
     /// Return an [`AesSiv`] instance.
     pub fn new(key: &[u8]) -> Result<AesSiv, TinkError> {
@@ -60,8 +76,24 @@ impl AesSiv {
             return Err(format!("AesSiv::new: invalid key size {}", key.len()).into());
         }
 
+        Self::new_compat(key)
+    }
+
+    /// Return an [`AesSiv`] instance for a key of any of the sizes defined in RFC 5297: 32, 48
+    /// or 64 bytes (for AES-128, AES-192 or AES-256 respectively).
+    ///
+    /// This is intended for compatibility with other systems (for example, to decrypt data
+    /// produced elsewhere with a 32-byte key); see the security note on [`AesSiv`] for why new
+    /// keys should be 64 bytes long.
+    pub fn new_compat(key: &[u8]) -> Result<AesSiv, TinkError> {
+        let cipher = match key.len() {
+            32 => AesSivVariant::Aes128(Aes128Siv::new(GenericArray::from_slice(key))), // safe: length matched
+            48 => AesSivVariant::Aes192(CmacSiv::<aes::Aes192>::new(GenericArray::from_slice(key))), // safe: length matched
+            64 => AesSivVariant::Aes256(Aes256Siv::new(GenericArray::from_slice(key))), // safe: length matched
+            l => return Err(format!("AesSiv::new: invalid key size {}", l).into()),
+        };
         Ok(AesSiv {
-            cipher: Rc::new(RefCell::new(Aes256Siv::new(GenericArray::from_slice(key)))),
+            cipher: Rc::new(RefCell::new(cipher)),
         })
     }
 }
@@ -75,10 +107,13 @@ impl tink_core::DeterministicAead for AesSiv {
         if plaintext.len() > (isize::MAX as usize) - AES_BLOCK_SIZE {
             return Err("AesSiv: plaintext too long".into());
         }
-        self.cipher
-            .borrow_mut()
-            .encrypt([additional_data], plaintext)
-            .map_err(|e| wrap_err("AesSiv: encrypt failed", e))
+        let headers = [additional_data];
+        match &mut *self.cipher.borrow_mut() {
+            AesSivVariant::Aes128(c) => c.encrypt(headers, plaintext),
+            AesSivVariant::Aes192(c) => c.encrypt(headers, plaintext),
+            AesSivVariant::Aes256(c) => c.encrypt(headers, plaintext),
+        }
+        .map_err(|e| wrap_err("AesSiv: encrypt failed", e))
     }
 
     fn decrypt_deterministically(
@@ -89,9 +124,12 @@ impl tink_core::DeterministicAead for AesSiv {
         if ciphertext.len() < aes_siv::siv::IV_SIZE {
             return Err("AesSiv: ciphertext is too short".into());
         }
-        self.cipher
-            .borrow_mut()
-            .decrypt([additional_data], ciphertext)
-            .map_err(|e| wrap_err("AesSiv: decrypt failed", e))
+        let headers = [additional_data];
+        match &mut *self.cipher.borrow_mut() {
+            AesSivVariant::Aes128(c) => c.decrypt(headers, ciphertext),
+            AesSivVariant::Aes192(c) => c.decrypt(headers, ciphertext),
+            AesSivVariant::Aes256(c) => c.decrypt(headers, ciphertext),
+        }
+        .map_err(|e| wrap_err("AesSiv: decrypt failed", e))
     }
 }
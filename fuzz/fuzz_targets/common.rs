@@ -0,0 +1,40 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Common utilities for fuzz targets.
+
+/// Register all of the key managers from the primitive crates.
+pub fn init() {
+    tink_aead::init();
+    tink_daead::init();
+    tink_hybrid::init();
+    tink_mac::init();
+    tink_prf::init();
+    tink_signature::init();
+    tink_streaming_aead::init();
+}
+
+/// Exercise the primitives for a keyset handle, which should not panic (but may fail).
+pub fn exercise_handle(kh: &tink_core::keyset::Handle) {
+    let _ = kh.keyset_info();
+    let _ = kh.public();
+    let _ = tink_aead::new(kh).map(|p| p.decrypt(b"ciphertext", b"aad"));
+    let _ = tink_daead::new(kh).map(|p| p.decrypt_deterministically(b"ciphertext", b"aad"));
+    let _ = tink_hybrid::new_decrypt(kh).map(|p| p.decrypt(b"ciphertext", b"context"));
+    let _ = tink_mac::new(kh).map(|p| p.verify_mac(b"mac value", b"data"));
+    let _ = tink_prf::Set::new(kh).map(|p| p.compute_primary_prf(b"input", 16));
+    let _ = tink_signature::new_verifier(kh).map(|p| p.verify(b"signature", b"data"));
+}
@@ -0,0 +1,42 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+mod common;
+
+// The first byte of the input selects one of the key managers registered by `common::init()`; the
+// remainder is used as a serialized key and as a serialized key format.
+fuzz_target!(|data: &[u8]| {
+    common::init();
+    if data.is_empty() {
+        return;
+    }
+    let type_urls = tink_core::registry::key_manager_type_urls();
+    let type_url = type_urls[data[0] as usize % type_urls.len()];
+    let km = match tink_core::registry::get_key_manager(type_url) {
+        Ok(km) => km,
+        Err(_) => return,
+    };
+    let input = &data[1..];
+    let _ = km.primitive(input);
+    let _ = km.new_key(input);
+    let _ = km.new_key_data(input);
+    if km.supports_private_keys() {
+        let _ = km.public_key_data(input);
+    }
+});
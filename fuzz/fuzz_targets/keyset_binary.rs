@@ -0,0 +1,30 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+mod common;
+
+fuzz_target!(|data: &[u8]| {
+    common::init();
+    let mut reader = tink_core::keyset::BinaryReader::new(data);
+    if let Ok(kh) = tink_core::keyset::insecure::read(&mut reader) {
+        common::exercise_handle(&kh);
+    }
+    let mut reader = tink_core::keyset::BinaryReader::new(data);
+    let _ = tink_core::keyset::Handle::read_with_no_secrets(&mut reader);
+});
@@ -86,3 +86,28 @@ fn bench_ecies_hkdf_aes128_ctr_hmac_decrypt_fail(b: &mut Bencher) {
     let (d, ct) = setup_failure(tink_hybrid::ecies_hkdf_aes128_ctr_hmac_sha256_key_template());
     b.iter(|| d.decrypt(&ct, CONTEXT).unwrap_err())
 }
+
+fn setup_kem_reuse(
+    kt: tink_proto::KeyTemplate,
+    max_messages: u64,
+) -> Box<dyn tink_core::HybridEncrypt> {
+    tink_hybrid::init();
+    let kh = tink_core::keyset::Handle::new(&kt).unwrap();
+    let pubkh = kh.public().unwrap();
+    tink_hybrid::new_encrypt_with_kem_reuse(&pubkh, max_messages).unwrap()
+}
+
+#[bench]
+fn bench_ecies_hkdf_aes128_gcm_encrypt_kem_reuse_100(b: &mut Bencher) {
+    let e = setup_kem_reuse(tink_hybrid::ecies_hkdf_aes128_gcm_key_template(), 100);
+    b.iter(|| e.encrypt(MSG, CONTEXT).unwrap())
+}
+
+#[bench]
+fn bench_ecies_hkdf_aes128_ctr_hmac_encrypt_kem_reuse_100(b: &mut Bencher) {
+    let e = setup_kem_reuse(
+        tink_hybrid::ecies_hkdf_aes128_ctr_hmac_sha256_key_template(),
+        100,
+    );
+    b.iter(|| e.encrypt(MSG, CONTEXT).unwrap())
+}
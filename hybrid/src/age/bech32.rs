@@ -0,0 +1,131 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Bech32 encoding (BIP 173), as used for age recipient and identity strings.  Unlike BIP 173,
+//! no limit is imposed on the overall length.
+
+use tink_core::TinkError;
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GENERATOR: [u32; 5] = [
+    0x3b6a_57b2,
+    0x2650_8e6d,
+    0x1ea1_19fa,
+    0x3d42_33dd,
+    0x2a14_62b3,
+];
+const CHECKSUM_SIZE: usize = 6;
+
+fn polymod(values: &[u8]) -> u32 {
+    let mut chk: u32 = 1;
+    for v in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x1ff_ffff) << 5) ^ u32::from(*v);
+        for (i, g) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+    }
+    chk
+}
+
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    let mut v: Vec<u8> = hrp.bytes().map(|b| b >> 5).collect();
+    v.push(0);
+    v.extend(hrp.bytes().map(|b| b & 31));
+    v
+}
+
+/// Regroup a sequence of `from`-bit values into `to`-bit values.
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>, TinkError> {
+    let mut acc: u32 = 0;
+    let mut bits: u32 = 0;
+    let max = (1u32 << to) - 1;
+    let mut out = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+    for b in data {
+        acc = (acc << from) | u32::from(*b);
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            out.push(((acc >> bits) & max) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            out.push(((acc << (to - bits)) & max) as u8);
+        }
+    } else if bits >= from || ((acc << (to - bits)) & max) != 0 {
+        return Err("bech32: invalid padding".into());
+    }
+    Ok(out)
+}
+
+/// Encode `data` with the given (lower case) human-readable part.
+pub(crate) fn encode(hrp: &str, data: &[u8]) -> String {
+    let values = convert_bits(data, 8, 5, true).unwrap(); // safe: padded conversion
+
+    let mut check_input = hrp_expand(hrp);
+    check_input.extend_from_slice(&values);
+    check_input.extend_from_slice(&[0; CHECKSUM_SIZE]);
+    let checksum = polymod(&check_input) ^ 1;
+
+    let mut s = String::with_capacity(hrp.len() + 1 + values.len() + CHECKSUM_SIZE);
+    s.push_str(hrp);
+    s.push('1');
+    for v in values {
+        s.push(CHARSET[v as usize] as char);
+    }
+    for i in 0..CHECKSUM_SIZE {
+        s.push(CHARSET[((checksum >> (5 * (5 - i))) & 31) as usize] as char);
+    }
+    s
+}
+
+/// Decode a Bech32 string, returning the (lower case) human-readable part and the data.
+pub(crate) fn decode(s: &str) -> Result<(String, Vec<u8>), TinkError> {
+    if s.bytes().any(|b| !(33..=126).contains(&b)) {
+        return Err("bech32: invalid character".into());
+    }
+    let lower = s.to_ascii_lowercase();
+    if lower != s && s.to_ascii_uppercase() != s {
+        return Err("bech32: mixed case".into());
+    }
+    let sep = lower
+        .rfind('1')
+        .ok_or_else(|| TinkError::new("bech32: no separator"))?;
+    if sep == 0 || sep + 1 + CHECKSUM_SIZE > lower.len() {
+        return Err("bech32: invalid length".into());
+    }
+    let (hrp, rest) = (&lower[..sep], &lower[sep + 1..]);
+    let values = rest
+        .bytes()
+        .map(|b| {
+            CHARSET
+                .iter()
+                .position(|c| *c == b)
+                .map(|p| p as u8)
+                .ok_or_else(|| TinkError::new("bech32: invalid character"))
+        })
+        .collect::<Result<Vec<u8>, _>>()?;
+    let mut check_input = hrp_expand(hrp);
+    check_input.extend_from_slice(&values);
+    if polymod(&check_input) != 1 {
+        return Err("bech32: invalid checksum".into());
+    }
+    let data = convert_bits(&values[..values.len() - CHECKSUM_SIZE], 5, 8, false)?;
+    Ok((hrp.to_string(), data))
+}
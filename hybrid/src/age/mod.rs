@@ -0,0 +1,210 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Interoperability with the [age](https://age-encryption.org/v1) file encryption tool.
+//!
+//! Keys created from [`age_x25519_key_template`](crate::age_x25519_key_template) produce
+//! [`HybridEncrypt`](tink_core::HybridEncrypt) and [`HybridDecrypt`](tink_core::HybridDecrypt)
+//! primitives whose ciphertexts are complete age files, so that (for example) backups encrypted
+//! with Tink can be decrypted by operators with `age -d -i key.txt`.  This module converts
+//! between keysets and the `age1...` / `AGE-SECRET-KEY-1...` strings used by the `age` tool,
+//! and encrypts to several recipients at once.
+//!
+//! Passphrase-protected files are handled directly by
+//! [`AgeEncrypt::with_passphrase`](crate::subtle::AgeEncrypt::with_passphrase) and
+//! [`AgeDecrypt::with_passphrase`](crate::subtle::AgeDecrypt::with_passphrase).
+//!
+//! The age format has no equivalent of Tink's `context_info`, so the primitives reject a
+//! non-empty `context_info`.
+
+use crate::subtle::{x25519_key, AgeEncrypt, AgeRecipient, X25519_KEY_SIZE};
+use tink_core::{
+    keyset::{Handle, Manager, MemReaderWriter},
+    utils::wrap_err,
+    TinkError,
+};
+use tink_proto::prost::Message;
+
+mod bech32;
+
+/// Human-readable prefix of age recipient strings.
+pub const AGE_RECIPIENT_HRP: &str = "age";
+/// Human-readable prefix of age identity strings.
+pub const AGE_IDENTITY_HRP: &str = "age-secret-key-";
+
+/// Return the X25519 public keys of the enabled age keys in a keyset holding either private or
+/// public keys, with the primary key first.
+fn public_keys(h: &Handle) -> Result<Vec<[u8; X25519_KEY_SIZE]>, TinkError> {
+    let info = h.keyset_info();
+    let public_handle = if info
+        .key_info
+        .iter()
+        .any(|k| k.type_url == crate::AGE_X25519_PRIVATE_KEY_TYPE_URL)
+    {
+        h.public()?
+    } else {
+        h.clone()
+    };
+    let mut mem = MemReaderWriter::default();
+    public_handle.write_with_no_secrets(&mut mem)?;
+    let ks = mem
+        .keyset
+        .ok_or_else(|| TinkError::new("age: failed to read keyset"))?;
+
+    let mut keys: Vec<_> = ks
+        .key
+        .iter()
+        .filter(|k| k.status == tink_proto::KeyStatusType::Enabled as i32)
+        .collect();
+    keys.sort_by_key(|k| k.key_id != ks.primary_key_id);
+    if keys.is_empty() {
+        return Err("age: no enabled keys".into());
+    }
+    keys.iter()
+        .map(|key| {
+            let key_data = key
+                .key_data
+                .as_ref()
+                .ok_or_else(|| TinkError::new("age: invalid keyset"))?;
+            if key_data.type_url != crate::AGE_X25519_PUBLIC_KEY_TYPE_URL {
+                return Err(format!("age: unsupported key type {}", key_data.type_url).into());
+            }
+            let key = tink_proto::AgeX25519PublicKey::decode(key_data.value.as_ref())
+                .map_err(|e| wrap_err("age: invalid key", e))?;
+            crate::validate_age_x25519_public_key(&key)
+        })
+        .collect()
+}
+
+/// Return the `age1...` recipient strings for the enabled age keys in the keyset, which may
+/// hold either private or public keys, with the primary key first.
+pub fn age_recipients(h: &Handle) -> Result<Vec<String>, TinkError> {
+    Ok(public_keys(h)?
+        .iter()
+        .map(|k| bech32::encode(AGE_RECIPIENT_HRP, k))
+        .collect())
+}
+
+/// Create a keyset [`Handle`] holding the public key in an `age1...` recipient string.
+///
+/// The key is added with a RAW output prefix, as age files carry no key identifier.
+pub fn import_age_recipient(recipient: &str) -> Result<Handle, TinkError> {
+    let (hrp, data) = bech32::decode(recipient.trim())?;
+    if hrp != AGE_RECIPIENT_HRP {
+        return Err("age: not an age recipient".into());
+    }
+    let key = tink_proto::AgeX25519PublicKey {
+        version: crate::AGE_X25519_PUBLIC_KEY_VERSION,
+        key_value: x25519_key(&data)?.to_vec(),
+    };
+    let mut serialized_key = Vec::new();
+    key.encode(&mut serialized_key)
+        .map_err(|e| wrap_err("age: failed to encode key", e))?;
+    let key_id = tink_core::subtle::random::get_random_uint32();
+    Handle::new_with_no_secrets(tink_proto::Keyset {
+        primary_key_id: key_id,
+        key: vec![tink_proto::keyset::Key {
+            key_data: Some(tink_proto::KeyData {
+                type_url: crate::AGE_X25519_PUBLIC_KEY_TYPE_URL.to_string(),
+                value: serialized_key,
+                key_material_type: tink_proto::key_data::KeyMaterialType::AsymmetricPublic as i32,
+            }),
+            status: tink_proto::KeyStatusType::Enabled as i32,
+            key_id,
+            output_prefix_type: tink_proto::OutputPrefixType::Raw as i32,
+        }],
+    })
+}
+
+/// Create a keyset [`Handle`] holding the private key in an `AGE-SECRET-KEY-1...` identity
+/// string (as written by `age-keygen`).  Comment lines (starting with `#`) and blank lines are
+/// ignored, so the contents of an identity file can be passed directly; the file must hold a
+/// single identity.
+///
+/// The crate must have been initialized with [`init`](crate::init) beforehand.
+pub fn import_age_identity(identity: &str) -> Result<Handle, TinkError> {
+    let mut lines = identity
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'));
+    let line = lines
+        .next()
+        .ok_or_else(|| TinkError::new("age: no identity found"))?;
+    if lines.next().is_some() {
+        return Err("age: only files with a single identity are supported".into());
+    }
+    let (hrp, data) = bech32::decode(line)?;
+    if hrp != AGE_IDENTITY_HRP {
+        return Err("age: not an age identity".into());
+    }
+    let secret = x25519_key(&data)?;
+    let mut ksm = Manager::new();
+    ksm.add_derived(
+        &crate::age_x25519_key_template(),
+        &mut std::io::Cursor::new(secret),
+        true,
+    )
+    .map_err(|e| wrap_err("age: failed to create key", e))?;
+    ksm.handle()
+}
+
+/// Return the `AGE-SECRET-KEY-1...` identity strings for the enabled age keys in the keyset,
+/// with the primary key first.
+///
+/// This exposes the secret key material, so is only available with the `insecure` feature.
+#[cfg(feature = "insecure")]
+#[cfg_attr(docsrs, doc(cfg(feature = "insecure")))]
+pub fn age_identities(h: &Handle) -> Result<Vec<String>, TinkError> {
+    let ks = tink_core::keyset::insecure::keyset_material(h);
+    let mut keys: Vec<_> = ks
+        .key
+        .iter()
+        .filter(|k| k.status == tink_proto::KeyStatusType::Enabled as i32)
+        .collect();
+    keys.sort_by_key(|k| k.key_id != ks.primary_key_id);
+    if keys.is_empty() {
+        return Err("age: no enabled keys".into());
+    }
+    keys.iter()
+        .map(|key| {
+            let key_data = key
+                .key_data
+                .as_ref()
+                .ok_or_else(|| TinkError::new("age: invalid keyset"))?;
+            if key_data.type_url != crate::AGE_X25519_PRIVATE_KEY_TYPE_URL {
+                return Err(format!("age: unsupported key type {}", key_data.type_url).into());
+            }
+            let key = tink_proto::AgeX25519PrivateKey::decode(key_data.value.as_ref())
+                .map_err(|e| wrap_err("age: invalid key", e))?;
+            let secret = x25519_key(&key.key_value)?;
+            Ok(bech32::encode(AGE_IDENTITY_HRP, &secret).to_ascii_uppercase())
+        })
+        .collect()
+}
+
+/// Return a [`HybridEncrypt`](tink_core::HybridEncrypt) primitive that produces age files
+/// decryptable by any of the enabled age keys in any of the given keysets (which may hold
+/// either private or public keys).  Unlike [`new_encrypt`](crate::new_encrypt), which only
+/// encrypts to the primary key of a single keyset, this wraps the file key once per recipient.
+pub fn new_multi_recipient_encrypt(
+    handles: &[&Handle],
+) -> Result<Box<dyn tink_core::HybridEncrypt>, TinkError> {
+    let mut recipients = Vec::new();
+    for h in handles {
+        recipients.extend(public_keys(h)?.into_iter().map(AgeRecipient::X25519));
+    }
+    Ok(Box::new(AgeEncrypt::new(recipients)?))
+}
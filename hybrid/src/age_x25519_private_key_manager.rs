@@ -0,0 +1,130 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Key manager for age X25519 private keys.
+
+use crate::subtle::{x25519_key, x25519_public_key, AgeDecrypt, AgeIdentity, X25519_KEY_SIZE};
+use tink_core::{utils::wrap_err, TinkError};
+use tink_proto::prost::Message;
+
+/// Maximal version of age X25519 private keys.
+pub const AGE_X25519_PRIVATE_KEY_VERSION: u32 = 0;
+/// Type URL of age X25519 private keys that Tink supports.
+pub const AGE_X25519_PRIVATE_KEY_TYPE_URL: &str =
+    "type.googleapis.com/tink_rust.AgeX25519PrivateKey";
+
+/// An implementation of the [`tink_core::registry::KeyManager`] trait.
+/// It generates new [`tink_proto::AgeX25519PrivateKey`] keys and produces new instances of
+/// [`crate::subtle::AgeDecrypt`].
+#[derive(Default)]
+pub(crate) struct AgeX25519PrivateKeyManager {}
+
+impl tink_core::registry::KeyManager for AgeX25519PrivateKeyManager {
+    fn primitive(&self, serialized_key: &[u8]) -> Result<tink_core::Primitive, TinkError> {
+        if serialized_key.is_empty() {
+            return Err("AgeX25519PrivateKeyManager: invalid key".into());
+        }
+        let key = tink_proto::AgeX25519PrivateKey::decode(serialized_key)
+            .map_err(|e| wrap_err("AgeX25519PrivateKeyManager: invalid key", e))?;
+        let secret = validate_key(&key).map_err(|e| wrap_err("AgeX25519PrivateKeyManager", e))?;
+        let p = AgeDecrypt::new(vec![AgeIdentity::X25519(secret)])?;
+        Ok(tink_core::Primitive::HybridDecrypt(Box::new(p)))
+    }
+
+    fn new_key(&self, _serialized_key_format: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let secret = tink_core::subtle::random::get_random_bytes(X25519_KEY_SIZE);
+        new_key_from_secret(&secret)
+    }
+
+    fn supports_derivation(&self) -> bool {
+        true
+    }
+
+    fn derive_key(
+        &self,
+        _serialized_key_format: &[u8],
+        pseudorandomness: &mut dyn std::io::Read,
+    ) -> Result<Vec<u8>, TinkError> {
+        let mut secret = [0u8; X25519_KEY_SIZE];
+        pseudorandomness
+            .read_exact(&mut secret)
+            .map_err(|e| wrap_err("AgeX25519PrivateKeyManager: not enough pseudorandomness", e))?;
+        new_key_from_secret(&secret)
+    }
+
+    fn type_url(&self) -> &'static str {
+        AGE_X25519_PRIVATE_KEY_TYPE_URL
+    }
+
+    fn key_material_type(&self) -> tink_proto::key_data::KeyMaterialType {
+        tink_proto::key_data::KeyMaterialType::AsymmetricPrivate
+    }
+
+    fn supports_private_keys(&self) -> bool {
+        true
+    }
+
+    fn public_key_data(
+        &self,
+        serialized_priv_key: &[u8],
+    ) -> Result<tink_proto::KeyData, TinkError> {
+        let priv_key = tink_proto::AgeX25519PrivateKey::decode(serialized_priv_key)
+            .map_err(|e| wrap_err("AgeX25519PrivateKeyManager: invalid private key", e))?;
+        let mut serialized_pub_key = Vec::new();
+        priv_key
+            .public_key
+            .ok_or_else(|| TinkError::new("AgeX25519PrivateKeyManager: no public key"))?
+            .encode(&mut serialized_pub_key)
+            .map_err(|e| wrap_err("AgeX25519PrivateKeyManager: invalid public key", e))?;
+        Ok(tink_proto::KeyData {
+            type_url: crate::AGE_X25519_PUBLIC_KEY_TYPE_URL.to_string(),
+            value: serialized_pub_key,
+            key_material_type: tink_proto::key_data::KeyMaterialType::AsymmetricPublic as i32,
+        })
+    }
+}
+
+/// Return a serialized [`tink_proto::AgeX25519PrivateKey`] for the given X25519 private key.
+fn new_key_from_secret(secret: &[u8]) -> Result<Vec<u8>, TinkError> {
+    let public = x25519_public_key(&x25519_key(secret)?);
+    let key = tink_proto::AgeX25519PrivateKey {
+        version: AGE_X25519_PRIVATE_KEY_VERSION,
+        key_value: secret.to_vec(),
+        public_key: Some(tink_proto::AgeX25519PublicKey {
+            version: crate::AGE_X25519_PUBLIC_KEY_VERSION,
+            key_value: public.to_vec(),
+        }),
+    };
+    let mut sk = Vec::new();
+    key.encode(&mut sk)
+        .map_err(|e| wrap_err("AgeX25519PrivateKeyManager: failed to encode new key", e))?;
+    Ok(sk)
+}
+
+/// Validate the given [`tink_proto::AgeX25519PrivateKey`] and return the private key.
+fn validate_key(key: &tink_proto::AgeX25519PrivateKey) -> Result<[u8; X25519_KEY_SIZE], TinkError> {
+    tink_core::keyset::validate_key_version(key.version, AGE_X25519_PRIVATE_KEY_VERSION)?;
+    let pub_key = key
+        .public_key
+        .as_ref()
+        .ok_or_else(|| TinkError::new("no public key"))?;
+    crate::validate_age_x25519_public_key(pub_key)?;
+    let secret = x25519_key(&key.key_value)?;
+    if x25519_public_key(&secret)[..] != pub_key.key_value[..] {
+        return Err("public key does not match private key".into());
+    }
+    Ok(secret)
+}
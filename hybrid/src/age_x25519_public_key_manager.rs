@@ -0,0 +1,65 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Key manager for age X25519 public keys.
+
+use crate::subtle::{x25519_key, AgeEncrypt, AgeRecipient, X25519_KEY_SIZE};
+use tink_core::{utils::wrap_err, TinkError};
+use tink_proto::prost::Message;
+
+/// Maximal version of age X25519 public keys.
+pub const AGE_X25519_PUBLIC_KEY_VERSION: u32 = 0;
+/// Type URL of age X25519 public keys that Tink supports.
+pub const AGE_X25519_PUBLIC_KEY_TYPE_URL: &str = "type.googleapis.com/tink_rust.AgeX25519PublicKey";
+
+/// An implementation of the [`tink_core::registry::KeyManager`] trait.
+/// It produces new instances of [`crate::subtle::AgeEncrypt`].
+#[derive(Default)]
+pub(crate) struct AgeX25519PublicKeyManager {}
+
+impl tink_core::registry::KeyManager for AgeX25519PublicKeyManager {
+    fn primitive(&self, serialized_key: &[u8]) -> Result<tink_core::Primitive, TinkError> {
+        if serialized_key.is_empty() {
+            return Err("AgeX25519PublicKeyManager: invalid key".into());
+        }
+        let key = tink_proto::AgeX25519PublicKey::decode(serialized_key)
+            .map_err(|e| wrap_err("AgeX25519PublicKeyManager: invalid key", e))?;
+        let public = validate_age_x25519_public_key(&key)
+            .map_err(|e| wrap_err("AgeX25519PublicKeyManager", e))?;
+        let p = AgeEncrypt::new(vec![AgeRecipient::X25519(public)])?;
+        Ok(tink_core::Primitive::HybridEncrypt(Box::new(p)))
+    }
+
+    fn new_key(&self, _serialized_key_format: &[u8]) -> Result<Vec<u8>, TinkError> {
+        Err("AgeX25519PublicKeyManager: new_key not implemented".into())
+    }
+
+    fn type_url(&self) -> &'static str {
+        AGE_X25519_PUBLIC_KEY_TYPE_URL
+    }
+
+    fn key_material_type(&self) -> tink_proto::key_data::KeyMaterialType {
+        tink_proto::key_data::KeyMaterialType::AsymmetricPublic
+    }
+}
+
+/// Validate the given [`tink_proto::AgeX25519PublicKey`] and return the public key.
+pub(crate) fn validate_age_x25519_public_key(
+    key: &tink_proto::AgeX25519PublicKey,
+) -> Result<[u8; X25519_KEY_SIZE], TinkError> {
+    tink_core::keyset::validate_key_version(key.version, AGE_X25519_PUBLIC_KEY_VERSION)?;
+    x25519_key(&key.key_value)
+}
@@ -0,0 +1,119 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Provides a reader that selects the key for streaming hybrid decryption by trial decryption.
+
+use std::io;
+use tink_core::utils::SharedCopyReader;
+
+/// Possible states for a [`DecryptReader`].
+enum State {
+    // Matching primitive not yet determined, raw ciphertext reader available.
+    Pending(Box<dyn io::Read>),
+    // Matching primitive that correctly decrypts has been found.
+    Found(Box<dyn io::Read>),
+    // No matching primitive available.
+    Failed,
+}
+
+/// A candidate primitive for decryption, together with the output prefix that the ciphertext
+/// needs to start with for the primitive to be applicable.
+pub(crate) struct Candidate {
+    pub(crate) primitive: Box<dyn tink_core::HybridDecrypt>,
+    pub(crate) prefix: Vec<u8>,
+}
+
+/// `DecryptReader` is a reader that tries each of a collection of candidate primitives in turn,
+/// until one of them successfully decrypts the start of the ciphertext.
+pub(crate) struct DecryptReader {
+    candidates: Vec<Candidate>,
+    context_info: Vec<u8>,
+    state: State,
+}
+
+impl DecryptReader {
+    pub(crate) fn new(
+        candidates: Vec<Candidate>,
+        reader: Box<dyn io::Read>,
+        context_info: &[u8],
+    ) -> Self {
+        Self {
+            candidates,
+            context_info: context_info.to_vec(),
+            state: State::Pending(reader),
+        }
+    }
+}
+
+impl io::Read for DecryptReader {
+    fn read(&mut self, p: &mut [u8]) -> io::Result<usize> {
+        match &mut self.state {
+            State::Found(reader) => return reader.read(p),
+            State::Failed => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "read previously failed",
+                ))
+            }
+            State::Pending(_) => {}
+        }
+        let state = std::mem::replace(&mut self.state, State::Failed);
+        let raw_reader = match state {
+            State::Pending(reader) => reader,
+            _ => unreachable!(), // safe: checked above
+        };
+        let mut copy_reader = SharedCopyReader::new(raw_reader);
+
+        for candidate in &self.candidates {
+            // Attempt a decrypting-read from a copy of the ciphertext reader, keeping a copy of
+            // the data read so that it can be re-scanned with a different key if decryption
+            // fails.
+            let mut prefix = vec![0; candidate.prefix.len()];
+            let mut cr = copy_reader.clone();
+            if io::Read::read_exact(&mut cr, &mut prefix).is_err() || prefix != candidate.prefix {
+                copy_reader.rewind();
+                continue;
+            }
+            let mut r = match candidate
+                .primitive
+                .new_decrypting_reader(Box::new(cr), &self.context_info)
+            {
+                Ok(r) => r,
+                Err(_) => {
+                    copy_reader.rewind();
+                    continue;
+                }
+            };
+            let n = match r.read(p) {
+                Ok(n) => n,
+                Err(_) => {
+                    copy_reader.rewind();
+                    continue;
+                }
+            };
+
+            // Reading has succeeded, so use this particular key from now on and no longer need
+            // to store copies of read data.
+            copy_reader.stop_copying();
+            self.state = State::Found(r);
+            return Ok(n);
+        }
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "hybrid::factory: decryption failed",
+        ))
+    }
+}
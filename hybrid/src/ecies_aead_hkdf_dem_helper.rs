@@ -21,10 +21,13 @@ use tink_proto::prost::Message;
 const AES_GCM_TYPE_URL: &str = "type.googleapis.com/google.crypto.tink.AesGcmKey";
 const AES_CTR_HMAC_AEAD_TYPE_URL: &str = "type.googleapis.com/google.crypto.tink.AesCtrHmacAeadKey";
 const AES_SIV_TYPE_URL: &str = "type.googleapis.com/google.crypto.tink.AesSivKey";
+const AES_GCM_HKDF_TYPE_URL: &str = "type.googleapis.com/google.crypto.tink.AesGcmHkdfStreamingKey";
+const AES_CTR_HMAC_STREAMING_TYPE_URL: &str =
+    "type.googleapis.com/google.crypto.tink.AesCtrHmacStreamingKey";
 
-/// Generator for [`tink_core::Aead`] or [`tink_core::DeterministicAead`] primitives for the
-/// specified [`tink_proto::KeyTemplate`] and key material. in order to implement the
-/// [`subtle::EciesAeadHkdfDemHelper`] interface.
+/// Generator for [`tink_core::Aead`], [`tink_core::DeterministicAead`] or
+/// [`tink_core::StreamingAead`] primitives for the specified [`tink_proto::KeyTemplate`] and key
+/// material. in order to implement the [`subtle::EciesAeadHkdfDemHelper`] interface.
 #[derive(Clone)]
 pub struct EciesAeadHkdfDemHelper {
     /// Protobuf key structure of the relevant type.  Note that the key material held in this key
@@ -41,6 +44,8 @@ enum AeadKey {
     AesGcm(tink_proto::AesGcmKey),
     AesCtrHmac(usize, tink_proto::AesCtrHmacAeadKey), // Also holds AES-CTR key size.
     AesSiv(tink_proto::AesSivKey),
+    AesGcmHkdf(tink_proto::AesGcmHkdfStreamingKey),
+    AesCtrHmacStreaming(tink_proto::AesCtrHmacStreamingKey),
 }
 
 impl AeadKey {
@@ -49,10 +54,26 @@ impl AeadKey {
             AeadKey::AesGcm(_) => AES_GCM_TYPE_URL,
             AeadKey::AesCtrHmac(_, _) => AES_CTR_HMAC_AEAD_TYPE_URL,
             AeadKey::AesSiv(_) => AES_SIV_TYPE_URL,
+            AeadKey::AesGcmHkdf(_) => AES_GCM_HKDF_TYPE_URL,
+            AeadKey::AesCtrHmacStreaming(_) => AES_CTR_HMAC_STREAMING_TYPE_URL,
         }
     }
 }
 
+/// Indicate whether keys with the given type URL can be used as a DEM.  This is the allowlist of
+/// DEMs that upstream Tink accepts in `EciesAeadDemParams` (AEAD and AES-SIV deterministic AEAD),
+/// together with the streaming AEADs supported by this crate.
+pub(crate) fn is_supported_dem(type_url: &str) -> bool {
+    matches!(
+        type_url,
+        AES_GCM_TYPE_URL
+            | AES_CTR_HMAC_AEAD_TYPE_URL
+            | AES_SIV_TYPE_URL
+            | AES_GCM_HKDF_TYPE_URL
+            | AES_CTR_HMAC_STREAMING_TYPE_URL
+    )
+}
+
 impl EciesAeadHkdfDemHelper {
     pub fn new(k: &tink_proto::KeyTemplate) -> Result<Self, TinkError> {
         let km = tink_core::registry::get_key_manager(&k.type_url)
@@ -81,8 +102,11 @@ impl EciesAeadHkdfDemHelper {
                 let aes_ctr_size = aes_ctr_key_format.key_size;
                 let aes_ctr_key = tink_proto::AesCtrHmacAeadKey::decode(&*key_data)
                     .map_err(|e| wrap_err("failed to decode key", e))?;
+                let symmetric_key_size = (aes_ctr_size as usize)
+                    .checked_add(hmac_key_format.key_size as usize)
+                    .ok_or_else(|| TinkError::new("invalid key format"))?;
                 (
-                    (aes_ctr_size + hmac_key_format.key_size) as usize,
+                    symmetric_key_size,
                     AeadKey::AesCtrHmac(aes_ctr_size as usize, aes_ctr_key),
                 )
             }
@@ -90,9 +114,31 @@ impl EciesAeadHkdfDemHelper {
                 let daead_key_format = tink_proto::AesSivKeyFormat::decode(&*k.value)
                     .map_err(|e| wrap_err("failed to decode key format", e))?;
                 let siv_key = tink_proto::AesSivKey::decode(&*key_data)
-                    .map_err(|e| wrap_err("failed to decode", e))?;
+                    .map_err(|e| wrap_err("failed to decode key", e))?;
                 (daead_key_format.key_size as usize, AeadKey::AesSiv(siv_key))
             }
+            AES_GCM_HKDF_TYPE_URL => {
+                let streaming_key_format =
+                    tink_proto::AesGcmHkdfStreamingKeyFormat::decode(&*k.value)
+                        .map_err(|e| wrap_err("failed to decode key format", e))?;
+                let streaming_key = tink_proto::AesGcmHkdfStreamingKey::decode(&*key_data)
+                    .map_err(|e| wrap_err("failed to decode key", e))?;
+                (
+                    streaming_key_format.key_size as usize,
+                    AeadKey::AesGcmHkdf(streaming_key),
+                )
+            }
+            AES_CTR_HMAC_STREAMING_TYPE_URL => {
+                let streaming_key_format =
+                    tink_proto::AesCtrHmacStreamingKeyFormat::decode(&*k.value)
+                        .map_err(|e| wrap_err("failed to decode key format", e))?;
+                let streaming_key = tink_proto::AesCtrHmacStreamingKey::decode(&*key_data)
+                    .map_err(|e| wrap_err("failed to decode key", e))?;
+                (
+                    streaming_key_format.key_size as usize,
+                    AeadKey::AesCtrHmacStreaming(streaming_key),
+                )
+            }
             _ => return Err(format!("unsupported AEAD DEM key type: {}", k.type_url).into()),
         };
 
@@ -101,6 +147,12 @@ impl EciesAeadHkdfDemHelper {
             symmetric_key_size,
         })
     }
+
+    /// Indicate whether the DEM is a deterministic AEAD, whose ciphertexts reveal equal
+    /// plaintexts encrypted under the same symmetric key.
+    pub(crate) fn is_deterministic(&self) -> bool {
+        matches!(self.key, AeadKey::AesSiv(_))
+    }
 }
 
 impl subtle::EciesAeadHkdfDemHelper for EciesAeadHkdfDemHelper {
@@ -145,10 +197,24 @@ impl subtle::EciesAeadHkdfDemHelper for EciesAeadHkdfDemHelper {
                     .encode(&mut sk)
                     .map_err(|e| wrap_err("failed to serialize key", e))?;
             }
+            AeadKey::AesGcmHkdf(mut streaming_key) => {
+                streaming_key.key_value = symmetric_key_value.to_vec();
+                streaming_key
+                    .encode(&mut sk)
+                    .map_err(|e| wrap_err("failed to serialize key", e))?;
+            }
+            AeadKey::AesCtrHmacStreaming(mut streaming_key) => {
+                streaming_key.key_value = symmetric_key_value.to_vec();
+                streaming_key
+                    .encode(&mut sk)
+                    .map_err(|e| wrap_err("failed to serialize key", e))?;
+            }
         }
         let p = tink_core::registry::primitive(self.key.type_url(), &sk)?;
         match p {
-            tink_core::Primitive::Aead(_) | tink_core::Primitive::DeterministicAead(_) => Ok(p),
+            tink_core::Primitive::Aead(_)
+            | tink_core::Primitive::DeterministicAead(_)
+            | tink_core::Primitive::StreamingAead(_) => Ok(p),
             _ => Err("Unexpected primitive type returned by the registry for the DEM".into()),
         }
     }
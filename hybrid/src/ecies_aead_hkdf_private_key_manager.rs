@@ -195,24 +195,28 @@ pub(crate) fn check_ecies_aead_hkdf_params(
         .as_ref()
         .ok_or_else(|| TinkError::new("no dem_params"))?;
 
-    let curve = EllipticCurveType::from_i32(kem_params.curve_type)
-        .unwrap_or(EllipticCurveType::UnknownCurve);
-
-    let hkdf_hash = match HashType::from_i32(kem_params.hkdf_hash_type) {
-        Some(HashType::UnknownHash) => return Err("unsupported HKDF hash".into()),
-        Some(h) => h,
-        None => return Err("unknown HKDF hash".into()),
+    let curve = kem_params
+        .try_curve_type()
+        .map_err(|e| wrap_err("unsupported curve", e))?;
+
+    let hkdf_hash = match kem_params.try_hkdf_hash_type() {
+        Ok(HashType::UnknownHash) => return Err("unsupported HKDF hash".into()),
+        Ok(h) => h,
+        Err(e) => return Err(wrap_err("unknown HKDF hash", e)),
     };
-    let ec_point_format = match EcPointFormat::from_i32(params.ec_point_format) {
-        Some(EcPointFormat::UnknownFormat) => return Err("unknown EC point format".into()),
-        Some(f) => f,
-        None => return Err("unknown EC point format".into()),
+    let ec_point_format = match params.try_ec_point_format() {
+        Ok(EcPointFormat::UnknownFormat) | Err(_) => return Err("unknown EC point format".into()),
+        Ok(f) => f,
     };
     let aead_dem = dem_params
         .aead_dem
         .as_ref()
         .ok_or_else(|| TinkError::new("no aead_dem"))?;
-    // Check that the relevant data encapsulation mechanism is supported in Tink.
+    // Check that the relevant data encapsulation mechanism is one that can be used as a DEM, and
+    // that it is supported in Tink.
+    if !crate::ecies_aead_hkdf_dem_helper::is_supported_dem(&aead_dem.type_url) {
+        return Err(format!("unsupported DEM key type: {}", aead_dem.type_url).into());
+    }
     let km = tink_core::registry::get_key_manager(&aead_dem.type_url)?;
     let _ = km.new_key_data(&aead_dem.value)?;
     Ok((ec_point_format, curve, hkdf_hash, kem_params, aead_dem))
@@ -16,6 +16,7 @@
 
 //! Key manager for ECIES-AEAD-HKDF public keys.
 
+use std::sync::Arc;
 use tink_core::{utils::wrap_err, TinkError};
 use tink_proto::{
     prost::Message, EcPointFormat, EciesHkdfKemParams, EllipticCurveType, HashType, KeyTemplate,
@@ -31,7 +32,29 @@ pub const ECIES_AEAD_HKDF_PUBLIC_KEY_TYPE_URL: &str =
 /// It generates new [`tink_proto::EciesAeadHkdfPublicKey`] keys and produces new instances of
 /// [`crate::subtle::EciesAeadHkdfHybridEncrypt`].
 #[derive(Default)]
-pub(crate) struct EciesAeadHkdfPublicKeyKeyManager {}
+pub(crate) struct EciesAeadHkdfPublicKeyKeyManager {
+    ephemeral_keys: Option<Arc<crate::subtle::EphemeralKeyPool>>,
+    kem_reuse: Option<u64>,
+}
+
+impl EciesAeadHkdfPublicKeyKeyManager {
+    /// Create a key manager whose primitives take ephemeral key pairs from the given pool.
+    pub(crate) fn new_with_ephemeral_key_pool(pool: Arc<crate::subtle::EphemeralKeyPool>) -> Self {
+        Self {
+            ephemeral_keys: Some(pool),
+            kem_reuse: None,
+        }
+    }
+
+    /// Create a key manager whose primitives reuse each ephemeral key exchange for up to
+    /// `max_messages` messages.
+    pub(crate) fn new_with_kem_reuse(max_messages: u64) -> Self {
+        Self {
+            ephemeral_keys: None,
+            kem_reuse: Some(max_messages),
+        }
+    }
+}
 
 impl tink_core::registry::KeyManager for EciesAeadHkdfPublicKeyKeyManager {
     fn primitive(&self, serialized_key: &[u8]) -> Result<tink_core::Primitive, TinkError> {
@@ -49,7 +72,19 @@ impl tink_core::registry::KeyManager for EciesAeadHkdfPublicKeyKeyManager {
         let salt = &kem_params.hkdf_salt;
         match crate::subtle::EciesAeadHkdfHybridEncrypt::new(&pub_key, salt, hash, pt_format, r_dem)
         {
-            Ok(p) => Ok(tink_core::Primitive::HybridEncrypt(Box::new(p))),
+            Ok(p) => {
+                let p = match &self.ephemeral_keys {
+                    Some(pool) => p.with_ephemeral_key_pool(pool.clone()),
+                    None => p,
+                };
+                let p = match self.kem_reuse {
+                    Some(max_messages) => p
+                        .with_kem_reuse(max_messages)
+                        .map_err(|e| wrap_err("EciesAeadHkdfPublicKeyKeyManager", e))?,
+                    None => p,
+                };
+                Ok(tink_core::Primitive::HybridEncrypt(Box::new(p)))
+            }
             Err(e) => Err(wrap_err("EciesAeadHkdfPublicKeyKeyManager: invalid key", e)),
         }
     }
@@ -0,0 +1,148 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Provide a hybrid decryption primitive that falls back to older keysets.
+
+use tink_core::{keyset::Handle, utils::wrap_err, KeyId, TinkError};
+
+/// `FallbackHybridDecrypt` decrypts by trying a primary keyset and then each of a list of
+/// fallback keysets in turn (for example, previous keyset generations kept in cold storage).
+/// This allows data to be migrated across keyset boundaries without retry loops in the
+/// application.
+///
+/// Keysets are identified by their index: 0 for the primary keyset, and `i + 1` for the `i`-th
+/// fallback keyset.
+/// [`decrypt_with_keyset_index`](FallbackHybridDecrypt::decrypt_with_keyset_index) reports which
+/// keyset decrypted a ciphertext.
+///
+/// Streaming decryption (via
+/// [`new_decrypting_reader`](tink_core::HybridDecrypt::new_decrypting_reader)) only uses the
+/// primary keyset: the fallback keysets are not tried for streams.
+pub struct FallbackHybridDecrypt {
+    primary: Box<dyn tink_core::HybridDecrypt>,
+    fallbacks: Vec<Box<dyn tink_core::HybridDecrypt>>,
+}
+
+/// Manual implementation of [`Clone`] relying on the trait bounds for
+/// primitives to provide `.box_clone()` methods.
+impl Clone for FallbackHybridDecrypt {
+    fn clone(&self) -> Self {
+        Self {
+            primary: self.primary.box_clone(),
+            fallbacks: self.fallbacks.iter().map(|d| d.box_clone()).collect(),
+        }
+    }
+}
+
+impl FallbackHybridDecrypt {
+    /// Create a [`FallbackHybridDecrypt`] from the given hybrid decryption primitives.
+    pub fn new(
+        primary: Box<dyn tink_core::HybridDecrypt>,
+        fallbacks: Vec<Box<dyn tink_core::HybridDecrypt>>,
+    ) -> FallbackHybridDecrypt {
+        FallbackHybridDecrypt { primary, fallbacks }
+    }
+
+    /// Create a [`FallbackHybridDecrypt`] from the hybrid decryption primitives of the given
+    /// private keyset handles.
+    pub fn from_handles(
+        primary: &Handle,
+        fallbacks: &[Handle],
+    ) -> Result<FallbackHybridDecrypt, TinkError> {
+        let primary = crate::new_decrypt(primary)
+            .map_err(|e| wrap_err("FallbackHybridDecrypt: invalid primary keyset", e))?;
+        let fallbacks = fallbacks
+            .iter()
+            .enumerate()
+            .map(|(i, h)| {
+                crate::new_decrypt(h).map_err(|e| {
+                    wrap_err(
+                        &format!("FallbackHybridDecrypt: invalid fallback keyset {}", i),
+                        e,
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, TinkError>>()?;
+        Ok(FallbackHybridDecrypt::new(primary, fallbacks))
+    }
+
+    /// Return the number of keysets, including the primary keyset.
+    pub fn keyset_count(&self) -> usize {
+        1 + self.fallbacks.len()
+    }
+
+    /// Decrypt `ciphertext` verifying the integrity of `context_info`, returning the plaintext
+    /// and the index of the keyset that decrypted it (0 for the primary keyset).
+    pub fn decrypt_with_keyset_index(
+        &self,
+        ciphertext: &[u8],
+        context_info: &[u8],
+    ) -> Result<(Vec<u8>, usize), TinkError> {
+        self.first_success(|d| d.decrypt(ciphertext, context_info))
+    }
+
+    fn first_success<F>(&self, f: F) -> Result<(Vec<u8>, usize), TinkError>
+    where
+        F: Fn(&dyn tink_core::HybridDecrypt) -> Result<Vec<u8>, TinkError>,
+    {
+        std::iter::once(&self.primary)
+            .chain(self.fallbacks.iter())
+            .enumerate()
+            .find_map(|(i, d)| f(d.as_ref()).ok().map(|pt| (pt, i)))
+            .ok_or_else(|| {
+                format!(
+                    "FallbackHybridDecrypt: decryption failed with all {} keysets",
+                    self.keyset_count()
+                )
+                .into()
+            })
+    }
+}
+
+impl tink_core::HybridDecrypt for FallbackHybridDecrypt {
+    fn decrypt(&self, ciphertext: &[u8], context_info: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.decrypt_with_keyset_index(ciphertext, context_info)
+            .map(|(pt, _)| pt)
+    }
+
+    /// Return a decrypting reader for the primary keyset.  The fallback keysets are not tried,
+    /// as that would require buffering the stream.
+    fn new_decrypting_reader(
+        &self,
+        r: Box<dyn std::io::Read>,
+        context_info: &[u8],
+    ) -> Result<Box<dyn std::io::Read>, TinkError> {
+        self.primary.new_decrypting_reader(r, context_info)
+    }
+
+    /// Decrypt with the key identified by `key_id` in whichever keyset holds it.
+    fn decrypt_with_key_id(
+        &self,
+        key_id: KeyId,
+        ciphertext: &[u8],
+        context_info: &[u8],
+    ) -> Result<Vec<u8>, TinkError> {
+        self.first_success(|d| d.decrypt_with_key_id(key_id, ciphertext, context_info))
+            .map(|(pt, _)| pt)
+    }
+
+    fn raw_key_ids(&self) -> Vec<KeyId> {
+        std::iter::once(&self.primary)
+            .chain(self.fallbacks.iter())
+            .flat_map(|d| d.raw_key_ids())
+            .collect()
+    }
+}
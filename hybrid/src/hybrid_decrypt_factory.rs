@@ -49,6 +49,9 @@ struct WrappedHybridDecrypt {
 
 impl WrappedHybridDecrypt {
     fn new(ps: tink_core::primitiveset::PrimitiveSet) -> Result<WrappedHybridDecrypt, TinkError> {
+        if !ps.usage.allows_decrypt() {
+            return Err("hybrid::factory: decryption not permitted".into());
+        }
         let entry = match &ps.primary {
             None => return Err("hybrid::factory: no primary primitive".into()),
             Some(p) => p,
@@ -73,6 +76,7 @@ impl WrappedHybridDecrypt {
 
 impl tink_core::HybridDecrypt for WrappedHybridDecrypt {
     fn decrypt(&self, ciphertext: &[u8], context_info: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let op = tink_core::trace::operation("hybrid", "decrypt", ciphertext.len());
         // try non-raw keys
         let prefix_size = tink_core::cryptofmt::NON_RAW_PREFIX_SIZE;
         if ciphertext.len() > prefix_size {
@@ -81,9 +85,11 @@ impl tink_core::HybridDecrypt for WrappedHybridDecrypt {
             if let Some(entries) = self.ps.entries_for_prefix(prefix) {
                 for entry in entries {
                     let result = entry.primitive.decrypt(ct_no_prefix, context_info);
-                    if result.is_ok() {
+                    if let Ok(pt) = &result {
+                        op.succeeded(entry.key_id, pt.len());
                         return result;
                     }
+                    op.key_failed(entry.key_id);
                 }
             }
         }
@@ -92,12 +98,80 @@ impl tink_core::HybridDecrypt for WrappedHybridDecrypt {
         if let Some(entries) = self.ps.raw_entries() {
             for entry in entries {
                 let result = entry.primitive.decrypt(ciphertext, context_info);
-                if result.is_ok() {
+                if let Ok(pt) = &result {
+                    op.succeeded(entry.key_id, pt.len());
                     return result;
                 }
+                op.key_failed(entry.key_id);
             }
         }
 
+        op.failed("no key could decrypt");
         Err("hybrid::factory: decryption failed".into())
     }
+
+    fn decrypt_with_key_id(
+        &self,
+        key_id: tink_core::KeyId,
+        ciphertext: &[u8],
+        context_info: &[u8],
+    ) -> Result<Vec<u8>, TinkError> {
+        let entry = self
+            .ps
+            .entry_for_key_id(key_id)
+            .ok_or_else(|| TinkError::new("hybrid::factory: unknown key ID"))?;
+        let ct = if entry.prefix.is_empty() {
+            ciphertext
+        } else if ciphertext.len() > entry.prefix.len() && ciphertext.starts_with(&entry.prefix) {
+            &ciphertext[entry.prefix.len()..]
+        } else {
+            return Err("hybrid::factory: ciphertext prefix does not match key".into());
+        };
+        entry
+            .primitive
+            .decrypt(ct, context_info)
+            .map_err(|e| wrap_err("hybrid::factory: decryption failed", e))
+    }
+
+    fn raw_key_ids(&self) -> Vec<tink_core::KeyId> {
+        self.ps
+            .raw_entries()
+            .map(|entries| entries.iter().map(|entry| entry.key_id).collect())
+            .unwrap_or_default()
+    }
+
+    /// Return a reader that decrypts a streaming hybrid ciphertext.  Keys whose output prefix
+    /// matches the start of the ciphertext are tried first, followed by raw keys; the first key
+    /// that successfully decrypts the start of the ciphertext is used for the remainder.
+    fn new_decrypting_reader(
+        &self,
+        r: Box<dyn std::io::Read>,
+        context_info: &[u8],
+    ) -> Result<Box<dyn std::io::Read>, TinkError> {
+        let mut candidates = Vec::new();
+        for (prefix, entries) in self.ps.entries.iter() {
+            if prefix.is_empty() {
+                continue;
+            }
+            for entry in entries {
+                candidates.push(crate::Candidate {
+                    primitive: entry.primitive.box_clone(),
+                    prefix: prefix.clone(),
+                });
+            }
+        }
+        if let Some(entries) = self.ps.raw_entries() {
+            for entry in entries {
+                candidates.push(crate::Candidate {
+                    primitive: entry.primitive.box_clone(),
+                    prefix: vec![],
+                });
+            }
+        }
+        Ok(Box::new(crate::DecryptReader::new(
+            candidates,
+            r,
+            context_info,
+        )))
+    }
 }
@@ -26,6 +26,43 @@ pub fn new_encrypt(
     new_encrypt_with_key_manager(h, None)
 }
 
+/// Return a [`tink_core::HybridEncrypt`] primitive from the given keyset handle, with ECIES keys
+/// taking their ephemeral key pairs from `pool` where possible rather than generating one for
+/// each message.  Keys of other types, and ECIES keys on a different curve from the pool, are
+/// unaffected.
+///
+/// See [`EphemeralKeyPool`](crate::subtle::EphemeralKeyPool) for the security implications of
+/// pooling ephemeral keys.
+pub fn new_encrypt_with_ephemeral_key_pool(
+    h: &tink_core::keyset::Handle,
+    pool: Arc<crate::subtle::EphemeralKeyPool>,
+) -> Result<Box<dyn tink_core::HybridEncrypt>, TinkError> {
+    new_encrypt_with_key_manager(
+        h,
+        Some(Arc::new(
+            crate::EciesAeadHkdfPublicKeyKeyManager::new_with_ephemeral_key_pool(pool),
+        )),
+    )
+}
+
+/// Return a [`tink_core::HybridEncrypt`] primitive from the given keyset handle, which reuses each
+/// ECIES ephemeral key exchange for up to `max_messages` messages.
+///
+/// This suits encrypting many messages to the same recipient; see
+/// [`EciesAeadHkdfHybridEncrypt::with_kem_reuse`](crate::subtle::EciesAeadHkdfHybridEncrypt::with_kem_reuse)
+/// for the security implications.
+pub fn new_encrypt_with_kem_reuse(
+    h: &tink_core::keyset::Handle,
+    max_messages: u64,
+) -> Result<Box<dyn tink_core::HybridEncrypt>, TinkError> {
+    new_encrypt_with_key_manager(
+        h,
+        Some(Arc::new(
+            crate::EciesAeadHkdfPublicKeyKeyManager::new_with_kem_reuse(max_messages),
+        )),
+    )
+}
+
 /// Return a [`tink_core::HybridEncrypt`] primitive from the given keyset handle and custom key
 /// manager.
 fn new_encrypt_with_key_manager(
@@ -49,6 +86,9 @@ struct WrappedHybridEncrypt {
 
 impl WrappedHybridEncrypt {
     fn new(ps: tink_core::primitiveset::PrimitiveSet) -> Result<WrappedHybridEncrypt, TinkError> {
+        if !ps.usage.allows_encrypt() {
+            return Err("hybrid::factory: encryption not permitted".into());
+        }
         let entry = match &ps.primary {
             None => return Err("hybrid::factory: no primary primitive".into()),
             Some(p) => p,
@@ -73,17 +113,47 @@ impl WrappedHybridEncrypt {
 
 impl tink_core::HybridEncrypt for WrappedHybridEncrypt {
     fn encrypt(&self, plaintext: &[u8], context_info: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let op = tink_core::trace::operation("hybrid", "encrypt", plaintext.len());
+        self.ps.check_primary_validity().map_err(|e| {
+            op.failed("primary key not valid");
+            e
+        })?;
+        self.ps.record_primary_usage(plaintext.len()).map_err(|e| {
+            op.failed("primary key usage limit reached");
+            e
+        })?;
         let primary = self
             .ps
             .primary
             .as_ref()
             .ok_or_else(|| TinkError::new("no primary"))?;
         let p = &primary.primitive;
-        let ct = p.encrypt(plaintext, context_info)?;
+        let ct = p.encrypt(plaintext, context_info).map_err(|e| {
+            op.failed("primitive encryption failed");
+            e
+        })?;
 
         let mut ret = Vec::with_capacity(primary.prefix.len() + ct.len());
         ret.extend_from_slice(&primary.prefix);
         ret.extend_from_slice(&ct);
+        op.succeeded(primary.key_id, ret.len());
         Ok(ret)
     }
+
+    fn new_encrypting_writer(
+        &self,
+        mut w: Box<dyn std::io::Write>,
+        context_info: &[u8],
+    ) -> Result<Box<dyn tink_core::EncryptingWrite>, TinkError> {
+        self.ps.check_primary_validity()?;
+        self.ps.record_primary_usage(0)?;
+        let primary = self
+            .ps
+            .primary
+            .as_ref()
+            .ok_or_else(|| TinkError::new("hybrid::factory: no primary"))?;
+        w.write_all(&primary.prefix)
+            .map_err(|e| wrap_err("hybrid::factory: write failed", e))?;
+        primary.primitive.new_encrypting_writer(w, context_info)
+    }
 }
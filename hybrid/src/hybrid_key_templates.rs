@@ -16,7 +16,10 @@
 
 //! This module contains pre-generated `KeyTemplate`s for `HybridEncrypt` keys
 /// One can use these templates to generate new Keysets.
-use tink_proto::{prost::Message, EcPointFormat, EllipticCurveType, HashType, KeyTemplate};
+use tink_core::TinkError;
+use tink_proto::{
+    prost::Message, EcPointFormat, EllipticCurveType, HashType, KeyTemplate, OutputPrefixType,
+};
 
 /// Return a [`KeyTemplate`] that generates an ECDH P-256 and decapsulation key AES128-GCM key with
 /// the following parameters:
@@ -56,6 +59,23 @@ pub fn ecies_hkdf_aes128_ctr_hmac_sha256_key_template() -> KeyTemplate {
     )
 }
 
+/// Return a [`KeyTemplate`] that generates an ECDH P-256 and decapsulation key for streaming
+/// hybrid encryption (via `new_encrypting_writer()`) with the following parameters:
+///  - KEM: ECDH over NIST P-256
+///  - DEM: AES128-GCM-HKDF streaming AEAD with 1MB segments
+///  - KDF: HKDF-HMAC-SHA256 with an empty salt
+#[cfg(feature = "streaming")]
+#[cfg_attr(docsrs, doc(cfg(feature = "streaming")))]
+pub fn ecies_hkdf_aes128_gcm_hkdf_1mb_key_template() -> KeyTemplate {
+    create_ecies_aead_hkdf_key_template(
+        EllipticCurveType::NistP256,
+        HashType::Sha256,
+        EcPointFormat::Uncompressed,
+        tink_streaming_aead::aes128_gcm_hkdf_1mb_key_template(),
+        &[],
+    )
+}
+
 /// Create a new ECIES-AEAD-HKDF key template with the given key size in bytes.
 fn create_ecies_aead_hkdf_key_template(
     ct: EllipticCurveType,
@@ -64,24 +84,132 @@ fn create_ecies_aead_hkdf_key_template(
     dek_t: KeyTemplate,
     salt: &[u8],
 ) -> KeyTemplate {
-    let format = tink_proto::EciesAeadHkdfKeyFormat {
-        params: Some(tink_proto::EciesAeadHkdfParams {
-            kem_params: Some(tink_proto::EciesHkdfKemParams {
-                curve_type: ct as i32,
-                hkdf_hash_type: ht as i32,
-                hkdf_salt: salt.to_vec(),
-            }),
-            dem_params: Some(tink_proto::EciesAeadDemParams {
-                aead_dem: Some(dek_t),
+    EciesAeadHkdfTemplateBuilder::new(dek_t)
+        .curve(ct)
+        .hkdf_hash(ht)
+        .point_format(ptfmt)
+        .hkdf_salt(salt)
+        .template()
+}
+
+/// Builder for ECIES-AEAD-HKDF [`KeyTemplate`]s, whose parameters are checked when the template
+/// is built.  Defaults to ECDH over NIST P-256 with uncompressed points, and HKDF-HMAC-SHA256 with
+/// an empty salt, with TINK output prefix.
+///
+/// The DEM template is only checked to be of a type supported as a DEM; build it with the
+/// corresponding builder (e.g. `tink_aead::AesGcmTemplateBuilder`) to check its parameters.
+#[derive(Clone, Debug)]
+pub struct EciesAeadHkdfTemplateBuilder {
+    curve: EllipticCurveType,
+    hkdf_hash: HashType,
+    hkdf_salt: Vec<u8>,
+    point_format: EcPointFormat,
+    dem: KeyTemplate,
+    prefix: OutputPrefixType,
+}
+
+impl EciesAeadHkdfTemplateBuilder {
+    /// Create a builder with the given DEM key template and default parameters.
+    pub fn new(dem: KeyTemplate) -> Self {
+        Self {
+            curve: EllipticCurveType::NistP256,
+            hkdf_hash: HashType::Sha256,
+            hkdf_salt: Vec::new(),
+            point_format: EcPointFormat::Uncompressed,
+            dem,
+            prefix: OutputPrefixType::Tink,
+        }
+    }
+
+    /// Set the elliptic curve used for the KEM (only NIST P-256 is supported).
+    pub fn curve(mut self, curve: EllipticCurveType) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    /// Set the hash function used by HKDF.
+    pub fn hkdf_hash(mut self, hkdf_hash: HashType) -> Self {
+        self.hkdf_hash = hkdf_hash;
+        self
+    }
+
+    /// Set the HKDF salt.
+    pub fn hkdf_salt(mut self, hkdf_salt: &[u8]) -> Self {
+        self.hkdf_salt = hkdf_salt.to_vec();
+        self
+    }
+
+    /// Set the format used to encode the ephemeral public key.
+    pub fn point_format(mut self, point_format: EcPointFormat) -> Self {
+        self.point_format = point_format;
+        self
+    }
+
+    /// Set the output prefix type.
+    pub fn prefix(mut self, prefix: OutputPrefixType) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    /// Build the [`KeyTemplate`], or return an error if the parameters are invalid.
+    pub fn build(&self) -> Result<KeyTemplate, TinkError> {
+        if self.curve != EllipticCurveType::NistP256 {
+            return Err(format!("unsupported curve {:?}", self.curve).into());
+        }
+        if tink_core::subtle::get_hash_func(self.hkdf_hash).is_none() {
+            return Err(format!("unsupported HKDF hash {:?}", self.hkdf_hash).into());
+        }
+        if self.point_format == EcPointFormat::UnknownFormat {
+            return Err("unknown EC point format".into());
+        }
+        if !crate::ecies_aead_hkdf_dem_helper::is_supported_dem(&self.dem.type_url) {
+            return Err(format!("unsupported DEM {}", self.dem.type_url).into());
+        }
+        if self.prefix == OutputPrefixType::UnknownPrefix {
+            return Err("invalid output prefix type".into());
+        }
+        Ok(self.template())
+    }
+
+    /// Serialize the parameters into a [`KeyTemplate`] without checking them.
+    fn template(&self) -> KeyTemplate {
+        let format = tink_proto::EciesAeadHkdfKeyFormat {
+            params: Some(tink_proto::EciesAeadHkdfParams {
+                kem_params: Some(tink_proto::EciesHkdfKemParams {
+                    curve_type: self.curve as i32,
+                    hkdf_hash_type: self.hkdf_hash as i32,
+                    hkdf_salt: self.hkdf_salt.clone(),
+                }),
+                dem_params: Some(tink_proto::EciesAeadDemParams {
+                    aead_dem: Some(self.dem.clone()),
+                }),
+                ec_point_format: self.point_format as i32,
             }),
-            ec_point_format: ptfmt as i32,
-        }),
+        };
+        let mut serialized_format = Vec::new();
+        format.encode(&mut serialized_format).unwrap(); // safe: proto-encode
+        KeyTemplate {
+            type_url: crate::ECIES_AEAD_HKDF_PRIVATE_KEY_TYPE_URL.to_string(),
+            value: serialized_format,
+            output_prefix_type: self.prefix as i32,
+        }
+    }
+}
+
+/// Return a [`KeyTemplate`] that generates an X25519 key for encryption in the
+/// [age](https://age-encryption.org/v1) file format, so that the ciphertext can be decrypted by
+/// the `age` command-line tool.  The output prefix is RAW, as age files carry no key identifier.
+#[cfg(feature = "age")]
+#[cfg_attr(docsrs, doc(cfg(feature = "age")))]
+pub fn age_x25519_key_template() -> KeyTemplate {
+    let format = tink_proto::AgeX25519KeyFormat {
+        version: crate::AGE_X25519_PRIVATE_KEY_VERSION,
     };
     let mut serialized_format = Vec::new();
     format.encode(&mut serialized_format).unwrap(); // safe: proto-encode
     KeyTemplate {
-        type_url: crate::ECIES_AEAD_HKDF_PRIVATE_KEY_TYPE_URL.to_string(),
+        type_url: crate::AGE_X25519_PRIVATE_KEY_TYPE_URL.to_string(),
         value: serialized_format,
-        output_prefix_type: tink_proto::OutputPrefixType::Tink as i32,
+        output_prefix_type: tink_proto::OutputPrefixType::Raw as i32,
     }
 }
@@ -33,18 +33,38 @@
 use std::sync::Once;
 use tink_core::registry::{register_key_manager, register_template_generator};
 
+#[cfg(feature = "age")]
+#[cfg_attr(docsrs, doc(cfg(feature = "age")))]
+pub mod age;
+#[cfg(feature = "age")]
+mod age_x25519_private_key_manager;
+#[cfg(feature = "age")]
+pub use age_x25519_private_key_manager::*;
+#[cfg(feature = "age")]
+mod age_x25519_public_key_manager;
+#[cfg(feature = "age")]
+pub use age_x25519_public_key_manager::*;
+mod decrypt_reader;
+use decrypt_reader::*;
 mod ecies_aead_hkdf_dem_helper;
 pub use ecies_aead_hkdf_dem_helper::*;
 mod ecies_aead_hkdf_private_key_manager;
 pub use ecies_aead_hkdf_private_key_manager::*;
 mod ecies_aead_hkdf_public_key_manager;
 pub use ecies_aead_hkdf_public_key_manager::*;
+mod fallback_decrypt;
+pub use fallback_decrypt::*;
 mod hybrid_decrypt_factory;
 pub use hybrid_decrypt_factory::*;
 mod hybrid_encrypt_factory;
 pub use hybrid_encrypt_factory::*;
 mod hybrid_key_templates;
 pub use hybrid_key_templates::*;
+#[cfg(feature = "aead")]
+#[cfg_attr(docsrs, doc(cfg(feature = "aead")))]
+mod multi_recipient;
+#[cfg(feature = "aead")]
+pub use multi_recipient::*;
 
 pub mod subtle;
 
@@ -61,6 +81,8 @@ pub fn init() {
     tink_aead::init();
     #[cfg(feature = "daead")]
     tink_daead::init();
+    #[cfg(feature = "streaming")]
+    tink_streaming_aead::init();
     INIT.call_once(|| {
         register_key_manager(std::sync::Arc::new(
             EciesAeadHkdfPrivateKeyKeyManager::default(),
@@ -79,5 +101,19 @@ pub fn init() {
             "ECIES_P256_HKDF_HMAC_SHA256_AES128_CTR_HMAC_SHA256",
             ecies_hkdf_aes128_ctr_hmac_sha256_key_template,
         );
+        #[cfg(feature = "streaming")]
+        register_template_generator(
+            "ECIES_P256_HKDF_HMAC_SHA256_AES128_GCM_HKDF_1MB",
+            ecies_hkdf_aes128_gcm_hkdf_1mb_key_template,
+        );
+
+        #[cfg(feature = "age")]
+        {
+            register_key_manager(std::sync::Arc::new(AgeX25519PrivateKeyManager::default()))
+                .expect("tink_hybrid::init() failed"); // safe: init
+            register_key_manager(std::sync::Arc::new(AgeX25519PublicKeyManager::default()))
+                .expect("tink_hybrid::init() failed"); // safe: init
+            register_template_generator("AGE_X25519", age_x25519_key_template);
+        }
     });
 }
@@ -0,0 +1,150 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Encryption of a payload for multiple recipients.
+//!
+//! The payload is encrypted once with AES-256-GCM under a random data encryption key (DEK), and
+//! the DEK is hybrid-encrypted separately for each recipient.  The container format is:
+//!  - version byte ([`MULTI_RECIPIENT_VERSION`])
+//!  - number of recipients (4 bytes, big-endian)
+//!  - for each recipient: length of the wrapped DEK (4 bytes, big-endian), then the wrapped DEK
+//!  - the encrypted payload
+//!
+//! The header (everything before the encrypted payload) and the context info are authenticated
+//! as associated data of the payload, so the recipient list cannot be modified undetected.
+
+use std::convert::TryInto;
+use tink_core::{keyset::Handle, utils::wrap_err, Aead, HybridDecrypt, HybridEncrypt, TinkError};
+
+/// Version byte at the start of a multi-recipient ciphertext.
+pub const MULTI_RECIPIENT_VERSION: u8 = 1;
+/// Size of the data encryption key, for AES-256-GCM.
+const DEK_SIZE: usize = 32;
+
+/// `MultiRecipientEncrypt` encrypts a payload so that it can be decrypted by any one of a set of
+/// recipients, each identified by a public hybrid encryption keyset.
+pub struct MultiRecipientEncrypt {
+    recipients: Vec<Box<dyn HybridEncrypt>>,
+}
+
+impl MultiRecipientEncrypt {
+    /// Create an encrypter for the recipients with the given public keyset handles.
+    pub fn new(recipients: &[Handle]) -> Result<MultiRecipientEncrypt, TinkError> {
+        if recipients.is_empty() {
+            return Err("MultiRecipientEncrypt: no recipients".into());
+        }
+        let recipients = recipients
+            .iter()
+            .map(crate::new_encrypt)
+            .collect::<Result<Vec<_>, TinkError>>()
+            .map_err(|e| wrap_err("MultiRecipientEncrypt: invalid recipient keyset", e))?;
+        Ok(MultiRecipientEncrypt { recipients })
+    }
+
+    /// Encrypt `plaintext` for all recipients, with `context_info` authenticated in the same way
+    /// as for [`HybridEncrypt::encrypt`].
+    pub fn encrypt(&self, plaintext: &[u8], context_info: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let dek = tink_core::subtle::random::get_random_bytes(DEK_SIZE);
+        let count: u32 = self
+            .recipients
+            .len()
+            .try_into()
+            .map_err(|_| TinkError::new("MultiRecipientEncrypt: too many recipients"))?;
+        let mut out = vec![MULTI_RECIPIENT_VERSION];
+        out.extend_from_slice(&count.to_be_bytes());
+        for recipient in &self.recipients {
+            let wrapped = recipient
+                .encrypt(&dek, context_info)
+                .map_err(|e| wrap_err("MultiRecipientEncrypt: cannot wrap key", e))?;
+            let wrapped_len: u32 = wrapped
+                .len()
+                .try_into()
+                .map_err(|_| TinkError::new("MultiRecipientEncrypt: wrapped key too long"))?;
+            out.extend_from_slice(&wrapped_len.to_be_bytes());
+            out.extend_from_slice(&wrapped);
+        }
+        let dem = tink_aead::subtle::AesGcm::new(&dek)?;
+        let ct = dem.encrypt(plaintext, &payload_ad(&out, context_info))?;
+        out.extend_from_slice(&ct);
+        Ok(out)
+    }
+}
+
+/// `MultiRecipientDecrypt` decrypts payloads produced by [`MultiRecipientEncrypt`], as one of the
+/// recipients.
+pub struct MultiRecipientDecrypt {
+    decrypter: Box<dyn HybridDecrypt>,
+}
+
+impl MultiRecipientDecrypt {
+    /// Create a decrypter for the recipient with the given private keyset handle.
+    pub fn new(h: &Handle) -> Result<MultiRecipientDecrypt, TinkError> {
+        Ok(MultiRecipientDecrypt {
+            decrypter: crate::new_decrypt(h)
+                .map_err(|e| wrap_err("MultiRecipientDecrypt: invalid keyset", e))?,
+        })
+    }
+
+    /// Decrypt `ciphertext`, which must have been encrypted with the same `context_info` for a
+    /// set of recipients that includes this one.
+    pub fn decrypt(&self, ciphertext: &[u8], context_info: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let (wrapped_keys, header_len) = parse_header(ciphertext)?;
+        let dek = wrapped_keys
+            .iter()
+            .find_map(|wrapped| self.decrypter.decrypt(wrapped, context_info).ok())
+            .filter(|dek| dek.len() == DEK_SIZE)
+            .ok_or_else(|| TinkError::new("MultiRecipientDecrypt: not a recipient"))?;
+        let (header, ct) = ciphertext.split_at(header_len);
+        let dem = tink_aead::subtle::AesGcm::new(&dek)?;
+        dem.decrypt(ct, &payload_ad(header, context_info))
+            .map_err(|e| wrap_err("MultiRecipientDecrypt: decryption failed", e))
+    }
+}
+
+/// Return the associated data for the payload encryption.
+fn payload_ad(header: &[u8], context_info: &[u8]) -> Vec<u8> {
+    let mut ad = Vec::with_capacity(8 + context_info.len() + header.len());
+    ad.extend_from_slice(&(context_info.len() as u64).to_be_bytes());
+    ad.extend_from_slice(context_info);
+    ad.extend_from_slice(header);
+    ad
+}
+
+/// Parse the header of a multi-recipient ciphertext, returning the wrapped keys and the length
+/// of the header.
+fn parse_header(ciphertext: &[u8]) -> Result<(Vec<&[u8]>, usize), TinkError> {
+    let too_short = || TinkError::new("MultiRecipientDecrypt: ciphertext too short");
+    match ciphertext.first() {
+        None => return Err(too_short()),
+        Some(&MULTI_RECIPIENT_VERSION) => {}
+        Some(_) => return Err("MultiRecipientDecrypt: unknown version".into()),
+    }
+    let read_u32 = |pos: usize| -> Result<usize, TinkError> {
+        let bytes = ciphertext.get(pos..pos + 4).ok_or_else(too_short)?;
+        Ok(u32::from_be_bytes(bytes.try_into().unwrap()) as usize) // safe: length checked
+    };
+    let count = read_u32(1)?;
+    let mut pos = 5;
+    let mut wrapped_keys = Vec::new();
+    for _ in 0..count {
+        let len = read_u32(pos)?;
+        pos += 4;
+        let end = pos.checked_add(len).ok_or_else(too_short)?;
+        wrapped_keys.push(ciphertext.get(pos..end).ok_or_else(too_short)?);
+        pos = end;
+    }
+    Ok((wrapped_keys, pos))
+}
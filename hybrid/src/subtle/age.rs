@@ -0,0 +1,334 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Hybrid encryption in the [age](https://age-encryption.org/v1) file format.
+
+use super::age_format::{
+    aead_unwrap, aead_wrap, b64_decode, b64_encode, encode_header, encrypt_payload, read_header,
+    PayloadReader, PayloadWriter, Stanza, AGE_FILE_KEY_SIZE,
+};
+use curve25519_dalek::{constants::X25519_BASEPOINT, montgomery::MontgomeryPoint, scalar::Scalar};
+use std::io::{Cursor, Read, Write};
+use tink_core::{subtle::compute_hkdf, utils::wrap_err, TinkError};
+use tink_proto::HashType;
+
+/// Size of X25519 private and public keys.
+pub const X25519_KEY_SIZE: usize = 32;
+/// Default scrypt work factor (log2 of N) used when encrypting to a passphrase.
+pub const AGE_SCRYPT_DEFAULT_LOG_N: u8 = 18;
+/// Default maximum scrypt work factor accepted when decrypting with a passphrase.
+pub const AGE_SCRYPT_DEFAULT_MAX_LOG_N: u8 = 22;
+
+const X25519_TAG: &str = "X25519";
+const X25519_INFO: &[u8] = b"age-encryption.org/v1/X25519";
+const SCRYPT_TAG: &str = "scrypt";
+const SCRYPT_SALT_LABEL: &[u8] = b"age-encryption.org/v1/scrypt";
+const SCRYPT_SALT_SIZE: usize = 16;
+
+/// A recipient that the file key of an age file is wrapped for.
+#[derive(Clone)]
+pub enum AgeRecipient {
+    /// An X25519 public key.
+    X25519([u8; X25519_KEY_SIZE]),
+    /// A passphrase, stretched with scrypt using the given work factor (log2 of N).
+    Scrypt { passphrase: Vec<u8>, log_n: u8 },
+}
+
+/// An identity that can unwrap the file key of an age file.
+#[derive(Clone)]
+pub enum AgeIdentity {
+    /// An X25519 private key.
+    X25519([u8; X25519_KEY_SIZE]),
+    /// A passphrase, accepting scrypt work factors up to `max_log_n`.
+    Scrypt { passphrase: Vec<u8>, max_log_n: u8 },
+}
+
+/// Return the X25519 function of the given scalar and point, as per RFC 7748.
+pub(crate) fn x25519(scalar: &[u8; X25519_KEY_SIZE], point: &[u8; X25519_KEY_SIZE]) -> [u8; 32] {
+    let mut k = *scalar;
+    k[0] &= 248;
+    k[31] &= 127;
+    k[31] |= 64;
+    (MontgomeryPoint(*point) * Scalar::from_bits(k)).to_bytes()
+}
+
+/// Return the X25519 public key for the given private key.
+pub fn x25519_public_key(secret: &[u8; X25519_KEY_SIZE]) -> [u8; X25519_KEY_SIZE] {
+    x25519(secret, &X25519_BASEPOINT.to_bytes())
+}
+
+/// Convert a slice into an X25519 key, checking its length.
+pub(crate) fn x25519_key(data: &[u8]) -> Result<[u8; X25519_KEY_SIZE], TinkError> {
+    if data.len() != X25519_KEY_SIZE {
+        return Err("age: invalid X25519 key length".into());
+    }
+    let mut key = [0u8; X25519_KEY_SIZE];
+    key.copy_from_slice(data);
+    Ok(key)
+}
+
+/// Derive the key that wraps the file key for an X25519 recipient, from the shared secret of
+/// `secret` and `peer` (one of which is the ephemeral key whose public part is `share`).
+fn x25519_wrap_key(
+    secret: &[u8; X25519_KEY_SIZE],
+    peer: &[u8; X25519_KEY_SIZE],
+    share: &[u8; X25519_KEY_SIZE],
+    recipient: &[u8; X25519_KEY_SIZE],
+) -> Result<Vec<u8>, TinkError> {
+    let shared = x25519(secret, peer);
+    if shared.iter().all(|b| *b == 0) {
+        return Err("age: low order X25519 point".into());
+    }
+    let mut salt = Vec::with_capacity(2 * X25519_KEY_SIZE);
+    salt.extend_from_slice(share);
+    salt.extend_from_slice(recipient);
+    compute_hkdf(HashType::Sha256, &shared, &salt, X25519_INFO, 32)
+}
+
+/// Derive the key that wraps the file key for a passphrase recipient.
+fn scrypt_wrap_key(passphrase: &[u8], salt: &[u8], log_n: u8) -> Result<Vec<u8>, TinkError> {
+    let mut full_salt = SCRYPT_SALT_LABEL.to_vec();
+    full_salt.extend_from_slice(salt);
+    let params =
+        scrypt::Params::new(log_n, 8, 1).map_err(|e| wrap_err("age: invalid scrypt params", e))?;
+    let mut key = vec![0u8; 32];
+    scrypt::scrypt(passphrase, &full_salt, &params, &mut key)
+        .map_err(|e| wrap_err("age: scrypt failed", e))?;
+    Ok(key)
+}
+
+impl AgeRecipient {
+    /// Return a stanza that wraps `file_key` for this recipient.
+    fn wrap(&self, file_key: &[u8]) -> Result<Stanza, TinkError> {
+        match self {
+            AgeRecipient::X25519(public) => {
+                let ephemeral = x25519_key(&tink_core::subtle::random::get_random_bytes(
+                    X25519_KEY_SIZE,
+                ))?;
+                let share = x25519_public_key(&ephemeral);
+                let key = x25519_wrap_key(&ephemeral, public, &share, public)?;
+                Ok(Stanza {
+                    tag: X25519_TAG.to_string(),
+                    args: vec![b64_encode(&share)],
+                    body: aead_wrap(&key, file_key)?,
+                })
+            }
+            AgeRecipient::Scrypt { passphrase, log_n } => {
+                let salt = tink_core::subtle::random::get_random_bytes(SCRYPT_SALT_SIZE);
+                let key = scrypt_wrap_key(passphrase, &salt, *log_n)?;
+                Ok(Stanza {
+                    tag: SCRYPT_TAG.to_string(),
+                    args: vec![b64_encode(&salt), log_n.to_string()],
+                    body: aead_wrap(&key, file_key)?,
+                })
+            }
+        }
+    }
+}
+
+impl AgeIdentity {
+    /// Attempt to unwrap the file key from `stanza`, returning `None` if the stanza is not
+    /// addressed to this identity.
+    fn unwrap(&self, stanza: &Stanza) -> Result<Option<Vec<u8>>, TinkError> {
+        match self {
+            AgeIdentity::X25519(secret) => {
+                if stanza.tag != X25519_TAG {
+                    return Ok(None);
+                }
+                if stanza.args.len() != 1 {
+                    return Err("age: invalid X25519 stanza".into());
+                }
+                let share = x25519_key(&b64_decode(&stanza.args[0])?)?;
+                let key = x25519_wrap_key(secret, &share, &share, &x25519_public_key(secret))?;
+                aead_unwrap(&key, &stanza.body)
+            }
+            AgeIdentity::Scrypt {
+                passphrase,
+                max_log_n,
+            } => {
+                if stanza.tag != SCRYPT_TAG {
+                    return Ok(None);
+                }
+                if stanza.args.len() != 2 {
+                    return Err("age: invalid scrypt stanza".into());
+                }
+                let salt = b64_decode(&stanza.args[0])?;
+                if salt.len() != SCRYPT_SALT_SIZE {
+                    return Err("age: invalid scrypt salt".into());
+                }
+                let log_n_str = &stanza.args[1];
+                if log_n_str.starts_with('0') || !log_n_str.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err("age: invalid scrypt work factor".into());
+                }
+                let log_n: u8 = log_n_str
+                    .parse()
+                    .map_err(|e| wrap_err("age: invalid scrypt work factor", e))?;
+                if log_n > *max_log_n {
+                    return Err("age: scrypt work factor too large".into());
+                }
+                let key = scrypt_wrap_key(passphrase, &salt, log_n)?;
+                aead_unwrap(&key, &stanza.body)
+            }
+        }
+    }
+}
+
+/// Check that the (empty) context info is acceptable; the age format has nowhere to bind it.
+fn check_context_info(context_info: &[u8]) -> Result<(), TinkError> {
+    if !context_info.is_empty() {
+        return Err("age: context_info not supported".into());
+    }
+    Ok(())
+}
+
+/// `AgeEncrypt` is an implementation of [`tink_core::HybridEncrypt`] that produces files in the
+/// age format, readable by the `age` command-line tool.
+#[derive(Clone)]
+pub struct AgeEncrypt {
+    recipients: Vec<AgeRecipient>,
+}
+
+impl AgeEncrypt {
+    /// Create an encrypter for the given recipients.  A passphrase recipient must be the only
+    /// recipient.
+    pub fn new(recipients: Vec<AgeRecipient>) -> Result<Self, TinkError> {
+        if recipients.is_empty() {
+            return Err("age: no recipients".into());
+        }
+        if recipients.len() > 1
+            && recipients
+                .iter()
+                .any(|r| matches!(r, AgeRecipient::Scrypt { .. }))
+        {
+            return Err("age: a passphrase recipient must be the only recipient".into());
+        }
+        Ok(AgeEncrypt { recipients })
+    }
+
+    /// Create an encrypter for a single passphrase, with the default scrypt work factor.
+    pub fn with_passphrase(passphrase: &[u8]) -> Self {
+        AgeEncrypt {
+            recipients: vec![AgeRecipient::Scrypt {
+                passphrase: passphrase.to_vec(),
+                log_n: AGE_SCRYPT_DEFAULT_LOG_N,
+            }],
+        }
+    }
+
+    /// Generate a file key and return it with the corresponding header.
+    fn header(&self) -> Result<(Vec<u8>, Vec<u8>), TinkError> {
+        let file_key = tink_core::subtle::random::get_random_bytes(AGE_FILE_KEY_SIZE);
+        let stanzas = self
+            .recipients
+            .iter()
+            .map(|r| r.wrap(&file_key))
+            .collect::<Result<Vec<_>, _>>()?;
+        let header = encode_header(&file_key, &stanzas)?;
+        Ok((file_key, header))
+    }
+}
+
+impl tink_core::HybridEncrypt for AgeEncrypt {
+    /// Encrypt `plaintext` into a complete age file.  The `context_info` must be empty.
+    fn encrypt(&self, plaintext: &[u8], context_info: &[u8]) -> Result<Vec<u8>, TinkError> {
+        check_context_info(context_info)?;
+        let (file_key, mut ct) = self.header()?;
+        ct.extend_from_slice(&encrypt_payload(&file_key, plaintext)?);
+        Ok(ct)
+    }
+
+    fn new_encrypting_writer(
+        &self,
+        mut w: Box<dyn Write>,
+        context_info: &[u8],
+    ) -> Result<Box<dyn tink_core::EncryptingWrite>, TinkError> {
+        check_context_info(context_info)?;
+        let (file_key, header) = self.header()?;
+        w.write_all(&header)
+            .map_err(|e| wrap_err("age: write failed", e))?;
+        Ok(Box::new(PayloadWriter::new(w, &file_key)?))
+    }
+}
+
+/// `AgeDecrypt` is an implementation of [`tink_core::HybridDecrypt`] that decrypts files in the
+/// age format, such as those produced by the `age` command-line tool.
+#[derive(Clone)]
+pub struct AgeDecrypt {
+    identities: Vec<AgeIdentity>,
+}
+
+impl AgeDecrypt {
+    /// Create a decrypter that tries each of the given identities.
+    pub fn new(identities: Vec<AgeIdentity>) -> Result<Self, TinkError> {
+        if identities.is_empty() {
+            return Err("age: no identities".into());
+        }
+        Ok(AgeDecrypt { identities })
+    }
+
+    /// Create a decrypter for a single passphrase, accepting the default maximum scrypt work
+    /// factor.
+    pub fn with_passphrase(passphrase: &[u8]) -> Self {
+        AgeDecrypt {
+            identities: vec![AgeIdentity::Scrypt {
+                passphrase: passphrase.to_vec(),
+                max_log_n: AGE_SCRYPT_DEFAULT_MAX_LOG_N,
+            }],
+        }
+    }
+
+    /// Read the header from `r` and return the file key it wraps for one of the identities.
+    fn file_key(&self, r: &mut dyn Read) -> Result<Vec<u8>, TinkError> {
+        let header = read_header(r)?;
+        if header.stanzas.len() > 1 && header.stanzas.iter().any(|s| s.tag == SCRYPT_TAG) {
+            return Err("age: scrypt stanza must be the only stanza".into());
+        }
+        for stanza in &header.stanzas {
+            for identity in &self.identities {
+                if let Some(file_key) = identity.unwrap(stanza)? {
+                    header.verify_mac(&file_key)?;
+                    return Ok(file_key);
+                }
+            }
+        }
+        Err("age: no identity matches any recipient".into())
+    }
+}
+
+impl tink_core::HybridDecrypt for AgeDecrypt {
+    /// Decrypt a complete age file.  The `context_info` must be empty.
+    fn decrypt(&self, ciphertext: &[u8], context_info: &[u8]) -> Result<Vec<u8>, TinkError> {
+        check_context_info(context_info)?;
+        let mut header_reader = Cursor::new(ciphertext);
+        let file_key = self.file_key(&mut header_reader)?;
+        let payload = ciphertext[header_reader.position() as usize..].to_vec();
+        let mut r = PayloadReader::new(Box::new(Cursor::new(payload)), &file_key)?;
+        let mut pt = Vec::new();
+        r.read_to_end(&mut pt)
+            .map_err(|e| wrap_err("age: decryption failed", e))?;
+        Ok(pt)
+    }
+
+    fn new_decrypting_reader(
+        &self,
+        mut r: Box<dyn Read>,
+        context_info: &[u8],
+    ) -> Result<Box<dyn Read>, TinkError> {
+        check_context_info(context_info)?;
+        let file_key = self.file_key(&mut r)?;
+        Ok(Box::new(PayloadReader::new(r, &file_key)?))
+    }
+}
@@ -0,0 +1,423 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Header and payload encoding for the [age](https://age-encryption.org/v1) file format.
+
+use chacha20poly1305::{aead::Aead, ChaCha20Poly1305, KeyInit};
+use hmac::{Hmac, Mac};
+use std::io::{self, Read, Write};
+use tink_core::{subtle::compute_hkdf, utils::wrap_err, TinkError};
+use tink_proto::HashType;
+
+/// First line of an age header.
+pub const AGE_VERSION_LINE: &str = "age-encryption.org/v1";
+/// Size of the file key that is wrapped for each recipient.
+pub const AGE_FILE_KEY_SIZE: usize = 16;
+/// Size of the nonce at the start of the payload.
+const PAYLOAD_NONCE_SIZE: usize = 16;
+/// Size of a plaintext payload chunk.
+const CHUNK_SIZE: usize = 64 * 1024;
+/// Size of a Poly1305 tag.
+const TAG_SIZE: usize = 16;
+/// Number of base64 characters in each full line of a stanza body.
+const BODY_LINE_SIZE: usize = 64;
+/// Maximum size of a header that will be read, to bound the work done on malformed input.
+const MAX_HEADER_SIZE: usize = 64 * 1024;
+
+/// A recipient stanza in an age header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Stanza {
+    pub(crate) tag: String,
+    pub(crate) args: Vec<String>,
+    pub(crate) body: Vec<u8>,
+}
+
+/// Encode data as unpadded standard base64, as used throughout the age header.
+pub(crate) fn b64_encode(data: &[u8]) -> String {
+    base64::encode_config(data, base64::STANDARD_NO_PAD)
+}
+
+/// Decode unpadded standard base64.
+pub(crate) fn b64_decode(data: &str) -> Result<Vec<u8>, TinkError> {
+    base64::decode_config(data, base64::STANDARD_NO_PAD)
+        .map_err(|e| wrap_err("age: invalid base64", e))
+}
+
+/// Wrap (or unwrap) a file key with ChaCha20-Poly1305 under the given key and a zero nonce, as
+/// done for every recipient type.
+pub(crate) fn aead_wrap(key: &[u8], file_key: &[u8]) -> Result<Vec<u8>, TinkError> {
+    let cipher = ChaCha20Poly1305::new_from_slice(key)
+        .map_err(|e| wrap_err("age: invalid wrapping key", e))?;
+    cipher
+        .encrypt(&Default::default(), file_key)
+        .map_err(|e| wrap_err("age: wrapping failed", e))
+}
+
+/// Reverse [`aead_wrap`], returning `None` if the stanza was not wrapped under `key`.
+pub(crate) fn aead_unwrap(key: &[u8], body: &[u8]) -> Result<Option<Vec<u8>>, TinkError> {
+    if body.len() != AGE_FILE_KEY_SIZE + TAG_SIZE {
+        return Err("age: invalid stanza body size".into());
+    }
+    let cipher = ChaCha20Poly1305::new_from_slice(key)
+        .map_err(|e| wrap_err("age: invalid wrapping key", e))?;
+    Ok(cipher.decrypt(&Default::default(), body).ok())
+}
+
+/// Return the header, including the MAC line, for a file with the given file key and stanzas.
+pub(crate) fn encode_header(file_key: &[u8], stanzas: &[Stanza]) -> Result<Vec<u8>, TinkError> {
+    let mut header = String::new();
+    header.push_str(AGE_VERSION_LINE);
+    header.push('\n');
+    for stanza in stanzas {
+        header.push_str("->");
+        for arg in std::iter::once(&stanza.tag).chain(stanza.args.iter()) {
+            header.push(' ');
+            header.push_str(arg);
+        }
+        header.push('\n');
+        let body = b64_encode(&stanza.body);
+        let mut rest = body.as_str();
+        while rest.len() >= BODY_LINE_SIZE {
+            let (line, tail) = rest.split_at(BODY_LINE_SIZE);
+            header.push_str(line);
+            header.push('\n');
+            rest = tail;
+        }
+        // The final line is always shorter than a full line, and may be empty.
+        header.push_str(rest);
+        header.push('\n');
+    }
+    header.push_str("---");
+    let mac = header_mac(file_key, header.as_bytes())?;
+    header.push(' ');
+    header.push_str(&b64_encode(&mac));
+    header.push('\n');
+    Ok(header.into_bytes())
+}
+
+/// A parsed age header.
+pub(crate) struct Header {
+    pub(crate) stanzas: Vec<Stanza>,
+    /// The header up to and including the `---` that starts the MAC line.
+    mac_input: Vec<u8>,
+    mac: Vec<u8>,
+}
+
+impl Header {
+    /// Check the header MAC using the unwrapped file key.
+    pub(crate) fn verify_mac(&self, file_key: &[u8]) -> Result<(), TinkError> {
+        let want = header_mac(file_key, &self.mac_input)?;
+        if !tink_core::subtle::constant_time_compare(&want, &self.mac) {
+            return Err("age: invalid header MAC".into());
+        }
+        Ok(())
+    }
+}
+
+/// Read and parse an age header from `r`, consuming exactly the bytes of the header.
+pub(crate) fn read_header(r: &mut dyn Read) -> Result<Header, TinkError> {
+    let mut consumed = 0;
+    let mut mac_input = Vec::new();
+    let mut next_line = |mac_input: &mut Vec<u8>| -> Result<String, TinkError> {
+        let line = read_line(&mut *r, MAX_HEADER_SIZE.saturating_sub(consumed))?;
+        consumed += line.len() + 1;
+        mac_input.extend_from_slice(line.as_bytes());
+        mac_input.push(b'\n');
+        Ok(line)
+    };
+    if next_line(&mut mac_input)? != AGE_VERSION_LINE {
+        return Err("age: unsupported format version".into());
+    }
+    let mut stanzas = Vec::new();
+    let mut line = next_line(&mut mac_input)?;
+    while let Some(rest) = line.strip_prefix("-> ") {
+        let mut args: Vec<String> = rest.split(' ').map(|s| s.to_string()).collect();
+        if args
+            .iter()
+            .any(|a| a.is_empty() || !a.bytes().all(|b| (33..=126).contains(&b)))
+        {
+            return Err("age: invalid stanza arguments".into());
+        }
+        let tag = args.remove(0);
+        let mut encoded_body = String::new();
+        loop {
+            let body_line = next_line(&mut mac_input)?;
+            if body_line.len() > BODY_LINE_SIZE {
+                return Err("age: invalid stanza body".into());
+            }
+            let last = body_line.len() < BODY_LINE_SIZE;
+            encoded_body.push_str(&body_line);
+            if last {
+                break;
+            }
+        }
+        stanzas.push(Stanza {
+            tag,
+            args,
+            body: b64_decode(&encoded_body)?,
+        });
+        line = next_line(&mut mac_input)?;
+    }
+    let encoded_mac = line
+        .strip_prefix("--- ")
+        .ok_or_else(|| TinkError::new("age: invalid header"))?;
+    let mac = b64_decode(encoded_mac)?;
+    // The MAC covers the header up to and including the "---".
+    mac_input.truncate(mac_input.len() - line.len() - 1 + 3);
+    if stanzas.is_empty() {
+        return Err("age: no recipients in header".into());
+    }
+    Ok(Header {
+        stanzas,
+        mac_input,
+        mac,
+    })
+}
+
+/// Read a `\n`-terminated line of at most `limit` bytes, without reading beyond its end.
+fn read_line(r: &mut dyn Read, limit: usize) -> Result<String, TinkError> {
+    let mut line = Vec::new();
+    let mut b = [0u8; 1];
+    loop {
+        r.read_exact(&mut b)
+            .map_err(|e| wrap_err("age: truncated header", e))?;
+        if b[0] == b'\n' {
+            break;
+        }
+        if line.len() >= limit {
+            return Err("age: header too large".into());
+        }
+        line.push(b[0]);
+    }
+    String::from_utf8(line).map_err(|e| wrap_err("age: invalid header", e))
+}
+
+/// Compute the header MAC.
+fn header_mac(file_key: &[u8], data: &[u8]) -> Result<Vec<u8>, TinkError> {
+    let hmac_key = compute_hkdf(HashType::Sha256, file_key, &[], b"header", 32)?;
+    let mut mac = <Hmac<sha2::Sha256> as Mac>::new_from_slice(&hmac_key)
+        .map_err(|e| wrap_err("age: invalid MAC key", e))?;
+    mac.update(data);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+/// The STREAM construction used to encrypt the payload in chunks.
+#[derive(Clone)]
+struct Stream {
+    cipher: ChaCha20Poly1305,
+    counter: u64,
+}
+
+impl Stream {
+    fn new(file_key: &[u8], nonce: &[u8]) -> Result<Self, TinkError> {
+        let key = compute_hkdf(HashType::Sha256, file_key, nonce, b"payload", 32)?;
+        let cipher = ChaCha20Poly1305::new_from_slice(&key)
+            .map_err(|e| wrap_err("age: invalid payload key", e))?;
+        Ok(Stream { cipher, counter: 0 })
+    }
+
+    fn next_nonce(&mut self, last: bool) -> Result<chacha20poly1305::Nonce, TinkError> {
+        let mut nonce = chacha20poly1305::Nonce::default();
+        nonce[3..11].copy_from_slice(&self.counter.to_be_bytes());
+        nonce[11] = if last { 1 } else { 0 };
+        self.counter = self
+            .counter
+            .checked_add(1)
+            .ok_or_else(|| TinkError::new("age: too many chunks"))?;
+        Ok(nonce)
+    }
+
+    fn seal(&mut self, chunk: &[u8], last: bool) -> Result<Vec<u8>, TinkError> {
+        let nonce = self.next_nonce(last)?;
+        self.cipher
+            .encrypt(&nonce, chunk)
+            .map_err(|e| wrap_err("age: encryption failed", e))
+    }
+
+    fn open(&mut self, chunk: &[u8], last: bool) -> Result<Vec<u8>, TinkError> {
+        if last && chunk.len() == TAG_SIZE && self.counter != 0 {
+            return Err("age: empty final chunk".into());
+        }
+        let nonce = self.next_nonce(last)?;
+        self.cipher
+            .decrypt(&nonce, chunk)
+            .map_err(|_| TinkError::new("age: payload decryption failed"))
+    }
+}
+
+/// Encrypt a complete payload, returning the payload nonce followed by the encrypted chunks.
+pub(crate) fn encrypt_payload(file_key: &[u8], pt: &[u8]) -> Result<Vec<u8>, TinkError> {
+    let nonce = tink_core::subtle::random::get_random_bytes(PAYLOAD_NONCE_SIZE);
+    let mut stream = Stream::new(file_key, &nonce)?;
+    let n_chunks = std::cmp::max(1, (pt.len() + CHUNK_SIZE - 1) / CHUNK_SIZE);
+    let mut ct = Vec::with_capacity(nonce.len() + pt.len() + n_chunks * TAG_SIZE);
+    ct.extend_from_slice(&nonce);
+    if pt.is_empty() {
+        ct.extend_from_slice(&stream.seal(&[], true)?);
+    }
+    let mut chunks = pt.chunks(CHUNK_SIZE).peekable();
+    while let Some(chunk) = chunks.next() {
+        ct.extend_from_slice(&stream.seal(chunk, chunks.peek().is_none())?);
+    }
+    Ok(ct)
+}
+
+/// Writer that encrypts a payload in chunks; see [`tink_core::EncryptingWrite`].
+pub(crate) struct PayloadWriter {
+    w: Box<dyn Write>,
+    stream: Stream,
+    buf: Vec<u8>,
+    closed: bool,
+}
+
+impl PayloadWriter {
+    /// Create a writer that writes the payload nonce to `w` followed by the encrypted chunks.
+    pub(crate) fn new(mut w: Box<dyn Write>, file_key: &[u8]) -> Result<Self, TinkError> {
+        let nonce = tink_core::subtle::random::get_random_bytes(PAYLOAD_NONCE_SIZE);
+        let stream = Stream::new(file_key, &nonce)?;
+        w.write_all(&nonce)
+            .map_err(|e| wrap_err("age: write failed", e))?;
+        Ok(PayloadWriter {
+            w,
+            stream,
+            buf: Vec::with_capacity(CHUNK_SIZE),
+            closed: false,
+        })
+    }
+}
+
+impl Write for PayloadWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        if self.closed {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "write on closed writer",
+            ));
+        }
+        self.buf.extend_from_slice(data);
+        // Hold back a full chunk, as only `close()` knows which chunk is the last.
+        while self.buf.len() > CHUNK_SIZE {
+            let ct = self
+                .stream
+                .seal(&self.buf[..CHUNK_SIZE], false)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("{:?}", e)))?;
+            self.w.write_all(&ct)?;
+            self.buf.drain(..CHUNK_SIZE);
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.w.flush()
+    }
+}
+
+impl tink_core::EncryptingWrite for PayloadWriter {
+    fn close(&mut self) -> Result<(), TinkError> {
+        if self.closed {
+            return Err("age: writer already closed".into());
+        }
+        self.closed = true;
+        let ct = self.stream.seal(&self.buf, true)?;
+        self.buf.clear();
+        self.w
+            .write_all(&ct)
+            .map_err(|e| wrap_err("age: write failed", e))?;
+        self.w.flush().map_err(|e| wrap_err("age: flush failed", e))
+    }
+}
+
+/// Reader that decrypts a payload in chunks.
+pub(crate) struct PayloadReader {
+    r: Box<dyn Read>,
+    stream: Stream,
+    raw: Vec<u8>,
+    pt: Vec<u8>,
+    pt_pos: usize,
+    done: bool,
+}
+
+impl PayloadReader {
+    /// Create a reader that reads the payload nonce and encrypted chunks from `r`.  The first
+    /// chunk is decrypted immediately, so an error is returned if the file key is wrong.
+    pub(crate) fn new(mut r: Box<dyn Read>, file_key: &[u8]) -> Result<Self, TinkError> {
+        let mut nonce = [0u8; PAYLOAD_NONCE_SIZE];
+        r.read_exact(&mut nonce)
+            .map_err(|e| wrap_err("age: truncated payload", e))?;
+        let mut reader = PayloadReader {
+            r,
+            stream: Stream::new(file_key, &nonce)?,
+            raw: Vec::with_capacity(CHUNK_SIZE + TAG_SIZE + 1),
+            pt: Vec::new(),
+            pt_pos: 0,
+            done: false,
+        };
+        reader.next_chunk()?;
+        Ok(reader)
+    }
+
+    /// Read and decrypt the next chunk into `pt`.
+    fn next_chunk(&mut self) -> Result<(), TinkError> {
+        // Read one byte beyond a full chunk to find out whether this is the last chunk.
+        let want = CHUNK_SIZE + TAG_SIZE + 1;
+        while self.raw.len() < want {
+            let start = self.raw.len();
+            self.raw.resize(want, 0);
+            let n = match self.r.read(&mut self.raw[start..]) {
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => 0,
+                Err(e) => {
+                    self.raw.truncate(start);
+                    return Err(wrap_err("age: read failed", e));
+                }
+            };
+            self.raw.truncate(start + n);
+            if n == 0 {
+                break;
+            }
+        }
+        let last = self.raw.len() < want;
+        let chunk_len = if last {
+            self.raw.len()
+        } else {
+            CHUNK_SIZE + TAG_SIZE
+        };
+        if chunk_len < TAG_SIZE {
+            return Err("age: truncated payload".into());
+        }
+        self.pt = self.stream.open(&self.raw[..chunk_len], last)?;
+        self.pt_pos = 0;
+        self.raw.drain(..chunk_len);
+        self.done = last;
+        Ok(())
+    }
+}
+
+impl Read for PayloadReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pt_pos == self.pt.len() {
+            if self.done {
+                return Ok(0);
+            }
+            self.next_chunk()
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", e)))?;
+        }
+        let n = std::cmp::min(buf.len(), self.pt.len() - self.pt_pos);
+        buf[..n].copy_from_slice(&self.pt[self.pt_pos..self.pt_pos + n]);
+        self.pt_pos += n;
+        Ok(n)
+    }
+}
@@ -19,7 +19,7 @@ pub trait EciesAeadHkdfDemHelper {
     /// Size of the DEM-key in bytes.
     fn get_symmetric_key_size(&self) -> usize;
 
-    /// Returns a newly created `Aead` or `DeterministicAead` primitive.
+    /// Returns a newly created `Aead`, `DeterministicAead` or `StreamingAead` primitive.
     fn get_aead_or_daead(
         &self,
         symmetric_key_value: &[u8],
@@ -17,7 +17,7 @@
 use crate::subtle::{
     encoding_size_in_bytes, EcPrivateKey, EciesAeadHkdfDemHelper, EciesHkdfRecipientKem,
 };
-use tink_core::TinkError;
+use tink_core::{utils::wrap_err, TinkError};
 use tink_proto::{EcPointFormat, HashType};
 
 /// An instance of ECIES decryption with HKDF-KEM (key encapsulation mechanism)
@@ -74,7 +74,37 @@ impl tink_core::HybridDecrypt for EciesAeadHkdfHybridDecrypt {
         match prim {
             tink_core::Primitive::Aead(a) => a.decrypt(ct, &[]),
             tink_core::Primitive::DeterministicAead(a) => a.decrypt_deterministically(ct, &[]),
+            tink_core::Primitive::StreamingAead(_) => {
+                Err("streaming DEM only supports new_decrypting_reader()".into())
+            }
             _ => Err("Internal error: unexpected primitive type".into()),
         }
     }
+
+    /// Decrypt using ECIES with a HKDF-KEM and streaming AEAD DEM mechanisms.
+    fn new_decrypting_reader(
+        &self,
+        mut r: Box<dyn std::io::Read>,
+        context_info: &[u8],
+    ) -> Result<Box<dyn std::io::Read>, TinkError> {
+        let curve = self.private_key.public_key().curve();
+        let header_size = encoding_size_in_bytes(curve, self.point_format)?;
+        let mut kem_bytes = vec![0; header_size];
+        r.read_exact(&mut kem_bytes)
+            .map_err(|e| wrap_err("cannot read KEM header", e))?;
+        let r_kem = EciesHkdfRecipientKem::new(&self.private_key);
+        let symmetric_key = r_kem.decapsulate(
+            &kem_bytes,
+            self.hkdf_hmac_algo,
+            &self.hkdf_salt,
+            context_info,
+            self.dem_helper.get_symmetric_key_size(),
+            self.point_format,
+        )?;
+        let prim = self.dem_helper.get_aead_or_daead(&symmetric_key)?;
+        match prim {
+            tink_core::Primitive::StreamingAead(a) => a.new_decrypting_reader(r, &[]),
+            _ => Err("streaming decryption requires a streaming AEAD DEM".into()),
+        }
+    }
 }
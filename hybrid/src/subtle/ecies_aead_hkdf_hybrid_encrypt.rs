@@ -14,8 +14,11 @@
 //
 ////////////////////////////////////////////////////////////////////////////////
 
-use crate::subtle::{EcPublicKey, EciesAeadHkdfDemHelper, EciesHkdfSenderKem};
-use tink_core::TinkError;
+use crate::subtle::{
+    EcPublicKey, EciesAeadHkdfDemHelper, EciesHkdfSenderKem, EphemeralKeyPool, KemCache,
+};
+use std::sync::Arc;
+use tink_core::{utils::wrap_err, TinkError};
 use tink_proto::{EcPointFormat, HashType};
 
 /// Instance of ECIES encryption with HKDF-KEM (key encapsulation mechanism)
@@ -27,8 +30,14 @@ pub struct EciesAeadHkdfHybridEncrypt {
     hkdf_hmac_algo: HashType,
     point_format: EcPointFormat,
     dem_helper: crate::EciesAeadHkdfDemHelper,
+    ephemeral_keys: Option<Arc<EphemeralKeyPool>>,
+    kem_cache: Option<Arc<KemCache>>,
 }
 
+/// Maximum number of messages that may share an ephemeral key exchange; see
+/// [`EciesAeadHkdfHybridEncrypt::with_kem_reuse`].
+pub const MAX_KEM_REUSE: u64 = 1 << 20;
+
 impl EciesAeadHkdfHybridEncrypt {
     /// Return an ECIES encryption construct with HKDF-KEM (key encapsulation mechanism)
     /// and AEAD-DEM (data encapsulation mechanism).
@@ -45,14 +54,67 @@ impl EciesAeadHkdfHybridEncrypt {
             hkdf_hmac_algo,
             point_format,
             dem_helper,
+            ephemeral_keys: None,
+            kem_cache: None,
         })
     }
+
+    /// Take ephemeral key pairs from `pool` where possible, rather than generating one for each
+    /// message.  See [`EphemeralKeyPool`] for the security implications.
+    pub fn with_ephemeral_key_pool(mut self, pool: Arc<EphemeralKeyPool>) -> Self {
+        self.ephemeral_keys = Some(pool);
+        self
+    }
+
+    /// Reuse each ephemeral key exchange for up to `max_messages` messages, rather than
+    /// performing one per message.  This removes key generation and ECDH from most calls to
+    /// `encrypt()`, which dominate the cost of encrypting many small messages to the same
+    /// recipient.  Ciphertexts are unchanged in format and decrypt as usual.
+    ///
+    /// The DEM key is still derived separately for each message, from the shared secret and the
+    /// message's `context_info`.  Messages that share an exchange and a `context_info` value are
+    /// encrypted under the same DEM key, so reuse is only applied for randomized DEMs (AES-GCM,
+    /// AES-CTR-HMAC and the streaming AEADs); a deterministic AES-SIV DEM always gets a fresh
+    /// exchange.
+    ///
+    /// Security notes:
+    /// - Messages that share an exchange have the same KEM header, which links them to each
+    ///   other.
+    /// - The shared secret is held in memory until the exchange is used up, so exposure of the
+    ///   process memory reveals up to `max_messages` messages rather than one.
+    /// - Clones of this primitive share the cached exchange.
+    pub fn with_kem_reuse(mut self, max_messages: u64) -> Result<Self, TinkError> {
+        if max_messages == 0 || max_messages > MAX_KEM_REUSE {
+            return Err(format!(
+                "EciesAeadHkdfHybridEncrypt: KEM reuse must be between 1 and {} messages",
+                MAX_KEM_REUSE
+            )
+            .into());
+        }
+        self.kem_cache = if self.dem_helper.is_deterministic() {
+            None
+        } else {
+            Some(Arc::new(KemCache::new(max_messages)))
+        };
+        Ok(self)
+    }
+
+    /// Return the number of messages that may share an ephemeral key exchange, if reuse is
+    /// enabled.
+    pub fn kem_reuse(&self) -> Option<u64> {
+        self.kem_cache.as_ref().map(|c| c.max_uses())
+    }
+
+    fn sender_kem(&self) -> EciesHkdfSenderKem<'_> {
+        EciesHkdfSenderKem::new(&self.public_key, self.ephemeral_keys.as_deref())
+            .with_kem_cache(self.kem_cache.as_deref())
+    }
 }
 
 impl tink_core::HybridEncrypt for EciesAeadHkdfHybridEncrypt {
     /// Encrypt using ECIES with a HKDF-KEM and AEAD-DEM mechanisms.
     fn encrypt(&self, plaintext: &[u8], context_info: &[u8]) -> Result<Vec<u8>, TinkError> {
-        let s_kem = EciesHkdfSenderKem::new(&self.public_key);
+        let s_kem = self.sender_kem();
         let kem_key = s_kem.encapsulate(
             self.hkdf_hmac_algo,
             &self.hkdf_salt,
@@ -66,10 +128,38 @@ impl tink_core::HybridEncrypt for EciesAeadHkdfHybridEncrypt {
             tink_core::Primitive::DeterministicAead(a) => {
                 a.encrypt_deterministically(plaintext, &[])?
             }
+            tink_core::Primitive::StreamingAead(_) => {
+                return Err("streaming DEM only supports new_encrypting_writer()".into())
+            }
             _ => return Err("Internal error: unexpected primitive type".into()),
         };
         let mut b = kem_key.kem;
         b.extend_from_slice(&ct);
         Ok(b)
     }
+
+    /// Encrypt using ECIES with a HKDF-KEM and streaming AEAD DEM mechanisms.  The output
+    /// consists of the KEM header followed by the streaming AEAD ciphertext.
+    fn new_encrypting_writer(
+        &self,
+        mut w: Box<dyn std::io::Write>,
+        context_info: &[u8],
+    ) -> Result<Box<dyn tink_core::EncryptingWrite>, TinkError> {
+        let s_kem = self.sender_kem();
+        let kem_key = s_kem.encapsulate(
+            self.hkdf_hmac_algo,
+            &self.hkdf_salt,
+            context_info,
+            self.dem_helper.get_symmetric_key_size(),
+            self.point_format,
+        )?;
+        let prim = self.dem_helper.get_aead_or_daead(&kem_key.symmetric_key)?;
+        let streaming_aead = match prim {
+            tink_core::Primitive::StreamingAead(a) => a,
+            _ => return Err("streaming encryption requires a streaming AEAD DEM".into()),
+        };
+        w.write_all(&kem_key.kem)
+            .map_err(|e| wrap_err("write failed", e))?;
+        streaming_aead.new_encrypting_writer(w, &[])
+    }
 }
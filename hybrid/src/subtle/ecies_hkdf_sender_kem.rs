@@ -14,7 +14,11 @@
 //
 ////////////////////////////////////////////////////////////////////////////////
 
-use crate::{subtle, subtle::EcPublicKey};
+use crate::{
+    subtle,
+    subtle::{EcPublicKey, EphemeralKeyPool},
+};
+use std::sync::Mutex;
 use tink_core::TinkError;
 use tink_proto::{EcPointFormat, HashType};
 
@@ -27,15 +31,25 @@ pub struct KemKey {
 /// Representation of an HKDF-based ECIES-KEM (key encapsulation mechanism) for ECIES sender.
 pub(crate) struct EciesHkdfSenderKem<'a> {
     recipient_public_key: &'a EcPublicKey,
+    ephemeral_keys: Option<&'a EphemeralKeyPool>,
+    kem_cache: Option<&'a KemCache>,
 }
 
 impl<'a> EciesHkdfSenderKem<'a> {
-    pub fn new(pub_key: &'a EcPublicKey) -> Self {
+    pub fn new(pub_key: &'a EcPublicKey, ephemeral_keys: Option<&'a EphemeralKeyPool>) -> Self {
         Self {
             recipient_public_key: pub_key,
+            ephemeral_keys,
+            kem_cache: None,
         }
     }
 
+    /// Reuse the ephemeral key exchange held in `cache`, where it has uses left.
+    pub fn with_kem_cache(mut self, cache: Option<&'a KemCache>) -> Self {
+        self.kem_cache = cache;
+        self
+    }
+
     /// Generate a HDKF based KEM.
     pub(crate) fn encapsulate(
         &self,
@@ -45,14 +59,14 @@ impl<'a> EciesHkdfSenderKem<'a> {
         key_size: usize,
         point_format: EcPointFormat,
     ) -> Result<KemKey, TinkError> {
-        let pvt = subtle::generate_ecdh_key_pair(self.recipient_public_key.curve())?;
-        let pub_key = pvt.public_key();
-        let secret = subtle::compute_shared_secret(self.recipient_public_key, &pvt)?;
+        let (sdata, secret) = match self.kem_cache {
+            Some(cache) => cache.get_or_exchange(|| self.exchange(point_format))?,
+            None => self.exchange(point_format)?,
+        };
 
-        let sdata = subtle::point_encode(pub_key.curve(), point_format, &pub_key)?;
+        // The symmetric key is always derived afresh, as it depends on `info`.
         let mut i = sdata.clone();
         i.extend_from_slice(&secret);
-
         let s_key = tink_core::subtle::compute_hkdf(hash_alg, &i, salt, info, key_size)?;
 
         Ok(KemKey {
@@ -60,4 +74,68 @@ impl<'a> EciesHkdfSenderKem<'a> {
             symmetric_key: s_key,
         })
     }
+
+    /// Perform an ECDH key exchange with a new ephemeral key, returning the encoded ephemeral
+    /// public key and the shared secret.
+    fn exchange(&self, point_format: EcPointFormat) -> Result<(Vec<u8>, Vec<u8>), TinkError> {
+        let curve = self.recipient_public_key.curve();
+        let pvt = match self.ephemeral_keys {
+            Some(pool) => pool.take(curve)?,
+            None => subtle::generate_ecdh_key_pair(curve)?,
+        };
+        let pub_key = pvt.public_key();
+        let secret = subtle::compute_shared_secret(self.recipient_public_key, &pvt)?;
+        let sdata = subtle::point_encode(pub_key.curve(), point_format, &pub_key)?;
+        Ok((sdata, secret))
+    }
+}
+
+/// Sender-side cache of a single ephemeral key exchange, shared by up to `max_uses` messages to
+/// the same recipient.
+pub(crate) struct KemCache {
+    max_uses: u64,
+    current: Mutex<Option<CachedExchange>>,
+}
+
+struct CachedExchange {
+    sdata: Vec<u8>,
+    secret: Vec<u8>,
+    uses: u64,
+}
+
+impl KemCache {
+    pub(crate) fn new(max_uses: u64) -> Self {
+        Self {
+            max_uses,
+            current: Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn max_uses(&self) -> u64 {
+        self.max_uses
+    }
+
+    /// Return the cached exchange, replacing it using `exchange` if there is none or it has been
+    /// used `max_uses` times.
+    fn get_or_exchange<F>(&self, exchange: F) -> Result<(Vec<u8>, Vec<u8>), TinkError>
+    where
+        F: FnOnce() -> Result<(Vec<u8>, Vec<u8>), TinkError>,
+    {
+        let mut current = self.current.lock().expect("internal lock corrupted"); // safe: lock
+        match current.as_mut() {
+            Some(cached) if cached.uses < self.max_uses => {
+                cached.uses += 1;
+                Ok((cached.sdata.clone(), cached.secret.clone()))
+            }
+            _ => {
+                let (sdata, secret) = exchange()?;
+                *current = Some(CachedExchange {
+                    sdata: sdata.clone(),
+                    secret: secret.clone(),
+                    uses: 1,
+                });
+                Ok((sdata, secret))
+            }
+        }
+    }
 }
@@ -254,7 +254,7 @@ pub fn compute_shared_secret(
 
 /// Create a new private key for a given curve.
 pub fn generate_ecdh_key_pair(c: EllipticCurveType) -> Result<EcPrivateKey, TinkError> {
-    let mut csprng = elliptic_curve::rand_core::OsRng {};
+    let mut csprng = tink_core::subtle::random::TinkRng;
     match c {
         EllipticCurveType::NistP256 => Ok(EcPrivateKey::NistP256(p256::NonZeroScalar::random(
             &mut csprng,
@@ -278,7 +278,7 @@ pub(crate) fn element_from_padded_slice<C: elliptic_curve::Curve>(
             }
         }
         Ok(elliptic_curve::FieldBytes::<C>::clone_from_slice(
-            &data[offset..],
+            &data[offset..], // safe: field size
         ))
     } else {
         // We have been given data that is too short for the field size.
@@ -286,7 +286,7 @@ pub(crate) fn element_from_padded_slice<C: elliptic_curve::Curve>(
         let mut data_copy = vec![0; point_len];
         data_copy[(point_len - data.len())..].copy_from_slice(data);
         Ok(elliptic_curve::FieldBytes::<C>::clone_from_slice(
-            &data_copy,
+            &data_copy, // safe: field size
         ))
     }
 }
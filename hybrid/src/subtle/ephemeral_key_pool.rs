@@ -0,0 +1,189 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use crate::subtle::{generate_ecdh_key_pair, EcPrivateKey};
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    thread::JoinHandle,
+};
+use tink_core::{utils::wrap_err, TinkError};
+use tink_proto::EllipticCurveType;
+
+/// `EphemeralKeyPool` holds ephemeral ECDH key pairs generated ahead of time by a background
+/// thread, for use by [`EciesAeadHkdfHybridEncrypt`](crate::subtle::EciesAeadHkdfHybridEncrypt)
+/// instead of generating a key pair for each message.  This moves the cost of key generation off
+/// the encryption path, which smooths out latency during bursts of encryptions of small messages.
+/// When the pool is empty, encryption generates a key pair as usual.
+///
+/// Security notes:
+/// - Each pooled key pair is removed from the pool when it is taken, and so is used for exactly
+///   one message.
+/// - Pooled private keys are held in memory until used.  Anyone who can read the memory of the
+///   process can decrypt the messages later encrypted with them, so keep the capacity small.
+/// - Do not use a pool in a process that forks: a child process inherits a copy of the pooled
+///   keys, which both processes would then use.
+pub struct EphemeralKeyPool {
+    state: Arc<PoolState>,
+    worker: Mutex<Option<JoinHandle<()>>>,
+}
+
+/// Counts of the key pairs supplied by an [`EphemeralKeyPool`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EphemeralKeyPoolStats {
+    /// Number of key pairs taken from the pool.
+    pub hits: u64,
+    /// Number of key pairs generated on demand because the pool was empty.
+    pub misses: u64,
+}
+
+struct PoolState {
+    curve: EllipticCurveType,
+    capacity: usize,
+    keys: Mutex<PoolKeys>,
+    // Signalled when a key is taken from the pool, or the pool is shutting down.
+    wake: Condvar,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+struct PoolKeys {
+    keys: Vec<EcPrivateKey>,
+    shutdown: bool,
+}
+
+impl EphemeralKeyPool {
+    /// Create a pool holding up to `capacity` key pairs on `curve`, and start the background
+    /// thread that fills it.
+    pub fn new(curve: EllipticCurveType, capacity: usize) -> Result<Self, TinkError> {
+        if capacity == 0 {
+            return Err("EphemeralKeyPool: capacity must be positive".into());
+        }
+        // Check that the curve is supported before starting the thread.
+        generate_ecdh_key_pair(curve)?;
+        let state = Arc::new(PoolState {
+            curve,
+            capacity,
+            keys: Mutex::new(PoolKeys {
+                keys: Vec::with_capacity(capacity),
+                shutdown: false,
+            }),
+            wake: Condvar::new(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        });
+        let worker_state = state.clone();
+        let worker = std::thread::Builder::new()
+            .name("tink-ephemeral-keys".to_string())
+            .spawn(move || worker_state.fill())
+            .map_err(|e| wrap_err("EphemeralKeyPool: cannot start thread", e))?;
+        Ok(EphemeralKeyPool {
+            state,
+            worker: Mutex::new(Some(worker)),
+        })
+    }
+
+    /// Return the curve of the pooled key pairs.
+    pub fn curve(&self) -> EllipticCurveType {
+        self.state.curve
+    }
+
+    /// Return the maximum number of key pairs held by the pool.
+    pub fn capacity(&self) -> usize {
+        self.state.capacity
+    }
+
+    /// Return the number of key pairs currently held by the pool.
+    pub fn available(&self) -> usize {
+        self.state.keys.lock().map(|k| k.keys.len()).unwrap_or(0)
+    }
+
+    /// Return the counts of key pairs supplied so far.
+    pub fn stats(&self) -> EphemeralKeyPoolStats {
+        EphemeralKeyPoolStats {
+            hits: self.state.hits.load(Ordering::Relaxed),
+            misses: self.state.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Return a key pair on `curve`, from the pool if possible.
+    pub(crate) fn take(&self, curve: EllipticCurveType) -> Result<EcPrivateKey, TinkError> {
+        if curve != self.state.curve {
+            return generate_ecdh_key_pair(curve);
+        }
+        let pooled = match self.state.keys.lock() {
+            Ok(mut k) => k.keys.pop(),
+            Err(_) => None,
+        };
+        match pooled {
+            Some(key) => {
+                self.state.wake.notify_one();
+                self.state.hits.fetch_add(1, Ordering::Relaxed);
+                Ok(key)
+            }
+            None => {
+                self.state.misses.fetch_add(1, Ordering::Relaxed);
+                generate_ecdh_key_pair(curve)
+            }
+        }
+    }
+}
+
+impl Drop for EphemeralKeyPool {
+    fn drop(&mut self) {
+        if let Ok(mut k) = self.state.keys.lock() {
+            k.shutdown = true;
+            k.keys.clear();
+        }
+        self.state.wake.notify_all();
+        if let Some(worker) = self.worker.lock().ok().and_then(|mut w| w.take()) {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl PoolState {
+    /// Keep the pool filled until shutdown.  Key pairs are generated without holding the lock.
+    fn fill(&self) {
+        loop {
+            {
+                let mut k = match self.keys.lock() {
+                    Ok(k) => k,
+                    Err(_) => return,
+                };
+                while !k.shutdown && k.keys.len() >= self.capacity {
+                    k = match self.wake.wait(k) {
+                        Ok(k) => k,
+                        Err(_) => return,
+                    };
+                }
+                if k.shutdown {
+                    return;
+                }
+            }
+            let key = match generate_ecdh_key_pair(self.curve) {
+                Ok(key) => key,
+                Err(_) => return,
+            };
+            match self.keys.lock() {
+                Ok(mut k) if !k.shutdown => k.keys.push(key),
+                _ => return,
+            }
+        }
+    }
+}
@@ -28,3 +28,11 @@ mod ecies_aead_hkdf_hybrid_decrypt;
 pub use ecies_aead_hkdf_hybrid_decrypt::*;
 mod ecies_aead_hkdf_hybrid_encrypt;
 pub use ecies_aead_hkdf_hybrid_encrypt::*;
+mod ephemeral_key_pool;
+pub use ephemeral_key_pool::*;
+#[cfg(feature = "age")]
+mod age;
+#[cfg(feature = "age")]
+mod age_format;
+#[cfg(feature = "age")]
+pub use self::age::*;
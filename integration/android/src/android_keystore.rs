@@ -0,0 +1,57 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Trait definition for access to the Android Keystore.
+
+use tink_core::TinkError;
+
+/// `AndroidKeystore` provides access to keys held in the Android Keystore (or an emulation of
+/// it), identified by their alias.  The Keystore performs operations with its keys but never
+/// reveals them.
+pub trait AndroidKeystore: Send + Sync {
+    /// Generate a new AES-256 key for use with GCM mode, under the given alias.
+    fn generate_aes_key(&self, alias: &str) -> Result<(), TinkError>;
+
+    /// Encrypt `plaintext` with AES-GCM using the key with the given alias, authenticating
+    /// `additional_data`.  The Keystore chooses a random 12-byte IV; the result holds the IV
+    /// followed by the ciphertext and 16-byte tag.
+    fn encrypt(
+        &self,
+        alias: &str,
+        plaintext: &[u8],
+        additional_data: &[u8],
+    ) -> Result<Vec<u8>, TinkError>;
+
+    /// Decrypt a `ciphertext` produced by [`encrypt`](AndroidKeystore::encrypt) with the key
+    /// with the given alias.
+    fn decrypt(
+        &self,
+        alias: &str,
+        ciphertext: &[u8],
+        additional_data: &[u8],
+    ) -> Result<Vec<u8>, TinkError>;
+
+    /// Generate a new P-256 key pair for signing under the given alias, returning its public key
+    /// as an uncompressed SEC1 point.
+    fn generate_ec_key(&self, alias: &str) -> Result<Vec<u8>, TinkError>;
+
+    /// Sign `data` with ECDSA-SHA256 using the key with the given alias, returning the
+    /// DER-encoded signature.
+    fn sign(&self, alias: &str, data: &[u8]) -> Result<Vec<u8>, TinkError>;
+
+    /// Delete the key with the given alias.
+    fn delete_key(&self, alias: &str) -> Result<(), TinkError>;
+}
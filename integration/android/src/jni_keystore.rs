@@ -0,0 +1,219 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Android Keystore access via a Java bridge object, using JNI.
+
+use crate::AndroidKeystore;
+use jni::{
+    objects::{GlobalRef, JByteArray, JObject, JValue},
+    JNIEnv, JavaVM,
+};
+use p256::{elliptic_curve::sec1::ToEncodedPoint, pkcs8::DecodePublicKey};
+use tink_core::{utils::wrap_err, TinkError};
+
+/// JNI signature of methods taking an alias.
+const SIG_ALIAS_VOID: &str = "(Ljava/lang/String;)V";
+/// JNI signature of methods taking an alias and returning bytes.
+const SIG_ALIAS_BYTES: &str = "(Ljava/lang/String;)[B";
+/// JNI signature of methods taking an alias and data, and returning bytes.
+const SIG_ALIAS_DATA_BYTES: &str = "(Ljava/lang/String;[B)[B";
+/// JNI signature of methods taking an alias, data and associated data, and returning bytes.
+const SIG_ALIAS_DATA_AD_BYTES: &str = "(Ljava/lang/String;[B[B)[B";
+
+/// `JniKeystore` is an [`AndroidKeystore`] that calls methods of a Java bridge object supplied by
+/// the application, which uses the `AndroidKeyStore` provider of the Java Cryptography
+/// Architecture.  The bridge object must have the following public methods:
+///
+/// ```java
+/// // Generate an AES-256 key with PURPOSE_ENCRYPT | PURPOSE_DECRYPT, BLOCK_MODE_GCM and
+/// // ENCRYPTION_PADDING_NONE, under `alias`.
+/// void generateAesKey(String alias);
+/// // Encrypt with "AES/GCM/NoPadding" and a 128-bit tag, returning IV || ciphertext.
+/// byte[] encrypt(String alias, byte[] plaintext, byte[] associatedData);
+/// // Decrypt the output of `encrypt`.
+/// byte[] decrypt(String alias, byte[] ciphertext, byte[] associatedData);
+/// // Generate a "secp256r1" EC key pair with PURPOSE_SIGN and DIGEST_SHA256 under `alias`,
+/// // returning the X.509 encoding of the public key (as from `PublicKey.getEncoded()`).
+/// byte[] generateEcKey(String alias);
+/// // Sign with "SHA256withECDSA".
+/// byte[] sign(String alias, byte[] data);
+/// // Delete the entry for `alias`.
+/// void deleteKey(String alias);
+/// ```
+///
+/// Any Java exception thrown by the bridge is cleared, and reported as an error.
+pub struct JniKeystore {
+    vm: JavaVM,
+    bridge: GlobalRef,
+}
+
+impl std::fmt::Debug for JniKeystore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JniKeystore").finish()
+    }
+}
+
+impl JniKeystore {
+    /// Return a Keystore that uses the given Java bridge object.
+    pub fn new(env: &mut JNIEnv, bridge: &JObject) -> Result<Self, TinkError> {
+        let vm = env
+            .get_java_vm()
+            .map_err(|e| wrap_err("JniKeystore: failed to get JVM", e))?;
+        let bridge = env
+            .new_global_ref(bridge)
+            .map_err(|e| wrap_err("JniKeystore: failed to reference bridge", e))?;
+        Ok(JniKeystore { vm, bridge })
+    }
+
+    /// Run `f` with a JNI environment attached to the current thread, clearing any pending Java
+    /// exception on failure.
+    fn with_env<T, F>(&self, method: &str, f: F) -> Result<T, TinkError>
+    where
+        F: FnOnce(&mut JNIEnv, &JObject) -> jni::errors::Result<T>,
+    {
+        let mut env = self
+            .vm
+            .attach_current_thread()
+            .map_err(|e| wrap_err("JniKeystore: failed to attach thread", e))?;
+        let result = f(&mut env, self.bridge.as_obj());
+        if let Err(jni::errors::Error::JavaException) = result {
+            // Leave the JVM in a usable state; the exception itself is reported as an error.
+            let _ = env.exception_clear();
+        }
+        result.map_err(|e| wrap_err(&format!("JniKeystore: {} failed", method), e))
+    }
+}
+
+/// Call a bridge method that returns a byte array.
+fn call_bytes(
+    env: &mut JNIEnv,
+    bridge: &JObject,
+    method: &str,
+    sig: &str,
+    args: &[JValue],
+) -> jni::errors::Result<Vec<u8>> {
+    let result = env.call_method(bridge, method, sig, args)?.l()?;
+    env.convert_byte_array(JByteArray::from(result))
+}
+
+impl AndroidKeystore for JniKeystore {
+    fn generate_aes_key(&self, alias: &str) -> Result<(), TinkError> {
+        self.with_env("generateAesKey", |env, bridge| {
+            let alias = env.new_string(alias)?;
+            env.call_method(
+                bridge,
+                "generateAesKey",
+                SIG_ALIAS_VOID,
+                &[JValue::Object(&alias)],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn encrypt(
+        &self,
+        alias: &str,
+        plaintext: &[u8],
+        additional_data: &[u8],
+    ) -> Result<Vec<u8>, TinkError> {
+        self.with_env("encrypt", |env, bridge| {
+            let alias = env.new_string(alias)?;
+            let plaintext = env.byte_array_from_slice(plaintext)?;
+            let additional_data = env.byte_array_from_slice(additional_data)?;
+            call_bytes(
+                env,
+                bridge,
+                "encrypt",
+                SIG_ALIAS_DATA_AD_BYTES,
+                &[
+                    JValue::Object(&alias),
+                    JValue::Object(&plaintext),
+                    JValue::Object(&additional_data),
+                ],
+            )
+        })
+    }
+
+    fn decrypt(
+        &self,
+        alias: &str,
+        ciphertext: &[u8],
+        additional_data: &[u8],
+    ) -> Result<Vec<u8>, TinkError> {
+        self.with_env("decrypt", |env, bridge| {
+            let alias = env.new_string(alias)?;
+            let ciphertext = env.byte_array_from_slice(ciphertext)?;
+            let additional_data = env.byte_array_from_slice(additional_data)?;
+            call_bytes(
+                env,
+                bridge,
+                "decrypt",
+                SIG_ALIAS_DATA_AD_BYTES,
+                &[
+                    JValue::Object(&alias),
+                    JValue::Object(&ciphertext),
+                    JValue::Object(&additional_data),
+                ],
+            )
+        })
+    }
+
+    fn generate_ec_key(&self, alias: &str) -> Result<Vec<u8>, TinkError> {
+        let spki = self.with_env("generateEcKey", |env, bridge| {
+            let alias = env.new_string(alias)?;
+            call_bytes(
+                env,
+                bridge,
+                "generateEcKey",
+                SIG_ALIAS_BYTES,
+                &[JValue::Object(&alias)],
+            )
+        })?;
+        let public_key = p256::PublicKey::from_public_key_der(&spki)
+            .map_err(|_| TinkError::new("JniKeystore: invalid public key"))?;
+        Ok(public_key
+            .to_encoded_point(/* compress= */ false)
+            .as_bytes()
+            .to_vec())
+    }
+
+    fn sign(&self, alias: &str, data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.with_env("sign", |env, bridge| {
+            let alias = env.new_string(alias)?;
+            let data = env.byte_array_from_slice(data)?;
+            call_bytes(
+                env,
+                bridge,
+                "sign",
+                SIG_ALIAS_DATA_BYTES,
+                &[JValue::Object(&alias), JValue::Object(&data)],
+            )
+        })
+    }
+
+    fn delete_key(&self, alias: &str) -> Result<(), TinkError> {
+        self.with_env("deleteKey", |env, bridge| {
+            let alias = env.new_string(alias)?;
+            env.call_method(
+                bridge,
+                "deleteKey",
+                SIG_ALIAS_VOID,
+                &[JValue::Object(&alias)],
+            )?;
+            Ok(())
+        })
+    }
+}
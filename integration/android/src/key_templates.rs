@@ -0,0 +1,57 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Key templates for Android Keystore keys.
+
+use tink_proto::{prost::Message, KeyTemplate};
+
+/// Return a [`KeyTemplate`] that generates a new AES-256-GCM key in the Android Keystore, with
+/// output prefix type TINK.
+pub fn android_keystore_aes256_gcm_key_template() -> KeyTemplate {
+    let format = tink_proto::AndroidKeystoreAesGcmKeyFormat {
+        version: crate::ANDROID_KEYSTORE_AES_GCM_KEY_VERSION,
+    };
+    let mut serialized_format = Vec::new();
+    format.encode(&mut serialized_format).unwrap(); // safe: proto-encode
+    KeyTemplate {
+        type_url: crate::ANDROID_KEYSTORE_AES_GCM_TYPE_URL.to_string(),
+        value: serialized_format,
+        output_prefix_type: tink_proto::OutputPrefixType::Tink as i32,
+    }
+}
+
+/// Return a [`KeyTemplate`] that generates a new ECDSA private key in the Android Keystore, with
+/// the following parameters:
+///   - Hash function: SHA256
+///   - Curve: NIST P-256
+///   - Signature encoding: DER
+///   - Output prefix type: TINK
+pub fn android_keystore_ecdsa_p256_key_template() -> KeyTemplate {
+    let format = tink_proto::AndroidKeystoreEcdsaKeyFormat {
+        params: Some(tink_proto::EcdsaParams {
+            hash_type: tink_proto::HashType::Sha256 as i32,
+            curve: tink_proto::EllipticCurveType::NistP256 as i32,
+            encoding: tink_proto::EcdsaSignatureEncoding::Der as i32,
+        }),
+    };
+    let mut serialized_format = Vec::new();
+    format.encode(&mut serialized_format).unwrap(); // safe: proto-encode
+    KeyTemplate {
+        type_url: crate::ANDROID_KEYSTORE_SIGNER_TYPE_URL.to_string(),
+        value: serialized_format,
+        output_prefix_type: tink_proto::OutputPrefixType::Tink as i32,
+    }
+}
@@ -0,0 +1,53 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! AEAD that delegates to the Android Keystore.
+
+use crate::AndroidKeystore;
+use std::sync::Arc;
+use tink_core::{utils::wrap_err, TinkError};
+
+/// `AndroidKeystoreAead` is an implementation of [`tink_core::Aead`] that encrypts with an
+/// AES-256-GCM key held in the Android Keystore.
+#[derive(Clone)]
+pub struct AndroidKeystoreAead {
+    keystore: Arc<dyn AndroidKeystore>,
+    alias: String,
+}
+
+impl AndroidKeystoreAead {
+    /// Create a new [`AndroidKeystoreAead`] for the Keystore key with the given alias.
+    pub fn new(keystore: Arc<dyn AndroidKeystore>, alias: &str) -> Self {
+        AndroidKeystoreAead {
+            keystore,
+            alias: alias.to_string(),
+        }
+    }
+}
+
+impl tink_core::Aead for AndroidKeystoreAead {
+    fn encrypt(&self, plaintext: &[u8], additional_data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.keystore
+            .encrypt(&self.alias, plaintext, additional_data)
+            .map_err(|e| wrap_err("AndroidKeystoreAead: encryption failed", e))
+    }
+
+    fn decrypt(&self, ciphertext: &[u8], additional_data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.keystore
+            .decrypt(&self.alias, ciphertext, additional_data)
+            .map_err(|e| wrap_err("AndroidKeystoreAead: decryption failed", e))
+    }
+}
@@ -0,0 +1,97 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Key manager for AES-GCM keys held in the Android Keystore.
+
+use crate::AndroidKeystore;
+use std::sync::Arc;
+use tink_core::{utils::wrap_err, TinkError};
+use tink_proto::prost::Message;
+
+/// Maximal version of Android Keystore AES-GCM keys.
+pub const ANDROID_KEYSTORE_AES_GCM_KEY_VERSION: u32 = 0;
+/// Type URL of Android Keystore AES-GCM keys.
+pub const ANDROID_KEYSTORE_AES_GCM_TYPE_URL: &str =
+    "type.googleapis.com/tink_rust.AndroidKeystoreAesGcmKey";
+
+/// An implementation of the [`tink_core::registry::KeyManager`] trait.
+/// It generates new AES-256-GCM keys in the Android Keystore and produces new instances of
+/// [`crate::AndroidKeystoreAead`].  The keys themselves hold only the alias of the key in the
+/// Keystore, so have key material type `REMOTE`.
+pub(crate) struct AndroidKeystoreAeadKeyManager {
+    keystore: Arc<dyn AndroidKeystore>,
+}
+
+impl AndroidKeystoreAeadKeyManager {
+    pub(crate) fn new(keystore: Arc<dyn AndroidKeystore>) -> Self {
+        AndroidKeystoreAeadKeyManager { keystore }
+    }
+}
+
+impl tink_core::registry::KeyManager for AndroidKeystoreAeadKeyManager {
+    fn primitive(&self, serialized_key: &[u8]) -> Result<tink_core::Primitive, TinkError> {
+        if serialized_key.is_empty() {
+            return Err("AndroidKeystoreAeadKeyManager: invalid key".into());
+        }
+        let key = tink_proto::AndroidKeystoreAesGcmKey::decode(serialized_key)
+            .map_err(|e| wrap_err("AndroidKeystoreAeadKeyManager: invalid key", e))?;
+        validate_key(&key)?;
+        Ok(tink_core::Primitive::Aead(Box::new(
+            crate::AndroidKeystoreAead::new(self.keystore.clone(), &key.key_alias),
+        )))
+    }
+
+    fn new_key(&self, serialized_key_format: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let key_format = tink_proto::AndroidKeystoreAesGcmKeyFormat::decode(serialized_key_format)
+            .map_err(|e| wrap_err("AndroidKeystoreAeadKeyManager: invalid key format", e))?;
+        tink_core::keyset::validate_key_version(
+            key_format.version,
+            ANDROID_KEYSTORE_AES_GCM_KEY_VERSION,
+        )
+        .map_err(|e| wrap_err("AndroidKeystoreAeadKeyManager: invalid key format", e))?;
+
+        let key_alias = crate::new_key_alias();
+        self.keystore
+            .generate_aes_key(&key_alias)
+            .map_err(|e| wrap_err("AndroidKeystoreAeadKeyManager: failed to generate key", e))?;
+        let key = tink_proto::AndroidKeystoreAesGcmKey {
+            version: ANDROID_KEYSTORE_AES_GCM_KEY_VERSION,
+            key_alias,
+        };
+        let mut sk = Vec::new();
+        key.encode(&mut sk)
+            .map_err(|e| wrap_err("AndroidKeystoreAeadKeyManager: failed to encode new key", e))?;
+        Ok(sk)
+    }
+
+    fn type_url(&self) -> &'static str {
+        ANDROID_KEYSTORE_AES_GCM_TYPE_URL
+    }
+
+    fn key_material_type(&self) -> tink_proto::key_data::KeyMaterialType {
+        tink_proto::key_data::KeyMaterialType::Remote
+    }
+}
+
+/// Validate the given [`AndroidKeystoreAesGcmKey`](tink_proto::AndroidKeystoreAesGcmKey).
+fn validate_key(key: &tink_proto::AndroidKeystoreAesGcmKey) -> Result<(), TinkError> {
+    tink_core::keyset::validate_key_version(key.version, ANDROID_KEYSTORE_AES_GCM_KEY_VERSION)
+        .map_err(|e| wrap_err("AndroidKeystoreAeadKeyManager", e))?;
+    if key.key_alias.is_empty() {
+        return Err("AndroidKeystoreAeadKeyManager: no key alias".into());
+    }
+    Ok(())
+}
@@ -0,0 +1,86 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! KMS client for keys held in the Android Keystore.
+
+use crate::AndroidKeystore;
+use std::sync::Arc;
+use tink_core::TinkError;
+
+/// Prefix for any Android Keystore key URIs.
+pub const ANDROID_KEYSTORE_PREFIX: &str = "android-keystore://";
+
+/// `AndroidKeystoreClient` represents a client that connects to the Android Keystore.
+///
+/// Key URIs have the form `android-keystore://<alias>`, where `<alias>` is the alias of an
+/// AES-256-GCM key in the Keystore.
+#[derive(Clone)]
+pub struct AndroidKeystoreClient {
+    keystore: Arc<dyn AndroidKeystore>,
+}
+
+impl std::fmt::Debug for AndroidKeystoreClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AndroidKeystoreClient").finish()
+    }
+}
+
+impl AndroidKeystoreClient {
+    /// Return a new client which uses `keystore` to handle keys with the
+    /// `android-keystore://` prefix.
+    pub fn new(keystore: Arc<dyn AndroidKeystore>) -> AndroidKeystoreClient {
+        AndroidKeystoreClient { keystore }
+    }
+
+    /// Generate a new AES-256-GCM key in the Keystore, with the alias given by `key_uri`.
+    pub fn generate_new_aead_key(&self, key_uri: &str) -> Result<(), TinkError> {
+        let alias = key_alias(key_uri)?;
+        self.keystore.generate_aes_key(alias)
+    }
+}
+
+impl tink_core::registry::KmsClient for AndroidKeystoreClient {
+    fn supported(&self, key_uri: &str) -> bool {
+        key_uri.starts_with(ANDROID_KEYSTORE_PREFIX)
+    }
+
+    /// Additional data is passed to the Keystore's AES-GCM operation.
+    fn supports_associated_data(&self, key_uri: &str) -> bool {
+        self.supported(key_uri)
+    }
+
+    /// Get an AEAD backed by `key_uri`.
+    /// `key_uri` must have the following format: `android-keystore://<alias>`.
+    fn get_aead(&self, key_uri: &str) -> Result<Box<dyn tink_core::Aead>, TinkError> {
+        let alias = key_alias(key_uri)?;
+        Ok(Box::new(crate::AndroidKeystoreAead::new(
+            self.keystore.clone(),
+            alias,
+        )))
+    }
+}
+
+/// Extract the Keystore alias from a key URI.
+fn key_alias(key_uri: &str) -> Result<&str, TinkError> {
+    match key_uri.strip_prefix(ANDROID_KEYSTORE_PREFIX) {
+        Some(alias) if !alias.is_empty() => Ok(alias),
+        _ => Err(format!(
+            "key_uri must have format {}<alias>, but got {}",
+            ANDROID_KEYSTORE_PREFIX, key_uri
+        )
+        .into()),
+    }
+}
@@ -0,0 +1,68 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Signer that delegates to the Android Keystore.
+
+use crate::AndroidKeystore;
+use std::sync::Arc;
+use tink_core::{utils::wrap_err, TinkError};
+use tink_proto::EcdsaSignatureEncoding;
+
+/// `AndroidKeystoreSigner` is an implementation of [`tink_core::Signer`] that signs with a P-256
+/// key held in the Android Keystore.
+#[derive(Clone)]
+pub struct AndroidKeystoreSigner {
+    keystore: Arc<dyn AndroidKeystore>,
+    alias: String,
+    encoding: EcdsaSignatureEncoding,
+}
+
+impl AndroidKeystoreSigner {
+    /// Create a new [`AndroidKeystoreSigner`] for the Keystore key with the given alias, which
+    /// emits signatures with the given encoding.
+    pub fn new(
+        keystore: Arc<dyn AndroidKeystore>,
+        alias: &str,
+        encoding: EcdsaSignatureEncoding,
+    ) -> Result<Self, TinkError> {
+        match encoding {
+            EcdsaSignatureEncoding::Der | EcdsaSignatureEncoding::IeeeP1363 => {}
+            _ => return Err("AndroidKeystoreSigner: unsupported encoding".into()),
+        }
+        Ok(AndroidKeystoreSigner {
+            keystore,
+            alias: alias.to_string(),
+            encoding,
+        })
+    }
+}
+
+impl tink_core::Signer for AndroidKeystoreSigner {
+    fn sign(&self, data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let der = self
+            .keystore
+            .sign(&self.alias, data)
+            .map_err(|e| wrap_err("AndroidKeystoreSigner: signing failed", e))?;
+        match self.encoding {
+            EcdsaSignatureEncoding::IeeeP1363 => {
+                let sig = p256::ecdsa::Signature::from_der(&der)
+                    .map_err(|e| wrap_err("AndroidKeystoreSigner: invalid signature", e))?;
+                Ok(sig.as_ref().to_vec())
+            }
+            _ => Ok(der),
+        }
+    }
+}
@@ -0,0 +1,186 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Key manager for ECDSA signing keys held in the Android Keystore.
+
+use crate::AndroidKeystore;
+use std::sync::Arc;
+use tink_core::{utils::wrap_err, TinkError};
+use tink_proto::{prost::Message, EcdsaSignatureEncoding, EllipticCurveType, HashType};
+
+/// Maximal version of Android Keystore ECDSA keys.
+pub const ANDROID_KEYSTORE_SIGNER_KEY_VERSION: u32 = 0;
+/// Type URL of Android Keystore ECDSA keys.
+pub const ANDROID_KEYSTORE_SIGNER_TYPE_URL: &str =
+    "type.googleapis.com/tink_rust.AndroidKeystoreEcdsaPrivateKey";
+
+/// Size of a P-256 field element.
+const P256_FIELD_SIZE: usize = 32;
+/// Prefix for uncompressed elliptic curve points.
+const UNCOMPRESSED_POINT_PREFIX: u8 = 0x04;
+
+/// An implementation of the [`tink_core::registry::KeyManager`] trait.
+/// It generates new ECDSA private keys in the Android Keystore and produces new instances of
+/// [`crate::AndroidKeystoreSigner`].  The keys themselves hold only the alias of the key in the
+/// Keystore, so have key material type `REMOTE`.
+pub(crate) struct AndroidKeystoreSignerKeyManager {
+    keystore: Arc<dyn AndroidKeystore>,
+}
+
+impl AndroidKeystoreSignerKeyManager {
+    pub(crate) fn new(keystore: Arc<dyn AndroidKeystore>) -> Self {
+        AndroidKeystoreSignerKeyManager { keystore }
+    }
+}
+
+impl tink_core::registry::KeyManager for AndroidKeystoreSignerKeyManager {
+    fn primitive(&self, serialized_key: &[u8]) -> Result<tink_core::Primitive, TinkError> {
+        if serialized_key.is_empty() {
+            return Err("AndroidKeystoreSignerKeyManager: invalid key".into());
+        }
+        let key = tink_proto::AndroidKeystoreEcdsaPrivateKey::decode(serialized_key)
+            .map_err(|e| wrap_err("AndroidKeystoreSignerKeyManager: invalid key", e))?;
+        let encoding = validate_key(&key)?;
+        let signer =
+            crate::AndroidKeystoreSigner::new(self.keystore.clone(), &key.key_alias, encoding)?;
+        Ok(tink_core::Primitive::Signer(Box::new(signer)))
+    }
+
+    fn new_key(&self, serialized_key_format: &[u8]) -> Result<Vec<u8>, TinkError> {
+        if serialized_key_format.is_empty() {
+            return Err("AndroidKeystoreSignerKeyManager: invalid key format".into());
+        }
+        let key_format =
+            tink_proto::AndroidKeystoreEcdsaKeyFormat::decode(serialized_key_format)
+                .map_err(|e| wrap_err("AndroidKeystoreSignerKeyManager: invalid key format", e))?;
+        let params = key_format
+            .params
+            .ok_or_else(|| TinkError::new("AndroidKeystoreSignerKeyManager: no key parameters"))?;
+        validate_params(&params)?;
+
+        let key_alias = crate::new_key_alias();
+        let point = self
+            .keystore
+            .generate_ec_key(&key_alias)
+            .map_err(|e| wrap_err("AndroidKeystoreSignerKeyManager: failed to generate key", e))?;
+        // The public key should be in uncompressed format:
+        //  - 1 byte uncompressed prefix (0x04)
+        //  - 32 bytes of X coordinate
+        //  - 32 bytes of Y coordinate
+        if point.len() != 2 * P256_FIELD_SIZE + 1 || point[0] != UNCOMPRESSED_POINT_PREFIX {
+            return Err(
+                "AndroidKeystoreSignerKeyManager: unexpected public key data format".into(),
+            );
+        }
+        let priv_key = tink_proto::AndroidKeystoreEcdsaPrivateKey {
+            version: ANDROID_KEYSTORE_SIGNER_KEY_VERSION,
+            public_key: Some(tink_proto::EcdsaPublicKey {
+                version: 0,
+                params: Some(params),
+                x: point[1..P256_FIELD_SIZE + 1].to_vec(),
+                y: point[P256_FIELD_SIZE + 1..].to_vec(),
+            }),
+            key_alias,
+        };
+        let mut sk = Vec::new();
+        priv_key.encode(&mut sk).map_err(|e| {
+            wrap_err(
+                "AndroidKeystoreSignerKeyManager: failed to encode new key",
+                e,
+            )
+        })?;
+        Ok(sk)
+    }
+
+    fn type_url(&self) -> &'static str {
+        ANDROID_KEYSTORE_SIGNER_TYPE_URL
+    }
+
+    fn key_material_type(&self) -> tink_proto::key_data::KeyMaterialType {
+        tink_proto::key_data::KeyMaterialType::Remote
+    }
+
+    fn supports_private_keys(&self) -> bool {
+        true
+    }
+
+    /// Return the public key as a standard ECDSA public key, so that signatures can be verified
+    /// without access to the Android Keystore.
+    fn public_key_data(
+        &self,
+        serialized_priv_key: &[u8],
+    ) -> Result<tink_proto::KeyData, TinkError> {
+        let priv_key = tink_proto::AndroidKeystoreEcdsaPrivateKey::decode(serialized_priv_key)
+            .map_err(|e| wrap_err("AndroidKeystoreSignerKeyManager: invalid private key", e))?;
+        let mut serialized_pub_key = Vec::new();
+        priv_key
+            .public_key
+            .ok_or_else(|| TinkError::new("AndroidKeystoreSignerKeyManager: no public key"))?
+            .encode(&mut serialized_pub_key)
+            .map_err(|e| wrap_err("AndroidKeystoreSignerKeyManager: invalid public key", e))?;
+        Ok(tink_proto::KeyData {
+            type_url: tink_signature::ECDSA_VERIFIER_TYPE_URL.to_string(),
+            value: serialized_pub_key,
+            key_material_type: tink_proto::key_data::KeyMaterialType::AsymmetricPublic as i32,
+        })
+    }
+}
+
+/// Validate the given [`AndroidKeystoreEcdsaPrivateKey`](tink_proto::AndroidKeystoreEcdsaPrivateKey)
+/// and return the signature encoding.
+fn validate_key(
+    key: &tink_proto::AndroidKeystoreEcdsaPrivateKey,
+) -> Result<EcdsaSignatureEncoding, TinkError> {
+    tink_core::keyset::validate_key_version(key.version, ANDROID_KEYSTORE_SIGNER_KEY_VERSION)
+        .map_err(|e| wrap_err("AndroidKeystoreSignerKeyManager", e))?;
+    if key.key_alias.is_empty() {
+        return Err("AndroidKeystoreSignerKeyManager: no key alias".into());
+    }
+    let pub_key = key
+        .public_key
+        .as_ref()
+        .ok_or_else(|| TinkError::new("AndroidKeystoreSignerKeyManager: no public key"))?;
+    let params = pub_key
+        .params
+        .as_ref()
+        .ok_or_else(|| TinkError::new("AndroidKeystoreSignerKeyManager: no key parameters"))?;
+    let encoding = validate_params(params)?;
+    // Check the public key point is on the curve by creating a verifier.
+    tink_signature::subtle::EcdsaVerifier::new(
+        HashType::Sha256,
+        EllipticCurveType::NistP256,
+        encoding,
+        &pub_key.x,
+        &pub_key.y,
+    )
+    .map_err(|e| wrap_err("AndroidKeystoreSignerKeyManager: invalid public key", e))?;
+    Ok(encoding)
+}
+
+/// Check that the given [`EcdsaParams`](tink_proto::EcdsaParams) are supported by the Android
+/// Keystore signer, and return the signature encoding.
+fn validate_params(params: &tink_proto::EcdsaParams) -> Result<EcdsaSignatureEncoding, TinkError> {
+    if params.hash_type != HashType::Sha256 as i32 {
+        return Err("AndroidKeystoreSignerKeyManager: unsupported hash type".into());
+    }
+    if params.curve != EllipticCurveType::NistP256 as i32 {
+        return Err("AndroidKeystoreSignerKeyManager: unsupported curve".into());
+    }
+    match params.try_encoding() {
+        Ok(e @ EcdsaSignatureEncoding::Der) | Ok(e @ EcdsaSignatureEncoding::IeeeP1363) => Ok(e),
+        _ => Err("AndroidKeystoreSignerKeyManager: unsupported signature encoding".into()),
+    }
+}
@@ -0,0 +1,85 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Provide integration with the Android Keystore, for keys that never leave the device.
+
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#![deny(broken_intra_doc_links)]
+
+use std::sync::Arc;
+use tink_core::TinkError;
+
+mod android_keystore;
+pub use android_keystore::*;
+#[cfg(feature = "jni-bridge")]
+#[cfg_attr(docsrs, doc(cfg(feature = "jni-bridge")))]
+mod jni_keystore;
+#[cfg(feature = "jni-bridge")]
+pub use jni_keystore::*;
+mod key_templates;
+pub use key_templates::*;
+mod keystore_aead;
+pub use keystore_aead::*;
+mod keystore_aead_key_manager;
+pub use keystore_aead_key_manager::*;
+mod keystore_client;
+pub use keystore_client::*;
+mod keystore_signer;
+pub use keystore_signer::*;
+mod keystore_signer_key_manager;
+pub use keystore_signer_key_manager::*;
+mod software_keystore;
+pub use software_keystore::*;
+
+/// Prefix for the aliases of Keystore keys generated by the key managers in this crate.
+const KEY_ALIAS_PREFIX: &str = "tink_";
+/// Number of random bytes (hex-encoded) in generated key aliases.
+const KEY_ALIAS_RANDOM_SIZE: usize = 16;
+
+/// Register key managers for Android Keystore AEAD and signing keys, and a KMS client for
+/// `android-keystore://` key URIs, which all use `keystore` to access keys.  This also
+/// initializes the `tink-aead` and `tink-signature` crates.
+///
+/// Only one keystore can be registered; subsequent calls fail.
+pub fn register(keystore: Arc<dyn AndroidKeystore>) -> Result<(), TinkError> {
+    tink_aead::init();
+    tink_signature::init();
+    tink_core::registry::register_key_manager(Arc::new(AndroidKeystoreAeadKeyManager::new(
+        keystore.clone(),
+    )))?;
+    tink_core::registry::register_key_manager(Arc::new(AndroidKeystoreSignerKeyManager::new(
+        keystore.clone(),
+    )))?;
+    tink_core::registry::register_kms_client(AndroidKeystoreClient::new(keystore));
+    tink_core::registry::register_template_generator(
+        "ANDROID_KEYSTORE_AES256_GCM",
+        android_keystore_aes256_gcm_key_template,
+    );
+    tink_core::registry::register_template_generator(
+        "ANDROID_KEYSTORE_ECDSA_P256",
+        android_keystore_ecdsa_p256_key_template,
+    );
+    Ok(())
+}
+
+/// Return a new random alias for a Keystore key.
+fn new_key_alias() -> String {
+    let mut alias = KEY_ALIAS_PREFIX.to_string();
+    for b in tink_core::subtle::random::get_random_bytes(KEY_ALIAS_RANDOM_SIZE) {
+        alias.push_str(&format!("{:02x}", b));
+    }
+    alias
+}
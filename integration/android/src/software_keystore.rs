@@ -0,0 +1,133 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Software emulation of the Android Keystore, for testing.
+
+use crate::AndroidKeystore;
+use p256::ecdsa::signature::{Signature, Signer};
+use std::{collections::HashMap, sync::Mutex};
+use tink_core::{utils::wrap_err, Aead, TinkError};
+
+/// Error message for key map lock.
+const KERR: &str = "key map lock poisoned";
+/// Size of generated AES keys.
+const AES_KEY_SIZE: usize = 32;
+
+/// A key held by the [`SoftwareKeystore`].
+enum SoftwareKey {
+    AesGcm(tink_aead::subtle::AesGcm),
+    Ec(p256::ecdsa::SigningKey),
+}
+
+/// `SoftwareKeystore` is a test double for the Android Keystore, which holds keys in memory.
+///
+/// This provides no protection for key material, and should only be used for testing.
+#[derive(Default)]
+pub struct SoftwareKeystore {
+    keys: Mutex<HashMap<String, SoftwareKey>>,
+}
+
+impl std::fmt::Debug for SoftwareKeystore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SoftwareKeystore").finish()
+    }
+}
+
+impl SoftwareKeystore {
+    /// Return a new emulated Keystore, holding no keys.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store `key` under `alias`, failing if the alias is already in use.
+    fn insert(&self, alias: &str, key: SoftwareKey) -> Result<(), TinkError> {
+        let mut keys = self.keys.lock().expect(KERR); // safe: lock
+        if keys.contains_key(alias) {
+            return Err(format!("SoftwareKeystore: alias {} already in use", alias).into());
+        }
+        keys.insert(alias.to_string(), key);
+        Ok(())
+    }
+
+    /// Return the AES-GCM cipher for the key with the given alias.
+    fn aes_gcm(&self, alias: &str) -> Result<tink_aead::subtle::AesGcm, TinkError> {
+        let keys = self.keys.lock().expect(KERR); // safe: lock
+        match keys.get(alias) {
+            Some(SoftwareKey::AesGcm(cipher)) => Ok(cipher.clone()),
+            Some(_) => Err("SoftwareKeystore: not an AES key".into()),
+            None => Err("SoftwareKeystore: key not found".into()),
+        }
+    }
+}
+
+impl AndroidKeystore for SoftwareKeystore {
+    fn generate_aes_key(&self, alias: &str) -> Result<(), TinkError> {
+        let key = tink_core::subtle::random::get_random_bytes(AES_KEY_SIZE);
+        self.insert(
+            alias,
+            SoftwareKey::AesGcm(tink_aead::subtle::AesGcm::new(&key)?),
+        )
+    }
+
+    fn encrypt(
+        &self,
+        alias: &str,
+        plaintext: &[u8],
+        additional_data: &[u8],
+    ) -> Result<Vec<u8>, TinkError> {
+        self.aes_gcm(alias)?.encrypt(plaintext, additional_data)
+    }
+
+    fn decrypt(
+        &self,
+        alias: &str,
+        ciphertext: &[u8],
+        additional_data: &[u8],
+    ) -> Result<Vec<u8>, TinkError> {
+        self.aes_gcm(alias)?.decrypt(ciphertext, additional_data)
+    }
+
+    fn generate_ec_key(&self, alias: &str) -> Result<Vec<u8>, TinkError> {
+        let secret = tink_core::subtle::random::get_random_bytes(32);
+        let key = p256::ecdsa::SigningKey::from_bytes(&secret)
+            .map_err(|e| wrap_err("SoftwareKeystore: failed to generate key", e))?;
+        let point = p256::ecdsa::VerifyingKey::from(&key)
+            .to_encoded_point(/* compress= */ false)
+            .as_bytes()
+            .to_vec();
+        self.insert(alias, SoftwareKey::Ec(key))?;
+        Ok(point)
+    }
+
+    fn sign(&self, alias: &str, data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let keys = self.keys.lock().expect(KERR); // safe: lock
+        match keys.get(alias) {
+            Some(SoftwareKey::Ec(key)) => {
+                let sig: p256::ecdsa::Signature = key.sign(data);
+                Ok(sig.to_der().as_bytes().to_vec())
+            }
+            Some(_) => Err("SoftwareKeystore: not an EC key".into()),
+            None => Err("SoftwareKeystore: key not found".into()),
+        }
+    }
+
+    fn delete_key(&self, alias: &str) -> Result<(), TinkError> {
+        let mut keys = self.keys.lock().expect(KERR); // safe: lock
+        keys.remove(alias)
+            .map(|_| ())
+            .ok_or_else(|| TinkError::new("SoftwareKeystore: key not found"))
+    }
+}
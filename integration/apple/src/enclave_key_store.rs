@@ -0,0 +1,32 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Trait definition for hardware-backed key stores.
+
+use tink_core::TinkError;
+
+/// `EnclaveKeyStore` represents a store of ECDSA P-256 private keys (such as the Secure Enclave)
+/// that can sign with its keys but never reveals them.  Keys are identified by an opaque
+/// application label.
+pub trait EnclaveKeyStore: Send + Sync {
+    /// Generate a new P-256 key pair in the store, returning its application label and its
+    /// public key as an uncompressed SEC1 point.
+    fn generate_key(&self) -> Result<(Vec<u8>, Vec<u8>), TinkError>;
+
+    /// Sign `data` with ECDSA-SHA256 using the key with the given application label, returning
+    /// the DER-encoded signature.
+    fn sign(&self, application_label: &[u8], data: &[u8]) -> Result<Vec<u8>, TinkError>;
+}
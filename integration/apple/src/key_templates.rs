@@ -0,0 +1,42 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Key templates for Secure Enclave keys.
+
+use tink_proto::{prost::Message, KeyTemplate};
+
+/// Return a [`KeyTemplate`] that generates a new ECDSA private key in the Secure Enclave, with
+/// the following parameters:
+///   - Hash function: SHA256
+///   - Curve: NIST P-256
+///   - Signature encoding: DER
+///   - Output prefix type: TINK
+pub fn secure_enclave_p256_key_template() -> KeyTemplate {
+    let format = tink_proto::SecureEnclaveEcdsaKeyFormat {
+        params: Some(tink_proto::EcdsaParams {
+            hash_type: tink_proto::HashType::Sha256 as i32,
+            curve: tink_proto::EllipticCurveType::NistP256 as i32,
+            encoding: tink_proto::EcdsaSignatureEncoding::Der as i32,
+        }),
+    };
+    let mut serialized_format = Vec::new();
+    format.encode(&mut serialized_format).unwrap(); // safe: proto-encode
+    KeyTemplate {
+        type_url: crate::SECURE_ENCLAVE_SIGNER_TYPE_URL.to_string(),
+        value: serialized_format,
+        output_prefix_type: tink_proto::OutputPrefixType::Tink as i32,
+    }
+}
@@ -0,0 +1,99 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Secure Enclave access via the Keychain Services API of the Security framework.
+
+use crate::EnclaveKeyStore;
+use security_framework::{
+    item::{ItemClass, ItemSearchOptions, KeyClass, Location, Reference, SearchResult},
+    key::{Algorithm, GenerateKeyOptions, KeyType, SecKey, Token},
+};
+use tink_core::{utils::wrap_err, TinkError};
+
+/// Size in bits of the P-256 keys supported by the Secure Enclave.
+const P256_KEY_BITS: u32 = 256;
+
+/// `Keychain` is an [`EnclaveKeyStore`] that generates and uses P-256 keys in the Secure Enclave
+/// of the current device.
+///
+/// Keys are stored as permanent items in the data protection keychain, so the calling
+/// application needs the appropriate keychain access group entitlement.
+#[derive(Debug, Default)]
+pub struct Keychain {
+    label: Option<String>,
+}
+
+impl Keychain {
+    /// Return a key store for the Secure Enclave of the current device.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return a key store that attaches the given user-visible label to keys it generates.
+    pub fn new_with_label(label: &str) -> Self {
+        Keychain {
+            label: Some(label.to_string()),
+        }
+    }
+
+    /// Find the private key with the given application label.
+    fn find_key(&self, application_label: &[u8]) -> Result<SecKey, TinkError> {
+        let results = ItemSearchOptions::new()
+            .class(ItemClass::key())
+            .key_class(KeyClass::private())
+            .application_label(application_label)
+            .load_refs(true)
+            .search()
+            .map_err(|e| wrap_err("Keychain: key search failed", e))?;
+        results
+            .into_iter()
+            .find_map(|r| match r {
+                SearchResult::Ref(Reference::Key(key)) => Some(key),
+                _ => None,
+            })
+            .ok_or_else(|| TinkError::new("Keychain: key not found"))
+    }
+}
+
+impl EnclaveKeyStore for Keychain {
+    fn generate_key(&self) -> Result<(Vec<u8>, Vec<u8>), TinkError> {
+        let mut options = GenerateKeyOptions::default();
+        options
+            .set_key_type(KeyType::ec())
+            .set_size_in_bits(P256_KEY_BITS)
+            .set_token(Token::SecureEnclave)
+            .set_location(Location::DataProtectionKeychain);
+        if let Some(label) = &self.label {
+            options.set_label(label.clone());
+        }
+        let key =
+            SecKey::new(&options).map_err(|e| wrap_err("Keychain: key generation failed", e))?;
+        let application_label = key
+            .application_label()
+            .ok_or_else(|| TinkError::new("Keychain: no application label"))?;
+        let point = key
+            .public_key()
+            .and_then(|k| k.external_representation())
+            .ok_or_else(|| TinkError::new("Keychain: failed to export public key"))?;
+        Ok((application_label, point.to_vec()))
+    }
+
+    fn sign(&self, application_label: &[u8], data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let key = self.find_key(application_label)?;
+        key.create_signature(Algorithm::ECDSASignatureMessageX962SHA256, data)
+            .map_err(|e| wrap_err("Keychain: signing failed", e))
+    }
+}
@@ -0,0 +1,54 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Provide integration with the Apple Secure Enclave, for signing with hardware-protected keys.
+
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#![deny(broken_intra_doc_links)]
+
+use std::sync::Arc;
+use tink_core::TinkError;
+
+mod enclave_key_store;
+pub use enclave_key_store::*;
+mod key_templates;
+pub use key_templates::*;
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+#[cfg_attr(docsrs, doc(cfg(any(target_os = "macos", target_os = "ios"))))]
+mod keychain;
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+pub use keychain::*;
+mod secure_enclave_signer;
+pub use secure_enclave_signer::*;
+mod secure_enclave_signer_key_manager;
+pub use secure_enclave_signer_key_manager::*;
+mod software_enclave;
+pub use software_enclave::*;
+
+/// Register a key manager for Secure Enclave signing keys, which uses `store` to generate keys
+/// and to sign.  This also initializes the `tink-signature` crate, so that signatures can be
+/// verified with the corresponding public keys.
+///
+/// Only one key store can be registered; subsequent calls fail.
+pub fn register(store: Arc<dyn EnclaveKeyStore>) -> Result<(), TinkError> {
+    tink_signature::init();
+    tink_core::registry::register_key_manager(Arc::new(SecureEnclaveSignerKeyManager::new(store)))?;
+    tink_core::registry::register_template_generator(
+        "SECURE_ENCLAVE_P256",
+        secure_enclave_p256_key_template,
+    );
+    Ok(())
+}
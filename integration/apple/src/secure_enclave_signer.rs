@@ -0,0 +1,68 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Signer that delegates to a hardware-backed key store.
+
+use crate::EnclaveKeyStore;
+use std::sync::Arc;
+use tink_core::{utils::wrap_err, TinkError};
+use tink_proto::EcdsaSignatureEncoding;
+
+/// `SecureEnclaveSigner` is an implementation of [`tink_core::Signer`] that signs with a P-256
+/// key held in an [`EnclaveKeyStore`].
+#[derive(Clone)]
+pub struct SecureEnclaveSigner {
+    store: Arc<dyn EnclaveKeyStore>,
+    application_label: Vec<u8>,
+    encoding: EcdsaSignatureEncoding,
+}
+
+impl SecureEnclaveSigner {
+    /// Create a new [`SecureEnclaveSigner`] for the key with the given application label, which
+    /// emits signatures with the given encoding.
+    pub fn new(
+        store: Arc<dyn EnclaveKeyStore>,
+        application_label: &[u8],
+        encoding: EcdsaSignatureEncoding,
+    ) -> Result<Self, TinkError> {
+        match encoding {
+            EcdsaSignatureEncoding::Der | EcdsaSignatureEncoding::IeeeP1363 => {}
+            _ => return Err("SecureEnclaveSigner: unsupported encoding".into()),
+        }
+        Ok(SecureEnclaveSigner {
+            store,
+            application_label: application_label.to_vec(),
+            encoding,
+        })
+    }
+}
+
+impl tink_core::Signer for SecureEnclaveSigner {
+    fn sign(&self, data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let der = self
+            .store
+            .sign(&self.application_label, data)
+            .map_err(|e| wrap_err("SecureEnclaveSigner: signing failed", e))?;
+        match self.encoding {
+            EcdsaSignatureEncoding::IeeeP1363 => {
+                let sig = p256::ecdsa::Signature::from_der(&der)
+                    .map_err(|e| wrap_err("SecureEnclaveSigner: invalid signature", e))?;
+                Ok(sig.as_ref().to_vec())
+            }
+            _ => Ok(der),
+        }
+    }
+}
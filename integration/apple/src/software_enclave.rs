@@ -0,0 +1,79 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Software emulation of the Secure Enclave, for testing.
+
+use crate::EnclaveKeyStore;
+use p256::ecdsa::signature::{Signature, Signer};
+use std::{collections::HashMap, sync::Mutex};
+use tink_core::{utils::wrap_err, TinkError};
+
+/// Error message for key map lock.
+const KERR: &str = "key map lock poisoned";
+/// Size of generated application labels, matching the SHA-1 labels used by the Keychain.
+const LABEL_SIZE: usize = 20;
+
+/// `SoftwareEnclave` is a test double for the Secure Enclave, which holds P-256 keys in memory.
+///
+/// This provides no protection for key material, and should only be used for testing.
+#[derive(Default)]
+pub struct SoftwareEnclave {
+    keys: Mutex<HashMap<Vec<u8>, p256::ecdsa::SigningKey>>,
+}
+
+impl std::fmt::Debug for SoftwareEnclave {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SoftwareEnclave").finish()
+    }
+}
+
+impl SoftwareEnclave {
+    /// Return a new emulated Secure Enclave, holding no keys.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Delete the key with the given application label, returning whether it was present.
+    pub fn delete_key(&self, application_label: &[u8]) -> bool {
+        let mut keys = self.keys.lock().expect(KERR); // safe: lock
+        keys.remove(application_label).is_some()
+    }
+}
+
+impl EnclaveKeyStore for SoftwareEnclave {
+    fn generate_key(&self) -> Result<(Vec<u8>, Vec<u8>), TinkError> {
+        let secret = tink_core::subtle::random::get_random_bytes(32);
+        let key = p256::ecdsa::SigningKey::from_bytes(&secret)
+            .map_err(|e| wrap_err("SoftwareEnclave: failed to generate key", e))?;
+        let point = p256::ecdsa::VerifyingKey::from(&key)
+            .to_encoded_point(/* compress= */ false)
+            .as_bytes()
+            .to_vec();
+        let label = tink_core::subtle::random::get_random_bytes(LABEL_SIZE);
+        let mut keys = self.keys.lock().expect(KERR); // safe: lock
+        keys.insert(label.clone(), key);
+        Ok((label, point))
+    }
+
+    fn sign(&self, application_label: &[u8], data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let keys = self.keys.lock().expect(KERR); // safe: lock
+        let key = keys
+            .get(application_label)
+            .ok_or_else(|| TinkError::new("SoftwareEnclave: key not found"))?;
+        let sig: p256::ecdsa::Signature = key.sign(data);
+        Ok(sig.to_der().as_bytes().to_vec())
+    }
+}
@@ -25,6 +25,8 @@ use tink_core::utils::wrap_err;
 pub struct AwsAead {
     key_uri: String,
     kms: rusoto_kms::KmsClient,
+    // Encryption context to use in place of one derived from the additional data.
+    encryption_context: Option<HashMap<String, String>>,
     // The Tokio runtime to execute KMS requests on, wrapped in:
     //  - a `RefCell` for interior mutability (the [`tink_core::Aead`] trait's methods take
     //    `&self`)
@@ -43,6 +45,7 @@ impl AwsAead {
         Ok(AwsAead {
             key_uri: key_uri.to_string(),
             kms,
+            encryption_context: None,
             runtime: Rc::new(RefCell::new(
                 tokio::runtime::Builder::new_current_thread()
                     .enable_all()
@@ -51,22 +54,80 @@ impl AwsAead {
             )),
         })
     }
-}
 
-impl tink_core::Aead for AwsAead {
-    fn encrypt(
+    /// Return a new AWS KMS service that passes `encryption_context` to KMS unchanged, and
+    /// which therefore only accepts empty additional data.
+    pub(crate) fn new_with_context(
+        key_uri: &str,
+        kms: rusoto_kms::KmsClient,
+        encryption_context: HashMap<String, String>,
+    ) -> Result<AwsAead, tink_core::TinkError> {
+        Ok(AwsAead {
+            encryption_context: Some(encryption_context),
+            ..Self::new(key_uri, kms)?
+        })
+    }
+
+    /// Generate a new data key of `key_size` bytes, encrypted under this KMS key with an empty
+    /// encryption context.
+    pub(crate) fn generate_data_key(
+        &self,
+        key_size: usize,
+    ) -> Result<tink_core::registry::DataKey, tink_core::TinkError> {
+        let req = rusoto_kms::GenerateDataKeyRequest {
+            encryption_context: None,
+            grant_tokens: None,
+            key_id: self.key_uri.clone(),
+            key_spec: None,
+            number_of_bytes: Some(key_size as i64),
+        };
+        let rsp = self
+            .runtime
+            .borrow_mut()
+            .block_on(self.kms.generate_data_key(req))
+            .map_err(|e| wrap_err("request failed", e))?;
+        match (rsp.plaintext, rsp.ciphertext_blob) {
+            (Some(plaintext), Some(ciphertext)) => Ok(tink_core::registry::DataKey {
+                plaintext: plaintext.to_vec(),
+                ciphertext: ciphertext.to_vec(),
+            }),
+            _ => Err("no data key in response".into()),
+        }
+    }
+
+    /// Build the encryption context for a request with the given additional data.
+    fn encryption_context(
         &self,
-        plaintext: &[u8],
         additional_data: &[u8],
-    ) -> Result<Vec<u8>, tink_core::TinkError> {
+    ) -> Result<Option<HashMap<String, String>>, tink_core::TinkError> {
+        if let Some(context) = &self.encryption_context {
+            if !additional_data.is_empty() {
+                return Err("additional data not supported with fixed encryption context".into());
+            }
+            return Ok(if context.is_empty() {
+                None
+            } else {
+                Some(context.clone())
+            });
+        }
         let ad = hex::encode(additional_data);
-        let encryption_context = if ad.is_empty() {
+        Ok(if ad.is_empty() {
             None
         } else {
             let mut context = HashMap::new();
             context.insert("additionalData".to_string(), ad);
             Some(context)
-        };
+        })
+    }
+}
+
+impl tink_core::Aead for AwsAead {
+    fn encrypt(
+        &self,
+        plaintext: &[u8],
+        additional_data: &[u8],
+    ) -> Result<Vec<u8>, tink_core::TinkError> {
+        let encryption_context = self.encryption_context(additional_data)?;
         let req = rusoto_kms::EncryptRequest {
             encryption_algorithm: None, // use default
             grant_tokens: None,
@@ -99,14 +160,7 @@ impl tink_core::Aead for AwsAead {
         ciphertext: &[u8],
         additional_data: &[u8],
     ) -> Result<Vec<u8>, tink_core::TinkError> {
-        let ad = hex::encode(additional_data);
-        let encryption_context = if ad.is_empty() {
-            None
-        } else {
-            let mut context = HashMap::new();
-            context.insert("additionalData".to_string(), ad);
-            Some(context)
-        };
+        let encryption_context = self.encryption_context(additional_data)?;
         let req = rusoto_kms::DecryptRequest {
             ciphertext_blob: ciphertext.to_vec().into(),
             encryption_algorithm: None, // use default
@@ -113,11 +113,75 @@ impl tink_core::registry::KmsClient for AwsClient {
         key_uri.starts_with(&self.key_uri_prefix)
     }
 
+    /// Additional data is passed to AWS KMS as encryption context.
+    fn supports_associated_data(&self, key_uri: &str) -> bool {
+        self.supported(key_uri)
+    }
+
     /// Get an AEAD backed by `key_uri`.
     /// `key_uri` must have the following format: `aws-kms://arn:<partition>:kms:<region>:[:path]`.
     /// See <http://docs.aws.amazon.com/general/latest/gr/aws-arns-and-namespaces.html>.
     fn get_aead(&self, key_uri: &str) -> Result<Box<dyn tink_core::Aead>, tink_core::TinkError> {
-        if !self.supported(key_uri) {
+        let uri = self.key_arn(key_uri)?;
+        Ok(Box::new(crate::AwsAead::new(uri, self.kms.clone())?))
+    }
+
+    fn capabilities(&self, key_uri: &str) -> tink_core::registry::KmsCapabilities {
+        let supported = self.supported(key_uri);
+        tink_core::registry::KmsCapabilities {
+            associated_data: supported,
+            generate_data_key: supported,
+            sign: supported,
+        }
+    }
+
+    /// Get a signer for the asymmetric ECDSA key at `key_uri` (key spec `ECC_NIST_P256` or
+    /// `ECC_NIST_P384`), which signs with AWS KMS `Sign`.
+    fn get_signer(&self, key_uri: &str) -> Result<Box<dyn tink_core::Signer>, TinkError> {
+        let uri = self.key_arn(key_uri)?;
+        Ok(Box::new(crate::AwsSigner::new(uri, self.kms.clone())?))
+    }
+
+    /// Get the public key of the asymmetric ECDSA key at `key_uri` with AWS KMS `GetPublicKey`,
+    /// as a Tink ECDSA public key.
+    fn get_public_key_data(&self, key_uri: &str) -> Result<tink_proto::KeyData, TinkError> {
+        let uri = self.key_arn(key_uri)?;
+        crate::aws_kms_signer::public_key_data(uri, &self.kms)
+    }
+
+    /// Generate a data key with AWS KMS `GenerateDataKey`.
+    fn generate_data_key(
+        &self,
+        key_uri: &str,
+        key_size: usize,
+    ) -> Result<tink_core::registry::DataKey, tink_core::TinkError> {
+        let uri = self.key_arn(key_uri)?;
+        crate::AwsAead::new(uri, self.kms.clone())?.generate_data_key(key_size)
+    }
+
+    /// Get an AEAD backed by `key_uri` that uses `context` as the AWS KMS encryption context.
+    fn get_aead_with_context(
+        &self,
+        key_uri: &str,
+        context: &std::collections::BTreeMap<String, String>,
+    ) -> Result<Box<dyn tink_core::Aead>, tink_core::TinkError> {
+        let uri = self.key_arn(key_uri)?;
+        let context = context
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        Ok(Box::new(crate::AwsAead::new_with_context(
+            uri,
+            self.kms.clone(),
+            context,
+        )?))
+    }
+}
+
+impl AwsClient {
+    /// Check that `key_uri` is supported, and return it without the URI scheme.
+    fn key_arn<'a>(&self, key_uri: &'a str) -> Result<&'a str, TinkError> {
+        if !tink_core::registry::KmsClient::supported(self, key_uri) {
             return Err(format!(
                 "key_uri must start with prefix {}, but got {}",
                 self.key_uri_prefix, key_uri
@@ -125,12 +189,11 @@ impl tink_core::registry::KmsClient for AwsClient {
             .into());
         }
 
-        let uri = if let Some(stripped) = key_uri.strip_prefix(AWS_PREFIX) {
+        Ok(if let Some(stripped) = key_uri.strip_prefix(AWS_PREFIX) {
             stripped
         } else {
             key_uri
-        };
-        Ok(Box::new(crate::AwsAead::new(uri, self.kms.clone())?))
+        })
     }
 }
 
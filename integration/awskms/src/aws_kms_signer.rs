@@ -0,0 +1,183 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Signing functionality via asymmetric AWS KMS keys.
+
+use rusoto_kms::Kms;
+use std::{cell::RefCell, rc::Rc};
+use tink_core::{utils::wrap_err, TinkError};
+use tink_proto::{prost::Message, EcdsaSignatureEncoding, EllipticCurveType, HashType};
+
+/// Type URL of the Tink public keys returned for AWS KMS signing keys.
+const ECDSA_PUBLIC_KEY_TYPE_URL: &str = "type.googleapis.com/google.crypto.tink.EcdsaPublicKey";
+
+/// An AWS KMS signing key specification that maps onto a Tink ECDSA key.
+struct EcdsaKeySpec {
+    /// DER encoding of the start of a `SubjectPublicKeyInfo` for the curve, up to the
+    /// uncompressed point.
+    spki_prefix: &'static [u8],
+    /// Length of each coordinate of a point on the curve.
+    coordinate_len: usize,
+    hash: HashType,
+    curve: EllipticCurveType,
+    /// The AWS KMS signing algorithm that matches `hash`.
+    signing_algorithm: &'static str,
+}
+
+/// The supported key specifications: `ECC_NIST_P256` and `ECC_NIST_P384`.  AWS KMS produces
+/// DER-encoded ECDSA signatures, as for Tink's `DER` signature encoding.
+const KEY_SPECS: &[EcdsaKeySpec] = &[
+    EcdsaKeySpec {
+        spki_prefix: &[
+            0x30, 0x59, 0x30, 0x13, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06,
+            0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07, 0x03, 0x42, 0x00, 0x04,
+        ],
+        coordinate_len: 32,
+        hash: HashType::Sha256,
+        curve: EllipticCurveType::NistP256,
+        signing_algorithm: "ECDSA_SHA_256",
+    },
+    EcdsaKeySpec {
+        spki_prefix: &[
+            0x30, 0x76, 0x30, 0x10, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06,
+            0x05, 0x2b, 0x81, 0x04, 0x00, 0x22, 0x03, 0x62, 0x00, 0x04,
+        ],
+        coordinate_len: 48,
+        hash: HashType::Sha384,
+        curve: EllipticCurveType::NistP384,
+        signing_algorithm: "ECDSA_SHA_384",
+    },
+];
+
+/// `AwsSigner` signs with an asymmetric ECDSA key held in AWS KMS.  Data is hashed locally, and
+/// only the digest is sent to AWS KMS.
+#[derive(Clone)]
+pub struct AwsSigner {
+    key_uri: String,
+    kms: rusoto_kms::KmsClient,
+    spec: &'static EcdsaKeySpec,
+    // The Tokio runtime to execute KMS requests on; see [`AwsAead`](crate::AwsAead).
+    runtime: Rc<RefCell<tokio::runtime::Runtime>>,
+}
+
+impl AwsSigner {
+    /// Return a new signer for the AWS KMS key at `key_uri`, which must be in the format
+    /// `arn:<partition>:kms:<region>:[:path]`.  The public key is fetched from AWS KMS to
+    /// determine the signing algorithm.
+    pub(crate) fn new(key_uri: &str, kms: rusoto_kms::KmsClient) -> Result<AwsSigner, TinkError> {
+        let runtime = new_runtime()?;
+        let (spec, _) = get_public_key(&runtime, &kms, key_uri)?;
+        Ok(AwsSigner {
+            key_uri: key_uri.to_string(),
+            kms,
+            spec,
+            runtime: Rc::new(RefCell::new(runtime)),
+        })
+    }
+}
+
+impl tink_core::Signer for AwsSigner {
+    fn sign(&self, data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let mut hash_func = tink_core::subtle::get_hash_func(self.spec.hash)
+            .ok_or_else(|| TinkError::new("unsupported hash function"))?;
+        let digest = tink_core::subtle::compute_hash(&mut hash_func, data)?;
+        let req = rusoto_kms::SignRequest {
+            grant_tokens: None,
+            key_id: self.key_uri.clone(),
+            message: digest.into(),
+            message_type: Some("DIGEST".to_string()),
+            signing_algorithm: self.spec.signing_algorithm.to_string(),
+        };
+        let rsp = self
+            .runtime
+            .borrow_mut()
+            .block_on(self.kms.sign(req))
+            .map_err(|e| wrap_err("request failed", e))?;
+        match rsp.signature {
+            None => Err("no signature in response".into()),
+            Some(sig) => Ok(sig.to_vec()),
+        }
+    }
+}
+
+/// Return the Tink [`KeyData`](tink_proto::KeyData) for the public key of the AWS KMS signing key
+/// at `key_uri`, as an ECDSA public key with `DER` signature encoding.
+pub(crate) fn public_key_data(
+    key_uri: &str,
+    kms: &rusoto_kms::KmsClient,
+) -> Result<tink_proto::KeyData, TinkError> {
+    let runtime = new_runtime()?;
+    let (spec, point) = get_public_key(&runtime, kms, key_uri)?;
+    let (x, y) = point.split_at(spec.coordinate_len);
+    let key = tink_proto::EcdsaPublicKey {
+        version: 0,
+        params: Some(tink_proto::EcdsaParams {
+            hash_type: spec.hash as i32,
+            curve: spec.curve as i32,
+            encoding: EcdsaSignatureEncoding::Der as i32,
+        }),
+        x: x.to_vec(),
+        y: y.to_vec(),
+    };
+    let mut value = Vec::new();
+    key.encode(&mut value)
+        .map_err(|e| wrap_err("failed to encode public key", e))?;
+    Ok(tink_proto::KeyData {
+        type_url: ECDSA_PUBLIC_KEY_TYPE_URL.to_string(),
+        value,
+        key_material_type: tink_proto::key_data::KeyMaterialType::AsymmetricPublic as i32,
+    })
+}
+
+fn new_runtime() -> Result<tokio::runtime::Runtime, TinkError> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| wrap_err("failed to build tokio runtime", e))
+}
+
+/// Fetch the public key of the AWS KMS signing key at `key_uri`, returning its key specification
+/// and the concatenated coordinates of the public point.
+fn get_public_key(
+    runtime: &tokio::runtime::Runtime,
+    kms: &rusoto_kms::KmsClient,
+    key_uri: &str,
+) -> Result<(&'static EcdsaKeySpec, Vec<u8>), TinkError> {
+    let req = rusoto_kms::GetPublicKeyRequest {
+        grant_tokens: None,
+        key_id: key_uri.to_string(),
+    };
+    let rsp = runtime
+        .block_on(kms.get_public_key(req))
+        .map_err(|e| wrap_err("request failed", e))?;
+    if rsp.key_usage.as_deref() != Some("SIGN_VERIFY") {
+        return Err("key is not a signing key".into());
+    }
+    let spki = rsp
+        .public_key
+        .ok_or_else(|| TinkError::new("no public key in response"))?;
+    KEY_SPECS
+        .iter()
+        .find_map(|spec| {
+            let point = spki.strip_prefix(spec.spki_prefix)?;
+            if point.len() == 2 * spec.coordinate_len {
+                Some((spec, point.to_vec()))
+            } else {
+                None
+            }
+        })
+        .ok_or_else(|| "unsupported key spec: only ECC_NIST_P256 and ECC_NIST_P384".into())
+}
@@ -20,6 +20,8 @@ mod aws_kms_aead;
 use aws_kms_aead::*;
 mod aws_kms_client;
 pub use aws_kms_client::*;
+mod aws_kms_signer;
+pub use aws_kms_signer::AwsSigner;
 
 /// The [upstream Tink](https://github.com/google/tink) version that this Rust
 /// port is based on.
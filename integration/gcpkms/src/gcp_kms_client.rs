@@ -72,6 +72,12 @@ impl tink_core::registry::KmsClient for GcpClient {
     fn supported(&self, key_uri: &str) -> bool {
         key_uri.starts_with(&self.key_uri_prefix)
     }
+
+    /// Additional data is passed to Cloud KMS as additional authenticated data.
+    fn supports_associated_data(&self, key_uri: &str) -> bool {
+        self.supported(key_uri)
+    }
+
     fn get_aead(&self, key_uri: &str) -> Result<Box<dyn tink_core::Aead>, tink_core::TinkError> {
         if !self.supported(key_uri) {
             return Err("unsupported key_uri".into());
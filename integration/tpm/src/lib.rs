@@ -0,0 +1,34 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Provide integration with TPM 2.0 devices, for protecting keysets at rest.
+
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#![deny(broken_intra_doc_links)]
+
+mod software_tpm;
+pub use software_tpm::*;
+mod tpm_aead;
+use tpm_aead::*;
+mod tpm_client;
+pub use tpm_client::*;
+mod tpm_device;
+pub use tpm_device::*;
+#[cfg(feature = "tss")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tss")))]
+mod tss_tpm;
+#[cfg(feature = "tss")]
+pub use tss_tpm::*;
@@ -0,0 +1,113 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Software emulation of a TPM, for testing.
+
+use crate::{TpmDevice, PCR_COUNT};
+use sha2::{Digest, Sha256};
+use std::sync::Mutex;
+use tink_core::{Aead, TinkError};
+
+/// Size of a SHA-256 PCR value.
+const PCR_SIZE: usize = 32;
+
+/// Error message for PCR bank lock.
+const PERR: &str = "PCR bank lock poisoned";
+
+/// `SoftwareTpm` is a test double for a TPM device, which emulates sealing to PCR state in
+/// software.  Sealed data is encrypted under a random storage key held in memory, so can only
+/// be unsealed by the same instance.
+///
+/// This provides no protection for key material, and should only be used for testing.
+pub struct SoftwareTpm {
+    storage_key: tink_aead::subtle::AesGcm,
+    pcrs: Mutex<[[u8; PCR_SIZE]; PCR_COUNT as usize]>,
+}
+
+impl Default for SoftwareTpm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for SoftwareTpm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SoftwareTpm").finish()
+    }
+}
+
+impl SoftwareTpm {
+    /// Return a new emulated TPM, with all PCRs zero.
+    pub fn new() -> Self {
+        let key = tink_core::subtle::random::get_random_bytes(32);
+        SoftwareTpm {
+            storage_key: tink_aead::subtle::AesGcm::new(&key).unwrap(), // safe: valid key size
+            pcrs: Mutex::new([[0; PCR_SIZE]; PCR_COUNT as usize]),
+        }
+    }
+
+    /// Extend PCR `index` with `data`, as done when measuring a boot component: the new value of
+    /// the PCR is the hash of its old value concatenated with the hash of `data`.
+    pub fn extend_pcr(&self, index: u8, data: &[u8]) -> Result<(), TinkError> {
+        check_index(index)?;
+        let mut pcrs = self.pcrs.lock().expect(PERR); // safe: lock
+        let pcr = &mut pcrs[index as usize];
+        let mut h = Sha256::new();
+        h.update(&pcr[..]);
+        h.update(Sha256::digest(data));
+        pcr.copy_from_slice(&h.finalize());
+        Ok(())
+    }
+
+    /// Return the current value of PCR `index`.
+    pub fn pcr_value(&self, index: u8) -> Result<[u8; PCR_SIZE], TinkError> {
+        check_index(index)?;
+        let pcrs = self.pcrs.lock().expect(PERR); // safe: lock
+        Ok(pcrs[index as usize])
+    }
+
+    /// Return the selected PCR indices followed by a digest of their current values.
+    fn policy(&self, selection: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let pcrs = self.pcrs.lock().expect(PERR); // safe: lock
+        let mut h = Sha256::new();
+        for index in selection {
+            check_index(*index)?;
+            h.update(&pcrs[*index as usize]);
+        }
+        let mut policy = selection.to_vec();
+        policy.extend_from_slice(&h.finalize());
+        Ok(policy)
+    }
+}
+
+impl TpmDevice for SoftwareTpm {
+    fn seal(&self, data: &[u8], pcrs: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.storage_key.encrypt(data, &self.policy(pcrs)?)
+    }
+
+    fn unseal(&self, blob: &[u8], pcrs: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.storage_key
+            .decrypt(blob, &self.policy(pcrs)?)
+            .map_err(|_| TinkError::new("PCR policy check failed"))
+    }
+}
+
+fn check_index(index: u8) -> Result<(), TinkError> {
+    if index >= PCR_COUNT {
+        return Err(format!("invalid PCR index {}", index).into());
+    }
+    Ok(())
+}
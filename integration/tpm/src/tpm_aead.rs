@@ -0,0 +1,75 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! AEAD functionality via a TPM.
+
+use crate::TpmDevice;
+use std::{convert::TryInto, sync::Arc};
+use tink_core::{utils::wrap_err, Aead, TinkError};
+
+/// Size of the AES-256-GCM data-encryption keys sealed by the TPM.
+const DEK_SIZE: usize = 32;
+/// Size of the big-endian length of the sealed data-encryption key.
+const LEN_SIZE: usize = 4;
+
+/// `TpmAead` is an AEAD that encrypts data under a fresh AES-256-GCM data-encryption key,
+/// which is sealed by a TPM to a set of PCRs.  The ciphertext holds the length of the sealed
+/// key, the sealed key, and the AES-GCM ciphertext.
+#[derive(Clone)]
+pub(crate) struct TpmAead {
+    pcrs: Vec<u8>,
+    device: Arc<dyn TpmDevice>,
+}
+
+impl TpmAead {
+    pub(crate) fn new(pcrs: Vec<u8>, device: Arc<dyn TpmDevice>) -> TpmAead {
+        TpmAead { pcrs, device }
+    }
+}
+
+impl Aead for TpmAead {
+    fn encrypt(&self, plaintext: &[u8], additional_data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let dek = tink_core::subtle::random::get_random_bytes(DEK_SIZE);
+        let sealed = self
+            .device
+            .seal(&dek, &self.pcrs)
+            .map_err(|e| wrap_err("tpm: failed to seal key", e))?;
+        let ct = tink_aead::subtle::AesGcm::new(&dek)?.encrypt(plaintext, additional_data)?;
+
+        let mut ret = Vec::with_capacity(LEN_SIZE + sealed.len() + ct.len());
+        ret.extend_from_slice(&(sealed.len() as u32).to_be_bytes());
+        ret.extend_from_slice(&sealed);
+        ret.extend_from_slice(&ct);
+        Ok(ret)
+    }
+
+    fn decrypt(&self, ciphertext: &[u8], additional_data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        if ciphertext.len() < LEN_SIZE {
+            return Err("tpm: ciphertext too short".into());
+        }
+        let (len, rest) = ciphertext.split_at(LEN_SIZE);
+        let len = u32::from_be_bytes(len.try_into().unwrap()) as usize; // safe: checked len
+        if rest.len() < len {
+            return Err("tpm: ciphertext too short".into());
+        }
+        let (sealed, ct) = rest.split_at(len);
+        let dek = self
+            .device
+            .unseal(sealed, &self.pcrs)
+            .map_err(|e| wrap_err("tpm: failed to unseal key", e))?;
+        tink_aead::subtle::AesGcm::new(&dek)?.decrypt(ct, additional_data)
+    }
+}
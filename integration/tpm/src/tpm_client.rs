@@ -0,0 +1,109 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! TPM client code.
+
+use crate::{TpmDevice, PCR_COUNT};
+use std::sync::Arc;
+use tink_core::TinkError;
+
+/// Prefix for any TPM key URIs.
+pub const TPM_PREFIX: &str = "tpm://";
+/// Name of the PCR bank that data is sealed to.
+const PCR_BANK: &str = "sha256";
+
+/// `TpmClient` represents a client that seals keyset-encryption keys with a TPM.
+///
+/// Key URIs have the form `tpm://sha256:<pcrs>`, where `<pcrs>` is a comma-separated list of
+/// PCR indices that data is sealed to (which may be empty, to bind data to the device only).
+#[derive(Clone)]
+pub struct TpmClient {
+    key_uri_prefix: String,
+    device: Arc<dyn TpmDevice>,
+}
+
+impl std::fmt::Debug for TpmClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TpmClient")
+            .field("key_uri_prefix", &self.key_uri_prefix)
+            .finish()
+    }
+}
+
+impl TpmClient {
+    /// Return a new TPM client which will use `device` to handle keys with `uri_prefix` prefix.
+    /// `uri_prefix` must start with `tpm://`.
+    pub fn new(uri_prefix: &str, device: Arc<dyn TpmDevice>) -> Result<TpmClient, TinkError> {
+        if !uri_prefix.to_lowercase().starts_with(TPM_PREFIX) {
+            return Err(format!(
+                "uri_prefix must start with {}, but got {}",
+                TPM_PREFIX, uri_prefix
+            )
+            .into());
+        }
+        Ok(TpmClient {
+            key_uri_prefix: uri_prefix.to_string(),
+            device,
+        })
+    }
+}
+
+impl tink_core::registry::KmsClient for TpmClient {
+    fn supported(&self, key_uri: &str) -> bool {
+        key_uri.starts_with(&self.key_uri_prefix)
+    }
+
+    /// Get an AEAD backed by `key_uri`.
+    /// `key_uri` must have the following format: `tpm://sha256:<pcrs>`.
+    fn get_aead(&self, key_uri: &str) -> Result<Box<dyn tink_core::Aead>, TinkError> {
+        if !self.supported(key_uri) {
+            return Err(format!(
+                "key_uri must start with prefix {}, but got {}",
+                self.key_uri_prefix, key_uri
+            )
+            .into());
+        }
+        let pcrs = parse_pcrs(key_uri)?;
+        Ok(Box::new(crate::TpmAead::new(pcrs, self.device.clone())))
+    }
+}
+
+/// Extract the sorted list of PCR indices from a key URI.
+fn parse_pcrs(key_uri: &str) -> Result<Vec<u8>, TinkError> {
+    let selection = key_uri
+        .get(TPM_PREFIX.len()..)
+        .and_then(|s| s.strip_prefix(PCR_BANK))
+        .and_then(|s| s.strip_prefix(':'))
+        .ok_or_else(|| TinkError::new("key_uri must have format tpm://sha256:<pcrs>"))?;
+    let mut pcrs = Vec::new();
+    if selection.is_empty() {
+        return Ok(pcrs);
+    }
+    for field in selection.split(',') {
+        let pcr: u8 = field
+            .parse()
+            .map_err(|_| TinkError::new(&format!("invalid PCR index {}", field)))?;
+        if pcr >= PCR_COUNT {
+            return Err(format!("invalid PCR index {}", pcr).into());
+        }
+        if pcrs.contains(&pcr) {
+            return Err(format!("duplicate PCR index {}", pcr).into());
+        }
+        pcrs.push(pcr);
+    }
+    pcrs.sort_unstable();
+    Ok(pcrs)
+}
@@ -0,0 +1,34 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Trait definition for TPM devices.
+
+use tink_core::TinkError;
+
+/// Number of PCRs in a PCR bank.
+pub const PCR_COUNT: u8 = 24;
+
+/// `TpmDevice` represents a TPM 2.0 device (or an emulation of one) that can seal small amounts
+/// of data to the current state of a set of PCRs.
+pub trait TpmDevice: Send + Sync {
+    /// Seal `data` so that it can only be unsealed by this device while each of the PCRs
+    /// (in the SHA-256 bank) listed in `pcrs` holds its current value.  Returns an opaque blob.
+    fn seal(&self, data: &[u8], pcrs: &[u8]) -> Result<Vec<u8>, TinkError>;
+
+    /// Unseal a blob produced by [`seal`](TpmDevice::seal) with the same `pcrs`, failing if any
+    /// of those PCRs has changed value since.
+    fn unseal(&self, blob: &[u8], pcrs: &[u8]) -> Result<Vec<u8>, TinkError>;
+}
@@ -0,0 +1,240 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! TPM device access via the TPM2 Software Stack.
+
+use crate::TpmDevice;
+use std::{
+    convert::{TryFrom, TryInto},
+    sync::Mutex,
+};
+use tink_core::{utils::wrap_err, TinkError};
+use tss_esapi::{
+    attributes::ObjectAttributesBuilder,
+    constants::SessionType,
+    handles::{KeyHandle, SessionHandle},
+    interface_types::{
+        algorithm::{HashingAlgorithm, PublicAlgorithm},
+        key_bits::RsaKeyBits,
+        resource_handles::Hierarchy,
+        session_handles::{AuthSession, PolicySession},
+    },
+    structures::{
+        Digest, KeyedHashScheme, PcrSelectionList, PcrSelectionListBuilder, PcrSlot, Private,
+        Public, PublicBuilder, PublicKeyedHashParameters, RsaExponent, SensitiveData,
+        SymmetricDefinition, SymmetricDefinitionObject,
+    },
+    tcti_ldr::TctiNameConf,
+    traits::{Marshall, UnMarshall},
+    Context,
+};
+
+/// Error message for TPM context lock.
+const CERR: &str = "TPM context lock poisoned";
+/// Size of the big-endian length of the marshalled public area in a sealed blob.
+const LEN_SIZE: usize = 2;
+
+/// `TssTpm` is a [`TpmDevice`] that uses a TPM 2.0 device via the TPM2 Software Stack.
+///
+/// Data is sealed as a keyed-hash object under the storage primary key of the owner hierarchy
+/// (which is re-derived on each use, so nothing is left in the TPM), with a policy requiring the
+/// selected PCRs to hold their values at the time of sealing.  The sealed blob holds the public
+/// and private areas of the object.
+pub struct TssTpm {
+    context: Mutex<Context>,
+}
+
+impl std::fmt::Debug for TssTpm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TssTpm").finish()
+    }
+}
+
+impl TssTpm {
+    /// Return a TPM device using the TCTI configuration given by the `TPM2TOOLS_TCTI`
+    /// environment variable (for example `device:/dev/tpmrm0`).
+    pub fn new() -> Result<Self, TinkError> {
+        let tcti = TctiNameConf::from_environment_variable()
+            .map_err(|e| wrap_err("tpm: invalid TCTI configuration", e))?;
+        Self::new_with_tcti(tcti)
+    }
+
+    /// Return a TPM device using the given TCTI configuration.
+    pub fn new_with_tcti(tcti: TctiNameConf) -> Result<Self, TinkError> {
+        let context = Context::new(tcti).map_err(|e| wrap_err("tpm: failed to connect", e))?;
+        Ok(TssTpm {
+            context: Mutex::new(context),
+        })
+    }
+}
+
+impl TpmDevice for TssTpm {
+    fn seal(&self, data: &[u8], pcrs: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let mut context = self.context.lock().expect(CERR); // safe: lock
+        let selection = pcr_selection(pcrs)?;
+        let primary = create_primary(&mut context)?;
+        let result = (|| -> Result<Vec<u8>, TinkError> {
+            let session = start_policy_session(&mut context, SessionType::Trial, selection)?;
+            let digest = context.policy_get_digest(session);
+            flush_session(&mut context, session);
+            let digest = digest.map_err(|e| wrap_err("tpm: failed to get policy digest", e))?;
+
+            let attributes = ObjectAttributesBuilder::new()
+                .with_fixed_tpm(true)
+                .with_fixed_parent(true)
+                .with_no_da(true)
+                .with_admin_with_policy(true)
+                .build()
+                .map_err(|e| wrap_err("tpm: invalid object attributes", e))?;
+            let public = PublicBuilder::new()
+                .with_public_algorithm(PublicAlgorithm::KeyedHash)
+                .with_name_hashing_algorithm(HashingAlgorithm::Sha256)
+                .with_object_attributes(attributes)
+                .with_auth_policy(digest)
+                .with_keyed_hash_parameters(PublicKeyedHashParameters::new(KeyedHashScheme::Null))
+                .with_keyed_hash_unique_identifier(Digest::default())
+                .build()
+                .map_err(|e| wrap_err("tpm: invalid public area", e))?;
+            let sensitive = SensitiveData::try_from(data.to_vec())
+                .map_err(|e| wrap_err("tpm: data too large to seal", e))?;
+            let created = context
+                .execute_with_nullauth_session(|ctx| {
+                    ctx.create(primary, public, None, Some(sensitive), None, None)
+                })
+                .map_err(|e| wrap_err("tpm: failed to create sealed object", e))?;
+
+            let public = created
+                .out_public
+                .marshall()
+                .map_err(|e| wrap_err("tpm: failed to marshal public area", e))?;
+            let public_len: u16 = public
+                .len()
+                .try_into()
+                .map_err(|e| wrap_err("tpm: public area too large", e))?;
+            let mut blob =
+                Vec::with_capacity(LEN_SIZE + public.len() + created.out_private.value().len());
+            blob.extend_from_slice(&public_len.to_be_bytes());
+            blob.extend_from_slice(&public);
+            blob.extend_from_slice(created.out_private.value());
+            Ok(blob)
+        })();
+        flush_key(&mut context, primary);
+        result
+    }
+
+    fn unseal(&self, blob: &[u8], pcrs: &[u8]) -> Result<Vec<u8>, TinkError> {
+        if blob.len() < LEN_SIZE {
+            return Err("tpm: sealed blob too short".into());
+        }
+        let public_len = u16::from_be_bytes([blob[0], blob[1]]) as usize;
+        if blob.len() < LEN_SIZE + public_len {
+            return Err("tpm: sealed blob too short".into());
+        }
+        let (public, private) = blob[LEN_SIZE..].split_at(public_len);
+        let public = Public::unmarshall(public)
+            .map_err(|e| wrap_err("tpm: invalid sealed public area", e))?;
+        let private = Private::try_from(private.to_vec())
+            .map_err(|e| wrap_err("tpm: invalid sealed private area", e))?;
+
+        let mut context = self.context.lock().expect(CERR); // safe: lock
+        let selection = pcr_selection(pcrs)?;
+        let primary = create_primary(&mut context)?;
+        let loaded = context
+            .execute_with_nullauth_session(|ctx| ctx.load(primary, private, public))
+            .map_err(|e| wrap_err("tpm: failed to load sealed object", e));
+        flush_key(&mut context, primary);
+        let loaded = loaded?;
+
+        let result = (|| -> Result<Vec<u8>, TinkError> {
+            let session = start_policy_session(&mut context, SessionType::Policy, selection)?;
+            let data =
+                context.execute_with_session(Some(session.into()), |ctx| ctx.unseal(loaded.into()));
+            flush_session(&mut context, session);
+            let data = data.map_err(|e| wrap_err("tpm: PCR policy check failed", e))?;
+            Ok(data.value().to_vec())
+        })();
+        flush_key(&mut context, loaded);
+        result
+    }
+}
+
+/// Return a selection of the given PCRs in the SHA-256 bank.
+fn pcr_selection(pcrs: &[u8]) -> Result<PcrSelectionList, TinkError> {
+    let slots = pcrs
+        .iter()
+        .map(|i| PcrSlot::try_from(1u32 << i).map_err(|e| wrap_err("tpm: invalid PCR index", e)))
+        .collect::<Result<Vec<_>, _>>()?;
+    PcrSelectionListBuilder::new()
+        .with_selection(HashingAlgorithm::Sha256, &slots)
+        .build()
+        .map_err(|e| wrap_err("tpm: invalid PCR selection", e))
+}
+
+/// Create the storage primary key of the owner hierarchy.  This is derived from the
+/// hierarchy's seed, so is the same each time.
+fn create_primary(context: &mut Context) -> Result<KeyHandle, TinkError> {
+    let public = tss_esapi::utils::create_restricted_decryption_rsa_public(
+        SymmetricDefinitionObject::AES_128_CFB,
+        RsaKeyBits::Rsa2048,
+        RsaExponent::default(),
+    )
+    .map_err(|e| wrap_err("tpm: invalid primary key template", e))?;
+    let primary = context
+        .execute_with_nullauth_session(|ctx| {
+            ctx.create_primary(Hierarchy::Owner, public, None, None, None, None)
+        })
+        .map_err(|e| wrap_err("tpm: failed to create primary key", e))?;
+    Ok(primary.key_handle)
+}
+
+/// Start a policy (or trial policy) session requiring the selected PCRs to hold their current
+/// values.
+fn start_policy_session(
+    context: &mut Context,
+    session_type: SessionType,
+    selection: PcrSelectionList,
+) -> Result<PolicySession, TinkError> {
+    let session = context
+        .start_auth_session(
+            None,
+            None,
+            None,
+            session_type,
+            SymmetricDefinition::AES_128_CFB,
+            HashingAlgorithm::Sha256,
+        )
+        .map_err(|e| wrap_err("tpm: failed to start session", e))?
+        .ok_or_else(|| TinkError::new("tpm: no session"))?;
+    let session =
+        PolicySession::try_from(session).map_err(|e| wrap_err("tpm: not a policy session", e))?;
+    // An empty digest means that the current PCR values are used.
+    if let Err(e) = context.policy_pcr(session, Digest::default(), selection) {
+        flush_session(context, session);
+        return Err(wrap_err("tpm: failed to set PCR policy", e));
+    }
+    Ok(session)
+}
+
+/// Release a session; failures are ignored, as there is nothing useful to do about them.
+fn flush_session(context: &mut Context, session: PolicySession) {
+    let session = SessionHandle::from(AuthSession::from(session));
+    let _ = context.flush_context(session.into());
+}
+
+/// Release a transient key; failures are ignored, as there is nothing useful to do about them.
+fn flush_key(context: &mut Context, key: KeyHandle) {
+    let _ = context.flush_context(key.into());
+}
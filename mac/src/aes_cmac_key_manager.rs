@@ -95,7 +95,7 @@ fn validate_key(key: &tink_proto::AesCmacKey) -> Result<usize, TinkError> {
 }
 
 /// Validate the given [`AesCmacKeyFormat`](tink_proto::AesCmacKeyFormat).
-fn validate_key_format(format: &tink_proto::AesCmacKeyFormat) -> Result<(), TinkError> {
+pub(crate) fn validate_key_format(format: &tink_proto::AesCmacKeyFormat) -> Result<(), TinkError> {
     match &format.params {
         None => Err("missing AES-CMAC params".into()),
         Some(params) => {
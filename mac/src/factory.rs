@@ -16,6 +16,7 @@
 
 //! Provides an implementation of MAC using a set of underlying implementations.
 
+use crate::{MacPolicy, PolicyHmacKeyManager};
 use std::sync::Arc;
 use tink_core::{utils::wrap_err, TinkError};
 use tink_proto::OutputPrefixType;
@@ -24,19 +25,50 @@ const MAX_INT: usize = usize::MAX >> 1;
 
 /// Create a [`tink_core::Mac`] primitive from the given keyset handle.
 pub fn new(h: &tink_core::keyset::Handle) -> Result<Box<dyn tink_core::Mac>, TinkError> {
-    new_with_key_manager(h, None)
+    new_with_key_manager(h, None, None)
+}
+
+/// Return a [`tink_core::Mac`] primitive for a single key, bypassing keyset wrapping.
+///
+/// The primitive's tags have no output prefix, and none of the keyset-level protections apply;
+/// prefer [`new`] with a keyset [`Handle`](tink_core::keyset::Handle) unless exactly one raw key
+/// is available.  Registered gatekeepers are consulted before the primitive is created.
+pub fn unwrapped_mac_from_key_data(
+    kd: &tink_proto::KeyData,
+) -> Result<Box<dyn tink_core::Mac>, TinkError> {
+    match tink_core::registry::unwrapped_primitive_from_key_data(kd)
+        .map_err(|e| wrap_err("mac::factory: cannot create primitive from key", e))?
+    {
+        tink_core::Primitive::Mac(p) => Ok(p),
+        _ => Err("mac::factory: not a Mac primitive".into()),
+    }
+}
+
+/// Create a [`tink_core::Mac`] primitive from the given keyset handle, enforcing the given
+/// [`MacPolicy`] on every enabled key.  Creation fails if any HMAC key violates the policy, and
+/// the resulting primitive never produces or accepts a tag shorter than the policy minimum.
+pub fn new_with_policy(
+    h: &tink_core::keyset::Handle,
+    policy: &MacPolicy,
+) -> Result<Box<dyn tink_core::Mac>, TinkError> {
+    new_with_key_manager(
+        h,
+        Some(Arc::new(PolicyHmacKeyManager::new(*policy))),
+        Some(*policy),
+    )
 }
 
 /// Create a [`tink_core::Mac`] primitive from the given keyset handle and a custom key manager.
 fn new_with_key_manager(
     h: &tink_core::keyset::Handle,
     km: Option<Arc<dyn tink_core::registry::KeyManager>>,
+    policy: Option<MacPolicy>,
 ) -> Result<Box<dyn tink_core::Mac>, TinkError> {
     let ps = h
         .primitives_with_key_manager(km)
         .map_err(|e| wrap_err("mac::factory: cannot obtain primitive set", e))?;
 
-    let ret = WrappedMac::new(ps)?;
+    let ret = WrappedMac::new(ps, policy)?;
     Ok(Box::new(ret))
 }
 
@@ -45,10 +77,17 @@ fn new_with_key_manager(
 #[derive(Clone)]
 struct WrappedMac {
     ps: tink_core::primitiveset::TypedPrimitiveSet<Box<dyn tink_core::Mac>>,
+    policy: Option<MacPolicy>,
 }
 
 impl WrappedMac {
-    fn new(ps: tink_core::primitiveset::PrimitiveSet) -> Result<WrappedMac, TinkError> {
+    fn new(
+        ps: tink_core::primitiveset::PrimitiveSet,
+        policy: Option<MacPolicy>,
+    ) -> Result<WrappedMac, TinkError> {
+        if !ps.usage.allows_compute() && !ps.usage.allows_verify() {
+            return Err("mac::factory: keyset usage does not permit MAC".into());
+        }
         let entry = match &ps.primary {
             None => return Err("mac::factory: no primary primitive".into()),
             Some(p) => p,
@@ -67,12 +106,33 @@ impl WrappedMac {
         }
         // The `.into()` call is only safe because we've just checked that all entries have
         // the right type of primitive
-        Ok(WrappedMac { ps: ps.into() })
+        Ok(WrappedMac {
+            ps: ps.into(),
+            policy,
+        })
+    }
+
+    /// Indicate whether a tag of the given size (excluding prefix) is acceptable.
+    fn tag_size_allowed(&self, tag_size: usize) -> bool {
+        match &self.policy {
+            Some(policy) => policy.check_tag_size(tag_size).is_ok(),
+            None => true,
+        }
     }
 }
 
 impl tink_core::Mac for WrappedMac {
     fn compute_mac(&self, data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let op = tink_core::trace::operation("mac", "compute", data.len());
+        if !self.ps.usage.allows_compute() {
+            op.failed("MAC computation not permitted");
+            return Err("mac::factory: MAC computation not permitted".into());
+        }
+        self.ps.check_primary_validity()?;
+        self.ps.record_primary_usage(data.len()).map_err(|e| {
+            op.failed("primary key usage limit reached");
+            e
+        })?;
         let primary = match &self.ps.primary {
             Some(p) => p,
             None => return Err("mac::factory: no primary primitive".into()),
@@ -88,25 +148,51 @@ impl tink_core::Mac for WrappedMac {
         } else {
             primary.primitive.compute_mac(data)?
         };
+        if let Some(policy) = &self.policy {
+            policy.check_tag_size(mac.len()).map_err(|e| {
+                op.failed("tag size below policy minimum");
+                wrap_err("mac::factory", e)
+            })?;
+        }
 
         let mut ret = Vec::with_capacity(primary.prefix.len() + mac.len());
         ret.extend_from_slice(&primary.prefix);
         ret.extend_from_slice(&mac);
+        op.succeeded(primary.key_id, ret.len());
         Ok(ret)
     }
 
     fn verify_mac(&self, mac: &[u8], data: &[u8]) -> Result<(), TinkError> {
+        self.verify_mac_with_key_id(mac, data).map(|_| ())
+    }
+
+    fn verify_mac_with_key_id(
+        &self,
+        mac: &[u8],
+        data: &[u8],
+    ) -> Result<tink_core::KeyId, TinkError> {
+        let op = tink_core::trace::operation("mac", "verify", data.len());
+        if !self.ps.usage.allows_verify() {
+            op.failed("MAC verification not permitted");
+            return Err("mac::factory: MAC verification not permitted".into());
+        }
         // This also rejects raw MAC with size of 4 bytes or fewer. Those MACs are
         // clearly insecure, thus should be discouraged.
         let prefix_size = tink_core::cryptofmt::NON_RAW_PREFIX_SIZE;
         if mac.len() <= prefix_size {
+            op.failed("MAC too short");
             return Err("mac::factory: invalid mac".into());
         }
 
         // try non raw keys
         let prefix = &mac[..prefix_size];
         let mac_no_prefix = &mac[prefix_size..];
-        if let Some(entries) = self.ps.entries_for_prefix(prefix) {
+        let entries = if self.tag_size_allowed(mac_no_prefix.len()) {
+            self.ps.entries_for_prefix(prefix)
+        } else {
+            None
+        };
+        if let Some(entries) = entries {
             for entry in entries {
                 let result = if entry.prefix_type == OutputPrefixType::Legacy {
                     if data.len() >= MAX_INT {
@@ -120,12 +206,19 @@ impl tink_core::Mac for WrappedMac {
                     entry.primitive.verify_mac(mac_no_prefix, data)
                 };
                 if result.is_ok() {
-                    return Ok(());
+                    op.succeeded(entry.key_id, 0);
+                    return Ok(entry.key_id);
                 }
+                op.key_failed(entry.key_id);
             }
         }
 
-        if let Some(entries) = self.ps.raw_entries() {
+        let entries = if self.tag_size_allowed(mac.len()) {
+            self.ps.raw_entries()
+        } else {
+            None
+        };
+        if let Some(entries) = entries {
             for entry in entries {
                 let result = if entry.prefix_type == OutputPrefixType::Legacy {
                     // This diverges from the upstream Go code (as of v1.5.0), but matches the
@@ -138,12 +231,15 @@ impl tink_core::Mac for WrappedMac {
                     entry.primitive.verify_mac(mac, data)
                 };
                 if result.is_ok() {
-                    return Ok(());
+                    op.succeeded(entry.key_id, 0);
+                    return Ok(entry.key_id);
                 }
+                op.key_failed(entry.key_id);
             }
         }
 
         // nothing worked
+        op.failed("no key could verify");
         Err("mac::factory: decryption failed".into())
     }
 }
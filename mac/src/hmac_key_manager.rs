@@ -17,7 +17,7 @@
 //! Key manager for AES-CMAC keys for HMAC.
 
 use tink_core::{utils::wrap_err, TinkError};
-use tink_proto::{prost::Message, HashType};
+use tink_proto::prost::Message;
 
 /// Maximal version of HMAC keys.
 pub const HMAC_KEY_VERSION: u32 = 0;
@@ -43,7 +43,7 @@ impl tink_core::registry::KeyManager for HmacKeyManager {
             None => return Err("HmacKeyManager: no key params".into()),
             Some(p) => p,
         };
-        let hash = HashType::from_i32(params.hash).unwrap_or(HashType::UnknownHash);
+        let hash = params.try_hash()?;
         match crate::subtle::Hmac::new(hash, &key.key_value, params.tag_size as usize) {
             Ok(p) => Ok(tink_core::Primitive::Mac(Box::new(p))),
             Err(e) => Err(wrap_err("HmacKeyManager: cannot create new primitive", e)),
@@ -72,6 +72,40 @@ impl tink_core::registry::KeyManager for HmacKeyManager {
         Ok(sk)
     }
 
+    fn supports_derivation(&self) -> bool {
+        true
+    }
+
+    /// Derive a new serialized [`HmacKey`](tink_proto::HmacKey) according to specification in
+    /// the given [`HmacKeyFormat`](tink_proto::HmacKeyFormat), reading the key value from
+    /// `pseudorandomness`.
+    fn derive_key(
+        &self,
+        serialized_key_format: &[u8],
+        pseudorandomness: &mut dyn std::io::Read,
+    ) -> Result<Vec<u8>, TinkError> {
+        if serialized_key_format.is_empty() {
+            return Err("HmacKeyManager: invalid key format".into());
+        }
+        let key_format = tink_proto::HmacKeyFormat::decode(serialized_key_format)
+            .map_err(|_| "HmacKeyManager: invalid key format")?;
+        validate_key_format(&key_format)
+            .map_err(|e| wrap_err("HmacKeyManager: invalid key format", e))?;
+        let mut key_value = vec![0; key_format.key_size as usize];
+        pseudorandomness
+            .read_exact(&mut key_value)
+            .map_err(|e| wrap_err("HmacKeyManager: not enough pseudorandomness", e))?;
+        let mut sk = Vec::new();
+        tink_proto::HmacKey {
+            version: HMAC_KEY_VERSION,
+            params: key_format.params,
+            key_value,
+        }
+        .encode(&mut sk)
+        .map_err(|e| wrap_err("HmacKeyManager: failed to encode new key", e))?;
+        Ok(sk)
+    }
+
     fn type_url(&self) -> &'static str {
         HMAC_TYPE_URL
     }
@@ -90,18 +124,18 @@ fn validate_key(key: &tink_proto::HmacKey) -> Result<(), TinkError> {
     match &key.params {
         None => Err("HmacKeyManager: missing HMAC params".into()),
         Some(params) => {
-            let hash = HashType::from_i32(params.hash).unwrap_or(HashType::UnknownHash);
+            let hash = params.try_hash()?;
             crate::subtle::validate_hmac_params(hash, key_size, params.tag_size as usize)
         }
     }
 }
 
 /// Validate the given [`HmacKeyFormat`](tink_proto::HmacKeyFormat).
-fn validate_key_format(format: &tink_proto::HmacKeyFormat) -> Result<(), TinkError> {
+pub(crate) fn validate_key_format(format: &tink_proto::HmacKeyFormat) -> Result<(), TinkError> {
     match &format.params {
         None => Err("missing HMAC params".into()),
         Some(params) => {
-            let hash = HashType::from_i32(params.hash).unwrap_or(HashType::UnknownHash);
+            let hash = params.try_hash()?;
             crate::subtle::validate_hmac_params(
                 hash,
                 format.key_size as usize,
@@ -16,14 +16,15 @@
 
 //! This module contains pre-generated [`KeyTemplate`] instances for MAC.
 
-use tink_proto::{prost::Message, KeyTemplate};
+use tink_core::TinkError;
+use tink_proto::{prost::Message, HashType, KeyTemplate, OutputPrefixType};
 
 /// Return a [`KeyTemplate`] that generates a HMAC key with the following parameters:
 ///  - Key size: 32 bytes
 ///  - Tag size: 16 bytes
 ///  - Hash function: SHA256
 pub fn hmac_sha256_tag128_key_template() -> KeyTemplate {
-    create_hmac_key_template(32, 16, tink_proto::HashType::Sha256)
+    create_hmac_key_template(32, 16, HashType::Sha256)
 }
 
 /// Return a [`KeyTemplate`] that generates a HMAC key with the following parameters:
@@ -31,7 +32,7 @@ pub fn hmac_sha256_tag128_key_template() -> KeyTemplate {
 ///  - Tag size: 32 bytes
 ///  - Hash function: SHA256
 pub fn hmac_sha256_tag256_key_template() -> KeyTemplate {
-    create_hmac_key_template(32, 32, tink_proto::HashType::Sha256)
+    create_hmac_key_template(32, 32, HashType::Sha256)
 }
 
 /// Return a [`KeyTemplate`] that generates a HMAC key with the following parameters:
@@ -39,7 +40,7 @@ pub fn hmac_sha256_tag256_key_template() -> KeyTemplate {
 ///  - Tag size: 32 bytes
 ///  - Hash function: SHA512
 pub fn hmac_sha512_tag256_key_template() -> KeyTemplate {
-    create_hmac_key_template(64, 32, tink_proto::HashType::Sha512)
+    create_hmac_key_template(64, 32, HashType::Sha512)
 }
 
 /// Return a [`KeyTemplate`] that generates a HMAC key with the following parameters:
@@ -47,7 +48,7 @@ pub fn hmac_sha512_tag256_key_template() -> KeyTemplate {
 ///  - Tag size: 64 bytes
 ///  - Hash function: SHA512
 pub fn hmac_sha512_tag512_key_template() -> KeyTemplate {
-    create_hmac_key_template(64, 64, tink_proto::HashType::Sha512)
+    create_hmac_key_template(64, 64, HashType::Sha512)
 }
 
 /// Return a [`KeyTemplate`] that generates a AES-CMAC key with the following parameters:
@@ -58,41 +59,160 @@ pub fn aes_cmac_tag128_key_template() -> KeyTemplate {
 }
 
 /// Create a new [`KeyTemplate`] for HMAC using the given parameters.
-fn create_hmac_key_template(
-    key_size: u32,
-    tag_size: u32,
-    hash_type: tink_proto::HashType,
-) -> KeyTemplate {
-    let params = tink_proto::HmacParams {
-        hash: hash_type as i32,
-        tag_size,
-    };
-    let format = tink_proto::HmacKeyFormat {
-        version: crate::HMAC_KEY_VERSION,
-        params: Some(params),
-        key_size,
-    };
-    let mut serialized_format = Vec::new();
-    format.encode(&mut serialized_format).unwrap(); // safe: proto-encode
-    KeyTemplate {
-        type_url: crate::HMAC_TYPE_URL.to_string(),
-        value: serialized_format,
-        output_prefix_type: tink_proto::OutputPrefixType::Tink as i32,
-    }
+fn create_hmac_key_template(key_size: u32, tag_size: u32, hash_type: HashType) -> KeyTemplate {
+    HmacTemplateBuilder::new()
+        .key_size(key_size)
+        .tag_size(tag_size)
+        .hash(hash_type)
+        .build()
+        .unwrap() // safe: valid parameters
 }
 
 /// Create a new [`KeyTemplate`] for CMAC using the given parameters.
 fn create_cmac_key_template(key_size: u32, tag_size: u32) -> KeyTemplate {
-    let params = tink_proto::AesCmacParams { tag_size };
-    let format = tink_proto::AesCmacKeyFormat {
-        params: Some(params),
-        key_size,
-    };
+    AesCmacTemplateBuilder::new()
+        .key_size(key_size)
+        .tag_size(tag_size)
+        .build()
+        .unwrap() // safe: valid parameters
+}
+
+/// Serialize a key format into a [`KeyTemplate`], checking the output prefix type.
+fn serialize_template<T: Message>(
+    type_url: &str,
+    format: &T,
+    output_prefix_type: OutputPrefixType,
+) -> Result<KeyTemplate, TinkError> {
+    if output_prefix_type == OutputPrefixType::UnknownPrefix {
+        return Err("invalid output prefix type".into());
+    }
     let mut serialized_format = Vec::new();
     format.encode(&mut serialized_format).unwrap(); // safe: proto-encode
-    KeyTemplate {
-        type_url: crate::CMAC_TYPE_URL.to_string(),
+    Ok(KeyTemplate {
+        type_url: type_url.to_string(),
         value: serialized_format,
-        output_prefix_type: tink_proto::OutputPrefixType::Tink as i32,
+        output_prefix_type: output_prefix_type as i32,
+    })
+}
+
+/// Builder for HMAC [`KeyTemplate`]s, whose parameters are checked when the template is built.
+/// Defaults to the parameters of [`hmac_sha256_tag256_key_template`].
+#[derive(Clone, Copy, Debug)]
+pub struct HmacTemplateBuilder {
+    key_size: u32,
+    tag_size: u32,
+    hash: HashType,
+    prefix: OutputPrefixType,
+}
+
+impl Default for HmacTemplateBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HmacTemplateBuilder {
+    /// Create a builder with the default parameters.
+    pub fn new() -> Self {
+        Self {
+            key_size: 32,
+            tag_size: 32,
+            hash: HashType::Sha256,
+            prefix: OutputPrefixType::Tink,
+        }
+    }
+
+    /// Set the key size in bytes (at least 16).
+    pub fn key_size(mut self, key_size: u32) -> Self {
+        self.key_size = key_size;
+        self
+    }
+
+    /// Set the tag size in bytes (at least 10, and at most the hash output size).
+    pub fn tag_size(mut self, tag_size: u32) -> Self {
+        self.tag_size = tag_size;
+        self
+    }
+
+    /// Set the hash function.
+    pub fn hash(mut self, hash: HashType) -> Self {
+        self.hash = hash;
+        self
+    }
+
+    /// Set the output prefix type.
+    pub fn prefix(mut self, prefix: OutputPrefixType) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    /// Build the [`KeyTemplate`], or return an error if the parameters are invalid.
+    pub fn build(&self) -> Result<KeyTemplate, TinkError> {
+        let format = tink_proto::HmacKeyFormat {
+            version: crate::HMAC_KEY_VERSION,
+            params: Some(tink_proto::HmacParams {
+                hash: self.hash as i32,
+                tag_size: self.tag_size,
+            }),
+            key_size: self.key_size,
+        };
+        crate::hmac_key_manager::validate_key_format(&format)?;
+        serialize_template(crate::HMAC_TYPE_URL, &format, self.prefix)
+    }
+}
+
+/// Builder for AES-CMAC [`KeyTemplate`]s, whose parameters are checked when the template is
+/// built.  Defaults to the parameters of [`aes_cmac_tag128_key_template`].
+#[derive(Clone, Copy, Debug)]
+pub struct AesCmacTemplateBuilder {
+    key_size: u32,
+    tag_size: u32,
+    prefix: OutputPrefixType,
+}
+
+impl Default for AesCmacTemplateBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AesCmacTemplateBuilder {
+    /// Create a builder with the default parameters.
+    pub fn new() -> Self {
+        Self {
+            key_size: 32,
+            tag_size: 16,
+            prefix: OutputPrefixType::Tink,
+        }
+    }
+
+    /// Set the key size in bytes (only 32 is supported).
+    pub fn key_size(mut self, key_size: u32) -> Self {
+        self.key_size = key_size;
+        self
+    }
+
+    /// Set the tag size in bytes (10 to 16).
+    pub fn tag_size(mut self, tag_size: u32) -> Self {
+        self.tag_size = tag_size;
+        self
+    }
+
+    /// Set the output prefix type.
+    pub fn prefix(mut self, prefix: OutputPrefixType) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    /// Build the [`KeyTemplate`], or return an error if the parameters are invalid.
+    pub fn build(&self) -> Result<KeyTemplate, TinkError> {
+        let format = tink_proto::AesCmacKeyFormat {
+            params: Some(tink_proto::AesCmacParams {
+                tag_size: self.tag_size,
+            }),
+            key_size: self.key_size,
+        };
+        crate::aes_cmac_key_manager::validate_key_format(&format)?;
+        serialize_template(crate::CMAC_TYPE_URL, &format, self.prefix)
     }
 }
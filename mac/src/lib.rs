@@ -32,6 +32,8 @@ mod hmac_key_manager;
 pub use hmac_key_manager::*;
 mod key_templates;
 pub use key_templates::*;
+mod policy;
+pub use policy::*;
 
 pub mod subtle;
 
@@ -0,0 +1,113 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Verification policy applied by the MAC wrapper on top of per-key validation.
+
+use crate::HmacKeyManager;
+use tink_core::{registry::KeyManager, utils::wrap_err, TinkError};
+use tink_proto::{prost::Message, HashType};
+
+/// Minimum tag size (in bytes) required by the default [`MacPolicy`].
+pub const DEFAULT_POLICY_MIN_TAG_SIZE: usize = 16;
+
+/// Constraints on MAC keys that are enforced by the MAC primitive created via
+/// [`new_with_policy`](crate::new_with_policy), independently of the parameters recorded in
+/// each key.
+///
+/// This guards against a misconfigured (or maliciously inserted) key in a shared keyset
+/// silently weakening authentication strength: keys that do not meet the policy cause primitive
+/// creation to fail, and tags shorter than the minimum are never produced or accepted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MacPolicy {
+    /// Minimum acceptable tag size in bytes, excluding any output prefix.
+    pub min_tag_size: usize,
+    /// Whether HMAC keys using SHA-1 are acceptable.
+    pub allow_sha1: bool,
+}
+
+impl Default for MacPolicy {
+    /// The default policy requires tags of at least 128 bits and forbids SHA-1.
+    fn default() -> Self {
+        MacPolicy {
+            min_tag_size: DEFAULT_POLICY_MIN_TAG_SIZE,
+            allow_sha1: false,
+        }
+    }
+}
+
+impl MacPolicy {
+    /// Check the parameters of an HMAC key against the policy.
+    fn check_hmac_params(&self, params: &tink_proto::HmacParams) -> Result<(), TinkError> {
+        let hash = params.try_hash()?;
+        if hash == HashType::Sha1 && !self.allow_sha1 {
+            return Err("MacPolicy: SHA-1 not permitted".into());
+        }
+        self.check_tag_size(params.tag_size as usize)
+    }
+
+    /// Check a tag size against the policy.
+    pub(crate) fn check_tag_size(&self, tag_size: usize) -> Result<(), TinkError> {
+        if tag_size < self.min_tag_size {
+            return Err(format!(
+                "MacPolicy: tag size {} below minimum {}",
+                tag_size, self.min_tag_size
+            )
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// Key manager for HMAC keys that checks each key against a [`MacPolicy`] before delegating to
+/// the standard [`HmacKeyManager`].
+pub(crate) struct PolicyHmacKeyManager {
+    policy: MacPolicy,
+    inner: HmacKeyManager,
+}
+
+impl PolicyHmacKeyManager {
+    pub(crate) fn new(policy: MacPolicy) -> Self {
+        PolicyHmacKeyManager {
+            policy,
+            inner: HmacKeyManager::default(),
+        }
+    }
+}
+
+impl KeyManager for PolicyHmacKeyManager {
+    fn primitive(&self, serialized_key: &[u8]) -> Result<tink_core::Primitive, TinkError> {
+        let key = tink_proto::HmacKey::decode(serialized_key)
+            .map_err(|e| wrap_err("PolicyHmacKeyManager: decode failed", e))?;
+        let params = key
+            .params
+            .as_ref()
+            .ok_or_else(|| TinkError::new("PolicyHmacKeyManager: no key params"))?;
+        self.policy.check_hmac_params(params)?;
+        self.inner.primitive(serialized_key)
+    }
+
+    fn new_key(&self, serialized_key_format: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.inner.new_key(serialized_key_format)
+    }
+
+    fn type_url(&self) -> &'static str {
+        self.inner.type_url()
+    }
+
+    fn key_material_type(&self) -> tink_proto::key_data::KeyMaterialType {
+        self.inner.key_material_type()
+    }
+}
@@ -54,6 +54,15 @@ impl AesCmac {
             .map_err(|e| wrap_err("AesCmac: could not create AES-CMAC prf", e))?;
         Ok(AesCmac { prf, tag_size })
     }
+
+    /// Create a new [`AesCmac`] object for a key that is explicitly checked to be 16, 24 or 32
+    /// bytes long, selecting AES-128, AES-192 or AES-256; this allows tags produced by legacy
+    /// systems to be verified.  Keys obtained through the AES-CMAC key manager are always 32 bytes
+    /// long, as required by [`validate_cmac_params`].
+    pub fn new_legacy(key: &[u8], tag_size: usize) -> Result<AesCmac, TinkError> {
+        validate_cmac_key_size(key.len()).map_err(|e| wrap_err("AesCmac", e))?;
+        Self::new(key, tag_size)
+    }
 }
 
 impl tink_core::Mac for AesCmac {
@@ -62,6 +71,18 @@ impl tink_core::Mac for AesCmac {
     }
 }
 
+/// Validate that `key_size` is a valid AES key size for AES-CMAC: 16, 24 or 32 bytes.
+pub fn validate_cmac_key_size(key_size: usize) -> Result<(), TinkError> {
+    match key_size {
+        16 | 24 | 32 => Ok(()),
+        _ => Err(format!(
+            "invalid AES-CMAC key size; want 16, 24 or 32, got {}",
+            key_size
+        )
+        .into()),
+    }
+}
+
 /// Validate the parameters for an AES-CMAC against the recommended parameters.
 pub fn validate_cmac_params(key_size: usize, tag_size: usize) -> Result<(), TinkError> {
     if key_size != RECOMMENDED_CMAC_KEY_SIZE_IN_BYTES {
@@ -45,6 +45,10 @@ impl Hmac {
 }
 
 /// Validate parameters of [`Hmac`] constructor.
+///
+/// All of SHA-1, SHA-224, SHA-256, SHA-384 and SHA-512 are accepted. The key must be at least
+/// 16 bytes, and the tag size must lie between 10 bytes and the digest size of the hash (20, 28,
+/// 32, 48 and 64 bytes respectively).
 pub fn validate_hmac_params(
     hash: HashType,
     key_size: usize,
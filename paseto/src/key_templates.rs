@@ -0,0 +1,36 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! This module contains pre-generated [`KeyTemplate`] instances for PASETO tokens.
+
+use tink_proto::KeyTemplate;
+
+/// Return a [`KeyTemplate`] that generates a new PASETO `v4.local` key.  Tokens identify their
+/// key with a `kid` footer rather than a Tink output prefix, so the output prefix type is RAW.
+pub fn v4_local_key_template() -> KeyTemplate {
+    KeyTemplate {
+        type_url: crate::PASETO_V4_LOCAL_TYPE_URL.to_string(),
+        output_prefix_type: tink_proto::OutputPrefixType::Raw as i32,
+        value: vec![],
+    }
+}
+
+/// Return a [`KeyTemplate`] that generates a new Ed25519 private key for PASETO `v4.public`
+/// tokens.  Tokens identify their key with a `kid` footer rather than a Tink output prefix, so
+/// the output prefix type is RAW.
+pub fn v4_public_key_template() -> KeyTemplate {
+    tink_signature::ed25519_key_without_prefix_template()
+}
@@ -0,0 +1,59 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Implementation of version 4 [PASETO](https://github.com/paseto-standard/paseto-spec) tokens,
+//! keyed by Tink keysets.
+//!
+//! - [`V4Local`] produces and consumes `v4.local` tokens, which are encrypted and authenticated
+//!   with XChaCha20 and a BLAKE2b-MAC, using keysets of [`PASETO_V4_LOCAL_TYPE_URL`] keys.
+//! - [`V4PublicSigner`] and [`V4PublicVerifier`] produce and consume `v4.public` tokens, which are
+//!   signed with Ed25519, using Ed25519 keysets from the [`tink_signature`] crate.
+//!
+//! Each token has a footer of the form `{"kid":"<kid>"}` identifying the Tink key that produced
+//! it (see [`key_id_to_kid`]).  When a token is consumed, its `kid` selects the key to use; tokens
+//! without a recognizable `kid` are tried against every key in the keyset.
+
+#![deny(broken_intra_doc_links)]
+
+use std::sync::Once;
+use tink_core::registry::{register_key_manager, register_template_generator};
+
+mod key_templates;
+pub use key_templates::*;
+mod pae;
+pub use pae::*;
+mod token;
+pub use token::*;
+mod v4_local_key_manager;
+pub use v4_local_key_manager::*;
+
+pub mod subtle;
+
+static INIT: Once = Once::new();
+
+/// Initialize the `tink-paseto` crate, registering its primitives so they are available via
+/// Tink.  This also initializes the [`tink_signature`] crate, whose Ed25519 keys are used for
+/// `v4.public` tokens.
+pub fn init() {
+    INIT.call_once(|| {
+        register_key_manager(std::sync::Arc::new(PasetoV4LocalKeyManager::default()))
+            .expect("tink_paseto::init() failed"); // safe: init
+
+        register_template_generator("PASETO_V4_LOCAL", v4_local_key_template);
+
+        tink_signature::init();
+    });
+}
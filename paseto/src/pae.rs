@@ -0,0 +1,72 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Pre-authentication encoding.
+
+use tink_core::TinkError;
+
+/// Encode a 64-bit unsigned integer in little-endian order, with the most significant bit
+/// cleared.
+fn le64(n: usize) -> [u8; 8] {
+    ((n as u64) & (u64::MAX >> 1)).to_le_bytes()
+}
+
+/// Return the PASETO pre-authentication encoding (PAE) of `pieces`: the number of pieces followed
+/// by each piece prefixed with its length, all lengths encoded with `LE64`.
+pub fn pre_auth_encode(pieces: &[&[u8]]) -> Vec<u8> {
+    let len = 8 + pieces.iter().map(|p| 8 + p.len()).sum::<usize>();
+    let mut out = Vec::with_capacity(len);
+    out.extend_from_slice(&le64(pieces.len()));
+    for piece in pieces {
+        out.extend_from_slice(&le64(piece.len()));
+        out.extend_from_slice(piece);
+    }
+    out
+}
+
+/// Split the output of [`pre_auth_encode`] back into its pieces.
+pub fn pre_auth_decode(data: &[u8]) -> Result<Vec<&[u8]>, TinkError> {
+    let (count, mut rest) = split_le64(data)?;
+    let mut pieces = Vec::new();
+    for _ in 0..count {
+        let (len, tail) = split_le64(rest)?;
+        if tail.len() < len {
+            return Err("paseto: truncated pre-authentication encoding".into());
+        }
+        let (piece, tail) = tail.split_at(len);
+        pieces.push(piece);
+        rest = tail;
+    }
+    if !rest.is_empty() {
+        return Err("paseto: trailing data after pre-authentication encoding".into());
+    }
+    Ok(pieces)
+}
+
+fn split_le64(data: &[u8]) -> Result<(usize, &[u8]), TinkError> {
+    if data.len() < 8 {
+        return Err("paseto: truncated pre-authentication encoding".into());
+    }
+    let (head, tail) = data.split_at(8);
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(head);
+    let n = u64::from_le_bytes(bytes);
+    if n > (tail.len() as u64) {
+        // Every piece needs at least one length byte, and every length needs its bytes.
+        return Err("paseto: truncated pre-authentication encoding".into());
+    }
+    Ok((n as usize, tail))
+}
@@ -0,0 +1,310 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Production and consumption of PASETO tokens using keysets.
+
+use crate::subtle::PASETO_V4_LOCAL_HEADER;
+use std::convert::TryInto;
+use tink_core::{
+    keyset::Handle,
+    primitiveset::{PrimitiveSet, TypedEntry, TypedPrimitiveSet},
+    utils::wrap_err,
+    KeyId, TinkError,
+};
+
+/// Header of `v4.public` tokens.
+pub const PASETO_V4_PUBLIC_HEADER: &str = "v4.public.";
+/// Size of the Ed25519 signature in a `v4.public` token.
+const V4_PUBLIC_SIGNATURE_SIZE: usize = 64;
+
+/// The contents of a PASETO token that has been decrypted or verified.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedToken {
+    /// The decrypted or verified message.
+    pub payload: Vec<u8>,
+    /// The (unencoded) footer of the token, which is authenticated but not encrypted.
+    pub footer: Vec<u8>,
+    /// The ID of the key in the keyset that decrypted or verified the token.
+    pub key_id: KeyId,
+}
+
+/// Return the `kid` used in token footers to identify the key with the given key ID: the
+/// unpadded base64url encoding of the big-endian key ID.
+pub fn key_id_to_kid(key_id: KeyId) -> String {
+    base64::encode_config(key_id.to_be_bytes(), base64::URL_SAFE_NO_PAD)
+}
+
+/// Return the key ID identified by a `kid` produced by [`key_id_to_kid`], if valid.
+pub fn kid_to_key_id(kid: &str) -> Option<KeyId> {
+    let data = base64::decode_config(kid, base64::URL_SAFE_NO_PAD).ok()?;
+    let bytes: [u8; 4] = data.as_slice().try_into().ok()?;
+    Some(KeyId::from_be_bytes(bytes))
+}
+
+/// `V4Local` produces and consumes PASETO `v4.local` tokens using a keyset of
+/// [`PASETO_V4_LOCAL_TYPE_URL`](crate::PASETO_V4_LOCAL_TYPE_URL) keys.
+#[derive(Clone)]
+pub struct V4Local {
+    ps: TypedPrimitiveSet<Box<dyn tink_core::Aead>>,
+}
+
+impl V4Local {
+    /// Return a `V4Local` that uses the keys in the given keyset handle.
+    pub fn new(h: &Handle) -> Result<V4Local, TinkError> {
+        check_key_types(h, crate::PASETO_V4_LOCAL_TYPE_URL)?;
+        let ps = primitive_set(h, |p| matches!(p, tink_core::Primitive::Aead(_)))?;
+        Ok(V4Local { ps: ps.into() })
+    }
+
+    /// Encrypt `message` with the primary key, returning a `v4.local` token whose footer
+    /// identifies the key.  The `implicit_assertion` is authenticated but not included in the
+    /// token, and must be provided again when the token is decrypted.
+    pub fn encrypt(&self, message: &[u8], implicit_assertion: &[u8]) -> Result<String, TinkError> {
+        if !self.ps.usage.allows_encrypt() {
+            return Err("paseto: encryption not permitted".into());
+        }
+        self.ps.check_primary_validity()?;
+        self.ps.record_primary_usage(message.len())?;
+        let primary = self
+            .ps
+            .primary
+            .as_ref()
+            .ok_or_else(|| TinkError::new("paseto: no primary key"))?;
+        let footer = kid_footer(primary.key_id);
+        let aad = crate::pre_auth_encode(&[&footer, implicit_assertion]);
+        let payload = primary.primitive.encrypt(message, &aad)?;
+        Ok(encode_token(PASETO_V4_LOCAL_HEADER, &payload, &footer))
+    }
+
+    /// Decrypt a `v4.local` token.  If the token footer has a `kid`, only the corresponding key is
+    /// used; otherwise each key in the keyset is tried in turn.
+    pub fn decrypt(
+        &self,
+        token: &str,
+        implicit_assertion: &[u8],
+    ) -> Result<DecodedToken, TinkError> {
+        if !self.ps.usage.allows_decrypt() {
+            return Err("paseto: decryption not permitted".into());
+        }
+        let (payload, footer) = decode_token(PASETO_V4_LOCAL_HEADER, token)?;
+        let aad = crate::pre_auth_encode(&[&footer, implicit_assertion]);
+        for entry in candidates(&self.ps, footer_key_id(&footer)?)? {
+            if let Ok(pt) = entry.primitive.decrypt(&payload, &aad) {
+                return Ok(DecodedToken {
+                    payload: pt,
+                    footer,
+                    key_id: entry.key_id,
+                });
+            }
+        }
+        Err("paseto: decryption failed".into())
+    }
+}
+
+/// `V4PublicSigner` produces PASETO `v4.public` tokens using a keyset of Ed25519 private keys.
+#[derive(Clone)]
+pub struct V4PublicSigner {
+    ps: TypedPrimitiveSet<Box<dyn tink_core::Signer>>,
+}
+
+impl V4PublicSigner {
+    /// Return a `V4PublicSigner` that uses the keys in the given keyset handle.
+    pub fn new(h: &Handle) -> Result<V4PublicSigner, TinkError> {
+        check_key_types(h, tink_signature::ED25519_SIGNER_TYPE_URL)?;
+        let ps = primitive_set(h, |p| matches!(p, tink_core::Primitive::Signer(_)))?;
+        Ok(V4PublicSigner { ps: ps.into() })
+    }
+
+    /// Sign `message` with the primary key, returning a `v4.public` token whose footer identifies
+    /// the key.  The `implicit_assertion` is authenticated but not included in the token, and
+    /// must be provided again when the token is verified.
+    pub fn sign(&self, message: &[u8], implicit_assertion: &[u8]) -> Result<String, TinkError> {
+        if !self.ps.usage.allows_compute() {
+            return Err("paseto: signing not permitted".into());
+        }
+        self.ps.check_primary_validity()?;
+        self.ps.record_primary_usage(message.len())?;
+        let primary = self
+            .ps
+            .primary
+            .as_ref()
+            .ok_or_else(|| TinkError::new("paseto: no primary key"))?;
+        let footer = kid_footer(primary.key_id);
+        let pre_auth = crate::pre_auth_encode(&[
+            PASETO_V4_PUBLIC_HEADER.as_bytes(),
+            message,
+            &footer,
+            implicit_assertion,
+        ]);
+        let signature = primary.primitive.sign(&pre_auth)?;
+        let mut payload = Vec::with_capacity(message.len() + signature.len());
+        payload.extend_from_slice(message);
+        payload.extend_from_slice(&signature);
+        Ok(encode_token(PASETO_V4_PUBLIC_HEADER, &payload, &footer))
+    }
+}
+
+/// `V4PublicVerifier` consumes PASETO `v4.public` tokens using a keyset of Ed25519 public keys.
+#[derive(Clone)]
+pub struct V4PublicVerifier {
+    ps: TypedPrimitiveSet<Box<dyn tink_core::Verifier>>,
+}
+
+impl V4PublicVerifier {
+    /// Return a `V4PublicVerifier` that uses the keys in the given keyset handle.
+    pub fn new(h: &Handle) -> Result<V4PublicVerifier, TinkError> {
+        check_key_types(h, tink_signature::ED25519_VERIFIER_TYPE_URL)?;
+        let ps = primitive_set(h, |p| matches!(p, tink_core::Primitive::Verifier(_)))?;
+        Ok(V4PublicVerifier { ps: ps.into() })
+    }
+
+    /// Verify a `v4.public` token, returning its message.  If the token footer has a `kid`, only
+    /// the corresponding key is used; otherwise each key in the keyset is tried in turn.
+    pub fn verify(
+        &self,
+        token: &str,
+        implicit_assertion: &[u8],
+    ) -> Result<DecodedToken, TinkError> {
+        if !self.ps.usage.allows_verify() {
+            return Err("paseto: verification not permitted".into());
+        }
+        let (payload, footer) = decode_token(PASETO_V4_PUBLIC_HEADER, token)?;
+        if payload.len() < V4_PUBLIC_SIGNATURE_SIZE {
+            return Err("paseto: token too short".into());
+        }
+        let (message, signature) = payload.split_at(payload.len() - V4_PUBLIC_SIGNATURE_SIZE);
+        let pre_auth = crate::pre_auth_encode(&[
+            PASETO_V4_PUBLIC_HEADER.as_bytes(),
+            message,
+            &footer,
+            implicit_assertion,
+        ]);
+        for entry in candidates(&self.ps, footer_key_id(&footer)?)? {
+            if entry.primitive.verify(signature, &pre_auth).is_ok() {
+                return Ok(DecodedToken {
+                    payload: message.to_vec(),
+                    footer,
+                    key_id: entry.key_id,
+                });
+            }
+        }
+        Err("paseto: verification failed".into())
+    }
+}
+
+/// Check that all of the enabled keys in the keyset have the given type.
+fn check_key_types(h: &Handle, type_url: &str) -> Result<(), TinkError> {
+    for key_info in h.keyset_info().key_info {
+        if key_info.status == tink_proto::KeyStatusType::Enabled as i32
+            && key_info.type_url != type_url
+        {
+            return Err(format!(
+                "paseto: unsupported key type {}, want {}",
+                key_info.type_url, type_url
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
+
+/// Return the primitive set for the keyset, checking that all of the primitives are of the
+/// expected kind.
+fn primitive_set<F>(h: &Handle, is_expected: F) -> Result<PrimitiveSet, TinkError>
+where
+    F: Fn(&tink_core::Primitive) -> bool,
+{
+    let ps = h
+        .primitives()
+        .map_err(|e| wrap_err("paseto: cannot obtain primitive set", e))?;
+    if ps.primary.is_none() {
+        return Err("paseto: no primary key".into());
+    }
+    for entries in ps.entries.values() {
+        for entry in entries {
+            if !is_expected(&entry.primitive) {
+                return Err("paseto: unexpected primitive type".into());
+            }
+        }
+    }
+    Ok(ps)
+}
+
+/// Return the entries that may have produced a token, given the key ID from its footer.
+fn candidates<P: From<tink_core::Primitive>>(
+    ps: &TypedPrimitiveSet<P>,
+    key_id: Option<KeyId>,
+) -> Result<Vec<&TypedEntry<P>>, TinkError> {
+    match key_id {
+        Some(key_id) => ps
+            .entry_for_key_id(key_id)
+            .map(|entry| vec![entry])
+            .ok_or_else(|| TinkError::new("paseto: unknown kid")),
+        None => Ok(ps.entries.values().flat_map(|v| v.iter()).collect()),
+    }
+}
+
+/// Return the footer identifying the key with the given ID.
+fn kid_footer(key_id: KeyId) -> Vec<u8> {
+    serde_json::json!({ "kid": key_id_to_kid(key_id) })
+        .to_string()
+        .into_bytes()
+}
+
+/// Return the key ID identified by the `kid` in a token footer, if the footer is a JSON object
+/// with a `kid`.
+fn footer_key_id(footer: &[u8]) -> Result<Option<KeyId>, TinkError> {
+    let kid = match serde_json::from_slice::<serde_json::Value>(footer) {
+        Ok(serde_json::Value::Object(obj)) => match obj.get("kid") {
+            Some(kid) => kid.clone(),
+            None => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+    kid.as_str()
+        .and_then(kid_to_key_id)
+        .map(Some)
+        .ok_or_else(|| TinkError::new("paseto: invalid kid"))
+}
+
+/// Assemble a token from its header, payload and footer.
+fn encode_token(header: &str, payload: &[u8], footer: &[u8]) -> String {
+    let mut token = header.to_string();
+    token.push_str(&base64::encode_config(payload, base64::URL_SAFE_NO_PAD));
+    if !footer.is_empty() {
+        token.push('.');
+        token.push_str(&base64::encode_config(footer, base64::URL_SAFE_NO_PAD));
+    }
+    token
+}
+
+/// Split a token with the given header into its (decoded) payload and footer.
+fn decode_token(header: &str, token: &str) -> Result<(Vec<u8>, Vec<u8>), TinkError> {
+    if !token.starts_with(header) {
+        return Err(format!("paseto: token does not start with {}", header).into());
+    }
+    let mut parts = token[header.len()..].split('.');
+    let payload = parts.next().unwrap_or_default();
+    let footer = parts.next().unwrap_or_default();
+    if parts.next().is_some() {
+        return Err("paseto: too many token segments".into());
+    }
+    let payload = base64::decode_config(payload, base64::URL_SAFE_NO_PAD)
+        .map_err(|e| wrap_err("paseto: invalid token payload", e))?;
+    let footer = base64::decode_config(footer, base64::URL_SAFE_NO_PAD)
+        .map_err(|e| wrap_err("paseto: invalid token footer", e))?;
+    Ok((payload, footer))
+}
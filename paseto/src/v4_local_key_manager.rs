@@ -0,0 +1,90 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Key manager for PASETO `v4.local` keys.
+
+use crate::subtle;
+use tink_core::{utils::wrap_err, TinkError};
+use tink_proto::prost::Message;
+
+/// Maximal version of PASETO `v4.local` keys.
+pub const PASETO_V4_LOCAL_KEY_VERSION: u32 = 0;
+/// Type URL of PASETO `v4.local` keys.  The key material is held in an
+/// [`XChaCha20Poly1305Key`](tink_proto::XChaCha20Poly1305Key) message, which has the same shape
+/// (a 32-byte key).
+pub const PASETO_V4_LOCAL_TYPE_URL: &str =
+    "type.googleapis.com/google.crypto.tink.PasetoV4LocalKey";
+
+/// [`PasetoV4LocalKeyManager`] is an implementation of the [`tink_core::registry::KeyManager`]
+/// trait. It generates new PASETO `v4.local` keys and produces new instances of
+/// [`subtle::PasetoV4Local`].
+#[derive(Default)]
+pub(crate) struct PasetoV4LocalKeyManager {}
+
+impl tink_core::registry::KeyManager for PasetoV4LocalKeyManager {
+    fn primitive(&self, serialized_key: &[u8]) -> Result<tink_core::Primitive, TinkError> {
+        if serialized_key.is_empty() {
+            return Err("PasetoV4LocalKeyManager: invalid key".into());
+        }
+        let key = tink_proto::XChaCha20Poly1305Key::decode(serialized_key)
+            .map_err(|e| wrap_err("PasetoV4LocalKeyManager: invalid key", e))?;
+        validate_key(&key)?;
+        match subtle::PasetoV4Local::new(&key.key_value) {
+            Ok(p) => Ok(tink_core::Primitive::Aead(Box::new(p))),
+            Err(e) => Err(wrap_err(
+                "PasetoV4LocalKeyManager: cannot create new primitive",
+                e,
+            )),
+        }
+    }
+
+    /// Create a new key, ignoring the specification in the given serialized key format
+    /// because the key size and other params are fixed.
+    fn new_key(&self, _serialized_key_format: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let key = tink_proto::XChaCha20Poly1305Key {
+            version: PASETO_V4_LOCAL_KEY_VERSION,
+            key_value: tink_core::subtle::random::get_random_bytes(
+                subtle::PASETO_V4_LOCAL_KEY_SIZE,
+            ),
+        };
+        let mut sk = Vec::new();
+        key.encode(&mut sk)
+            .map_err(|e| wrap_err("PasetoV4LocalKeyManager: failed to encode new key", e))?;
+        Ok(sk)
+    }
+
+    fn type_url(&self) -> &'static str {
+        PASETO_V4_LOCAL_TYPE_URL
+    }
+
+    fn key_material_type(&self) -> tink_proto::key_data::KeyMaterialType {
+        tink_proto::key_data::KeyMaterialType::Symmetric
+    }
+}
+
+/// Validates the given [`tink_proto::XChaCha20Poly1305Key`] as a PASETO `v4.local` key.
+fn validate_key(key: &tink_proto::XChaCha20Poly1305Key) -> Result<(), TinkError> {
+    tink_core::keyset::validate_key_version(key.version, PASETO_V4_LOCAL_KEY_VERSION)
+        .map_err(|e| wrap_err("PasetoV4LocalKeyManager", e))?;
+    if key.key_value.len() != subtle::PASETO_V4_LOCAL_KEY_SIZE {
+        return Err(format!(
+            "PasetoV4LocalKeyManager: keySize != {}",
+            subtle::PASETO_V4_LOCAL_KEY_SIZE
+        )
+        .into());
+    }
+    Ok(())
+}
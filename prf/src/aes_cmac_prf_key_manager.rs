@@ -90,6 +90,8 @@ fn validate_key(key: &tink_proto::AesCmacPrfKey) -> Result<(), TinkError> {
 }
 
 /// Validate the given [`AesCmacPrfKeyFormat`](tink_proto::AesCmacPrfKeyFormat).
-fn validate_key_format(format: &tink_proto::AesCmacPrfKeyFormat) -> Result<(), TinkError> {
+pub(crate) fn validate_key_format(
+    format: &tink_proto::AesCmacPrfKeyFormat,
+) -> Result<(), TinkError> {
     subtle::validate_aes_cmac_prf_params(format.key_size as usize)
 }
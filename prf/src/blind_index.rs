@@ -0,0 +1,108 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Blind index tokens for searching over encrypted data.
+
+use std::convert::TryInto;
+use tink_core::{utils::wrap_err, TinkError};
+
+/// Minimum length of a blind index token, in bytes.
+pub const MIN_BLIND_INDEX_TOKEN_LENGTH: usize = 1;
+/// Maximum length of a blind index token, in bytes.  This is small enough to be supported by all
+/// of the PRF types.
+pub const MAX_BLIND_INDEX_TOKEN_LENGTH: usize = 16;
+
+/// Domain separation string included in the PRF input for blind index tokens.
+const BLIND_INDEX_CONTEXT: &[u8] = b"tink-blind-index-v1\0";
+
+/// `BlindIndex` computes fixed-length blind index tokens for the values of a single field, so
+/// that encrypted records can be looked up by an exact match on the field.
+///
+/// A token is the first `token_length` bytes of the output of a PRF over
+/// `context || field_salt_length (4 bytes, big-endian) || field_salt || value`, so different
+/// fields (with different salts) give unrelated tokens for the same value.
+///
+/// Shorter tokens deliberately produce false positives (which must be filtered out after
+/// decrypting the matching records), and so reveal less about which records share a value.
+pub struct BlindIndex<'a> {
+    prf_set: &'a crate::Set,
+    field_salt: Vec<u8>,
+    token_length: usize,
+}
+
+impl<'a> BlindIndex<'a> {
+    /// Create a blind index for the field identified by `field_salt`, producing tokens of
+    /// `token_length` bytes.
+    pub fn new(
+        prf_set: &'a crate::Set,
+        field_salt: &[u8],
+        token_length: usize,
+    ) -> Result<BlindIndex<'a>, TinkError> {
+        if !(MIN_BLIND_INDEX_TOKEN_LENGTH..=MAX_BLIND_INDEX_TOKEN_LENGTH).contains(&token_length) {
+            return Err(format!(
+                "BlindIndex: token length must be between {} and {}",
+                MIN_BLIND_INDEX_TOKEN_LENGTH, MAX_BLIND_INDEX_TOKEN_LENGTH
+            )
+            .into());
+        }
+        Ok(BlindIndex {
+            prf_set,
+            field_salt: field_salt.to_vec(),
+            token_length,
+        })
+    }
+
+    /// Return the token for `value` under the primary key, for storing alongside a record.
+    pub fn token(&self, value: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.prf_set
+            .compute_primary_prf(&self.prf_input(value)?, self.token_length)
+            .map_err(|e| wrap_err("BlindIndex: cannot compute token", e))
+    }
+
+    /// Return the tokens for `value` under every key in the PRF set, as (key ID, token) pairs
+    /// ordered by key ID.  During key rotation, records may be found by searching for any of
+    /// these tokens.
+    pub fn tokens_for_all_keys(&self, value: &[u8]) -> Result<Vec<(u32, Vec<u8>)>, TinkError> {
+        let input = self.prf_input(value)?;
+        let mut tokens = self
+            .prf_set
+            .prfs
+            .iter()
+            .map(|(key_id, prf)| {
+                prf.compute_prf(&input, self.token_length)
+                    .map(|token| (*key_id, token))
+                    .map_err(|e| wrap_err("BlindIndex: cannot compute token", e))
+            })
+            .collect::<Result<Vec<_>, TinkError>>()?;
+        tokens.sort_by_key(|(key_id, _)| *key_id);
+        Ok(tokens)
+    }
+
+    fn prf_input(&self, value: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let salt_len: u32 = self
+            .field_salt
+            .len()
+            .try_into()
+            .map_err(|_| TinkError::new("BlindIndex: field salt too long"))?;
+        let mut input =
+            Vec::with_capacity(BLIND_INDEX_CONTEXT.len() + 4 + self.field_salt.len() + value.len());
+        input.extend_from_slice(BLIND_INDEX_CONTEXT);
+        input.extend_from_slice(&salt_len.to_be_bytes());
+        input.extend_from_slice(&self.field_salt);
+        input.extend_from_slice(value);
+        Ok(input)
+    }
+}
@@ -0,0 +1,131 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Derivation of key material from PRF output.
+
+use std::{convert::TryFrom, io};
+use tink_core::{utils::wrap_err, TinkError};
+use tink_proto::prost::Message;
+
+/// Number of bytes of PRF output used per block of derived key material.  This is small enough to
+/// be supported by all of the PRF types.
+const DERIVATION_BLOCK_SIZE: usize = 16;
+
+/// Derive a [`KeyData`](tink_proto::KeyData) for the key template `kt`, whose key material is
+/// taken from the output of the primary PRF of `prf_set` over `salt`.  The same PRF set, salt
+/// and template always give the same key, and the PRF input is bound to the template so that
+/// keys derived for different templates are unrelated.
+///
+/// Only some key types can be derived in this way (AES-GCM, HMAC, ChaCha20-Poly1305 and
+/// Ed25519); other key types result in an error.
+pub fn derive_key_data(
+    prf_set: &crate::Set,
+    salt: &[u8],
+    kt: &tink_proto::KeyTemplate,
+) -> Result<tink_proto::KeyData, TinkError> {
+    tink_core::registry::derive_key_data(kt, &mut PrfStream::new(prf_set, salt, kt)?)
+        .map_err(|e| wrap_err("prf::derive_key_data: cannot derive key", e))
+}
+
+/// Derive a [`Handle`](tink_core::keyset::Handle) for a keyset holding a single key for the key
+/// template `kt`, as per [`derive_key_data`].  The key ID of the key is chosen randomly.
+pub fn derive_handle(
+    prf_set: &crate::Set,
+    salt: &[u8],
+    kt: &tink_proto::KeyTemplate,
+) -> Result<tink_core::keyset::Handle, TinkError> {
+    let mut km = tink_core::keyset::Manager::new();
+    km.add_derived(kt, &mut PrfStream::new(prf_set, salt, kt)?, true)
+        .map_err(|e| wrap_err("prf::derive_handle: cannot derive key", e))?;
+    km.handle()
+}
+
+/// Stream of pseudorandom bytes, formed by concatenating the outputs of the primary PRF of a
+/// [`Set`](crate::Set) over `type_url || template || salt || counter`, where the first three
+/// fields are each prefixed by their 4-byte big-endian length, `template` is the serialized
+/// key template, and `counter` is a 4-byte big-endian block counter.
+struct PrfStream<'a> {
+    prf_set: &'a crate::Set,
+    prefix: Vec<u8>,
+    counter: u32,
+    block: Vec<u8>,
+    pos: usize,
+}
+
+/// Append `data` to `out`, prefixed by its 4-byte big-endian length.
+fn append_length_prefixed(out: &mut Vec<u8>, data: &[u8]) -> Result<(), TinkError> {
+    let len = u32::try_from(data.len()).map_err(|_| TinkError::new("derivation input too long"))?;
+    out.extend_from_slice(&len.to_be_bytes());
+    out.extend_from_slice(data);
+    Ok(())
+}
+
+impl<'a> PrfStream<'a> {
+    fn new(
+        prf_set: &'a crate::Set,
+        salt: &[u8],
+        kt: &tink_proto::KeyTemplate,
+    ) -> Result<Self, TinkError> {
+        let mut template = Vec::new();
+        kt.encode(&mut template)
+            .map_err(|e| wrap_err("failed to encode key template", e))?;
+        let mut prefix = Vec::new();
+        append_length_prefixed(&mut prefix, kt.type_url.as_bytes())?;
+        append_length_prefixed(&mut prefix, &template)?;
+        append_length_prefixed(&mut prefix, salt)?;
+        Ok(PrfStream {
+            prf_set,
+            prefix,
+            counter: 0,
+            block: Vec::new(),
+            pos: 0,
+        })
+    }
+
+    fn next_block(&mut self) -> Result<(), TinkError> {
+        let mut input = Vec::with_capacity(self.prefix.len() + 4);
+        input.extend_from_slice(&self.prefix);
+        input.extend_from_slice(&self.counter.to_be_bytes());
+        self.block = self
+            .prf_set
+            .compute_primary_prf(&input, DERIVATION_BLOCK_SIZE)?;
+        if self.block.len() != DERIVATION_BLOCK_SIZE {
+            return Err("unexpected PRF output length".into());
+        }
+        self.counter = self
+            .counter
+            .checked_add(1)
+            .ok_or_else(|| TinkError::new("too much key material requested"))?;
+        self.pos = 0;
+        Ok(())
+    }
+}
+
+impl<'a> io::Read for PrfStream<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.pos >= self.block.len() {
+            self.next_block()
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{:?}", e)))?;
+        }
+        let n = std::cmp::min(buf.len(), self.block.len() - self.pos);
+        buf[..n].copy_from_slice(&self.block[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
@@ -81,7 +81,7 @@ impl tink_core::registry::KeyManager for HkdfPrfKeyManager {
 
 /// Validate the given [`HkdfPrfKey`](tink_proto::HkdfPrfKey). It only validates the version of the
 /// key because other parameters will be validated in primitive construction.
-fn validate_key(
+pub(crate) fn validate_key(
     key: &tink_proto::HkdfPrfKey,
 ) -> Result<(tink_proto::HkdfPrfParams, HashType), TinkError> {
     tink_core::keyset::validate_key_version(key.version, HKDF_PRF_KEY_VERSION)
@@ -91,17 +91,17 @@ fn validate_key(
         None => return Err("HkdfPrfKeyManager: no key params".into()),
         Some(p) => p,
     };
-    let hash = HashType::from_i32(params.hash).unwrap_or(HashType::UnknownHash);
+    let hash = params.try_hash()?;
     subtle::validate_hkdf_prf_params(hash, key_size, &params.salt)?;
     Ok((params.clone(), hash))
 }
 
 /// Validate the given [`HkdfPrfKeyFormat`](tink_proto::HkdfPrfKeyFormat).
-fn validate_key_format(format: &tink_proto::HkdfPrfKeyFormat) -> Result<(), TinkError> {
+pub(crate) fn validate_key_format(format: &tink_proto::HkdfPrfKeyFormat) -> Result<(), TinkError> {
     let params = format
         .params
         .as_ref()
         .ok_or_else(|| TinkError::new("no key params"))?;
-    let hash = HashType::from_i32(params.hash).unwrap_or(HashType::UnknownHash);
+    let hash = params.try_hash()?;
     subtle::validate_hkdf_prf_params(hash, format.key_size as usize, &params.salt)
 }
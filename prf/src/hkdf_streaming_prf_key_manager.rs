@@ -0,0 +1,66 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Key manager for HKDF keys for streaming PRF.
+
+use crate::{hkdf_prf_key_manager::validate_key, subtle};
+use tink_core::{registry::KeyManager, utils::wrap_err, TinkError};
+use tink_proto::prost::Message;
+
+/// Produces HKDF streaming PRF instances for HKDF PRF keys.
+///
+/// As in upstream Tink, streaming PRF uses the ordinary [`HkdfPrfKey`](tink_proto::HkdfPrfKey)
+/// key type (with type URL [`HKDF_PRF_TYPE_URL`](crate::HKDF_PRF_TYPE_URL)); the same key gives
+/// a [`tink_core::Prf`] through the registry and a [`tink_core::StreamingPrf`] through
+/// [`new_streaming_prf`](crate::new_streaming_prf), which passes this key manager in place of the
+/// registered one.  It is therefore not registered itself.
+#[derive(Default)]
+pub(crate) struct HkdfStreamingPrfKeyManager;
+
+impl KeyManager for HkdfStreamingPrfKeyManager {
+    /// Construct an HKDF streaming PRF instance for the given serialized
+    /// [`HkdfPrfKey`](tink_proto::HkdfPrfKey).
+    fn primitive(&self, serialized_key: &[u8]) -> Result<tink_core::Primitive, TinkError> {
+        if serialized_key.is_empty() {
+            return Err("HkdfStreamingPrfKeyManager: invalid key".into());
+        }
+        let key = tink_proto::HkdfPrfKey::decode(serialized_key)
+            .map_err(|_| "HkdfStreamingPrfKeyManager: invalid key")?;
+        let (params, hash) =
+            validate_key(&key).map_err(|e| wrap_err("HkdfStreamingPrfKeyManager", e))?;
+
+        match subtle::HkdfStreamingPrf::new(hash, &key.key_value, &params.salt) {
+            Ok(p) => Ok(tink_core::Primitive::StreamingPrf(Box::new(p))),
+            Err(e) => Err(wrap_err(
+                "HkdfStreamingPrfKeyManager: cannot create new primitive",
+                e,
+            )),
+        }
+    }
+
+    /// Generate a new [`HkdfPrfKey`](tink_proto::HkdfPrfKey), as for HKDF PRF.
+    fn new_key(&self, serialized_key_format: &[u8]) -> Result<Vec<u8>, TinkError> {
+        crate::hkdf_prf_key_manager::HkdfPrfKeyManager.new_key(serialized_key_format)
+    }
+
+    fn type_url(&self) -> &'static str {
+        crate::HKDF_PRF_TYPE_URL
+    }
+
+    fn key_material_type(&self) -> tink_proto::key_data::KeyMaterialType {
+        tink_proto::key_data::KeyMaterialType::Symmetric
+    }
+}
@@ -89,17 +89,17 @@ fn validate_key(
         None => return Err("no key params".into()),
         Some(p) => p,
     };
-    let hash = HashType::from_i32(params.hash).unwrap_or(HashType::UnknownHash);
+    let hash = params.try_hash()?;
     subtle::validate_hmac_prf_params(hash, key_size)?;
     Ok((params.clone(), hash))
 }
 
 /// Validates the given [`HmacPrfKeyFormat`](tink_proto::HmacPrfKeyFormat).
-fn validate_key_format(format: &tink_proto::HmacPrfKeyFormat) -> Result<(), TinkError> {
+pub(crate) fn validate_key_format(format: &tink_proto::HmacPrfKeyFormat) -> Result<(), TinkError> {
     let params = format
         .params
         .as_ref()
         .ok_or_else(|| TinkError::new("no params"))?;
-    let hash = HashType::from_i32(params.hash).unwrap_or(HashType::UnknownHash);
+    let hash = params.try_hash()?;
     subtle::validate_hmac_prf_params(hash, format.key_size as usize)
 }
@@ -16,20 +16,21 @@
 
 //! This module contains pre-generated [`KeyTemplate`] instances for PRF.
 
-use tink_proto::{prost::Message, KeyTemplate};
+use tink_core::TinkError;
+use tink_proto::{prost::Message, HashType, KeyTemplate};
 
 /// Return a [`KeyTemplate`] that generates an HMAC key with the following parameters:
 ///  - Key size: 32 bytes
 ///  - Hash function: SHA256
 pub fn hmac_sha256_prf_key_template() -> KeyTemplate {
-    create_hmac_prf_key_template(32, tink_proto::HashType::Sha256)
+    create_hmac_prf_key_template(32, HashType::Sha256)
 }
 
 /// Return a [`KeyTemplate`] that generates an HMAC key with the following parameters:
 ///  - Key size: 64 bytes
 ///  - Hash function: SHA512
 pub fn hmac_sha512_prf_key_template() -> KeyTemplate {
-    create_hmac_prf_key_template(64, tink_proto::HashType::Sha512)
+    create_hmac_prf_key_template(64, HashType::Sha512)
 }
 
 /// Return a [`KeyTemplate`] that generates an HKDF key with the following parameters:
@@ -37,7 +38,7 @@ pub fn hmac_sha512_prf_key_template() -> KeyTemplate {
 ///  - Salt: empty
 ///  - Hash function: SHA256
 pub fn hkdf_sha256_prf_key_template() -> KeyTemplate {
-    create_hkdf_prf_key_template(32, tink_proto::HashType::Sha256, &[])
+    create_hkdf_prf_key_template(32, HashType::Sha256, &[])
 }
 
 /// Return a [`KeyTemplate`] that generates an AES-CMAC key with the following parameters:
@@ -47,59 +48,182 @@ pub fn aes_cmac_prf_key_template() -> KeyTemplate {
 }
 
 /// Create a new [`KeyTemplate`] for HMAC using the given parameters.
-fn create_hmac_prf_key_template(key_size: u32, hash_type: tink_proto::HashType) -> KeyTemplate {
-    let params = tink_proto::HmacPrfParams {
-        hash: hash_type as i32,
-    };
-    let format = tink_proto::HmacPrfKeyFormat {
-        params: Some(params),
-        key_size,
-        version: super::HMAC_PRF_KEY_VERSION,
-    };
-    let mut serialized_format = Vec::new();
-    format.encode(&mut serialized_format).unwrap(); // safe: proto-encode
-    KeyTemplate {
-        type_url: super::HMAC_PRF_TYPE_URL.to_string(),
-        output_prefix_type: tink_proto::OutputPrefixType::Raw as i32,
-        value: serialized_format,
-    }
+fn create_hmac_prf_key_template(key_size: u32, hash_type: HashType) -> KeyTemplate {
+    HmacPrfTemplateBuilder::new()
+        .key_size(key_size)
+        .hash(hash_type)
+        .build()
+        .unwrap() // safe: valid parameters
 }
 
 /// Creates a new [`KeyTemplate`] for HKDF using the given parameters.
-fn create_hkdf_prf_key_template(
-    key_size: u32,
-    hash_type: tink_proto::HashType,
-    salt: &[u8],
-) -> KeyTemplate {
-    let params = tink_proto::HkdfPrfParams {
-        hash: hash_type as i32,
-        salt: salt.to_vec(),
-    };
-    let format = tink_proto::HkdfPrfKeyFormat {
-        params: Some(params),
-        key_size,
-        version: super::HKDF_PRF_KEY_VERSION,
-    };
-    let mut serialized_format = Vec::new();
-    format.encode(&mut serialized_format).unwrap(); // safe: proto-encode
-    KeyTemplate {
-        type_url: super::HKDF_PRF_TYPE_URL.to_string(),
-        output_prefix_type: tink_proto::OutputPrefixType::Raw as i32,
-        value: serialized_format,
-    }
+fn create_hkdf_prf_key_template(key_size: u32, hash_type: HashType, salt: &[u8]) -> KeyTemplate {
+    HkdfPrfTemplateBuilder::new()
+        .key_size(key_size)
+        .hash(hash_type)
+        .salt(salt)
+        .build()
+        .unwrap() // safe: valid parameters
 }
 
 // Create a new [`KeyTemplate`] for AES-CMAC using the given parameters.
 fn create_aes_cmac_prf_key_template(key_size: u32) -> KeyTemplate {
-    let format = tink_proto::AesCmacPrfKeyFormat {
-        key_size,
-        version: super::AES_CMAC_PRF_KEY_VERSION,
-    };
+    AesCmacPrfTemplateBuilder::new()
+        .key_size(key_size)
+        .build()
+        .unwrap() // safe: valid parameters
+}
+
+/// Serialize a key format into a [`KeyTemplate`].  PRF keys always use RAW output prefix.
+fn serialize_template<T: Message>(type_url: &str, format: &T) -> KeyTemplate {
     let mut serialized_format = Vec::new();
     format.encode(&mut serialized_format).unwrap(); // safe: proto-encode
     KeyTemplate {
-        type_url: super::AES_CMAC_PRF_TYPE_URL.to_string(),
+        type_url: type_url.to_string(),
         output_prefix_type: tink_proto::OutputPrefixType::Raw as i32,
         value: serialized_format,
     }
 }
+
+/// Builder for HMAC-PRF [`KeyTemplate`]s, whose parameters are checked when the template is
+/// built.  Defaults to the parameters of [`hmac_sha256_prf_key_template`].
+#[derive(Clone, Copy, Debug)]
+pub struct HmacPrfTemplateBuilder {
+    key_size: u32,
+    hash: HashType,
+}
+
+impl Default for HmacPrfTemplateBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HmacPrfTemplateBuilder {
+    /// Create a builder with the default parameters.
+    pub fn new() -> Self {
+        Self {
+            key_size: 32,
+            hash: HashType::Sha256,
+        }
+    }
+
+    /// Set the key size in bytes (at least 16).
+    pub fn key_size(mut self, key_size: u32) -> Self {
+        self.key_size = key_size;
+        self
+    }
+
+    /// Set the hash function.
+    pub fn hash(mut self, hash: HashType) -> Self {
+        self.hash = hash;
+        self
+    }
+
+    /// Build the [`KeyTemplate`], or return an error if the parameters are invalid.
+    pub fn build(&self) -> Result<KeyTemplate, TinkError> {
+        let format = tink_proto::HmacPrfKeyFormat {
+            params: Some(tink_proto::HmacPrfParams {
+                hash: self.hash as i32,
+            }),
+            key_size: self.key_size,
+            version: super::HMAC_PRF_KEY_VERSION,
+        };
+        crate::hmac_prf_key_manager::validate_key_format(&format)?;
+        Ok(serialize_template(super::HMAC_PRF_TYPE_URL, &format))
+    }
+}
+
+/// Builder for HKDF-PRF [`KeyTemplate`]s, whose parameters are checked when the template is
+/// built.  Defaults to the parameters of [`hkdf_sha256_prf_key_template`].
+#[derive(Clone, Debug)]
+pub struct HkdfPrfTemplateBuilder {
+    key_size: u32,
+    hash: HashType,
+    salt: Vec<u8>,
+}
+
+impl Default for HkdfPrfTemplateBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HkdfPrfTemplateBuilder {
+    /// Create a builder with the default parameters.
+    pub fn new() -> Self {
+        Self {
+            key_size: 32,
+            hash: HashType::Sha256,
+            salt: Vec::new(),
+        }
+    }
+
+    /// Set the key size in bytes (at least 32).
+    pub fn key_size(mut self, key_size: u32) -> Self {
+        self.key_size = key_size;
+        self
+    }
+
+    /// Set the hash function (SHA256 or SHA512).
+    pub fn hash(mut self, hash: HashType) -> Self {
+        self.hash = hash;
+        self
+    }
+
+    /// Set the HKDF salt.
+    pub fn salt(mut self, salt: &[u8]) -> Self {
+        self.salt = salt.to_vec();
+        self
+    }
+
+    /// Build the [`KeyTemplate`], or return an error if the parameters are invalid.
+    pub fn build(&self) -> Result<KeyTemplate, TinkError> {
+        let format = tink_proto::HkdfPrfKeyFormat {
+            params: Some(tink_proto::HkdfPrfParams {
+                hash: self.hash as i32,
+                salt: self.salt.clone(),
+            }),
+            key_size: self.key_size,
+            version: super::HKDF_PRF_KEY_VERSION,
+        };
+        crate::hkdf_prf_key_manager::validate_key_format(&format)?;
+        Ok(serialize_template(super::HKDF_PRF_TYPE_URL, &format))
+    }
+}
+
+/// Builder for AES-CMAC-PRF [`KeyTemplate`]s, whose parameters are checked when the template is
+/// built.  Defaults to the parameters of [`aes_cmac_prf_key_template`].
+#[derive(Clone, Copy, Debug)]
+pub struct AesCmacPrfTemplateBuilder {
+    key_size: u32,
+}
+
+impl Default for AesCmacPrfTemplateBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AesCmacPrfTemplateBuilder {
+    /// Create a builder with the default parameters.
+    pub fn new() -> Self {
+        Self { key_size: 32 }
+    }
+
+    /// Set the key size in bytes (only 32 is supported).
+    pub fn key_size(mut self, key_size: u32) -> Self {
+        self.key_size = key_size;
+        self
+    }
+
+    /// Build the [`KeyTemplate`], or return an error if the parameters are invalid.
+    pub fn build(&self) -> Result<KeyTemplate, TinkError> {
+        let format = tink_proto::AesCmacPrfKeyFormat {
+            key_size: self.key_size,
+            version: super::AES_CMAC_PRF_KEY_VERSION,
+        };
+        crate::aes_cmac_prf_key_manager::validate_key_format(&format)?;
+        Ok(serialize_template(super::AES_CMAC_PRF_TYPE_URL, &format))
+    }
+}
@@ -14,7 +14,8 @@
 //
 ////////////////////////////////////////////////////////////////////////////////
 
-//! This crate provides implementations of the [`tink_core::Prf`] primitive.
+//! This crate provides implementations of the [`tink_core::Prf`] and
+//! [`tink_core::StreamingPrf`] primitives.
 
 #![deny(broken_intra_doc_links)]
 
@@ -23,14 +24,22 @@ use tink_core::registry::register_key_manager;
 
 mod aes_cmac_prf_key_manager;
 pub use aes_cmac_prf_key_manager::*;
+mod blind_index;
+pub use blind_index::*;
+mod derive;
+pub use derive::*;
 mod hkdf_prf_key_manager;
 pub use hkdf_prf_key_manager::*;
+mod hkdf_streaming_prf_key_manager;
+use hkdf_streaming_prf_key_manager::*;
 mod hmac_prf_key_manager;
 pub use hmac_prf_key_manager::*;
 mod key_templates;
 pub use key_templates::*;
 mod set_factory;
 pub use set_factory::*;
+mod streaming_prf_factory;
+pub use streaming_prf_factory::*;
 
 pub mod subtle;
 
@@ -64,6 +64,9 @@ impl Set {
 }
 
 fn wrap_prf_set(ps: tink_core::primitiveset::PrimitiveSet) -> Result<Set, TinkError> {
+    if !ps.usage.allows_compute() {
+        return Err("prf::Set: PRF computation not permitted".into());
+    }
     let entry = match &ps.primary {
         None => return Err("prf::Set: no primary available".into()),
         Some(e) => e,
@@ -0,0 +1,47 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Provides a [`StreamingPrf`] for the primary key of a keyset.
+
+use tink_core::{utils::wrap_err, StreamingPrf, TinkError};
+
+/// Return a [`StreamingPrf`] primitive from the given keyset handle.  Only keysets containing a
+/// single (primary) key with RAW output prefix are supported, as there is no way to select between
+/// keys for a PRF.  The key must be an HKDF PRF key (for example from
+/// [`hkdf_sha256_prf_key_template`](crate::hkdf_sha256_prf_key_template)); the streaming PRF
+/// output starts with the output of the (non-streaming) HKDF PRF for the same key.
+pub fn new_streaming_prf(
+    h: &tink_core::keyset::Handle,
+) -> Result<Box<dyn StreamingPrf>, TinkError> {
+    let ps = h
+        .primitives_with_key_manager(Some(std::sync::Arc::new(
+            crate::HkdfStreamingPrfKeyManager::default(),
+        )))
+        .map_err(|e| wrap_err("streaming_prf::factory: cannot obtain primitive set", e))?;
+    if !ps.usage.allows_compute() {
+        return Err("streaming_prf::factory: PRF computation not permitted".into());
+    }
+    if ps.entries.len() != 1 || ps.raw_entries().len() != 1 {
+        return Err("streaming_prf::factory: only a single RAW key is allowed".into());
+    }
+    match ps.primary {
+        Some(entry) => match entry.primitive {
+            tink_core::Primitive::StreamingPrf(p) => Ok(p),
+            _ => Err("streaming_prf::factory: not a StreamingPrf primitive".into()),
+        },
+        None => Err("streaming_prf::factory: no primary available".into()),
+    }
+}
@@ -76,6 +76,16 @@ pub fn validate_hkdf_prf_params(
 
 impl tink_core::Prf for HkdfPrf {
     fn compute_prf(&self, data: &[u8], out_len: usize) -> Result<Vec<u8>, TinkError> {
+        let hash = match &self.prk {
+            HkdfPrfVariant::Sha1(_) => HashType::Sha1,
+            HkdfPrfVariant::Sha256(_) => HashType::Sha256,
+            HkdfPrfVariant::Sha512(_) => HashType::Sha512,
+        };
+        // Check the length before allocating the output.
+        let max_len = tink_core::subtle::hkdf_max_output_size(hash)?;
+        if out_len > max_len {
+            return Err(format!("HkdfPrf: output_length must be between 0 and {}", max_len).into());
+        }
         let mut okm = vec![0; out_len];
         match &self.prk {
             HkdfPrfVariant::Sha1(prk) => prk
@@ -0,0 +1,115 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Provides an implementation of streaming PRF using HKDF.
+
+use super::{validate_hkdf_prf_params, HmacPrf};
+use tink_core::{Prf, TinkError};
+use tink_proto::HashType;
+
+/// Maximum number of HKDF-Expand output blocks (RFC 5869 section 2.3).
+const MAX_HKDF_OUTPUT_BLOCKS: u8 = 255;
+
+/// `HkdfStreamingPrf` produces the output of HKDF-Expand as a stream, with the `input` to the
+/// PRF used as the HKDF `info` parameter.  The output is limited to 255 times the digest size.
+#[derive(Clone)]
+pub struct HkdfStreamingPrf {
+    prk: HmacPrf,
+    digest_size: usize,
+}
+
+impl HkdfStreamingPrf {
+    /// Create a new [`HkdfStreamingPrf`] object, performing HKDF-Extract on the key material.
+    pub fn new(hash: HashType, key: &[u8], salt: &[u8]) -> Result<HkdfStreamingPrf, TinkError> {
+        validate_hkdf_prf_params(hash, key.len(), salt)
+            .map_err(|e| tink_core::utils::wrap_err("HkdfStreamingPrf", e))?;
+        let (prk, digest_size) = match hash {
+            HashType::Sha256 => {
+                let (prk, _) = hkdf::Hkdf::<sha2::Sha256>::extract(Some(salt), key);
+                (prk.to_vec(), 32)
+            }
+            HashType::Sha512 => {
+                let (prk, _) = hkdf::Hkdf::<sha2::Sha512>::extract(Some(salt), key);
+                (prk.to_vec(), 64)
+            }
+            h => return Err(format!("HkdfStreamingPrf: unsupported hash {:?}", h).into()),
+        };
+        Ok(HkdfStreamingPrf {
+            prk: HmacPrf::new(hash, &prk)?,
+            digest_size,
+        })
+    }
+}
+
+impl tink_core::StreamingPrf for HkdfStreamingPrf {
+    fn compute_prf(&self, input: &[u8]) -> Result<Box<dyn std::io::Read>, TinkError> {
+        Ok(Box::new(HkdfExpandReader {
+            prk: self.prk.clone(),
+            digest_size: self.digest_size,
+            info: input.to_vec(),
+            block: Vec::new(),
+            pos: 0,
+            counter: 0,
+        }))
+    }
+}
+
+/// Reader that generates HKDF-Expand output one block at a time.
+struct HkdfExpandReader {
+    prk: HmacPrf,
+    digest_size: usize,
+    info: Vec<u8>,
+    /// Most recent output block T(counter).
+    block: Vec<u8>,
+    /// Number of bytes of `block` already returned.
+    pos: usize,
+    counter: u8,
+}
+
+impl HkdfExpandReader {
+    /// Compute the next output block: T(n) = HMAC-Hash(PRK, T(n-1) | info | n).
+    fn next_block(&mut self) -> std::io::Result<()> {
+        self.counter += 1;
+        let mut data = Vec::with_capacity(self.block.len() + self.info.len() + 1);
+        data.extend_from_slice(&self.block);
+        data.extend_from_slice(&self.info);
+        data.push(self.counter);
+        self.block = self
+            .prk
+            .compute_prf(&data, self.digest_size)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("{:?}", e)))?;
+        self.pos = 0;
+        Ok(())
+    }
+}
+
+impl std::io::Read for HkdfExpandReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.pos == self.block.len() {
+            if self.counter == MAX_HKDF_OUTPUT_BLOCKS {
+                return Ok(0);
+            }
+            self.next_block()?;
+        }
+        let n = std::cmp::min(buf.len(), self.block.len() - self.pos);
+        buf[..n].copy_from_slice(&self.block[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
@@ -17,23 +17,25 @@
 //! Provides an implementation of PRF using HMAC.
 
 use ::hmac::{Hmac, Mac};
-use std::{
-    cmp::min,
-    ops::DerefMut,
-    sync::{Arc, Mutex},
-};
+use std::cmp::min;
 use tink_core::TinkError;
 use tink_proto::HashType;
 
 const MIN_HMAC_KEY_SIZE_IN_BYTES: usize = 16;
 
 /// `HmacPrf` is a type that can be used to compute several HMACs with the same key material.
+///
+/// The key is expanded into the inner and outer hash states once, on construction. Each
+/// computation then starts from a copy of this keyed state, so short messages do not pay for
+/// key processing and concurrent callers do not contend on a lock.
 #[derive(Clone)]
 pub struct HmacPrf {
-    mac: Arc<Mutex<HmacPrfVariant>>,
+    mac: HmacPrfVariant,
     mac_size: usize,
 }
 
+/// Keyed HMAC state for each supported hash function; never updated after construction.
+#[derive(Clone)]
 enum HmacPrfVariant {
     Sha1(Hmac<sha1::Sha1>),
     Sha224(Hmac<sha2::Sha224>),
@@ -75,10 +77,7 @@ impl HmacPrf {
             HmacPrfVariant::Sha512(_) => 64,
         };
 
-        Ok(HmacPrf {
-            mac: Arc::new(Mutex::new(mac)),
-            mac_size,
-        })
+        Ok(HmacPrf { mac, mac_size })
     }
 }
 
@@ -103,39 +102,20 @@ impl tink_core::Prf for HmacPrf {
             )
             .into());
         }
-        Ok(
-            match self
-                .mac
-                .lock()
-                .expect("internal lock corrupted") // safe: lock
-                .deref_mut()
-            {
-                HmacPrfVariant::Sha1(mac) => {
-                    mac.update(data);
-                    let result = mac.finalize_reset().into_bytes();
-                    result[..min(result.len(), output_length)].to_vec()
-                }
-                HmacPrfVariant::Sha224(mac) => {
-                    mac.update(data);
-                    let result = mac.finalize_reset().into_bytes();
-                    result[..min(result.len(), output_length)].to_vec()
-                }
-                HmacPrfVariant::Sha256(mac) => {
-                    mac.update(data);
-                    let result = mac.finalize_reset().into_bytes();
-                    result[..min(result.len(), output_length)].to_vec()
-                }
-                HmacPrfVariant::Sha384(mac) => {
-                    mac.update(data);
-                    let result = mac.finalize_reset().into_bytes();
-                    result[..min(result.len(), output_length)].to_vec()
-                }
-                HmacPrfVariant::Sha512(mac) => {
-                    mac.update(data);
-                    let result = mac.finalize_reset().into_bytes();
-                    result[..min(result.len(), output_length)].to_vec()
-                }
-            },
-        )
+        Ok(match &self.mac {
+            HmacPrfVariant::Sha1(mac) => compute_from_keyed_state(mac, data, output_length),
+            HmacPrfVariant::Sha224(mac) => compute_from_keyed_state(mac, data, output_length),
+            HmacPrfVariant::Sha256(mac) => compute_from_keyed_state(mac, data, output_length),
+            HmacPrfVariant::Sha384(mac) => compute_from_keyed_state(mac, data, output_length),
+            HmacPrfVariant::Sha512(mac) => compute_from_keyed_state(mac, data, output_length),
+        })
     }
 }
+
+/// Compute the (truncated) MAC of `data`, starting from a copy of the precomputed keyed state.
+fn compute_from_keyed_state<M: Mac + Clone>(mac: &M, data: &[u8], output_length: usize) -> Vec<u8> {
+    let mut mac = mac.clone();
+    mac.update(data);
+    let result = mac.finalize().into_bytes();
+    result[..min(result.len(), output_length)].to_vec()
+}
@@ -14,11 +14,14 @@
 //
 ////////////////////////////////////////////////////////////////////////////////
 
-//! Provides subtle implementations of the `tink_core::Prf` primitive.
+//! Provides subtle implementations of the `tink_core::Prf` and `tink_core::StreamingPrf`
+//! primitives.
 
 mod aes_cmac;
 pub use self::aes_cmac::*;
 mod hkdf;
 pub use self::hkdf::*;
+mod hkdf_streaming;
+pub use self::hkdf_streaming::*;
 mod hmac;
 pub use self::hmac::*;
@@ -17,6 +17,12 @@
 use std::path::{Path, PathBuf};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // The `regenerate` feature builds the generated code from the vendored `.proto` files into
+    // `OUT_DIR` and uses that in place of the checked-in code.
+    if std::env::var_os("CARGO_FEATURE_REGENERATE").is_some() {
+        return regenerate();
+    }
+
     // As of prost-build v0.11, there is no longer a fallback version of `protoc` included in
     // prost-build; instead, prost-build needs an externally supplied version of `protoc`.
     //
@@ -48,7 +54,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         "hkdf_prf.proto",
         "hmac.proto",
         "hmac_prf.proto",
+        "hpke.proto",
+        "jwt_ecdsa.proto",
         "jwt_hmac.proto",
+        "jwt_rsa_ssa_pkcs1.proto",
+        "jwt_rsa_ssa_pss.proto",
         "kms_aead.proto",
         "kms_envelope.proto",
         "prf_based_deriver.proto",
@@ -59,22 +69,122 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     ];
     let proto_path = Path::new("proto").to_path_buf();
     let proto_files: Vec<PathBuf> = source_files.iter().map(|f| proto_path.join(f)).collect();
+    let project_files = project_proto_files()?;
 
     // Tell cargo to rerun this build script if any proto file has changed.
     // https://doc.rust-lang.org/cargo/reference/build-scripts.html#cargorerun-if-changedpath
-    for proto_file in &proto_files {
+    for proto_file in proto_files.iter().chain(&project_files) {
         println!("cargo:rerun-if-changed={}", proto_file.display());
     }
 
+    // Emit generated code into the source directory, so it can be checked in.
+    generate(
+        &proto_files,
+        &project_files,
+        Path::new("."),
+        Path::new("src/codegen"),
+    )
+}
+
+/// Return the `.proto` files for the key types that are specific to this Rust port, which live in
+/// the `tink_rust` package (in the `tink_rust/` directory) rather than `google.crypto.tink`.
+fn project_proto_files() -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir("tink_rust")? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().map_or(false, |ext| ext == "proto") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Generate code for all of the `.proto` files in the vendored proto directory (or the directory
+/// given by `TINK_PROTO_DIR`), emitting it into `OUT_DIR`.
+fn regenerate() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-env-changed=TINK_PROTO_DIR");
+    println!("cargo:rerun-if-env-changed=PROTOC");
+    println!("cargo:rustc-check-cfg=cfg(tink_proto_regenerated)");
+
+    // Fail rather than silently use the checked-in code, which would not include any new message
+    // definitions that the `regenerate` feature was enabled to pick up.
+    let protoc = prost_build::protoc_from_env();
+    if let Err(e) = std::process::Command::new(&protoc)
+        .arg("--version")
+        .output()
+    {
+        return Err(format!(
+            "tink-proto: the `regenerate` feature needs protoc, but cannot run {} ({}); install \
+             protoc or set the PROTOC environment variable to its location",
+            protoc.display(),
+            e
+        )
+        .into());
+    }
+
+    // Upstream `.proto` files import each other as "proto/<name>.proto", so the include path is
+    // the parent of the proto directory.
+    let proto_path = match std::env::var_os("TINK_PROTO_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => Path::new("proto").to_path_buf(),
+    };
+    let include_dir = match proto_path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    println!("cargo:rerun-if-changed={}", proto_path.display());
+
+    // Only top-level files are included; subdirectories (e.g. `experimental/`) are skipped.
+    let mut proto_files = Vec::new();
+    for entry in std::fs::read_dir(&proto_path)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().map_or(false, |ext| ext == "proto") {
+            println!("cargo:rerun-if-changed={}", path.display());
+            proto_files.push(path);
+        }
+    }
+    proto_files.sort();
+    let project_files = project_proto_files()?;
+    for path in &project_files {
+        println!("cargo:rerun-if-changed={}", path.display());
+    }
+
+    let out_dir = PathBuf::from(std::env::var_os("OUT_DIR").ok_or("OUT_DIR not set")?);
+    std::fs::create_dir_all(out_dir.join("serde"))?;
+    generate(&proto_files, &project_files, &include_dir, &out_dir)?;
+    println!("cargo:rustc-cfg=tink_proto_regenerated");
+    Ok(())
+}
+
+/// Generate code for the given upstream and project `.proto` files into `out_dir`, together with
+/// a variant that includes serde-related annotations into `out_dir/serde`.
+///
+/// The project files (in the `tink_rust` package) are generated separately, with references to
+/// upstream messages mapped onto the crate root where the upstream code is included.
+fn generate(
+    proto_files: &[PathBuf],
+    project_files: &[PathBuf],
+    include_dir: &Path,
+    out_dir: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Project files import upstream files as "proto/<name>.proto" relative to `include_dir`, and
+    // are themselves found relative to the crate directory.
+    let project_include_dirs = [include_dir, Path::new(".")];
+    for dir in [out_dir.to_path_buf(), out_dir.join("serde")] {
+        prost_build::Config::new()
+            .out_dir(dir)
+            .extern_path(".google.crypto.tink", "crate")
+            .compile_protos(project_files, &project_include_dirs)?;
+    }
+
     prost_build::Config::new()
-        // Emit generated code into the source directory, so it can be checked in.
-        .out_dir("src/codegen")
-        .compile_protos(&proto_files, &[PathBuf::from(".")])?;
+        .out_dir(out_dir)
+        .compile_protos(proto_files, &[include_dir])?;
 
     // Separate variant with serde-related annotations
     prost_build::Config::new()
-        // Emit generated code into the source directory, so it can be checked in.
-        .out_dir("src/codegen/serde")
+        .out_dir(out_dir.join("serde"))
         // Set up serde-json options for Keyset-related messages
         .type_attribute(
             "EncryptedKeyset",
@@ -126,7 +236,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             "EncryptedKeyset.encrypted_keyset",
             "#[serde(with = \"crate::json::b64\")]",
         )
-        .compile_protos(&proto_files, &[PathBuf::from(".")])?;
+        .compile_protos(proto_files, &[include_dir])?;
 
     Ok(())
 }
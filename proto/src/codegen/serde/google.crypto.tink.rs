@@ -319,49 +319,6 @@ pub struct AesSivKey {
     #[prost(bytes = "vec", tag = "2")]
     pub key_value: ::prost::alloc::vec::Vec<u8>,
 }
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct ChaCha20Poly1305KeyFormat {}
-/// key_type: type.googleapis.com/google.crypto.tink.ChaCha20Poly1305.
-/// This key type actually implements ChaCha20Poly1305 as described
-/// at <https://tools.ietf.org/html/rfc7539#section-2.8.>
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct ChaCha20Poly1305Key {
-    #[prost(uint32, tag = "1")]
-    pub version: u32,
-    #[prost(bytes = "vec", tag = "2")]
-    pub key_value: ::prost::alloc::vec::Vec<u8>,
-}
-/// An entry that describes a key type to be used with Tink library,
-/// specifying the corresponding primitive, key manager, and deprecation status.
-/// All fields are required.
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct KeyTypeEntry {
-    /// E.g. “Aead”, “Mac”, ... (case-insensitive)
-    #[prost(string, tag = "1")]
-    pub primitive_name: ::prost::alloc::string::String,
-    /// Name of the key type.
-    #[prost(string, tag = "2")]
-    pub type_url: ::prost::alloc::string::String,
-    /// Minimum required version of key manager.
-    #[prost(uint32, tag = "3")]
-    pub key_manager_version: u32,
-    /// Can the key manager create new keys?
-    #[prost(bool, tag = "4")]
-    pub new_key_allowed: bool,
-    /// Catalogue to be queried for key manager,
-    #[prost(string, tag = "5")]
-    pub catalogue_name: ::prost::alloc::string::String,
-}
-/// A complete configuration of Tink library: a list of key types
-/// to be available via the Registry after initialization.
-/// All fields are required.
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct RegistryConfig {
-    #[prost(string, tag = "1")]
-    pub config_name: ::prost::alloc::string::String,
-    #[prost(message, repeated, tag = "2")]
-    pub entry: ::prost::alloc::vec::Vec<KeyTypeEntry>,
-}
 /// Protos for Ecdsa.
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct EcdsaParams {
@@ -445,6 +402,49 @@ impl EcdsaSignatureEncoding {
     }
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChaCha20Poly1305KeyFormat {}
+/// key_type: type.googleapis.com/google.crypto.tink.ChaCha20Poly1305.
+/// This key type actually implements ChaCha20Poly1305 as described
+/// at <https://tools.ietf.org/html/rfc7539#section-2.8.>
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChaCha20Poly1305Key {
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+    #[prost(bytes = "vec", tag = "2")]
+    pub key_value: ::prost::alloc::vec::Vec<u8>,
+}
+/// An entry that describes a key type to be used with Tink library,
+/// specifying the corresponding primitive, key manager, and deprecation status.
+/// All fields are required.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct KeyTypeEntry {
+    /// E.g. “Aead”, “Mac”, ... (case-insensitive)
+    #[prost(string, tag = "1")]
+    pub primitive_name: ::prost::alloc::string::String,
+    /// Name of the key type.
+    #[prost(string, tag = "2")]
+    pub type_url: ::prost::alloc::string::String,
+    /// Minimum required version of key manager.
+    #[prost(uint32, tag = "3")]
+    pub key_manager_version: u32,
+    /// Can the key manager create new keys?
+    #[prost(bool, tag = "4")]
+    pub new_key_allowed: bool,
+    /// Catalogue to be queried for key manager,
+    #[prost(string, tag = "5")]
+    pub catalogue_name: ::prost::alloc::string::String,
+}
+/// A complete configuration of Tink library: a list of key types
+/// to be available via the Registry after initialization.
+/// All fields are required.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RegistryConfig {
+    #[prost(string, tag = "1")]
+    pub config_name: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag = "2")]
+    pub entry: ::prost::alloc::vec::Vec<KeyTypeEntry>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct KeyTemplate {
     /// Required. The type_url of the key type in format
     /// type.googleapis.com/packagename.messagename -- see above for details.
@@ -842,6 +842,164 @@ pub struct HmacPrfKeyFormat {
     #[prost(uint32, tag = "3")]
     pub version: u32,
 }
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HpkeParams {
+    #[prost(enumeration = "HpkeKem", tag = "1")]
+    pub kem: i32,
+    #[prost(enumeration = "HpkeKdf", tag = "2")]
+    pub kdf: i32,
+    #[prost(enumeration = "HpkeAead", tag = "3")]
+    pub aead: i32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HpkePublicKey {
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+    #[prost(message, optional, tag = "2")]
+    pub params: ::core::option::Option<HpkeParams>,
+    /// KEM-encoding of public key (i.e., SerializePublicKey() ) as described in
+    /// <https://www.ietf.org/archive/id/draft-irtf-cfrg-hpke-09.html#name-cryptographic-dependencies.>
+    #[prost(bytes = "vec", tag = "3")]
+    pub public_key: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HpkePrivateKey {
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+    #[prost(message, optional, tag = "2")]
+    pub public_key: ::core::option::Option<HpkePublicKey>,
+    /// KEM-encoding of private key (i.e., SerializePrivateKey() ) as described in
+    /// <https://www.ietf.org/archive/id/draft-irtf-cfrg-hpke-09.html#name-cryptographic-dependencies.>
+    #[prost(bytes = "vec", tag = "3")]
+    pub private_key: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HpkeKeyFormat {
+    #[prost(message, optional, tag = "1")]
+    pub params: ::core::option::Option<HpkeParams>,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum HpkeKem {
+    KemUnknown = 0,
+    DhkemX25519HkdfSha256 = 1,
+}
+impl HpkeKem {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            HpkeKem::KemUnknown => "KEM_UNKNOWN",
+            HpkeKem::DhkemX25519HkdfSha256 => "DHKEM_X25519_HKDF_SHA256",
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum HpkeKdf {
+    KdfUnknown = 0,
+    HkdfSha256 = 1,
+}
+impl HpkeKdf {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            HpkeKdf::KdfUnknown => "KDF_UNKNOWN",
+            HpkeKdf::HkdfSha256 => "HKDF_SHA256",
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum HpkeAead {
+    AeadUnknown = 0,
+    Aes128Gcm = 1,
+    Aes256Gcm = 2,
+    Chacha20Poly1305 = 3,
+}
+impl HpkeAead {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            HpkeAead::AeadUnknown => "AEAD_UNKNOWN",
+            HpkeAead::Aes128Gcm => "AES_128_GCM",
+            HpkeAead::Aes256Gcm => "AES_256_GCM",
+            HpkeAead::Chacha20Poly1305 => "CHACHA20_POLY1305",
+        }
+    }
+}
+/// key_type: type.googleapis.com/google.crypto.tink.JwtEcdsaPublicKey
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct JwtEcdsaPublicKey {
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+    #[prost(enumeration = "JwtEcdsaAlgorithm", tag = "2")]
+    pub algorithm: i32,
+    #[prost(bytes = "vec", tag = "3")]
+    pub x: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "4")]
+    pub y: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "5")]
+    pub custom_kid: ::core::option::Option<jwt_ecdsa_public_key::CustomKid>,
+}
+/// Nested message and enum types in `JwtEcdsaPublicKey`.
+pub mod jwt_ecdsa_public_key {
+    /// Optional, custom kid header value to be used with "RAW" keys.
+    /// "TINK" keys with this value set will be rejected.
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct CustomKid {
+        #[prost(string, tag = "1")]
+        pub value: ::prost::alloc::string::String,
+    }
+}
+/// key_type: type.googleapis.com/google.crypto.tink.JwtEcdsaPrivateKey
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct JwtEcdsaPrivateKey {
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+    #[prost(message, optional, tag = "2")]
+    pub public_key: ::core::option::Option<JwtEcdsaPublicKey>,
+    /// Unsigned big integer in bigendian representation.
+    #[prost(bytes = "vec", tag = "3")]
+    pub key_value: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct JwtEcdsaKeyFormat {
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+    #[prost(enumeration = "JwtEcdsaAlgorithm", tag = "2")]
+    pub algorithm: i32,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum JwtEcdsaAlgorithm {
+    EsUnknown = 0,
+    Es256 = 1,
+    Es384 = 2,
+    Es512 = 3,
+}
+impl JwtEcdsaAlgorithm {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            JwtEcdsaAlgorithm::EsUnknown => "ES_UNKNOWN",
+            JwtEcdsaAlgorithm::Es256 => "ES256",
+            JwtEcdsaAlgorithm::Es384 => "ES384",
+            JwtEcdsaAlgorithm::Es512 => "ES512",
+        }
+    }
+}
 /// key_type: type.googleapis.com/google.crypto.tink.JwtHmacKey
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct JwtHmacKey {
@@ -895,6 +1053,194 @@ impl JwtHmacAlgorithm {
         }
     }
 }
+/// key_type: type.googleapis.com/google.crypto.tink.JwtRsaSsaPkcs1PublicKey
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct JwtRsaSsaPkcs1PublicKey {
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+    #[prost(enumeration = "JwtRsaSsaPkcs1Algorithm", tag = "2")]
+    pub algorithm: i32,
+    /// Modulus.
+    /// Unsigned big integer in bigendian representation.
+    #[prost(bytes = "vec", tag = "3")]
+    pub n: ::prost::alloc::vec::Vec<u8>,
+    /// Public exponent.
+    /// Unsigned big integer in bigendian representation.
+    #[prost(bytes = "vec", tag = "4")]
+    pub e: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "5")]
+    pub custom_kid: ::core::option::Option<jwt_rsa_ssa_pkcs1_public_key::CustomKid>,
+}
+/// Nested message and enum types in `JwtRsaSsaPkcs1PublicKey`.
+pub mod jwt_rsa_ssa_pkcs1_public_key {
+    /// Optional, custom kid header value to be used with "RAW" keys.
+    /// "TINK" keys with this value set will be rejected.
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct CustomKid {
+        #[prost(string, tag = "1")]
+        pub value: ::prost::alloc::string::String,
+    }
+}
+/// key_type: type.googleapis.com/google.crypto.tink.RsaSsaPkcs1PrivateKey
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct JwtRsaSsaPkcs1PrivateKey {
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+    #[prost(message, optional, tag = "2")]
+    pub public_key: ::core::option::Option<JwtRsaSsaPkcs1PublicKey>,
+    /// Private exponent.
+    /// Unsigned big integer in bigendian representation.
+    #[prost(bytes = "vec", tag = "3")]
+    pub d: ::prost::alloc::vec::Vec<u8>,
+    /// The following parameters are used to optimize RSA signature computation.
+    /// The prime factor p of n.
+    /// Unsigned big integer in bigendian representation.
+    #[prost(bytes = "vec", tag = "4")]
+    pub p: ::prost::alloc::vec::Vec<u8>,
+    /// The prime factor q of n.
+    /// Unsigned big integer in bigendian representation.
+    #[prost(bytes = "vec", tag = "5")]
+    pub q: ::prost::alloc::vec::Vec<u8>,
+    /// d mod (p - 1).
+    /// Unsigned big integer in bigendian representation.
+    #[prost(bytes = "vec", tag = "6")]
+    pub dp: ::prost::alloc::vec::Vec<u8>,
+    /// d mod (q - 1).
+    /// Unsigned big integer in bigendian representation.
+    #[prost(bytes = "vec", tag = "7")]
+    pub dq: ::prost::alloc::vec::Vec<u8>,
+    /// Chinese Remainder Theorem coefficient q^(-1) mod p.
+    /// Unsigned big integer in bigendian representation.
+    #[prost(bytes = "vec", tag = "8")]
+    pub crt: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct JwtRsaSsaPkcs1KeyFormat {
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+    #[prost(enumeration = "JwtRsaSsaPkcs1Algorithm", tag = "2")]
+    pub algorithm: i32,
+    #[prost(uint32, tag = "3")]
+    pub modulus_size_in_bits: u32,
+    #[prost(bytes = "vec", tag = "4")]
+    pub public_exponent: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum JwtRsaSsaPkcs1Algorithm {
+    RsUnknown = 0,
+    Rs256 = 1,
+    Rs384 = 2,
+    Rs512 = 3,
+}
+impl JwtRsaSsaPkcs1Algorithm {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            JwtRsaSsaPkcs1Algorithm::RsUnknown => "RS_UNKNOWN",
+            JwtRsaSsaPkcs1Algorithm::Rs256 => "RS256",
+            JwtRsaSsaPkcs1Algorithm::Rs384 => "RS384",
+            JwtRsaSsaPkcs1Algorithm::Rs512 => "RS512",
+        }
+    }
+}
+/// key_type: type.googleapis.com/google.crypto.tink.JwtRsaSsaPssPublicKey
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct JwtRsaSsaPssPublicKey {
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+    #[prost(enumeration = "JwtRsaSsaPssAlgorithm", tag = "2")]
+    pub algorithm: i32,
+    /// Modulus.
+    /// Unsigned big integer in bigendian representation.
+    #[prost(bytes = "vec", tag = "3")]
+    pub n: ::prost::alloc::vec::Vec<u8>,
+    /// Public exponent.
+    /// Unsigned big integer in bigendian representation.
+    #[prost(bytes = "vec", tag = "4")]
+    pub e: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "5")]
+    pub custom_kid: ::core::option::Option<jwt_rsa_ssa_pss_public_key::CustomKid>,
+}
+/// Nested message and enum types in `JwtRsaSsaPssPublicKey`.
+pub mod jwt_rsa_ssa_pss_public_key {
+    /// Optional, custom kid header value to be used with "RAW" keys.
+    /// "TINK" keys with this value set will be rejected.
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct CustomKid {
+        #[prost(string, tag = "1")]
+        pub value: ::prost::alloc::string::String,
+    }
+}
+/// key_type: type.googleapis.com/google.crypto.tink.JwtRsaSsaPssPrivateKey
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct JwtRsaSsaPssPrivateKey {
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+    #[prost(message, optional, tag = "2")]
+    pub public_key: ::core::option::Option<JwtRsaSsaPssPublicKey>,
+    /// Private exponent.
+    /// Unsigned big integer in bigendian representation.
+    #[prost(bytes = "vec", tag = "3")]
+    pub d: ::prost::alloc::vec::Vec<u8>,
+    /// The following parameters are used to optimize RSA signature computation.
+    /// The prime factor p of n.
+    /// Unsigned big integer in bigendian representation.
+    #[prost(bytes = "vec", tag = "4")]
+    pub p: ::prost::alloc::vec::Vec<u8>,
+    /// The prime factor q of n.
+    /// Unsigned big integer in bigendian representation.
+    #[prost(bytes = "vec", tag = "5")]
+    pub q: ::prost::alloc::vec::Vec<u8>,
+    /// d mod (p - 1).
+    /// Unsigned big integer in bigendian representation.
+    #[prost(bytes = "vec", tag = "6")]
+    pub dp: ::prost::alloc::vec::Vec<u8>,
+    /// d mod (q - 1).
+    /// Unsigned big integer in bigendian representation.
+    #[prost(bytes = "vec", tag = "7")]
+    pub dq: ::prost::alloc::vec::Vec<u8>,
+    /// Chinese Remainder Theorem coefficient q^(-1) mod p.
+    /// Unsigned big integer in bigendian representation.
+    #[prost(bytes = "vec", tag = "8")]
+    pub crt: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct JwtRsaSsaPssKeyFormat {
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+    #[prost(enumeration = "JwtRsaSsaPssAlgorithm", tag = "2")]
+    pub algorithm: i32,
+    #[prost(uint32, tag = "3")]
+    pub modulus_size_in_bits: u32,
+    #[prost(bytes = "vec", tag = "4")]
+    pub public_exponent: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum JwtRsaSsaPssAlgorithm {
+    PsUnknown = 0,
+    Ps256 = 1,
+    Ps384 = 2,
+    Ps512 = 3,
+}
+impl JwtRsaSsaPssAlgorithm {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            JwtRsaSsaPssAlgorithm::PsUnknown => "PS_UNKNOWN",
+            JwtRsaSsaPssAlgorithm::Ps256 => "PS256",
+            JwtRsaSsaPssAlgorithm::Ps384 => "PS384",
+            JwtRsaSsaPssAlgorithm::Ps512 => "PS512",
+        }
+    }
+}
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct KmsAeadKeyFormat {
     /// Required.
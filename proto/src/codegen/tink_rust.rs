@@ -0,0 +1,134 @@
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct KmsSignerKeyFormat {
+    /// The location of the signing key in a remote KMS.
+    /// Required.
+    #[prost(string, tag = "1")]
+    pub key_uri: ::prost::alloc::string::String,
+}
+/// key_type: type.googleapis.com/tink_rust.KmsSignerPrivateKey
+/// There is no secret key material in the key.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct KmsSignerPrivateKey {
+    /// Required.
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+    /// The location of the signing key in a remote KMS.
+    /// Required.
+    #[prost(string, tag = "2")]
+    pub key_uri: ::prost::alloc::string::String,
+    /// The corresponding public key, as reported by the KMS when the key was
+    /// added to the keyset.  Signatures can be verified with it without access
+    /// to the KMS.
+    /// Required.
+    #[prost(message, optional, tag = "3")]
+    pub public_key_data: ::core::option::Option<crate::KeyData>,
+}
+/// Key-committing AEAD built on AES-GCM, where per-message AES-GCM keys and a
+/// key commitment are derived from the main key with HMAC-SHA256.
+/// The only allowed IV size is 12 bytes and tag size is 16 bytes.
+/// Thus, accept no params.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AesGcmCommittingKeyFormat {
+    #[prost(uint32, tag = "2")]
+    pub key_size: u32,
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+}
+/// key_type: type.googleapis.com/tink_rust.AesGcmCommittingKey
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AesGcmCommittingKey {
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+    #[prost(bytes = "vec", tag = "3")]
+    pub key_value: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AgeX25519KeyFormat {
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+}
+/// key_type: type.googleapis.com/tink_rust.AgeX25519PublicKey
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AgeX25519PublicKey {
+    /// Required.
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+    /// The 32-byte X25519 public key.
+    /// Required.
+    #[prost(bytes = "vec", tag = "2")]
+    pub key_value: ::prost::alloc::vec::Vec<u8>,
+}
+/// key_type: type.googleapis.com/tink_rust.AgeX25519PrivateKey
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AgeX25519PrivateKey {
+    /// Required.
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+    /// The 32-byte X25519 private key.
+    /// Required.
+    #[prost(bytes = "vec", tag = "2")]
+    pub key_value: ::prost::alloc::vec::Vec<u8>,
+    /// The corresponding public key.
+    #[prost(message, optional, tag = "3")]
+    pub public_key: ::core::option::Option<AgeX25519PublicKey>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SecureEnclaveEcdsaKeyFormat {
+    /// Only NIST_P256 with SHA256 is supported.
+    /// Required.
+    #[prost(message, optional, tag = "1")]
+    pub params: ::core::option::Option<crate::EcdsaParams>,
+}
+/// key_type: type.googleapis.com/tink_rust.SecureEnclaveEcdsaPrivateKey
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SecureEnclaveEcdsaPrivateKey {
+    /// Required.
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+    /// The corresponding public key, usable with the EcdsaVerifier key manager.
+    /// Required.
+    #[prost(message, optional, tag = "2")]
+    pub public_key: ::core::option::Option<crate::EcdsaPublicKey>,
+    /// The Keychain application label of the Secure Enclave key.
+    /// Required.
+    #[prost(bytes = "vec", tag = "3")]
+    pub application_label: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AndroidKeystoreAesGcmKeyFormat {
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+}
+/// key_type: type.googleapis.com/tink_rust.AndroidKeystoreAesGcmKey
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AndroidKeystoreAesGcmKey {
+    /// Required.
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+    /// The alias of the AES-256-GCM key in the Android Keystore.
+    /// Required.
+    #[prost(string, tag = "2")]
+    pub key_alias: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AndroidKeystoreEcdsaKeyFormat {
+    /// Only NIST_P256 with SHA256 is supported.
+    /// Required.
+    #[prost(message, optional, tag = "1")]
+    pub params: ::core::option::Option<crate::EcdsaParams>,
+}
+/// key_type: type.googleapis.com/tink_rust.AndroidKeystoreEcdsaPrivateKey
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AndroidKeystoreEcdsaPrivateKey {
+    /// Required.
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+    /// The corresponding public key, usable with the EcdsaVerifier key manager.
+    /// Required.
+    #[prost(message, optional, tag = "2")]
+    pub public_key: ::core::option::Option<crate::EcdsaPublicKey>,
+    /// The alias of the EC key pair in the Android Keystore.
+    /// Required.
+    #[prost(string, tag = "3")]
+    pub key_alias: ::prost::alloc::string::String,
+}
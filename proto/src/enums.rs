@@ -0,0 +1,122 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Fallible typed accessors for enum-valued fields.
+//!
+//! Protobuf enum fields are stored as `i32` values, and the accessors generated by
+//! [prost](https://docs.rs/prost) (e.g. [`EcdsaParams::hash_type()`]) silently map unrecognized
+//! values onto the default variant.  The `try_` accessors here instead return an
+//! [`UnknownEnumValue`] error for values that do not correspond to any variant.
+
+use crate::*;
+
+/// Error indicating that an enum-valued field holds a value that is not a known variant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnknownEnumValue {
+    /// Name of the enum type.
+    pub enum_name: &'static str,
+    /// The unrecognized value.
+    pub value: i32,
+}
+
+impl std::fmt::Display for UnknownEnumValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "unknown {} value {}", self.enum_name, self.value)
+    }
+}
+
+impl std::error::Error for UnknownEnumValue {}
+
+macro_rules! try_enum_accessors {
+    { $msg:ty { $( $method:ident => $field:ident: $enum:ty ),* $(,)? } } => {
+        impl $msg {
+            $(
+                #[doc = concat!("Return the `", stringify!($field), "` field as a [`",
+                                stringify!($enum), "`], or an error if its value is unknown.")]
+                pub fn $method(&self) -> Result<$enum, UnknownEnumValue> {
+                    <$enum>::from_i32(self.$field).ok_or(UnknownEnumValue {
+                        enum_name: stringify!($enum),
+                        value: self.$field,
+                    })
+                }
+            )*
+        }
+    };
+}
+
+try_enum_accessors! { AesCtrHmacStreamingParams { try_hkdf_hash_type => hkdf_hash_type: HashType } }
+try_enum_accessors! { AesGcmHkdfStreamingParams { try_hkdf_hash_type => hkdf_hash_type: HashType } }
+try_enum_accessors! {
+    EcdsaParams {
+        try_hash_type => hash_type: HashType,
+        try_curve => curve: EllipticCurveType,
+        try_encoding => encoding: EcdsaSignatureEncoding,
+    }
+}
+try_enum_accessors! { EciesAeadHkdfParams { try_ec_point_format => ec_point_format: EcPointFormat } }
+try_enum_accessors! {
+    EciesHkdfKemParams {
+        try_curve_type => curve_type: EllipticCurveType,
+        try_hkdf_hash_type => hkdf_hash_type: HashType,
+    }
+}
+try_enum_accessors! { HkdfPrfParams { try_hash => hash: HashType } }
+try_enum_accessors! { HmacParams { try_hash => hash: HashType } }
+try_enum_accessors! { HmacPrfParams { try_hash => hash: HashType } }
+try_enum_accessors! {
+    HpkeParams {
+        try_kem => kem: HpkeKem,
+        try_kdf => kdf: HpkeKdf,
+        try_aead => aead: HpkeAead,
+    }
+}
+try_enum_accessors! { JwtEcdsaKeyFormat { try_algorithm => algorithm: JwtEcdsaAlgorithm } }
+try_enum_accessors! { JwtEcdsaPublicKey { try_algorithm => algorithm: JwtEcdsaAlgorithm } }
+try_enum_accessors! { JwtHmacKey { try_algorithm => algorithm: JwtHmacAlgorithm } }
+try_enum_accessors! { JwtHmacKeyFormat { try_algorithm => algorithm: JwtHmacAlgorithm } }
+try_enum_accessors! {
+    JwtRsaSsaPkcs1KeyFormat { try_algorithm => algorithm: JwtRsaSsaPkcs1Algorithm }
+}
+try_enum_accessors! {
+    JwtRsaSsaPkcs1PublicKey { try_algorithm => algorithm: JwtRsaSsaPkcs1Algorithm }
+}
+try_enum_accessors! { JwtRsaSsaPssKeyFormat { try_algorithm => algorithm: JwtRsaSsaPssAlgorithm } }
+try_enum_accessors! { JwtRsaSsaPssPublicKey { try_algorithm => algorithm: JwtRsaSsaPssAlgorithm } }
+try_enum_accessors! {
+    KeyData { try_key_material_type => key_material_type: key_data::KeyMaterialType }
+}
+try_enum_accessors! {
+    KeyTemplate { try_output_prefix_type => output_prefix_type: OutputPrefixType }
+}
+try_enum_accessors! {
+    keyset::Key {
+        try_status => status: KeyStatusType,
+        try_output_prefix_type => output_prefix_type: OutputPrefixType,
+    }
+}
+try_enum_accessors! {
+    keyset_info::KeyInfo {
+        try_status => status: KeyStatusType,
+        try_output_prefix_type => output_prefix_type: OutputPrefixType,
+    }
+}
+try_enum_accessors! { RsaSsaPkcs1Params { try_hash_type => hash_type: HashType } }
+try_enum_accessors! {
+    RsaSsaPssParams {
+        try_sig_hash => sig_hash: HashType,
+        try_mgf1_hash => mgf1_hash: HashType,
+    }
+}
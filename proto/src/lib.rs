@@ -19,6 +19,18 @@
 //! Almost all of the code in this crate is auto-generated (using [prost](https://docs.rs/prost)) from the protocol
 //! buffer message definitions in the `proto/` subdirectory.  These `.proto` files are copies from
 //! the upstream [Tink project](https://github.com/google/tink/tree/master/proto).
+//!
+//! Key types that only exist in this Rust port are defined in the `tink_rust/` subdirectory,
+//! in the `tink_rust` protobuf package, so that their type URLs
+//! (`type.googleapis.com/tink_rust.<Message>`) cannot collide with upstream Tink key types.  Their
+//! generated code is also exposed at the top level of this crate.
+//!
+//! By default the crate uses checked-in generated code.  With the `regenerate` feature enabled, the
+//! code is instead generated at build time from all of the top-level `.proto` files in the
+//! `proto/` subdirectory (or in the directory named by the `TINK_PROTO_DIR` environment variable),
+//! so that new upstream message definitions can be picked up by just copying in the `.proto`
+//! files.  This requires a `protoc` binary, taken from the `PROTOC` environment variable or the
+//! `PATH`; if none is available, the build fails.
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![deny(broken_intra_doc_links)]
@@ -27,10 +39,27 @@
 /// Re-export to ensure that users of this crate can access the same version.
 pub use prost;
 
-#[cfg(not(feature = "json"))]
+#[cfg(all(not(tink_proto_regenerated), not(feature = "json")))]
 include!("codegen/google.crypto.tink.rs");
-#[cfg(feature = "json")]
+#[cfg(all(not(tink_proto_regenerated), feature = "json"))]
 include!("codegen/serde/google.crypto.tink.rs");
+#[cfg(all(not(tink_proto_regenerated), not(feature = "json")))]
+include!("codegen/tink_rust.rs");
+#[cfg(all(not(tink_proto_regenerated), feature = "json"))]
+include!("codegen/serde/tink_rust.rs");
+
+// Code generated at build time by the `regenerate` feature.
+#[cfg(all(tink_proto_regenerated, not(feature = "json")))]
+include!(concat!(env!("OUT_DIR"), "/google.crypto.tink.rs"));
+#[cfg(all(tink_proto_regenerated, feature = "json"))]
+include!(concat!(env!("OUT_DIR"), "/serde/google.crypto.tink.rs"));
+#[cfg(all(tink_proto_regenerated, not(feature = "json")))]
+include!(concat!(env!("OUT_DIR"), "/tink_rust.rs"));
+#[cfg(all(tink_proto_regenerated, feature = "json"))]
+include!(concat!(env!("OUT_DIR"), "/serde/tink_rust.rs"));
+
+mod enums;
+pub use enums::*;
 
 #[cfg(feature = "json")]
 #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
@@ -2,7 +2,7 @@
 set -e
 
 # Crates to be published. Order is significant; later crates can only rely on earlier crates
-CRATE_DIRS=(proto core prf mac aead daead streaming signature hybrid integration/awskms integration/gcpkms rinkey)
+CRATE_DIRS=(proto core prf mac aead daead streaming signature hybrid paseto all integration/android integration/apple integration/awskms integration/gcpkms integration/tpm bindings/python bindings/uniffi rinkey)
 
 # Release crates in dependency order. Assumes `cargo login` has been done.
 for dir in "${CRATE_DIRS[@]}"; do
@@ -68,6 +68,18 @@ fn bench_ecdsa_p256_verify_fail(b: &mut Bencher) {
     b.iter(|| v.verify(&sig, MSG).unwrap_err());
 }
 
+#[bench]
+fn bench_ecdsa_p256_new_verifier_and_verify(b: &mut Bencher) {
+    tink_signature::init();
+    let kh = tink_core::keyset::Handle::new(&tink_signature::ecdsa_p256_key_template()).unwrap();
+    let sig = tink_signature::new_signer(&kh).unwrap().sign(MSG).unwrap();
+    let pubkh = kh.public().unwrap();
+    b.iter(|| {
+        let v = tink_signature::new_verifier(&pubkh).unwrap();
+        v.verify(&sig, MSG).unwrap()
+    });
+}
+
 #[bench]
 fn bench_ed25519_sign(b: &mut Bencher) {
     let (s, _v, _sig) = setup(tink_signature::ed25519_key_template());
@@ -16,10 +16,8 @@
 
 //! Key manager for ECDSA signing keys.
 
-use generic_array::typenum::Unsigned;
-use p256::elliptic_curve;
 use tink_core::{utils::wrap_err, TinkError};
-use tink_proto::{prost::Message, EllipticCurveType};
+use tink_proto::prost::Message;
 
 /// Maximal version of ECDSA keys.
 pub const ECDSA_SIGNER_KEY_VERSION: u32 = 0;
@@ -30,7 +28,17 @@ pub const ECDSA_SIGNER_TYPE_URL: &str = "type.googleapis.com/google.crypto.tink.
 /// It generates new ECDSA private keys and produces new instances of
 /// [`crate::subtle::EcdsaSigner`].
 #[derive(Default)]
-pub(crate) struct EcdsaSignerKeyManager {}
+pub(crate) struct EcdsaSignerKeyManager {
+    /// Whether produced signers normalize signatures to low-S form.
+    low_s: bool,
+}
+
+impl EcdsaSignerKeyManager {
+    /// Create a key manager whose signers emit low-S normalized signatures.
+    pub(crate) fn new_low_s() -> Self {
+        Self { low_s: true }
+    }
+}
 
 /// Prefix for uncompressed elliptic curve points.
 pub const ECDSA_UNCOMPRESSED_POINT_PREFIX: u8 = 0x04;
@@ -44,9 +52,11 @@ impl tink_core::registry::KeyManager for EcdsaSignerKeyManager {
             .map_err(|e| wrap_err("EcdsaSignerKeyManager: invalid key", e))?;
         let params = validate_key(&key)?;
 
-        let (hash, curve, encoding) = crate::get_ecdsa_param_ids(&params);
+        let (hash, curve, encoding) = crate::get_ecdsa_param_ids(&params)?;
         match crate::subtle::EcdsaSigner::new(hash, curve, encoding, &key.key_value) {
-            Ok(p) => Ok(tink_core::Primitive::Signer(Box::new(p))),
+            Ok(p) => Ok(tink_core::Primitive::Signer(Box::new(
+                p.with_low_s(self.low_s),
+            ))),
             Err(e) => Err(wrap_err("EcdsaSignerKeyManager: invalid key", e)),
         }
     }
@@ -59,38 +69,14 @@ impl tink_core::registry::KeyManager for EcdsaSignerKeyManager {
             .map_err(|e| wrap_err("EcdsaSignerKeyManager: invalid key", e))?;
         let (params, curve) = validate_key_format(&key_format)?;
 
-        // generate key
-        let mut csprng = signature::rand_core::OsRng {};
-
-        let (secret_key_data, pub_x_data, pub_y_data) = match curve {
-            EllipticCurveType::NistP256 => {
-                // Generate a new keypair.
-                let secret_key = p256::ecdsa::SigningKey::random(&mut csprng);
-                let public_key = p256::ecdsa::VerifyingKey::from(&secret_key);
-                let public_key_point = public_key.to_encoded_point(/* compress= */ false);
-                let public_key_data = public_key_point.as_bytes();
-
-                // Check that the public key data is in the expected uncompressed format:
-                //  - 1 byte uncompressed prefix (0x04)
-                //  - P bytes of X coordinate
-                //  - P bytes of Y coordinate
-                // where P is the field element size.
-                let point_len = elliptic_curve::FieldSize::<p256::NistP256>::to_usize();
-                if public_key_data.len() != 2 * point_len + 1
-                    || public_key_data[0] != ECDSA_UNCOMPRESSED_POINT_PREFIX
-                {
-                    return Err("EcdsaSignerKeyManager: unexpected public key data format".into());
-                }
-                (
-                    secret_key.to_bytes().to_vec(),
-                    public_key_data[1..point_len + 1].to_vec(),
-                    public_key_data[point_len + 1..].to_vec(),
-                )
-            }
-            _ => {
-                return Err(format!("EcdsaSignerKeyManager: unsupported curve {:?}", curve).into())
-            }
-        };
+        // Generate a new keypair.
+        let secret_key = crate::subtle::EcdsaPrivateKey::generate(curve)
+            .map_err(|e| wrap_err("EcdsaSignerKeyManager", e))?;
+        let (pub_x_data, pub_y_data) = secret_key
+            .public_key()
+            .coordinates()
+            .map_err(|e| wrap_err("EcdsaSignerKeyManager", e))?;
+        let secret_key_data = secret_key.to_bytes();
         let pub_key = tink_proto::EcdsaPublicKey {
             version: ECDSA_SIGNER_KEY_VERSION,
             params: Some(params),
@@ -154,7 +140,7 @@ fn validate_key(key: &tink_proto::EcdsaPrivateKey) -> Result<tink_proto::EcdsaPa
         .ok_or_else(|| TinkError::new("EcdsaSignerKeyManager: no public key"))?;
     let params = crate::validate_ecdsa_public_key(pub_key)
         .map_err(|e| wrap_err("EcdsaSignerKeyManager", e))?;
-    let (hash, curve, encoding) = crate::get_ecdsa_param_ids(&params);
+    let (hash, curve, encoding) = crate::get_ecdsa_param_ids(&params)?;
     // Check the public key points are on the curve by creating a verifier.
     crate::subtle::EcdsaVerifier::new(hash, curve, encoding, &pub_key.x, &pub_key.y)
         .map_err(|e| wrap_err("EcdsaVerifierKeyManager: invalid key", e))?;
@@ -164,14 +150,14 @@ fn validate_key(key: &tink_proto::EcdsaPrivateKey) -> Result<tink_proto::EcdsaPa
 
 /// Validate the given [`EcdsaKeyFormat`](tink_proto::EcdsaKeyFormat) and return
 /// the parameters.
-fn validate_key_format(
+pub(crate) fn validate_key_format(
     key_format: &tink_proto::EcdsaKeyFormat,
 ) -> Result<(tink_proto::EcdsaParams, tink_proto::EllipticCurveType), TinkError> {
     let params = key_format
         .params
         .as_ref()
         .ok_or_else(|| TinkError::new("no public key parameters"))?;
-    let (hash, curve, encoding) = crate::get_ecdsa_param_ids(params);
+    let (hash, curve, encoding) = crate::get_ecdsa_param_ids(params)?;
     crate::subtle::validate_ecdsa_params(hash, curve, encoding)?;
     Ok((params.clone(), curve))
 }
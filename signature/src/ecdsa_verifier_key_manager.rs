@@ -16,6 +16,7 @@
 
 //! Key manager for ECDSA verification keys.
 
+use std::{collections::HashMap, sync::Mutex};
 use tink_core::{utils::wrap_err, TinkError};
 use tink_proto::prost::Message;
 
@@ -23,27 +24,62 @@ use tink_proto::prost::Message;
 pub const ECDSA_VERIFIER_KEY_VERSION: u32 = 0;
 /// Type URL of ECDSA keys that Tink supports.
 pub const ECDSA_VERIFIER_TYPE_URL: &str = "type.googleapis.com/google.crypto.tink.EcdsaPublicKey";
+/// Maximum number of parsed verifiers held by the key manager.
+const ECDSA_VERIFIER_CACHE_SIZE: usize = 256;
 
 /// An implementation of the [`tink_core::registry::KeyManager`] trait.
 /// It doesn't support key generation.
+///
+/// Parsed verifiers are cached by serialized key, so that repeatedly building a
+/// [`tink_core::Verifier`] from the same keyset does not re-parse and re-validate the public key
+/// points.
 #[derive(Default)]
-pub(crate) struct EcdsaVerifierKeyManager {}
+pub(crate) struct EcdsaVerifierKeyManager {
+    cache: Mutex<HashMap<Vec<u8>, crate::subtle::EcdsaVerifier>>,
+    /// Options applied to produced verifiers.
+    options: crate::EcdsaVerifierOptions,
+}
+
+impl EcdsaVerifierKeyManager {
+    /// Create a key manager whose verifiers are configured with the given options.
+    pub(crate) fn new_with_options(options: crate::EcdsaVerifierOptions) -> Self {
+        Self {
+            cache: Mutex::new(HashMap::new()),
+            options,
+        }
+    }
+}
 
 impl tink_core::registry::KeyManager for EcdsaVerifierKeyManager {
     fn primitive(&self, serialized_key: &[u8]) -> Result<tink_core::Primitive, TinkError> {
         if serialized_key.is_empty() {
             return Err("EcdsaVerifierKeyManager: invalid key".into());
         }
+        if let Ok(cache) = self.cache.lock() {
+            if let Some(p) = cache.get(serialized_key) {
+                return Ok(tink_core::Primitive::Verifier(Box::new(p.clone())));
+            }
+        }
         let key = tink_proto::EcdsaPublicKey::decode(serialized_key)
             .map_err(|e| wrap_err("EcdsaVerifierKeyManager: invalid key", e))?;
         let params =
             validate_ecdsa_public_key(&key).map_err(|e| wrap_err("EcdsaVerifierKeyManager", e))?;
 
-        let (hash, curve, encoding) = crate::get_ecdsa_param_ids(&params);
-        match crate::subtle::EcdsaVerifier::new(hash, curve, encoding, &key.x, &key.y) {
-            Ok(p) => Ok(tink_core::Primitive::Verifier(Box::new(p))),
-            Err(e) => Err(wrap_err("EcdsaVerifierKeyManager: invalid key", e)),
+        let (hash, curve, encoding) = crate::get_ecdsa_param_ids(&params)?;
+        let mut p = crate::subtle::EcdsaVerifier::new(hash, curve, encoding, &key.x, &key.y)
+            .map_err(|e| wrap_err("EcdsaVerifierKeyManager: invalid key", e))?
+            .with_strict(self.options.strict)
+            .with_any_encoding(self.options.any_encoding);
+        if let Some(counters) = &self.options.encoding_counters {
+            p = p.with_encoding_counters(counters.clone());
+        }
+        if let Ok(mut cache) = self.cache.lock() {
+            if cache.len() >= ECDSA_VERIFIER_CACHE_SIZE {
+                cache.clear();
+            }
+            cache.insert(serialized_key.to_vec(), p.clone());
         }
+        Ok(tink_core::Primitive::Verifier(Box::new(p)))
     }
 
     fn new_key(&self, _serialized_key_format: &[u8]) -> Result<Vec<u8>, TinkError> {
@@ -69,7 +105,7 @@ pub(crate) fn validate_ecdsa_public_key(
         .params
         .as_ref()
         .ok_or_else(|| TinkError::new("no public key parameters"))?;
-    let (hash, curve, encoding) = crate::get_ecdsa_param_ids(params);
+    let (hash, curve, encoding) = crate::get_ecdsa_param_ids(params)?;
     crate::subtle::validate_ecdsa_params(hash, curve, encoding)?;
     Ok(params.clone())
 }
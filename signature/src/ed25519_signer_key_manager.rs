@@ -47,7 +47,7 @@ impl tink_core::registry::KeyManager for Ed25519SignerKeyManager {
     }
 
     fn new_key(&self, _serialized_key_format: &[u8]) -> Result<Vec<u8>, TinkError> {
-        let mut csprng = rand::rngs::OsRng {};
+        let mut csprng = tink_core::subtle::random::TinkRng;
         let keypair = ed25519_dalek::Keypair::generate(&mut csprng);
 
         let public_proto = tink_proto::Ed25519PublicKey {
@@ -65,6 +65,39 @@ impl tink_core::registry::KeyManager for Ed25519SignerKeyManager {
         Ok(sk)
     }
 
+    fn supports_derivation(&self) -> bool {
+        true
+    }
+
+    /// Derive a new key, using 32 bytes read from `pseudorandomness` as the private key seed.
+    fn derive_key(
+        &self,
+        _serialized_key_format: &[u8],
+        pseudorandomness: &mut dyn std::io::Read,
+    ) -> Result<Vec<u8>, TinkError> {
+        let mut seed = [0u8; ed25519_dalek::SECRET_KEY_LENGTH];
+        pseudorandomness
+            .read_exact(&mut seed)
+            .map_err(|e| wrap_err("Ed25519SignerKeyManager: not enough pseudorandomness", e))?;
+        let secret = ed25519_dalek::SecretKey::from_bytes(&seed)
+            .map_err(|e| wrap_err("Ed25519SignerKeyManager: invalid seed", e))?;
+        let public = ed25519_dalek::PublicKey::from(&secret);
+
+        let public_proto = tink_proto::Ed25519PublicKey {
+            version: ED25519_SIGNER_KEY_VERSION,
+            key_value: public.as_bytes().to_vec(),
+        };
+        let key = tink_proto::Ed25519PrivateKey {
+            version: ED25519_SIGNER_KEY_VERSION,
+            public_key: Some(public_proto),
+            key_value: secret.as_bytes().to_vec(),
+        };
+        let mut sk = Vec::new();
+        key.encode(&mut sk)
+            .map_err(|e| wrap_err("Ed25519SignerKeyManager: failed to encode new key", e))?;
+        Ok(sk)
+    }
+
     fn type_url(&self) -> &'static str {
         ED25519_SIGNER_TYPE_URL
     }
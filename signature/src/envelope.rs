@@ -0,0 +1,126 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Helpers for encoding signatures together with (or separately from) the signed payload.
+
+use std::convert::TryInto;
+use tink_core::{utils::wrap_err, TinkError};
+
+/// Version byte at the start of an attached signature.
+pub const ATTACHED_SIGNATURE_VERSION: u8 = 1;
+
+/// Size of the header of an attached signature: a version byte followed by a 4-byte big-endian
+/// payload length.
+const ATTACHED_HEADER_SIZE: usize = 5;
+
+/// `SignatureEncoding` determines how [`sign_and_encode`] combines a signature with its payload.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignatureEncoding {
+    /// The output holds only the signature (including any key prefix); the payload must be
+    /// conveyed separately.  This is the format produced by [`tink_core::Signer::sign`].
+    Detached,
+    /// The output holds the payload as well as the signature, encoded as:
+    ///  - version byte ([`ATTACHED_SIGNATURE_VERSION`])
+    ///  - payload length (4 bytes, big-endian)
+    ///  - payload
+    ///  - signature (including any key prefix)
+    Attached,
+}
+
+/// The components of an attached signature, as returned by [`parse_attached`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttachedSignature<'a> {
+    /// The signed payload.
+    pub payload: &'a [u8],
+    /// The signature over the payload, including any key prefix.
+    pub signature: &'a [u8],
+}
+
+/// Sign `payload` with `signer`, and encode the result according to `encoding`.
+pub fn sign_and_encode(
+    signer: &dyn tink_core::Signer,
+    payload: &[u8],
+    encoding: SignatureEncoding,
+) -> Result<Vec<u8>, TinkError> {
+    let signature = signer
+        .sign(payload)
+        .map_err(|e| wrap_err("signature::envelope: signing failed", e))?;
+    match encoding {
+        SignatureEncoding::Detached => Ok(signature),
+        SignatureEncoding::Attached => {
+            let payload_len: u32 = payload
+                .len()
+                .try_into()
+                .map_err(|_| TinkError::new("signature::envelope: payload too long"))?;
+            let mut ret =
+                Vec::with_capacity(ATTACHED_HEADER_SIZE + payload.len() + signature.len());
+            ret.push(ATTACHED_SIGNATURE_VERSION);
+            ret.extend_from_slice(&payload_len.to_be_bytes());
+            ret.extend_from_slice(payload);
+            ret.extend_from_slice(&signature);
+            Ok(ret)
+        }
+    }
+}
+
+/// Parse an attached signature into its components, without verifying the signature.
+pub fn parse_attached(encoded: &[u8]) -> Result<AttachedSignature, TinkError> {
+    if encoded.len() < ATTACHED_HEADER_SIZE {
+        return Err("signature::envelope: attached signature too short".into());
+    }
+    if encoded[0] != ATTACHED_SIGNATURE_VERSION {
+        return Err("signature::envelope: unknown attached signature version".into());
+    }
+    let mut len_bytes = [0u8; 4];
+    len_bytes.copy_from_slice(&encoded[1..ATTACHED_HEADER_SIZE]);
+    let payload_len = u32::from_be_bytes(len_bytes) as usize;
+    let rest = &encoded[ATTACHED_HEADER_SIZE..];
+    if rest.len() < payload_len {
+        return Err("signature::envelope: invalid payload length".into());
+    }
+    let (payload, signature) = rest.split_at(payload_len);
+    Ok(AttachedSignature { payload, signature })
+}
+
+/// Verify an encoded signature with `verifier`, returning the signed payload on success.
+///
+/// For [`SignatureEncoding::Detached`] the payload must be provided in `detached_payload`; for
+/// [`SignatureEncoding::Attached`] the payload is extracted from `encoded`, and `detached_payload`
+/// must be `None`.
+pub fn verify_and_decode(
+    verifier: &dyn tink_core::Verifier,
+    encoded: &[u8],
+    encoding: SignatureEncoding,
+    detached_payload: Option<&[u8]>,
+) -> Result<Vec<u8>, TinkError> {
+    let (signature, payload) = match (encoding, detached_payload) {
+        (SignatureEncoding::Detached, Some(payload)) => (encoded, payload),
+        (SignatureEncoding::Detached, None) => {
+            return Err("signature::envelope: detached signature requires payload".into())
+        }
+        (SignatureEncoding::Attached, None) => {
+            let attached = parse_attached(encoded)?;
+            (attached.signature, attached.payload)
+        }
+        (SignatureEncoding::Attached, Some(_)) => {
+            return Err("signature::envelope: unexpected payload for attached signature".into())
+        }
+    };
+    verifier
+        .verify(signature, payload)
+        .map_err(|e| wrap_err("signature::envelope: verification failed", e))?;
+    Ok(payload.to_vec())
+}
@@ -0,0 +1,123 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Key manager for signing keys held in a remote KMS.
+
+use tink_core::{utils::wrap_err, TinkError};
+use tink_proto::prost::Message;
+
+/// Maximal version of KMS signing keys.
+pub const KMS_SIGNER_KEY_VERSION: u32 = 0;
+/// Type URL of KMS signing keys.
+pub const KMS_SIGNER_TYPE_URL: &str = "type.googleapis.com/tink_rust.KmsSignerPrivateKey";
+
+/// An implementation of the [`tink_core::registry::KeyManager`] trait for asymmetric signing keys
+/// held in a remote KMS, which produces the [`tink_core::Signer`] provided by the registered
+/// [`KmsClient`](tink_core::registry::KmsClient) for the key's URI.  The keys only hold a
+/// reference to the KMS key and its public key, so have key material type `REMOTE`.
+#[derive(Default)]
+pub(crate) struct KmsSignerKeyManager {}
+
+impl tink_core::registry::KeyManager for KmsSignerKeyManager {
+    fn primitive(&self, serialized_key: &[u8]) -> Result<tink_core::Primitive, TinkError> {
+        if serialized_key.is_empty() {
+            return Err("KmsSignerKeyManager: invalid key".into());
+        }
+        let key = tink_proto::KmsSignerPrivateKey::decode(serialized_key)
+            .map_err(|e| wrap_err("KmsSignerKeyManager: invalid key", e))?;
+        validate_key(&key)?;
+        let kms_client = tink_core::registry::get_kms_client(&key.key_uri)?;
+        let signer = kms_client
+            .get_signer(&key.key_uri)
+            .map_err(|e| wrap_err("KmsSignerKeyManager: invalid signer backend", e))?;
+        Ok(tink_core::Primitive::Signer(signer))
+    }
+
+    /// Create a new key referring to the KMS signing key given in the serialized
+    /// [`tink_proto::KmsSignerKeyFormat`].  No key is generated; instead the KMS is asked for
+    /// the public key of the existing key.
+    fn new_key(&self, serialized_key_format: &[u8]) -> Result<Vec<u8>, TinkError> {
+        if serialized_key_format.is_empty() {
+            return Err("KmsSignerKeyManager: invalid key format".into());
+        }
+        let key_format = tink_proto::KmsSignerKeyFormat::decode(serialized_key_format)
+            .map_err(|e| wrap_err("KmsSignerKeyManager: invalid key format", e))?;
+        if key_format.key_uri.is_empty() {
+            return Err("KmsSignerKeyManager: missing URI".into());
+        }
+        let kms_client = tink_core::registry::get_kms_client(&key_format.key_uri)?;
+        if !kms_client.capabilities(&key_format.key_uri).sign {
+            return Err("KmsSignerKeyManager: KMS client does not support signing".into());
+        }
+        let public_key_data = kms_client
+            .get_public_key_data(&key_format.key_uri)
+            .map_err(|e| wrap_err("KmsSignerKeyManager: failed to get public key", e))?;
+        let key = tink_proto::KmsSignerPrivateKey {
+            version: KMS_SIGNER_KEY_VERSION,
+            key_uri: key_format.key_uri,
+            public_key_data: Some(public_key_data),
+        };
+        validate_key(&key)?;
+        let mut sk = Vec::new();
+        key.encode(&mut sk)
+            .map_err(|e| wrap_err("KmsSignerKeyManager: failed to encode new key", e))?;
+        Ok(sk)
+    }
+
+    fn type_url(&self) -> &'static str {
+        KMS_SIGNER_TYPE_URL
+    }
+
+    fn key_material_type(&self) -> tink_proto::key_data::KeyMaterialType {
+        tink_proto::key_data::KeyMaterialType::Remote
+    }
+
+    fn supports_private_keys(&self) -> bool {
+        true
+    }
+
+    /// Return the public key recorded in the key, so that signatures can be verified without
+    /// access to the KMS.
+    fn public_key_data(
+        &self,
+        serialized_priv_key: &[u8],
+    ) -> Result<tink_proto::KeyData, TinkError> {
+        let key = tink_proto::KmsSignerPrivateKey::decode(serialized_priv_key)
+            .map_err(|e| wrap_err("KmsSignerKeyManager: invalid private key", e))?;
+        validate_key(&key)?;
+        key.public_key_data
+            .ok_or_else(|| TinkError::new("KmsSignerKeyManager: no public key"))
+    }
+}
+
+/// Validate the given [`tink_proto::KmsSignerPrivateKey`].
+fn validate_key(key: &tink_proto::KmsSignerPrivateKey) -> Result<(), TinkError> {
+    tink_core::keyset::validate_key_version(key.version, KMS_SIGNER_KEY_VERSION)
+        .map_err(|e| wrap_err("KmsSignerKeyManager", e))?;
+    if key.key_uri.is_empty() {
+        return Err("KmsSignerKeyManager: missing URI".into());
+    }
+    match &key.public_key_data {
+        Some(kd)
+            if kd.key_material_type
+                == tink_proto::key_data::KeyMaterialType::AsymmetricPublic as i32 =>
+        {
+            Ok(())
+        }
+        Some(_) => Err("KmsSignerKeyManager: public key is not an asymmetric public key".into()),
+        None => Err("KmsSignerKeyManager: no public key".into()),
+    }
+}
@@ -31,10 +31,22 @@ mod ed25519_signer_key_manager;
 pub use ed25519_signer_key_manager::*;
 mod ed25519_verifier_key_manager;
 pub use ed25519_verifier_key_manager::*;
+mod envelope;
+pub use envelope::*;
+mod kms_signer_key_manager;
+pub use kms_signer_key_manager::*;
+mod multisig;
+pub use multisig::*;
+mod openssh;
+pub use openssh::*;
+mod pkcs8;
+pub use pkcs8::*;
 mod proto;
 pub(crate) use proto::*;
 mod signature_key_templates;
 pub use signature_key_templates::*;
+mod signed_keyset;
+pub use signed_keyset::*;
 mod signer_factory;
 pub use signer_factory::*;
 mod verifier_factory;
@@ -64,6 +76,10 @@ pub fn init() {
         register_key_manager(std::sync::Arc::new(Ed25519VerifierKeyManager::default()))
             .expect("tink_signature::init() failed"); // safe: init
 
+        // Remote KMS keys
+        register_key_manager(std::sync::Arc::new(KmsSignerKeyManager::default()))
+            .expect("tink_signature::init() failed"); // safe: init
+
         register_template_generator("ECDSA_P256", ecdsa_p256_key_template);
         register_template_generator("ECDSA_P256_RAW", ecdsa_p256_raw_key_template);
         register_template_generator("ECDSA_P256_IEEE_P1363", ecdsa_p256_key_p1363_template);
@@ -0,0 +1,191 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Threshold (k-of-n) multi-signatures built from individual signatures.
+
+use std::{collections::HashSet, convert::TryInto};
+use tink_core::{utils::wrap_err, KeyId, TinkError};
+
+/// Size of each of the big-endian length fields in an encoded multi-signature.
+const LEN_SIZE: usize = 4;
+
+/// Produce a combined multi-signature over `data`, holding one signature from each of the
+/// `signers`.
+///
+/// Each signer should be a [`tink_core::Signer`] for a keyset whose primary key has a non-RAW
+/// output prefix, so that the verifier can identify the key that produced each signature.  The
+/// encoded multi-signature consists of:
+///  - the number of signatures (4 bytes, big-endian)
+///  - for each signature: its length (4 bytes, big-endian) followed by the signature.
+pub fn sign_multi(signers: &[&dyn tink_core::Signer], data: &[u8]) -> Result<Vec<u8>, TinkError> {
+    let count: u32 = signers
+        .len()
+        .try_into()
+        .map_err(|_| TinkError::new("signature::multisig: too many signers"))?;
+    let mut ret = Vec::new();
+    ret.extend_from_slice(&count.to_be_bytes());
+    for signer in signers {
+        let signature = signer
+            .sign(data)
+            .map_err(|e| wrap_err("signature::multisig: signing failed", e))?;
+        let len: u32 = signature
+            .len()
+            .try_into()
+            .map_err(|_| TinkError::new("signature::multisig: signature too long"))?;
+        ret.extend_from_slice(&len.to_be_bytes());
+        ret.extend_from_slice(&signature);
+    }
+    Ok(ret)
+}
+
+/// Split an encoded multi-signature into its constituent signatures.
+pub fn parse_multi(multi_signature: &[u8]) -> Result<Vec<&[u8]>, TinkError> {
+    let (count, mut rest) = read_len(multi_signature)?;
+    // Each signature needs at least its length field, so bound the allocation.
+    if count > rest.len() / LEN_SIZE {
+        return Err("signature::multisig: invalid signature count".into());
+    }
+    let mut signatures = Vec::with_capacity(count);
+    for _ in 0..count {
+        let (len, remaining) = read_len(rest)?;
+        if remaining.len() < len {
+            return Err("signature::multisig: invalid signature length".into());
+        }
+        let (signature, remaining) = remaining.split_at(len);
+        signatures.push(signature);
+        rest = remaining;
+    }
+    if !rest.is_empty() {
+        return Err("signature::multisig: trailing data".into());
+    }
+    Ok(signatures)
+}
+
+/// Read a big-endian length field from the start of `data`, returning it together with the
+/// remaining data.
+fn read_len(data: &[u8]) -> Result<(usize, &[u8]), TinkError> {
+    if data.len() < LEN_SIZE {
+        return Err("signature::multisig: multi-signature too short".into());
+    }
+    let mut len_bytes = [0u8; LEN_SIZE];
+    len_bytes.copy_from_slice(&data[..LEN_SIZE]);
+    Ok((u32::from_be_bytes(len_bytes) as usize, &data[LEN_SIZE..]))
+}
+
+/// Return a [`tink_core::Verifier`] that accepts a multi-signature (as produced by [`sign_multi`])
+/// only if it includes valid signatures from at least `threshold` distinct keys of the given
+/// keyset handle.
+///
+/// All enabled keys in the keyset must have a non-RAW output prefix, and `threshold` must be
+/// between 1 and the number of enabled keys.
+pub fn new_threshold_verifier(
+    h: &tink_core::keyset::Handle,
+    threshold: usize,
+) -> Result<Box<dyn tink_core::Verifier>, TinkError> {
+    let ps = h
+        .primitives()
+        .map_err(|e| wrap_err("signature::multisig: cannot obtain primitive set", e))?;
+    let ret = ThresholdVerifier::new(ps, threshold)?;
+    Ok(Box::new(ret))
+}
+
+/// A [`tink_core::Verifier`] implementation that requires signatures from a threshold number of
+/// keys in the underlying primitive set.
+#[derive(Clone)]
+struct ThresholdVerifier {
+    ps: tink_core::primitiveset::TypedPrimitiveSet<Box<dyn tink_core::Verifier>>,
+    threshold: usize,
+}
+
+impl ThresholdVerifier {
+    fn new(
+        ps: tink_core::primitiveset::PrimitiveSet,
+        threshold: usize,
+    ) -> Result<ThresholdVerifier, TinkError> {
+        if !ps.usage.allows_verify() {
+            return Err("signature::multisig: verification not permitted".into());
+        }
+        let mut key_count = 0;
+        for (prefix, primitives) in ps.entries.iter() {
+            if prefix.is_empty() {
+                return Err(
+                    "signature::multisig: keys with RAW output prefix not supported".into(),
+                );
+            }
+            for p in primitives {
+                match p.primitive {
+                    tink_core::Primitive::Verifier(_) => {}
+                    _ => return Err("signature::multisig: not a Verifier primitive".into()),
+                };
+                key_count += 1;
+            }
+        }
+        if threshold == 0 || threshold > key_count {
+            return Err(format!(
+                "signature::multisig: invalid threshold {} for {} keys",
+                threshold, key_count
+            )
+            .into());
+        }
+        // The `.into()` call is only safe because we've just checked that all entries have
+        // the right type of primitive
+        Ok(ThresholdVerifier {
+            ps: ps.into(),
+            threshold,
+        })
+    }
+
+    /// Return the ID of the key that produced a valid `signature` over `data`, if any.
+    fn matching_key(&self, signature: &[u8], data: &[u8]) -> Option<KeyId> {
+        let prefix_size = tink_core::cryptofmt::NON_RAW_PREFIX_SIZE;
+        if signature.len() < prefix_size {
+            return None;
+        }
+        let (prefix, signature_no_prefix) = signature.split_at(prefix_size);
+        for entry in self.ps.entries_for_prefix(prefix)? {
+            let result = if entry.prefix_type == tink_proto::OutputPrefixType::Legacy {
+                let mut signed_data_copy = Vec::with_capacity(data.len() + 1);
+                signed_data_copy.extend_from_slice(data);
+                signed_data_copy.push(0u8);
+                entry
+                    .primitive
+                    .verify(signature_no_prefix, &signed_data_copy)
+            } else {
+                entry.primitive.verify(signature_no_prefix, data)
+            };
+            if result.is_ok() {
+                return Some(entry.key_id);
+            }
+        }
+        None
+    }
+}
+
+impl tink_core::Verifier for ThresholdVerifier {
+    fn verify(&self, signature: &[u8], data: &[u8]) -> Result<(), TinkError> {
+        let signatures = parse_multi(signature)?;
+        let mut matched = HashSet::new();
+        for signature in signatures {
+            if let Some(key_id) = self.matching_key(signature, data) {
+                matched.insert(key_id);
+                if matched.len() >= self.threshold {
+                    return Ok(());
+                }
+            }
+        }
+        Err("signature::multisig: insufficient valid signatures".into())
+    }
+}
@@ -16,17 +16,23 @@
 
 //! Utilities for dealing with protobuf types for signing.
 
+use tink_core::{utils::wrap_err, TinkError};
 use tink_proto::{EcdsaParams, EcdsaSignatureEncoding, EllipticCurveType, HashType};
 
 /// Return the enum values of each parameter in
 /// the given [`EcdsaParams`](tink_proto::EcdsaParams).
 pub(crate) fn get_ecdsa_param_ids(
     params: &EcdsaParams,
-) -> (HashType, EllipticCurveType, EcdsaSignatureEncoding) {
-    (
-        HashType::from_i32(params.hash_type).unwrap_or(HashType::UnknownHash),
-        EllipticCurveType::from_i32(params.curve).unwrap_or(EllipticCurveType::UnknownCurve),
-        EcdsaSignatureEncoding::from_i32(params.encoding)
-            .unwrap_or(EcdsaSignatureEncoding::UnknownEncoding),
-    )
+) -> Result<(HashType, EllipticCurveType, EcdsaSignatureEncoding), TinkError> {
+    Ok((
+        params
+            .try_hash_type()
+            .map_err(|e| wrap_err("invalid hash type", e))?,
+        params
+            .try_curve()
+            .map_err(|e| wrap_err("unsupported curve", e))?,
+        params
+            .try_encoding()
+            .map_err(|e| wrap_err("unsupported encoding", e))?,
+    ))
 }
@@ -16,6 +16,7 @@
 
 //! This module contains pre-generated KeyTemplates for Signer and Verifier.
 /// One can use these templates to generate new Keysets.
+use tink_core::TinkError;
 use tink_proto::{prost::Message, KeyTemplate};
 
 /// Return a [`KeyTemplate`] that generates a new ECDSA private key with the following parameters:
@@ -174,20 +175,103 @@ fn create_ecdsa_key_template(
     encoding: tink_proto::EcdsaSignatureEncoding,
     prefix_type: tink_proto::OutputPrefixType,
 ) -> KeyTemplate {
-    let params = tink_proto::EcdsaParams {
-        hash_type: hash_type as i32,
-        curve: curve as i32,
-        encoding: encoding as i32,
-    };
-    let format = tink_proto::EcdsaKeyFormat {
-        params: Some(params),
+    EcdsaTemplateBuilder::new()
+        .hash(hash_type)
+        .curve(curve)
+        .encoding(encoding)
+        .prefix(prefix_type)
+        .build()
+        .unwrap() // safe: valid parameters
+}
+
+/// Builder for ECDSA [`KeyTemplate`]s, whose parameters are checked when the template is built.
+/// Defaults to the parameters of [`ecdsa_p256_key_template`].
+#[derive(Clone, Copy, Debug)]
+pub struct EcdsaTemplateBuilder {
+    hash: tink_proto::HashType,
+    curve: tink_proto::EllipticCurveType,
+    encoding: tink_proto::EcdsaSignatureEncoding,
+    prefix: tink_proto::OutputPrefixType,
+}
+
+impl Default for EcdsaTemplateBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EcdsaTemplateBuilder {
+    /// Create a builder with the default parameters.
+    pub fn new() -> Self {
+        Self {
+            hash: tink_proto::HashType::Sha256,
+            curve: tink_proto::EllipticCurveType::NistP256,
+            encoding: tink_proto::EcdsaSignatureEncoding::Der,
+            prefix: tink_proto::OutputPrefixType::Tink,
+        }
+    }
+
+    /// Set the hash function, which must be suitable for the curve.
+    pub fn hash(mut self, hash: tink_proto::HashType) -> Self {
+        self.hash = hash;
+        self
+    }
+
+    /// Set the elliptic curve.
+    pub fn curve(mut self, curve: tink_proto::EllipticCurveType) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    /// Set the signature encoding.
+    pub fn encoding(mut self, encoding: tink_proto::EcdsaSignatureEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Set the output prefix type.
+    pub fn prefix(mut self, prefix: tink_proto::OutputPrefixType) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    /// Build the [`KeyTemplate`], or return an error if the parameters are invalid.
+    pub fn build(&self) -> Result<KeyTemplate, TinkError> {
+        if self.prefix == tink_proto::OutputPrefixType::UnknownPrefix {
+            return Err("invalid output prefix type".into());
+        }
+        let format = tink_proto::EcdsaKeyFormat {
+            params: Some(tink_proto::EcdsaParams {
+                hash_type: self.hash as i32,
+                curve: self.curve as i32,
+                encoding: self.encoding as i32,
+            }),
+        };
+        crate::ecdsa_signer_key_manager::validate_key_format(&format)?;
+        let mut serialized_format = Vec::new();
+        format.encode(&mut serialized_format).unwrap(); // safe: proto-encode
+        Ok(KeyTemplate {
+            type_url: crate::ECDSA_SIGNER_TYPE_URL.to_string(),
+            value: serialized_format,
+            output_prefix_type: self.prefix as i32,
+        })
+    }
+}
+
+/// Return a [`KeyTemplate`] for an existing asymmetric signing key held in a remote KMS at `uri`.
+/// Creating a key from this template contacts the KMS (through the registered
+/// [`KmsClient`](tink_core::registry::KmsClient)) for the key's public key; the client must
+/// support signing, as the `tink-awskms` client does for ECDSA keys.
+pub fn kms_signer_key_template(uri: &str) -> KeyTemplate {
+    let f = tink_proto::KmsSignerKeyFormat {
+        key_uri: uri.to_string(),
     };
     let mut serialized_format = Vec::new();
-    format.encode(&mut serialized_format).unwrap(); // safe: proto-encode
+    f.encode(&mut serialized_format).unwrap(); // safe: proto-encode
     KeyTemplate {
-        type_url: crate::ECDSA_SIGNER_TYPE_URL.to_string(),
+        type_url: crate::KMS_SIGNER_TYPE_URL.to_string(),
+        output_prefix_type: tink_proto::OutputPrefixType::Tink as i32,
         value: serialized_format,
-        output_prefix_type: prefix_type as i32,
     }
 }
 
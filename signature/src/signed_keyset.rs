@@ -0,0 +1,214 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Distribution of public keysets that are signed by a distribution keyset.
+//!
+//! A signed public keyset allows clients to fetch rotating public keys (for example hybrid
+//! encryption keys) over an untrusted channel, and authenticate them with a pinned verification
+//! keyset.  Signed public keysets can be chained: a root keyset signs a (public) distribution
+//! keyset, which in turn signs the public keyset to distribute.
+
+use crate::{sign_and_encode, verify_and_decode, SignatureEncoding};
+use std::{
+    convert::TryInto,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tink_core::{
+    keyset::{Clock, Handle, SystemClock},
+    utils::wrap_err,
+    TinkError,
+};
+
+/// Context string included in the signed payload, so that a signed public keyset cannot be
+/// confused with other data signed by the same distribution keyset.
+const SIGNED_PUBLIC_KEYSET_CONTEXT: &[u8] = b"tink-signed-public-keyset-v2\0";
+
+/// Freshness requirements that a signed public keyset must meet to be accepted by
+/// [`verify_signed_public_keyset`].
+///
+/// Without these, an attacker on the distribution channel could replay an older (validly signed)
+/// keyset, for example one that still contains a key that has since been rotated out because it
+/// was compromised.
+#[derive(Clone, Default)]
+pub struct FreshnessPolicy {
+    /// Reject keysets whose serial number is lower than this; typically the serial of the last
+    /// keyset that the client accepted.
+    pub min_serial: u64,
+    /// Reject keysets that were issued longer ago than this.
+    pub max_age: Option<Duration>,
+    /// Clock used to check `max_age`; the system clock if `None`.
+    pub clock: Option<Arc<dyn Clock>>,
+}
+
+impl FreshnessPolicy {
+    fn check(&self, serial: u64, issued_at: SystemTime) -> Result<(), TinkError> {
+        if serial < self.min_serial {
+            return Err(format!(
+                "signature::signed_keyset: serial {} is below the minimum {}",
+                serial, self.min_serial
+            )
+            .into());
+        }
+        if let Some(max_age) = self.max_age {
+            let now = match &self.clock {
+                Some(clock) => clock.now(),
+                None => SystemClock.now(),
+            };
+            // A keyset issued in the future (because of clock skew) has age zero.
+            let age = now.duration_since(issued_at).unwrap_or_default();
+            if age > max_age {
+                return Err("signature::signed_keyset: keyset has expired".into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A public keyset whose signature and freshness have been checked by
+/// [`verify_signed_public_keyset`].
+pub struct VerifiedPublicKeyset {
+    serial: u64,
+    issued_at: SystemTime,
+    handle: Handle,
+}
+
+impl VerifiedPublicKeyset {
+    /// Return the serial number that the keyset was signed with.
+    pub fn serial(&self) -> u64 {
+        self.serial
+    }
+
+    /// Return the time at which the keyset was signed (to a precision of one second).
+    pub fn issued_at(&self) -> SystemTime {
+        self.issued_at
+    }
+
+    /// Return a handle for the public keyset.
+    pub fn handle(&self) -> &Handle {
+        &self.handle
+    }
+
+    /// Consume the verified keyset, returning a handle for the public keyset.
+    pub fn into_handle(self) -> Handle {
+        self.handle
+    }
+}
+
+/// Sign the public keyset held in `public` with `signer`, labelled with `serial` and the current
+/// time.  The serial number would typically be incremented whenever the keyset is rotated, so
+/// that clients can refuse to go back to an older keyset; see [`FreshnessPolicy`].
+///
+/// The result is an attached signature (see [`SignatureEncoding::Attached`]) whose payload is a
+/// context string, the serial number (8 bytes, big-endian), the issue time in seconds since the
+/// Unix epoch (8 bytes, big-endian) and the serialized keyset.  An error is returned if `public`
+/// contains secret key material.
+pub fn sign_public_keyset(
+    public: &Handle,
+    signer: &dyn tink_core::Signer,
+    serial: u64,
+) -> Result<Vec<u8>, TinkError> {
+    sign_public_keyset_at(public, signer, serial, SystemClock.now())
+}
+
+/// Sign the public keyset held in `public` as for [`sign_public_keyset`], but with an explicit
+/// issue time.
+pub fn sign_public_keyset_at(
+    public: &Handle,
+    signer: &dyn tink_core::Signer,
+    serial: u64,
+    issued_at: SystemTime,
+) -> Result<Vec<u8>, TinkError> {
+    let issued_at = issued_at
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| TinkError::new("signature::signed_keyset: issue time before Unix epoch"))?
+        .as_secs();
+    let mut payload = SIGNED_PUBLIC_KEYSET_CONTEXT.to_vec();
+    payload.extend_from_slice(&serial.to_be_bytes());
+    payload.extend_from_slice(&issued_at.to_be_bytes());
+    public
+        .write_with_no_secrets(&mut tink_core::keyset::BinaryWriter::new(&mut payload))
+        .map_err(|e| wrap_err("signature::signed_keyset: cannot serialize keyset", e))?;
+    sign_and_encode(signer, &payload, SignatureEncoding::Attached)
+}
+
+/// Verify a signed public keyset produced by [`sign_public_keyset`] with `verifier`, check that
+/// it meets `policy`, and parse the public keyset that it contains.
+pub fn verify_signed_public_keyset(
+    signed: &[u8],
+    verifier: &dyn tink_core::Verifier,
+    policy: &FreshnessPolicy,
+) -> Result<VerifiedPublicKeyset, TinkError> {
+    let payload = verify_and_decode(verifier, signed, SignatureEncoding::Attached, None)?;
+    let rest = payload
+        .strip_prefix(SIGNED_PUBLIC_KEYSET_CONTEXT)
+        .ok_or_else(|| TinkError::new("signature::signed_keyset: not a signed public keyset"))?;
+    if rest.len() < 16 {
+        return Err("signature::signed_keyset: not a signed public keyset".into());
+    }
+    let (header, serialized) = rest.split_at(16);
+    let serial = u64::from_be_bytes(header[..8].try_into().unwrap()); // safe: checked length
+    let issued_at = u64::from_be_bytes(header[8..].try_into().unwrap()); // safe: checked length
+    let issued_at = UNIX_EPOCH
+        .checked_add(Duration::from_secs(issued_at))
+        .ok_or_else(|| TinkError::new("signature::signed_keyset: invalid issue time"))?;
+    policy.check(serial, issued_at)?;
+    let handle =
+        Handle::read_with_no_secrets(&mut tink_core::keyset::BinaryReader::new(serialized))
+            .map_err(|e| wrap_err("signature::signed_keyset: invalid keyset", e))?;
+    Ok(VerifiedPublicKeyset {
+        serial,
+        issued_at,
+        handle,
+    })
+}
+
+/// Verify a chain of signed public keysets, returning the public keyset at the end of the chain.
+///
+/// The first element of `chain` is verified with the pinned `root` verifier; every subsequent
+/// element is verified with the (signature) public keyset contained in the element before it.
+/// The `max_age` of `policy` applies to every element of the chain, but its `min_serial` only
+/// applies to the last element, as the intermediate keysets have serial numbers of their own.
+pub fn verify_signed_public_keyset_chain(
+    chain: &[&[u8]],
+    root: &dyn tink_core::Verifier,
+    policy: &FreshnessPolicy,
+) -> Result<VerifiedPublicKeyset, TinkError> {
+    let (last, intermediate) = chain
+        .split_last()
+        .ok_or_else(|| TinkError::new("signature::signed_keyset: empty chain"))?;
+    let intermediate_policy = FreshnessPolicy {
+        min_serial: 0,
+        ..policy.clone()
+    };
+    let mut verifier = root.box_clone();
+    for (i, signed) in intermediate.iter().enumerate() {
+        let handle = verify_signed_public_keyset(signed, verifier.as_ref(), &intermediate_policy)
+            .map_err(|e| wrap_err(&format!("signature::signed_keyset: chain element {}", i), e))?
+            .into_handle();
+        verifier = crate::new_verifier(&handle)
+            .map_err(|e| wrap_err(&format!("signature::signed_keyset: chain element {}", i), e))?;
+    }
+    verify_signed_public_keyset(last, verifier.as_ref(), policy).map_err(|e| {
+        wrap_err(
+            &format!(
+                "signature::signed_keyset: chain element {}",
+                intermediate.len()
+            ),
+            e,
+        )
+    })
+}
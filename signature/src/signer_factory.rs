@@ -24,6 +24,34 @@ pub fn new_signer(h: &tink_core::keyset::Handle) -> Result<Box<dyn tink_core::Si
     new_signer_with_key_manager(h, None)
 }
 
+/// Return a [`tink_core::Signer`] primitive from the given keyset handle, with ECDSA signatures
+/// normalized to low-S form.
+///
+/// Signatures from other key types are unaffected.  Use this when signatures are consumed by
+/// systems that reject high-S ECDSA signatures, such as blockchains and some HSMs.
+pub fn new_signer_with_low_s(
+    h: &tink_core::keyset::Handle,
+) -> Result<Box<dyn tink_core::Signer>, TinkError> {
+    new_signer_with_key_manager(h, Some(Arc::new(crate::EcdsaSignerKeyManager::new_low_s())))
+}
+
+/// Return a [`tink_core::Signer`] primitive for a single private key, bypassing keyset wrapping.
+///
+/// The primitive's signatures have no output prefix, and none of the keyset-level protections
+/// apply; prefer [`new_signer`] with a keyset [`Handle`](tink_core::keyset::Handle) unless
+/// exactly one raw key is available.  Registered gatekeepers are consulted before the primitive
+/// is created.
+pub fn unwrapped_signer_from_key_data(
+    kd: &tink_proto::KeyData,
+) -> Result<Box<dyn tink_core::Signer>, TinkError> {
+    match tink_core::registry::unwrapped_primitive_from_key_data(kd)
+        .map_err(|e| wrap_err("signer::factory: cannot create primitive from key", e))?
+    {
+        tink_core::Primitive::Signer(p) => Ok(p),
+        _ => Err("signer::factory: not a Signer primitive".into()),
+    }
+}
+
 /// Return a [`tink_core::Signer`] primitive from the given keyset handle and custom key manager.
 fn new_signer_with_key_manager(
     h: &tink_core::keyset::Handle,
@@ -45,6 +73,9 @@ struct WrappedSigner {
 
 impl WrappedSigner {
     fn new(ps: tink_core::primitiveset::PrimitiveSet) -> Result<WrappedSigner, TinkError> {
+        if !ps.usage.allows_compute() {
+            return Err("signer::factory: signing not permitted".into());
+        }
         let primary = match &ps.primary {
             None => return Err("signer::factory: no primary primitive".into()),
             Some(p) => p,
@@ -71,6 +102,15 @@ impl tink_core::Signer for WrappedSigner {
     /// Sign the given data and returns the signature concatenated with the identifier of the
     /// primary primitive.
     fn sign(&self, data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let op = tink_core::trace::operation("signer", "sign", data.len());
+        self.ps.check_primary_validity().map_err(|e| {
+            op.failed("primary key not valid");
+            e
+        })?;
+        self.ps.record_primary_usage(data.len()).map_err(|e| {
+            op.failed("primary key usage limit reached");
+            e
+        })?;
         let primary = match &self.ps.primary {
             Some(p) => p,
             None => return Err("signer::factory: no primary primitive".into()),
@@ -88,6 +128,7 @@ impl tink_core::Signer for WrappedSigner {
         let mut ret = Vec::with_capacity(primary.prefix.len() + signature.len());
         ret.extend_from_slice(&primary.prefix);
         ret.extend_from_slice(&signature);
+        op.succeeded(primary.key_id, ret.len());
         Ok(ret)
     }
 }
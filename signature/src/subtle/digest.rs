@@ -0,0 +1,68 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Signing and verification of precomputed message digests.
+
+use tink_core::TinkError;
+use tink_proto::HashType;
+
+/// `DigestSigner` is implemented by signers that can sign a message digest that has already been
+/// computed, for use with systems that only provide the digest (such as TLS stacks).
+pub trait DigestSigner {
+    /// Sign `digest`, which must be the output of the hash function `hash` that is specified by
+    /// the key parameters.  The signature is the same as would be produced by
+    /// [`tink_core::Signer::sign`] on the original message.
+    fn sign_digest(&self, hash: HashType, digest: &[u8]) -> Result<Vec<u8>, TinkError>;
+}
+
+/// `DigestVerifier` is implemented by verifiers that can verify a signature over a message digest
+/// that has already been computed.
+pub trait DigestVerifier {
+    /// Verify `signature` over `digest`, which must be the output of the hash function `hash`
+    /// that is specified by the key parameters.
+    fn verify_digest(
+        &self,
+        signature: &[u8],
+        hash: HashType,
+        digest: &[u8],
+    ) -> Result<(), TinkError>;
+}
+
+/// Check that a digest was produced by the hash function `key_hash` of the key parameters.
+pub(crate) fn validate_digest(
+    key_hash: HashType,
+    hash: HashType,
+    digest: &[u8],
+) -> Result<(), TinkError> {
+    if hash != key_hash {
+        return Err(format!(
+            "digest hash {:?} does not match key hash {:?}",
+            hash, key_hash
+        )
+        .into());
+    }
+    let size = tink_core::subtle::get_hash_digest_size(hash)?;
+    if digest.len() != size {
+        return Err(format!(
+            "invalid digest length {} for {:?}, want {}",
+            digest.len(),
+            hash,
+            size
+        )
+        .into());
+    }
+    Ok(())
+}
@@ -58,3 +58,66 @@ pub fn validate_ecdsa_params(
     }
     Ok(encoding)
 }
+
+/// Order of the NIST P-256 group, as a big-endian byte string.
+pub(crate) const NIST_P256_ORDER: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xbc, 0xe6, 0xfa, 0xad, 0xa7, 0x17, 0x9e, 0x84, 0xf3, 0xb9, 0xca, 0xc2, 0xfc, 0x63, 0x25, 0x51,
+];
+
+/// Order of the NIST P-384 group, as a big-endian byte string.
+#[cfg(feature = "ecdsa-p384")]
+pub(crate) const NIST_P384_ORDER: [u8; 48] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xc7, 0x63, 0x4d, 0x81, 0xf4, 0x37, 0x2d, 0xdf,
+    0x58, 0x1a, 0x0d, 0xb2, 0x48, 0xb0, 0xa7, 0x7a, 0xec, 0xec, 0x19, 0x6a, 0xcc, 0xc5, 0x29, 0x73,
+];
+
+/// Indicate whether an IEEE-P1363 encoded signature (`r || s`) for a curve whose group has the
+/// given (big-endian) `order` has its `s` value in the lower half of the group order.
+pub fn ecdsa_is_low_s(order: &[u8], signature: &[u8]) -> bool {
+    if signature.len() != 2 * order.len() {
+        return false;
+    }
+    // Compute floor(n / 2) by shifting right one bit.
+    let mut half_order = vec![0u8; order.len()];
+    let mut carry = 0u8;
+    for (h, o) in half_order.iter_mut().zip(order.iter()) {
+        *h = (o >> 1) | carry;
+        carry = (o & 1) << 7;
+    }
+    // Big-endian byte strings of equal length compare in the same order as the integers.
+    signature[order.len()..] <= half_order[..]
+}
+
+/// Normalize an IEEE-P1363 encoded signature (`r || s`) in place so that its `s` value is in the
+/// lower half of the group `order`, by replacing `s` with `n - s` if necessary.  Both forms are
+/// valid signatures for the same message.
+pub fn ecdsa_normalize_s(order: &[u8], signature: &mut [u8]) -> Result<(), TinkError> {
+    if signature.len() != 2 * order.len() {
+        return Err("ecdsa: invalid signature length".into());
+    }
+    if ecdsa_is_low_s(order, signature) {
+        return Ok(());
+    }
+    let s = &mut signature[order.len()..];
+    let mut borrow = 0u16;
+    for i in (0..order.len()).rev() {
+        let diff = 0x100 + order[i] as u16 - s[i] as u16 - borrow;
+        s[i] = diff as u8;
+        borrow = if diff < 0x100 { 1 } else { 0 };
+    }
+    Ok(())
+}
+
+/// Indicate whether an IEEE-P1363 encoded NIST P-256 signature (`r || s`) has its `s` value in
+/// the lower half of the group order.
+pub fn nist_p256_is_low_s(signature: &[u8]) -> bool {
+    ecdsa_is_low_s(&NIST_P256_ORDER, signature)
+}
+
+/// Normalize an IEEE-P1363 encoded NIST P-256 signature (`r || s`) in place so that its `s` value
+/// is in the lower half of the group order.
+pub fn nist_p256_normalize_s(signature: &mut [u8]) -> Result<(), TinkError> {
+    ecdsa_normalize_s(&NIST_P256_ORDER, signature)
+}
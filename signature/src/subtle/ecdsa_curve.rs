@@ -0,0 +1,452 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Curve-generic ECDSA key support.
+
+use std::sync::Arc;
+use tink_core::TinkError;
+use tink_proto::{EllipticCurveType, HashType};
+
+/// An elliptic curve that can be used for ECDSA signatures.
+///
+/// Support for a curve is added by implementing this trait for the curve type (normally with the
+/// `impl_ecdsa_curve!` macro, behind a crate feature for curves other than NIST P-256) and adding
+/// the curve to the `with_ecdsa_curve!` dispatch macro, which maps an [`EllipticCurveType`] to its
+/// implementation.
+pub trait EcdsaCurve: 'static {
+    /// Tink identifier for the curve.
+    const CURVE: EllipticCurveType;
+    /// Hash function that [`sign`](EcdsaCurve::sign) and [`verify`](EcdsaCurve::verify) use to
+    /// digest messages.  Keys on the curve are not tied to this hash function: messages digested
+    /// with another hash function are signed and verified with the prehash operations.
+    const DIGEST_HASH: HashType;
+    /// Order of the curve's group, as a big-endian byte string.  Its length is the size of a
+    /// scalar, and of each half of an IEEE-P1363 signature.
+    const ORDER: &'static [u8];
+
+    /// Private signing key type.
+    type SigningKey: Send + Sync + 'static;
+    /// Public verifying key type.
+    type VerifyingKey: Send + Sync + 'static;
+
+    /// Parse a private key from its big-endian scalar encoding.
+    fn signing_key_from_bytes(key_value: &[u8]) -> Result<Self::SigningKey, TinkError>;
+    /// Return the big-endian scalar encoding of a private key.
+    fn signing_key_to_bytes(key: &Self::SigningKey) -> Vec<u8>;
+    /// Generate a fresh private key.
+    fn random_signing_key() -> Self::SigningKey;
+    /// Return the public key corresponding to a private key.
+    fn verifying_key(key: &Self::SigningKey) -> Self::VerifyingKey;
+    /// Build a public key from its affine coordinates, which may be zero-padded.
+    fn verifying_key_from_coordinates(x: &[u8], y: &[u8]) -> Result<Self::VerifyingKey, TinkError>;
+    /// Return the affine `(x, y)` coordinates of a public key.
+    fn verifying_key_coordinates(key: &Self::VerifyingKey)
+        -> Result<(Vec<u8>, Vec<u8>), TinkError>;
+    /// Sign `data` digested with [`DIGEST_HASH`](EcdsaCurve::DIGEST_HASH), returning an
+    /// IEEE-P1363 encoded signature.
+    fn sign(key: &Self::SigningKey, data: &[u8]) -> Result<Vec<u8>, TinkError>;
+    /// Verify an IEEE-P1363 encoded signature over `data` digested with
+    /// [`DIGEST_HASH`](EcdsaCurve::DIGEST_HASH).
+    fn verify(key: &Self::VerifyingKey, signature: &[u8], data: &[u8]) -> Result<(), TinkError>;
+    /// Sign a precomputed message digest, returning an IEEE-P1363 encoded signature.
+    fn sign_prehash(key: &Self::SigningKey, digest: &[u8]) -> Result<Vec<u8>, TinkError>;
+    /// Verify an IEEE-P1363 encoded signature over a precomputed message digest.
+    fn verify_prehash(
+        key: &Self::VerifyingKey,
+        signature: &[u8],
+        digest: &[u8],
+    ) -> Result<(), TinkError>;
+    /// Convert a DER encoded signature to IEEE-P1363 encoding.
+    fn der_to_ieee_p1363(signature: &[u8]) -> Result<Vec<u8>, TinkError>;
+    /// Convert an IEEE-P1363 encoded signature to (canonical) DER encoding.
+    fn ieee_p1363_to_der(signature: &[u8]) -> Result<Vec<u8>, TinkError>;
+}
+
+/// Implement [`EcdsaCurve`] for a curve from one of the RustCrypto elliptic curve crates.
+macro_rules! impl_ecdsa_curve {
+    ($curve:ty, $krate:ident, $curve_type:expr, $digest_hash:expr, $order:expr) => {
+        impl EcdsaCurve for $curve {
+            const CURVE: EllipticCurveType = $curve_type;
+            const DIGEST_HASH: HashType = $digest_hash;
+            const ORDER: &'static [u8] = &$order;
+
+            type SigningKey = $krate::ecdsa::SigningKey;
+            type VerifyingKey = $krate::ecdsa::VerifyingKey;
+
+            fn signing_key_from_bytes(key_value: &[u8]) -> Result<Self::SigningKey, TinkError> {
+                if key_value.len() != Self::ORDER.len() {
+                    return Err("invalid private key len".into());
+                }
+                $krate::ecdsa::SigningKey::from_bytes(key_value)
+                    .map_err(|e| wrap_err("invalid private key", e))
+            }
+
+            fn signing_key_to_bytes(key: &Self::SigningKey) -> Vec<u8> {
+                key.to_bytes().to_vec()
+            }
+
+            fn random_signing_key() -> Self::SigningKey {
+                let mut csprng = tink_core::subtle::random::TinkRng;
+                $krate::ecdsa::SigningKey::random(&mut csprng)
+            }
+
+            fn verifying_key(key: &Self::SigningKey) -> Self::VerifyingKey {
+                $krate::ecdsa::VerifyingKey::from(key)
+            }
+
+            fn verifying_key_from_coordinates(
+                x: &[u8],
+                y: &[u8],
+            ) -> Result<Self::VerifyingKey, TinkError> {
+                let x = crate::subtle::element_from_padded_slice::<$curve>(x)?;
+                let y = crate::subtle::element_from_padded_slice::<$curve>(y)?;
+                let pt = elliptic_curve::sec1::EncodedPoint::<$curve>::from_affine_coordinates(
+                    &x, &y, /* compress= */ false,
+                );
+                $krate::ecdsa::VerifyingKey::from_encoded_point(&pt)
+                    .map_err(|e| wrap_err("invalid point", e))
+            }
+
+            fn verifying_key_coordinates(
+                key: &Self::VerifyingKey,
+            ) -> Result<(Vec<u8>, Vec<u8>), TinkError> {
+                let point = key.to_encoded_point(/* compress= */ false);
+                let data = point.as_bytes();
+                // Expect the uncompressed format:
+                //  - 1 byte uncompressed prefix (0x04)
+                //  - P bytes of X coordinate
+                //  - P bytes of Y coordinate
+                // where P is the field element size.
+                let point_len = Self::ORDER.len();
+                if data.len() != 2 * point_len + 1
+                    || data[0] != crate::ECDSA_UNCOMPRESSED_POINT_PREFIX
+                {
+                    return Err("unexpected public key data format".into());
+                }
+                Ok((
+                    data[1..point_len + 1].to_vec(),
+                    data[point_len + 1..].to_vec(),
+                ))
+            }
+
+            fn sign(key: &Self::SigningKey, data: &[u8]) -> Result<Vec<u8>, TinkError> {
+                let mut csprng = tink_core::subtle::random::TinkRng;
+                let signature: $krate::ecdsa::Signature = key
+                    .try_sign_with_rng(&mut csprng, data)
+                    .map_err(|e| wrap_err("signing failed", e))?;
+                Ok(signature.as_bytes().to_vec())
+            }
+
+            fn verify(
+                key: &Self::VerifyingKey,
+                signature: &[u8],
+                data: &[u8],
+            ) -> Result<(), TinkError> {
+                let signature = $krate::ecdsa::Signature::from_bytes(signature)
+                    .map_err(|e| wrap_err("invalid IEEE-P1363 signature", e))?;
+                key.verify(data, &signature)
+                    .map_err(|e| wrap_err("invalid signature", e))
+            }
+
+            fn sign_prehash(key: &Self::SigningKey, digest: &[u8]) -> Result<Vec<u8>, TinkError> {
+                let signature: $krate::ecdsa::Signature = key
+                    .sign_prehash(digest)
+                    .map_err(|e| wrap_err("signing failed", e))?;
+                Ok(signature.as_bytes().to_vec())
+            }
+
+            fn verify_prehash(
+                key: &Self::VerifyingKey,
+                signature: &[u8],
+                digest: &[u8],
+            ) -> Result<(), TinkError> {
+                let signature = $krate::ecdsa::Signature::from_bytes(signature)
+                    .map_err(|e| wrap_err("invalid IEEE-P1363 signature", e))?;
+                key.verify_prehash(digest, &signature)
+                    .map_err(|e| wrap_err("invalid signature", e))
+            }
+
+            fn der_to_ieee_p1363(signature: &[u8]) -> Result<Vec<u8>, TinkError> {
+                let signature = $krate::ecdsa::Signature::from_der(signature)
+                    .map_err(|e| wrap_err("invalid ASN.1 signature", e))?;
+                Ok(signature.as_bytes().to_vec())
+            }
+
+            fn ieee_p1363_to_der(signature: &[u8]) -> Result<Vec<u8>, TinkError> {
+                let signature = $krate::ecdsa::Signature::from_bytes(signature)
+                    .map_err(|e| wrap_err("invalid IEEE-P1363 signature", e))?;
+                Ok(signature.to_der().as_bytes().to_vec())
+            }
+        }
+    };
+}
+
+mod curves {
+    use super::EcdsaCurve;
+    use signature::{
+        hazmat::{PrehashSigner, PrehashVerifier},
+        RandomizedSigner, Signature, Verifier,
+    };
+    use tink_core::{utils::wrap_err, TinkError};
+    use tink_proto::{EllipticCurveType, HashType};
+
+    mod p256_impl {
+        use super::*;
+        use p256::elliptic_curve;
+        impl_ecdsa_curve!(
+            p256::NistP256,
+            p256,
+            EllipticCurveType::NistP256,
+            HashType::Sha256,
+            crate::subtle::NIST_P256_ORDER
+        );
+    }
+
+    #[cfg(feature = "ecdsa-p384")]
+    mod p384_impl {
+        use super::*;
+        use p384::elliptic_curve;
+        impl_ecdsa_curve!(
+            p384::NistP384,
+            p384,
+            EllipticCurveType::NistP384,
+            HashType::Sha384,
+            crate::subtle::NIST_P384_ORDER
+        );
+    }
+}
+
+/// Invoke the generic function `$f::<C>(args...)` with the [`EcdsaCurve`] implementation `C` for
+/// the [`EllipticCurveType`] value `$curve`, or return an error for an unsupported curve.
+macro_rules! with_ecdsa_curve {
+    ($curve:expr, $f:ident ( $($arg:expr),* )) => {
+        match $curve {
+            EllipticCurveType::NistP256 => $f::<p256::NistP256>($($arg),*),
+            #[cfg(feature = "ecdsa-p384")]
+            EllipticCurveType::NistP384 => $f::<p384::NistP384>($($arg),*),
+            curve => Err(format!("unsupported curve {:?}", curve).into()),
+        }
+    };
+}
+
+/// Digest `data` with the hash function `hash`.
+fn digest(hash: HashType, data: &[u8]) -> Result<Vec<u8>, TinkError> {
+    let mut hash_func = tink_core::subtle::get_hash_func(hash)
+        .ok_or_else(|| TinkError::new(&format!("unsupported hash {:?}", hash)))?;
+    tink_core::subtle::compute_hash(&mut hash_func, data)
+}
+
+/// Curve-erased operations on an ECDSA private key.
+trait DynSigningKey: Send + Sync {
+    fn curve(&self) -> EllipticCurveType;
+    fn order(&self) -> &'static [u8];
+    fn to_bytes(&self) -> Vec<u8>;
+    fn public_key(&self) -> EcdsaPublicKey;
+    fn sign(&self, hash: HashType, data: &[u8]) -> Result<Vec<u8>, TinkError>;
+    fn sign_prehash(&self, digest: &[u8]) -> Result<Vec<u8>, TinkError>;
+    fn ieee_p1363_to_der(&self, signature: &[u8]) -> Result<Vec<u8>, TinkError>;
+}
+
+/// Curve-erased operations on an ECDSA public key.
+trait DynVerifyingKey: Send + Sync {
+    fn curve(&self) -> EllipticCurveType;
+    fn order(&self) -> &'static [u8];
+    fn coordinates(&self) -> Result<(Vec<u8>, Vec<u8>), TinkError>;
+    fn verify(&self, hash: HashType, signature: &[u8], data: &[u8]) -> Result<(), TinkError>;
+    fn verify_prehash(&self, signature: &[u8], digest: &[u8]) -> Result<(), TinkError>;
+    fn der_to_ieee_p1363(&self, signature: &[u8]) -> Result<Vec<u8>, TinkError>;
+    fn ieee_p1363_to_der(&self, signature: &[u8]) -> Result<Vec<u8>, TinkError>;
+}
+
+struct CurveSigningKey<C: EcdsaCurve>(C::SigningKey);
+
+impl<C: EcdsaCurve> DynSigningKey for CurveSigningKey<C> {
+    fn curve(&self) -> EllipticCurveType {
+        C::CURVE
+    }
+    fn order(&self) -> &'static [u8] {
+        C::ORDER
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        C::signing_key_to_bytes(&self.0)
+    }
+    fn public_key(&self) -> EcdsaPublicKey {
+        EcdsaPublicKey::new::<C>(C::verifying_key(&self.0))
+    }
+    fn sign(&self, hash: HashType, data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        if hash == C::DIGEST_HASH {
+            C::sign(&self.0, data)
+        } else {
+            C::sign_prehash(&self.0, &digest(hash, data)?)
+        }
+    }
+    fn sign_prehash(&self, digest: &[u8]) -> Result<Vec<u8>, TinkError> {
+        C::sign_prehash(&self.0, digest)
+    }
+    fn ieee_p1363_to_der(&self, signature: &[u8]) -> Result<Vec<u8>, TinkError> {
+        C::ieee_p1363_to_der(signature)
+    }
+}
+
+struct CurveVerifyingKey<C: EcdsaCurve>(C::VerifyingKey);
+
+impl<C: EcdsaCurve> DynVerifyingKey for CurveVerifyingKey<C> {
+    fn curve(&self) -> EllipticCurveType {
+        C::CURVE
+    }
+    fn order(&self) -> &'static [u8] {
+        C::ORDER
+    }
+    fn coordinates(&self) -> Result<(Vec<u8>, Vec<u8>), TinkError> {
+        C::verifying_key_coordinates(&self.0)
+    }
+    fn verify(&self, hash: HashType, signature: &[u8], data: &[u8]) -> Result<(), TinkError> {
+        if hash == C::DIGEST_HASH {
+            C::verify(&self.0, signature, data)
+        } else {
+            C::verify_prehash(&self.0, signature, &digest(hash, data)?)
+        }
+    }
+    fn verify_prehash(&self, signature: &[u8], digest: &[u8]) -> Result<(), TinkError> {
+        C::verify_prehash(&self.0, signature, digest)
+    }
+    fn der_to_ieee_p1363(&self, signature: &[u8]) -> Result<Vec<u8>, TinkError> {
+        C::der_to_ieee_p1363(signature)
+    }
+    fn ieee_p1363_to_der(&self, signature: &[u8]) -> Result<Vec<u8>, TinkError> {
+        C::ieee_p1363_to_der(signature)
+    }
+}
+
+/// An ECDSA private key on any supported [`EcdsaCurve`].
+#[derive(Clone)]
+pub struct EcdsaPrivateKey(Arc<dyn DynSigningKey>);
+
+impl EcdsaPrivateKey {
+    /// Wrap a private key for the curve `C`.
+    pub fn new<C: EcdsaCurve>(key: C::SigningKey) -> Self {
+        Self(Arc::new(CurveSigningKey::<C>(key)))
+    }
+
+    /// Parse a private key for `curve` from its big-endian scalar encoding.
+    pub fn from_bytes(curve: EllipticCurveType, key_value: &[u8]) -> Result<Self, TinkError> {
+        fn parse<C: EcdsaCurve>(key_value: &[u8]) -> Result<EcdsaPrivateKey, TinkError> {
+            Ok(EcdsaPrivateKey::new::<C>(C::signing_key_from_bytes(
+                key_value,
+            )?))
+        }
+        with_ecdsa_curve!(curve, parse(key_value))
+    }
+
+    /// Generate a fresh private key for `curve`.
+    pub fn generate(curve: EllipticCurveType) -> Result<Self, TinkError> {
+        fn generate<C: EcdsaCurve>() -> Result<EcdsaPrivateKey, TinkError> {
+            Ok(EcdsaPrivateKey::new::<C>(C::random_signing_key()))
+        }
+        with_ecdsa_curve!(curve, generate())
+    }
+
+    /// Return the curve of the key.
+    pub fn curve(&self) -> EllipticCurveType {
+        self.0.curve()
+    }
+
+    /// Return the big-endian scalar encoding of the key.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_bytes()
+    }
+
+    /// Return the corresponding public key.
+    pub fn public_key(&self) -> EcdsaPublicKey {
+        self.0.public_key()
+    }
+
+    pub(crate) fn order(&self) -> &'static [u8] {
+        self.0.order()
+    }
+
+    pub(crate) fn sign(&self, hash: HashType, data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.0.sign(hash, data)
+    }
+
+    pub(crate) fn sign_prehash(&self, digest: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.0.sign_prehash(digest)
+    }
+
+    pub(crate) fn ieee_p1363_to_der(&self, signature: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.0.ieee_p1363_to_der(signature)
+    }
+}
+
+/// An ECDSA public key on any supported [`EcdsaCurve`].
+#[derive(Clone)]
+pub struct EcdsaPublicKey(Arc<dyn DynVerifyingKey>);
+
+impl EcdsaPublicKey {
+    /// Wrap a public key for the curve `C`.
+    pub fn new<C: EcdsaCurve>(key: C::VerifyingKey) -> Self {
+        Self(Arc::new(CurveVerifyingKey::<C>(key)))
+    }
+
+    /// Build a public key for `curve` from its affine coordinates, which may be zero-padded.
+    pub fn from_coordinates(
+        curve: EllipticCurveType,
+        x: &[u8],
+        y: &[u8],
+    ) -> Result<Self, TinkError> {
+        fn parse<C: EcdsaCurve>(x: &[u8], y: &[u8]) -> Result<EcdsaPublicKey, TinkError> {
+            Ok(EcdsaPublicKey::new::<C>(C::verifying_key_from_coordinates(
+                x, y,
+            )?))
+        }
+        with_ecdsa_curve!(curve, parse(x, y))
+    }
+
+    /// Return the curve of the key.
+    pub fn curve(&self) -> EllipticCurveType {
+        self.0.curve()
+    }
+
+    /// Return the affine `(x, y)` coordinates of the key.
+    pub fn coordinates(&self) -> Result<(Vec<u8>, Vec<u8>), TinkError> {
+        self.0.coordinates()
+    }
+
+    pub(crate) fn order(&self) -> &'static [u8] {
+        self.0.order()
+    }
+
+    pub(crate) fn verify(
+        &self,
+        hash: HashType,
+        signature: &[u8],
+        data: &[u8],
+    ) -> Result<(), TinkError> {
+        self.0.verify(hash, signature, data)
+    }
+
+    pub(crate) fn verify_prehash(&self, signature: &[u8], digest: &[u8]) -> Result<(), TinkError> {
+        self.0.verify_prehash(signature, digest)
+    }
+
+    pub(crate) fn der_to_ieee_p1363(&self, signature: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.0.der_to_ieee_p1363(signature)
+    }
+
+    pub(crate) fn ieee_p1363_to_der(&self, signature: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.0.ieee_p1363_to_der(signature)
+    }
+}
@@ -14,38 +14,22 @@
 //
 ////////////////////////////////////////////////////////////////////////////////
 
-use generic_array::typenum::Unsigned;
-use p256::{
-    ecdsa::signature::{RandomizedSigner, Signature},
-    elliptic_curve,
-};
+use super::EcdsaPrivateKey;
 use tink_core::{utils::wrap_err, TinkError};
 use tink_proto::{EcdsaSignatureEncoding, EllipticCurveType, HashType};
 
-/// An ECDSA private key.
-pub enum EcdsaPrivateKey {
-    NistP256(p256::ecdsa::SigningKey),
-}
-
-/// Manual implementation of [`Clone`].
-impl Clone for EcdsaPrivateKey {
-    fn clone(&self) -> Self {
-        match self {
-            EcdsaPrivateKey::NistP256(k) => {
-                EcdsaPrivateKey::NistP256(
-                    p256::ecdsa::SigningKey::from_bytes(&k.to_bytes()).unwrap(), /* safe: round-trip */
-                )
-            }
-        }
-    }
-}
-
 /// `EcdsaSigner` is an implementation of [`tink_core::Signer`] for ECDSA.
 /// At the moment, the implementation only accepts DER encoding.
+///
+/// By default signatures are emitted exactly as produced by the underlying curve implementation,
+/// so roughly half of them have a "high" `s` value; use [`EcdsaSigner::with_low_s`] to normalize
+/// them for consumers that reject such signatures.
 #[derive(Clone)]
 pub struct EcdsaSigner {
     private_key: EcdsaPrivateKey,
+    hash_alg: HashType,
     encoding: super::SignatureEncoding,
+    low_s: bool,
 }
 
 impl EcdsaSigner {
@@ -56,18 +40,8 @@ impl EcdsaSigner {
         encoding: EcdsaSignatureEncoding,
         key_value: &[u8],
     ) -> Result<Self, TinkError> {
-        let priv_key = match curve {
-            EllipticCurveType::NistP256 => {
-                if key_value.len() != elliptic_curve::FieldSize::<p256::NistP256>::to_usize() {
-                    return Err("EcdsaSigner: invalid private key len".into());
-                }
-                EcdsaPrivateKey::NistP256(
-                    p256::ecdsa::SigningKey::from_bytes(key_value)
-                        .map_err(|e| wrap_err("EcdsaSigner: invalid private key", e))?,
-                )
-            }
-            _ => return Err(format!("EcdsaSigner: unsupported curve {:?}", curve).into()),
-        };
+        let priv_key = EcdsaPrivateKey::from_bytes(curve, key_value)
+            .map_err(|e| wrap_err("EcdsaSigner", e))?;
         Self::new_from_private_key(hash_alg, curve, encoding, priv_key)
     }
 
@@ -80,27 +54,66 @@ impl EcdsaSigner {
     ) -> Result<Self, TinkError> {
         let encoding = super::ecdsa_common::validate_ecdsa_params(hash_alg, curve, encoding)
             .map_err(|e| wrap_err("EcdsaSigner", e))?;
+        if private_key.curve() != curve {
+            return Err(format!(
+                "EcdsaSigner: key for {:?} does not support {:?}",
+                private_key.curve(),
+                curve,
+            )
+            .into());
+        }
         Ok(EcdsaSigner {
             private_key,
+            hash_alg,
             encoding,
+            low_s: false,
         })
     }
+
+    /// Configure whether emitted signatures are normalized to have an `s` value in the lower
+    /// half of the group order (as required by e.g. Bitcoin and Ethereum consumers, and some
+    /// HSMs).
+    pub fn with_low_s(mut self, low_s: bool) -> Self {
+        self.low_s = low_s;
+        self
+    }
+}
+
+impl EcdsaSigner {
+    /// Normalize and encode an IEEE-P1363 signature according to the signer's configuration.
+    fn encode(&self, mut signature: Vec<u8>) -> Result<Vec<u8>, TinkError> {
+        if self.low_s {
+            super::ecdsa_normalize_s(self.private_key.order(), &mut signature)
+                .map_err(|e| wrap_err("EcdsaSigner", e))?;
+        }
+        match self.encoding {
+            super::SignatureEncoding::Der => self
+                .private_key
+                .ieee_p1363_to_der(&signature)
+                .map_err(|e| wrap_err("EcdsaSigner", e)),
+            super::SignatureEncoding::IeeeP1363 => Ok(signature),
+        }
+    }
 }
 
 impl tink_core::Signer for EcdsaSigner {
     fn sign(&self, data: &[u8]) -> Result<Vec<u8>, tink_core::TinkError> {
-        let mut csprng = signature::rand_core::OsRng {};
-        match &self.private_key {
-            EcdsaPrivateKey::NistP256(secret_key) => match self.encoding {
-                super::SignatureEncoding::Der => {
-                    let signature = secret_key.sign_with_rng(&mut csprng, data).to_der();
-                    Ok(signature.as_bytes().to_vec())
-                }
-                super::SignatureEncoding::IeeeP1363 => {
-                    let signature = secret_key.sign_with_rng(&mut csprng, data);
-                    Ok(signature.as_bytes().to_vec())
-                }
-            },
-        }
+        let signature = self
+            .private_key
+            .sign(self.hash_alg, data)
+            .map_err(|e| wrap_err("EcdsaSigner", e))?;
+        self.encode(signature)
+    }
+}
+
+impl super::DigestSigner for EcdsaSigner {
+    fn sign_digest(&self, hash: HashType, digest: &[u8]) -> Result<Vec<u8>, TinkError> {
+        super::validate_digest(self.hash_alg, hash, digest)
+            .map_err(|e| wrap_err("EcdsaSigner", e))?;
+        let signature = self
+            .private_key
+            .sign_prehash(digest)
+            .map_err(|e| wrap_err("EcdsaSigner", e))?;
+        self.encode(signature)
     }
 }
@@ -14,28 +14,74 @@
 //
 ////////////////////////////////////////////////////////////////////////////////
 
+use super::EcdsaPublicKey;
 use generic_array::typenum::Unsigned;
-use p256::{
-    ecdsa::{signature::Verifier, Signature},
-    elliptic_curve,
-    elliptic_curve::sec1::EncodedPoint,
+use p256::elliptic_curve;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
 };
-use signature::Signature as _;
 use tink_core::{utils::wrap_err, TinkError};
 use tink_proto::{EcdsaSignatureEncoding, EllipticCurveType, HashType};
 
-/// An ECDSA public key.
-#[derive(Clone)]
-pub enum EcdsaPublicKey {
-    NistP256(p256::ecdsa::VerifyingKey),
-}
-
 /// `EcdsaVerifier` is an implementation of [`tink_core::Verifier`] for ECDSA.
 /// At the moment, the implementation only accepts signatures with strict DER encoding.
+///
+/// In compatibility mode (see [`EcdsaVerifier::with_any_encoding`]) signatures are accepted in
+/// either DER or IEEE-P1363 encoding.
+///
+/// In strict mode (see [`EcdsaVerifier::with_strict`]) the verifier additionally rejects
+/// signatures with a "high" `s` value, and DER signatures that are not byte-for-byte identical to
+/// their canonical re-encoding, so that each message/key pair has exactly one accepted signature.
+///
+/// The parsed public key is shared between clones, so cloning a verifier is cheap.  (The backing
+/// curve implementation does not currently support precomputed tables for arbitrary public keys,
+/// so none are built.)
 #[derive(Clone)]
 pub struct EcdsaVerifier {
     public_key: EcdsaPublicKey,
+    hash_alg: HashType,
     encoding: super::SignatureEncoding,
+    strict: bool,
+    any_encoding: bool,
+    encoding_counters: Option<Arc<EcdsaEncodingCounters>>,
+}
+
+/// Snapshot of the number of signatures accepted in each encoding, as recorded by
+/// [`EcdsaEncodingCounters`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EcdsaEncodingStats {
+    /// Number of signatures accepted in DER encoding.
+    pub der: u64,
+    /// Number of signatures accepted in IEEE-P1363 encoding.
+    pub ieee_p1363: u64,
+}
+
+/// `EcdsaEncodingCounters` counts the signatures accepted by [`EcdsaVerifier`]s in each encoding.
+/// It can be shared between verifiers (and their clones), for example to monitor the progress of
+/// a migration from one signature encoding to the other.
+#[derive(Debug, Default)]
+pub struct EcdsaEncodingCounters {
+    der: AtomicU64,
+    ieee_p1363: AtomicU64,
+}
+
+impl EcdsaEncodingCounters {
+    /// Return the counts so far.
+    pub fn stats(&self) -> EcdsaEncodingStats {
+        EcdsaEncodingStats {
+            der: self.der.load(Ordering::Relaxed),
+            ieee_p1363: self.ieee_p1363.load(Ordering::Relaxed),
+        }
+    }
+
+    fn record(&self, encoding: &super::SignatureEncoding) {
+        let counter = match encoding {
+            super::SignatureEncoding::Der => &self.der,
+            super::SignatureEncoding::IeeeP1363 => &self.ieee_p1363,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
 }
 
 impl EcdsaVerifier {
@@ -47,19 +93,8 @@ impl EcdsaVerifier {
         x: &[u8],
         y: &[u8],
     ) -> Result<Self, TinkError> {
-        let public_key = match curve {
-            EllipticCurveType::NistP256 => {
-                let x = element_from_padded_slice::<p256::NistP256>(x)?;
-                let y = element_from_padded_slice::<p256::NistP256>(y)?;
-                let pt = EncodedPoint::<p256::NistP256>::from_affine_coordinates(
-                    &x, &y, /* compress= */ false,
-                );
-                let verify_key = p256::ecdsa::VerifyingKey::from_encoded_point(&pt)
-                    .map_err(|e| wrap_err("EcdsaVerifier: invalid point", e))?;
-                EcdsaPublicKey::NistP256(verify_key)
-            }
-            _ => return Err(format!("EcdsaVerifier: unsupported curve {:?}", curve,).into()),
-        };
+        let public_key = EcdsaPublicKey::from_coordinates(curve, x, y)
+            .map_err(|e| wrap_err("EcdsaVerifier", e))?;
         Self::new_from_public_key(hash_alg, curve, encoding, public_key)
     }
 
@@ -72,11 +107,45 @@ impl EcdsaVerifier {
     ) -> Result<Self, TinkError> {
         let encoding = super::validate_ecdsa_params(hash_alg, curve, encoding)
             .map_err(|e| wrap_err("EcdsaVerifier", e))?;
+        if public_key.curve() != curve {
+            return Err(format!(
+                "EcdsaVerifier: key for {:?} does not support {:?}",
+                public_key.curve(),
+                curve,
+            )
+            .into());
+        }
         Ok(EcdsaVerifier {
             public_key,
+            hash_alg,
             encoding,
+            strict: false,
+            any_encoding: false,
+            encoding_counters: None,
         })
     }
+
+    /// Configure whether the verifier rejects malleable signatures: those with an `s` value in
+    /// the upper half of the group order, or a DER encoding that differs from the canonical one.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Configure whether the verifier accepts signatures in both DER and IEEE-P1363 encodings,
+    /// rather than only in the encoding given by the key's parameters.  The key's own encoding is
+    /// tried first.  This allows a single key to verify a mix of signature formats while
+    /// signers migrate from one encoding to the other.
+    pub fn with_any_encoding(mut self, any_encoding: bool) -> Self {
+        self.any_encoding = any_encoding;
+        self
+    }
+
+    /// Record the encoding of each accepted signature in the given counters.
+    pub fn with_encoding_counters(mut self, counters: Arc<EcdsaEncodingCounters>) -> Self {
+        self.encoding_counters = Some(counters);
+        self
+    }
 }
 
 /// Produce an elliptic field element from a byte slice, allowing for padding
@@ -94,7 +163,7 @@ pub fn element_from_padded_slice<C: elliptic_curve::Curve>(
             }
         }
         Ok(elliptic_curve::FieldBytes::<C>::clone_from_slice(
-            &data[offset..],
+            &data[offset..], // safe: field size
         ))
     } else {
         // We have been given data that is too short for the field size.
@@ -102,23 +171,91 @@ pub fn element_from_padded_slice<C: elliptic_curve::Curve>(
         let mut data_copy = vec![0; point_len];
         data_copy[(point_len - data.len())..].copy_from_slice(data);
         Ok(elliptic_curve::FieldBytes::<C>::clone_from_slice(
-            &data_copy,
+            &data_copy, // safe: field size
         ))
     }
 }
 
-impl tink_core::Verifier for EcdsaVerifier {
-    fn verify(&self, signature: &[u8], data: &[u8]) -> Result<(), tink_core::TinkError> {
-        let signature = match self.encoding {
-            super::SignatureEncoding::Der => Signature::from_der(signature)
-                .map_err(|e| wrap_err("EcdsaVerifier: invalid ASN.1 signature", e))?,
-            super::SignatureEncoding::IeeeP1363 => Signature::from_bytes(signature)
-                .map_err(|e| wrap_err("EcdsaVerifier: invalid IEEE-P1363 signature", e))?,
+/// Input to a signature verification.
+#[derive(Clone, Copy)]
+enum Signed<'a> {
+    /// The message itself.
+    Data(&'a [u8]),
+    /// A precomputed digest of the message.
+    Digest(&'a [u8]),
+}
+
+impl EcdsaVerifier {
+    /// Verify a signature that is expected to be in the given encoding.
+    fn verify_encoded(
+        &self,
+        encoding: &super::SignatureEncoding,
+        signature: &[u8],
+        signed: Signed,
+    ) -> Result<(), TinkError> {
+        let p1363 = match encoding {
+            super::SignatureEncoding::Der => {
+                let p1363 = self
+                    .public_key
+                    .der_to_ieee_p1363(signature)
+                    .map_err(|e| wrap_err("EcdsaVerifier", e))?;
+                if self.strict && self.public_key.ieee_p1363_to_der(&p1363)? != signature {
+                    return Err("EcdsaVerifier: non-canonical DER signature".into());
+                }
+                p1363
+            }
+            super::SignatureEncoding::IeeeP1363 => signature.to_vec(),
+        };
+        let order = self.public_key.order();
+        if self.strict && p1363.len() == 2 * order.len() && !super::ecdsa_is_low_s(order, &p1363) {
+            return Err("EcdsaVerifier: signature has high S value".into());
+        }
+        match signed {
+            Signed::Data(data) => self.public_key.verify(self.hash_alg, &p1363, data),
+            Signed::Digest(digest) => self.public_key.verify_prehash(&p1363, digest),
+        }
+        .map_err(|e| wrap_err("EcdsaVerifier", e))
+    }
+
+    /// Verify a signature in the key's encoding, or (in compatibility mode) either encoding.
+    fn verify_signed(&self, signature: &[u8], signed: Signed) -> Result<(), TinkError> {
+        let result = self.verify_encoded(&self.encoding, signature, signed);
+        let (result, encoding) = match result {
+            Err(e) if self.any_encoding => {
+                let other = match self.encoding {
+                    super::SignatureEncoding::Der => super::SignatureEncoding::IeeeP1363,
+                    super::SignatureEncoding::IeeeP1363 => super::SignatureEncoding::Der,
+                };
+                match self.verify_encoded(&other, signature, signed) {
+                    Ok(()) => (Ok(()), other),
+                    // Report the failure for the key's own encoding.
+                    Err(_) => (Err(e), other),
+                }
+            }
+            result => (result, self.encoding.clone()),
         };
-        match &self.public_key {
-            EcdsaPublicKey::NistP256(verify_key) => verify_key
-                .verify(data, &signature)
-                .map_err(|e| wrap_err("EcdsaVerifier: invalid signature", e)),
+        if let (Ok(()), Some(counters)) = (&result, &self.encoding_counters) {
+            counters.record(&encoding);
         }
+        result
+    }
+}
+
+impl tink_core::Verifier for EcdsaVerifier {
+    fn verify(&self, signature: &[u8], data: &[u8]) -> Result<(), tink_core::TinkError> {
+        self.verify_signed(signature, Signed::Data(data))
+    }
+}
+
+impl super::DigestVerifier for EcdsaVerifier {
+    fn verify_digest(
+        &self,
+        signature: &[u8],
+        hash: HashType,
+        digest: &[u8],
+    ) -> Result<(), TinkError> {
+        super::validate_digest(self.hash_alg, hash, digest)
+            .map_err(|e| wrap_err("EcdsaVerifier", e))?;
+        self.verify_signed(signature, Signed::Digest(digest))
     }
 }
@@ -18,6 +18,11 @@ use signature::{Signature, Verifier as RustCryptoVerifier};
 use tink_core::{utils::wrap_err, TinkError};
 
 /// A [`tink_core::Verifier`] for ED25519.
+///
+/// Batches of signatures are verified one at a time (the default
+/// [`verify_batch`](tink_core::Verifier::verify_batch)): the batch verification equation is
+/// cofactored, and so accepts some signatures that [`verify`](tink_core::Verifier::verify)
+/// rejects.
 #[derive(Clone)]
 pub struct Ed25519Verifier {
     public_key: ed25519_dalek::PublicKey,
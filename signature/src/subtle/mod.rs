@@ -16,8 +16,12 @@
 
 //! This module provides subtle implementations of digital signature primitives.
 
+mod digest;
+pub use digest::*;
 mod ecdsa_common;
 pub use ecdsa_common::*;
+mod ecdsa_curve;
+pub use ecdsa_curve::*;
 mod ecdsa_signer;
 pub use ecdsa_signer::*;
 mod ecdsa_verifier;
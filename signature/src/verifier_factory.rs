@@ -16,7 +16,7 @@
 
 //! Factory methods for [`tink_core::Verifier`] instances.
 
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 use tink_core::{utils::wrap_err, TinkError};
 
 /// Return a [`tink_core::Verifier`] primitive from the given keyset handle.
@@ -26,6 +26,68 @@ pub fn new_verifier(
     new_verifier_with_key_manager(h, None)
 }
 
+/// Options for the verification of ECDSA signatures, for use with
+/// [`new_verifier_with_ecdsa_options`].  Keys of other types are unaffected.
+#[derive(Clone, Debug, Default)]
+pub struct EcdsaVerifierOptions {
+    /// Reject ECDSA signatures with a high `s` value and DER signatures that are not canonically
+    /// encoded, so that a signature cannot be altered into a different valid one.
+    pub strict: bool,
+    /// Accept ECDSA signatures in both DER and IEEE-P1363 encodings, whatever the encoding in the
+    /// key's parameters, for example while signers migrate from one encoding to the other.
+    pub any_encoding: bool,
+    /// Counters recording the encoding of each accepted ECDSA signature.
+    pub encoding_counters: Option<Arc<crate::subtle::EcdsaEncodingCounters>>,
+}
+
+/// Return a [`tink_core::Verifier`] primitive from the given keyset handle, with ECDSA
+/// verification in strict mode.
+///
+/// Strict mode rejects ECDSA signatures with a high `s` value and DER signatures that are not
+/// canonically encoded, so that a signature cannot be altered into a different valid one.  Keys
+/// of other types are unaffected.
+pub fn new_verifier_with_strict_ecdsa(
+    h: &tink_core::keyset::Handle,
+) -> Result<Box<dyn tink_core::Verifier>, TinkError> {
+    new_verifier_with_ecdsa_options(
+        h,
+        EcdsaVerifierOptions {
+            strict: true,
+            ..Default::default()
+        },
+    )
+}
+
+/// Return a [`tink_core::Verifier`] primitive from the given keyset handle, with ECDSA keys
+/// verified according to the given options.
+pub fn new_verifier_with_ecdsa_options(
+    h: &tink_core::keyset::Handle,
+    options: EcdsaVerifierOptions,
+) -> Result<Box<dyn tink_core::Verifier>, TinkError> {
+    new_verifier_with_key_manager(
+        h,
+        Some(Arc::new(crate::EcdsaVerifierKeyManager::new_with_options(
+            options,
+        ))),
+    )
+}
+
+/// Return a [`tink_core::Verifier`] primitive for a single public key, bypassing keyset wrapping.
+///
+/// The primitive only accepts signatures without an output prefix; prefer [`new_verifier`] with
+/// a keyset [`Handle`](tink_core::keyset::Handle) unless exactly one raw key is available.  As
+/// no secret key material is involved, gatekeepers are not consulted.
+pub fn unwrapped_verifier_from_key_data(
+    kd: &tink_proto::KeyData,
+) -> Result<Box<dyn tink_core::Verifier>, TinkError> {
+    match tink_core::registry::primitive_from_key_data(kd)
+        .map_err(|e| wrap_err("verifier::factory: cannot create primitive from key", e))?
+    {
+        tink_core::Primitive::Verifier(p) => Ok(p),
+        _ => Err("verifier::factory: not a Verifier primitive".into()),
+    }
+}
+
 /// Return a [`tink_core::Verifier`] primitive from the given keyset handle and custom key manager.
 fn new_verifier_with_key_manager(
     h: &tink_core::keyset::Handle,
@@ -47,6 +109,9 @@ struct WrappedVerifier {
 
 impl WrappedVerifier {
     fn new(ps: tink_core::primitiveset::PrimitiveSet) -> Result<WrappedVerifier, TinkError> {
+        if !ps.usage.allows_verify() {
+            return Err("verifier::factory: verification not permitted".into());
+        }
         let primary = match &ps.primary {
             None => return Err("verifier::factory: no primary primitive".into()),
             Some(p) => p,
@@ -71,8 +136,18 @@ impl WrappedVerifier {
 
 impl tink_core::Verifier for WrappedVerifier {
     fn verify(&self, signature: &[u8], data: &[u8]) -> Result<(), TinkError> {
+        self.verify_with_key_id(signature, data).map(|_| ())
+    }
+
+    fn verify_with_key_id(
+        &self,
+        signature: &[u8],
+        data: &[u8],
+    ) -> Result<tink_core::KeyId, TinkError> {
+        let op = tink_core::trace::operation("verifier", "verify", data.len());
         let prefix_size = tink_core::cryptofmt::NON_RAW_PREFIX_SIZE;
         if signature.len() < prefix_size {
+            op.failed("signature too short");
             return Err("verifier::factory: invalid signature".into());
         }
 
@@ -92,8 +167,10 @@ impl tink_core::Verifier for WrappedVerifier {
                     entry.primitive.verify(signature_no_prefix, data)
                 };
                 if result.is_ok() {
-                    return Ok(());
+                    op.succeeded(entry.key_id, 0);
+                    return Ok(entry.key_id);
                 }
+                op.key_failed(entry.key_id);
             }
         }
 
@@ -101,11 +178,90 @@ impl tink_core::Verifier for WrappedVerifier {
         if let Some(entries) = self.ps.raw_entries() {
             for entry in entries {
                 if entry.primitive.verify(signature, data).is_ok() {
-                    return Ok(());
+                    op.succeeded(entry.key_id, 0);
+                    return Ok(entry.key_id);
                 }
+                op.key_failed(entry.key_id);
             }
         }
 
+        op.failed("no key could verify");
         Err("verifier::factory: invalid signature".into())
     }
+
+    fn verify_batch(&self, items: &[(&[u8], &[u8])]) -> Result<(), TinkError> {
+        let op = tink_core::trace::operation(
+            "verifier",
+            "verify_batch",
+            items.iter().map(|(_, data)| data.len()).sum(),
+        );
+        // Group the items whose prefix identifies a single (non-raw) key, so that each group can
+        // be handed to the underlying primitive as a batch.  Anything else is verified
+        // individually.
+        let prefix_size = tink_core::cryptofmt::NON_RAW_PREFIX_SIZE;
+        let mut groups: HashMap<tink_core::KeyId, BatchGroup> = HashMap::new();
+        let mut remaining = Vec::new();
+        for &(signature, data) in items {
+            let entries = if signature.len() < prefix_size {
+                None
+            } else {
+                self.ps.entries_for_prefix(&signature[..prefix_size])
+            };
+            match entries {
+                Some(entries) if entries.len() == 1 => {
+                    let entry = &entries[0];
+                    let group = groups.entry(entry.key_id).or_insert_with(|| BatchGroup {
+                        primitive: entry.primitive.as_ref(),
+                        signatures: Vec::new(),
+                        data: Vec::new(),
+                        originals: Vec::new(),
+                    });
+                    let mut signed_data = data.to_vec();
+                    if entry.prefix_type == tink_proto::OutputPrefixType::Legacy {
+                        signed_data.push(0u8);
+                    }
+                    group.signatures.push(&signature[prefix_size..]);
+                    group.data.push(signed_data);
+                    group.originals.push((signature, data));
+                }
+                _ => remaining.push((signature, data)),
+            }
+        }
+
+        for (key_id, group) in groups.iter() {
+            let batch: Vec<(&[u8], &[u8])> = group
+                .signatures
+                .iter()
+                .zip(group.data.iter())
+                .map(|(signature, data)| (*signature, &data[..]))
+                .collect();
+            if group.primitive.verify_batch(&batch).is_ok() {
+                op.succeeded(*key_id, 0);
+            } else {
+                // The batch may include signatures that are only valid under a raw key, so fall
+                // back to individual verification.
+                op.key_failed(*key_id);
+                remaining.extend_from_slice(&group.originals);
+            }
+        }
+
+        for (signature, data) in remaining {
+            self.verify_with_key_id(signature, data).map_err(|e| {
+                op.failed("no key could verify");
+                e
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Items of a batch that are all to be verified with the same key.
+struct BatchGroup<'a> {
+    primitive: &'a dyn tink_core::Verifier,
+    /// Signatures with their key prefix removed.
+    signatures: Vec<&'a [u8]>,
+    /// Signed data, adjusted for the output prefix type of the key.
+    data: Vec<Vec<u8>>,
+    /// The original `(signature, data)` items.
+    originals: Vec<(&'a [u8], &'a [u8])>,
 }
@@ -0,0 +1,117 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+#![feature(test)]
+extern crate test;
+use std::{
+    io::{Cursor, Read, Write},
+    time::Duration,
+};
+use test::Bencher;
+use tink_streaming_aead::ReadAheadReader;
+
+const SEGMENT_SIZE: usize = 4096;
+const PLAINTEXT_SIZE: usize = 16 * SEGMENT_SIZE;
+const AAD: &[u8] = b"this data needs to be authenticated, but not encrypted";
+
+/// Ciphertext source that simulates a remote object store, where each read has a fixed
+/// latency and returns at most `max_read` bytes.
+struct SlowReader {
+    inner: Cursor<Vec<u8>>,
+    latency: Duration,
+    max_read: usize,
+}
+
+impl Read for SlowReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        std::thread::sleep(self.latency);
+        let n = std::cmp::min(buf.len(), self.max_read);
+        self.inner.read(&mut buf[..n])
+    }
+}
+
+fn setup() -> (Box<dyn tink_core::StreamingAead>, Vec<u8>) {
+    tink_streaming_aead::init();
+    let kh =
+        tink_core::keyset::Handle::new(&tink_streaming_aead::aes128_gcm_hkdf_4kb_key_template())
+            .unwrap();
+    let a = tink_streaming_aead::new(&kh).unwrap();
+    let ct = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut w = a
+        .new_encrypting_writer(Box::new(SharedVec(ct.clone())), AAD)
+        .unwrap();
+    w.write_all(&vec![0xab; PLAINTEXT_SIZE]).unwrap();
+    w.close().unwrap();
+    let ct = ct.lock().unwrap().clone();
+    (a, ct)
+}
+
+#[derive(Clone)]
+struct SharedVec(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+impl Write for SharedVec {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn decrypt(a: &dyn tink_core::StreamingAead, source: Box<dyn Read>) {
+    let mut r = a.new_decrypting_reader(source, AAD).unwrap();
+    let mut pt = Vec::with_capacity(PLAINTEXT_SIZE);
+    r.read_to_end(&mut pt).unwrap();
+    assert_eq!(pt.len(), PLAINTEXT_SIZE);
+}
+
+fn slow(ct: &[u8], latency_us: u64) -> SlowReader {
+    SlowReader {
+        inner: Cursor::new(ct.to_vec()),
+        latency: Duration::from_micros(latency_us),
+        max_read: 1024,
+    }
+}
+
+#[bench]
+fn bench_decrypt_low_latency(b: &mut Bencher) {
+    let (a, ct) = setup();
+    b.iter(|| decrypt(a.as_ref(), Box::new(slow(&ct, 50))));
+}
+
+#[bench]
+fn bench_decrypt_low_latency_read_ahead(b: &mut Bencher) {
+    let (a, ct) = setup();
+    b.iter(|| {
+        let source = ReadAheadReader::new(slow(&ct, 50), SEGMENT_SIZE, 4);
+        decrypt(a.as_ref(), Box::new(source))
+    });
+}
+
+#[bench]
+fn bench_decrypt_high_latency(b: &mut Bencher) {
+    let (a, ct) = setup();
+    b.iter(|| decrypt(a.as_ref(), Box::new(slow(&ct, 1000))));
+}
+
+#[bench]
+fn bench_decrypt_high_latency_read_ahead(b: &mut Bencher) {
+    let (a, ct) = setup();
+    b.iter(|| {
+        let source = ReadAheadReader::new(slow(&ct, 1000), SEGMENT_SIZE, 4);
+        decrypt(a.as_ref(), Box::new(source))
+    });
+}
@@ -103,7 +103,7 @@ fn validate_key(
 }
 
 /// Validate the given [`tink_proto::AesCtrHmacStreamingKeyFormat`].
-fn validate_key_format(
+pub(crate) fn validate_key_format(
     format: &tink_proto::AesCtrHmacStreamingKeyFormat,
 ) -> Result<tink_proto::AesCtrHmacStreamingParams, TinkError> {
     tink_core::keyset::validate_key_version(format.version, AES_CTR_HMAC_KEY_VERSION)?;
@@ -121,21 +121,21 @@ fn validate_params(
     params: &tink_proto::AesCtrHmacStreamingParams,
 ) -> Result<(tink_proto::HmacParams, HashType, HashType), TinkError> {
     crate::subtle::validate_aes_key_size(params.derived_key_size as usize)?;
-    let hkdf_hash = match HashType::from_i32(params.hkdf_hash_type) {
-        Some(HashType::UnknownHash) => return Err("AesCtrHmacKeyManager: unknown HKDF hash".into()),
-        Some(h) => h,
-        None => return Err("AesCtrHmacKeyManager: unknown HKDF hash".into()),
+    let hkdf_hash = match params.try_hkdf_hash_type() {
+        Ok(HashType::UnknownHash) | Err(_) => {
+            return Err("AesCtrHmacKeyManager: unknown HKDF hash".into())
+        }
+        Ok(h) => h,
     };
     let hmac_params = params
         .hmac_params
         .as_ref()
         .ok_or_else(|| TinkError::new("AesCtrHmacKeyManager: no HMAC params"))?;
-    let hmac_hash = match HashType::from_i32(hmac_params.hash) {
-        Some(HashType::UnknownHash) => {
+    let hmac_hash = match hmac_params.try_hash() {
+        Ok(HashType::UnknownHash) | Err(_) => {
             return Err("AesCtrHmacKeyManager: unknown tag algorithm".into())
         }
-        Some(h) => h,
-        None => return Err("AesCtrHmacKeyManager: unknown tag algorithm".into()),
+        Ok(h) => h,
     };
     tink_mac::subtle::validate_hmac_params(
         hmac_hash,
@@ -101,7 +101,7 @@ fn validate_key(
 }
 
 /// Validate the given [`tink_proto::AesGcmHkdfStreamingKeyFormat`].
-fn validate_key_format(
+pub(crate) fn validate_key_format(
     format: &tink_proto::AesGcmHkdfStreamingKeyFormat,
 ) -> Result<tink_proto::AesGcmHkdfStreamingParams, TinkError> {
     crate::subtle::validate_aes_key_size(format.key_size as usize)?;
@@ -116,10 +116,9 @@ fn validate_key_format(
 /// Validate the given [`tink_proto::AesGcmHkdfStreamingParams`].
 fn validate_params(params: &tink_proto::AesGcmHkdfStreamingParams) -> Result<HashType, TinkError> {
     crate::subtle::validate_aes_key_size(params.derived_key_size as usize)?;
-    let hkdf_hash = match HashType::from_i32(params.hkdf_hash_type) {
-        Some(HashType::UnknownHash) => return Err("unknown HKDF hash type".into()),
-        Some(h) => h,
-        None => return Err("unknown HKDF hash type".into()),
+    let hkdf_hash = match params.try_hkdf_hash_type() {
+        Ok(HashType::UnknownHash) | Err(_) => return Err("unknown HKDF hash type".into()),
+        Ok(h) => h,
     };
     let min_segment_size = (params.derived_key_size as usize)
         + subtle::AES_GCM_HKDF_NONCE_PREFIX_SIZE_IN_BYTES
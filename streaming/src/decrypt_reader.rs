@@ -13,11 +13,8 @@
 // limitations under the License.
 //
 
-use std::{
-    cell::{RefCell, RefMut},
-    io,
-    rc::Rc,
-};
+use std::io;
+use tink_core::utils::SharedCopyReader;
 
 /// Possible states for a [`DecryptReader`].
 enum State {
@@ -34,6 +31,7 @@ enum State {
 pub(crate) struct DecryptReader {
     wrapped: crate::WrappedStreamingAead,
     aad: Vec<u8>,
+    segment_aad: Vec<u8>,
     state: State,
 }
 
@@ -42,10 +40,12 @@ impl DecryptReader {
         wrapped: crate::WrappedStreamingAead,
         reader: Box<dyn io::Read>,
         aad: &[u8],
+        segment_aad: &[u8],
     ) -> Self {
         Self {
             wrapped,
             aad: aad.to_vec(),
+            segment_aad: segment_aad.to_vec(),
             state: State::Pending(reader),
         }
     }
@@ -77,10 +77,11 @@ impl io::Read for DecryptReader {
                 // Attempt a decrypting-read from the ciphertext reader `cr`, but also keep a copy
                 // of the read data into a buffer so that it can be re-scanned with
                 // a different key if decryption fails.
-                let mut r = match e
-                    .primitive
-                    .new_decrypting_reader(Box::new(copy_reader.clone()), &self.aad)
-                {
+                let mut r = match e.primitive.new_decrypting_reader_with_segment_aad(
+                    Box::new(copy_reader.clone()),
+                    &self.aad,
+                    &self.segment_aad,
+                ) {
                     Ok(r) => r,
                     Err(_) => {
                         copy_reader.rewind();
@@ -111,78 +112,3 @@ impl io::Read for DecryptReader {
         ))
     }
 }
-
-/// Wrapper around an [`io::Read`] trait object that stores a copy of all of the data
-/// read from the underlying object.
-struct CopyReader {
-    reader: Box<dyn io::Read>,
-    copying: bool,
-    read_pos: usize,
-    copied_data: Vec<u8>,
-}
-
-impl CopyReader {
-    fn new(reader: Box<dyn io::Read>) -> Self {
-        Self {
-            reader,
-            copying: true,
-            read_pos: 0,
-            copied_data: vec![],
-        }
-    }
-    fn rewind(&mut self) {
-        self.read_pos = 0;
-    }
-    fn stop_copying(&mut self) {
-        self.copying = false;
-        // Buffered data has been consumed, so drop it.
-        self.copied_data = vec![];
-        self.read_pos = 0;
-    }
-}
-
-impl io::Read for CopyReader {
-    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        if self.read_pos < self.copied_data.len() {
-            // Read from the buffered copy of the data.
-            let available_data = self.copied_data.len() - self.read_pos;
-            let n = std::cmp::min(buf.len(), available_data);
-            buf[..n].copy_from_slice(&self.copied_data[self.read_pos..self.read_pos + n]);
-            self.read_pos += n;
-            Ok(n)
-        } else {
-            // Read from the underlying object
-            let n = self.reader.read(buf)?;
-            if self.copying {
-                // Store a copy of the data read.
-                self.copied_data.extend_from_slice(&buf[..n]);
-                self.read_pos += n;
-            }
-            Ok(n)
-        }
-    }
-}
-
-#[derive(Clone)]
-struct SharedCopyReader(Rc<RefCell<CopyReader>>);
-
-impl SharedCopyReader {
-    fn new(reader: Box<dyn io::Read>) -> Self {
-        Self(Rc::new(RefCell::new(CopyReader::new(reader))))
-    }
-    fn rewind(&mut self) {
-        let mut cr: RefMut<_> = self.0.borrow_mut();
-        cr.rewind();
-    }
-    fn stop_copying(&mut self) {
-        let mut cr: RefMut<_> = self.0.borrow_mut();
-        cr.stop_copying();
-    }
-}
-
-impl io::Read for SharedCopyReader {
-    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        let mut cr: RefMut<_> = self.0.borrow_mut();
-        cr.read(buf)
-    }
-}
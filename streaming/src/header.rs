@@ -0,0 +1,164 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Introspection of streaming AEAD ciphertext headers, without decryption.
+
+use crate::subtle::{validate_aes_key_size, AES_GCM_HKDF_NONCE_PREFIX_SIZE_IN_BYTES};
+use std::io::Read;
+use tink_core::{utils::wrap_err, TinkError};
+use tink_proto::{prost::Message, HashType};
+
+/// Parsed header of a streaming AEAD ciphertext.
+///
+/// Both of the [`AesGcmHkdf`](crate::subtle::AesGcmHkdf) and
+/// [`AesCtrHmac`](crate::subtle::AesCtrHmac) schemes emit a header made up of a single byte
+/// holding the header length, followed by a random salt that is the same size as the derived
+/// segment keys, and then a random nonce prefix.  The header therefore reveals the derived key
+/// size, but not the segment size or the key type; use [`StreamingKeyParameters`] to find the
+/// key parameters that are compatible with a header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StreamingHeader {
+    /// Salt used, together with the main key, to derive the segment keys.
+    pub salt: Vec<u8>,
+    /// Prefix of the per-segment nonces.
+    pub nonce_prefix: Vec<u8>,
+}
+
+impl StreamingHeader {
+    /// Return the total length of the header, including the length byte.
+    pub fn header_length(&self) -> usize {
+        1 + self.salt.len() + self.nonce_prefix.len()
+    }
+
+    /// Return the size in bytes of the segment keys derived for this ciphertext.
+    pub fn derived_key_size(&self) -> usize {
+        self.salt.len()
+    }
+}
+
+/// Parse the header at the start of a streaming AEAD ciphertext.  Only the header bytes need to
+/// be present in `ciphertext`.
+pub fn parse_header(ciphertext: &[u8]) -> Result<StreamingHeader, TinkError> {
+    let header_len = header_length_from(ciphertext.first().copied())?;
+    if ciphertext.len() < header_len {
+        return Err("streaming header: ciphertext too short".into());
+    }
+    Ok(split_header(&ciphertext[..header_len]))
+}
+
+/// Read and parse the header from the start of a streaming AEAD ciphertext, consuming exactly the
+/// header bytes from `r`.
+pub fn read_header(r: &mut dyn Read) -> Result<StreamingHeader, TinkError> {
+    let mut hlen = [0u8; 1];
+    r.read_exact(&mut hlen)
+        .map_err(|e| wrap_err("streaming header: failed to read header length", e))?;
+    let header_len = header_length_from(Some(hlen[0]))?;
+    let mut header = vec![0u8; header_len];
+    header[0] = hlen[0];
+    r.read_exact(&mut header[1..])
+        .map_err(|e| wrap_err("streaming header: failed to read header", e))?;
+    Ok(split_header(&header))
+}
+
+/// Check the header length byte, returning the header length.
+fn header_length_from(hlen: Option<u8>) -> Result<usize, TinkError> {
+    let header_len = match hlen {
+        Some(hlen) => hlen as usize,
+        None => return Err("streaming header: empty ciphertext".into()),
+    };
+    let key_size = header_len
+        .checked_sub(1 + AES_GCM_HKDF_NONCE_PREFIX_SIZE_IN_BYTES)
+        .ok_or_else(|| TinkError::new("streaming header: invalid header length"))?;
+    validate_aes_key_size(key_size)
+        .map_err(|e| wrap_err("streaming header: invalid header length", e))?;
+    Ok(header_len)
+}
+
+/// Split a complete header (including the length byte) into its components.  Both schemes use
+/// nonce prefixes of the same size.
+fn split_header(header: &[u8]) -> StreamingHeader {
+    let salt_end = header.len() - AES_GCM_HKDF_NONCE_PREFIX_SIZE_IN_BYTES;
+    StreamingHeader {
+        salt: header[1..salt_end].to_vec(),
+        nonce_prefix: header[salt_end..].to_vec(),
+    }
+}
+
+/// Parameters of a streaming AEAD key type that are needed to decrypt a ciphertext.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StreamingKeyParameters {
+    /// Type URL of the key.
+    pub type_url: String,
+    /// Size in bytes of the derived segment keys.
+    pub derived_key_size: usize,
+    /// Size in bytes of each ciphertext segment.
+    pub ciphertext_segment_size: usize,
+    /// Hash function used for HKDF derivation of the segment keys.
+    pub hkdf_hash_type: HashType,
+}
+
+impl StreamingKeyParameters {
+    /// Extract the streaming parameters from a key template for one of the key types in this
+    /// crate.
+    pub fn from_template(kt: &tink_proto::KeyTemplate) -> Result<Self, TinkError> {
+        let (derived_key_size, ciphertext_segment_size, hkdf_hash_type) = match kt.type_url.as_str()
+        {
+            crate::AES_GCM_HKDF_TYPE_URL => {
+                let format = tink_proto::AesGcmHkdfStreamingKeyFormat::decode(kt.value.as_ref())
+                    .map_err(|e| wrap_err("StreamingKeyParameters: invalid key format", e))?;
+                let params = format
+                    .params
+                    .ok_or_else(|| TinkError::new("StreamingKeyParameters: no params"))?;
+                (
+                    params.derived_key_size,
+                    params.ciphertext_segment_size,
+                    params.try_hkdf_hash_type()?,
+                )
+            }
+            crate::AES_CTR_HMAC_TYPE_URL => {
+                let format = tink_proto::AesCtrHmacStreamingKeyFormat::decode(kt.value.as_ref())
+                    .map_err(|e| wrap_err("StreamingKeyParameters: invalid key format", e))?;
+                let params = format
+                    .params
+                    .ok_or_else(|| TinkError::new("StreamingKeyParameters: no params"))?;
+                (
+                    params.derived_key_size,
+                    params.ciphertext_segment_size,
+                    params.try_hkdf_hash_type()?,
+                )
+            }
+            _ => {
+                return Err(format!(
+                    "StreamingKeyParameters: unsupported key type {}",
+                    kt.type_url
+                )
+                .into())
+            }
+        };
+        Ok(StreamingKeyParameters {
+            type_url: kt.type_url.clone(),
+            derived_key_size: derived_key_size as usize,
+            ciphertext_segment_size: ciphertext_segment_size as usize,
+            hkdf_hash_type,
+        })
+    }
+
+    /// Indicate whether a ciphertext with the given header could have been produced by a key with
+    /// these parameters.
+    pub fn matches(&self, header: &StreamingHeader) -> bool {
+        self.derived_key_size == header.derived_key_size()
+    }
+}
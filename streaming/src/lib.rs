@@ -30,6 +30,12 @@ mod aes_gcm_hkdf_key_manager;
 pub use aes_gcm_hkdf_key_manager::*;
 mod decrypt_reader;
 use decrypt_reader::*;
+mod header;
+pub use header::*;
+mod read_ahead;
+pub use read_ahead::*;
+mod reencrypt;
+pub use reencrypt::*;
 mod streamingaead_factory;
 pub use streamingaead_factory::*;
 mod streamingaead_key_templates;
@@ -0,0 +1,128 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Read-ahead of ciphertext from high-latency sources.
+
+use std::{
+    io,
+    sync::mpsc::{sync_channel, Receiver},
+    thread,
+};
+
+/// Default number of segments that a [`ReadAheadReader`] buffers ahead of the consumer.
+pub const DEFAULT_READ_AHEAD_SEGMENTS: usize = 4;
+
+/// `ReadAheadReader` wraps a (typically high-latency) ciphertext source, and reads ahead from it on
+/// a background thread so that fetching data overlaps with decryption.
+///
+/// Data is read from the source in chunks of `chunk_size` bytes, which should normally be the
+/// ciphertext segment size of the key in use.  At most `segments` chunks are buffered ahead of the
+/// consumer, bounding memory use.  Wrap the source before passing it to
+/// [`tink_core::StreamingAead::new_decrypting_reader`]:
+///
+/// ```ignore
+/// let source = ReadAheadReader::new(network_stream, 1024 * 1024, DEFAULT_READ_AHEAD_SEGMENTS);
+/// let r = streaming_aead.new_decrypting_reader(Box::new(source), aad)?;
+/// ```
+///
+/// If the source returns an error, the error is reported to the consumer in order, after any data
+/// read before it.  Dropping the reader stops the background thread once its current read
+/// completes.
+pub struct ReadAheadReader {
+    rx: Receiver<io::Result<Vec<u8>>>,
+    chunk_size: usize,
+    chunk: Vec<u8>,
+    pos: usize,
+    done: bool,
+}
+
+impl ReadAheadReader {
+    /// Create a reader that reads ahead from `source` on a background thread.
+    pub fn new<R>(source: R, chunk_size: usize, segments: usize) -> Self
+    where
+        R: io::Read + Send + 'static,
+    {
+        let (tx, rx) = sync_channel(segments);
+        let chunk_size = std::cmp::max(chunk_size, 1);
+        thread::spawn(move || {
+            let mut source = source;
+            loop {
+                let result = read_chunk(&mut source, chunk_size);
+                let finished = !matches!(&result, Ok(chunk) if chunk.len() == chunk_size);
+                if tx.send(result).is_err() || finished {
+                    // Either the consumer has gone away, or there is no more data.
+                    return;
+                }
+            }
+        });
+        ReadAheadReader {
+            rx,
+            chunk_size,
+            chunk: vec![],
+            pos: 0,
+            done: false,
+        }
+    }
+}
+
+/// Read up to `chunk_size` bytes from `source`, only returning a short chunk at end of input.
+fn read_chunk<R: io::Read>(source: &mut R, chunk_size: usize) -> io::Result<Vec<u8>> {
+    let mut chunk = vec![0; chunk_size];
+    let mut n = 0;
+    while n < chunk_size {
+        match source.read(&mut chunk[n..]) {
+            Ok(0) => break,
+            Ok(m) => n += m,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    chunk.truncate(n);
+    Ok(chunk)
+}
+
+impl io::Read for ReadAheadReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos >= self.chunk.len() {
+            if self.done {
+                return Ok(0);
+            }
+            match self.rx.recv() {
+                Ok(Ok(chunk)) => {
+                    // A short chunk is the last one.
+                    self.done = chunk.len() < self.chunk_size;
+                    self.chunk = chunk;
+                    self.pos = 0;
+                }
+                Ok(Err(e)) => {
+                    self.done = true;
+                    return Err(e);
+                }
+                Err(_) => {
+                    self.done = true;
+                    return Err(io::Error::new(
+                        io::ErrorKind::Other,
+                        "read-ahead thread terminated unexpectedly",
+                    ));
+                }
+            }
+        }
+        let n = std::cmp::min(buf.len(), self.chunk.len() - self.pos);
+        buf[..n].copy_from_slice(&self.chunk[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
@@ -0,0 +1,183 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Re-encryption of data from one keyset to another, for key rotation and migration.
+
+use std::{
+    cell::Cell,
+    io::{Read, Write},
+    rc::Rc,
+};
+use tink_core::{keyset::Handle, utils::wrap_err, Aead, Primitive, StreamingAead, TinkError};
+
+/// Default size of the chunks of plaintext that are passed between the old and new primitives.
+pub const DEFAULT_REENCRYPT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Progress of a re-encryption, as reported after each chunk of data.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReencryptProgress {
+    /// Number of ciphertext bytes read from the input so far.
+    pub ciphertext_bytes_read: u64,
+    /// Number of plaintext bytes that have been re-encrypted so far.
+    pub plaintext_bytes: u64,
+}
+
+/// The primitive for one side of a re-encryption.
+enum Scheme {
+    Aead(Box<dyn Aead>),
+    Streaming(Box<dyn StreamingAead>),
+}
+
+impl Scheme {
+    /// Build the primitive for a keyset handle, according to the type of its primary key.
+    fn new(h: &Handle) -> Result<Scheme, TinkError> {
+        let ps = h.primitives()?;
+        match ps.primary.map(|e| e.primitive) {
+            Some(Primitive::Aead(_)) => Ok(Scheme::Aead(tink_aead::new(h)?)),
+            Some(Primitive::StreamingAead(_)) => Ok(Scheme::Streaming(crate::new(h)?)),
+            Some(_) => Err("not an AEAD or streaming AEAD keyset".into()),
+            None => Err("no primary key".into()),
+        }
+    }
+}
+
+/// `Reencrypter` decrypts data encrypted under an old keyset and encrypts it under a new keyset,
+/// with the same associated data.  Each keyset may hold either [`Aead`] or [`StreamingAead`]
+/// keys.
+///
+/// When both keysets are for streaming AEAD, data is processed in bounded memory.  An [`Aead`]
+/// ciphertext can only be processed as a whole, so the complete ciphertext (for the old keyset)
+/// or plaintext (for the new keyset) is held in memory.
+pub struct Reencrypter {
+    old: Scheme,
+    new: Scheme,
+    chunk_size: usize,
+}
+
+impl Reencrypter {
+    /// Create a re-encrypter from `old` to `new`.  The `old` keyset handle only needs to permit
+    /// decryption.
+    pub fn new(old: &Handle, new: &Handle) -> Result<Reencrypter, TinkError> {
+        Ok(Reencrypter {
+            old: Scheme::new(old).map_err(|e| wrap_err("Reencrypter: invalid old keyset", e))?,
+            new: Scheme::new(new).map_err(|e| wrap_err("Reencrypter: invalid new keyset", e))?,
+            chunk_size: DEFAULT_REENCRYPT_CHUNK_SIZE,
+        })
+    }
+
+    /// Set the size of the chunks of plaintext passed between the primitives, which is also the
+    /// granularity of progress reports.
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = std::cmp::max(chunk_size, 1);
+        self
+    }
+
+    /// Read ciphertext for the old keyset from `r`, and write the corresponding ciphertext for the
+    /// new keyset to `w`, using `aad` as associated data for both.  The `progress` callback is
+    /// invoked after each chunk of plaintext has been re-encrypted.
+    ///
+    /// On error, the data written to `w` so far is incomplete and must be discarded.
+    pub fn reencrypt(
+        &self,
+        r: Box<dyn Read>,
+        mut w: Box<dyn Write>,
+        aad: &[u8],
+        progress: &mut dyn FnMut(&ReencryptProgress),
+    ) -> Result<ReencryptProgress, TinkError> {
+        let bytes_read = Rc::new(Cell::new(0));
+        let mut r = Box::new(CountingReader {
+            inner: r,
+            count: bytes_read.clone(),
+        });
+        let mut src: Box<dyn Read> = match &self.old {
+            Scheme::Aead(a) => {
+                let mut ct = Vec::new();
+                r.read_to_end(&mut ct)
+                    .map_err(|e| wrap_err("Reencrypter: read failed", e))?;
+                Box::new(std::io::Cursor::new(a.decrypt(&ct, aad)?))
+            }
+            Scheme::Streaming(s) => s.new_decrypting_reader(r, aad)?,
+        };
+
+        let mut stats = ReencryptProgress::default();
+        let mut buf = vec![0; self.chunk_size];
+        match &self.new {
+            Scheme::Aead(a) => {
+                let mut pt = Vec::new();
+                while let Some(chunk) = next_chunk(&mut src, &mut buf)? {
+                    pt.extend_from_slice(chunk);
+                    stats.record(chunk.len(), &bytes_read, progress);
+                }
+                let ct = a.encrypt(&pt, aad)?;
+                w.write_all(&ct)
+                    .and_then(|_| w.flush())
+                    .map_err(|e| wrap_err("Reencrypter: write failed", e))?;
+            }
+            Scheme::Streaming(s) => {
+                let mut ew = s.new_encrypting_writer(w, aad)?;
+                while let Some(chunk) = next_chunk(&mut src, &mut buf)? {
+                    ew.write_all(chunk)
+                        .map_err(|e| wrap_err("Reencrypter: write failed", e))?;
+                    stats.record(chunk.len(), &bytes_read, progress);
+                }
+                ew.close()?;
+            }
+        }
+        Ok(stats)
+    }
+}
+
+impl ReencryptProgress {
+    fn record(
+        &mut self,
+        len: usize,
+        bytes_read: &Cell<u64>,
+        progress: &mut dyn FnMut(&ReencryptProgress),
+    ) {
+        self.plaintext_bytes += len as u64;
+        self.ciphertext_bytes_read = bytes_read.get();
+        progress(self);
+    }
+}
+
+/// Read the next chunk of plaintext into `buf`, returning `None` at the end of the stream.
+fn next_chunk<'a>(
+    src: &mut Box<dyn Read>,
+    buf: &'a mut [u8],
+) -> Result<Option<&'a [u8]>, TinkError> {
+    loop {
+        match src.read(buf) {
+            Ok(0) => return Ok(None),
+            Ok(n) => return Ok(Some(&buf[..n])),
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(wrap_err("Reencrypter: decryption failed", e)),
+        }
+    }
+}
+
+/// Reader wrapper that counts the bytes read from the underlying reader.
+struct CountingReader {
+    inner: Box<dyn Read>,
+    count: Rc<Cell<u64>>,
+}
+
+impl Read for CountingReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count.set(self.count.get() + n as u64);
+        Ok(n)
+    }
+}
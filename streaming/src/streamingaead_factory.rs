@@ -47,6 +47,11 @@ pub(crate) struct WrappedStreamingAead {
 
 impl WrappedStreamingAead {
     fn new(ps: tink_core::primitiveset::PrimitiveSet) -> Result<WrappedStreamingAead, TinkError> {
+        if !ps.usage.allows_encrypt() && !ps.usage.allows_decrypt() {
+            return Err(
+                "streaming_aead::factory: keyset usage does not permit streaming AEAD".into(),
+            );
+        }
         let entry = match &ps.primary {
             None => return Err("streaming_aead::factory: no primary primitive".into()),
             Some(p) => p,
@@ -75,11 +80,7 @@ impl tink_core::StreamingAead for WrappedStreamingAead {
         w: Box<dyn std::io::Write>,
         aad: &[u8],
     ) -> Result<Box<dyn tink_core::EncryptingWrite>, TinkError> {
-        let entry = match &self.ps.primary {
-            None => return Err("streaming_aead::factory: no primary primitive".into()),
-            Some(p) => p,
-        };
-        entry.primitive.new_encrypting_writer(w, aad)
+        self.new_encrypting_writer_with_segment_aad(w, aad, &[])
     }
 
     /// Return a wrapper around an underlying `std::io::Read`, such that any read-operation
@@ -90,6 +91,43 @@ impl tink_core::StreamingAead for WrappedStreamingAead {
         r: Box<dyn std::io::Read>,
         aad: &[u8],
     ) -> Result<Box<dyn std::io::Read>, TinkError> {
-        Ok(Box::new(crate::DecryptReader::new(self.clone(), r, aad)))
+        self.new_decrypting_reader_with_segment_aad(r, aad, &[])
+    }
+
+    fn new_encrypting_writer_with_segment_aad(
+        &self,
+        w: Box<dyn std::io::Write>,
+        aad: &[u8],
+        segment_aad: &[u8],
+    ) -> Result<Box<dyn tink_core::EncryptingWrite>, TinkError> {
+        if !self.ps.usage.allows_encrypt() {
+            return Err("streaming_aead::factory: encryption not permitted".into());
+        }
+        self.ps.check_primary_validity()?;
+        self.ps.record_primary_usage(0)?;
+        let entry = match &self.ps.primary {
+            None => return Err("streaming_aead::factory: no primary primitive".into()),
+            Some(p) => p,
+        };
+        entry
+            .primitive
+            .new_encrypting_writer_with_segment_aad(w, aad, segment_aad)
+    }
+
+    fn new_decrypting_reader_with_segment_aad(
+        &self,
+        r: Box<dyn std::io::Read>,
+        aad: &[u8],
+        segment_aad: &[u8],
+    ) -> Result<Box<dyn std::io::Read>, TinkError> {
+        if !self.ps.usage.allows_decrypt() {
+            return Err("streaming_aead::factory: decryption not permitted".into());
+        }
+        Ok(Box::new(crate::DecryptReader::new(
+            self.clone(),
+            r,
+            aad,
+            segment_aad,
+        )))
     }
 }
@@ -17,6 +17,7 @@
 //! This module contains pre-generated [`KeyTemplate`]s for streaming AEAD keys. One can use these
 //! templates to generate new Keysets.
 
+use tink_core::TinkError;
 use tink_proto::{prost::Message, HashType, KeyTemplate, OutputPrefixType};
 
 /// Return a [`KeyTemplate`] that generates an AES-GCM key with the following parameters:
@@ -107,26 +108,15 @@ fn new_aes_gcm_hkdf_key_template(
     derived_key_size: u32,
     ciphertext_segment_size: u32,
 ) -> KeyTemplate {
-    let format = tink_proto::AesGcmHkdfStreamingKeyFormat {
-        version: crate::AES_GCM_HKDF_KEY_VERSION,
-        key_size: main_key_size,
-        params: Some(tink_proto::AesGcmHkdfStreamingParams {
-            ciphertext_segment_size,
-            derived_key_size,
-            hkdf_hash_type: hkdf_hash_type as i32,
-        }),
-    };
-    let mut serialized_format = Vec::new();
-    format.encode(&mut serialized_format).unwrap(); // safe: proto-encode
-    KeyTemplate {
-        type_url: crate::AES_GCM_HKDF_TYPE_URL.to_string(),
-        value: serialized_format,
-        output_prefix_type: OutputPrefixType::Raw as i32,
-    }
+    AesGcmHkdfTemplateBuilder::new()
+        .key_size(main_key_size)
+        .hkdf_hash(hkdf_hash_type)
+        .derived_key_size(derived_key_size)
+        .ciphertext_segment_size(ciphertext_segment_size)
+        .build()
+        .unwrap() // safe: valid parameters
 }
 
-/// Create a KeyTemplate containing a [`tink_proto::AesCtrHmacStreamingKeyFormat`] with the
-/// specified parameters.
 fn new_aes_ctr_hmac_key_template(
     main_key_size: u32,
     hkdf_hash_type: HashType,
@@ -135,24 +125,180 @@ fn new_aes_ctr_hmac_key_template(
     tag_size: u32,
     ciphertext_segment_size: u32,
 ) -> KeyTemplate {
-    let format = tink_proto::AesCtrHmacStreamingKeyFormat {
-        version: crate::AES_CTR_HMAC_KEY_VERSION,
-        key_size: main_key_size,
-        params: Some(tink_proto::AesCtrHmacStreamingParams {
-            ciphertext_segment_size,
-            derived_key_size,
-            hkdf_hash_type: hkdf_hash_type as i32,
-            hmac_params: Some(tink_proto::HmacParams {
-                hash: tag_alg as i32,
-                tag_size,
-            }),
-        }),
-    };
+    AesCtrHmacTemplateBuilder::new()
+        .key_size(main_key_size)
+        .hkdf_hash(hkdf_hash_type)
+        .derived_key_size(derived_key_size)
+        .tag_hash(tag_alg)
+        .tag_size(tag_size)
+        .ciphertext_segment_size(ciphertext_segment_size)
+        .build()
+        .unwrap() // safe: valid parameters
+}
+
+/// Serialize a key format into a [`KeyTemplate`].  Streaming AEAD keys always use RAW output
+/// prefix.
+fn serialize_template<T: Message>(type_url: &str, format: &T) -> KeyTemplate {
     let mut serialized_format = Vec::new();
     format.encode(&mut serialized_format).unwrap(); // safe: proto-encode
     KeyTemplate {
-        type_url: crate::AES_CTR_HMAC_TYPE_URL.to_string(),
+        type_url: type_url.to_string(),
         value: serialized_format,
         output_prefix_type: OutputPrefixType::Raw as i32,
     }
 }
+
+/// Builder for AES-GCM-HKDF streaming [`KeyTemplate`]s, whose parameters are checked when the
+/// template is built.  Defaults to the parameters of [`aes128_gcm_hkdf_4kb_key_template`].
+#[derive(Clone, Copy, Debug)]
+pub struct AesGcmHkdfTemplateBuilder {
+    key_size: u32,
+    hkdf_hash: HashType,
+    derived_key_size: u32,
+    ciphertext_segment_size: u32,
+}
+
+impl Default for AesGcmHkdfTemplateBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AesGcmHkdfTemplateBuilder {
+    /// Create a builder with the default parameters.
+    pub fn new() -> Self {
+        Self {
+            key_size: 16,
+            hkdf_hash: HashType::Sha256,
+            derived_key_size: 16,
+            ciphertext_segment_size: 4096,
+        }
+    }
+
+    /// Set the size of the main key in bytes (16 or 32).
+    pub fn key_size(mut self, key_size: u32) -> Self {
+        self.key_size = key_size;
+        self
+    }
+
+    /// Set the hash function used by HKDF to derive segment keys.
+    pub fn hkdf_hash(mut self, hkdf_hash: HashType) -> Self {
+        self.hkdf_hash = hkdf_hash;
+        self
+    }
+
+    /// Set the size of the derived AES keys in bytes (16 or 32).
+    pub fn derived_key_size(mut self, derived_key_size: u32) -> Self {
+        self.derived_key_size = derived_key_size;
+        self
+    }
+
+    /// Set the size of ciphertext segments in bytes.
+    pub fn ciphertext_segment_size(mut self, ciphertext_segment_size: u32) -> Self {
+        self.ciphertext_segment_size = ciphertext_segment_size;
+        self
+    }
+
+    /// Build the [`KeyTemplate`], or return an error if the parameters are invalid.
+    pub fn build(&self) -> Result<KeyTemplate, TinkError> {
+        let format = tink_proto::AesGcmHkdfStreamingKeyFormat {
+            version: crate::AES_GCM_HKDF_KEY_VERSION,
+            key_size: self.key_size,
+            params: Some(tink_proto::AesGcmHkdfStreamingParams {
+                ciphertext_segment_size: self.ciphertext_segment_size,
+                derived_key_size: self.derived_key_size,
+                hkdf_hash_type: self.hkdf_hash as i32,
+            }),
+        };
+        crate::aes_gcm_hkdf_key_manager::validate_key_format(&format)?;
+        Ok(serialize_template(crate::AES_GCM_HKDF_TYPE_URL, &format))
+    }
+}
+
+/// Builder for AES-CTR-HMAC streaming [`KeyTemplate`]s, whose parameters are checked when the
+/// template is built.  Defaults to the parameters of
+/// [`aes128_ctr_hmac_sha256_segment_4kb_key_template`].
+#[derive(Clone, Copy, Debug)]
+pub struct AesCtrHmacTemplateBuilder {
+    key_size: u32,
+    hkdf_hash: HashType,
+    derived_key_size: u32,
+    tag_hash: HashType,
+    tag_size: u32,
+    ciphertext_segment_size: u32,
+}
+
+impl Default for AesCtrHmacTemplateBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AesCtrHmacTemplateBuilder {
+    /// Create a builder with the default parameters.
+    pub fn new() -> Self {
+        Self {
+            key_size: 16,
+            hkdf_hash: HashType::Sha256,
+            derived_key_size: 16,
+            tag_hash: HashType::Sha256,
+            tag_size: 32,
+            ciphertext_segment_size: 4096,
+        }
+    }
+
+    /// Set the size of the main key in bytes (16 or 32).
+    pub fn key_size(mut self, key_size: u32) -> Self {
+        self.key_size = key_size;
+        self
+    }
+
+    /// Set the hash function used by HKDF to derive segment keys.
+    pub fn hkdf_hash(mut self, hkdf_hash: HashType) -> Self {
+        self.hkdf_hash = hkdf_hash;
+        self
+    }
+
+    /// Set the size of the derived AES keys in bytes (16 or 32).
+    pub fn derived_key_size(mut self, derived_key_size: u32) -> Self {
+        self.derived_key_size = derived_key_size;
+        self
+    }
+
+    /// Set the hash function used for the HMAC tags.
+    pub fn tag_hash(mut self, tag_hash: HashType) -> Self {
+        self.tag_hash = tag_hash;
+        self
+    }
+
+    /// Set the HMAC tag size in bytes (at least 10, and at most the hash output size).
+    pub fn tag_size(mut self, tag_size: u32) -> Self {
+        self.tag_size = tag_size;
+        self
+    }
+
+    /// Set the size of ciphertext segments in bytes.
+    pub fn ciphertext_segment_size(mut self, ciphertext_segment_size: u32) -> Self {
+        self.ciphertext_segment_size = ciphertext_segment_size;
+        self
+    }
+
+    /// Build the [`KeyTemplate`], or return an error if the parameters are invalid.
+    pub fn build(&self) -> Result<KeyTemplate, TinkError> {
+        let format = tink_proto::AesCtrHmacStreamingKeyFormat {
+            version: crate::AES_CTR_HMAC_KEY_VERSION,
+            key_size: self.key_size,
+            params: Some(tink_proto::AesCtrHmacStreamingParams {
+                ciphertext_segment_size: self.ciphertext_segment_size,
+                derived_key_size: self.derived_key_size,
+                hkdf_hash_type: self.hkdf_hash as i32,
+                hmac_params: Some(tink_proto::HmacParams {
+                    hash: self.tag_hash as i32,
+                    tag_size: self.tag_size,
+                }),
+            }),
+        };
+        crate::aes_ctr_hmac_key_manager::validate_key_format(&format)?;
+        Ok(serialize_template(crate::AES_CTR_HMAC_TYPE_URL, &format))
+    }
+}
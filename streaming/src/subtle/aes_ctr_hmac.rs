@@ -120,24 +120,67 @@ impl AesCtrHmac {
         header_length_for(self.aes_variant.key_size())
     }
 
+    /// Return the layout of ciphertexts produced by this primitive, from which ciphertext and
+    /// plaintext sizes can be computed.
+    pub fn segment_layout(&self) -> super::SegmentLayout {
+        super::SegmentLayout {
+            header_length: self.header_length(),
+            ciphertext_segment_size: self.ciphertext_segment_size,
+            first_segment_offset: self.first_ciphertext_segment_offset - self.header_length(),
+            tag_size: self.tag_size_in_bytes,
+        }
+    }
+
     /// Return a key derived from the main key using` salt` and `aad` as parameters.
     fn derive_key_material(&self, salt: &[u8], aad: &[u8]) -> Result<Vec<u8>, TinkError> {
         let key_material_size = self.aes_variant.key_size() + AES_CTR_HMAC_KEY_SIZE_IN_BYTES;
         tink_core::subtle::compute_hkdf(self.hkdf_alg, &self.main_key, salt, aad, key_material_size)
     }
-}
 
-impl tink_core::StreamingAead for AesCtrHmac {
-    /// Return a wrapper around an underlying [`std::io.Write`], such that
-    /// any write-operation via the wrapper results in AEAD-encryption of the
-    /// written data, using `aad` as associated authenticated data. The associated
-    /// data is not included in the ciphertext and has to be passed in as parameter
-    /// for decryption.
-    fn new_encrypting_writer(
+    /// Return working buffers that are large enough for segments of this primitive, for use
+    /// with [`new_encrypting_writer_with_buffers`](Self::new_encrypting_writer_with_buffers) and
+    /// [`new_decrypting_reader_with_buffers`](Self::new_decrypting_reader_with_buffers).
+    pub fn segment_buffers(&self) -> noncebased::SegmentBuffers {
+        noncebased::SegmentBuffers::new(self.ciphertext_segment_size)
+    }
+
+    /// As for [`new_encrypting_writer`](tink_core::StreamingAead::new_encrypting_writer), but
+    /// using caller-provided `buffers` (which must have the capacity of those returned by
+    /// [`segment_buffers`](Self::segment_buffers)) as the writer's working storage.  The
+    /// buffers can be recovered for re-use with [`noncebased::Writer::into_buffers`].
+    pub fn new_encrypting_writer_with_buffers(
+        &self,
+        w: Box<dyn std::io::Write>,
+        aad: &[u8],
+        buffers: noncebased::SegmentBuffers,
+    ) -> Result<noncebased::Writer, TinkError> {
+        buffers.check_capacity(self.ciphertext_segment_size)?;
+        self.encrypting_writer(w, aad, &[], buffers)
+    }
+
+    /// As for [`new_decrypting_reader`](tink_core::StreamingAead::new_decrypting_reader), but
+    /// using caller-provided `buffers` (which must have the capacity of those returned by
+    /// [`segment_buffers`](Self::segment_buffers)) as the reader's working storage.  The
+    /// buffers can be recovered for re-use with [`noncebased::Reader::into_buffers`].
+    pub fn new_decrypting_reader_with_buffers(
+        &self,
+        r: Box<dyn std::io::Read>,
+        aad: &[u8],
+        buffers: noncebased::SegmentBuffers,
+    ) -> Result<noncebased::Reader, TinkError> {
+        buffers.check_capacity(self.ciphertext_segment_size)?;
+        self.decrypting_reader(r, aad, &[], buffers)
+    }
+
+    /// Build the [`noncebased::Writer`] for a new ciphertext, using `buffers` as its working
+    /// storage.
+    fn encrypting_writer(
         &self,
         mut w: Box<dyn std::io::Write>,
         aad: &[u8],
-    ) -> Result<Box<dyn tink_core::EncryptingWrite>, TinkError> {
+        segment_aad: &[u8],
+        buffers: noncebased::SegmentBuffers,
+    ) -> Result<noncebased::Writer, TinkError> {
         let key_size = self.aes_variant.key_size();
         let salt = get_random_bytes(key_size);
         let nonce_prefix = get_random_bytes(AES_CTR_HMAC_NONCE_PREFIX_SIZE_IN_BYTES);
@@ -169,29 +212,33 @@ impl tink_core::StreamingAead for AesCtrHmac {
         header.extend_from_slice(&nonce_prefix);
         w.write(&header).map_err(|e| wrap_err("write failed", e))?;
 
-        let nw = noncebased::Writer::new(noncebased::WriterParams {
-            w,
-            segment_encrypter: Box::new(AesCtrHmacSegmentEncrypter {
-                aes_key,
-                hmac,
-                tag_size_in_bytes: self.tag_size_in_bytes,
-            }),
-            nonce_size: AES_CTR_HMAC_NONCE_SIZE_IN_BYTES,
-            nonce_prefix,
-            plaintext_segment_size: self.plaintext_segment_size,
-            first_ciphertext_segment_offset: self.first_ciphertext_segment_offset,
-        })?;
-        Ok(Box::new(nw))
+        noncebased::Writer::new_with_buffers(
+            noncebased::WriterParams {
+                w,
+                segment_encrypter: Box::new(AesCtrHmacSegmentEncrypter {
+                    aes_key,
+                    hmac,
+                    tag_size_in_bytes: self.tag_size_in_bytes,
+                    segment_aad: segment_aad.to_vec(),
+                }),
+                nonce_size: AES_CTR_HMAC_NONCE_SIZE_IN_BYTES,
+                nonce_prefix,
+                plaintext_segment_size: self.plaintext_segment_size,
+                first_ciphertext_segment_offset: self.first_ciphertext_segment_offset,
+            },
+            buffers,
+        )
     }
 
-    /// Return a wrapper around an underlying [`std::io::Read`], such that
-    /// any read-operation via the wrapper results in AEAD-decryption of the
-    /// underlying ciphertext, using aad as associated authenticated data.
-    fn new_decrypting_reader(
+    /// Build the [`noncebased::Reader`] for an existing ciphertext, using `buffers` as its
+    /// working storage.
+    fn decrypting_reader(
         &self,
         mut r: Box<dyn std::io::Read>,
         aad: &[u8],
-    ) -> Result<Box<dyn std::io::Read>, TinkError> {
+        segment_aad: &[u8],
+        buffers: noncebased::SegmentBuffers,
+    ) -> Result<noncebased::Reader, TinkError> {
         let mut hlen = vec![0; 1];
         r.read_exact(&mut hlen)
             .map_err(|e| wrap_err("failed to reader header len", e))?;
@@ -225,21 +272,90 @@ impl tink_core::StreamingAead for AesCtrHmac {
         let hmac_key = &km[self.aes_variant.key_size()..];
         let hmac = tink_mac::subtle::Hmac::new(self.tag_alg, hmac_key, self.tag_size_in_bytes)?;
 
-        let nr = noncebased::Reader::new(noncebased::ReaderParams {
-            r,
-            segment_decrypter: Box::new(AesCtrHmacSegmentDecrypter {
-                aes_key,
-                hmac,
-                tag_size_in_bytes: self.tag_size_in_bytes,
-            }),
-            nonce_size: AES_CTR_HMAC_NONCE_SIZE_IN_BYTES,
-            nonce_prefix,
-            ciphertext_segment_size: self.ciphertext_segment_size,
-            first_ciphertext_segment_offset: self.first_ciphertext_segment_offset,
-        })?;
+        noncebased::Reader::new_with_buffers(
+            noncebased::ReaderParams {
+                r,
+                segment_decrypter: Box::new(AesCtrHmacSegmentDecrypter {
+                    aes_key,
+                    hmac,
+                    tag_size_in_bytes: self.tag_size_in_bytes,
+                    segment_aad: segment_aad.to_vec(),
+                }),
+                nonce_size: AES_CTR_HMAC_NONCE_SIZE_IN_BYTES,
+                nonce_prefix,
+                ciphertext_segment_size: self.ciphertext_segment_size,
+                first_ciphertext_segment_offset: self.first_ciphertext_segment_offset,
+            },
+            buffers,
+        )
+    }
+}
 
-        Ok(Box::new(nr))
+impl tink_core::StreamingAead for AesCtrHmac {
+    /// Return a wrapper around an underlying [`std::io.Write`], such that
+    /// any write-operation via the wrapper results in AEAD-encryption of the
+    /// written data, using `aad` as associated authenticated data. The associated
+    /// data is not included in the ciphertext and has to be passed in as parameter
+    /// for decryption.
+    fn new_encrypting_writer(
+        &self,
+        w: Box<dyn std::io::Write>,
+        aad: &[u8],
+    ) -> Result<Box<dyn tink_core::EncryptingWrite>, TinkError> {
+        self.new_encrypting_writer_with_segment_aad(w, aad, &[])
+    }
+
+    /// Return a wrapper around an underlying [`std::io::Read`], such that
+    /// any read-operation via the wrapper results in AEAD-decryption of the
+    /// underlying ciphertext, using aad as associated authenticated data.
+    fn new_decrypting_reader(
+        &self,
+        r: Box<dyn std::io::Read>,
+        aad: &[u8],
+    ) -> Result<Box<dyn std::io::Read>, TinkError> {
+        self.new_decrypting_reader_with_segment_aad(r, aad, &[])
+    }
+
+    /// As for [`new_encrypting_writer`](Self::new_encrypting_writer), but with `segment_aad`
+    /// included in the HMAC tag of every segment.
+    fn new_encrypting_writer_with_segment_aad(
+        &self,
+        w: Box<dyn std::io::Write>,
+        aad: &[u8],
+        segment_aad: &[u8],
+    ) -> Result<Box<dyn tink_core::EncryptingWrite>, TinkError> {
+        self.encrypting_writer(w, aad, segment_aad, noncebased::SegmentBuffers::default())
+            .map(|w| Box::new(w) as Box<dyn tink_core::EncryptingWrite>)
+    }
+
+    /// As for [`new_decrypting_reader`](Self::new_decrypting_reader), but with `segment_aad`
+    /// included in the HMAC tag of every segment.
+    fn new_decrypting_reader_with_segment_aad(
+        &self,
+        r: Box<dyn std::io::Read>,
+        aad: &[u8],
+        segment_aad: &[u8],
+    ) -> Result<Box<dyn std::io::Read>, TinkError> {
+        self.decrypting_reader(r, aad, segment_aad, noncebased::SegmentBuffers::default())
+            .map(|r| Box::new(r) as Box<dyn std::io::Read>)
+    }
+}
+
+/// Build the input to the HMAC tag for a segment:
+///
+///   nonce || ciphertext [ || segment_aad || segment_aad_length ]
+///
+/// where the trailing parts are only present for a non-empty `segment_aad`, and
+/// `segment_aad_length` is an 8-byte big-endian length.
+fn segment_mac_input(nonce: &[u8], ciphertext: &[u8], segment_aad: &[u8]) -> Vec<u8> {
+    let mut mac_input = Vec::with_capacity(nonce.len() + ciphertext.len() + segment_aad.len() + 8);
+    mac_input.extend_from_slice(nonce);
+    mac_input.extend_from_slice(ciphertext);
+    if !segment_aad.is_empty() {
+        mac_input.extend_from_slice(segment_aad);
+        mac_input.extend_from_slice(&(segment_aad.len() as u64).to_be_bytes());
     }
+    mac_input
 }
 
 /// A [`noncebased::SegmentEncrypter`] based on AES-CTR-HMAC.
@@ -247,12 +363,12 @@ struct AesCtrHmacSegmentEncrypter {
     aes_key: AesCtrKeyVariant,
     hmac: tink_mac::subtle::Hmac,
     tag_size_in_bytes: usize,
+    segment_aad: Vec<u8>,
 }
 
 impl noncebased::SegmentEncrypter for AesCtrHmacSegmentEncrypter {
     fn encrypt_segment(&self, segment: &[u8], nonce: &[u8]) -> Result<Vec<u8>, TinkError> {
         let s_len = segment.len();
-        let n_len = nonce.len();
         let ct_len = s_len + self.tag_size_in_bytes;
         let mut ciphertext = vec![0; ct_len];
 
@@ -270,9 +386,7 @@ impl noncebased::SegmentEncrypter for AesCtrHmacSegmentEncrypter {
             }
         }
 
-        let mut mac_input = Vec::with_capacity(n_len + s_len);
-        mac_input.extend_from_slice(nonce);
-        mac_input.extend_from_slice(&ciphertext[..s_len]);
+        let mac_input = segment_mac_input(nonce, &ciphertext[..s_len], &self.segment_aad);
         let tag = self.hmac.compute_mac(&mac_input)?;
         ciphertext[s_len..].copy_from_slice(&tag);
 
@@ -285,21 +399,19 @@ struct AesCtrHmacSegmentDecrypter {
     aes_key: AesCtrKeyVariant,
     hmac: tink_mac::subtle::Hmac,
     tag_size_in_bytes: usize,
+    segment_aad: Vec<u8>,
 }
 
 impl noncebased::SegmentDecrypter for AesCtrHmacSegmentDecrypter {
     fn decrypt_segment(&self, segment: &[u8], nonce: &[u8]) -> Result<Vec<u8>, TinkError> {
         let s_len = segment.len();
-        let n_len = nonce.len();
         if self.tag_size_in_bytes > s_len {
             return Err("segment too short".into());
         }
         let tag_start = s_len - self.tag_size_in_bytes;
         let tag = &segment[tag_start..];
 
-        let mut mac_input = Vec::with_capacity(n_len + s_len);
-        mac_input.extend_from_slice(nonce);
-        mac_input.extend_from_slice(&segment[..tag_start]);
+        let mac_input = segment_mac_input(nonce, &segment[..tag_start], &self.segment_aad);
         if self.hmac.verify_mac(tag, &mac_input).is_err() {
             return Err("tag mismatch".into());
         }
@@ -18,7 +18,7 @@
 
 use super::{noncebased, AesVariant};
 use aes_gcm::{
-    aead::{generic_array::GenericArray, Aead},
+    aead::{generic_array::GenericArray, Aead, AeadInPlace, Payload},
     KeyInit,
 };
 use std::convert::TryInto;
@@ -107,6 +107,17 @@ impl AesGcmHkdf {
         header_length_for(self.aes_variant.key_size())
     }
 
+    /// Return the layout of ciphertexts produced by this primitive, from which ciphertext and
+    /// plaintext sizes can be computed.
+    pub fn segment_layout(&self) -> super::SegmentLayout {
+        super::SegmentLayout {
+            header_length: self.header_length(),
+            ciphertext_segment_size: self.ciphertext_segment_size,
+            first_segment_offset: self.first_ciphertext_segment_offset - self.header_length(),
+            tag_size: AES_GCM_HKDF_TAG_SIZE_IN_BYTES,
+        }
+    }
+
     /// Return a key derived from the given main key using `salt` and `aad` parameters.
     fn derive_key(&self, salt: &[u8], aad: &[u8]) -> Result<Vec<u8>, TinkError> {
         tink_core::subtle::compute_hkdf(
@@ -117,19 +128,51 @@ impl AesGcmHkdf {
             self.aes_variant.key_size(),
         )
     }
-}
 
-impl tink_core::StreamingAead for AesGcmHkdf {
-    /// Return a wrapper around an underlying [`std::io::Write`], such that
-    /// any write-operation via the wrapper results in AEAD-encryption of the
-    /// written data, using aad as associated authenticated data. The associated
-    /// data is not included in the ciphertext and has to be passed in as parameter
-    /// for decryption.
-    fn new_encrypting_writer(
+    /// Return working buffers that are large enough for segments of this primitive, for use
+    /// with [`new_encrypting_writer_with_buffers`](Self::new_encrypting_writer_with_buffers) and
+    /// [`new_decrypting_reader_with_buffers`](Self::new_decrypting_reader_with_buffers).
+    pub fn segment_buffers(&self) -> noncebased::SegmentBuffers {
+        noncebased::SegmentBuffers::new(self.ciphertext_segment_size)
+    }
+
+    /// As for [`new_encrypting_writer`](tink_core::StreamingAead::new_encrypting_writer), but
+    /// using caller-provided `buffers` (which must have the capacity of those returned by
+    /// [`segment_buffers`](Self::segment_buffers)) as the writer's working storage.  The
+    /// buffers can be recovered for re-use with [`noncebased::Writer::into_buffers`].
+    pub fn new_encrypting_writer_with_buffers(
+        &self,
+        w: Box<dyn std::io::Write>,
+        aad: &[u8],
+        buffers: noncebased::SegmentBuffers,
+    ) -> Result<noncebased::Writer, TinkError> {
+        buffers.check_capacity(self.ciphertext_segment_size)?;
+        self.encrypting_writer(w, aad, &[], buffers)
+    }
+
+    /// As for [`new_decrypting_reader`](tink_core::StreamingAead::new_decrypting_reader), but
+    /// using caller-provided `buffers` (which must have the capacity of those returned by
+    /// [`segment_buffers`](Self::segment_buffers)) as the reader's working storage.  The
+    /// buffers can be recovered for re-use with [`noncebased::Reader::into_buffers`].
+    pub fn new_decrypting_reader_with_buffers(
+        &self,
+        r: Box<dyn std::io::Read>,
+        aad: &[u8],
+        buffers: noncebased::SegmentBuffers,
+    ) -> Result<noncebased::Reader, TinkError> {
+        buffers.check_capacity(self.ciphertext_segment_size)?;
+        self.decrypting_reader(r, aad, &[], buffers)
+    }
+
+    /// Build the [`noncebased::Writer`] for a new ciphertext, using `buffers` as its working
+    /// storage.
+    fn encrypting_writer(
         &self,
         mut w: Box<dyn std::io::Write>,
         aad: &[u8],
-    ) -> Result<Box<dyn tink_core::EncryptingWrite>, TinkError> {
+        segment_aad: &[u8],
+        buffers: noncebased::SegmentBuffers,
+    ) -> Result<noncebased::Writer, TinkError> {
         let salt = get_random_bytes(self.aes_variant.key_size());
         let nonce_prefix = get_random_bytes(AES_GCM_HKDF_NONCE_PREFIX_SIZE_IN_BYTES);
 
@@ -146,26 +189,31 @@ impl tink_core::StreamingAead for AesGcmHkdf {
         header.extend_from_slice(&nonce_prefix);
         w.write(&header).map_err(|e| wrap_err("write failed", e))?;
 
-        let nw = noncebased::Writer::new(noncebased::WriterParams {
-            w,
-            segment_encrypter: Box::new(AesGcmHkdfSegmentEncrypter { cipher_key }),
-            nonce_size: AES_GCM_HKDF_NONCE_SIZE_IN_BYTES,
-            nonce_prefix,
-            plaintext_segment_size: self.plaintext_segment_size,
-            first_ciphertext_segment_offset: self.first_ciphertext_segment_offset,
-        })?;
-
-        Ok(Box::new(nw))
+        noncebased::Writer::new_with_buffers(
+            noncebased::WriterParams {
+                w,
+                segment_encrypter: Box::new(AesGcmHkdfSegmentEncrypter {
+                    cipher_key,
+                    segment_aad: segment_aad.to_vec(),
+                }),
+                nonce_size: AES_GCM_HKDF_NONCE_SIZE_IN_BYTES,
+                nonce_prefix,
+                plaintext_segment_size: self.plaintext_segment_size,
+                first_ciphertext_segment_offset: self.first_ciphertext_segment_offset,
+            },
+            buffers,
+        )
     }
 
-    /// Return a wrapper around an underlying [`std::io::Read`], such that
-    /// any read-operation via the wrapper results in AEAD-decryption of the
-    /// underlying ciphertext, using aad as associated authenticated data.
-    fn new_decrypting_reader(
+    /// Build the [`noncebased::Reader`] for an existing ciphertext, using `buffers` as its
+    /// working storage.
+    fn decrypting_reader(
         &self,
         mut r: Box<dyn std::io::Read>,
         aad: &[u8],
-    ) -> Result<Box<dyn std::io::Read>, TinkError> {
+        segment_aad: &[u8],
+        buffers: noncebased::SegmentBuffers,
+    ) -> Result<noncebased::Reader, TinkError> {
         let mut hlen = vec![0; 1];
         r.read_exact(&mut hlen)
             .map_err(|e| wrap_err("failed to reader header len", e))?;
@@ -184,16 +232,70 @@ impl tink_core::StreamingAead for AesGcmHkdf {
         let dkey = self.derive_key(&salt, aad)?;
         let cipher_key = new_cipher_key(self.aes_variant, &dkey)?;
 
-        let nr = noncebased::Reader::new(noncebased::ReaderParams {
-            r,
-            segment_decrypter: Box::new(AesGcmHkdfSegmentDecrypter { cipher_key }),
-            nonce_size: AES_GCM_HKDF_NONCE_SIZE_IN_BYTES,
-            nonce_prefix,
-            ciphertext_segment_size: self.ciphertext_segment_size,
-            first_ciphertext_segment_offset: self.first_ciphertext_segment_offset,
-        })?;
+        noncebased::Reader::new_with_buffers(
+            noncebased::ReaderParams {
+                r,
+                segment_decrypter: Box::new(AesGcmHkdfSegmentDecrypter {
+                    cipher_key,
+                    segment_aad: segment_aad.to_vec(),
+                }),
+                nonce_size: AES_GCM_HKDF_NONCE_SIZE_IN_BYTES,
+                nonce_prefix,
+                ciphertext_segment_size: self.ciphertext_segment_size,
+                first_ciphertext_segment_offset: self.first_ciphertext_segment_offset,
+            },
+            buffers,
+        )
+    }
+}
+
+impl tink_core::StreamingAead for AesGcmHkdf {
+    /// Return a wrapper around an underlying [`std::io::Write`], such that
+    /// any write-operation via the wrapper results in AEAD-encryption of the
+    /// written data, using aad as associated authenticated data. The associated
+    /// data is not included in the ciphertext and has to be passed in as parameter
+    /// for decryption.
+    fn new_encrypting_writer(
+        &self,
+        w: Box<dyn std::io::Write>,
+        aad: &[u8],
+    ) -> Result<Box<dyn tink_core::EncryptingWrite>, TinkError> {
+        self.new_encrypting_writer_with_segment_aad(w, aad, &[])
+    }
 
-        Ok(Box::new(nr))
+    /// Return a wrapper around an underlying [`std::io::Read`], such that
+    /// any read-operation via the wrapper results in AEAD-decryption of the
+    /// underlying ciphertext, using aad as associated authenticated data.
+    fn new_decrypting_reader(
+        &self,
+        r: Box<dyn std::io::Read>,
+        aad: &[u8],
+    ) -> Result<Box<dyn std::io::Read>, TinkError> {
+        self.new_decrypting_reader_with_segment_aad(r, aad, &[])
+    }
+
+    /// As for [`new_encrypting_writer`](Self::new_encrypting_writer), but with `segment_aad`
+    /// used as the AES-GCM associated data for every segment.
+    fn new_encrypting_writer_with_segment_aad(
+        &self,
+        w: Box<dyn std::io::Write>,
+        aad: &[u8],
+        segment_aad: &[u8],
+    ) -> Result<Box<dyn tink_core::EncryptingWrite>, TinkError> {
+        self.encrypting_writer(w, aad, segment_aad, noncebased::SegmentBuffers::default())
+            .map(|w| Box::new(w) as Box<dyn tink_core::EncryptingWrite>)
+    }
+
+    /// As for [`new_decrypting_reader`](Self::new_decrypting_reader), but with `segment_aad`
+    /// used as the AES-GCM associated data for every segment.
+    fn new_decrypting_reader_with_segment_aad(
+        &self,
+        r: Box<dyn std::io::Read>,
+        aad: &[u8],
+        segment_aad: &[u8],
+    ) -> Result<Box<dyn std::io::Read>, TinkError> {
+        self.decrypting_reader(r, aad, segment_aad, noncebased::SegmentBuffers::default())
+            .map(|r| Box::new(r) as Box<dyn std::io::Read>)
     }
 }
 
@@ -201,10 +303,10 @@ impl tink_core::StreamingAead for AesGcmHkdf {
 fn new_cipher_key(aes_variant: AesVariant, key: &[u8]) -> Result<AesGcmKeyVariant, TinkError> {
     match aes_variant {
         AesVariant::Aes128 => Ok(AesGcmKeyVariant::Aes128(Box::new(aes_gcm::Aes128Gcm::new(
-            GenericArray::from_slice(key),
+            GenericArray::from_slice(key), // safe: HKDF output of variant key size
         )))),
         AesVariant::Aes256 => Ok(AesGcmKeyVariant::Aes256(Box::new(aes_gcm::Aes256Gcm::new(
-            GenericArray::from_slice(key),
+            GenericArray::from_slice(key), // safe: HKDF output of variant key size
         )))),
     }
 }
@@ -212,31 +314,91 @@ fn new_cipher_key(aes_variant: AesVariant, key: &[u8]) -> Result<AesGcmKeyVarian
 /// A [`noncebased::SegmentEncrypter`] based on AES-GCM-HKDF.
 struct AesGcmHkdfSegmentEncrypter {
     cipher_key: AesGcmKeyVariant,
+    segment_aad: Vec<u8>,
 }
 
 impl noncebased::SegmentEncrypter for AesGcmHkdfSegmentEncrypter {
     fn encrypt_segment(&self, segment: &[u8], nonce: &[u8]) -> Result<Vec<u8>, TinkError> {
-        let iv = GenericArray::from_slice(nonce);
+        let iv = GenericArray::from_slice(nonce); // safe: noncebased nonce size
+        let payload = Payload {
+            msg: segment,
+            aad: &self.segment_aad,
+        };
         match &self.cipher_key {
-            AesGcmKeyVariant::Aes128(key) => key.encrypt(iv, segment),
-            AesGcmKeyVariant::Aes256(key) => key.encrypt(iv, segment),
+            AesGcmKeyVariant::Aes128(key) => key.encrypt(iv, payload),
+            AesGcmKeyVariant::Aes256(key) => key.encrypt(iv, payload),
         }
         .map_err(|e| wrap_err("AesGcmHkdf: encryption failed", e))
     }
+
+    fn encrypt_segment_into(
+        &self,
+        segment: &[u8],
+        nonce: &[u8],
+        out: &mut Vec<u8>,
+    ) -> Result<(), TinkError> {
+        let iv = GenericArray::from_slice(nonce); // safe: noncebased nonce size
+        out.clear();
+        out.extend_from_slice(segment);
+        let tag = match &self.cipher_key {
+            AesGcmKeyVariant::Aes128(key) => {
+                key.encrypt_in_place_detached(iv, &self.segment_aad, out)
+            }
+            AesGcmKeyVariant::Aes256(key) => {
+                key.encrypt_in_place_detached(iv, &self.segment_aad, out)
+            }
+        }
+        .map_err(|e| wrap_err("AesGcmHkdf: encryption failed", e))?;
+        out.extend_from_slice(&tag);
+        Ok(())
+    }
 }
 
 /// A [`noncebased::SegmentDecrypter`] based on AES-GCM-HKDF.
 struct AesGcmHkdfSegmentDecrypter {
     cipher_key: AesGcmKeyVariant,
+    segment_aad: Vec<u8>,
 }
 
 impl noncebased::SegmentDecrypter for AesGcmHkdfSegmentDecrypter {
     fn decrypt_segment(&self, segment: &[u8], nonce: &[u8]) -> Result<Vec<u8>, TinkError> {
-        let iv = GenericArray::from_slice(nonce);
+        let iv = GenericArray::from_slice(nonce); // safe: noncebased nonce size
+        let payload = Payload {
+            msg: segment,
+            aad: &self.segment_aad,
+        };
         match &self.cipher_key {
-            AesGcmKeyVariant::Aes128(key) => key.decrypt(iv, segment),
-            AesGcmKeyVariant::Aes256(key) => key.decrypt(iv, segment),
+            AesGcmKeyVariant::Aes128(key) => key.decrypt(iv, payload),
+            AesGcmKeyVariant::Aes256(key) => key.decrypt(iv, payload),
         }
         .map_err(|e| wrap_err("AesGcmHkdf: decryption failed", e))
     }
+
+    fn decrypt_segment_into(
+        &self,
+        segment: &[u8],
+        nonce: &[u8],
+        out: &mut Vec<u8>,
+    ) -> Result<(), TinkError> {
+        out.clear();
+        if segment.len() < AES_GCM_HKDF_TAG_SIZE_IN_BYTES {
+            return Err("AesGcmHkdf: segment too short".into());
+        }
+        let (ct, tag) = segment.split_at(segment.len() - AES_GCM_HKDF_TAG_SIZE_IN_BYTES);
+        let iv = GenericArray::from_slice(nonce); // safe: noncebased nonce size
+        let tag = GenericArray::from_slice(tag); // safe: length split off above
+        out.extend_from_slice(ct);
+        match &self.cipher_key {
+            AesGcmKeyVariant::Aes128(key) => {
+                key.decrypt_in_place_detached(iv, &self.segment_aad, out, tag)
+            }
+            AesGcmKeyVariant::Aes256(key) => {
+                key.decrypt_in_place_detached(iv, &self.segment_aad, out, tag)
+            }
+        }
+        .map_err(|e| {
+            out.clear();
+            wrap_err("AesGcmHkdf: decryption failed", e)
+        })
+    }
 }
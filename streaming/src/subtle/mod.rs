@@ -22,6 +22,8 @@ mod aes_gcm_hkdf;
 pub use aes_gcm_hkdf::*;
 
 pub mod noncebased;
+mod segment_layout;
+pub use segment_layout::*;
 
 /// Supported AES variants.
 #[derive(Clone, Copy)]
@@ -47,12 +47,84 @@
 //!
 //!   ciphertext_segment_size - header_length() - first_ciphertext_segment_offset.
 
-use std::{convert::TryFrom, io};
+use std::{convert::TryFrom, io, mem};
 use tink_core::{utils::wrap_err, EncryptingWrite, TinkError};
 
 /// `SegmentEncrypter` facilitates implementing various streaming AEAD encryption modes.
 pub trait SegmentEncrypter {
     fn encrypt_segment(&self, segment: &[u8], nonce: &[u8]) -> Result<Vec<u8>, TinkError>;
+
+    /// Encrypt `segment` into `out`, replacing its previous contents.  The default
+    /// implementation copies the result of [`encrypt_segment`](Self::encrypt_segment);
+    /// implementations that can encrypt in place should override this so that no heap
+    /// allocation is needed per segment when `out` has sufficient capacity.
+    fn encrypt_segment_into(
+        &self,
+        segment: &[u8],
+        nonce: &[u8],
+        out: &mut Vec<u8>,
+    ) -> Result<(), TinkError> {
+        let ciphertext = self.encrypt_segment(segment, nonce)?;
+        out.clear();
+        out.extend_from_slice(&ciphertext);
+        Ok(())
+    }
+}
+
+/// `SegmentBuffers` holds the working storage for a [`Writer`] or [`Reader`], allowing the caller
+/// to supply (and later recover) the per-stream buffers rather than having them allocated
+/// internally.  Only the capacity of the buffers matters; any existing contents are discarded.
+#[derive(Default)]
+pub struct SegmentBuffers {
+    plaintext: Vec<u8>,
+    ciphertext: Vec<u8>,
+}
+
+impl SegmentBuffers {
+    /// Allocate buffers that are large enough for ciphertext segments of up to
+    /// `ciphertext_segment_size` bytes.
+    pub fn new(ciphertext_segment_size: usize) -> Self {
+        Self::from_vecs(
+            Vec::with_capacity(ciphertext_segment_size),
+            // The [`Reader`] uses an extra byte to detect the last segment.
+            Vec::with_capacity(ciphertext_segment_size + 1),
+        )
+    }
+
+    /// Create a `SegmentBuffers` from caller-allocated vectors.
+    pub fn from_vecs(plaintext: Vec<u8>, ciphertext: Vec<u8>) -> Self {
+        SegmentBuffers {
+            plaintext,
+            ciphertext,
+        }
+    }
+
+    /// Return the underlying `(plaintext, ciphertext)` vectors.
+    pub fn into_vecs(self) -> (Vec<u8>, Vec<u8>) {
+        (self.plaintext, self.ciphertext)
+    }
+
+    /// Check that the buffers can process ciphertext segments of up to
+    /// `ciphertext_segment_size` bytes without being reallocated.
+    pub fn check_capacity(&self, ciphertext_segment_size: usize) -> Result<(), TinkError> {
+        if self.plaintext.capacity() < ciphertext_segment_size {
+            return Err(format!(
+                "plaintext buffer too small: capacity {} < {}",
+                self.plaintext.capacity(),
+                ciphertext_segment_size
+            )
+            .into());
+        }
+        if self.ciphertext.capacity() <= ciphertext_segment_size {
+            return Err(format!(
+                "ciphertext buffer too small: capacity {} < {}",
+                self.ciphertext.capacity(),
+                ciphertext_segment_size + 1
+            )
+            .into());
+        }
+        Ok(())
+    }
 }
 
 /// `Writer` provides a framework for ingesting plaintext data and
@@ -64,13 +136,16 @@ pub struct Writer {
     segment_encrypter: Box<dyn SegmentEncrypter>,
     encrypted_segment_cnt: u64,
     first_ciphertext_segment_offset: usize,
-    nonce_size: usize,
     nonce_prefix: Vec<u8>,
+    /// Buffer holding the nonce for the current segment.
+    nonce: Vec<u8>,
     /// Buffer to hold incomplete segments of plaintext, until they are complete and
     /// ready for encryption.
     plaintext: Vec<u8>,
     /// Next free position in `plaintext`.
     plaintext_pos: usize,
+    /// Buffer to hold the encrypted form of the current segment.
+    ciphertext: Vec<u8>,
     /// A final smaller segment can be written by calling `close()`, but after that
     /// no more data can be written.
     closed: bool,
@@ -105,6 +180,16 @@ pub struct WriterParams {
 impl Writer {
     /// Create a new Writer instance.
     pub fn new(params: WriterParams) -> Result<Writer, TinkError> {
+        Self::new_with_buffers(params, SegmentBuffers::default())
+    }
+
+    /// Create a new Writer instance that uses the given `buffers` for its working storage.  If
+    /// the buffers have sufficient capacity for the segment size, no further heap allocation is
+    /// performed per segment beyond that done by the [`SegmentEncrypter`].
+    pub fn new_with_buffers(
+        params: WriterParams,
+        buffers: SegmentBuffers,
+    ) -> Result<Writer, TinkError> {
         if params.nonce_size - params.nonce_prefix.len() < 5 {
             return Err("nonce size too short".into());
         }
@@ -120,18 +205,35 @@ impl Writer {
             }
             _ => {}
         }
+        let SegmentBuffers {
+            mut plaintext,
+            mut ciphertext,
+        } = buffers;
+        plaintext.clear();
+        plaintext.resize(params.plaintext_segment_size, 0);
+        ciphertext.clear();
         Ok(Writer {
             w: params.w,
             segment_encrypter: params.segment_encrypter,
             encrypted_segment_cnt: 0,
             first_ciphertext_segment_offset: params.first_ciphertext_segment_offset,
-            nonce_size: params.nonce_size,
             nonce_prefix: params.nonce_prefix,
-            plaintext: vec![0; params.plaintext_segment_size],
+            nonce: vec![0; params.nonce_size],
+            plaintext,
             plaintext_pos: 0,
+            ciphertext,
             closed: false,
         })
     }
+
+    /// Close the writer and return its working buffers, for re-use with a subsequent stream.
+    pub fn into_buffers(mut self) -> Result<SegmentBuffers, TinkError> {
+        self.close()?;
+        Ok(SegmentBuffers {
+            plaintext: mem::take(&mut self.plaintext),
+            ciphertext: mem::take(&mut self.ciphertext),
+        })
+    }
 }
 
 impl io::Write for Writer {
@@ -176,18 +278,17 @@ impl io::Write for Writer {
                     ),
                 ));
             }
-            let nonce = generate_segment_nonce(
-                self.nonce_size,
+            fill_segment_nonce(
+                &mut self.nonce,
                 &self.nonce_prefix,
                 self.encrypted_segment_cnt,
                 /* last= */ false,
             )?;
 
-            let ciphertext = self
-                .segment_encrypter
-                .encrypt_segment(&self.plaintext[..pt_lim], &nonce)
+            self.segment_encrypter
+                .encrypt_segment_into(&self.plaintext[..pt_lim], &self.nonce, &mut self.ciphertext)
                 .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("{:?}", e)))?;
-            self.w.write_all(&ciphertext)?;
+            self.w.write_all(&self.ciphertext)?;
 
             // Ready to accumulate next segment.
             self.plaintext_pos = 0;
@@ -209,18 +310,20 @@ impl EncryptingWrite for Writer {
             return Ok(());
         }
 
-        let nonce = generate_segment_nonce(
-            self.nonce_size,
+        fill_segment_nonce(
+            &mut self.nonce,
             &self.nonce_prefix,
             self.encrypted_segment_cnt,
             /* last= */ true,
         )
         .map_err(|e| wrap_err("internal error", e))?;
-        let ciphertext = self
-            .segment_encrypter
-            .encrypt_segment(&self.plaintext[..self.plaintext_pos], &nonce)?;
+        self.segment_encrypter.encrypt_segment_into(
+            &self.plaintext[..self.plaintext_pos],
+            &self.nonce,
+            &mut self.ciphertext,
+        )?;
         self.w
-            .write_all(&ciphertext)
+            .write_all(&self.ciphertext)
             .map_err(|e| wrap_err("write failure", e))?;
 
         self.plaintext_pos = 0;
@@ -240,6 +343,23 @@ impl Drop for Writer {
 /// `SegmentDecrypter` facilitates implementing various streaming AEAD encryption modes.
 pub trait SegmentDecrypter {
     fn decrypt_segment(&self, segment: &[u8], nonce: &[u8]) -> Result<Vec<u8>, TinkError>;
+
+    /// Decrypt `segment` into `out`, replacing its previous contents; on failure `out` is left
+    /// empty.  The default implementation copies the result of
+    /// [`decrypt_segment`](Self::decrypt_segment); implementations that can decrypt in place
+    /// should override this so that no heap allocation is needed per segment when `out` has
+    /// sufficient capacity.
+    fn decrypt_segment_into(
+        &self,
+        segment: &[u8],
+        nonce: &[u8],
+        out: &mut Vec<u8>,
+    ) -> Result<(), TinkError> {
+        out.clear();
+        let plaintext = self.decrypt_segment(segment, nonce)?;
+        out.extend_from_slice(&plaintext);
+        Ok(())
+    }
 }
 
 /// `Reader` facilitates the decryption of ciphertexts created using a [`Writer`].
@@ -252,8 +372,9 @@ pub struct Reader {
     segment_decrypter: Box<dyn SegmentDecrypter>,
     decrypted_segment_cnt: u64,
     first_ciphertext_segment_offset: usize,
-    nonce_size: usize,
     nonce_prefix: Vec<u8>,
+    /// Buffer holding the nonce for the current segment.
+    nonce: Vec<u8>,
     /// `plaintext` holds data that has already been decrypted, and `plaintext_pos`
     /// indicates the part of it that has not yet been returns from a `read` operation.
     plaintext: Vec<u8>,
@@ -295,6 +416,17 @@ pub struct ReaderParams {
 impl Reader {
     /// Create a new `Reader` instance.
     pub fn new(params: ReaderParams) -> Result<Reader, TinkError> {
+        Self::new_with_buffers(params, SegmentBuffers::default())
+    }
+
+    /// Create a new `Reader` instance that uses the given `buffers` for its working storage.  If
+    /// the buffers have sufficient capacity for the segment size (see
+    /// [`SegmentBuffers::check_capacity`]), no further heap allocation is performed per segment
+    /// beyond that done by the [`SegmentDecrypter`].
+    pub fn new_with_buffers(
+        params: ReaderParams,
+        buffers: SegmentBuffers,
+    ) -> Result<Reader, TinkError> {
         if params.nonce_size - params.nonce_prefix.len() < 5 {
             return Err("nonce size too short".into());
         }
@@ -312,23 +444,38 @@ impl Reader {
             }
             _ => {}
         }
+        let SegmentBuffers {
+            mut plaintext,
+            mut ciphertext,
+        } = buffers;
+        plaintext.clear();
+        ciphertext.clear();
+        // Use an extra byte to detect the last segment.
+        ciphertext.resize(params.ciphertext_segment_size + 1, 0);
         Ok(Reader {
             r: params.r,
             segment_decrypter: params.segment_decrypter,
             decrypted_segment_cnt: 0,
             first_ciphertext_segment_offset: params.first_ciphertext_segment_offset,
-            nonce_size: params.nonce_size,
+            nonce: vec![0; params.nonce_size],
             nonce_prefix: params.nonce_prefix,
-            plaintext: vec![],
+            plaintext,
             plaintext_pos: 0,
-            // Allocate an extra byte to detect the last segment.
-            ciphertext: vec![0; params.ciphertext_segment_size + 1],
+            ciphertext,
             // Offset of data in `ciphertext`. Only ever set to:
             //  - 0 (for first segment), or
             //  - 1 (for all subsequent segments).
             ciphertext_pos: 0,
         })
     }
+
+    /// Return the reader's working buffers, for re-use with a subsequent stream.
+    pub fn into_buffers(self) -> SegmentBuffers {
+        SegmentBuffers {
+            plaintext: self.plaintext,
+            ciphertext: self.ciphertext,
+        }
+    }
 }
 
 /// Extension trait for [`std::io::Read`] to support `read_full()` method.
@@ -385,6 +532,7 @@ impl io::Read for Reader {
             return Ok(n);
         }
         // No available plaintext.
+        self.plaintext.clear();
         self.plaintext_pos = 0;
 
         // Read up to a segment's worth of ciphertext.
@@ -419,16 +567,22 @@ impl io::Read for Reader {
         }
 
         // Calculate the expected segment nonce and decrypt a segment.
-        let nonce = generate_segment_nonce(
-            self.nonce_size,
+        fill_segment_nonce(
+            &mut self.nonce,
             &self.nonce_prefix,
             self.decrypted_segment_cnt,
             last_segment,
         )?;
-        self.plaintext = self
-            .segment_decrypter
-            .decrypt_segment(&self.ciphertext[..segment], &nonce)
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("{:?}", e)))?;
+        self.segment_decrypter
+            .decrypt_segment_into(
+                &self.ciphertext[..segment],
+                &self.nonce,
+                &mut self.plaintext,
+            )
+            .map_err(|e| {
+                self.plaintext.clear();
+                io::Error::new(io::ErrorKind::InvalidInput, format!("{:?}", e))
+            })?;
 
         // Copy 1 byte remainder to the beginning of `self.ciphertext`.
         if !last_segment {
@@ -447,7 +601,7 @@ impl io::Read for Reader {
     }
 }
 
-/// Return a nonce for a segment.
+/// Fill `nonce` with the nonce for a segment.
 ///
 /// The format of the nonce is:
 ///
@@ -459,12 +613,12 @@ impl io::Read for Reader {
 ///
 /// last_block is 1 byte which is set to 1 for the last segment and 0
 /// otherwise.
-fn generate_segment_nonce(
-    size: usize,
+fn fill_segment_nonce(
+    nonce: &mut [u8],
     prefix: &[u8],
     segment_num: u64,
     last: bool,
-) -> io::Result<Vec<u8>> {
+) -> io::Result<()> {
     let segment_num = match u32::try_from(segment_num) {
         Ok(v) => v,
         Err(_) => {
@@ -474,13 +628,15 @@ fn generate_segment_nonce(
             ))
         }
     };
-    let mut nonce = vec![0; size];
     nonce[..prefix.len()].copy_from_slice(prefix);
     let mut offset = prefix.len();
     nonce[offset..offset + 4].copy_from_slice(&segment_num.to_be_bytes()[..]);
     offset += 4;
+    for b in &mut nonce[offset..] {
+        *b = 0;
+    }
     if last {
         nonce[offset] = 1;
     }
-    Ok(nonce)
+    Ok(())
 }
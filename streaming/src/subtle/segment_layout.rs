@@ -0,0 +1,143 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use super::{
+    validate_aes_key_size, AES_CTR_HMAC_NONCE_PREFIX_SIZE_IN_BYTES,
+    AES_GCM_HKDF_NONCE_PREFIX_SIZE_IN_BYTES, AES_GCM_HKDF_TAG_SIZE_IN_BYTES,
+};
+use tink_core::TinkError;
+
+/// `SegmentLayout` describes how a segmented streaming ciphertext is laid out, allowing the size
+/// of a ciphertext to be computed from the size of its plaintext (and vice versa) without
+/// encrypting anything.
+///
+/// Ciphertext sizes count the bytes written by an encrypting writer: the header followed by the
+/// encrypted segments.  They do not include the `first_segment_offset` bytes that the caller
+/// places in the stream ahead of the header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SegmentLayout {
+    /// Length of the header, which holds its own length, the salt and the nonce prefix.
+    pub header_length: usize,
+    /// Size of each ciphertext segment, including its tag; the first segment is shorter by the
+    /// header length and `first_segment_offset`, and the last may be shorter.
+    pub ciphertext_segment_size: usize,
+    /// Number of bytes preceding the header in the stream.
+    pub first_segment_offset: usize,
+    /// Size of the tag appended to each segment.
+    pub tag_size: usize,
+}
+
+impl SegmentLayout {
+    /// Return the layout of [`AesGcmHkdf`](super::AesGcmHkdf) ciphertexts with the given
+    /// parameters.
+    pub fn aes_gcm_hkdf(
+        key_size_in_bytes: usize,
+        ciphertext_segment_size: usize,
+        first_segment_offset: usize,
+    ) -> Result<Self, TinkError> {
+        validate_aes_key_size(key_size_in_bytes)?;
+        Self::new(
+            1 + key_size_in_bytes + AES_GCM_HKDF_NONCE_PREFIX_SIZE_IN_BYTES,
+            ciphertext_segment_size,
+            first_segment_offset,
+            AES_GCM_HKDF_TAG_SIZE_IN_BYTES,
+        )
+    }
+
+    /// Return the layout of [`AesCtrHmac`](super::AesCtrHmac) ciphertexts with the given
+    /// parameters.
+    pub fn aes_ctr_hmac(
+        key_size_in_bytes: usize,
+        tag_size_in_bytes: usize,
+        ciphertext_segment_size: usize,
+        first_segment_offset: usize,
+    ) -> Result<Self, TinkError> {
+        validate_aes_key_size(key_size_in_bytes)?;
+        Self::new(
+            1 + key_size_in_bytes + AES_CTR_HMAC_NONCE_PREFIX_SIZE_IN_BYTES,
+            ciphertext_segment_size,
+            first_segment_offset,
+            tag_size_in_bytes,
+        )
+    }
+
+    fn new(
+        header_length: usize,
+        ciphertext_segment_size: usize,
+        first_segment_offset: usize,
+        tag_size: usize,
+    ) -> Result<Self, TinkError> {
+        if ciphertext_segment_size <= first_segment_offset + header_length + tag_size {
+            return Err("ciphertext_segment_size too small".into());
+        }
+        Ok(Self {
+            header_length,
+            ciphertext_segment_size,
+            first_segment_offset,
+            tag_size,
+        })
+    }
+
+    /// Size of the first ciphertext segment, when it is not also the last.
+    fn first_segment_size(&self) -> u64 {
+        (self.ciphertext_segment_size - self.first_segment_offset - self.header_length) as u64
+    }
+
+    /// Return the size of the ciphertext for a plaintext of `plaintext_len` bytes.
+    pub fn ciphertext_size(&self, plaintext_len: u64) -> Result<u64, TinkError> {
+        let tag_size = self.tag_size as u64;
+        let first_plaintext = self.first_segment_size() - tag_size;
+        let plaintext_segment = self.ciphertext_segment_size as u64 - tag_size;
+        // An empty plaintext is encrypted as a single empty segment, and a final segment is only
+        // started once the previous one is full.
+        let segments = if plaintext_len <= first_plaintext {
+            1
+        } else {
+            2 + (plaintext_len - first_plaintext - 1) / plaintext_segment
+        };
+        segments
+            .checked_mul(tag_size)
+            .and_then(|tags| tags.checked_add(plaintext_len))
+            .and_then(|n| n.checked_add(self.header_length as u64))
+            .ok_or_else(|| "plaintext too long".into())
+    }
+
+    /// Return the size of the plaintext of a ciphertext of `ciphertext_len` bytes, or an error if
+    /// no ciphertext has this size.
+    pub fn plaintext_size(&self, ciphertext_len: u64) -> Result<u64, TinkError> {
+        let tag_size = self.tag_size as u64;
+        let segments_len = ciphertext_len
+            .checked_sub(self.header_length as u64)
+            .ok_or_else(|| TinkError::new("ciphertext too short"))?;
+        let first_segment = self.first_segment_size();
+        let segments = if segments_len <= first_segment {
+            if segments_len < tag_size {
+                return Err("ciphertext too short".into());
+            }
+            1
+        } else {
+            let rest = segments_len - first_segment;
+            let segment = self.ciphertext_segment_size as u64;
+            let last = rest % segment;
+            // Only the first segment can hold an empty plaintext.
+            if last != 0 && last <= tag_size {
+                return Err("invalid ciphertext size".into());
+            }
+            2 + (rest - 1) / segment
+        };
+        Ok(segments_len - segments * tag_size)
+    }
+}
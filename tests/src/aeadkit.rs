@@ -0,0 +1,167 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Standard battery of checks for [`tink_core::Aead`] implementations.
+
+use tink_core::{subtle::random::get_random_bytes, utils::wrap_err, Aead, TinkError};
+
+/// Size of plaintext used by [`check_aead_large_plaintext`].
+pub const AEAD_KIT_LARGE_PLAINTEXT_SIZE: usize = 1 << 20;
+
+/// Run the standard battery of AEAD checks against primitives generated from the given key
+/// template.  The key manager for the template must already be registered.
+///
+/// The battery consists of:
+///  - [`check_aead_roundtrip`]
+///  - [`check_aead_empty_inputs`]
+///  - [`check_aead_aad_mismatch`]
+///  - [`check_aead_mutations`]
+///  - [`check_aead_large_plaintext`]
+///  - [`check_aead_cross_key`]
+pub fn check_aead_template(kt: &tink_proto::KeyTemplate) -> Result<(), TinkError> {
+    let a = new_aead(kt)?;
+    check_aead_roundtrip(a.as_ref()).map_err(|e| wrap_err("roundtrip", e))?;
+    check_aead_empty_inputs(a.as_ref()).map_err(|e| wrap_err("empty inputs", e))?;
+    check_aead_aad_mismatch(a.as_ref()).map_err(|e| wrap_err("aad mismatch", e))?;
+    check_aead_mutations(a.as_ref()).map_err(|e| wrap_err("mutations", e))?;
+    check_aead_large_plaintext(a.as_ref()).map_err(|e| wrap_err("large plaintext", e))?;
+    let other = new_aead(kt)?;
+    check_aead_cross_key(a.as_ref(), other.as_ref()).map_err(|e| wrap_err("cross key", e))?;
+    Ok(())
+}
+
+/// Create an [`Aead`] primitive for a fresh keyset generated from the key template.
+fn new_aead(kt: &tink_proto::KeyTemplate) -> Result<Box<dyn Aead>, TinkError> {
+    let kh = tink_core::keyset::Handle::new(kt)
+        .map_err(|e| wrap_err("cannot create keyset handle", e))?;
+    tink_aead::new(&kh).map_err(|e| wrap_err("cannot create AEAD primitive", e))
+}
+
+/// Encrypt then decrypt, and check the result matches the original plaintext.
+fn roundtrip(a: &dyn Aead, pt: &[u8], aad: &[u8]) -> Result<Vec<u8>, TinkError> {
+    let ct = a
+        .encrypt(pt, aad)
+        .map_err(|e| wrap_err("encryption failed", e))?;
+    let decrypted = a
+        .decrypt(&ct, aad)
+        .map_err(|e| wrap_err("decryption failed", e))?;
+    if decrypted != pt {
+        return Err("decrypted plaintext does not match".into());
+    }
+    Ok(ct)
+}
+
+/// Check that various plaintexts and associated data survive encryption and decryption, and
+/// that encryption is randomized.
+pub fn check_aead_roundtrip(a: &dyn Aead) -> Result<(), TinkError> {
+    for pt_size in &[1, 15, 16, 17, 100] {
+        for aad_size in &[1, 16, 100] {
+            let pt = get_random_bytes(*pt_size);
+            let aad = get_random_bytes(*aad_size);
+            let ct1 = roundtrip(a, &pt, &aad)?;
+            let ct2 = roundtrip(a, &pt, &aad)?;
+            if ct1 == ct2 {
+                return Err(format!(
+                    "encryption not randomized (pt size {}, aad size {})",
+                    pt_size, aad_size
+                )
+                .into());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check that empty plaintext and/or empty associated data are supported.
+pub fn check_aead_empty_inputs(a: &dyn Aead) -> Result<(), TinkError> {
+    let data = get_random_bytes(20);
+    roundtrip(a, &[], &[]).map_err(|e| wrap_err("empty plaintext and aad", e))?;
+    roundtrip(a, &[], &data).map_err(|e| wrap_err("empty plaintext", e))?;
+    roundtrip(a, &data, &[]).map_err(|e| wrap_err("empty aad", e))?;
+    Ok(())
+}
+
+/// Check that decryption fails if the associated data differs from that used for encryption.
+pub fn check_aead_aad_mismatch(a: &dyn Aead) -> Result<(), TinkError> {
+    let pt = get_random_bytes(20);
+    let aad = get_random_bytes(20);
+    let ct = a
+        .encrypt(&pt, &aad)
+        .map_err(|e| wrap_err("encryption failed", e))?;
+    for modified_aad in crate::generate_mutations(&aad)
+        .iter()
+        .chain(std::iter::once(&Vec::new()))
+    {
+        if a.decrypt(&ct, modified_aad).is_ok() {
+            return Err(format!(
+                "decryption succeeded with aad {}",
+                hex::encode(modified_aad)
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
+
+/// Check that decryption fails for every mutation (bit flip, truncation, extension) of a
+/// ciphertext.
+pub fn check_aead_mutations(a: &dyn Aead) -> Result<(), TinkError> {
+    let pt = get_random_bytes(16);
+    let aad = get_random_bytes(16);
+    let ct = a
+        .encrypt(&pt, &aad)
+        .map_err(|e| wrap_err("encryption failed", e))?;
+    for modified in crate::generate_mutations(&ct) {
+        if a.decrypt(&modified, &aad).is_ok() {
+            return Err(format!(
+                "decryption succeeded for ciphertext {}",
+                hex::encode(&modified)
+            )
+            .into());
+        }
+    }
+    for len in 0..ct.len() {
+        if a.decrypt(&ct[..len], &aad).is_ok() {
+            return Err(format!("decryption succeeded for ciphertext truncated to {}", len).into());
+        }
+    }
+    Ok(())
+}
+
+/// Check that a large plaintext survives encryption and decryption.
+pub fn check_aead_large_plaintext(a: &dyn Aead) -> Result<(), TinkError> {
+    let pt = get_random_bytes(AEAD_KIT_LARGE_PLAINTEXT_SIZE);
+    let aad = get_random_bytes(16);
+    roundtrip(a, &pt, &aad)?;
+    Ok(())
+}
+
+/// Check that ciphertexts produced by one primitive cannot be decrypted by another primitive
+/// with a different key, and vice versa.
+pub fn check_aead_cross_key(a: &dyn Aead, b: &dyn Aead) -> Result<(), TinkError> {
+    let pt = get_random_bytes(20);
+    let aad = get_random_bytes(20);
+    let ct_a = a
+        .encrypt(&pt, &aad)
+        .map_err(|e| wrap_err("encryption failed", e))?;
+    let ct_b = b
+        .encrypt(&pt, &aad)
+        .map_err(|e| wrap_err("encryption failed", e))?;
+    if b.decrypt(&ct_a, &aad).is_ok() || a.decrypt(&ct_b, &aad).is_ok() {
+        return Err("decryption succeeded with a different key".into());
+    }
+    Ok(())
+}
@@ -29,6 +29,11 @@ pub const AES_GCM_KEY_VERSION: u32 = 0;
 /// Type URL of AES-GCM keys that Tink supports.
 pub const AES_GCM_TYPE_URL: &str = "type.googleapis.com/google.crypto.tink.AesGcmKey";
 
+/// Maximal version of key-committing AES-GCM keys.
+pub const AES_GCM_COMMITTING_KEY_VERSION: u32 = 0;
+/// Type URL of key-committing AES-GCM keys that Tink supports.
+pub const AES_GCM_COMMITTING_TYPE_URL: &str = "type.googleapis.com/tink_rust.AesGcmCommittingKey";
+
 /// Maximal version of AES-GCM-SIV keys.
 pub const AES_GCM_SIV_KEY_VERSION: u32 = 0;
 /// Type URL of AES-GCM-SIV keys that Tink supports.
@@ -50,13 +50,10 @@ impl FakeClient {
     }
 }
 
-impl tink_core::registry::KmsClient for FakeClient {
-    fn supported(&self, key_uri: &str) -> bool {
-        key_uri.starts_with(&self.uri_prefix)
-    }
-
-    fn get_aead(&self, key_uri: &str) -> Result<Box<dyn tink_core::Aead>, crate::TinkError> {
-        if !self.supported(key_uri) {
+impl FakeClient {
+    /// Decode the keyset encoded in `key_uri`.
+    fn handle(&self, key_uri: &str) -> Result<tink_core::keyset::Handle, TinkError> {
+        if !tink_core::registry::KmsClient::supported(self, key_uri) {
             return Err(format!(
                 "key_uri must start with prefix {}, but got {}",
                 self.uri_prefix, key_uri
@@ -74,14 +71,81 @@ impl tink_core::registry::KmsClient for FakeClient {
             .map_err(|e| wrap_err("Failed to decode", e))?;
         let cursor = std::io::Cursor::new(keyset_data);
         let mut reader = tink_core::keyset::BinaryReader::new(cursor);
-        let handle = tink_core::keyset::insecure::read(&mut reader)?;
-        tink_aead::new(&handle)
+        tink_core::keyset::insecure::read(&mut reader)
+    }
+
+    /// Return the key data of the primary key of the keyset encoded in `key_uri`.
+    fn primary_key_data(&self, key_uri: &str) -> Result<tink_proto::KeyData, TinkError> {
+        let ks = tink_core::keyset::insecure::keyset_material(&self.handle(key_uri)?);
+        ks.key
+            .into_iter()
+            .find(|k| k.key_id == ks.primary_key_id)
+            .and_then(|k| k.key_data)
+            .ok_or_else(|| "no primary key".into())
+    }
+}
+
+impl tink_core::registry::KmsClient for FakeClient {
+    fn supported(&self, key_uri: &str) -> bool {
+        key_uri.starts_with(&self.uri_prefix)
+    }
+
+    fn supports_associated_data(&self, key_uri: &str) -> bool {
+        self.supported(key_uri)
+    }
+
+    fn get_aead(&self, key_uri: &str) -> Result<Box<dyn tink_core::Aead>, crate::TinkError> {
+        tink_aead::new(&self.handle(key_uri)?)
+    }
+
+    /// Signing is supported for URIs that encode a keyset of private signing keys, and data key
+    /// generation for all others.
+    fn capabilities(&self, key_uri: &str) -> tink_core::registry::KmsCapabilities {
+        let sign = self.primary_key_data(key_uri).map_or(false, |kd| {
+            kd.key_material_type == tink_proto::key_data::KeyMaterialType::AsymmetricPrivate as i32
+        });
+        tink_core::registry::KmsCapabilities {
+            associated_data: self.supports_associated_data(key_uri),
+            generate_data_key: !sign,
+            sign,
+        }
+    }
+
+    fn generate_data_key(
+        &self,
+        key_uri: &str,
+        key_size: usize,
+    ) -> Result<tink_core::registry::DataKey, TinkError> {
+        let plaintext = tink_core::subtle::random::get_random_bytes(key_size);
+        let ciphertext = self.get_aead(key_uri)?.encrypt(&plaintext, &[])?;
+        Ok(tink_core::registry::DataKey {
+            plaintext,
+            ciphertext,
+        })
+    }
+
+    fn get_signer(&self, key_uri: &str) -> Result<Box<dyn tink_core::Signer>, TinkError> {
+        match tink_core::registry::primitive_from_key_data(&self.primary_key_data(key_uri)?)? {
+            tink_core::Primitive::Signer(p) => Ok(p),
+            _ => Err("key_uri does not hold a signing key".into()),
+        }
+    }
+
+    fn get_public_key_data(&self, key_uri: &str) -> Result<tink_proto::KeyData, TinkError> {
+        let kd = self.primary_key_data(key_uri)?;
+        tink_core::registry::get_key_manager(&kd.type_url)?.public_key_data(&kd.value)
     }
 }
 
 /// Return a new, random fake KMS key URI.
 pub fn new_key_uri() -> Result<String, TinkError> {
-    let handle = tink_core::keyset::Handle::new(&tink_aead::aes128_gcm_key_template())?;
+    new_key_uri_with_template(&tink_aead::aes128_gcm_key_template())
+}
+
+/// Return a new, random fake KMS key URI for a key generated from `template`, for example an
+/// asymmetric signing key.
+pub fn new_key_uri_with_template(template: &tink_proto::KeyTemplate) -> Result<String, TinkError> {
+    let handle = tink_core::keyset::Handle::new(template)?;
     let mut buf = vec![];
     let mut writer = tink_core::keyset::BinaryWriter::new(&mut buf);
     tink_core::keyset::insecure::write(&handle, &mut writer)?;
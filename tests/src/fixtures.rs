@@ -0,0 +1,261 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! Golden wire-format fixtures, and harness functions that check primitives against them.
+//!
+//! Fixtures live in testdata/fixtures/<primitive>[-<generator>].json.  Each file records which
+//! generator produced it, and each fixture holds the cleartext keyset (in Tink's JSON keyset
+//! format), the input and associated data, and the expected output (ciphertext, tag, signature
+//! or PRF output, including any output prefix).  Fixtures for keysets with several keys may also
+//! list the output of every key.  Files produced by other Tink implementations can be added
+//! alongside, in the same format.
+
+use serde::Deserialize;
+use tink_core::{
+    keyset::{insecure, Handle},
+    Aead, DeterministicAead, Mac, Signer, TinkError, Verifier,
+};
+
+/// A single golden fixture, with its keyset(s) loaded.
+pub struct Fixture {
+    /// Name of the fixture, typically `<template>/<output prefix type>`.
+    pub name: String,
+    /// Description of what produced the fixture.
+    pub generator: String,
+    /// Keyset holding the key(s) used to produce the output; the output is produced by the
+    /// primary key.
+    pub keyset: Handle,
+    /// Public keyset corresponding to `keyset`, for asymmetric primitives.
+    pub public_keyset: Option<Handle>,
+    pub input: Vec<u8>,
+    pub associated_data: Vec<u8>,
+    pub output: Vec<u8>,
+    /// The output of each key in `keyset`, for fixtures with several keys.
+    pub key_outputs: Vec<(tink_core::KeyId, Vec<u8>)>,
+}
+
+#[derive(Deserialize)]
+struct FixtureFile {
+    generator: String,
+    fixtures: Vec<FixtureEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FixtureEntry {
+    name: String,
+    keyset: tink_proto::Keyset,
+    #[serde(default)]
+    public_keyset: Option<tink_proto::Keyset>,
+    #[serde(with = "crate::hex_string")]
+    input: Vec<u8>,
+    #[serde(default, with = "crate::hex_string")]
+    associated_data: Vec<u8>,
+    #[serde(with = "crate::hex_string")]
+    output: Vec<u8>,
+    #[serde(default)]
+    key_outputs: Vec<KeyOutputEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KeyOutputEntry {
+    key_id: tink_core::KeyId,
+    #[serde(with = "crate::hex_string")]
+    output: Vec<u8>,
+}
+
+/// Load the fixtures for the given primitive type (e.g. "aead") from testdata/fixtures, reading
+/// both `<primitive>.json` and any `<primitive>-<generator>.json` files.
+pub fn fixtures(primitive: &str) -> Vec<Fixture> {
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("testdata")
+        .join("fixtures");
+    let mut paths: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap_or_else(|e| panic!("failed to read {}: {:?}", dir.display(), e))
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let stem = path.file_stem()?.to_str()?.to_string();
+            let wanted = path.extension()? == "json"
+                && (stem == primitive || stem.starts_with(&format!("{}-", primitive)));
+            if wanted {
+                Some(path)
+            } else {
+                None
+            }
+        })
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "no {} fixtures found", primitive);
+
+    let mut result = Vec::new();
+    for path in paths {
+        let data = std::fs::read(&path)
+            .unwrap_or_else(|e| panic!("failed to read {}: {:?}", path.display(), e));
+        let file: FixtureFile = serde_json::from_slice(&data)
+            .unwrap_or_else(|e| panic!("failed to parse {}: {:?}", path.display(), e));
+        result.extend(file.fixtures.into_iter().map(|f| {
+            Fixture {
+                keyset: insecure::new_handle(f.keyset)
+                    .unwrap_or_else(|e| panic!("{}: invalid keyset: {:?}", f.name, e)),
+                public_keyset: f.public_keyset.map(|ks| {
+                    insecure::new_handle(ks)
+                        .unwrap_or_else(|e| panic!("{}: invalid public keyset: {:?}", f.name, e))
+                }),
+                name: f.name,
+                generator: file.generator.clone(),
+                input: f.input,
+                associated_data: f.associated_data,
+                output: f.output,
+                key_outputs: f
+                    .key_outputs
+                    .into_iter()
+                    .map(|k| (k.key_id, k.output))
+                    .collect(),
+            }
+        }));
+    }
+    result
+}
+
+/// Check that AEAD primitives created by `new` decrypt every AEAD fixture, and that their own
+/// ciphertexts round-trip.
+pub fn check_aead_fixtures<F>(new: F)
+where
+    F: Fn(&Handle) -> Result<Box<dyn Aead>, TinkError>,
+{
+    for f in fixtures("aead") {
+        let a = new(&f.keyset).unwrap_or_else(|e| panic!("{}: {:?}", f.name, e));
+        let pt = a
+            .decrypt(&f.output, &f.associated_data)
+            .unwrap_or_else(|e| panic!("{} ({}): decrypt failed: {:?}", f.name, f.generator, e));
+        assert_eq!(pt, f.input, "{} ({})", f.name, f.generator);
+        let ct = a.encrypt(&f.input, &f.associated_data).unwrap();
+        let prefix =
+            tink_core::cryptofmt::output_prefix(&insecure::keyset_material(&f.keyset).key[0])
+                .unwrap();
+        assert!(ct.starts_with(&prefix), "{}: output prefix differs", f.name);
+        assert_eq!(a.decrypt(&ct, &f.associated_data).unwrap(), f.input);
+    }
+}
+
+/// Check that deterministic AEAD primitives created by `new` reproduce and decrypt every
+/// deterministic AEAD fixture.
+pub fn check_daead_fixtures<F>(new: F)
+where
+    F: Fn(&Handle) -> Result<Box<dyn DeterministicAead>, TinkError>,
+{
+    for f in fixtures("daead") {
+        let d = new(&f.keyset).unwrap_or_else(|e| panic!("{}: {:?}", f.name, e));
+        let ct = d
+            .encrypt_deterministically(&f.input, &f.associated_data)
+            .unwrap();
+        assert_eq!(ct, f.output, "{} ({})", f.name, f.generator);
+        let pt = d
+            .decrypt_deterministically(&f.output, &f.associated_data)
+            .unwrap_or_else(|e| panic!("{} ({}): decrypt failed: {:?}", f.name, f.generator, e));
+        assert_eq!(pt, f.input, "{} ({})", f.name, f.generator);
+    }
+}
+
+/// Check that MAC primitives created by `new` reproduce and verify every MAC fixture.
+pub fn check_mac_fixtures<F>(new: F)
+where
+    F: Fn(&Handle) -> Result<Box<dyn Mac>, TinkError>,
+{
+    for f in fixtures("mac") {
+        let m = new(&f.keyset).unwrap_or_else(|e| panic!("{}: {:?}", f.name, e));
+        assert_eq!(
+            m.compute_mac(&f.input).unwrap(),
+            f.output,
+            "{} ({})",
+            f.name,
+            f.generator
+        );
+        m.verify_mac(&f.output, &f.input)
+            .unwrap_or_else(|e| panic!("{} ({}): verify failed: {:?}", f.name, f.generator, e));
+    }
+}
+
+/// Check that PRF outputs computed by `compute` (given a keyset, the input and the output length)
+/// match every PRF fixture.
+pub fn check_prf_fixtures<F>(compute: F)
+where
+    F: Fn(&Handle, &[u8], usize) -> Result<Vec<u8>, TinkError>,
+{
+    for f in fixtures("prf") {
+        let out = compute(&f.keyset, &f.input, f.output.len())
+            .unwrap_or_else(|e| panic!("{}: {:?}", f.name, e));
+        assert_eq!(out, f.output, "{} ({})", f.name, f.generator);
+    }
+}
+
+/// Check that `compute` gives the expected output for every key of every PRF fixture that lists
+/// per-key outputs, whichever key of the keyset is primary.  `compute` is given a handle and the
+/// ID of the key to compute the PRF with.
+pub fn check_prf_set_fixtures<F>(compute: F)
+where
+    F: Fn(&Handle, tink_core::KeyId, &[u8], usize) -> Result<Vec<u8>, TinkError>,
+{
+    let mut checked = 0;
+    for f in fixtures("prf") {
+        let ks = insecure::keyset_material(&f.keyset);
+        for (primary_id, _) in &f.key_outputs {
+            let mut ks = ks.clone();
+            ks.primary_key_id = *primary_id;
+            let h = insecure::new_handle(ks).unwrap();
+            for (key_id, output) in &f.key_outputs {
+                let out = compute(&h, *key_id, &f.input, output.len()).unwrap_or_else(|e| {
+                    panic!("{}: key {}: {:?}", f.name, key_id, e);
+                });
+                assert_eq!(
+                    &out, output,
+                    "{} ({}): key {} with primary {}",
+                    f.name, f.generator, key_id, primary_id
+                );
+                checked += 1;
+            }
+        }
+    }
+    assert!(checked > 0, "no PRF fixtures with per-key outputs found");
+}
+
+/// Check that verifiers created by `new_verifier` accept every signature fixture, and that
+/// signers created by `new_signer` produce signatures that verify.  Fixtures for deterministic
+/// signature schemes (Ed25519) are also checked for an exact match.
+pub fn check_signature_fixtures<S, V>(new_signer: S, new_verifier: V)
+where
+    S: Fn(&Handle) -> Result<Box<dyn Signer>, TinkError>,
+    V: Fn(&Handle) -> Result<Box<dyn Verifier>, TinkError>,
+{
+    for f in fixtures("signature") {
+        let public = f
+            .public_keyset
+            .as_ref()
+            .unwrap_or_else(|| panic!("{}: no public keyset", f.name));
+        let verifier = new_verifier(public).unwrap_or_else(|e| panic!("{}: {:?}", f.name, e));
+        verifier
+            .verify(&f.output, &f.input)
+            .unwrap_or_else(|e| panic!("{} ({}): verify failed: {:?}", f.name, f.generator, e));
+
+        let signer = new_signer(&f.keyset).unwrap_or_else(|e| panic!("{}: {:?}", f.name, e));
+        let sig = signer.sign(&f.input).unwrap();
+        verifier.verify(&sig, &f.input).unwrap();
+        if f.name.starts_with("ED25519") {
+            assert_eq!(sig, f.output, "{} ({})", f.name, f.generator);
+        }
+    }
+}
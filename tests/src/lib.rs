@@ -25,11 +25,17 @@ use std::convert::TryInto;
 use tink_core::{subtle::random::get_random_bytes, utils::wrap_err, Aead, TinkError};
 use tink_proto::{prost, EcdsaSignatureEncoding, EllipticCurveType, HashType, KeyData, Keyset};
 
+mod aeadkit;
+pub use aeadkit::*;
 mod constant;
 pub use constant::*;
 pub mod fakekms;
+mod fixtures;
+pub use fixtures::*;
 mod sharedbuf;
 pub use sharedbuf::*;
+mod strategies;
+pub use strategies::*;
 mod testdata;
 pub use testdata::*;
 mod wycheproofutil;
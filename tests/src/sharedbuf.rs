@@ -21,42 +21,175 @@ use std::sync::{Arc, Mutex};
 /// Shared buffer, which allows [`Read`](std::io::Read) and [`Write`](std::io::Write) operations to
 /// happen in parallel. This also means that a `clone`d copy can be enclosed as a `Box<dyn Read>`
 /// (which is implicitly `Box<dyn Read + 'static>`) without lifetime concerns.
+///
+/// The buffer can also be configured to return short reads or writes, or to fail after a given
+/// number of bytes, so that error handling in stream-processing code can be exercised
+/// deterministically.  Configuration and byte counts are shared between clones.  With the `async`
+/// feature, the buffer also implements [`futures_io::AsyncRead`] and [`futures_io::AsyncWrite`]
+/// (always completing immediately).
 #[derive(Clone, Default)]
 pub struct SharedBuf {
-    contents: Arc<Mutex<Vec<u8>>>,
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    contents: Vec<u8>,
+    max_read: Option<usize>,
+    max_write: Option<usize>,
+    fail_read_after: Option<usize>,
+    fail_write_after: Option<usize>,
+    bytes_read: usize,
+    bytes_written: usize,
 }
 
 impl SharedBuf {
     pub fn new() -> Self {
-        SharedBuf {
-            contents: Arc::new(Mutex::new(vec![])),
-        }
+        Self::default()
+    }
+
+    /// Create a buffer holding the given contents.
+    pub fn with_contents(contents: Vec<u8>) -> Self {
+        let buf = Self::new();
+        buf.state.lock().unwrap().contents = contents;
+        buf
+    }
+
+    /// Return at most `max` bytes from each read operation.
+    pub fn with_short_reads(self, max: usize) -> Self {
+        assert!(max > 0, "a zero-length read signals EOF");
+        self.state.lock().unwrap().max_read = Some(max);
+        self
+    }
+
+    /// Accept at most `max` bytes in each write operation.
+    pub fn with_short_writes(self, max: usize) -> Self {
+        assert!(max > 0, "a zero-length write signals failure");
+        self.state.lock().unwrap().max_write = Some(max);
+        self
+    }
+
+    /// Fail any read operation once `count` bytes have been read in total.
+    pub fn fail_reads_after(self, count: usize) -> Self {
+        self.state.lock().unwrap().fail_read_after = Some(count);
+        self
+    }
+
+    /// Fail any write operation once `count` bytes have been written in total.
+    pub fn fail_writes_after(self, count: usize) -> Self {
+        self.state.lock().unwrap().fail_write_after = Some(count);
+        self
     }
 
     /// Return a copy of the contents of the buffer.
     pub fn contents(&self) -> Vec<u8> {
-        self.contents.lock().unwrap().clone()
+        self.state.lock().unwrap().contents.clone()
+    }
+
+    /// Return the total number of bytes read from the buffer.
+    pub fn bytes_read(&self) -> usize {
+        self.state.lock().unwrap().bytes_read
+    }
+
+    /// Return the total number of bytes written to the buffer.
+    pub fn bytes_written(&self) -> usize {
+        self.state.lock().unwrap().bytes_written
     }
 }
 
+/// Return the number of bytes allowed by a per-operation limit and an overall failure point.
+fn allowed(
+    mut count: usize,
+    max: Option<usize>,
+    fail_after: Option<usize>,
+    done: usize,
+    op: &str,
+) -> std::io::Result<usize> {
+    if let Some(max) = max {
+        count = std::cmp::min(count, max);
+    }
+    if let Some(fail_after) = fail_after {
+        if done >= fail_after {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                format!("SharedBuf: injected {} failure after {} bytes", op, done),
+            ));
+        }
+        count = std::cmp::min(count, fail_after - done);
+    }
+    Ok(count)
+}
+
 impl std::io::Read for SharedBuf {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        let mut v = self.contents.lock().unwrap();
-        let count = std::cmp::min(buf.len(), v.len());
-        for (i, b) in v.drain(0..count).enumerate() {
+        let mut s = self.state.lock().unwrap();
+        let available = std::cmp::min(buf.len(), s.contents.len());
+        let count = allowed(
+            available,
+            s.max_read,
+            s.fail_read_after,
+            s.bytes_read,
+            "read",
+        )?;
+        for (i, b) in s.contents.drain(0..count).enumerate() {
             buf[i] = b;
         }
+        s.bytes_read += count;
         Ok(count)
     }
 }
 
 impl std::io::Write for SharedBuf {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        let mut v = self.contents.lock().unwrap();
-        v.extend_from_slice(buf);
-        Ok(buf.len())
+        let mut s = self.state.lock().unwrap();
+        let count = allowed(
+            buf.len(),
+            s.max_write,
+            s.fail_write_after,
+            s.bytes_written,
+            "write",
+        )?;
+        s.contents.extend_from_slice(&buf[..count]);
+        s.bytes_written += count;
+        Ok(count)
     }
     fn flush(&mut self) -> std::result::Result<(), std::io::Error> {
         Ok(())
     }
 }
+
+#[cfg(feature = "async")]
+impl futures_io::AsyncRead for SharedBuf {
+    fn poll_read(
+        self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+        buf: &mut [u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        std::task::Poll::Ready(std::io::Read::read(self.get_mut(), buf))
+    }
+}
+
+#[cfg(feature = "async")]
+impl futures_io::AsyncWrite for SharedBuf {
+    fn poll_write(
+        self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        std::task::Poll::Ready(std::io::Write::write(self.get_mut(), buf))
+    }
+
+    fn poll_flush(
+        self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(
+        self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+}
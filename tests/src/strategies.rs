@@ -0,0 +1,102 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+//! [proptest](https://docs.rs/proptest) strategies for generating keysets.
+
+use proptest::prelude::*;
+use tink_proto::{KeyStatusType, Keyset, OutputPrefixType};
+
+/// Maximum number of keys in a keyset produced by [`arb_keyset`].
+pub const ARB_KEYSET_MAX_KEYS: usize = 5;
+
+/// Strategy that produces symmetric key templates supported by the registered primitive crates.
+///
+/// Note that generating values from this strategy registers the AEAD, deterministic AEAD and MAC
+/// key managers.
+pub fn arb_key_template() -> impl Strategy<Value = tink_proto::KeyTemplate> {
+    prop_oneof![
+        Just(tink_aead::aes128_gcm_key_template()),
+        Just(tink_aead::aes256_gcm_no_prefix_key_template()),
+        Just(tink_aead::aes128_ctr_hmac_sha256_key_template()),
+        Just(tink_aead::cha_cha20_poly1305_key_template()),
+        Just(tink_daead::aes_siv_key_template()),
+        Just(tink_mac::hmac_sha256_tag128_key_template()),
+        Just(tink_mac::aes_cmac_tag128_key_template()),
+    ]
+    .prop_map(|kt| {
+        tink_aead::init();
+        tink_daead::init();
+        tink_mac::init();
+        kt
+    })
+}
+
+/// Strategy that produces a key status, weighted towards [`KeyStatusType::Enabled`].
+pub fn arb_key_status() -> impl Strategy<Value = KeyStatusType> {
+    prop_oneof![
+        3 => Just(KeyStatusType::Enabled),
+        1 => Just(KeyStatusType::Disabled),
+        1 => Just(KeyStatusType::Destroyed),
+    ]
+}
+
+/// Strategy that produces an output prefix type.
+pub fn arb_output_prefix_type() -> impl Strategy<Value = OutputPrefixType> {
+    prop_oneof![
+        Just(OutputPrefixType::Tink),
+        Just(OutputPrefixType::Legacy),
+        Just(OutputPrefixType::Raw),
+        Just(OutputPrefixType::Crunchy),
+    ]
+}
+
+/// Strategy that produces valid keysets holding between 1 and [`ARB_KEYSET_MAX_KEYS`] keys, all
+/// generated from the same key template (so that a primitive can be created for the keyset).  The
+/// primary key is always enabled, and the key IDs are unique.
+pub fn arb_keyset() -> impl Strategy<Value = Keyset> {
+    (
+        arb_key_template(),
+        prop::collection::vec(
+            (arb_key_status(), arb_output_prefix_type()),
+            1..=ARB_KEYSET_MAX_KEYS,
+        ),
+        any::<prop::sample::Index>(),
+    )
+        .prop_map(|(kt, key_params, primary_idx)| {
+            let primary = primary_idx.index(key_params.len());
+            let mut keys = Vec::with_capacity(key_params.len());
+            for (i, (status, prefix_type)) in key_params.into_iter().enumerate() {
+                let key_data =
+                    tink_core::registry::new_key_data(&kt).expect("failed to generate key data"); // safe: test code
+                let status = if i == primary {
+                    KeyStatusType::Enabled
+                } else {
+                    status
+                };
+                keys.push(tink_proto::keyset::Key {
+                    key_data: if status == KeyStatusType::Destroyed {
+                        None
+                    } else {
+                        Some(key_data)
+                    },
+                    status: status as i32,
+                    key_id: i as u32 + 1,
+                    output_prefix_type: prefix_type as i32,
+                });
+            }
+            crate::new_keyset(primary as u32 + 1, keys)
+        })
+}
@@ -0,0 +1,312 @@
+#!/usr/bin/env python3
+# Copyright 2020 The Tink-Rust Authors
+#
+# Licensed under the Apache License, Version 2.0 (the "License");
+# you may not use this file except in compliance with the License.
+# You may obtain a copy of the License at
+#
+#      http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing, software
+# distributed under the License is distributed on an "AS IS" BASIS,
+# WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+# See the License for the specific language governing permissions and
+# limitations under the License.
+#
+################################################################################
+"""Generate golden wire-format fixtures for the tink-tests fixture harness.
+
+The fixtures are built from first principles with pyca/cryptography, following
+the Tink wire formats, so they are independent of the Rust implementation.
+Key material and nonces are derived deterministically, so re-running this
+script reproduces the checked-in files exactly.
+
+Usage: python3 generate.py  (writes *.json next to this script)
+"""
+
+import base64
+import hashlib
+import json
+import os
+import struct
+
+from cryptography.hazmat.primitives import cmac, hashes, hmac
+from cryptography.hazmat.primitives.asymmetric.ed25519 import Ed25519PrivateKey
+from cryptography.hazmat.primitives.ciphers import Cipher, algorithms, modes
+from cryptography.hazmat.primitives.ciphers.aead import AESGCM, AESSIV
+from cryptography.hazmat.primitives.kdf.hkdf import HKDF
+from cryptography.hazmat.primitives.serialization import Encoding, PublicFormat
+
+GENERATOR = "tests/testdata/fixtures/generate.py (pyca/cryptography)"
+TYPE_URL_PREFIX = "type.googleapis.com/google.crypto.tink."
+SHA256 = 3  # tink_proto::HashType::Sha256
+SHA512 = 4  # tink_proto::HashType::Sha512
+
+
+def derive(label, size):
+    """Deterministic pseudo-random bytes for key material and nonces."""
+    out = b""
+    counter = 0
+    while len(out) < size:
+        out += hashlib.sha256(b"%s/%d" % (label.encode(), counter)).digest()
+        counter += 1
+    return out[:size]
+
+
+# Minimal protobuf encoding (proto3: default-valued fields are omitted).
+def varint(n):
+    out = b""
+    while True:
+        b = n & 0x7F
+        n >>= 7
+        if n:
+            out += bytes([b | 0x80])
+        else:
+            return out + bytes([b])
+
+
+def pb_uint(field, value):
+    return varint(field << 3) + varint(value) if value else b""
+
+
+def pb_bytes(field, value):
+    return varint((field << 3) | 2) + varint(len(value)) + value
+
+
+def output_prefix(prefix_type, key_id):
+    if prefix_type == "TINK":
+        return b"\x01" + struct.pack(">I", key_id)
+    if prefix_type in ("LEGACY", "CRUNCHY"):
+        return b"\x00" + struct.pack(">I", key_id)
+    return b""
+
+
+def keyset(type_name, value, material, key_id, prefix_type):
+    return {
+        "primaryKeyId": key_id,
+        "key": [
+            {
+                "keyData": {
+                    "typeUrl": TYPE_URL_PREFIX + type_name,
+                    "value": base64.b64encode(value).decode(),
+                    "keyMaterialType": material,
+                },
+                "status": "ENABLED",
+                "keyId": key_id,
+                "outputPrefixType": prefix_type,
+            }
+        ],
+    }
+
+
+def fixture(name, ks, data, output, aad=b"", public_keyset=None):
+    f = {"name": name, "keyset": ks}
+    if public_keyset is not None:
+        f["publicKeyset"] = public_keyset
+    f["input"] = data.hex()
+    if aad:
+        f["associatedData"] = aad.hex()
+    f["output"] = output.hex()
+    return f
+
+
+def hmac_digest(hash_type, key, data):
+    h = hmac.HMAC(key, hashes.SHA256() if hash_type == SHA256 else hashes.SHA512())
+    h.update(data)
+    return h.finalize()
+
+
+def hmac_key(hash_type, tag_size, key):
+    return pb_bytes(2, pb_uint(1, hash_type) + pb_uint(2, tag_size)) + pb_bytes(3, key)
+
+
+def aead_fixtures():
+    out = []
+    data = b"Tink wire-format fixture"
+    aad = b"associated data"
+    for name, size, key_id, prefix in [
+        ("AES128_GCM/TINK", 16, 0x2B3C4D5E, "TINK"),
+        ("AES256_GCM/RAW", 32, 0x0A0B0C0D, "RAW"),
+    ]:
+        key = derive(name + "/key", size)
+        iv = derive(name + "/iv", 12)
+        ct = iv + AESGCM(key).encrypt(iv, data, aad)
+        ks = keyset("AesGcmKey", pb_bytes(3, key), "SYMMETRIC", key_id, prefix)
+        out.append(fixture(name, ks, data, output_prefix(prefix, key_id) + ct, aad))
+
+    name, key_id, prefix = "AES128_CTR_HMAC_SHA256/TINK", 0x600DF00D, "TINK"
+    aes_key = derive(name + "/aes", 16)
+    mac_key = derive(name + "/hmac", 32)
+    iv = derive(name + "/iv", 16)
+    enc = Cipher(algorithms.AES(aes_key), modes.CTR(iv)).encryptor()
+    payload = iv + enc.update(data) + enc.finalize()
+    tag = hmac_digest(SHA256, mac_key, aad + payload + struct.pack(">Q", len(aad) * 8))[:16]
+    ctr_key = pb_bytes(2, pb_uint(1, 16)) + pb_bytes(3, aes_key)
+    value = pb_bytes(2, ctr_key) + pb_bytes(3, hmac_key(SHA256, 16, mac_key))
+    ks = keyset("AesCtrHmacAeadKey", value, "SYMMETRIC", key_id, prefix)
+    out.append(fixture(name, ks, data, output_prefix(prefix, key_id) + payload + tag, aad))
+    return out
+
+
+def daead_fixtures():
+    out = []
+    data = b"Tink wire-format fixture"
+    for name, aad, key_id, prefix in [
+        ("AES256_SIV/TINK", b"associated data", 0x51515151, "TINK"),
+        ("AES256_SIV/RAW/empty-ad", b"", 0x00000007, "RAW"),
+    ]:
+        key = derive(name + "/key", 64)
+        ct = AESSIV(key).encrypt(data, [aad])
+        ks = keyset("AesSivKey", pb_bytes(2, key), "SYMMETRIC", key_id, prefix)
+        out.append(fixture(name, ks, data, output_prefix(prefix, key_id) + ct, aad))
+    return out
+
+
+def mac_fixtures():
+    out = []
+    data = b"Tink wire-format fixture"
+    # The CRUNCHY fixture shares its key with the LEGACY one, to show the difference in the MAC.
+    for name, key_name, hash_type, tag_size, key_id, prefix in [
+        ("HMAC_SHA256_128BITTAG/TINK", None, SHA256, 16, 0x01020304, "TINK"),
+        ("HMAC_SHA256_256BITTAG/LEGACY", None, SHA256, 32, 0x7FFFFFFF, "LEGACY"),
+        (
+            "HMAC_SHA256_256BITTAG/CRUNCHY",
+            "HMAC_SHA256_256BITTAG/LEGACY",
+            SHA256,
+            32,
+            0x7FFFFFFF,
+            "CRUNCHY",
+        ),
+        ("HMAC_SHA512_512BITTAG/RAW", None, SHA512, 64, 0x00000042, "RAW"),
+    ]:
+        key = derive((key_name or name) + "/key", 32)
+        signed = data + b"\x00" if prefix == "LEGACY" else data
+        tag = hmac_digest(hash_type, key, signed)[:tag_size]
+        ks = keyset("HmacKey", hmac_key(hash_type, tag_size, key), "SYMMETRIC", key_id, prefix)
+        out.append(fixture(name, ks, data, output_prefix(prefix, key_id) + tag))
+
+    name, key_id, prefix = "AES_CMAC/TINK", 0x0C0C0C0C, "TINK"
+    key = derive(name + "/key", 32)
+    c = cmac.CMAC(algorithms.AES(key))
+    c.update(data)
+    tag = c.finalize()
+    value = pb_bytes(2, key) + pb_bytes(3, pb_uint(1, 16))
+    ks = keyset("AesCmacKey", value, "SYMMETRIC", key_id, prefix)
+    out.append(fixture(name, ks, data, output_prefix(prefix, key_id) + tag))
+    return out
+
+
+def hmac_prf_key(hash_type, key):
+    return pb_bytes(2, pb_uint(1, hash_type)) + pb_bytes(3, key)
+
+
+def hkdf_prf_key(hash_type, salt, key):
+    return pb_bytes(2, pb_uint(1, hash_type) + pb_bytes(2, salt)) + pb_bytes(3, key)
+
+
+def aes_cmac_prf_key(key):
+    return pb_bytes(2, key)
+
+
+def cmac_digest(key, data):
+    c = cmac.CMAC(algorithms.AES(key))
+    c.update(data)
+    return c.finalize()
+
+
+def prf_fixtures():
+    out = []
+    data = b"Tink wire-format fixture"
+    name, key_id = "HMAC_SHA256_PRF", 0x00ABCDEF
+    key = derive(name + "/key", 32)
+    ks = keyset("HmacPrfKey", hmac_prf_key(SHA256, key), "SYMMETRIC", key_id, "RAW")
+    out.append(fixture(name, ks, data, hmac_digest(SHA256, key, data)[:32]))
+
+    name, key_id = "HKDF_SHA256", 0x00FEDCBA
+    key = derive(name + "/key", 32)
+    salt = derive(name + "/salt", 16)
+    output = HKDF(algorithm=hashes.SHA256(), length=48, salt=salt, info=data).derive(key)
+    ks = keyset("HkdfPrfKey", hkdf_prf_key(SHA256, salt, key), "SYMMETRIC", key_id, "RAW")
+    out.append(fixture(name, ks, data, output))
+
+    name, key_id = "HMAC_SHA512_PRF", 0x00512512
+    key = derive(name + "/key", 64)
+    ks = keyset("HmacPrfKey", hmac_prf_key(SHA512, key), "SYMMETRIC", key_id, "RAW")
+    out.append(fixture(name, ks, data, hmac_digest(SHA512, key, data)))
+
+    name, key_id = "AES_CMAC_PRF", 0x00C0AC00
+    key = derive(name + "/key", 32)
+    ks = keyset("AesCmacPrfKey", aes_cmac_prf_key(key), "SYMMETRIC", key_id, "RAW")
+    out.append(fixture(name, ks, data, cmac_digest(key, data)))
+
+    # A PRF set holding one key of each type, with the output of every key, so that switching
+    # the primary key can be checked not to change the output of any key.
+    name = "PRF_SET"
+    hmac_key_value = derive(name + "/hmac", 32)
+    hkdf_key_value = derive(name + "/hkdf", 32)
+    hkdf_salt = derive(name + "/salt", 16)
+    cmac_key_value = derive(name + "/cmac", 32)
+    members = [
+        (0x11111111, "HmacPrfKey", hmac_prf_key(SHA256, hmac_key_value),
+         hmac_digest(SHA256, hmac_key_value, data)[:16]),
+        (0x22222222, "HkdfPrfKey", hkdf_prf_key(SHA256, hkdf_salt, hkdf_key_value),
+         HKDF(algorithm=hashes.SHA256(), length=16, salt=hkdf_salt, info=data)
+         .derive(hkdf_key_value)),
+        (0x33333333, "AesCmacPrfKey", aes_cmac_prf_key(cmac_key_value),
+         cmac_digest(cmac_key_value, data)),
+    ]
+    ks = keyset(members[0][1], members[0][2], "SYMMETRIC", members[0][0], "RAW")
+    for key_id, type_name, value, _ in members[1:]:
+        ks["key"].extend(keyset(type_name, value, "SYMMETRIC", key_id, "RAW")["key"])
+    f = fixture(name, ks, data, members[0][3])
+    f["keyOutputs"] = [{"keyId": key_id, "output": output.hex()}
+                       for key_id, _, _, output in members]
+    out.append(f)
+    return out
+
+
+def signature_fixtures():
+    out = []
+    data = b"Tink wire-format fixture"
+    # The CRUNCHY fixture shares its key with the LEGACY one, to show the difference in the
+    # signature.
+    for name, key_name, key_id, prefix in [
+        ("ED25519/TINK", None, 0x3E3E3E3E, "TINK"),
+        ("ED25519/LEGACY", None, 0x00001234, "LEGACY"),
+        ("ED25519/CRUNCHY", "ED25519/LEGACY", 0x00001234, "CRUNCHY"),
+        ("ED25519/RAW", None, 0x00000099, "RAW"),
+    ]:
+        seed = derive((key_name or name) + "/seed", 32)
+        sk = Ed25519PrivateKey.from_private_bytes(seed)
+        pk = sk.public_key().public_bytes(Encoding.Raw, PublicFormat.Raw)
+        signed = data + b"\x00" if prefix == "LEGACY" else data
+        sig = output_prefix(prefix, key_id) + sk.sign(signed)
+        public = pb_bytes(2, pk)
+        ks = keyset(
+            "Ed25519PrivateKey",
+            pb_bytes(2, seed) + pb_bytes(3, public),
+            "ASYMMETRIC_PRIVATE",
+            key_id,
+            prefix,
+        )
+        pub_ks = keyset("Ed25519PublicKey", public, "ASYMMETRIC_PUBLIC", key_id, prefix)
+        out.append(fixture(name, ks, data, sig, public_keyset=pub_ks))
+    return out
+
+
+def main():
+    here = os.path.dirname(os.path.abspath(__file__))
+    for primitive, fixtures in [
+        ("aead", aead_fixtures()),
+        ("daead", daead_fixtures()),
+        ("mac", mac_fixtures()),
+        ("prf", prf_fixtures()),
+        ("signature", signature_fixtures()),
+    ]:
+        with open(os.path.join(here, primitive + ".json"), "w") as f:
+            json.dump({"generator": GENERATOR, "fixtures": fixtures}, f, indent=2)
+            f.write("\n")
+
+
+if __name__ == "__main__":
+    main()
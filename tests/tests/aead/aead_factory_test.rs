@@ -171,3 +171,34 @@ fn test_factory_with_aes256_ctr_hmac_sha512() {
 
     tink_aead::new(&good_kh).expect("calling new() with good keyset::Handle failed");
 }
+
+#[test]
+fn test_unwrapped_aead_from_key_data() {
+    tink_aead::init();
+    let kd = tink_tests::new_aes_gcm_key_data(16);
+    let a = tink_aead::unwrapped_aead_from_key_data(&kd).unwrap();
+    let pt = get_random_bytes(20);
+    let ct = a.encrypt(&pt, b"aad").unwrap();
+
+    // Equivalent to a RAW key in a keyset.
+    let key = tink_tests::new_key(
+        &kd,
+        tink_proto::KeyStatusType::Enabled,
+        1,
+        OutputPrefixType::Raw,
+    );
+    let kh = tink_core::keyset::insecure::new_handle(tink_tests::new_keyset(1, vec![key])).unwrap();
+    let wrapped = tink_aead::new(&kh).unwrap();
+    assert_eq!(wrapped.decrypt(&ct, b"aad").unwrap(), pt);
+    assert_eq!(
+        a.decrypt(&wrapped.encrypt(&pt, b"").unwrap(), b"").unwrap(),
+        pt
+    );
+
+    tink_mac::init();
+    let mac_kd = tink_tests::new_hmac_key_data(tink_proto::HashType::Sha256, 16);
+    tink_tests::expect_err(
+        tink_aead::unwrapped_aead_from_key_data(&mac_kd),
+        "not an AEAD primitive",
+    );
+}
@@ -15,6 +15,7 @@
 ////////////////////////////////////////////////////////////////////////////////
 
 use tink_core::{utils::wrap_err, TinkError};
+use tink_proto::prost::Message;
 
 #[test]
 fn test_key_templates() {
@@ -54,6 +55,39 @@ fn test_key_templates() {
     }
 }
 
+#[test]
+fn test_committing_key_templates() {
+    tink_aead::init();
+    let test_cases = vec![
+        (
+            "AES128_GCM_COMMITTING",
+            16,
+            tink_aead::aes128_gcm_committing_key_template(),
+        ),
+        (
+            "AES256_GCM_COMMITTING",
+            32,
+            tink_aead::aes256_gcm_committing_key_template(),
+        ),
+    ];
+    for (name, key_size, template) in test_cases {
+        assert_eq!(template.type_url, tink_tests::AES_GCM_COMMITTING_TYPE_URL);
+        assert_eq!(
+            template.output_prefix_type,
+            tink_proto::OutputPrefixType::Tink as i32
+        );
+        let format =
+            tink_proto::AesGcmCommittingKeyFormat::decode(template.value.as_ref()).unwrap();
+        assert_eq!(format.key_size, key_size);
+
+        let generator = tink_core::registry::get_template_generator(name).unwrap();
+        let registered = generator();
+        assert_eq!(registered, template);
+
+        assert!(test_encrypt_decrypt(&template).is_ok());
+    }
+}
+
 #[test]
 fn test_no_prefix_key_templates() {
     tink_aead::init();
@@ -173,3 +207,83 @@ fn test_encrypt_decrypt(template: &tink_proto::KeyTemplate) -> Result<(), TinkEr
     }
     Ok(())
 }
+
+#[test]
+fn test_template_builders() {
+    tink_aead::init();
+    assert_eq!(
+        tink_aead::aes256_gcm_no_prefix_key_template(),
+        tink_aead::AesGcmTemplateBuilder::new()
+            .key_size(32)
+            .prefix(tink_proto::OutputPrefixType::Raw)
+            .build()
+            .unwrap()
+    );
+    assert_eq!(
+        tink_aead::aes256_gcm_siv_key_template(),
+        tink_aead::AesGcmSivTemplateBuilder::new()
+            .key_size(32)
+            .build()
+            .unwrap()
+    );
+    assert_eq!(
+        tink_aead::aes128_gcm_committing_key_template(),
+        tink_aead::AesGcmCommittingTemplateBuilder::new()
+            .build()
+            .unwrap()
+    );
+    assert_eq!(
+        tink_aead::aes256_ctr_hmac_sha512_key_template(),
+        tink_aead::AesCtrHmacAeadTemplateBuilder::new()
+            .aes_key_size(32)
+            .hmac_key_size(64)
+            .tag_size(64)
+            .hash(tink_proto::HashType::Sha512)
+            .build()
+            .unwrap()
+    );
+
+    let template = tink_aead::AesGcmTemplateBuilder::new()
+        .key_size(24)
+        .build()
+        .unwrap();
+    assert!(test_encrypt_decrypt(&template).is_ok());
+}
+
+#[test]
+fn test_template_builders_invalid() {
+    tink_tests::expect_err(
+        tink_aead::AesGcmTemplateBuilder::new().key_size(20).build(),
+        "invalid AES key size",
+    );
+    tink_tests::expect_err(
+        tink_aead::AesGcmTemplateBuilder::new()
+            .prefix(tink_proto::OutputPrefixType::UnknownPrefix)
+            .build(),
+        "invalid output prefix type",
+    );
+    tink_tests::expect_err(
+        tink_aead::AesGcmCommittingTemplateBuilder::new()
+            .key_size(24)
+            .build(),
+        "invalid AES key size",
+    );
+    tink_tests::expect_err(
+        tink_aead::AesCtrHmacAeadTemplateBuilder::new()
+            .iv_size(8)
+            .build(),
+        "IV size out of range",
+    );
+    tink_tests::expect_err(
+        tink_aead::AesCtrHmacAeadTemplateBuilder::new()
+            .hmac_key_size(8)
+            .build(),
+        "HMAC key_size is too small",
+    );
+    tink_tests::expect_err(
+        tink_aead::AesCtrHmacAeadTemplateBuilder::new()
+            .tag_size(33)
+            .build(),
+        "too big",
+    );
+}
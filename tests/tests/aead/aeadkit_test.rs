@@ -0,0 +1,74 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_core::Aead;
+
+#[test]
+fn test_aead_kit_templates() {
+    tink_aead::init();
+    let templates = vec![
+        ("AES128_GCM", tink_aead::aes128_gcm_key_template()),
+        ("AES256_GCM", tink_aead::aes256_gcm_key_template()),
+        (
+            "AES256_GCM_NO_PREFIX",
+            tink_aead::aes256_gcm_no_prefix_key_template(),
+        ),
+        ("AES128_GCM_SIV", tink_aead::aes128_gcm_siv_key_template()),
+        ("AES256_GCM_SIV", tink_aead::aes256_gcm_siv_key_template()),
+        (
+            "AES128_CTR_HMAC_SHA256",
+            tink_aead::aes128_ctr_hmac_sha256_key_template(),
+        ),
+        (
+            "AES256_CTR_HMAC_SHA512",
+            tink_aead::aes256_ctr_hmac_sha512_key_template(),
+        ),
+        (
+            "CHACHA20_POLY1305",
+            tink_aead::cha_cha20_poly1305_key_template(),
+        ),
+        (
+            "XCHACHA20_POLY1305",
+            tink_aead::x_cha_cha20_poly1305_key_template(),
+        ),
+    ];
+    for (name, kt) in templates {
+        if let Err(e) = tink_tests::check_aead_template(&kt) {
+            panic!("{}: {}", name, e);
+        }
+    }
+}
+
+#[test]
+fn test_aead_kit_detects_broken_aead() {
+    // An "AEAD" that ignores associated data must fail the battery.
+    #[derive(Clone)]
+    struct IgnoreAad(tink_aead::subtle::AesGcm);
+    impl Aead for IgnoreAad {
+        fn encrypt(&self, pt: &[u8], _aad: &[u8]) -> Result<Vec<u8>, tink_core::TinkError> {
+            self.0.encrypt(pt, &[])
+        }
+        fn decrypt(&self, ct: &[u8], _aad: &[u8]) -> Result<Vec<u8>, tink_core::TinkError> {
+            self.0.decrypt(ct, &[])
+        }
+    }
+    let a = IgnoreAad(tink_aead::subtle::AesGcm::new(&[0x42; 16]).unwrap());
+    assert!(tink_tests::check_aead_roundtrip(&a).is_ok());
+    tink_tests::expect_err(
+        tink_tests::check_aead_aad_mismatch(&a),
+        "decryption succeeded",
+    );
+}
@@ -0,0 +1,148 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use std::collections::HashSet;
+use tink_core::subtle::random::get_random_bytes;
+use tink_proto::prost::Message;
+use tink_tests::proto_encode;
+
+const KEY_SIZES: &[u32] = &[16, 32];
+
+fn new_key(version: u32, key_size: u32) -> tink_proto::AesGcmCommittingKey {
+    tink_proto::AesGcmCommittingKey {
+        version,
+        key_value: get_random_bytes(key_size as usize),
+    }
+}
+
+fn new_key_format(key_size: u32) -> tink_proto::AesGcmCommittingKeyFormat {
+    tink_proto::AesGcmCommittingKeyFormat {
+        key_size,
+        version: tink_tests::AES_GCM_COMMITTING_KEY_VERSION,
+    }
+}
+
+#[test]
+fn test_aes_gcm_committing_get_primitive_basic() {
+    tink_aead::init();
+    let key_manager = tink_core::registry::get_key_manager(tink_tests::AES_GCM_COMMITTING_TYPE_URL)
+        .expect("cannot obtain committing AES-GCM key manager");
+    assert_eq!(
+        key_manager.type_url(),
+        tink_tests::AES_GCM_COMMITTING_TYPE_URL
+    );
+    assert_eq!(
+        key_manager.key_material_type(),
+        tink_proto::key_data::KeyMaterialType::Symmetric
+    );
+    for key_size in KEY_SIZES {
+        let key = new_key(tink_tests::AES_GCM_COMMITTING_KEY_VERSION, *key_size);
+        let p = key_manager
+            .primitive(&proto_encode(&key))
+            .unwrap()
+            .into_aead()
+            .unwrap();
+        let expected = tink_aead::subtle::AesGcmCommitting::new(&key.key_value).unwrap();
+        let pt = get_random_bytes(32);
+        let aad = get_random_bytes(32);
+        let ct = p.encrypt(&pt, &aad).unwrap();
+        assert_eq!(expected.decrypt(&ct, &aad).unwrap(), pt);
+        let ct = expected.encrypt(&pt, &aad).unwrap();
+        assert_eq!(p.decrypt(&ct, &aad).unwrap(), pt);
+    }
+}
+
+#[test]
+fn test_aes_gcm_committing_get_primitive_with_invalid_input() {
+    tink_aead::init();
+    let key_manager = tink_core::registry::get_key_manager(tink_tests::AES_GCM_COMMITTING_TYPE_URL)
+        .expect("cannot obtain committing AES-GCM key manager");
+    let test_keys = vec![
+        // not an AesGcmCommittingKey
+        proto_encode(&new_key_format(32)),
+        // bad key size
+        proto_encode(&new_key(tink_tests::AES_GCM_COMMITTING_KEY_VERSION, 17)),
+        proto_encode(&new_key(tink_tests::AES_GCM_COMMITTING_KEY_VERSION, 24)),
+        proto_encode(&new_key(tink_tests::AES_GCM_COMMITTING_KEY_VERSION, 33)),
+        // bad version
+        proto_encode(&new_key(tink_tests::AES_GCM_COMMITTING_KEY_VERSION + 1, 16)),
+    ];
+    for (i, serialized_key) in test_keys.iter().enumerate() {
+        assert!(
+            key_manager.primitive(serialized_key).is_err(),
+            "expect an error in test case {}",
+            i
+        );
+    }
+    assert!(
+        key_manager.primitive(&[]).is_err(),
+        "expect an error when input is empty"
+    );
+}
+
+#[test]
+fn test_aes_gcm_committing_new_key_basic() {
+    tink_aead::init();
+    let key_manager = tink_core::registry::get_key_manager(tink_tests::AES_GCM_COMMITTING_TYPE_URL)
+        .expect("cannot obtain committing AES-GCM key manager");
+    let mut keys = HashSet::new();
+    for key_size in KEY_SIZES {
+        let format = new_key_format(*key_size);
+        let serialized_key = key_manager.new_key(&proto_encode(&format)).unwrap();
+        let key = tink_proto::AesGcmCommittingKey::decode(serialized_key.as_ref()).unwrap();
+        assert_eq!(key.version, tink_tests::AES_GCM_COMMITTING_KEY_VERSION);
+        assert_eq!(key.key_value.len(), *key_size as usize);
+        assert!(keys.insert(key.key_value), "key is repeated");
+    }
+}
+
+#[test]
+fn test_aes_gcm_committing_new_key_with_invalid_input() {
+    tink_aead::init();
+    let key_manager = tink_core::registry::get_key_manager(tink_tests::AES_GCM_COMMITTING_TYPE_URL)
+        .expect("cannot obtain committing AES-GCM key manager");
+    for key_size in &[0, 1, 15, 17, 24, 31, 33] {
+        key_manager
+            .new_key(&proto_encode(&new_key_format(*key_size)))
+            .expect_err(&format!("expect an error for key size {}", key_size));
+    }
+    // bad version
+    let mut format = new_key_format(16);
+    format.version = tink_tests::AES_GCM_COMMITTING_KEY_VERSION + 1;
+    tink_tests::expect_err(
+        key_manager.new_key(&proto_encode(&format)),
+        "version in range",
+    );
+    key_manager
+        .new_key(&[])
+        .expect_err("expect an error when input is empty");
+}
+
+#[test]
+fn test_aes_gcm_committing_new_key_data_basic() {
+    tink_aead::init();
+    let key_manager = tink_core::registry::get_key_manager(tink_tests::AES_GCM_COMMITTING_TYPE_URL)
+        .expect("cannot obtain committing AES-GCM key manager");
+    let key_data = key_manager
+        .new_key_data(&proto_encode(&new_key_format(32)))
+        .unwrap();
+    assert_eq!(key_data.type_url, tink_tests::AES_GCM_COMMITTING_TYPE_URL);
+    assert_eq!(
+        key_data.key_material_type,
+        tink_proto::key_data::KeyMaterialType::Symmetric as i32
+    );
+    let _key = tink_proto::AesGcmCommittingKey::decode(key_data.value.as_ref()).unwrap();
+}
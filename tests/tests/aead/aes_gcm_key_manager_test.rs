@@ -19,7 +19,7 @@ use tink_core::{subtle::random::get_random_bytes, TinkError};
 use tink_proto::prost::Message;
 use tink_tests::proto_encode;
 
-const KEY_SIZES: &[u32] = &[16, 32];
+const KEY_SIZES: &[u32] = &[16, 24, 32];
 
 #[test]
 fn test_aes_gcm_get_primitive_basic() {
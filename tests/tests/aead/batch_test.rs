@@ -0,0 +1,173 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use tink_aead::{BatchExecutor, BatchJob, SequentialExecutor, ThreadPoolExecutor};
+use tink_core::Aead;
+
+fn new_handle() -> tink_core::keyset::Handle {
+    tink_aead::init();
+    tink_core::keyset::Handle::new(&tink_aead::aes128_gcm_key_template()).unwrap()
+}
+
+fn records(count: usize) -> Vec<(Vec<u8>, Vec<u8>)> {
+    (0..count)
+        .map(|i| {
+            (
+                format!("record {}", i).into_bytes(),
+                format!("aad {}", i).into_bytes(),
+            )
+        })
+        .collect()
+}
+
+fn as_slices(records: &[(Vec<u8>, Vec<u8>)]) -> Vec<(&[u8], &[u8])> {
+    records
+        .iter()
+        .map(|(data, aad)| (data.as_slice(), aad.as_slice()))
+        .collect()
+}
+
+#[test]
+fn test_batch_round_trip() {
+    let kh = new_handle();
+    let a = tink_aead::new(&kh).unwrap();
+    let input = records(1000);
+    let executors: Vec<Arc<dyn BatchExecutor>> = vec![
+        Arc::new(SequentialExecutor),
+        Arc::new(ThreadPoolExecutor::new(4)),
+    ];
+    for executor in executors {
+        let b = tink_aead::new_batch(&kh, executor).unwrap();
+        let cts = b.encrypt_batch(&as_slices(&input)).unwrap();
+        assert_eq!(cts.len(), input.len());
+
+        // Ciphertexts are in input order, and interoperate with the non-batch primitive.
+        for ((pt, aad), ct) in input.iter().zip(cts.iter()) {
+            assert_eq!(&a.decrypt(ct, aad).unwrap(), pt);
+        }
+        let ct_records: Vec<(&[u8], &[u8])> = cts
+            .iter()
+            .zip(input.iter())
+            .map(|(ct, (_, aad))| (ct.as_slice(), aad.as_slice()))
+            .collect();
+        let pts = b.decrypt_batch(&ct_records);
+        for ((pt, _), result) in input.iter().zip(pts.iter()) {
+            assert_eq!(result.as_ref().unwrap(), pt);
+        }
+    }
+}
+
+#[test]
+fn test_batch_decrypt_reports_failures_per_record() {
+    let kh = new_handle();
+    let a = tink_aead::new(&kh).unwrap();
+    let b = tink_aead::new_batch(&kh, Arc::new(ThreadPoolExecutor::new(3))).unwrap();
+    let cts: Vec<Vec<u8>> = (0..10)
+        .map(|i| {
+            a.encrypt(format!("record {}", i).as_bytes(), b"aad")
+                .unwrap()
+        })
+        .collect();
+    let mut ct_records: Vec<(&[u8], &[u8])> = cts
+        .iter()
+        .map(|ct| (ct.as_slice(), b"aad" as &[u8]))
+        .collect();
+    ct_records[4].1 = b"wrong aad";
+    ct_records[7].0 = b"short";
+
+    let results = b.decrypt_batch(&ct_records);
+    assert_eq!(results.len(), 10);
+    for (i, result) in results.iter().enumerate() {
+        if i == 4 || i == 7 {
+            tink_tests::expect_err(result.as_ref(), "decryption failed");
+        } else {
+            assert_eq!(result.as_ref().unwrap(), format!("record {}", i).as_bytes());
+        }
+    }
+}
+
+#[test]
+fn test_batch_empty() {
+    let kh = new_handle();
+    let b = tink_aead::new_batch(&kh, Arc::new(ThreadPoolExecutor::new(2))).unwrap();
+    assert!(b.encrypt_batch(&[]).unwrap().is_empty());
+    assert!(b.decrypt_batch(&[]).is_empty());
+}
+
+#[test]
+fn test_batch_uses_executor() {
+    /// Executor that runs jobs in reverse order, counting them.
+    struct ReversingExecutor {
+        jobs: AtomicUsize,
+    }
+    impl BatchExecutor for ReversingExecutor {
+        fn run(&self, jobs: Vec<BatchJob>) {
+            for job in jobs.into_iter().rev() {
+                self.jobs.fetch_add(1, Ordering::SeqCst);
+                job();
+            }
+        }
+        fn parallelism(&self) -> usize {
+            8
+        }
+    }
+
+    let kh = new_handle();
+    let executor = Arc::new(ReversingExecutor {
+        jobs: AtomicUsize::new(0),
+    });
+    let b = tink_aead::new_batch(&kh, executor.clone()).unwrap();
+    let input = records(20);
+    let cts = b.encrypt_batch(&as_slices(&input)).unwrap();
+    assert_eq!(executor.jobs.load(Ordering::SeqCst), 7);
+
+    // Results are returned in input order regardless of the order the jobs ran in.
+    let a = tink_aead::new(&kh).unwrap();
+    for ((pt, aad), ct) in input.iter().zip(cts.iter()) {
+        assert_eq!(&a.decrypt(ct, aad).unwrap(), pt);
+    }
+
+    // Fewer records than the executor's parallelism need fewer jobs.
+    b.encrypt_batch(&as_slices(&records(3))).unwrap();
+    assert_eq!(executor.jobs.load(Ordering::SeqCst), 10);
+}
+
+#[test]
+fn test_batch_respects_usage_restrictions() {
+    let kh = new_handle();
+    let b = tink_aead::new_batch(
+        &kh.restrict(tink_core::keyset::KeyUsage::DecryptOnly)
+            .unwrap(),
+        Arc::new(SequentialExecutor),
+    )
+    .unwrap();
+    let input = records(2);
+    tink_tests::expect_err(b.encrypt_batch(&as_slices(&input)), "record 0");
+}
+
+#[test]
+fn test_new_batch_with_invalid_handle() {
+    tink_mac::init();
+    let kh = tink_core::keyset::Handle::new(&tink_mac::hmac_sha256_tag128_key_template()).unwrap();
+    tink_tests::expect_err(
+        tink_aead::new_batch(&kh, Arc::new(SequentialExecutor)),
+        "aead::new_batch",
+    );
+}
@@ -0,0 +1,78 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_aead::{EpochAead, EPOCH_SIZE};
+use tink_core::Aead;
+
+fn new_inner() -> Box<dyn tink_core::Aead> {
+    tink_aead::init();
+    let kh = tink_core::keyset::Handle::new(&tink_aead::aes128_gcm_key_template()).unwrap();
+    tink_aead::new(&kh).unwrap()
+}
+
+#[test]
+fn test_epoch_aead_round_trip() {
+    let a = EpochAead::new(new_inner(), 7);
+    assert_eq!(a.epoch(), 7);
+    let pt = b"archived record";
+    let aad = b"aad";
+    let ct = a.encrypt(pt, aad).unwrap();
+    assert_eq!(&ct[..EPOCH_SIZE], &7u64.to_le_bytes());
+    assert_eq!(EpochAead::ciphertext_epoch(&ct).unwrap(), 7);
+    assert_eq!(a.decrypt(&ct, aad).unwrap(), pt);
+    assert_eq!(a.decrypt_with_epoch(&ct, aad).unwrap(), (pt.to_vec(), 7));
+    assert!(a.decrypt(&ct, b"other aad").is_err());
+}
+
+#[test]
+fn test_epoch_aead_binds_epoch() {
+    let inner = new_inner();
+    let a = EpochAead::new(inner.box_clone(), 3);
+    let ct = a.encrypt(b"data", b"").unwrap();
+
+    // Changing the stored epoch breaks authentication.
+    let mut modified = ct.clone();
+    modified[..EPOCH_SIZE].copy_from_slice(&4u64.to_le_bytes());
+    tink_tests::expect_err(a.decrypt(&modified, b""), "decryption failed");
+
+    // The wrapped ciphertext uses the epoch as an associated data suffix.
+    let mut aad = Vec::new();
+    aad.extend_from_slice(&3u64.to_le_bytes());
+    assert_eq!(inner.decrypt(&ct[EPOCH_SIZE..], &aad).unwrap(), b"data");
+}
+
+#[test]
+fn test_epoch_aead_min_epoch() {
+    let inner = new_inner();
+    let old = EpochAead::new(inner.box_clone(), 1);
+    let current = EpochAead::new(inner, 2).with_min_epoch(2);
+    let old_ct = old.encrypt(b"data", b"").unwrap();
+    tink_tests::expect_err(
+        current.decrypt(&old_ct, b""),
+        "ciphertext epoch 1 is older than minimum epoch 2",
+    );
+
+    let ct = current.encrypt_with_epoch(b"data", b"", 5).unwrap();
+    assert_eq!(current.decrypt_with_epoch(&ct, b"").unwrap().1, 5);
+    assert_eq!(old.decrypt(&ct, b"").unwrap(), b"data");
+}
+
+#[test]
+fn test_epoch_aead_short_ciphertext() {
+    let a = EpochAead::new(new_inner(), 0);
+    tink_tests::expect_err(a.decrypt(&[0; EPOCH_SIZE - 1], b""), "ciphertext too short");
+    assert!(a.decrypt(&[0; EPOCH_SIZE], b"").is_err());
+}
@@ -0,0 +1,364 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use std::{collections::BTreeMap, sync::Once};
+use tink_aead::esdk::{self, AlgorithmSuite};
+use tink_core::{registry::KmsClient, TinkError};
+use tink_tests::{expect_err, fakekms};
+
+const PLAINTEXT: &[u8] = b"The quick brown fox jumps over the lazy dog.";
+/// Key URI of the data key in the test messages.
+const STATIC_KEY_URI: &str = "test-static://key-1";
+
+// Messages built independently from the published message format specification, with a
+// frame length of 16, encryption context `{"purpose": "test"}`, and a single data key held
+// in the clear by a `test-static` key provider.
+const V1_NON_FRAMED_AES128_NO_KDF: &str = concat!(
+    "01800014404142434445464748494a4b4c4d4e4f001100010007707572706f73650004746573740001000b746573742d",
+    "73746174696300056b65792d310010000102030405060708090a0b0c0d0e0f01000000000c0000000000000000000000",
+    "0000000000cb757c29b9b49d8dca49f7ca49fb7cf5000000000000000000000001000000000000002ceebdca43bc9ca3",
+    "4d2564268e4cd26004bad8111b9d226aa7cdf076bc4fa0854621c696bb5e78c59b250c2debd1f3a45c1a0e9b6a30fb09",
+    "cd68380851",
+);
+
+const V1_FRAMED_AES256_HKDF_SHA256: &str = concat!(
+    "01800178404142434445464748494a4b4c4d4e4f001100010007707572706f73650004746573740001000b746573742d",
+    "73746174696300056b65792d310020000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f02",
+    "000000000c00000010000000000000000000000000c3981430b9a697be2bd19fa868041b010000000100000000000000",
+    "0000000001224efadfd392c641bfdb202ad34fd810a34a98bc430d2bec79cdfa73145fd4910000000200000000000000",
+    "0000000002f5865825cfdd78bff9739f23f8eb18f3541288cb759481c0129f88a6a1b0c621ffffffff00000003000000",
+    "0000000000000000030000000c3137040681c8840ade55ecb91a59bcbbcbce832b3fbba317ec395525",
+);
+
+const V1_FRAMED_AES128_HKDF_SHA256_ECDSA_P256: &str = concat!(
+    "01800214404142434445464748494a4b4c4d4e4f0056000200156177732d63727970746f2d7075626c69632d6b657900",
+    "2c416f35544f322b67763374474a6273775a6e77422b32422b2b66693471412f76577a41474b48417868374b6a000770",
+    "7572706f73650004746573740001000b746573742d73746174696300056b65792d310010000102030405060708090a0b",
+    "0c0d0e0f02000000000c00000010000000000000000000000000c52ca8f5d4c9074b089f995b2c95e4d4000000010000",
+    "00000000000000000001d07d2fa9267fab2733c532970a2c6e1d2a0d35dc4f433469672b8aea307b3d93000000020000",
+    "000000000000000000021e50a9f816a997a4965b370f8a9e57ce0ced0fe396bd2d42c2e4797d41d09c14ffffffff0000",
+    "00030000000000000000000000030000000cd766edf106fa0a1273190b795e63decaff22c7f6d6385b569b146e440048",
+    "3046022100b02a0c13a1365761c6ffa15ee8817a0110267ff7be7680718a3f38a0799b9cc6022100ae25f4bd2d85bd8b",
+    "0f1c11905143e479578fe81f62c812e47f869b05ddd585e8",
+);
+
+const V2_FRAMED_AES256_COMMIT_KEY_ECDSA_P384: &str = concat!(
+    "020578404142434445464748494a4b4c4d4e4f505152535455565758595a5b5c5d5e5f006e000200156177732d637279",
+    "70746f2d7075626c69632d6b657900444169673848584e6c7a6b654938702b4f76794e4f332f3674622b6d582b2b7066",
+    "2b6931597a4a333665787851697756536231573536374945447757306a37625134513d3d0007707572706f7365000474",
+    "6573740001000b746573742d73746174696300056b65792d310020000102030405060708090a0b0c0d0e0f1011121314",
+    "15161718191a1b1c1d1e1f02000000104fc8f3b078da9a1778fd776cf39f21e62e3c512006129bc6f16a06f8ccdead3a",
+    "196fc81ed282710c486e9e39abb39cb200000001000000000000000000000001c4827d0d93859e9d68db59871c2fe7ba",
+    "1f5ef4737282c5237d0a18e74227ceb000000002000000000000000000000002db490265c56e925535dd89b0a02179a7",
+    "4719a967340a4765632443e22da919faffffffff000000030000000000000000000000030000000cac4ea519b3b84607",
+    "2ecaa8cec43d559ef40383b627aa3d95e089c8a100683066023100b90f9350acc44fdc64bffdf405bb84a6d5f824cc86",
+    "e05110752868a132ff90ec7d03db71d5c82b659ebe6a86c854ea42023100aa60b34b6232094f3750f964a07c544c2518",
+    "0db6393b87c50b4763f0617f48b6f2559440474cdeee9b3ff9e69b455142",
+);
+
+/// Offset of the key commitment in the version 2 test message.
+const V2_COMMITMENT_OFFSET: usize = 208;
+
+/// KMS client for `test-static://` key URIs, whose "encrypted" data keys are held in the
+/// clear.  It only accepts the encryption context used by the test messages.
+struct StaticKeyClient;
+
+impl KmsClient for StaticKeyClient {
+    fn supported(&self, key_uri: &str) -> bool {
+        key_uri.starts_with("test-static://")
+    }
+
+    fn get_aead(&self, _key_uri: &str) -> Result<Box<dyn tink_core::Aead>, TinkError> {
+        Err("encryption context required".into())
+    }
+
+    fn get_aead_with_context(
+        &self,
+        _key_uri: &str,
+        context: &BTreeMap<String, String>,
+    ) -> Result<Box<dyn tink_core::Aead>, TinkError> {
+        if context.get("purpose").map(String::as_str) != Some("test") {
+            return Err("unexpected encryption context".into());
+        }
+        Ok(Box::new(PassthroughAead))
+    }
+}
+
+#[derive(Clone)]
+struct PassthroughAead;
+
+impl tink_core::Aead for PassthroughAead {
+    fn encrypt(&self, plaintext: &[u8], _additional_data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        Ok(plaintext.to_vec())
+    }
+
+    fn decrypt(&self, ciphertext: &[u8], _additional_data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        Ok(ciphertext.to_vec())
+    }
+}
+
+static INIT: Once = Once::new();
+
+fn setup() {
+    INIT.call_once(|| {
+        tink_aead::init();
+        tink_core::registry::register_kms_client(StaticKeyClient);
+        tink_core::registry::register_kms_client(fakekms::FakeClient::new("fake-kms://").unwrap());
+    });
+}
+
+fn test_context() -> BTreeMap<String, String> {
+    let mut context = BTreeMap::new();
+    context.insert("purpose".to_string(), "test".to_string());
+    context
+}
+
+fn vectors() -> Vec<(&'static str, AlgorithmSuite, Vec<u8>)> {
+    vec![
+        (
+            "v1-non-framed",
+            AlgorithmSuite::Aes128GcmIv12Tag16NoKdf,
+            hex::decode(V1_NON_FRAMED_AES128_NO_KDF).unwrap(),
+        ),
+        (
+            "v1-framed",
+            AlgorithmSuite::Aes256GcmIv12Tag16HkdfSha256,
+            hex::decode(V1_FRAMED_AES256_HKDF_SHA256).unwrap(),
+        ),
+        (
+            "v1-signed",
+            AlgorithmSuite::Aes128GcmIv12Tag16HkdfSha256EcdsaP256,
+            hex::decode(V1_FRAMED_AES128_HKDF_SHA256_ECDSA_P256).unwrap(),
+        ),
+        (
+            "v2-signed",
+            AlgorithmSuite::Aes256GcmHkdfSha512CommitKeyEcdsaP384,
+            hex::decode(V2_FRAMED_AES256_COMMIT_KEY_ECDSA_P384).unwrap(),
+        ),
+    ]
+}
+
+#[test]
+fn test_decrypt_vectors() {
+    setup();
+    for (name, suite, message) in vectors() {
+        let result = esdk::decrypt(&[STATIC_KEY_URI], &message)
+            .unwrap_or_else(|e| panic!("{}: {:?}", name, e));
+        assert_eq!(result.plaintext, PLAINTEXT, "{}", name);
+        assert_eq!(result.algorithm_suite, suite, "{}", name);
+        assert_eq!(result.encryption_context["purpose"], "test", "{}", name);
+        assert_eq!(
+            result
+                .encryption_context
+                .contains_key(esdk::PUBLIC_KEY_CONTEXT_KEY),
+            name.ends_with("signed"),
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_decrypt_modified_vectors() {
+    setup();
+    for (name, _suite, message) in vectors() {
+        for i in 0..message.len() {
+            let mut modified = message.clone();
+            modified[i] ^= 0x01;
+            assert!(
+                esdk::decrypt(&[STATIC_KEY_URI], &modified).is_err(),
+                "{}: modified byte {} accepted",
+                name,
+                i
+            );
+        }
+        for len in 0..message.len() {
+            assert!(
+                esdk::decrypt(&[STATIC_KEY_URI], &message[..len]).is_err(),
+                "{}: truncated message of length {} accepted",
+                name,
+                len
+            );
+        }
+        let mut extended = message.clone();
+        extended.push(0);
+        expect_err(esdk::decrypt(&[STATIC_KEY_URI], &extended), "trailing data");
+    }
+}
+
+#[test]
+fn test_decrypt_commitment_mismatch() {
+    setup();
+    let mut message = hex::decode(V2_FRAMED_AES256_COMMIT_KEY_ECDSA_P384).unwrap();
+    message[V2_COMMITMENT_OFFSET] ^= 0x01;
+    expect_err(
+        esdk::decrypt(&[STATIC_KEY_URI], &message),
+        "key commitment mismatch",
+    );
+}
+
+#[test]
+fn test_decrypt_unknown_key_provider() {
+    setup();
+    let message = hex::decode(V1_FRAMED_AES256_HKDF_SHA256).unwrap();
+    let provider = b"test-static";
+    let pos = message
+        .windows(provider.len())
+        .position(|w| w == provider)
+        .unwrap();
+    let mut modified = message.clone();
+    modified[pos..pos + provider.len()].copy_from_slice(b"test-absent");
+    expect_err(
+        esdk::decrypt(&[STATIC_KEY_URI, "test-absent://key-1"], &modified),
+        "failed to decrypt data key",
+    );
+}
+
+#[test]
+fn test_decrypt_key_uri_not_allowed() {
+    setup();
+    let message = hex::decode(V1_FRAMED_AES256_HKDF_SHA256).unwrap();
+    expect_err(esdk::decrypt(&[], &message), "not allowed");
+    expect_err(
+        esdk::decrypt(&["test-static://key-2"], &message),
+        "test-static://key-1 not allowed",
+    );
+}
+
+#[test]
+fn test_decrypt_unexpected_frame_iv() {
+    setup();
+    let message = hex::decode(V1_FRAMED_AES256_HKDF_SHA256).unwrap();
+    // The first frame starts with its sequence number, followed by an IV of the same value.
+    let mut first_frame = vec![0, 0, 0, 1];
+    first_frame.extend_from_slice(&[0; 11]);
+    first_frame.push(1);
+    let pos = message
+        .windows(first_frame.len())
+        .position(|w| w == first_frame.as_slice())
+        .unwrap();
+    let mut modified = message.clone();
+    modified[pos + first_frame.len() - 1] = 2;
+    expect_err(
+        esdk::decrypt(&[STATIC_KEY_URI], &modified),
+        "unexpected frame IV",
+    );
+}
+
+#[test]
+fn test_encrypt_decrypt() {
+    setup();
+    let key_uri = fakekms::new_key_uri().unwrap();
+    let suites = [
+        AlgorithmSuite::Aes256GcmHkdfSha512CommitKey,
+        AlgorithmSuite::Aes256GcmHkdfSha512CommitKeyEcdsaP384,
+    ];
+    let frame = esdk::FRAME_LENGTH as usize;
+    for suite in &suites {
+        for len in &[0, 1, frame - 1, frame, frame + 1, 3 * frame + 7] {
+            let plaintext = vec![0xab; *len];
+            let message = esdk::encrypt(&[key_uri.as_str()], &plaintext, &test_context(), *suite)
+                .unwrap_or_else(|e| panic!("{:?} len {}: {:?}", suite, len, e));
+            let result = esdk::decrypt(&[key_uri.as_str()], &message).unwrap();
+            assert_eq!(result.plaintext, plaintext, "{:?} len {}", suite, len);
+            assert_eq!(result.algorithm_suite, *suite);
+            assert_eq!(result.encryption_context["purpose"], "test");
+        }
+    }
+}
+
+#[test]
+fn test_encrypt_multiple_keys() {
+    setup();
+    let key_uri = fakekms::new_key_uri().unwrap();
+    let message = esdk::encrypt(
+        &[key_uri.as_str(), "test-static://key-1"],
+        PLAINTEXT,
+        &test_context(),
+        AlgorithmSuite::Aes256GcmHkdfSha512CommitKey,
+    )
+    .unwrap();
+    for allowed in &[key_uri.as_str(), STATIC_KEY_URI] {
+        assert_eq!(
+            esdk::decrypt(&[*allowed], &message).unwrap().plaintext,
+            PLAINTEXT
+        );
+    }
+    expect_err(
+        esdk::decrypt(&["test-static://key-2"], &message),
+        "not allowed",
+    );
+}
+
+#[test]
+fn test_encrypt_invalid() {
+    setup();
+    let key_uri = fakekms::new_key_uri().unwrap();
+    expect_err(
+        esdk::encrypt(
+            &[key_uri.as_str()],
+            PLAINTEXT,
+            &test_context(),
+            AlgorithmSuite::Aes256GcmIv12Tag16HkdfSha256,
+        ),
+        "key-committing",
+    );
+    expect_err(
+        esdk::encrypt(
+            &[],
+            PLAINTEXT,
+            &test_context(),
+            AlgorithmSuite::Aes256GcmHkdfSha512CommitKey,
+        ),
+        "no key URIs",
+    );
+    expect_err(
+        esdk::encrypt(
+            &["no-scheme"],
+            PLAINTEXT,
+            &test_context(),
+            AlgorithmSuite::Aes256GcmHkdfSha512CommitKey,
+        ),
+        "has no scheme",
+    );
+    let mut context = test_context();
+    context.insert(esdk::PUBLIC_KEY_CONTEXT_KEY.to_string(), "AAAA".to_string());
+    expect_err(
+        esdk::encrypt(
+            &[key_uri.as_str()],
+            PLAINTEXT,
+            &context,
+            AlgorithmSuite::Aes256GcmHkdfSha512CommitKey,
+        ),
+        "reserved key",
+    );
+}
+
+#[test]
+fn test_algorithm_suite_ids() {
+    for id in 0..=0xffffu16 {
+        if let Some(suite) = AlgorithmSuite::from_id(id) {
+            assert_eq!(suite.id(), id);
+        }
+    }
+    assert_eq!(
+        AlgorithmSuite::from_id(0x0578),
+        Some(AlgorithmSuite::Aes256GcmHkdfSha512CommitKeyEcdsaP384)
+    );
+    assert_eq!(AlgorithmSuite::Aes192GcmIv12Tag16HkdfSha256.key_size(), 24);
+    assert!(!AlgorithmSuite::Aes256GcmIv12Tag16NoKdf.is_committing());
+    assert_eq!(AlgorithmSuite::from_id(0x0001), None);
+}
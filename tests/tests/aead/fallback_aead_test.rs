@@ -0,0 +1,93 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_aead::FallbackAead;
+use tink_core::Aead;
+use tink_tests::expect_err;
+
+fn new_handle() -> tink_core::keyset::Handle {
+    tink_aead::init();
+    tink_core::keyset::Handle::new(&tink_aead::aes128_gcm_key_template()).unwrap()
+}
+
+#[test]
+fn test_fallback_aead_reports_keyset() {
+    let (current, previous, oldest) = (new_handle(), new_handle(), new_handle());
+    let a = FallbackAead::from_handles(&current, &[previous.clone(), oldest.clone()]).unwrap();
+    assert_eq!(a.keyset_count(), 3);
+    let aad = b"aad";
+
+    let ct = a.encrypt(b"new", aad).unwrap();
+    assert_eq!(
+        a.decrypt_with_keyset_index(&ct, aad).unwrap(),
+        (b"new".to_vec(), 0)
+    );
+    let ct = tink_aead::new(&previous)
+        .unwrap()
+        .encrypt(b"old", aad)
+        .unwrap();
+    assert_eq!(
+        a.decrypt_with_keyset_index(&ct, aad).unwrap(),
+        (b"old".to_vec(), 1)
+    );
+    let ct = tink_aead::new(&oldest)
+        .unwrap()
+        .encrypt(b"oldest", aad)
+        .unwrap();
+    assert_eq!(a.decrypt(&ct, aad).unwrap(), b"oldest");
+    assert_eq!(a.clone().decrypt_with_keyset_index(&ct, aad).unwrap().1, 2);
+}
+
+#[test]
+fn test_fallback_aead_encrypts_with_primary() {
+    let (current, previous) = (new_handle(), new_handle());
+    let a = FallbackAead::from_handles(&current, &[previous]).unwrap();
+    let ct = a.encrypt(b"data", b"").unwrap();
+    assert_eq!(
+        tink_aead::new(&current).unwrap().decrypt(&ct, b"").unwrap(),
+        b"data"
+    );
+}
+
+#[test]
+fn test_fallback_aead_all_fail() {
+    let a = FallbackAead::from_handles(&new_handle(), &[new_handle()]).unwrap();
+    let ct = tink_aead::new(&new_handle())
+        .unwrap()
+        .encrypt(b"data", b"")
+        .unwrap();
+    expect_err(a.decrypt(&ct, b""), "decryption failed with all 2 keysets");
+    let ct = a.encrypt(b"data", b"aad").unwrap();
+    expect_err(a.decrypt(&ct, b"other"), "decryption failed");
+}
+
+#[test]
+fn test_fallback_aead_no_fallbacks() {
+    let a = FallbackAead::from_handles(&new_handle(), &[]).unwrap();
+    assert_eq!(a.keyset_count(), 1);
+    let ct = a.encrypt(b"data", b"").unwrap();
+    assert_eq!(a.decrypt_with_keyset_index(&ct, b"").unwrap().1, 0);
+}
+
+#[test]
+fn test_fallback_aead_invalid_keyset() {
+    tink_mac::init();
+    let mac = tink_core::keyset::Handle::new(&tink_mac::hmac_sha256_tag128_key_template()).unwrap();
+    expect_err(
+        FallbackAead::from_handles(&new_handle(), &[mac]),
+        "invalid fallback keyset 0",
+    );
+}
@@ -14,6 +14,8 @@
 //
 ////////////////////////////////////////////////////////////////////////////////
 
+use tink_core::Aead;
+
 fn create_kms_envelope_aead() -> Box<dyn tink_core::Aead> {
     let kh = tink_core::keyset::Handle::new(&tink_aead::aes256_gcm_key_template())
         .expect("failed to create new handle");
@@ -63,3 +65,232 @@ fn test_kms_envelope_short_ciphertext() {
     let result = a.decrypt(&[0, 0, 0, 3, 1], &[]); // length of 3, only 1 byte available
     tink_tests::expect_err(result, "invalid ciphertext");
 }
+
+/// AEAD that records the additional data passed to it, standing in for a remote KMS.
+#[derive(Clone)]
+struct RecordingAead {
+    inner: Box<dyn tink_core::Aead>,
+    seen: std::sync::Arc<std::sync::Mutex<Vec<Vec<u8>>>>,
+}
+
+impl tink_core::Aead for RecordingAead {
+    fn encrypt(&self, pt: &[u8], aad: &[u8]) -> Result<Vec<u8>, tink_core::TinkError> {
+        self.seen.lock().unwrap().push(aad.to_vec());
+        self.inner.encrypt(pt, aad)
+    }
+
+    fn decrypt(&self, ct: &[u8], aad: &[u8]) -> Result<Vec<u8>, tink_core::TinkError> {
+        self.seen.lock().unwrap().push(aad.to_vec());
+        self.inner.decrypt(ct, aad)
+    }
+}
+
+#[test]
+fn test_kms_envelope_associated_data_modes() {
+    tink_aead::init();
+    let kh = tink_core::keyset::Handle::new(&tink_aead::aes256_gcm_key_template()).unwrap();
+    let aad = b"tenant=42";
+    let mut hash_func = tink_core::subtle::get_hash_func(tink_proto::HashType::Sha256).unwrap();
+    let digest = tink_core::subtle::compute_hash(&mut hash_func, aad).unwrap();
+
+    for (mode, want) in &[
+        (tink_aead::KmsAssociatedData::Omit, vec![]),
+        (tink_aead::KmsAssociatedData::Forward, aad.to_vec()),
+        (tink_aead::KmsAssociatedData::Sha256, digest),
+    ] {
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let remote = RecordingAead {
+            inner: tink_aead::new(&kh).unwrap(),
+            seen: seen.clone(),
+        };
+        let a =
+            tink_aead::KmsEnvelopeAead::new(tink_aead::aes256_gcm_key_template(), Box::new(remote))
+                .with_associated_data(*mode);
+
+        let ct = a.encrypt(b"hello world", aad).unwrap();
+        assert_eq!(a.decrypt(&ct, aad).unwrap(), b"hello world");
+        assert!(a.decrypt(&ct, b"tenant=43").is_err());
+        let seen = seen.lock().unwrap();
+        assert_eq!(&seen[0], want, "{:?}: encrypt", mode);
+        assert_eq!(&seen[1], want, "{:?}: decrypt", mode);
+    }
+}
+
+#[test]
+fn test_kms_envelope_associated_data_mismatch() {
+    tink_aead::init();
+    let kh = tink_core::keyset::Handle::new(&tink_aead::aes256_gcm_key_template()).unwrap();
+    let plain = tink_aead::KmsEnvelopeAead::new(
+        tink_aead::aes256_gcm_key_template(),
+        tink_aead::new(&kh).unwrap(),
+    );
+    let forwarding = plain
+        .clone()
+        .with_associated_data(tink_aead::KmsAssociatedData::Forward);
+
+    // With forwarding, the wrapped DEK is bound to the associated data, so ciphertexts are not
+    // interchangeable between the two settings.
+    let ct = plain.encrypt(b"data", b"aad").unwrap();
+    assert!(forwarding.decrypt(&ct, b"aad").is_err());
+    let ct = forwarding.encrypt(b"data", b"aad").unwrap();
+    assert!(plain.decrypt(&ct, b"aad").is_err());
+    assert_eq!(forwarding.decrypt(&ct, b"aad").unwrap(), b"data");
+
+    // Empty associated data is forwarded as empty, matching the default format.
+    let ct = plain.encrypt(b"data", &[]).unwrap();
+    assert_eq!(forwarding.decrypt(&ct, &[]).unwrap(), b"data");
+}
+
+#[test]
+fn test_kms_envelope_factory_with_associated_data() {
+    tink_aead::init();
+    let key_uri = tink_tests::fakekms::new_key_uri().unwrap();
+    tink_core::registry::register_kms_client(
+        tink_tests::fakekms::FakeClient::new(&key_uri).unwrap(),
+    );
+    let kh = tink_core::keyset::Handle::new(&tink_aead::kms_envelope_aead_key_template(
+        &key_uri,
+        tink_aead::aes128_gcm_key_template(),
+    ))
+    .unwrap();
+
+    let default_aead = tink_aead::new(&kh).unwrap();
+    let forwarding_aead =
+        tink_aead::new_with_kms_associated_data(&kh, tink_aead::KmsAssociatedData::Sha256).unwrap();
+    let ct = forwarding_aead.encrypt(b"data", b"aad").unwrap();
+    assert_eq!(forwarding_aead.decrypt(&ct, b"aad").unwrap(), b"data");
+    assert!(default_aead.decrypt(&ct, b"aad").is_err());
+    assert!(forwarding_aead.decrypt(&ct, b"other").is_err());
+
+    // The default setting behaves as the plain factory.
+    let aead =
+        tink_aead::new_with_kms_associated_data(&kh, tink_aead::KmsAssociatedData::Omit).unwrap();
+    let ct = default_aead.encrypt(b"data", b"aad").unwrap();
+    assert_eq!(aead.decrypt(&ct, b"aad").unwrap(), b"data");
+}
+
+#[test]
+fn test_kms_envelope_factory_associated_data_unsupported() {
+    tink_aead::init();
+    tink_core::registry::register_kms_client(tink_tests::DummyKmsClient);
+    let kh = tink_core::keyset::Handle::new(&tink_aead::kms_envelope_aead_key_template(
+        "dummy",
+        tink_aead::aes128_gcm_key_template(),
+    ))
+    .unwrap();
+    assert!(tink_aead::new(&kh).is_ok());
+    tink_tests::expect_err(
+        tink_aead::new_with_kms_associated_data(&kh, tink_aead::KmsAssociatedData::Forward),
+        "does not support associated data",
+    );
+}
+
+fn aes256_gcm_only() -> tink_aead::KmsEnvelopeDekPolicy {
+    tink_aead::KmsEnvelopeDekPolicy::new(&[tink_aead::aes256_gcm_key_template()]).unwrap()
+}
+
+#[test]
+fn test_kms_envelope_dek_policy() {
+    tink_aead::init();
+    let kh = tink_core::keyset::Handle::new(&tink_aead::aes256_gcm_key_template()).unwrap();
+    let parent_aead = tink_aead::new(&kh).unwrap();
+
+    // The policy is checked against the DEK template on construction.
+    let result = tink_aead::KmsEnvelopeAead::new(
+        tink_aead::aes128_gcm_key_template(),
+        parent_aead.box_clone(),
+    )
+    .with_dek_policy(aes256_gcm_only());
+    tink_tests::expect_err(result, "DEK template not permitted");
+    let result = tink_aead::KmsEnvelopeAead::new(
+        tink_aead::aes256_gcm_siv_key_template(),
+        parent_aead.box_clone(),
+    )
+    .with_dek_policy(aes256_gcm_only());
+    tink_tests::expect_err(result, "DEK template not permitted");
+
+    // Output prefix types are ignored.
+    let a = tink_aead::KmsEnvelopeAead::new(
+        tink_aead::aes256_gcm_no_prefix_key_template(),
+        parent_aead.box_clone(),
+    )
+    .with_dek_policy(aes256_gcm_only())
+    .unwrap();
+    let ct = a.encrypt(b"plaintext", b"aad").unwrap();
+    assert_eq!(a.decrypt(&ct, b"aad").unwrap(), b"plaintext");
+    let a = a.box_clone();
+    assert_eq!(a.decrypt(&ct, b"aad").unwrap(), b"plaintext");
+}
+
+#[test]
+fn test_kms_envelope_dek_policy_rejects_foreign_dek() {
+    tink_aead::init();
+    let kh = tink_core::keyset::Handle::new(&tink_aead::aes256_gcm_key_template()).unwrap();
+    let parent_aead = tink_aead::new(&kh).unwrap();
+
+    // A ciphertext whose wrapped DEK is an AES-128-GCM key.
+    let foreign = tink_aead::KmsEnvelopeAead::new(
+        tink_aead::aes128_gcm_key_template(),
+        parent_aead.box_clone(),
+    );
+    let ct = foreign.encrypt(b"plaintext", b"aad").unwrap();
+
+    // Without a policy, the DEK is accepted as it has the template's key type.
+    let unrestricted = tink_aead::KmsEnvelopeAead::new(
+        tink_aead::aes256_gcm_key_template(),
+        parent_aead.box_clone(),
+    );
+    assert_eq!(unrestricted.decrypt(&ct, b"aad").unwrap(), b"plaintext");
+
+    // With a policy, it is rejected.
+    let restricted = unrestricted.with_dek_policy(aes256_gcm_only()).unwrap();
+    tink_tests::expect_err(restricted.decrypt(&ct, b"aad"), "DEK not permitted");
+
+    // As is a DEK of a different key type.
+    let foreign = tink_aead::KmsEnvelopeAead::new(
+        tink_aead::aes256_ctr_hmac_sha256_key_template(),
+        parent_aead.box_clone(),
+    );
+    let ct = foreign.encrypt(b"plaintext", b"aad").unwrap();
+    assert!(restricted.decrypt(&ct, b"aad").is_err());
+}
+
+#[test]
+fn test_kms_envelope_dek_policy_templates() {
+    tink_aead::init();
+    let policy = tink_aead::KmsEnvelopeDekPolicy::new(&[
+        tink_aead::aes256_gcm_key_template(),
+        tink_aead::aes256_ctr_hmac_sha256_key_template(),
+        tink_aead::cha_cha20_poly1305_key_template(),
+    ])
+    .unwrap();
+    policy
+        .check_template(&tink_aead::aes256_gcm_key_template())
+        .unwrap();
+    policy
+        .check_template(&tink_aead::aes256_ctr_hmac_sha256_key_template())
+        .unwrap();
+    policy
+        .check_template(&tink_aead::cha_cha20_poly1305_key_template())
+        .unwrap();
+    tink_tests::expect_err(
+        policy.check_template(&tink_aead::aes128_ctr_hmac_sha256_key_template()),
+        "not in the allowlist",
+    );
+    tink_tests::expect_err(
+        policy.check_template(&tink_aead::x_cha_cha20_poly1305_key_template()),
+        "not in the allowlist",
+    );
+
+    // Only AEAD key types can be used as DEKs.
+    tink_tests::expect_err(
+        tink_aead::KmsEnvelopeDekPolicy::new(&[tink_mac::hmac_sha256_tag256_key_template()]),
+        "unsupported DEK type",
+    );
+    let mut invalid = tink_aead::aes256_gcm_key_template();
+    invalid.value = vec![0xff];
+    tink_tests::expect_err(
+        tink_aead::KmsEnvelopeDekPolicy::new(&[invalid]),
+        "invalid key format",
+    );
+}
@@ -163,3 +163,28 @@ fn test_kms_envelope_key_manager_params() {
     );
     assert!(!key_manager.supports_private_keys());
 }
+
+#[test]
+fn test_kms_envelope_get_primitive_with_dek_policy() {
+    tink_aead::init();
+
+    let key_uri = "aws-kms://arn:aws:kms:us-east-2:1234:key/abcd-1234";
+    let ini_file = std::path::PathBuf::from("testdata/credentials_aws.ini");
+    let g = tink_awskms::AwsClient::new_with_credentials(key_uri, &ini_file).unwrap();
+    tink_core::registry::register_kms_client(g);
+
+    let dek = tink_aead::aes128_ctr_hmac_sha256_key_template();
+    let kh =
+        tink_core::keyset::Handle::new(&tink_aead::kms_envelope_aead_key_template(key_uri, dek))
+            .expect("error getting a new keyset handle");
+
+    let policy =
+        tink_aead::KmsEnvelopeDekPolicy::new(&[tink_aead::aes128_ctr_hmac_sha256_key_template()])
+            .unwrap();
+    let _a = tink_aead::new_with_kms_dek_policy(&kh, policy).expect("error getting the primitive");
+
+    let policy =
+        tink_aead::KmsEnvelopeDekPolicy::new(&[tink_aead::aes256_gcm_key_template()]).unwrap();
+    let result = tink_aead::new_with_kms_dek_policy(&kh, policy);
+    tink_tests::expect_err(result, "DEK template not permitted");
+}
@@ -16,13 +16,21 @@
 
 mod aead_factory_test;
 mod aead_key_templates_test;
+mod aeadkit_test;
 mod aes_ctr_hmac_aead_key_manager_test;
+mod aes_gcm_committing_key_manager_test;
 mod aes_gcm_key_manager_test;
 mod aes_gcm_siv_key_manager_test;
+mod batch_test;
 mod chacha20poly1305_key_manager_test;
+mod epoch_aead_test;
+mod esdk_test;
+mod fallback_aead_test;
+mod fixtures_test;
 mod integration_test;
 mod kms_envelope_aead_test;
 mod kms_envelope_key_manager_test;
+mod prefix_migration_test;
 mod xchacha20poly1305_key_manager_test;
 
 mod subtle;
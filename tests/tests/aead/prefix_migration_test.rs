@@ -0,0 +1,181 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_aead::{CiphertextPrefix, PrefixMigrationStats, PrefixMigrator};
+use tink_core::keyset::{insecure, Handle, KeysetBuilder, KeysetEntry};
+use tink_proto::OutputPrefixType;
+use tink_tests::expect_err;
+
+const AAD: &[u8] = b"associated data";
+
+fn template(prefix: OutputPrefixType) -> tink_proto::KeyTemplate {
+    let mut kt = tink_aead::aes128_gcm_key_template();
+    kt.output_prefix_type = prefix as i32;
+    kt
+}
+
+/// Return a keyset with LEGACY (ID 1), CRUNCHY (2), RAW (3) and TINK (4, primary) keys.
+fn migration_keyset() -> Handle {
+    tink_aead::init();
+    KeysetBuilder::new()
+        .add_entry(KeysetEntry::from_template(&template(OutputPrefixType::Legacy)).with_fixed_id(1))
+        .add_entry(
+            KeysetEntry::from_template(&template(OutputPrefixType::Crunchy)).with_fixed_id(2),
+        )
+        .add_entry(KeysetEntry::from_template(&template(OutputPrefixType::Raw)).with_fixed_id(3))
+        .add_entry(
+            KeysetEntry::from_template(&template(OutputPrefixType::Tink))
+                .with_fixed_id(4)
+                .make_primary(),
+        )
+        .build()
+        .unwrap()
+}
+
+/// Encrypt `plaintext` under the key with the given ID alone.
+fn encrypt_with_key(h: &Handle, key_id: u32, plaintext: &[u8]) -> Vec<u8> {
+    let ks = insecure::keyset_material(h);
+    let key = ks.key.iter().find(|k| k.key_id == key_id).unwrap();
+    let single = KeysetBuilder::new()
+        .add_entry(
+            KeysetEntry::from_key_data(
+                key.key_data.clone().unwrap(),
+                key.try_output_prefix_type().unwrap(),
+            )
+            .with_fixed_id(key_id)
+            .make_primary(),
+        )
+        .build()
+        .unwrap();
+    tink_aead::new(&single)
+        .unwrap()
+        .encrypt(plaintext, AAD)
+        .unwrap()
+}
+
+#[test]
+fn test_prefix_migration() {
+    let h = migration_keyset();
+    let migrator = PrefixMigrator::new(&h).unwrap();
+    let aead = tink_aead::new(&h).unwrap();
+
+    for (key_id, want_source) in &[
+        (1, CiphertextPrefix::Legacy),
+        (2, CiphertextPrefix::Crunchy),
+        (3, CiphertextPrefix::Raw),
+        (4, CiphertextPrefix::Tink),
+    ] {
+        let plaintext = format!("message for key {}", key_id);
+        let ct = encrypt_with_key(&h, *key_id, plaintext.as_bytes());
+        let migrated = migrator.migrate(&ct, AAD).unwrap();
+        assert_eq!(migrated.source, *want_source);
+        // The result has the TINK prefix for the primary key.
+        assert_eq!(&migrated.ciphertext[..5], &[1, 0, 0, 0, 4]);
+        assert_eq!(
+            aead.decrypt(&migrated.ciphertext, AAD).unwrap(),
+            plaintext.as_bytes()
+        );
+        if *want_source == CiphertextPrefix::Tink {
+            assert!(!migrated.reencrypted());
+            assert_eq!(migrated.ciphertext, ct);
+        } else {
+            assert!(migrated.reencrypted());
+        }
+    }
+    assert_eq!(
+        migrator.stats(),
+        PrefixMigrationStats {
+            unchanged: 1,
+            from_legacy: 1,
+            from_crunchy: 1,
+            from_raw: 1,
+            failed: 0,
+        }
+    );
+}
+
+#[test]
+fn test_prefix_migration_failures() {
+    let h = migration_keyset();
+    let migrator = PrefixMigrator::new(&h).unwrap();
+
+    let ct = encrypt_with_key(&h, 1, b"plaintext");
+    expect_err(migrator.migrate(&ct, b"wrong aad"), "decryption failed");
+    let mut modified = ct.clone();
+    modified[10] ^= 0x01;
+    expect_err(migrator.migrate(&modified, AAD), "decryption failed");
+    expect_err(migrator.migrate(&[], AAD), "decryption failed");
+
+    // A ciphertext for a key outside the keyset cannot be migrated.
+    let other = migration_keyset();
+    let ct = encrypt_with_key(&other, 2, b"plaintext");
+    expect_err(migrator.migrate(&ct, AAD), "decryption failed");
+    assert_eq!(migrator.stats().failed, 4);
+}
+
+#[test]
+fn test_prefix_migration_batch() {
+    let h = migration_keyset();
+    let migrator = PrefixMigrator::new(&h).unwrap();
+    let aead = tink_aead::new(&h).unwrap();
+    let cts: Vec<Vec<u8>> = (0..20u8)
+        .map(|i| encrypt_with_key(&h, 1 + (i % 4) as u32, &[i; 8]))
+        .collect();
+
+    let mut records: Vec<(&[u8], &[u8])> = cts.iter().map(|ct| (&ct[..], AAD)).collect();
+    records[5].1 = b"wrong aad";
+    let results = migrator.migrate_batch(&records);
+    assert_eq!(results.len(), 20);
+    for (i, result) in results.into_iter().enumerate() {
+        if i == 5 {
+            assert!(result.is_err());
+            continue;
+        }
+        let migrated = result.unwrap();
+        assert_eq!(
+            aead.decrypt(&migrated.ciphertext, AAD).unwrap(),
+            [i as u8; 8]
+        );
+        assert_eq!(migrated.ciphertext[0], 1);
+    }
+
+    // The iterator form processes records as they are consumed.
+    let migrator = PrefixMigrator::new(&h).unwrap();
+    let mut migrated = migrator.migrate_iter(cts.iter().map(|ct| (ct.clone(), AAD.to_vec())));
+    assert_eq!(migrator.stats(), PrefixMigrationStats::default());
+    migrated.next().unwrap().unwrap();
+    migrated.next().unwrap().unwrap();
+    assert_eq!(
+        migrator.stats().from_legacy + migrator.stats().from_crunchy,
+        2
+    );
+    assert_eq!(migrated.count(), 18);
+    let stats = migrator.stats();
+    assert_eq!(stats.unchanged, 5);
+    assert_eq!(stats.from_legacy, 5);
+    assert_eq!(stats.from_crunchy, 5);
+    assert_eq!(stats.from_raw, 5);
+}
+
+#[test]
+fn test_prefix_migration_requires_tink_primary() {
+    tink_aead::init();
+    let h = KeysetBuilder::new()
+        .add_entry(KeysetEntry::from_template(&template(OutputPrefixType::Crunchy)).make_primary())
+        .build()
+        .unwrap();
+    expect_err(PrefixMigrator::new(&h), "TINK output prefix type");
+}
@@ -0,0 +1,369 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use super::{chacha20poly1305_vectors::*, wycheproof};
+use std::io::Read;
+use tink_aead::subtle::{self, AadChunks};
+use tink_core::{subtle::random::get_random_bytes, Aead};
+use tink_tests::WycheproofResult;
+
+/// Reader that returns at most `max` bytes per call, to exercise partial-block buffering.
+struct ShortReader<'a> {
+    data: &'a [u8],
+    max: usize,
+}
+
+impl<'a> Read for ShortReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = std::cmp::min(std::cmp::min(buf.len(), self.max), self.data.len());
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        Ok(n)
+    }
+}
+
+/// Reader that fails immediately.
+struct FailingReader;
+
+impl Read for FailingReader {
+    fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+        Err(std::io::Error::new(std::io::ErrorKind::Other, "no data"))
+    }
+}
+
+const AAD_SIZES: &[usize] = &[0, 1, 15, 16, 17, 31, 100, 4095, 4096, 4097, 70000];
+const READ_SIZES: &[usize] = &[1, 7, 16, 1000, usize::MAX];
+
+fn primitives() -> Vec<(&'static str, Box<dyn Aead>, Box<dyn Aead>)> {
+    let mut result: Vec<(&'static str, Box<dyn Aead>, Box<dyn Aead>)> = Vec::new();
+    for (name, key_size) in &[
+        ("AES-128-GCM", 16),
+        ("AES-192-GCM", 24),
+        ("AES-256-GCM", 32),
+    ] {
+        let key = get_random_bytes(*key_size);
+        let a = subtle::AesGcm::new(&key).unwrap();
+        let r = subtle::AesGcmWithAadReader::new(&key).unwrap();
+        result.push((*name, Box::new(a), Box::new(r)));
+    }
+    let c = subtle::ChaCha20Poly1305::new(&get_random_bytes(32)).unwrap();
+    result.push(("ChaCha20Poly1305", Box::new(c.clone()), Box::new(c)));
+    let x = subtle::XChaCha20Poly1305::new(&get_random_bytes(32)).unwrap();
+    result.push(("XChaCha20Poly1305", Box::new(x.clone()), Box::new(x)));
+    result
+}
+
+#[test]
+fn test_aad_reader_interoperates_with_aead() {
+    for (name, aead, reader_aead) in primitives() {
+        for pt_size in &[0, 1, 16, 33] {
+            let pt = get_random_bytes(*pt_size);
+            for aad_size in AAD_SIZES {
+                let aad = get_random_bytes(*aad_size);
+                for read_size in READ_SIZES {
+                    let ct = reader_aead
+                        .encrypt_with_aad_reader(
+                            &pt,
+                            &mut ShortReader {
+                                data: &aad,
+                                max: *read_size,
+                            },
+                        )
+                        .unwrap();
+                    let got = aead.decrypt(&ct, &aad).unwrap_or_else(|e| {
+                        panic!(
+                            "{}: pt={} aad={} read={}: decrypt failed: {:?}",
+                            name, pt_size, aad_size, read_size, e
+                        )
+                    });
+                    assert_eq!(got, pt, "{}", name);
+
+                    let ct = aead.encrypt(&pt, &aad).unwrap();
+                    let got = reader_aead
+                        .decrypt_with_aad_reader(
+                            &ct,
+                            &mut ShortReader {
+                                data: &aad,
+                                max: *read_size,
+                            },
+                        )
+                        .unwrap_or_else(|e| {
+                            panic!(
+                                "{}: pt={} aad={} read={}: decrypt_with_aad_reader failed: {:?}",
+                                name, pt_size, aad_size, read_size, e
+                            )
+                        });
+                    assert_eq!(got, pt, "{}", name);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_aad_reader_chunks() {
+    let aad = get_random_bytes(1000);
+    for (name, aead, reader_aead) in primitives() {
+        let pt = get_random_bytes(20);
+        let chunks = vec![&aad[..3], &aad[3..3], &aad[3..500], &aad[500..]];
+        let ct = reader_aead
+            .encrypt_with_aad_reader(&pt, &mut AadChunks::new(chunks))
+            .unwrap();
+        assert_eq!(aead.decrypt(&ct, &aad).unwrap(), pt, "{}", name);
+    }
+}
+
+#[test]
+fn test_aad_reader_wrong_aad() {
+    let aad = get_random_bytes(100);
+    for (name, aead, reader_aead) in primitives() {
+        let pt = get_random_bytes(20);
+        let ct = aead.encrypt(&pt, &aad).unwrap();
+
+        let mut wrong = aad.clone();
+        wrong[99] ^= 1;
+        assert!(
+            reader_aead
+                .decrypt_with_aad_reader(&ct, &mut &wrong[..])
+                .is_err(),
+            "{}: modified aad accepted",
+            name
+        );
+        assert!(
+            reader_aead
+                .decrypt_with_aad_reader(&ct, &mut &aad[..99])
+                .is_err(),
+            "{}: truncated aad accepted",
+            name
+        );
+
+        let mut modified = ct.clone();
+        let last = modified.len() - 1;
+        modified[last] ^= 1;
+        assert!(
+            reader_aead
+                .decrypt_with_aad_reader(&modified, &mut &aad[..])
+                .is_err(),
+            "{}: modified ciphertext accepted",
+            name
+        );
+        tink_tests::expect_err(
+            reader_aead.decrypt_with_aad_reader(&ct[..27], &mut &aad[..]),
+            "too short",
+        );
+        tink_tests::expect_err(
+            reader_aead.encrypt_with_aad_reader(&pt, &mut FailingReader),
+            "failed to read associated data",
+        );
+    }
+}
+
+#[test]
+fn test_aad_reader_truncated_ciphertext() {
+    let aad = get_random_bytes(20);
+    for (name, aead, reader_aead) in primitives() {
+        let ct = aead.encrypt(&get_random_bytes(20), &aad).unwrap();
+        for len in 0..ct.len() {
+            assert!(
+                reader_aead
+                    .decrypt_with_aad_reader(&ct[..len], &mut &aad[..])
+                    .is_err(),
+                "{}: ciphertext truncated to {} bytes accepted",
+                name,
+                len
+            );
+        }
+    }
+}
+
+#[test]
+fn test_aad_reader_cha_cha20_poly1305_vectors() {
+    for (i, test) in CHA_CHA20_POLY1305_TESTS.iter().enumerate() {
+        let key = hex::decode(test.key).unwrap();
+        let pt = hex::decode(test.plaintext).unwrap();
+        let aad = hex::decode(test.aad).unwrap();
+        let mut ct = hex::decode(test.nonce).unwrap();
+        ct.extend_from_slice(&hex::decode(test.out).unwrap());
+
+        let ca = subtle::ChaCha20Poly1305::new(&key).unwrap();
+        let got = ca
+            .decrypt_with_aad_reader(&ct, &mut ShortReader { data: &aad, max: 5 })
+            .unwrap_or_else(|e| panic!("#{}: decrypt_with_aad_reader failed: {:?}", i, e));
+        assert_eq!(got, pt, "#{}", i);
+    }
+}
+
+/// Run the Wycheproof vectors in `filename` through the reader-based operations of the
+/// primitive created by `new`, for groups with the given key and nonce sizes.
+fn run_wycheproof_with_aad_reader(
+    filename: &str,
+    key_size: usize,
+    iv_size: usize,
+    new: fn(&[u8]) -> Box<dyn Aead>,
+) {
+    println!("wycheproof file '{}'", filename);
+    let bytes = tink_tests::wycheproof_data(filename);
+    let data: wycheproof::TestData = serde_json::from_slice(&bytes).unwrap();
+
+    for g in &data.test_groups {
+        if (g.key_size / 8) as usize != key_size {
+            println!("   skipping tests for key_size={}", g.key_size);
+            continue;
+        }
+        if (g.iv_size / 8) as usize != iv_size {
+            println!("   skipping tests for iv_size={}", g.iv_size);
+            continue;
+        }
+        for tc in &g.tests {
+            println!(
+                "     case {} [{}] {}",
+                tc.case.case_id, tc.case.result, tc.case.comment
+            );
+            let mut combined_ct = Vec::new();
+            combined_ct.extend_from_slice(&tc.iv);
+            combined_ct.extend_from_slice(&tc.ct);
+            combined_ct.extend_from_slice(&tc.tag);
+
+            let cipher = new(&tc.key);
+            let result = cipher.decrypt_with_aad_reader(
+                &combined_ct,
+                &mut ShortReader {
+                    data: &tc.aad,
+                    max: 7,
+                },
+            );
+            match result {
+                Err(e) => {
+                    assert_ne!(
+                        tc.case.result,
+                        WycheproofResult::Valid,
+                        "unexpected error in test case {}: {}",
+                        tc.case.case_id,
+                        e
+                    );
+                }
+                Ok(decrypted) => {
+                    assert_ne!(
+                        tc.case.result,
+                        WycheproofResult::Invalid,
+                        "decrypted invalid test case {}",
+                        tc.case.case_id
+                    );
+                    assert_eq!(
+                        decrypted, tc.msg,
+                        "incorrect decryption in test case {}",
+                        tc.case.case_id,
+                    );
+                }
+            }
+
+            if tc.case.result == WycheproofResult::Valid {
+                let ct = cipher
+                    .encrypt_with_aad_reader(&tc.msg, &mut &tc.aad[..])
+                    .unwrap();
+                assert_eq!(
+                    cipher.decrypt(&ct, &tc.aad).unwrap(),
+                    tc.msg,
+                    "round trip failed in test case {}",
+                    tc.case.case_id
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_aad_reader_aes_gcm_wycheproof_vectors() {
+    for key_size in &[16, 24, 32] {
+        run_wycheproof_with_aad_reader(
+            "testvectors/aes_gcm_test.json",
+            *key_size,
+            subtle::AES_GCM_IV_SIZE,
+            |key| Box::new(subtle::AesGcmWithAadReader::new(key).unwrap()),
+        );
+    }
+}
+
+#[test]
+fn test_aad_reader_cha_cha20_poly1305_wycheproof_vectors() {
+    run_wycheproof_with_aad_reader(
+        "testvectors/chacha20_poly1305_test.json",
+        subtle::CHA_CHA20_KEY_SIZE,
+        subtle::CHA_CHA20_NONCE_SIZE,
+        |key| Box::new(subtle::ChaCha20Poly1305::new(key).unwrap()),
+    );
+}
+
+#[test]
+fn test_aad_reader_x_cha_cha20_poly1305_wycheproof_vectors() {
+    run_wycheproof_with_aad_reader(
+        "testvectors/xchacha20_poly1305_test.json",
+        subtle::X_CHA_CHA20_KEY_SIZE,
+        subtle::X_CHA_CHA20_NONCE_SIZE,
+        |key| Box::new(subtle::XChaCha20Poly1305::new(key).unwrap()),
+    );
+}
+
+#[test]
+fn test_aad_reader_factory() {
+    tink_aead::init();
+    for template in &[
+        tink_aead::aes128_gcm_key_template(),
+        tink_aead::cha_cha20_poly1305_key_template(),
+        tink_aead::x_cha_cha20_poly1305_key_template(),
+    ] {
+        let kh = tink_core::keyset::Handle::new(template).unwrap();
+        let a = tink_aead::new(&kh).unwrap();
+        let r = tink_aead::new_with_aad_reader(&kh).unwrap();
+        let pt = get_random_bytes(20);
+        let aad = get_random_bytes(5000);
+
+        let ct = r
+            .encrypt_with_aad_reader(&pt, &mut AadChunks::new(vec![&aad[..10], &aad[10..]]))
+            .unwrap();
+        assert_eq!(a.decrypt(&ct, &aad).unwrap(), pt);
+        let ct = a.encrypt(&pt, &aad).unwrap();
+        assert_eq!(
+            r.decrypt_with_aad_reader(&ct, &mut ShortReader { data: &aad, max: 3 })
+                .unwrap(),
+            pt
+        );
+        // The default implementation reads the associated data into memory.
+        assert_eq!(a.decrypt_with_aad_reader(&ct, &mut &aad[..]).unwrap(), pt);
+        assert!(r.decrypt_with_aad_reader(&ct, &mut &aad[1..]).is_err());
+    }
+}
+
+#[test]
+fn test_aad_reader_factory_several_candidate_keys() {
+    tink_aead::init();
+    let mut km = tink_core::keyset::Manager::new();
+    km.rotate(&tink_aead::aes256_gcm_no_prefix_key_template())
+        .unwrap();
+    let old = tink_aead::new_with_aad_reader(&km.handle().unwrap()).unwrap();
+    km.rotate(&tink_aead::aes256_gcm_no_prefix_key_template())
+        .unwrap();
+    let r = tink_aead::new_with_aad_reader(&km.handle().unwrap()).unwrap();
+
+    let pt = get_random_bytes(20);
+    let aad = get_random_bytes(100);
+    let ct = old.encrypt(&pt, &aad).unwrap();
+    assert_eq!(
+        r.decrypt_with_aad_reader(&ct, &mut AadChunks::new(vec![&aad[..50], &aad[50..]]))
+            .unwrap(),
+        pt
+    );
+}
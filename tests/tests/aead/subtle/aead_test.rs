@@ -33,3 +33,14 @@ fn test_validate_aes_key_size() {
         }
     }
 }
+
+#[test]
+fn test_validate_aes_gcm_key_size() {
+    for i in 0..65 {
+        let result = tink_aead::subtle::validate_aes_gcm_key_size(i);
+        match i {
+            16 | 24 | 32 => result.unwrap(),
+            _ => tink_tests::expect_err(result, "invalid AES key size; want 16, 24 or 32"),
+        }
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_aead::subtle;
+use tink_core::{subtle::random::get_random_bytes, Aead};
+
+const KEY_SIZES: &[usize] = &[16, 32];
+
+#[test]
+fn test_aes_gcm_committing_rejects_invalid_key_length() {
+    for key_size in &[4, 8, 15, 17, 24, 31, 33, 64] {
+        let key = get_random_bytes(*key_size);
+        let result = subtle::AesGcmCommitting::new(&key);
+        tink_tests::expect_err(result, "invalid AES key size");
+    }
+}
+
+#[test]
+fn test_aes_gcm_committing_encrypt_decrypt() {
+    for key_size in KEY_SIZES {
+        let key = get_random_bytes(*key_size);
+        let a = subtle::AesGcmCommitting::new(&key).unwrap();
+        let aad = get_random_bytes(5);
+        for pt_size in &[0, 1, 16, 33, 1024] {
+            let pt = get_random_bytes(*pt_size);
+            let ct = a.encrypt(&pt, &aad).unwrap();
+            assert_eq!(
+                ct.len(),
+                subtle::AES_GCM_IV_SIZE
+                    + subtle::AES_GCM_COMMITMENT_SIZE
+                    + pt.len()
+                    + subtle::AES_GCM_TAG_SIZE
+            );
+            let decrypted = a.decrypt(&ct, &aad).unwrap();
+            assert_eq!(pt, decrypted);
+        }
+    }
+}
+
+#[test]
+fn test_aes_gcm_committing_wrong_key() {
+    for key_size in KEY_SIZES {
+        let a = subtle::AesGcmCommitting::new(&get_random_bytes(*key_size)).unwrap();
+        let b = subtle::AesGcmCommitting::new(&get_random_bytes(*key_size)).unwrap();
+        let ct = a.encrypt(b"plaintext", b"aad").unwrap();
+        tink_tests::expect_err(b.decrypt(&ct, b"aad"), "key commitment mismatch");
+    }
+}
+
+#[test]
+fn test_aes_gcm_committing_modified_ciphertext() {
+    let key = get_random_bytes(16);
+    let a = subtle::AesGcmCommitting::new(&key).unwrap();
+    let aad = get_random_bytes(33);
+    let pt = get_random_bytes(32);
+    let ct = a.encrypt(&pt, &aad).unwrap();
+
+    // Flipping bits.
+    for i in 0..ct.len() {
+        let mut tmp_ct = ct.clone();
+        tmp_ct[i] ^= 0x01;
+        assert!(
+            a.decrypt(&tmp_ct, &aad).is_err(),
+            "expected error when flipping bit in byte {}",
+            i
+        );
+    }
+    // Truncated ciphertext.
+    for i in 1..ct.len() {
+        assert!(
+            a.decrypt(&ct[..ct.len() - i], &aad).is_err(),
+            "expected error when truncating {} bytes",
+            i
+        );
+    }
+    // Modified associated data.
+    for i in 0..aad.len() {
+        let mut tmp_aad = aad.clone();
+        tmp_aad[i] ^= 0x01;
+        assert!(
+            a.decrypt(&ct, &tmp_aad).is_err(),
+            "expected error when modifying aad in byte {}",
+            i
+        );
+    }
+}
+
+#[test]
+fn test_aes_gcm_committing_short_ciphertext() {
+    let a = subtle::AesGcmCommitting::new(&get_random_bytes(16)).unwrap();
+    let short = vec![0; subtle::AES_GCM_IV_SIZE + subtle::AES_GCM_COMMITMENT_SIZE];
+    tink_tests::expect_err(a.decrypt(&short, b""), "ciphertext too short");
+}
+
+#[test]
+fn test_aes_gcm_committing_random_nonce() {
+    let a = subtle::AesGcmCommitting::new(&get_random_bytes(16)).unwrap();
+    let mut cts = std::collections::HashSet::new();
+    for _ in 0..64 {
+        let ct = a.encrypt(&[], &[]).unwrap();
+        assert!(cts.insert(ct), "repeated ciphertext");
+    }
+}
@@ -20,11 +20,11 @@ use tink_aead::subtle;
 use tink_core::{subtle::random::get_random_bytes, Aead};
 use tink_tests::WycheproofResult;
 
-const KEY_SIZES: &[usize] = &[16, 32];
+const KEY_SIZES: &[usize] = &[16, 24, 32];
 
 #[test]
 fn test_aes_gcm_siv_rejects_invalid_key_length() {
-    let invalid_key_sizes = vec![4, 8, 12, 15, 17, 24, 30, 31, 33, 64, 128];
+    let invalid_key_sizes = vec![4, 8, 12, 15, 17, 23, 25, 30, 31, 33, 64, 128];
 
     for key_size in invalid_key_sizes {
         let key = get_random_bytes(key_size);
@@ -20,7 +20,7 @@ use tink_aead::subtle;
 use tink_core::{subtle::random::get_random_bytes, Aead};
 use tink_tests::WycheproofResult;
 
-const KEY_SIZES: &[usize] = &[16, 32];
+const KEY_SIZES: &[usize] = &[16, 24, 32];
 
 // Check that the tag size is always 128 bit.
 #[test]
@@ -166,6 +166,36 @@ fn test_aes_gcm_random_nonce() {
     }
 }
 
+#[test]
+fn test_aes_gcm_invocation_limit() {
+    let key = get_random_bytes(16);
+    let unlimited = subtle::AesGcm::new(&key).unwrap();
+    assert_eq!(None, unlimited.invocations());
+
+    let a = unlimited.with_invocation_limit(2);
+    assert_eq!(Some(0), a.invocations());
+    let ct = a.encrypt(b"plaintext", b"aad").unwrap();
+    // Clones share the count.
+    let cloned = a.clone();
+    cloned.encrypt(b"plaintext", b"aad").unwrap();
+    assert_eq!(Some(2), a.invocations());
+    tink_tests::expect_err(a.encrypt(b"plaintext", b"aad"), "invocation limit");
+    tink_tests::expect_err(cloned.encrypt(b"plaintext", b"aad"), "invocation limit");
+    assert_eq!(Some(2), a.invocations());
+
+    // Decryption is unaffected, as is the original instance.
+    assert_eq!(a.decrypt(&ct, b"aad").unwrap(), b"plaintext");
+    assert!(unlimited.encrypt(b"plaintext", b"aad").is_ok());
+}
+
+#[test]
+fn test_aes_gcm_default_invocation_limit() {
+    let a = subtle::AesGcm::new_with_default_limit(&get_random_bytes(32)).unwrap();
+    a.encrypt(b"plaintext", b"").unwrap();
+    assert_eq!(Some(1), a.invocations());
+    assert_eq!(1 << 32, subtle::AES_GCM_DEFAULT_MAX_INVOCATIONS);
+}
+
 #[test]
 fn test_aes_gcm_vectors() {
     let filename = "testvectors/aes_gcm_test.json";
@@ -175,7 +205,7 @@ fn test_aes_gcm_vectors() {
     assert_eq!("AES-GCM", data.suite.algorithm);
 
     for g in &data.test_groups {
-        if subtle::validate_aes_key_size(g.key_size as usize / 8).is_err() {
+        if subtle::validate_aes_gcm_key_size(g.key_size as usize / 8).is_err() {
             println!("   skipping tests for key_size={}", g.key_size);
             continue;
         }
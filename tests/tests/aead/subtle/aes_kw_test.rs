@@ -0,0 +1,152 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_aead::subtle::{AesKw, AesKwp, AES_KW_MAX_KEY_SIZE};
+use tink_core::KeyWrap;
+use tink_tests::expect_err;
+
+struct KwTestVector {
+    kek: &'static str,
+    key: &'static str,
+    wrapped: &'static str,
+}
+
+/// Test vectors from RFC 3394 section 4.
+const KW_TEST_VECTORS: &[KwTestVector] = &[
+    KwTestVector {
+        kek: "000102030405060708090a0b0c0d0e0f",
+        key: "00112233445566778899aabbccddeeff",
+        wrapped: "1fa68b0a8112b447aef34bd8fb5a7b829d3e862371d2cfe5",
+    },
+    KwTestVector {
+        kek: "000102030405060708090a0b0c0d0e0f1011121314151617",
+        key: "00112233445566778899aabbccddeeff",
+        wrapped: "96778b25ae6ca435f92b5b97c050aed2468ab8a17ad84e5d",
+    },
+    KwTestVector {
+        kek: "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+        key: "00112233445566778899aabbccddeeff",
+        wrapped: "64e8c3f9ce0f5ba263e9777905818a2a93c8191e7d6e8ae7",
+    },
+    KwTestVector {
+        kek: "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+        key: "00112233445566778899aabbccddeeff000102030405060708090a0b0c0d0e0f",
+        wrapped: "28c9f404c4b810f4cbccb35cfb87f8263f5786e2d80ed326cbc7f0e71a99f43bfb988b9b7a02dd21",
+    },
+];
+
+/// Test vectors from RFC 5649 section 6.
+const KWP_TEST_VECTORS: &[KwTestVector] = &[
+    KwTestVector {
+        kek: "5840df6e29b02af1ab493b705bf16ea1ae8338f4dcc176a8",
+        key: "c37b7e6492584340bed12207808941155068f738",
+        wrapped: "138bdeaa9b8fa7fc61f97742e72248ee5ae6ae5360d1ae6a5f54f373fa543b6a",
+    },
+    KwTestVector {
+        kek: "5840df6e29b02af1ab493b705bf16ea1ae8338f4dcc176a8",
+        key: "466f7250617369",
+        wrapped: "afbeb0f07dfbf5419200f2ccb50bb24f",
+    },
+];
+
+#[test]
+fn test_aes_kw_test_vectors() {
+    for tv in KW_TEST_VECTORS {
+        let kw = AesKw::new(&hex::decode(tv.kek).unwrap()).unwrap();
+        let key = hex::decode(tv.key).unwrap();
+        let wrapped = kw.wrap_key(&key).unwrap();
+        assert_eq!(hex::encode(&wrapped), tv.wrapped);
+        assert_eq!(kw.unwrap_key(&wrapped).unwrap(), key);
+    }
+}
+
+#[test]
+fn test_aes_kwp_test_vectors() {
+    for tv in KWP_TEST_VECTORS {
+        let kwp = AesKwp::new(&hex::decode(tv.kek).unwrap()).unwrap();
+        let key = hex::decode(tv.key).unwrap();
+        let wrapped = kwp.wrap_key(&key).unwrap();
+        assert_eq!(hex::encode(&wrapped), tv.wrapped);
+        assert_eq!(kwp.unwrap_key(&wrapped).unwrap(), key);
+    }
+}
+
+#[test]
+fn test_aes_kw_modified_ciphertext() {
+    let kw = AesKw::new(&[0x42; 32]).unwrap();
+    let kwp = AesKwp::new(&[0x42; 32]).unwrap();
+    for (wrapper, key) in &[
+        (&kw as &dyn KeyWrap, vec![0x11; 32]),
+        (&kwp as &dyn KeyWrap, vec![0x11; 32]),
+        (&kwp as &dyn KeyWrap, vec![0x11; 5]),
+        (&kwp as &dyn KeyWrap, vec![0x11; 17]),
+    ] {
+        let wrapped = wrapper.wrap_key(key).unwrap();
+        for i in 0..wrapped.len() {
+            let mut modified = wrapped.clone();
+            modified[i] ^= 0x01;
+            expect_err(wrapper.unwrap_key(&modified), "integrity check failed");
+        }
+        assert!(wrapper.unwrap_key(&wrapped[..wrapped.len() - 8]).is_err());
+    }
+
+    // A wrapping under a different KEK, or with the other scheme, is rejected.
+    let other = AesKw::new(&[0x43; 32]).unwrap();
+    let wrapped = kw.wrap_key(&[0x11; 32]).unwrap();
+    expect_err(other.unwrap_key(&wrapped), "integrity check failed");
+    expect_err(kwp.unwrap_key(&wrapped), "integrity check failed");
+    let wrapped = kwp.wrap_key(&[0x11; 32]).unwrap();
+    expect_err(kw.unwrap_key(&wrapped), "integrity check failed");
+}
+
+#[test]
+fn test_aes_kwp_sizes() {
+    let kwp = AesKwp::new(&[0x42; 16]).unwrap();
+    for size in 1..=40 {
+        let key: Vec<u8> = (0..size as u8).collect();
+        let wrapped = kwp.wrap_key(&key).unwrap();
+        assert_eq!(wrapped.len(), 8 + (size + 7) / 8 * 8);
+        assert_eq!(kwp.unwrap_key(&wrapped).unwrap(), key);
+    }
+    let key = vec![0x11; AES_KW_MAX_KEY_SIZE];
+    let wrapped = kwp.wrap_key(&key).unwrap();
+    assert_eq!(kwp.unwrap_key(&wrapped).unwrap(), key);
+}
+
+#[test]
+fn test_aes_kw_invalid() {
+    expect_err(AesKw::new(&[0; 15]), "invalid KEK size");
+    expect_err(AesKwp::new(&[0; 33]), "invalid KEK size");
+
+    let kw = AesKw::new(&[0x42; 16]).unwrap();
+    expect_err(kw.wrap_key(&[0; 8]), "invalid key size");
+    expect_err(kw.wrap_key(&[0; 20]), "invalid key size");
+    expect_err(
+        kw.wrap_key(&vec![0; AES_KW_MAX_KEY_SIZE + 8]),
+        "invalid key size",
+    );
+    expect_err(kw.unwrap_key(&[0; 16]), "invalid wrapped key size");
+    expect_err(kw.unwrap_key(&[0; 30]), "invalid wrapped key size");
+
+    let kwp = AesKwp::new(&[0x42; 16]).unwrap();
+    expect_err(kwp.wrap_key(&[]), "invalid key size");
+    expect_err(
+        kwp.wrap_key(&vec![0; AES_KW_MAX_KEY_SIZE + 1]),
+        "invalid key size",
+    );
+    expect_err(kwp.unwrap_key(&[0; 8]), "invalid wrapped key size");
+    expect_err(kwp.unwrap_key(&[0; 20]), "invalid wrapped key size");
+}
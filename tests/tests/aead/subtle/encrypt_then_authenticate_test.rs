@@ -14,8 +14,8 @@
 //
 ////////////////////////////////////////////////////////////////////////////////
 
-use tink_aead::subtle;
-use tink_core::{subtle::random::get_random_bytes, TinkError};
+use tink_aead::{subtle, subtle::IndCpaCipher};
+use tink_core::{subtle::random::get_random_bytes, Aead, TinkError};
 use tink_proto::HashType;
 
 fn create_aead_with_keys(
@@ -278,3 +278,87 @@ fn test_eta_empty_params() {
         .encrypt(&[], &[])
         .expect("encryption failed with empty ciphertext and aad");
 }
+
+/// An [`IndCpaCipher`] with a protocol-specific nonce convention: the nonce is an 8-byte
+/// big-endian message counter, and the keystream is derived from AES-CTR with the nonce as IV.
+#[derive(Clone)]
+struct CountedCtr {
+    key: Vec<u8>,
+    counter: std::rc::Rc<std::cell::Cell<u64>>,
+}
+
+impl IndCpaCipher for CountedCtr {
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, TinkError> {
+        let nonce = self.counter.get();
+        self.counter.set(nonce + 1);
+        let mut ciphertext = nonce.to_be_bytes().to_vec();
+        ciphertext.extend_from_slice(&self.keystream_xor(&nonce.to_be_bytes(), plaintext)?);
+        Ok(ciphertext)
+    }
+
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, TinkError> {
+        if ciphertext.len() < 8 {
+            return Err("ciphertext too short".into());
+        }
+        self.keystream_xor(&ciphertext[..8], &ciphertext[8..])
+    }
+}
+
+impl CountedCtr {
+    fn keystream_xor(&self, nonce: &[u8], data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        // AesCtr decryption of (IV || data) applies the keystream for the IV.
+        let ctr = subtle::AesCtr::new(&self.key, 12)?;
+        let mut iv_and_data = nonce.to_vec();
+        iv_and_data.extend_from_slice(&[0; 4]);
+        iv_and_data.extend_from_slice(data);
+        ctr.decrypt(&iv_and_data)
+    }
+}
+
+#[test]
+fn test_eta_with_custom_ind_cpa_cipher() {
+    let counter = std::rc::Rc::new(std::cell::Cell::new(7));
+    let cipher = CountedCtr {
+        key: get_random_bytes(16),
+        counter: counter.clone(),
+    };
+    let mac = tink_mac::subtle::Hmac::new(HashType::Sha256, &get_random_bytes(32), 16).unwrap();
+    let aead = subtle::EncryptThenAuthenticate::new(Box::new(cipher), Box::new(mac), 16).unwrap();
+    let aead2 = aead.clone();
+
+    let aad = b"aad";
+    let ct1 = aead.encrypt(b"first message", aad).unwrap();
+    let ct2 = aead2.encrypt(b"second message", aad).unwrap();
+    assert_eq!(counter.get(), 9);
+    // The ciphertext is (nonce || encrypted plaintext || tag).
+    assert_eq!(&ct1[..8], &7u64.to_be_bytes());
+    assert_eq!(&ct2[..8], &8u64.to_be_bytes());
+    assert_eq!(ct1.len(), 8 + 13 + 16);
+
+    assert_eq!(aead2.decrypt(&ct1, aad).unwrap(), b"first message");
+    assert_eq!(aead.decrypt(&ct2, aad).unwrap(), b"second message");
+
+    // The nonce is authenticated.
+    let mut modified = ct1.clone();
+    modified[7] ^= 0x01;
+    assert!(aead.decrypt(&modified, aad).is_err());
+}
+
+#[test]
+fn test_aes_ctr_ciphertext_layout() {
+    let key = get_random_bytes(16);
+    for iv_size in subtle::AES_CTR_MIN_IV_SIZE..=subtle::AES_BLOCK_SIZE_IN_BYTES {
+        let ctr = subtle::AesCtr::new(&key, iv_size).unwrap();
+        let plaintext = get_random_bytes(40);
+        let ct = ctr.encrypt(&plaintext).unwrap();
+        assert_eq!(ct.len(), iv_size + plaintext.len());
+
+        // Encryption is the keystream for (IV || zero padding) applied to the plaintext, so a
+        // cipher with the full block size decrypts the zero-padded form.
+        let full = subtle::AesCtr::new(&key, subtle::AES_BLOCK_SIZE_IN_BYTES).unwrap();
+        let mut padded = ct[..iv_size].to_vec();
+        padded.resize(subtle::AES_BLOCK_SIZE_IN_BYTES, 0);
+        padded.extend_from_slice(&ct[iv_size..]);
+        assert_eq!(full.decrypt(&padded).unwrap(), plaintext);
+    }
+}
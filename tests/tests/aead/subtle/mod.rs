@@ -14,10 +14,13 @@
 //
 ////////////////////////////////////////////////////////////////////////////////
 
+mod aad_reader_test;
 mod aead_test;
 mod aes_ctr_test;
+mod aes_gcm_committing_test;
 mod aes_gcm_siv_test;
 mod aes_gcm_test;
+mod aes_kw_test;
 mod chacha20poly1305_test;
 mod chacha20poly1305_vectors;
 mod encrypt_then_authenticate_test;
@@ -0,0 +1,31 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+// This test registers a conflicting key manager, which leaves the registry in a state that
+// other tests cannot use, so it lives in its own test binary.
+
+#[test]
+fn test_init_all_detects_conflict() {
+    let km = tink_tests::DummyAeadKeyManager {
+        type_url: tink_daead::AES_SIV_TYPE_URL,
+    };
+    tink_core::registry::register_key_manager(std::sync::Arc::new(km)).unwrap();
+
+    let result = tink_all::init_all();
+    tink_tests::expect_err(result, "tink_daead::init() failed");
+    // Crates initialized before the conflict are available.
+    assert!(tink_core::registry::get_key_manager(tink_aead::AES_GCM_TYPE_URL).is_ok());
+}
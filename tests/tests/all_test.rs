@@ -0,0 +1,50 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+// Tests for `tink_all` live in their own test binaries, as they depend on the contents of the
+// global registry.
+
+#[test]
+fn test_init_all() {
+    tink_all::init_all().unwrap();
+    // Repeated initialization is harmless.
+    tink_all::init_all().unwrap();
+    tink_all::init();
+
+    let templates = vec![
+        tink_aead::aes256_gcm_key_template(),
+        tink_daead::aes_siv_key_template(),
+        tink_mac::hmac_sha256_tag256_key_template(),
+        tink_prf::hmac_sha256_prf_key_template(),
+        tink_signature::ed25519_key_template(),
+        tink_streaming_aead::aes128_gcm_hkdf_4kb_key_template(),
+        tink_hybrid::ecies_hkdf_aes128_gcm_key_template(),
+    ];
+    for template in templates {
+        assert!(
+            tink_core::keyset::Handle::new(&template).is_ok(),
+            "failed to generate key for {}",
+            template.type_url
+        );
+    }
+    for name in &["AES256_GCM", "AES256_SIV", "HMAC_SHA256_PRF", "ED25519"] {
+        assert!(
+            tink_core::registry::get_template_generator(name).is_some(),
+            "missing template generator {}",
+            name
+        );
+    }
+}
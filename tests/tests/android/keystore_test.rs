@@ -0,0 +1,218 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use lazy_static::lazy_static;
+use std::sync::{Arc, Once};
+use tink_android::{AndroidKeystore, AndroidKeystoreClient, SoftwareKeystore};
+use tink_core::{
+    keyset::{insecure, Handle, MemReaderWriter},
+    registry::KmsClient,
+};
+use tink_proto::{
+    key_data::KeyMaterialType, prost::Message, EcdsaSignatureEncoding, EllipticCurveType, HashType,
+    KeyTemplate, OutputPrefixType,
+};
+use tink_tests::expect_err;
+
+lazy_static! {
+    static ref KEYSTORE: Arc<SoftwareKeystore> = Arc::new(SoftwareKeystore::new());
+}
+static INIT: Once = Once::new();
+
+fn setup() {
+    INIT.call_once(|| {
+        tink_android::register(KEYSTORE.clone()).unwrap();
+    });
+}
+
+/// Return the alias of the Keystore key referenced by the primary key of `kh`.
+fn primary_alias(kh: &Handle) -> String {
+    let ks = insecure::keyset_material(kh);
+    let key_data = ks.key[0].key_data.as_ref().unwrap();
+    match key_data.type_url.as_str() {
+        tink_android::ANDROID_KEYSTORE_AES_GCM_TYPE_URL => {
+            tink_proto::AndroidKeystoreAesGcmKey::decode(key_data.value.as_ref())
+                .unwrap()
+                .key_alias
+        }
+        _ => {
+            tink_proto::AndroidKeystoreEcdsaPrivateKey::decode(key_data.value.as_ref())
+                .unwrap()
+                .key_alias
+        }
+    }
+}
+
+#[test]
+fn test_android_keystore_aead() {
+    setup();
+    let kh = Handle::new(&tink_android::android_keystore_aes256_gcm_key_template()).unwrap();
+    let ks = insecure::keyset_material(&kh);
+    assert_eq!(
+        ks.key[0].key_data.as_ref().unwrap().key_material_type,
+        KeyMaterialType::Remote as i32
+    );
+    assert!(primary_alias(&kh).starts_with("tink_"));
+
+    let a = tink_aead::new(&kh).unwrap();
+    let ct = a.encrypt(b"plaintext", b"aad").unwrap();
+    assert_eq!(a.decrypt(&ct, b"aad").unwrap(), b"plaintext");
+    expect_err(a.decrypt(&ct, b"other"), "decryption failed");
+
+    // The keyset only refers to the key in the Keystore, so can be stored in the clear.
+    let mut mem_keyset = MemReaderWriter::default();
+    kh.write_with_no_secrets(&mut mem_keyset).unwrap();
+    let kh2 = Handle::read_with_no_secrets(&mut mem_keyset).unwrap();
+    let a2 = tink_aead::new(&kh2).unwrap();
+    assert_eq!(a2.decrypt(&ct, b"aad").unwrap(), b"plaintext");
+
+    KEYSTORE.delete_key(&primary_alias(&kh)).unwrap();
+    expect_err(a.encrypt(b"plaintext", b"aad"), "key not found");
+}
+
+#[test]
+fn test_android_keystore_signer() {
+    setup();
+    let kh = Handle::new(&tink_android::android_keystore_ecdsa_p256_key_template()).unwrap();
+    let signer = tink_signature::new_signer(&kh).unwrap();
+    let sig = signer.sign(b"data").unwrap();
+
+    let pub_kh = kh.public().unwrap();
+    let pub_ks = insecure::keyset_material(&pub_kh);
+    assert_eq!(
+        pub_ks.key[0].key_data.as_ref().unwrap().type_url,
+        tink_signature::ECDSA_VERIFIER_TYPE_URL
+    );
+    let verifier = tink_signature::new_verifier(&pub_kh).unwrap();
+    verifier.verify(&sig, b"data").unwrap();
+    expect_err(verifier.verify(&sig, b"other data"), "invalid signature");
+
+    KEYSTORE.delete_key(&primary_alias(&kh)).unwrap();
+    expect_err(signer.sign(b"data"), "key not found");
+}
+
+#[test]
+fn test_android_keystore_signer_p1363() {
+    setup();
+    let format = tink_proto::AndroidKeystoreEcdsaKeyFormat {
+        params: Some(tink_proto::EcdsaParams {
+            hash_type: HashType::Sha256 as i32,
+            curve: EllipticCurveType::NistP256 as i32,
+            encoding: EcdsaSignatureEncoding::IeeeP1363 as i32,
+        }),
+    };
+    let mut value = Vec::new();
+    format.encode(&mut value).unwrap();
+    let mut kt = KeyTemplate {
+        type_url: tink_android::ANDROID_KEYSTORE_SIGNER_TYPE_URL.to_string(),
+        value,
+        output_prefix_type: OutputPrefixType::Raw as i32,
+    };
+    let kh = Handle::new(&kt).unwrap();
+    let sig = tink_signature::new_signer(&kh)
+        .unwrap()
+        .sign(b"data")
+        .unwrap();
+    assert_eq!(sig.len(), 64);
+    let verifier = tink_signature::new_verifier(&kh.public().unwrap()).unwrap();
+    verifier.verify(&sig, b"data").unwrap();
+
+    let format = tink_proto::AndroidKeystoreEcdsaKeyFormat {
+        params: Some(tink_proto::EcdsaParams {
+            hash_type: HashType::Sha256 as i32,
+            curve: EllipticCurveType::NistP384 as i32,
+            encoding: EcdsaSignatureEncoding::Der as i32,
+        }),
+    };
+    kt.value.clear();
+    format.encode(&mut kt.value).unwrap();
+    expect_err(Handle::new(&kt), "unsupported curve");
+}
+
+#[test]
+fn test_android_keystore_client() {
+    setup();
+    let client = AndroidKeystoreClient::new(KEYSTORE.clone());
+    assert!(client.supported("android-keystore://main"));
+    assert!(!client.supported("gcp-kms://main"));
+    expect_err(client.get_aead("android-keystore://"), "must have format");
+    expect_err(
+        client.generate_new_aead_key("gcp-kms://main"),
+        "must have format",
+    );
+
+    let key_uri = "android-keystore://keyset_main_key";
+    client.generate_new_aead_key(key_uri).unwrap();
+    expect_err(client.generate_new_aead_key(key_uri), "already in use");
+
+    // The registered client can protect keysets at rest.
+    let main_key = tink_core::registry::get_kms_client(key_uri)
+        .unwrap()
+        .get_aead(key_uri)
+        .unwrap();
+    let kh1 = Handle::new(&tink_aead::aes128_gcm_key_template()).unwrap();
+    let mut mem_keyset = MemReaderWriter::default();
+    kh1.write(&mut mem_keyset, main_key.box_clone()).unwrap();
+    let kh2 = Handle::read(&mut mem_keyset, main_key).unwrap();
+    assert_eq!(
+        insecure::keyset_material(&kh1),
+        insecure::keyset_material(&kh2)
+    );
+
+    // The Keystore keys can also be used via envelope encryption.
+    let kh = Handle::new(&tink_aead::kms_envelope_aead_key_template(
+        key_uri,
+        tink_aead::aes128_gcm_key_template(),
+    ))
+    .unwrap();
+    let a = tink_aead::new(&kh).unwrap();
+    let ct = a.encrypt(b"plaintext", b"aad").unwrap();
+    assert_eq!(a.decrypt(&ct, b"aad").unwrap(), b"plaintext");
+}
+
+#[test]
+fn test_android_keystore_invalid_key() {
+    setup();
+    let km = tink_core::registry::get_key_manager(tink_android::ANDROID_KEYSTORE_AES_GCM_TYPE_URL)
+        .unwrap();
+    let mut serialized = Vec::new();
+    tink_proto::AndroidKeystoreAesGcmKey {
+        version: 0,
+        key_alias: "".to_string(),
+    }
+    .encode(&mut serialized)
+    .unwrap();
+    expect_err(km.primitive(&serialized), "no key alias");
+
+    serialized.clear();
+    tink_proto::AndroidKeystoreAesGcmKey {
+        version: 1,
+        key_alias: "alias".to_string(),
+    }
+    .encode(&mut serialized)
+    .unwrap();
+    expect_err(km.primitive(&serialized), "version");
+    expect_err(km.primitive(&[]), "invalid key");
+}
+
+#[test]
+fn test_android_keystore_register_twice() {
+    setup();
+    expect_err(
+        tink_android::register(Arc::new(SoftwareKeystore::new())),
+        "already registered",
+    );
+}
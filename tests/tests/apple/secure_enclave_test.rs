@@ -0,0 +1,216 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use lazy_static::lazy_static;
+use std::sync::{Arc, Once};
+use tink_apple::{EnclaveKeyStore, SoftwareEnclave};
+use tink_core::keyset::{insecure, Handle, MemReaderWriter};
+use tink_proto::{
+    key_data::KeyMaterialType, prost::Message, EcdsaSignatureEncoding, EllipticCurveType, HashType,
+    KeyTemplate, OutputPrefixType,
+};
+use tink_tests::expect_err;
+
+lazy_static! {
+    static ref ENCLAVE: Arc<SoftwareEnclave> = Arc::new(SoftwareEnclave::new());
+}
+static INIT: Once = Once::new();
+
+fn setup() {
+    INIT.call_once(|| {
+        tink_apple::register(ENCLAVE.clone()).unwrap();
+    });
+}
+
+fn template(
+    hash: HashType,
+    curve: EllipticCurveType,
+    encoding: EcdsaSignatureEncoding,
+) -> KeyTemplate {
+    let format = tink_proto::SecureEnclaveEcdsaKeyFormat {
+        params: Some(tink_proto::EcdsaParams {
+            hash_type: hash as i32,
+            curve: curve as i32,
+            encoding: encoding as i32,
+        }),
+    };
+    let mut value = Vec::new();
+    format.encode(&mut value).unwrap();
+    KeyTemplate {
+        type_url: tink_apple::SECURE_ENCLAVE_SIGNER_TYPE_URL.to_string(),
+        value,
+        output_prefix_type: OutputPrefixType::Tink as i32,
+    }
+}
+
+#[test]
+fn test_secure_enclave_sign_verify() {
+    setup();
+    let kh = Handle::new(&tink_apple::secure_enclave_p256_key_template()).unwrap();
+    let signer = tink_signature::new_signer(&kh).unwrap();
+    let data = b"data to sign";
+    let sig = signer.sign(data).unwrap();
+
+    // Verification only needs the standard ECDSA verifier.
+    let pub_kh = kh.public().unwrap();
+    let pub_ks = insecure::keyset_material(&pub_kh);
+    assert_eq!(
+        pub_ks.key[0].key_data.as_ref().unwrap().type_url,
+        tink_signature::ECDSA_VERIFIER_TYPE_URL
+    );
+    let verifier = tink_signature::new_verifier(&pub_kh).unwrap();
+    verifier.verify(&sig, data).unwrap();
+    expect_err(verifier.verify(&sig, b"other data"), "invalid signature");
+}
+
+#[test]
+fn test_secure_enclave_p1363_encoding() {
+    setup();
+    let kh = Handle::new(&template(
+        HashType::Sha256,
+        EllipticCurveType::NistP256,
+        EcdsaSignatureEncoding::IeeeP1363,
+    ))
+    .unwrap();
+    let signer = tink_signature::new_signer(&kh).unwrap();
+    let sig = signer.sign(b"data").unwrap();
+    // 5-byte TINK prefix followed by r || s.
+    assert_eq!(sig.len(), 5 + 64);
+    let verifier = tink_signature::new_verifier(&kh.public().unwrap()).unwrap();
+    verifier.verify(&sig, b"data").unwrap();
+}
+
+#[test]
+fn test_secure_enclave_keyset_has_no_secrets() {
+    setup();
+    let kh = Handle::new(&tink_apple::secure_enclave_p256_key_template()).unwrap();
+    let ks = insecure::keyset_material(&kh);
+    assert_eq!(
+        ks.key[0].key_data.as_ref().unwrap().key_material_type,
+        KeyMaterialType::Remote as i32
+    );
+
+    // The keyset only refers to the key in the enclave, so can be stored in the clear.
+    let mut mem_keyset = MemReaderWriter::default();
+    kh.write_with_no_secrets(&mut mem_keyset).unwrap();
+    let kh2 = Handle::read_with_no_secrets(&mut mem_keyset).unwrap();
+    let sig = tink_signature::new_signer(&kh2)
+        .unwrap()
+        .sign(b"data")
+        .unwrap();
+    let verifier = tink_signature::new_verifier(&kh.public().unwrap()).unwrap();
+    verifier.verify(&sig, b"data").unwrap();
+}
+
+#[test]
+fn test_secure_enclave_deleted_key() {
+    setup();
+    let kh = Handle::new(&tink_apple::secure_enclave_p256_key_template()).unwrap();
+    let signer = tink_signature::new_signer(&kh).unwrap();
+    let ks = insecure::keyset_material(&kh);
+    let key = tink_proto::SecureEnclaveEcdsaPrivateKey::decode(
+        ks.key[0].key_data.as_ref().unwrap().value.as_ref(),
+    )
+    .unwrap();
+    assert!(ENCLAVE.delete_key(&key.application_label));
+    expect_err(signer.sign(b"data"), "key not found");
+}
+
+#[test]
+fn test_secure_enclave_invalid_params() {
+    setup();
+    for (hash, curve, encoding, err) in &[
+        (
+            HashType::Sha512,
+            EllipticCurveType::NistP256,
+            EcdsaSignatureEncoding::Der,
+            "unsupported hash type",
+        ),
+        (
+            HashType::Sha256,
+            EllipticCurveType::NistP384,
+            EcdsaSignatureEncoding::Der,
+            "unsupported curve",
+        ),
+        (
+            HashType::Sha256,
+            EllipticCurveType::NistP256,
+            EcdsaSignatureEncoding::UnknownEncoding,
+            "unsupported signature encoding",
+        ),
+    ] {
+        expect_err(Handle::new(&template(*hash, *curve, *encoding)), err);
+    }
+
+    let mut kt = tink_apple::secure_enclave_p256_key_template();
+    kt.value = vec![];
+    expect_err(Handle::new(&kt), "invalid key format");
+}
+
+#[test]
+fn test_secure_enclave_invalid_key() {
+    setup();
+    let (application_label, point) = ENCLAVE.generate_key().unwrap();
+    let params = tink_proto::EcdsaParams {
+        hash_type: HashType::Sha256 as i32,
+        curve: EllipticCurveType::NistP256 as i32,
+        encoding: EcdsaSignatureEncoding::Der as i32,
+    };
+    let valid_key = tink_proto::SecureEnclaveEcdsaPrivateKey {
+        version: 0,
+        public_key: Some(tink_proto::EcdsaPublicKey {
+            version: 0,
+            params: Some(params),
+            x: point[1..33].to_vec(),
+            y: point[33..].to_vec(),
+        }),
+        application_label,
+    };
+    let km =
+        tink_core::registry::get_key_manager(tink_apple::SECURE_ENCLAVE_SIGNER_TYPE_URL).unwrap();
+    let mut serialized = Vec::new();
+    valid_key.encode(&mut serialized).unwrap();
+    assert!(km.primitive(&serialized).is_ok());
+
+    let mut key = valid_key.clone();
+    key.version = 1;
+    let mut bad_version = Vec::new();
+    key.encode(&mut bad_version).unwrap();
+    expect_err(km.primitive(&bad_version), "version");
+
+    let mut key = valid_key.clone();
+    key.application_label = vec![];
+    let mut no_label = Vec::new();
+    key.encode(&mut no_label).unwrap();
+    expect_err(km.primitive(&no_label), "no application label");
+
+    let mut key = valid_key;
+    key.public_key.as_mut().unwrap().y[0] ^= 0x01;
+    let mut off_curve = Vec::new();
+    key.encode(&mut off_curve).unwrap();
+    expect_err(km.primitive(&off_curve), "invalid public key");
+
+    expect_err(km.primitive(&[]), "invalid key");
+}
+
+#[test]
+fn test_secure_enclave_register_twice() {
+    setup();
+    expect_err(
+        tink_apple::register(Arc::new(SoftwareEnclave::new())),
+        "already registered",
+    );
+}
@@ -183,3 +183,33 @@ fn test_get_aead_non_supported_uri() {
         "must start with prefix",
     );
 }
+
+#[test]
+fn test_capabilities() {
+    let uri_prefix = "aws-kms://arn:aws-us-gov:kms:us-gov-east-1:235739564943:key/";
+    let supported_key_uri = "aws-kms://arn:aws-us-gov:kms:us-gov-east-1:235739564943:key/3ee50705-5a82-4f5b-9753-05c4f473922f";
+    let non_supported_key_uri = "aws-kms://arn:aws-us-gov:kms:us-gov-east-DOES-NOT-EXIST:key/";
+
+    let client = AwsClient::new(uri_prefix).unwrap();
+    let capabilities = client.capabilities(supported_key_uri);
+    assert!(capabilities.associated_data);
+    assert!(capabilities.generate_data_key);
+    assert!(capabilities.sign);
+    assert!(!client.capabilities(non_supported_key_uri).sign);
+}
+
+#[test]
+fn test_get_signer_non_supported_uri() {
+    let uri_prefix = "aws-kms://arn:aws-us-gov:kms:us-gov-east-1:235739564943:key/";
+    let non_supported_key_uri = "aws-kms://arn:aws-us-gov:kms:us-gov-east-DOES-NOT-EXIST:key/";
+
+    let client = AwsClient::new(uri_prefix).unwrap();
+    tink_tests::expect_err(
+        client.get_signer(non_supported_key_uri),
+        "must start with prefix",
+    );
+    tink_tests::expect_err(
+        client.get_public_key_data(non_supported_key_uri),
+        "must start with prefix",
+    );
+}
@@ -0,0 +1,127 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use std::io::{Read, Write};
+use tink_core::associated_data::{
+    canonicalize, AeadWithOptionalAd, DeterministicAeadWithOptionalAd,
+    HybridDecryptWithOptionalContext, HybridEncryptWithOptionalContext,
+    StreamingAeadWithOptionalAd,
+};
+
+const PLAINTEXT: &[u8] = b"null associated data";
+
+/// AES-256-GCM ciphertext (IV || ciphertext || tag) produced by Java's JCE with key 00..1f and IV
+/// 10..1b, without calling `updateAAD()` (which is what Tink Java's `AesGcmJce` does for null
+/// associated data).
+const JAVA_NULL_AAD_AES_GCM: &str = "101112131415161718191a1b138bf47a69a849c0a516617c7b1c0d73b33\
+                                     13a6fcc99f1c8d926b91299b9b78be955bbc7";
+
+/// AES-SIV ciphertext with key 00..3f and a single empty associated data element, which is how
+/// the S2V construction in Tink Java's `AesSiv` treats null associated data.
+const NULL_AAD_AES_SIV: &str = "b37430dec336b607213d40174f1213221b14a5a28983b4a55c2f899566db3d\
+                                a9bc609df0";
+
+/// AES-SIV ciphertext with the same key but no associated data elements at all; Tink never
+/// produces this.
+const NO_HEADERS_AES_SIV: &str = "b84733491b0b70affb0e03b6e405d6c6806dd9b6cc899fd499f1b871ade76b\
+                                  828c3f52d3";
+
+#[test]
+fn test_canonicalize() {
+    assert_eq!(canonicalize(None), b"");
+    assert_eq!(canonicalize(Some(b"")), b"");
+    assert_eq!(canonicalize(Some(b"aad")), b"aad");
+}
+
+#[test]
+fn test_aes_gcm_null_aad_fixture() {
+    let key: Vec<u8> = (0..32).collect();
+    let aead = tink_aead::subtle::AesGcm::new(&key).unwrap();
+    let ct = hex::decode(JAVA_NULL_AAD_AES_GCM).unwrap();
+    assert_eq!(aead.decrypt_with_optional_ad(&ct, None).unwrap(), PLAINTEXT);
+    assert_eq!(
+        aead.decrypt_with_optional_ad(&ct, Some(b"")).unwrap(),
+        PLAINTEXT
+    );
+    assert!(aead.decrypt_with_optional_ad(&ct, Some(b"x")).is_err());
+}
+
+#[test]
+fn test_aes_siv_null_aad_fixture() {
+    let key: Vec<u8> = (0..64).collect();
+    let daead = tink_daead::subtle::AesSiv::new(&key).unwrap();
+    let expected = hex::decode(NULL_AAD_AES_SIV).unwrap();
+    assert_eq!(
+        daead
+            .encrypt_deterministically_with_optional_ad(PLAINTEXT, None)
+            .unwrap(),
+        expected
+    );
+    assert_eq!(
+        daead
+            .decrypt_deterministically_with_optional_ad(&expected, Some(b""))
+            .unwrap(),
+        PLAINTEXT
+    );
+    let no_headers = hex::decode(NO_HEADERS_AES_SIV).unwrap();
+    assert!(daead
+        .decrypt_deterministically_with_optional_ad(&no_headers, None)
+        .is_err());
+}
+
+#[test]
+fn test_streaming_aead_optional_aad() {
+    tink_streaming_aead::init();
+    let kh =
+        tink_core::keyset::Handle::new(&tink_streaming_aead::aes128_gcm_hkdf_4kb_key_template())
+            .unwrap();
+    let a = tink_streaming_aead::new(&kh).unwrap();
+
+    let buf = tink_tests::SharedBuf::new();
+    let mut w = a
+        .new_encrypting_writer_with_optional_ad(Box::new(buf.clone()), None)
+        .unwrap();
+    w.write_all(PLAINTEXT).unwrap();
+    w.close().unwrap();
+
+    let mut r = a
+        .new_decrypting_reader_with_optional_ad(Box::new(buf), Some(b""))
+        .unwrap();
+    let mut pt = vec![];
+    r.read_to_end(&mut pt).unwrap();
+    assert_eq!(pt, PLAINTEXT);
+}
+
+#[test]
+fn test_hybrid_optional_context_info() {
+    tink_hybrid::init();
+    let kh_priv =
+        tink_core::keyset::Handle::new(&tink_hybrid::ecies_hkdf_aes128_gcm_key_template()).unwrap();
+    let e = tink_hybrid::new_encrypt(&kh_priv.public().unwrap()).unwrap();
+    let d = tink_hybrid::new_decrypt(&kh_priv).unwrap();
+
+    let ct = e.encrypt_with_optional_context(PLAINTEXT, None).unwrap();
+    assert_eq!(d.decrypt(&ct, b"").unwrap(), PLAINTEXT);
+    assert_eq!(
+        d.decrypt_with_optional_context(&ct, None).unwrap(),
+        PLAINTEXT
+    );
+    let ct = e.encrypt(PLAINTEXT, b"").unwrap();
+    assert_eq!(
+        d.decrypt_with_optional_context(&ct, None).unwrap(),
+        PLAINTEXT
+    );
+}
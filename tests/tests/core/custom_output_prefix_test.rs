@@ -0,0 +1,222 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_core::{
+    registry::{register_custom_output_prefix, CustomOutputPrefix},
+    Aead, Mac,
+};
+use tink_proto::{KeyStatusType, OutputPrefixType};
+
+/// Build a keyset holding a single key with the given (possibly custom) output prefix type.
+fn single_key_keyset(
+    key_data: &tink_proto::KeyData,
+    key_id: tink_core::KeyId,
+    output_prefix_type: i32,
+) -> tink_core::keyset::Handle {
+    let mut key = tink_tests::new_key(
+        key_data,
+        KeyStatusType::Enabled,
+        key_id,
+        OutputPrefixType::Tink,
+    );
+    key.output_prefix_type = output_prefix_type;
+    tink_core::keyset::insecure::new_handle(tink_tests::new_keyset(key_id, vec![key])).unwrap()
+}
+
+#[test]
+fn test_register_custom_output_prefix_validation() {
+    let valid = CustomOutputPrefix {
+        output_prefix_type: 1001,
+        start_byte: 0xa1,
+        behaves_as: OutputPrefixType::Tink,
+    };
+    for (bad, err) in &[
+        (
+            CustomOutputPrefix {
+                output_prefix_type: OutputPrefixType::Crunchy as i32,
+                ..valid
+            },
+            "standard output prefix type",
+        ),
+        (
+            CustomOutputPrefix {
+                output_prefix_type: OutputPrefixType::UnknownPrefix as i32,
+                ..valid
+            },
+            "standard output prefix type",
+        ),
+        (
+            CustomOutputPrefix {
+                start_byte: tink_core::cryptofmt::TINK_START_BYTE,
+                ..valid
+            },
+            "reserved",
+        ),
+        (
+            CustomOutputPrefix {
+                start_byte: tink_core::cryptofmt::LEGACY_START_BYTE,
+                ..valid
+            },
+            "reserved",
+        ),
+        (
+            CustomOutputPrefix {
+                behaves_as: OutputPrefixType::Raw,
+                ..valid
+            },
+            "must behave as TINK or LEGACY",
+        ),
+    ] {
+        tink_tests::expect_err(register_custom_output_prefix(*bad), err);
+    }
+
+    register_custom_output_prefix(valid).unwrap();
+    assert_eq!(tink_core::registry::custom_output_prefix(1001), Some(valid));
+    tink_tests::expect_err(
+        register_custom_output_prefix(CustomOutputPrefix {
+            start_byte: 0xa2,
+            ..valid
+        }),
+        "already registered",
+    );
+    tink_tests::expect_err(
+        register_custom_output_prefix(CustomOutputPrefix {
+            output_prefix_type: 1002,
+            ..valid
+        }),
+        "already registered",
+    );
+    assert_eq!(tink_core::registry::custom_output_prefix(1002), None);
+}
+
+#[test]
+fn test_custom_output_prefix_encode_parse() {
+    let custom = CustomOutputPrefix {
+        output_prefix_type: 1010,
+        start_byte: 0xb0,
+        behaves_as: OutputPrefixType::Tink,
+    };
+    let prefix = custom.encode(0x01020304);
+    assert_eq!(prefix, vec![0xb0, 0x01, 0x02, 0x03, 0x04]);
+    let mut data = prefix.clone();
+    data.extend_from_slice(b"payload");
+    assert_eq!(custom.parse(&data), Some(0x01020304));
+    assert_eq!(custom.parse(&prefix[..4]), None);
+    assert_eq!(custom.parse(&[0x01, 0x01, 0x02, 0x03, 0x04]), None);
+}
+
+#[test]
+fn test_custom_output_prefix_aead() {
+    tink_aead::init();
+    let custom = CustomOutputPrefix {
+        output_prefix_type: 1020,
+        start_byte: 0xc0,
+        behaves_as: OutputPrefixType::Tink,
+    };
+    let key = tink_tests::new_aes_gcm_key(tink_tests::AES_GCM_KEY_VERSION, 16);
+    let key_data = tink_tests::new_key_data(
+        tink_tests::AES_GCM_TYPE_URL,
+        &tink_tests::proto_encode(&key),
+        tink_proto::key_data::KeyMaterialType::Symmetric,
+    );
+
+    // Unregistered custom prefix types are rejected.
+    let mut ks_key = tink_tests::new_key(
+        &key_data,
+        KeyStatusType::Enabled,
+        42,
+        OutputPrefixType::Tink,
+    );
+    ks_key.output_prefix_type = custom.output_prefix_type;
+    tink_tests::expect_err(tink_core::keyset::validate_key(&ks_key), "unknown prefix");
+
+    register_custom_output_prefix(custom).unwrap();
+    assert!(tink_core::keyset::validate_key(&ks_key).is_ok());
+    let kh = single_key_keyset(&key_data, 42, custom.output_prefix_type);
+    let a = tink_aead::new(&kh).unwrap();
+
+    let ct = a.encrypt(b"plaintext", b"aad").unwrap();
+    assert_eq!(&ct[..5], &custom.encode(42)[..]);
+    assert_eq!(custom.parse(&ct), Some(42));
+    assert_eq!(a.decrypt(&ct, b"aad").unwrap(), b"plaintext");
+
+    // A ciphertext produced by the foreign system: the custom prefix followed by a raw AES-GCM
+    // ciphertext.
+    let raw = tink_aead::subtle::AesGcm::new(&key.key_value).unwrap();
+    let mut foreign_ct = custom.encode(42);
+    foreign_ct.extend_from_slice(&raw.encrypt(b"foreign", b"aad").unwrap());
+    assert_eq!(a.decrypt(&foreign_ct, b"aad").unwrap(), b"foreign");
+
+    // The same ciphertext with a TINK prefix is not recognized.
+    let mut tink_ct = tink_core::cryptofmt::output_prefix(&tink_tests::new_key(
+        &key_data,
+        KeyStatusType::Enabled,
+        42,
+        OutputPrefixType::Tink,
+    ))
+    .unwrap();
+    tink_ct.extend_from_slice(&foreign_ct[5..]);
+    assert!(a.decrypt(&tink_ct, b"aad").is_err());
+}
+
+#[test]
+fn test_custom_output_prefix_behaves_as_legacy() {
+    tink_mac::init();
+    let custom = CustomOutputPrefix {
+        output_prefix_type: 1030,
+        start_byte: 0xd0,
+        behaves_as: OutputPrefixType::Legacy,
+    };
+    register_custom_output_prefix(custom).unwrap();
+    let key_data = tink_tests::new_hmac_key_data(tink_proto::HashType::Sha256, 16);
+    let kh = single_key_keyset(&key_data, 7, custom.output_prefix_type);
+    let m = tink_mac::new(&kh).unwrap();
+    let tag = m.compute_mac(b"data").unwrap();
+    assert_eq!(&tag[..5], &custom.encode(7)[..]);
+    assert!(m.verify_mac(&tag, b"data").is_ok());
+
+    // As for LEGACY keys, the MAC covers the data followed by a zero byte.
+    let raw_kh = single_key_keyset(&key_data, 7, OutputPrefixType::Raw as i32);
+    let raw = tink_mac::new(&raw_kh).unwrap();
+    assert!(raw.verify_mac(&tag[5..], b"data\x00").is_ok());
+}
+
+#[test]
+fn test_custom_output_prefix_key_template() {
+    tink_aead::init();
+    let custom = CustomOutputPrefix {
+        output_prefix_type: 1040,
+        start_byte: 0xe0,
+        behaves_as: OutputPrefixType::Tink,
+    };
+    let mut template = tink_aead::aes128_gcm_key_template();
+    template.output_prefix_type = custom.output_prefix_type;
+    tink_tests::expect_err(
+        tink_core::keyset::Handle::new(&template),
+        "unknown output prefix type",
+    );
+
+    register_custom_output_prefix(custom).unwrap();
+    let kh = tink_core::keyset::Handle::new(&template).unwrap();
+    let info = kh.keyset_info();
+    assert_eq!(
+        info.key_info[0].output_prefix_type,
+        custom.output_prefix_type
+    );
+    let a = tink_aead::new(&kh).unwrap();
+    let ct = a.encrypt(b"plaintext", &[]).unwrap();
+    assert_eq!(custom.parse(&ct), Some(info.primary_key_id));
+}
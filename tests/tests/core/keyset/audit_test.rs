@@ -0,0 +1,153 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use tink_core::keyset::{
+    insecure, AuditTrail, FixedClock, KeyEvent, KeyEventKind, KeysetStore, Manager, MemKeysetStore,
+};
+
+fn main_key() -> Box<tink_aead::subtle::AesGcm> {
+    Box::new(tink_aead::subtle::AesGcm::new(&[b'A'; 32]).unwrap())
+}
+
+#[test]
+fn test_audit_trail_records_events() {
+    tink_aead::init();
+    let kt = tink_aead::aes128_gcm_key_template();
+    let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+    let clock = Arc::new(FixedClock::new(start));
+
+    let mut ksm = Manager::new();
+    assert!(ksm.audit_trail().is_none());
+    ksm.set_audit_clock(clock.clone());
+    ksm.enable_audit_trail("alice");
+    let id1 = ksm.rotate(&kt).unwrap();
+    clock.advance(Duration::from_secs(10));
+    ksm.set_audit_actor("bob");
+    let id2 = ksm.rotate(&kt).unwrap();
+    ksm.disable(id1).unwrap();
+    ksm.enable(id1).unwrap();
+    ksm.disable(id1).unwrap();
+    ksm.destroy(id1).unwrap();
+    ksm.delete(id1).unwrap();
+    // Failed operations are not recorded.
+    assert!(ksm.disable(id2).is_err());
+
+    let later = start + Duration::from_secs(10);
+    let ev = |key_id, kind, time, actor: &str| KeyEvent {
+        key_id,
+        kind,
+        time,
+        actor: actor.to_string(),
+    };
+    let want = vec![
+        ev(id1, KeyEventKind::Created, start, "alice"),
+        ev(id1, KeyEventKind::Rotated { from: 0 }, start, "alice"),
+        ev(id2, KeyEventKind::Created, later, "bob"),
+        ev(id2, KeyEventKind::Rotated { from: id1 }, later, "bob"),
+        ev(id1, KeyEventKind::Disabled, later, "bob"),
+        ev(id1, KeyEventKind::Enabled, later, "bob"),
+        ev(id1, KeyEventKind::Disabled, later, "bob"),
+        ev(id1, KeyEventKind::Destroyed, later, "bob"),
+        ev(id1, KeyEventKind::Deleted, later, "bob"),
+    ];
+    let trail = ksm.audit_trail().unwrap();
+    assert_eq!(trail.events, want);
+    assert_eq!(trail.events_for_key(id2).len(), 2);
+
+    let decoded = AuditTrail::decode(&trail.encode().unwrap()).unwrap();
+    assert_eq!(&decoded, trail);
+}
+
+#[test]
+fn test_audit_trail_survives_store() {
+    tink_aead::init();
+    let kt = tink_aead::aes128_gcm_key_template();
+    let store = MemKeysetStore::default();
+
+    let mut ksm = Manager::new();
+    ksm.enable_audit_trail("alice");
+    let id1 = ksm.rotate(&kt).unwrap();
+    ksm.save_to_store(&store, main_key()).unwrap();
+
+    let mut ksm2 = Manager::new_from_store(&store, main_key()).unwrap();
+    assert_eq!(ksm2.audit_trail(), ksm.audit_trail());
+    assert_eq!(
+        insecure::keyset_material(&ksm2.handle().unwrap()),
+        insecure::keyset_material(&ksm.handle().unwrap())
+    );
+    ksm2.set_audit_actor("bob");
+    let id2 = ksm2.rotate(&kt).unwrap();
+    ksm2.save_to_store(&store, main_key()).unwrap();
+
+    let ksm3 = Manager::new_from_store(&store, main_key()).unwrap();
+    let trail = ksm3.audit_trail().unwrap();
+    assert_eq!(trail.events.len(), 4);
+    assert_eq!(trail.events[3].kind, KeyEventKind::Rotated { from: id1 });
+    assert_eq!(trail.events[3].key_id, id2);
+    assert_eq!(trail.events[3].actor, "bob");
+
+    // The metadata is bound to the encrypted keyset.
+    let mut stored = store.load().unwrap();
+    let mut modified = trail.clone();
+    modified.events.truncate(2);
+    stored.metadata = modified.encode().unwrap();
+    store.save(&stored).unwrap();
+    assert!(Manager::new_from_store(&store, main_key()).is_err());
+}
+
+#[test]
+fn test_store_without_audit_trail() {
+    tink_aead::init();
+    let store = MemKeysetStore::default();
+    assert!(Manager::new_from_store(&store, main_key()).is_err());
+
+    let mut ksm = Manager::new();
+    ksm.rotate(&tink_aead::aes128_gcm_key_template()).unwrap();
+    ksm.save_to_store(&store, main_key()).unwrap();
+    assert!(store.load().unwrap().metadata.is_empty());
+    let ksm2 = Manager::new_from_store(&store, main_key()).unwrap();
+    assert!(ksm2.audit_trail().is_none());
+}
+
+#[test]
+fn test_audit_trail_decode_invalid() {
+    let trail = AuditTrail {
+        events: vec![KeyEvent {
+            key_id: 42,
+            kind: KeyEventKind::Destroyed,
+            time: SystemTime::UNIX_EPOCH + Duration::new(1, 2),
+            actor: "carol".to_string(),
+        }],
+    };
+    let encoded = trail.encode().unwrap();
+    assert_eq!(AuditTrail::decode(&encoded).unwrap(), trail);
+    for len in 0..encoded.len() {
+        assert!(AuditTrail::decode(&encoded[..len]).is_err(), "len {}", len);
+    }
+    let mut bad_version = encoded.clone();
+    bad_version[0] = 2;
+    tink_tests::expect_err(AuditTrail::decode(&bad_version), "unknown version");
+    let mut bad_kind = encoded.clone();
+    bad_kind[9] = 99;
+    tink_tests::expect_err(AuditTrail::decode(&bad_kind), "unknown event kind");
+    let mut trailing = encoded;
+    trailing.push(0);
+    tink_tests::expect_err(AuditTrail::decode(&trailing), "trailing data");
+}
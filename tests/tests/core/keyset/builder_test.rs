@@ -0,0 +1,197 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_core::keyset::{insecure, Handle, KeysetBuilder, KeysetEntry};
+use tink_proto::{key_data::KeyMaterialType, KeyStatusType, OutputPrefixType};
+use tink_tests::expect_err;
+
+#[test]
+fn test_keyset_builder() {
+    tink_aead::init();
+    let kt = tink_aead::aes128_gcm_key_template();
+    let raw_kt = tink_aead::aes256_gcm_no_prefix_key_template();
+    let h = Handle::builder()
+        .add_entry(KeysetEntry::from_template(&kt).with_fixed_id(1))
+        .add_entry(
+            KeysetEntry::from_template(&raw_kt)
+                .with_fixed_id(2)
+                .make_primary(),
+        )
+        .add_entry(KeysetEntry::from_template(&kt).with_status(KeyStatusType::Disabled))
+        .add_entry(KeysetEntry::from_template(&kt))
+        .build()
+        .unwrap();
+
+    let ks = insecure::keyset_material(&h);
+    assert_eq!(ks.primary_key_id, 2);
+    assert_eq!(ks.key.len(), 4);
+    assert_eq!(ks.key[0].key_id, 1);
+    assert_eq!(ks.key[1].key_id, 2);
+    assert_eq!(ks.key[1].output_prefix_type, OutputPrefixType::Raw as i32);
+    assert_eq!(ks.key[0].output_prefix_type, OutputPrefixType::Tink as i32);
+    assert_eq!(ks.key[2].status, KeyStatusType::Disabled as i32);
+    assert_eq!(ks.key[3].status, KeyStatusType::Enabled as i32);
+    assert_ne!(ks.key[2].key_id, ks.key[3].key_id);
+    for key in &ks.key {
+        assert_eq!(
+            key.key_data.as_ref().unwrap().key_material_type,
+            KeyMaterialType::Symmetric as i32
+        );
+    }
+
+    // The keyset is usable, and holds independent keys.
+    let a = tink_aead::new(&h).unwrap();
+    let ct = a.encrypt(b"plaintext", b"aad").unwrap();
+    assert_eq!(a.decrypt(&ct, b"aad").unwrap(), b"plaintext");
+    assert_ne!(ks.key[0].key_data, ks.key[3].key_data);
+}
+
+#[test]
+fn test_keyset_builder_from_key_data() {
+    tink_aead::init();
+    let kd = tink_tests::new_key_data(
+        tink_tests::AES_GCM_TYPE_URL,
+        &tink_tests::proto_encode(&tink_tests::new_aes_gcm_key(0, 16)),
+        KeyMaterialType::Symmetric,
+    );
+    let h = KeysetBuilder::new()
+        .add_entry(
+            KeysetEntry::from_key_data(kd.clone(), OutputPrefixType::Tink)
+                .with_fixed_id(42)
+                .make_primary(),
+        )
+        .add_entry(KeysetEntry::from_template(
+            &tink_aead::aes128_gcm_key_template(),
+        ))
+        .build()
+        .unwrap();
+    let ks = insecure::keyset_material(&h);
+    assert_eq!(ks.primary_key_id, 42);
+    assert_eq!(ks.key[0].key_data.as_ref(), Some(&kd));
+
+    // A migrated keyset with the same key decrypts ciphertexts from the original.
+    let original = Handle::builder()
+        .add_entry(
+            KeysetEntry::from_key_data(kd, OutputPrefixType::Tink)
+                .with_fixed_id(42)
+                .make_primary(),
+        )
+        .build()
+        .unwrap();
+    let ct = tink_aead::new(&original)
+        .unwrap()
+        .encrypt(b"plaintext", b"")
+        .unwrap();
+    assert_eq!(
+        tink_aead::new(&h).unwrap().decrypt(&ct, b"").unwrap(),
+        b"plaintext"
+    );
+
+    // Invalid key data is rejected.
+    let bad = tink_tests::new_key_data(
+        tink_tests::AES_GCM_TYPE_URL,
+        b"not a key",
+        KeyMaterialType::Symmetric,
+    );
+    expect_err(
+        KeysetBuilder::new()
+            .add_entry(KeysetEntry::from_key_data(bad, OutputPrefixType::Tink).make_primary())
+            .build(),
+        "invalid key data",
+    );
+}
+
+#[test]
+fn test_keyset_builder_invalid() {
+    tink_aead::init();
+    let kt = tink_aead::aes128_gcm_key_template();
+
+    expect_err(
+        KeysetBuilder::new().build(),
+        "exactly one entry must be primary",
+    );
+    expect_err(
+        KeysetBuilder::new()
+            .add_entry(KeysetEntry::from_template(&kt))
+            .build(),
+        "exactly one entry must be primary",
+    );
+    expect_err(
+        KeysetBuilder::new()
+            .add_entry(KeysetEntry::from_template(&kt).make_primary())
+            .add_entry(KeysetEntry::from_template(&kt).make_primary())
+            .build(),
+        "exactly one entry must be primary",
+    );
+    expect_err(
+        KeysetBuilder::new()
+            .add_entry(
+                KeysetEntry::from_template(&kt)
+                    .with_fixed_id(7)
+                    .make_primary(),
+            )
+            .add_entry(KeysetEntry::from_template(&kt).with_fixed_id(7))
+            .build(),
+        "duplicate key ID 7",
+    );
+    expect_err(
+        KeysetBuilder::new()
+            .add_entry(
+                KeysetEntry::from_template(&kt)
+                    .with_status(KeyStatusType::Disabled)
+                    .make_primary(),
+            )
+            .build(),
+        "primary key must be enabled",
+    );
+    expect_err(
+        KeysetBuilder::new()
+            .add_entry(KeysetEntry::from_template(&kt).make_primary())
+            .add_entry(KeysetEntry::from_template(&kt).with_status(KeyStatusType::Destroyed))
+            .build(),
+        "entry 1: invalid key status",
+    );
+    let mut bad_kt = kt.clone();
+    bad_kt.type_url = "some unknown type".to_string();
+    expect_err(
+        KeysetBuilder::new()
+            .add_entry(KeysetEntry::from_template(&bad_kt).make_primary())
+            .build(),
+        "cannot create KeyData",
+    );
+}
+
+#[test]
+fn test_keyset_builder_reuse() {
+    tink_aead::init();
+    let builder = KeysetBuilder::new()
+        .add_entry(
+            KeysetEntry::from_template(&tink_aead::aes128_gcm_key_template())
+                .with_fixed_id(5)
+                .with_random_id()
+                .make_primary(),
+        )
+        .add_entry(KeysetEntry::from_template(
+            &tink_aead::aes128_gcm_key_template(),
+        ));
+    assert_eq!(builder.entry_count(), 2);
+
+    // Each build generates fresh keys.
+    let ks1 = insecure::keyset_material(&builder.build().unwrap());
+    let ks2 = insecure::keyset_material(&builder.build().unwrap());
+    assert_eq!(ks1.key.len(), 2);
+    assert_ne!(ks1.key[0].key_data, ks2.key[0].key_data);
+}
@@ -0,0 +1,245 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use std::sync::Arc;
+use tink_core::keyset::{Handle, Manager, VerificationBundle, VerificationBundleCache};
+use tink_proto::KeyStatusType;
+use tink_tests::expect_err;
+
+const MESSAGE: &[u8] = b"data signed by a key in the bundle";
+
+/// Return signer and verifier handles for the key that signs bundles.
+fn bundle_signing_keys() -> (Handle, Handle) {
+    let signing = Handle::new(&tink_signature::ed25519_key_template()).unwrap();
+    let verifying = signing.public().unwrap();
+    (signing, verifying)
+}
+
+/// Return a private keyset holding two ECDSA keys and an Ed25519 key.
+fn private_keyset() -> Handle {
+    let mut km = Manager::new();
+    km.rotate(&tink_signature::ecdsa_p256_key_template())
+        .unwrap();
+    km.rotate(&tink_signature::ed25519_key_template()).unwrap();
+    km.rotate(&tink_signature::ecdsa_p256_key_template())
+        .unwrap();
+    km.handle().unwrap()
+}
+
+fn bundle(h: &Handle, serial: u64, signing: &Handle) -> Vec<u8> {
+    let signer = tink_signature::new_signer(signing).unwrap();
+    h.verification_bundle(serial, signer.as_ref()).unwrap()
+}
+
+fn parse(bundle: &[u8], verifying: &Handle) -> Result<VerificationBundle, tink_core::TinkError> {
+    let verifier = tink_signature::new_verifier(verifying).unwrap();
+    VerificationBundle::parse(bundle, verifier.as_ref())
+}
+
+#[test]
+fn test_verification_bundle_round_trip() {
+    tink_signature::init();
+    let (signing, verifying) = bundle_signing_keys();
+    let h = private_keyset();
+    let data = bundle(&h, 7, &signing);
+
+    let loaded = parse(&data, &verifying).unwrap();
+    assert_eq!(loaded.serial(), 7);
+    assert_eq!(
+        loaded.handle().keyset_info(),
+        h.public().unwrap().keyset_info()
+    );
+
+    // Signatures from the original keyset verify with the bundled keys.
+    let signer = tink_signature::new_signer(&h).unwrap();
+    let signature = signer.sign(MESSAGE).unwrap();
+    let verifier = tink_signature::new_verifier(loaded.handle()).unwrap();
+    verifier.verify(&signature, MESSAGE).unwrap();
+}
+
+#[test]
+fn test_verification_bundle_from_public_keyset() {
+    tink_signature::init();
+    let (signing, verifying) = bundle_signing_keys();
+    let h = private_keyset();
+    let from_private = bundle(&h, 1, &signing);
+    let from_public = bundle(&h.public().unwrap(), 1, &signing);
+
+    let a = parse(&from_private, &verifying).unwrap();
+    let b = parse(&from_public, &verifying).unwrap();
+    assert_eq!(a.handle().keyset_info(), b.handle().keyset_info());
+}
+
+#[test]
+fn test_verification_bundle_is_smaller_than_keyset() {
+    tink_signature::init();
+    let (signing, _) = bundle_signing_keys();
+    let h = private_keyset();
+    let data = bundle(&h, 1, &signing);
+
+    let public = h.public().unwrap();
+    let mut serialized = Vec::new();
+    public
+        .write_with_no_secrets(&mut tink_core::keyset::BinaryWriter::new(&mut serialized))
+        .unwrap();
+    // The bundle carries a 64-byte signature and is still smaller.
+    assert!(
+        data.len() < serialized.len(),
+        "bundle {} bytes, keyset {} bytes",
+        data.len(),
+        serialized.len()
+    );
+}
+
+#[test]
+fn test_verification_bundle_omits_disabled_keys() {
+    tink_signature::init();
+    let (signing, verifying) = bundle_signing_keys();
+    let h = private_keyset();
+    let info = h.keyset_info();
+    let disabled = info
+        .key_info
+        .iter()
+        .find(|k| k.key_id != info.primary_key_id)
+        .unwrap()
+        .key_id;
+    let mut km = Manager::new_from_handle(h);
+    km.disable(disabled).unwrap();
+    let h = km.handle().unwrap();
+
+    let loaded = parse(&bundle(&h, 1, &signing), &verifying).unwrap();
+    let loaded_info = loaded.handle().keyset_info();
+    assert_eq!(loaded_info.key_info.len(), 2);
+    assert!(loaded_info
+        .key_info
+        .iter()
+        .all(|k| k.key_id != disabled && k.status == KeyStatusType::Enabled as i32));
+    assert_eq!(loaded_info.primary_key_id, info.primary_key_id);
+}
+
+#[test]
+fn test_verification_bundle_rejects_tampering() {
+    tink_signature::init();
+    let (signing, verifying) = bundle_signing_keys();
+    let data = bundle(&private_keyset(), 1, &signing);
+
+    for i in 0..data.len() {
+        let mut modified = data.clone();
+        modified[i] ^= 0x01;
+        assert!(parse(&modified, &verifying).is_err(), "byte {} modified", i);
+    }
+    for len in 0..data.len() {
+        assert!(
+            parse(&data[..len], &verifying).is_err(),
+            "truncated to {}",
+            len
+        );
+    }
+    let mut extended = data.clone();
+    extended.push(0);
+    assert!(parse(&extended, &verifying).is_err());
+
+    // A bundle signed by a different key is rejected.
+    let (other_signing, _) = bundle_signing_keys();
+    let other = bundle(&private_keyset(), 1, &other_signing);
+    expect_err(parse(&other, &verifying), "invalid signature");
+}
+
+#[test]
+fn test_verification_bundle_rejects_symmetric_keys() {
+    tink_mac::init();
+    tink_signature::init();
+    let (signing, _) = bundle_signing_keys();
+    let h = Handle::new(&tink_mac::hmac_sha256_tag256_key_template()).unwrap();
+    let signer = tink_signature::new_signer(&signing).unwrap();
+    assert!(h.verification_bundle(1, signer.as_ref()).is_err());
+}
+
+#[test]
+fn test_verification_bundle_cache() {
+    tink_signature::init();
+    let (signing, verifying) = bundle_signing_keys();
+    let verifier = tink_signature::new_verifier(&verifying).unwrap();
+    let cache = VerificationBundleCache::new();
+    assert!(cache.current().is_none());
+    assert!(cache.handle().is_none());
+
+    let h1 = private_keyset();
+    let h2 = private_keyset();
+    let b1 = bundle(&h1, 1, &signing);
+    let b2 = bundle(&h2, 2, &signing);
+
+    assert!(cache.refresh(&b1, verifier.as_ref()).unwrap());
+    assert_eq!(cache.serial(), Some(1));
+    let held = cache.current().unwrap();
+
+    // Refreshing with the same serial leaves the cached bundle in place.
+    assert!(!cache.refresh(&b1, verifier.as_ref()).unwrap());
+    assert!(Arc::ptr_eq(&held, &cache.current().unwrap()));
+
+    assert!(cache.refresh(&b2, verifier.as_ref()).unwrap());
+    assert_eq!(cache.serial(), Some(2));
+    assert_eq!(
+        cache.handle().unwrap().keyset_info(),
+        h2.public().unwrap().keyset_info()
+    );
+    // Readers holding the previous bundle keep a complete copy of it.
+    assert_eq!(held.serial(), 1);
+    assert_eq!(
+        held.handle().keyset_info(),
+        h1.public().unwrap().keyset_info()
+    );
+
+    // Older bundles are rejected.
+    expect_err(cache.refresh(&b1, verifier.as_ref()), "older than cached");
+    assert_eq!(cache.serial(), Some(2));
+
+    // Invalid bundles are rejected without disturbing the cache.
+    let mut bad = bundle(&h1, 3, &signing);
+    let last = bad.len() - 1;
+    bad[last] ^= 0x01;
+    expect_err(cache.refresh(&bad, verifier.as_ref()), "invalid signature");
+    assert_eq!(cache.serial(), Some(2));
+}
+
+#[test]
+fn test_verification_bundle_cache_shared_between_threads() {
+    tink_signature::init();
+    let (signing, verifying) = bundle_signing_keys();
+    let bundles: Vec<Vec<u8>> = (1..=20)
+        .map(|serial| bundle(&private_keyset(), serial, &signing))
+        .collect();
+    let cache = Arc::new(VerificationBundleCache::new());
+
+    let threads: Vec<_> = bundles
+        .into_iter()
+        .map(|data| {
+            let cache = cache.clone();
+            let verifying = verifying.clone();
+            std::thread::spawn(move || {
+                let verifier = tink_signature::new_verifier(&verifying).unwrap();
+                // Refreshes arriving out of order may be rejected as rollbacks.
+                let _ = cache.refresh(&data, verifier.as_ref());
+                let current = cache.current().unwrap();
+                assert_eq!(current.handle().keyset_info().key_info.len(), 3);
+            })
+        })
+        .collect();
+    for t in threads {
+        t.join().unwrap();
+    }
+    assert_eq!(cache.serial(), Some(20));
+}
@@ -0,0 +1,148 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_core::keyset;
+use tink_proto::{keyset_info::KeyInfo, KeyStatusType, KeysetInfo, OutputPrefixType};
+
+fn key_info(key_id: u32, status: KeyStatusType, prefix: OutputPrefixType) -> KeyInfo {
+    KeyInfo {
+        type_url: "type.googleapis.com/google.crypto.tink.AesGcmKey".to_string(),
+        status: status as i32,
+        key_id,
+        output_prefix_type: prefix as i32,
+    }
+}
+
+fn keyset_info(primary_key_id: u32, key_info: Vec<KeyInfo>) -> KeysetInfo {
+    KeysetInfo {
+        primary_key_id,
+        key_info,
+    }
+}
+
+#[test]
+fn test_diff_identical() {
+    let info = keyset_info(
+        1,
+        vec![
+            key_info(1, KeyStatusType::Enabled, OutputPrefixType::Tink),
+            key_info(2, KeyStatusType::Enabled, OutputPrefixType::Raw),
+        ],
+    );
+    let result = keyset::diff(&info, &info);
+    assert!(result.is_empty());
+    assert!(keyset::undecryptable_prefixes(&info, &info)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_diff_changes() {
+    let old = keyset_info(
+        1,
+        vec![
+            key_info(1, KeyStatusType::Enabled, OutputPrefixType::Tink),
+            key_info(2, KeyStatusType::Enabled, OutputPrefixType::Legacy),
+            key_info(3, KeyStatusType::Enabled, OutputPrefixType::Raw),
+        ],
+    );
+    let new = keyset_info(
+        4,
+        vec![
+            key_info(1, KeyStatusType::Enabled, OutputPrefixType::Tink),
+            key_info(3, KeyStatusType::Disabled, OutputPrefixType::Raw),
+            key_info(4, KeyStatusType::Enabled, OutputPrefixType::Tink),
+        ],
+    );
+    let result = keyset::diff(&old, &new);
+    assert!(!result.is_empty());
+    assert_eq!(
+        result.added.iter().map(|k| k.key_id).collect::<Vec<_>>(),
+        vec![4]
+    );
+    assert_eq!(
+        result.removed.iter().map(|k| k.key_id).collect::<Vec<_>>(),
+        vec![2]
+    );
+    assert_eq!(
+        result.status_changed,
+        vec![keyset::KeyStatusChange {
+            key_id: 3,
+            old_status: KeyStatusType::Enabled,
+            new_status: KeyStatusType::Disabled,
+        }]
+    );
+    assert_eq!(result.primary_changed, Some((1, 4)));
+
+    let prefixes = keyset::undecryptable_prefixes(&old, &new).unwrap();
+    assert_eq!(
+        prefixes,
+        vec![
+            keyset::UndecryptablePrefix {
+                key_id: 2,
+                prefix: vec![0, 0, 0, 0, 2],
+            },
+            keyset::UndecryptablePrefix {
+                key_id: 3,
+                prefix: vec![],
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_undecryptable_prefixes_ignores_disabled_keys() {
+    let old = keyset_info(
+        1,
+        vec![
+            key_info(1, KeyStatusType::Enabled, OutputPrefixType::Tink),
+            key_info(2, KeyStatusType::Disabled, OutputPrefixType::Tink),
+        ],
+    );
+    let new = keyset_info(
+        1,
+        vec![key_info(1, KeyStatusType::Enabled, OutputPrefixType::Tink)],
+    );
+    assert!(keyset::undecryptable_prefixes(&old, &new)
+        .unwrap()
+        .is_empty());
+
+    // Re-enabling a key does not make anything undecryptable.
+    let result = keyset::diff(&new, &old);
+    assert_eq!(
+        result.added.iter().map(|k| k.key_id).collect::<Vec<_>>(),
+        vec![2]
+    );
+    assert!(keyset::undecryptable_prefixes(&new, &old)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_undecryptable_prefixes_tink_prefix() {
+    let old = keyset_info(
+        0x01020304,
+        vec![key_info(
+            0x01020304,
+            KeyStatusType::Enabled,
+            OutputPrefixType::Tink,
+        )],
+    );
+    let new = keyset_info(0, vec![]);
+    let prefixes = keyset::undecryptable_prefixes(&old, &new).unwrap();
+    assert_eq!(prefixes.len(), 1);
+    assert_eq!(prefixes[0].prefix, vec![1, 1, 2, 3, 4]);
+}
@@ -14,12 +14,18 @@
 //
 ////////////////////////////////////////////////////////////////////////////////
 
-use std::sync::Arc;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 use tink_core::{
-    keyset::{insecure, Handle},
+    keyset::{
+        insecure, FixedClock, Handle, KeyUsage, KeyValidity, MemUnwrapStore, UnwrapCache,
+        UnwrapCacheMode, UnwrapStore, UsageLimit, UsageStats,
+    },
     TinkError,
 };
-use tink_proto::{key_data::KeyMaterialType, KeyData};
+use tink_proto::{key_data::KeyMaterialType, prost::Message, KeyData};
 
 #[test]
 fn test_new_handle() {
@@ -139,6 +145,43 @@ fn test_read_with_mismatched_associated_data() {
     tink_tests::expect_err(result, "decryption failed");
 }
 
+#[test]
+fn test_read_encrypted_with_kms() {
+    tink_aead::init();
+    tink_core::registry::register_kms_client(
+        tink_tests::fakekms::FakeClient::new("fake-kms://").unwrap(),
+    );
+    let kek_uri = tink_tests::fakekms::new_key_uri().unwrap();
+    let kek = tink_core::registry::get_kms_client(&kek_uri)
+        .unwrap()
+        .get_aead(&kek_uri)
+        .unwrap();
+
+    let h = Handle::new(&tink_aead::aes128_gcm_key_template()).unwrap();
+    let mem_keyset = &mut tink_core::keyset::MemReaderWriter::default();
+    h.write(mem_keyset, kek).unwrap();
+    let h2 = Handle::read_encrypted_with_kms(mem_keyset, &kek_uri).unwrap();
+    assert_eq!(
+        insecure::keyset_material(&h),
+        insecure::keyset_material(&h2)
+    );
+
+    // A different key encryption key cannot decrypt the keyset.
+    let other_uri = tink_tests::fakekms::new_key_uri().unwrap();
+    let result = Handle::read_encrypted_with_kms(mem_keyset, &other_uri);
+    tink_tests::expect_err(result, "cannot read keyset encrypted with fake-kms://");
+}
+
+#[test]
+fn test_read_encrypted_with_kms_unbound_uri() {
+    let mem_keyset = &mut tink_core::keyset::MemReaderWriter::default();
+    let result = Handle::read_encrypted_with_kms(mem_keyset, "unbound-kms://some-key");
+    tink_tests::expect_err(
+        result,
+        "no KMS client registered for unbound-kms://some-key",
+    );
+}
+
 #[test]
 fn test_read_with_no_secrets() {
     // Create a keyset containing public key material
@@ -426,3 +469,807 @@ fn test_insecure_read_empty() {
     let result = insecure::read(&mut mem_keyset);
     tink_tests::expect_err(result, "insecure: invalid keyset");
 }
+
+fn new_cache_test_handle() -> Handle {
+    let key_data = tink_tests::new_key_data("some type url", &[0], KeyMaterialType::Symmetric);
+    let key = tink_tests::new_key(
+        &key_data,
+        tink_proto::KeyStatusType::Enabled,
+        1,
+        tink_proto::OutputPrefixType::Tink,
+    );
+    insecure::new_handle(tink_tests::new_keyset(1, vec![key])).unwrap()
+}
+
+#[test]
+fn test_read_with_unwrap_cache_fallback() {
+    let main_key = Box::new(tink_aead::subtle::AesGcm::new(&[b'A'; 32]).unwrap());
+    let unavailable_key = Box::new(tink_aead::subtle::AesGcm::new(&[b'B'; 32]).unwrap());
+    let h = new_cache_test_handle();
+    let mem_keyset = &mut tink_core::keyset::MemReaderWriter::default();
+    h.write_with_associated_data(mem_keyset, main_key.clone(), &[0x01])
+        .unwrap();
+
+    let cache = UnwrapCache::new(UnwrapCacheMode::Fallback, Duration::from_secs(60));
+    // Nothing cached yet, so a failing master key means failure.
+    let result =
+        Handle::read_with_unwrap_cache(mem_keyset, unavailable_key.clone(), &[0x01], &cache);
+    tink_tests::expect_err(result, "decryption failed");
+
+    let h2 = Handle::read_with_unwrap_cache(mem_keyset, main_key, &[0x01], &cache).unwrap();
+    assert_eq!(
+        insecure::keyset_material(&h),
+        insecure::keyset_material(&h2)
+    );
+
+    // The cached result is now available when the master key fails.
+    let h3 = Handle::read_with_unwrap_cache(mem_keyset, unavailable_key.clone(), &[0x01], &cache)
+        .unwrap();
+    assert_eq!(
+        insecure::keyset_material(&h),
+        insecure::keyset_material(&h3)
+    );
+
+    // But only for the same associated data.
+    let result =
+        Handle::read_with_unwrap_cache(mem_keyset, unavailable_key.clone(), &[0x02], &cache);
+    tink_tests::expect_err(result, "decryption failed");
+
+    cache.clear();
+    let result = Handle::read_with_unwrap_cache(mem_keyset, unavailable_key, &[0x01], &cache);
+    tink_tests::expect_err(result, "decryption failed");
+}
+
+#[test]
+fn test_read_with_unwrap_cache_expired() {
+    let main_key = Box::new(tink_aead::subtle::AesGcm::new(&[b'A'; 32]).unwrap());
+    let unavailable_key = Box::new(tink_aead::subtle::AesGcm::new(&[b'B'; 32]).unwrap());
+    let h = new_cache_test_handle();
+    let mem_keyset = &mut tink_core::keyset::MemReaderWriter::default();
+    h.write(mem_keyset, main_key.clone()).unwrap();
+
+    let cache = UnwrapCache::new(UnwrapCacheMode::Eager, Duration::from_secs(0));
+    Handle::read_with_unwrap_cache(mem_keyset, main_key, &[], &cache).unwrap();
+    let result = Handle::read_with_unwrap_cache(mem_keyset, unavailable_key, &[], &cache);
+    tink_tests::expect_err(result, "decryption failed");
+}
+
+#[derive(Clone, Default)]
+struct SharedUnwrapStore(Arc<Mutex<MemUnwrapStore>>);
+
+impl UnwrapStore for SharedUnwrapStore {
+    fn load(&self, id: &[u8]) -> Result<Option<Vec<u8>>, TinkError> {
+        self.0.lock().unwrap().load(id)
+    }
+    fn store(&mut self, id: &[u8], data: &[u8]) -> Result<(), TinkError> {
+        self.0.lock().unwrap().store(id, data)
+    }
+}
+
+/// Store whose operations always fail.
+struct FailingUnwrapStore;
+
+impl UnwrapStore for FailingUnwrapStore {
+    fn load(&self, _id: &[u8]) -> Result<Option<Vec<u8>>, TinkError> {
+        Err("store unavailable".into())
+    }
+    fn store(&mut self, _id: &[u8], _data: &[u8]) -> Result<(), TinkError> {
+        Err("store unavailable".into())
+    }
+}
+
+#[test]
+fn test_read_with_unwrap_cache_persisted() {
+    let main_key = Box::new(tink_aead::subtle::AesGcm::new(&[b'A'; 32]).unwrap());
+    let unavailable_key = Box::new(tink_aead::subtle::AesGcm::new(&[b'B'; 32]).unwrap());
+    let local_key = Box::new(tink_aead::subtle::AesGcm::new(&[b'L'; 32]).unwrap());
+    let h = new_cache_test_handle();
+    let mem_keyset = &mut tink_core::keyset::MemReaderWriter::default();
+    h.write(mem_keyset, main_key.clone()).unwrap();
+
+    let store = SharedUnwrapStore::default();
+    let cache = UnwrapCache::new_with_store(
+        UnwrapCacheMode::Fallback,
+        Duration::from_secs(60),
+        Box::new(store.clone()),
+        local_key.clone(),
+    );
+    Handle::read_with_unwrap_cache(mem_keyset, main_key, &[], &cache).unwrap();
+    assert_eq!(1, store.0.lock().unwrap().len());
+    for (_, data) in store.0.lock().unwrap().iter() {
+        // Persisted entries do not hold the keyset in the clear.
+        let serialized = insecure::keyset_material(&h).encode_to_vec();
+        assert!(!data.windows(serialized.len()).any(|w| w == &serialized[..]));
+    }
+
+    // A fresh cache (as after a process restart) can recover the keyset from the store.
+    let cache = UnwrapCache::new_with_store(
+        UnwrapCacheMode::Fallback,
+        Duration::from_secs(60),
+        Box::new(store.clone()),
+        local_key,
+    );
+    let h2 =
+        Handle::read_with_unwrap_cache(mem_keyset, unavailable_key.clone(), &[], &cache).unwrap();
+    assert_eq!(
+        insecure::keyset_material(&h),
+        insecure::keyset_material(&h2)
+    );
+
+    // A different local key cannot recover the keyset.
+    let cache = UnwrapCache::new_with_store(
+        UnwrapCacheMode::Fallback,
+        Duration::from_secs(60),
+        Box::new(store),
+        Box::new(tink_aead::subtle::AesGcm::new(&[b'M'; 32]).unwrap()),
+    );
+    let result = Handle::read_with_unwrap_cache(mem_keyset, unavailable_key, &[], &cache);
+    tink_tests::expect_err(result, "decryption failed");
+}
+
+#[test]
+fn test_read_with_unwrap_cache_store_failure() {
+    let main_key = Box::new(tink_aead::subtle::AesGcm::new(&[b'A'; 32]).unwrap());
+    let unavailable_key = Box::new(tink_aead::subtle::AesGcm::new(&[b'B'; 32]).unwrap());
+    let local_key = Box::new(tink_aead::subtle::AesGcm::new(&[b'L'; 32]).unwrap());
+    let h = new_cache_test_handle();
+    let mem_keyset = &mut tink_core::keyset::MemReaderWriter::default();
+    h.write(mem_keyset, main_key.clone()).unwrap();
+
+    for mode in &[UnwrapCacheMode::Fallback, UnwrapCacheMode::Eager] {
+        let cache = UnwrapCache::new_with_store(
+            *mode,
+            Duration::from_secs(60),
+            Box::new(FailingUnwrapStore),
+            local_key.clone(),
+        );
+        // A failing store does not prevent decryption with the master key.
+        let h2 = Handle::read_with_unwrap_cache(mem_keyset, main_key.clone(), &[], &cache).unwrap();
+        assert_eq!(
+            insecure::keyset_material(&h),
+            insecure::keyset_material(&h2)
+        );
+        // The in-memory entry is still available.
+        Handle::read_with_unwrap_cache(mem_keyset, unavailable_key.clone(), &[], &cache).unwrap();
+
+        // Without an in-memory entry, the master key error is reported.
+        cache.clear();
+        let result =
+            Handle::read_with_unwrap_cache(mem_keyset, unavailable_key.clone(), &[], &cache);
+        tink_tests::expect_err(result, "decryption failed");
+    }
+}
+
+#[test]
+fn test_key_validity() {
+    tink_aead::init();
+    let start = std::time::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+    let clock = Arc::new(FixedClock::new(start));
+    let mut kh = Handle::new(&tink_aead::aes128_gcm_key_template()).unwrap();
+    let key_id = kh.keyset_info().primary_key_id;
+    let validity = KeyValidity {
+        not_before: Some(start + Duration::from_secs(10)),
+        not_after: Some(start + Duration::from_secs(20)),
+    };
+    kh.set_key_validity(key_id, validity).unwrap();
+    kh.set_clock(clock.clone());
+    assert_eq!(Some(validity), kh.key_validity(key_id));
+    assert_eq!(None, kh.key_validity(key_id + 1));
+    tink_tests::expect_err(kh.set_key_validity(key_id + 1, validity), "not found");
+
+    let a = tink_aead::new(&kh).unwrap();
+    // Not yet valid.
+    tink_tests::expect_err(a.encrypt(b"plaintext", b"aad"), "validity period");
+
+    clock.advance(Duration::from_secs(15));
+    let ct = a.encrypt(b"plaintext", b"aad").unwrap();
+
+    // Expired keys can still decrypt.
+    clock.advance(Duration::from_secs(10));
+    tink_tests::expect_err(a.encrypt(b"plaintext", b"aad"), "validity period");
+    assert_eq!(a.decrypt(&ct, b"aad").unwrap(), b"plaintext");
+}
+
+#[test]
+fn test_key_validity_public() {
+    tink_signature::init();
+    let now = std::time::SystemTime::now();
+    let mut kh = Handle::new(&tink_signature::ed25519_key_template()).unwrap();
+    let key_id = kh.keyset_info().primary_key_id;
+    let validity = KeyValidity {
+        not_before: None,
+        not_after: Some(now - Duration::from_secs(1)),
+    };
+    kh.set_key_validity(key_id, validity).unwrap();
+
+    let signer = tink_signature::new_signer(&kh).unwrap();
+    tink_tests::expect_err(signer.sign(b"data"), "validity period");
+
+    // Annotations are carried over to the public keyset handle.
+    let pub_kh = kh.public().unwrap();
+    assert_eq!(Some(validity), pub_kh.key_validity(key_id));
+}
+
+#[test]
+fn test_restrict_aead() {
+    tink_aead::init();
+    let kh = Handle::new(&tink_aead::aes128_gcm_key_template()).unwrap();
+    assert_eq!(KeyUsage::Unrestricted, kh.usage());
+    let ct = tink_aead::new(&kh).unwrap().encrypt(b"pt", b"aad").unwrap();
+
+    let encrypt_only = kh.restrict(KeyUsage::EncryptOnly).unwrap();
+    assert_eq!(KeyUsage::EncryptOnly, encrypt_only.usage());
+    let a = tink_aead::new(&encrypt_only).unwrap();
+    assert!(a.encrypt(b"pt", b"aad").is_ok());
+    tink_tests::expect_err(a.decrypt(&ct, b"aad"), "decryption not permitted");
+
+    let decrypt_only = kh.restrict(KeyUsage::DecryptOnly).unwrap();
+    let a = tink_aead::new(&decrypt_only).unwrap();
+    tink_tests::expect_err(a.encrypt(b"pt", b"aad"), "encryption not permitted");
+    assert_eq!(a.decrypt(&ct, b"aad").unwrap(), b"pt");
+
+    let verify_only = kh.restrict(KeyUsage::VerifyOnly).unwrap();
+    tink_tests::expect_err(tink_aead::new(&verify_only), "does not permit AEAD");
+
+    // Restrictions cannot be changed or removed.
+    tink_tests::expect_err(
+        encrypt_only.restrict(KeyUsage::Unrestricted),
+        "cannot change usage restriction",
+    );
+    tink_tests::expect_err(
+        encrypt_only.restrict(KeyUsage::DecryptOnly),
+        "cannot change usage restriction",
+    );
+    assert!(encrypt_only.restrict(KeyUsage::EncryptOnly).is_ok());
+    let manager = tink_core::keyset::Manager::new_from_handle(encrypt_only);
+    assert_eq!(KeyUsage::EncryptOnly, manager.handle().unwrap().usage());
+
+    // Restricted handles cannot be exported.
+    let main_key = Box::new(tink_aead::subtle::AesGcm::new(&[b'A'; 32]).unwrap());
+    let mut mem_keyset = tink_core::keyset::MemReaderWriter::default();
+    tink_tests::expect_err(
+        decrypt_only.write(&mut mem_keyset, main_key),
+        "cannot export usage-restricted keyset",
+    );
+    tink_tests::expect_err(
+        insecure::write(&decrypt_only, &mut mem_keyset),
+        "cannot export usage-restricted keyset",
+    );
+    assert!(insecure::keyset_material(&decrypt_only)
+        .key
+        .iter()
+        .all(|k| k.key_data.is_none()));
+
+    // The restriction also applies to primitives taken from the primitive set directly.
+    let ps = decrypt_only.primitives().unwrap();
+    let p = ps.primary.unwrap().primitive.into_aead().unwrap();
+    tink_tests::expect_err(p.encrypt(b"pt", b"aad"), "encryption not permitted");
+    assert_eq!(p.decrypt(&ct, b"aad").unwrap(), b"pt");
+    tink_tests::expect_err(verify_only.primitives(), "does not permit AEAD");
+}
+
+#[test]
+fn test_restrict_mac() {
+    tink_mac::init();
+    let kh = Handle::new(&tink_mac::hmac_sha256_tag256_key_template()).unwrap();
+    let tag = tink_mac::new(&kh).unwrap().compute_mac(b"data").unwrap();
+
+    let verify_only = kh.restrict(KeyUsage::VerifyOnly).unwrap();
+    let m = tink_mac::new(&verify_only).unwrap();
+    assert!(m.verify_mac(&tag, b"data").is_ok());
+    tink_tests::expect_err(m.compute_mac(b"data"), "MAC computation not permitted");
+    let ps = verify_only.primitives().unwrap();
+    let p = ps.primary.unwrap().primitive.into_mac().unwrap();
+    assert!(p.verify_mac(&tag, b"data").is_ok());
+    tink_tests::expect_err(p.compute_mac(b"data"), "MAC computation not permitted");
+
+    let encrypt_only = kh.restrict(KeyUsage::EncryptOnly).unwrap();
+    tink_tests::expect_err(tink_mac::new(&encrypt_only), "does not permit MAC");
+}
+
+#[test]
+fn test_restrict_hybrid() {
+    tink_hybrid::init();
+    let kh = Handle::new(&tink_hybrid::ecies_hkdf_aes128_gcm_key_template()).unwrap();
+    let pub_kh = kh.public().unwrap();
+
+    let decrypt_only = kh.restrict(KeyUsage::DecryptOnly).unwrap();
+    assert!(tink_hybrid::new_decrypt(&decrypt_only).is_ok());
+    let encrypt_only = kh.restrict(KeyUsage::EncryptOnly).unwrap();
+    tink_tests::expect_err(
+        tink_hybrid::new_decrypt(&encrypt_only),
+        "decryption not permitted",
+    );
+    let pub_decrypt_only = pub_kh.restrict(KeyUsage::DecryptOnly).unwrap();
+    tink_tests::expect_err(
+        tink_hybrid::new_encrypt(&pub_decrypt_only),
+        "encryption not permitted",
+    );
+}
+
+#[test]
+fn test_restrict_signature() {
+    tink_signature::init();
+    let kh = Handle::new(&tink_signature::ed25519_key_template()).unwrap();
+    let sig = tink_signature::new_signer(&kh)
+        .unwrap()
+        .sign(b"data")
+        .unwrap();
+
+    let verify_only = kh.restrict(KeyUsage::VerifyOnly).unwrap();
+    tink_tests::expect_err(
+        tink_signature::new_signer(&verify_only),
+        "signing not permitted",
+    );
+    // The restriction carries over to the public keyset.
+    let pub_kh = verify_only.public().unwrap();
+    assert_eq!(KeyUsage::VerifyOnly, pub_kh.usage());
+    let v = tink_signature::new_verifier(&pub_kh).unwrap();
+    assert!(v.verify(&sig, b"data").is_ok());
+
+    let pub_encrypt_only = kh
+        .public()
+        .unwrap()
+        .restrict(KeyUsage::EncryptOnly)
+        .unwrap();
+    tink_tests::expect_err(
+        tink_signature::new_verifier(&pub_encrypt_only),
+        "verification not permitted",
+    );
+}
+
+fn assert_send_sync_clone<T: Send + Sync + Clone>() {}
+
+#[test]
+fn test_handle_is_shareable() {
+    assert_send_sync_clone::<Handle>();
+    assert_send_sync_clone::<tink_core::keyset::SharedHandle>();
+
+    tink_mac::init();
+    let kh = Handle::new(&tink_mac::hmac_sha256_tag128_key_template()).unwrap();
+    let cloned = kh.clone();
+    assert_eq!(
+        insecure::keyset_material(&kh),
+        insecure::keyset_material(&cloned)
+    );
+
+    let shared = kh.into_shared();
+    let tag = tink_mac::new(&shared)
+        .unwrap()
+        .compute_mac(b"data")
+        .unwrap();
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let shared = shared.clone();
+            let tag = tag.clone();
+            std::thread::spawn(move || {
+                let m = tink_mac::new(&shared).unwrap();
+                m.verify_mac(&tag, b"data").unwrap();
+            })
+        })
+        .collect();
+    for h in handles {
+        h.join().unwrap();
+    }
+}
+
+#[test]
+fn test_handle_caches_primitives() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tink_core::registry::KeyManager;
+
+    const TYPE_URL: &str = "type.googleapis.com/tink_tests.CountingAesGcmKey";
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    static REGISTER: std::sync::Once = std::sync::Once::new();
+
+    /// Key manager that counts the primitives it creates.
+    struct CountingKeyManager {
+        inner: Arc<dyn KeyManager>,
+    }
+    impl KeyManager for CountingKeyManager {
+        fn primitive(&self, serialized_key: &[u8]) -> Result<tink_core::Primitive, TinkError> {
+            COUNT.fetch_add(1, Ordering::SeqCst);
+            self.inner.primitive(serialized_key)
+        }
+        fn new_key(&self, serialized_key_format: &[u8]) -> Result<Vec<u8>, TinkError> {
+            self.inner.new_key(serialized_key_format)
+        }
+        fn type_url(&self) -> &'static str {
+            TYPE_URL
+        }
+        fn key_material_type(&self) -> KeyMaterialType {
+            self.inner.key_material_type()
+        }
+    }
+
+    tink_aead::init();
+    REGISTER.call_once(|| {
+        tink_core::registry::register_key_manager(Arc::new(CountingKeyManager {
+            inner: tink_core::registry::get_key_manager(tink_tests::AES_GCM_TYPE_URL).unwrap(),
+        }))
+        .unwrap();
+    });
+    let mut ks =
+        insecure::keyset_material(&Handle::new(&tink_aead::aes128_gcm_key_template()).unwrap());
+    ks.key[0].key_data.as_mut().unwrap().type_url = TYPE_URL.to_string();
+    let mut kh = insecure::new_handle(ks).unwrap();
+
+    // Repeated use of a handle (or a clone of it) only builds its primitives once per thread.  Key
+    // managers registered concurrently by other tests may force the odd rebuild.
+    let ct = tink_aead::new(&kh)
+        .unwrap()
+        .encrypt(b"plaintext", b"aad")
+        .unwrap();
+    assert!(COUNT.load(Ordering::SeqCst) >= 1);
+    COUNT.store(0, Ordering::SeqCst);
+    let shared = kh.clone().into_shared();
+    for _ in 0..5 {
+        let a = tink_aead::new(&shared).unwrap();
+        assert_eq!(a.decrypt(&ct, b"aad").unwrap(), b"plaintext");
+    }
+    assert!(COUNT.load(Ordering::SeqCst) < 5);
+
+    // Another thread builds its own primitives.
+    let before = COUNT.load(Ordering::SeqCst);
+    std::thread::spawn(move || {
+        let a = tink_aead::new(&shared).unwrap();
+        assert_eq!(a.decrypt(&ct, b"aad").unwrap(), b"plaintext");
+    })
+    .join()
+    .unwrap();
+    assert!(COUNT.load(Ordering::SeqCst) > before);
+
+    // Changing the handle discards its cached primitives.
+    let before = COUNT.load(Ordering::SeqCst);
+    kh.set_clock(Arc::new(FixedClock::new(std::time::SystemTime::now())));
+    tink_aead::new(&kh).unwrap();
+    assert!(COUNT.load(Ordering::SeqCst) > before);
+}
+
+#[test]
+fn test_handle_clone_is_independent() {
+    tink_aead::init();
+    let mut kh = Handle::new(&tink_aead::aes128_gcm_key_template()).unwrap();
+    let cloned = kh.clone();
+    let key_id = kh.keyset_info().primary_key_id;
+    let now = std::time::SystemTime::now();
+    kh.set_key_validity(
+        key_id,
+        KeyValidity {
+            not_before: None,
+            not_after: Some(now),
+        },
+    )
+    .unwrap();
+    assert!(kh.key_validity(key_id).is_some());
+    assert!(cloned.key_validity(key_id).is_none());
+}
+
+#[test]
+fn test_key_usage_stats() {
+    tink_aead::init();
+    let mut kh = Handle::new(&tink_aead::aes128_gcm_key_template()).unwrap();
+    let key_id = kh.keyset_info().primary_key_id;
+    assert_eq!(None, kh.key_usage_stats(key_id));
+
+    kh.enable_usage_counters();
+    assert_eq!(Some(UsageStats::default()), kh.key_usage_stats(key_id));
+    let a = tink_aead::new(&kh).unwrap();
+    let ct = a.encrypt(b"plaintext", b"aad").unwrap();
+    a.encrypt(b"more", b"").unwrap();
+    // Decryption is not counted.
+    a.decrypt(&ct, b"aad").unwrap();
+
+    // Usage is counted across primitives and clones of the handle.
+    let cloned = kh.clone();
+    tink_aead::new(&cloned).unwrap().encrypt(b"x", b"").unwrap();
+    let want = UsageStats {
+        operations: 3,
+        bytes: 14,
+    };
+    assert_eq!(Some(want), kh.key_usage_stats(key_id));
+    assert_eq!(Some(want), cloned.key_usage_stats(key_id));
+}
+
+#[test]
+fn test_key_usage_limit() {
+    tink_mac::init();
+    let mut kh = Handle::new(&tink_mac::hmac_sha256_tag256_key_template()).unwrap();
+    let key_id = kh.keyset_info().primary_key_id;
+    let limit = UsageLimit {
+        max_operations: Some(2),
+        max_bytes: None,
+    };
+    tink_tests::expect_err(kh.set_key_usage_limit(key_id + 1, limit), "not found");
+    kh.set_key_usage_limit(key_id, limit).unwrap();
+    assert_eq!(Some(limit), kh.key_usage_limit(key_id));
+
+    let m = tink_mac::new(&kh).unwrap();
+    let tag = m.compute_mac(b"data").unwrap();
+    m.compute_mac(b"data").unwrap();
+    tink_tests::expect_err(m.compute_mac(b"data"), "usage limit");
+    // Verification is unaffected by the limit.
+    assert!(m.verify_mac(&tag, b"data").is_ok());
+
+    // A fresh primitive from the same handle shares the counters.
+    let m2 = tink_mac::new(&kh).unwrap();
+    tink_tests::expect_err(m2.compute_mac(b"data"), "usage limit");
+    assert_eq!(2, kh.key_usage_stats(key_id).unwrap().operations);
+}
+
+#[test]
+fn test_key_usage_limit_bytes() {
+    tink_aead::init();
+    let mut kh = Handle::new(&tink_aead::aes128_gcm_key_template()).unwrap();
+    let key_id = kh.keyset_info().primary_key_id;
+    kh.set_key_usage_limit(
+        key_id,
+        UsageLimit {
+            max_operations: None,
+            max_bytes: Some(10),
+        },
+    )
+    .unwrap();
+    let a = tink_aead::new(&kh).unwrap();
+    a.encrypt(b"12345678", b"").unwrap();
+    tink_tests::expect_err(a.encrypt(b"123", b""), "usage limit");
+    // A refused operation is not counted.
+    a.encrypt(b"12", b"").unwrap();
+    assert_eq!(
+        Some(UsageStats {
+            operations: 2,
+            bytes: 10
+        }),
+        kh.key_usage_stats(key_id)
+    );
+}
+
+#[test]
+fn test_key_usage_limit_aes_gcm() {
+    let limit = UsageLimit::aes_gcm();
+    assert_eq!(Some(1 << 32), limit.max_operations);
+    assert_eq!(None, limit.max_bytes);
+}
+
+#[test]
+fn test_self_test() {
+    tink_aead::init();
+    tink_daead::init();
+    tink_hybrid::init();
+    tink_mac::init();
+    tink_prf::init();
+    tink_signature::init();
+    tink_streaming_aead::init();
+    let templates = vec![
+        tink_aead::aes128_gcm_key_template(),
+        tink_daead::aes_siv_key_template(),
+        tink_hybrid::ecies_hkdf_aes128_gcm_key_template(),
+        tink_mac::hmac_sha256_tag256_key_template(),
+        tink_prf::hmac_sha256_prf_key_template(),
+        tink_signature::ecdsa_p256_key_template(),
+        tink_signature::ed25519_key_template(),
+        tink_streaming_aead::aes128_gcm_hkdf_4kb_key_template(),
+    ];
+    for kt in templates {
+        let kh = Handle::new(&kt).unwrap();
+        let results = kh.self_test();
+        assert_eq!(1, results.len());
+        assert_eq!(kt.type_url, results[0].type_url);
+        assert!(results[0].result.is_ok(), "{:?}", results[0]);
+        assert!(kh.check_self_test().is_ok());
+
+        // Public keysets are only checked for construction.
+        if let Ok(pub_kh) = kh.public() {
+            assert!(pub_kh.check_self_test().is_ok());
+        }
+    }
+}
+
+#[test]
+fn test_self_test_failures() {
+    tink_aead::init();
+    let kh = Handle::new(&tink_aead::aes128_gcm_key_template()).unwrap();
+    let mut ks = insecure::keyset_material(&kh);
+    let good_key_id = ks.primary_key_id;
+
+    // Corrupted key material for a supported key type.
+    let mut corrupt = ks.key[0].clone();
+    corrupt.key_id = good_key_id + 1;
+    corrupt.key_data.as_mut().unwrap().value = vec![0x0a, 0x03, 1, 2, 3];
+    // Unsupported key type.
+    let mut unknown = ks.key[0].clone();
+    unknown.key_id = good_key_id + 2;
+    unknown.key_data.as_mut().unwrap().type_url = "some unknown type_url".to_string();
+    // Disabled keys are not checked.
+    let mut disabled = unknown.clone();
+    disabled.key_id = good_key_id + 3;
+    disabled.status = tink_proto::KeyStatusType::Disabled as i32;
+    ks.key.extend(vec![corrupt, unknown, disabled]);
+    let kh = insecure::new_handle(ks).unwrap();
+
+    let results = kh.self_test();
+    assert_eq!(3, results.len());
+    assert_eq!(good_key_id, results[0].key_id);
+    assert!(results[0].result.is_ok());
+    assert_eq!(good_key_id + 1, results[1].key_id);
+    assert!(results[1].result.is_err());
+    assert_eq!(good_key_id + 2, results[2].key_id);
+    tink_tests::expect_err(results[2].result.as_ref(), "cannot create primitive");
+    tink_tests::expect_err(
+        kh.check_self_test(),
+        &format!("self-test failed for key {}", good_key_id + 1),
+    );
+}
+
+#[test]
+fn test_key_version_policy() {
+    use tink_core::keyset::KeyVersionPolicy;
+    tink_aead::init();
+    let key = tink_tests::new_aes_gcm_key(tink_tests::AES_GCM_KEY_VERSION + 1, 16);
+    let key_data = tink_tests::new_key_data(
+        tink_tests::AES_GCM_TYPE_URL,
+        &tink_tests::proto_encode(&key),
+        KeyMaterialType::Symmetric,
+    );
+    let key = tink_tests::new_key(
+        &key_data,
+        tink_proto::KeyStatusType::Enabled,
+        1,
+        tink_proto::OutputPrefixType::Tink,
+    );
+    let mut h = insecure::new_handle(tink_tests::new_keyset(1, vec![key])).unwrap();
+
+    // Keys with a newer version are rejected by default.
+    assert_eq!(h.key_version_policy(), KeyVersionPolicy::Reject);
+    tink_tests::expect_err(tink_aead::new(&h), "only keys with version in range");
+
+    h.set_key_version_policy(KeyVersionPolicy::WarnAndAccept);
+    let a = tink_aead::new(&h).unwrap();
+    let ct = a.encrypt(b"plaintext", b"aad").unwrap();
+    assert_eq!(a.decrypt(&ct, b"aad").unwrap(), b"plaintext");
+
+    // The policy is inherited by derived handles, and does not leak to other code.
+    let restricted = h.restrict(KeyUsage::DecryptOnly).unwrap();
+    assert_eq!(
+        restricted.key_version_policy(),
+        KeyVersionPolicy::WarnAndAccept
+    );
+    tink_tests::expect_err(
+        tink_core::registry::primitive_from_key_data(&key_data),
+        "only keys with version in range",
+    );
+    assert!(
+        tink_core::keyset::with_key_version_policy(KeyVersionPolicy::WarnAndAccept, || {
+            tink_core::registry::primitive_from_key_data(&key_data)
+        })
+        .is_ok()
+    );
+}
+
+/// Return `count` AES-GCM keys with IDs `1..=count`.
+fn aes_gcm_keys(count: u32) -> Vec<tink_proto::keyset::Key> {
+    (1..=count)
+        .map(|key_id| {
+            let key = tink_tests::new_aes_gcm_key(tink_tests::AES_GCM_KEY_VERSION, 16);
+            let key_data = tink_tests::new_key_data(
+                tink_tests::AES_GCM_TYPE_URL,
+                &tink_tests::proto_encode(&key),
+                KeyMaterialType::Symmetric,
+            );
+            tink_tests::new_key(
+                &key_data,
+                tink_proto::KeyStatusType::Enabled,
+                key_id,
+                tink_proto::OutputPrefixType::Tink,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_key_parsing() {
+    use tink_core::keyset::KeyParsing;
+    tink_aead::init();
+    let mut keys = aes_gcm_keys(100);
+    let old_ct =
+        tink_aead::new(&insecure::new_handle(tink_tests::new_keyset(50, keys.clone())).unwrap())
+            .unwrap()
+            .encrypt(b"plaintext", b"aad")
+            .unwrap();
+    let corrupt_key = tink_tests::new_key(
+        &tink_tests::new_key_data(
+            tink_tests::AES_GCM_TYPE_URL,
+            b"not a key",
+            KeyMaterialType::Symmetric,
+        ),
+        tink_proto::KeyStatusType::Enabled,
+        101,
+        tink_proto::OutputPrefixType::Tink,
+    );
+    let corrupt_prefix = tink_core::cryptofmt::output_prefix(&corrupt_key).unwrap();
+    keys.push(corrupt_key);
+    let mut h = insecure::new_handle(tink_tests::new_keyset(100, keys)).unwrap();
+
+    // By default every key is parsed up front, so the corrupt key is reported immediately.
+    assert_eq!(h.key_parsing(), KeyParsing::Eager);
+    tink_tests::expect_err(tink_aead::new(&h), "cannot get primitive from key");
+
+    // With lazy parsing, the corrupt key only matters for ciphertexts that use it.
+    h.set_key_parsing(KeyParsing::Lazy);
+    let a = tink_aead::new(&h).unwrap();
+    let ct = a.encrypt(b"plaintext", b"aad").unwrap();
+    assert_eq!(a.decrypt(&ct, b"aad").unwrap(), b"plaintext");
+    assert_eq!(a.decrypt(&old_ct, b"aad").unwrap(), b"plaintext");
+    let mut bad_ct = corrupt_prefix;
+    bad_ct.extend_from_slice(&ct[tink_core::cryptofmt::NON_RAW_PREFIX_SIZE..]);
+    tink_tests::expect_err(a.decrypt(&bad_ct, b"aad"), "decryption failed");
+
+    let restricted = h.restrict(KeyUsage::DecryptOnly).unwrap();
+    assert_eq!(restricted.key_parsing(), KeyParsing::Lazy);
+}
+
+#[test]
+fn test_lazy_key_parsing_defers_primitive_creation() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tink_core::{keyset::KeyParsing, registry::KeyManager};
+
+    /// Key manager that counts the primitives it creates.
+    struct CountingKeyManager {
+        inner: Arc<dyn KeyManager>,
+        count: Arc<AtomicUsize>,
+    }
+    impl KeyManager for CountingKeyManager {
+        fn primitive(&self, serialized_key: &[u8]) -> Result<tink_core::Primitive, TinkError> {
+            self.count.fetch_add(1, Ordering::SeqCst);
+            self.inner.primitive(serialized_key)
+        }
+        fn new_key(&self, serialized_key_format: &[u8]) -> Result<Vec<u8>, TinkError> {
+            self.inner.new_key(serialized_key_format)
+        }
+        fn type_url(&self) -> &'static str {
+            self.inner.type_url()
+        }
+        fn key_material_type(&self) -> KeyMaterialType {
+            self.inner.key_material_type()
+        }
+    }
+
+    tink_aead::init();
+    let count = Arc::new(AtomicUsize::new(0));
+    let km: Arc<dyn KeyManager> = Arc::new(CountingKeyManager {
+        inner: tink_core::registry::get_key_manager(tink_tests::AES_GCM_TYPE_URL).unwrap(),
+        count: count.clone(),
+    });
+    let keys = aes_gcm_keys(100);
+    let old_ct =
+        tink_aead::new(&insecure::new_handle(tink_tests::new_keyset(50, keys.clone())).unwrap())
+            .unwrap()
+            .encrypt(b"plaintext", b"aad")
+            .unwrap();
+    let old_prefix = tink_core::cryptofmt::output_prefix(&keys[49]).unwrap();
+    let mut h = insecure::new_handle(tink_tests::new_keyset(100, keys)).unwrap();
+
+    h.primitives_with_key_manager(Some(km.clone())).unwrap();
+    assert_eq!(count.swap(0, Ordering::SeqCst), 100);
+
+    // Only the first key and the primary key are parsed up front.
+    h.set_key_parsing(KeyParsing::Lazy);
+    let ps = h.primitives_with_key_manager(Some(km)).unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+
+    // Other keys are parsed on first use, once, with the result shared between clones.
+    let entry = ps.entries_for_prefix(&old_prefix).remove(0);
+    assert_eq!(entry.key_id, 50);
+    let cloned = entry.clone();
+    for e in &[entry, cloned] {
+        let a = match &e.primitive {
+            tink_core::Primitive::Aead(a) => a,
+            _ => panic!("not an AEAD primitive"),
+        };
+        let pt = a
+            .decrypt(&old_ct[tink_core::cryptofmt::NON_RAW_PREFIX_SIZE..], b"aad")
+            .unwrap();
+        assert_eq!(pt, b"plaintext");
+    }
+    assert_eq!(count.load(Ordering::SeqCst), 3);
+}
@@ -411,3 +411,98 @@ fn test_json_read_invalid_b64() {
     let result = r.read();
     tink_tests::expect_err(result, "base64");
 }
+
+/// Return a JSON keyset holding a single key with the given (encoded) value.
+fn json_keyset_with_value(value: &str) -> String {
+    format!(
+        r#"{{
+         "primaryKeyId":42,
+         "key":[
+            {{
+               "keyData":{{
+                  "typeUrl":"type.googleapis.com/google.crypto.tink.AesGcmKey",
+                  "keyMaterialType":"SYMMETRIC",
+                  "value": "{}"
+               }},
+               "outputPrefixType":"TINK",
+               "keyId":42,
+               "status":"ENABLED"
+            }}
+         ]
+      }}"#,
+        value
+    )
+}
+
+#[test]
+fn test_json_reader_tolerant() {
+    // Bytes whose base64 encoding includes characters that differ between the alphabets.
+    let value: Vec<u8> = (0..=255u8).rev().collect();
+    let url_safe = base64::encode_config(&value, base64::URL_SAFE_NO_PAD);
+    assert!(url_safe.contains('-') && url_safe.contains('_'));
+    let wrapped = url_safe
+        .as_bytes()
+        .chunks(64)
+        .map(|c| std::str::from_utf8(c).unwrap())
+        .collect::<Vec<_>>()
+        .join("\\n  ");
+
+    for encoded in &[
+        base64::encode(&value),
+        base64::encode_config(&value, base64::STANDARD_NO_PAD),
+        base64::encode_config(&value, base64::URL_SAFE),
+        url_safe.clone(),
+        wrapped,
+    ] {
+        let json_keyset = json_keyset_with_value(encoded);
+        let mut r = tink_core::keyset::JsonReader::new(json_keyset.as_bytes())
+            .with_parsing(tink_core::keyset::JsonParsing::Tolerant);
+        let ks = r.read().expect("cannot read keyset");
+        assert_eq!(ks.key[0].key_data.as_ref().unwrap().value, value);
+    }
+
+    // Strict parsing only accepts standard, padded base64.
+    let json_keyset = json_keyset_with_value(&url_safe);
+    let mut r = tink_core::keyset::JsonReader::new(json_keyset.as_bytes());
+    tink_tests::expect_err(r.read(), "base64");
+    let mut r = tink_core::keyset::JsonReader::new(json_keyset.as_bytes())
+        .with_parsing(tink_core::keyset::JsonParsing::Strict);
+    tink_tests::expect_err(r.read(), "base64");
+
+    // Tolerant parsing still rejects invalid data.
+    let json_keyset = json_keyset_with_value("non base64 data ^&*%");
+    let mut r = tink_core::keyset::JsonReader::new(json_keyset.as_bytes())
+        .with_parsing(tink_core::keyset::JsonParsing::Tolerant);
+    tink_tests::expect_err(r.read(), "base64");
+}
+
+#[test]
+fn test_json_reader_tolerant_encrypted() {
+    let encrypted: Vec<u8> = (0..=255u8).collect();
+    let json_keyset = format!(
+        r#"{{
+         "encryptedKeyset": "{}",
+         "keysetInfo": {{
+            "primaryKeyId": 42,
+            "keyInfo": [
+               {{
+                  "typeUrl": "type.googleapis.com/google.crypto.tink.AesGcmKey",
+                  "outputPrefixType": "TINK",
+                  "keyId": 42,
+                  "status": "ENABLED"
+               }}
+            ]
+         }}
+      }}"#,
+        base64::encode_config(&encrypted, base64::URL_SAFE_NO_PAD)
+    );
+
+    let mut r = tink_core::keyset::JsonReader::new(json_keyset.as_bytes())
+        .with_parsing(tink_core::keyset::JsonParsing::Tolerant);
+    let eks = r.read_encrypted().expect("cannot read encrypted keyset");
+    assert_eq!(eks.encrypted_keyset, encrypted);
+    assert_eq!(eks.keyset_info.unwrap().primary_key_id, 42);
+
+    let mut r = tink_core::keyset::JsonReader::new(json_keyset.as_bytes());
+    tink_tests::expect_err(r.read_encrypted(), "base64");
+}
@@ -0,0 +1,187 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_core::keyset::insecure;
+use tink_proto::{prost::Message, EllipticCurveType, HashType, OutputPrefixType};
+use tink_tests::proto_encode;
+
+/// Value that is not a valid member of any of the Tink proto enums.
+const INVALID_ENUM: i32 = 999;
+
+fn init() {
+    tink_aead::init();
+    tink_hybrid::init();
+    tink_mac::init();
+    tink_prf::init();
+    tink_signature::init();
+    tink_streaming_aead::init();
+}
+
+/// Check that the key manager for `type_url` rejects `key` with an error (rather than a panic).
+fn assert_primitive_err<T: Message>(type_url: &str, key: &T, desc: &str) {
+    let result = tink_core::registry::primitive(type_url, &proto_encode(key));
+    assert!(result.is_err(), "malformed {} unexpectedly accepted", desc);
+}
+
+#[test]
+fn test_malformed_keyset_fields() {
+    init();
+    type Mutation = fn(&mut tink_proto::keyset::Key);
+    let mutations: Vec<(&str, Mutation)> = vec![
+        ("output prefix type", |k| {
+            k.output_prefix_type = INVALID_ENUM
+        }),
+        ("status", |k| k.status = INVALID_ENUM),
+        ("missing key data", |k| k.key_data = None),
+        ("key material type", |k| {
+            k.key_data.as_mut().unwrap().key_material_type = INVALID_ENUM
+        }),
+        ("type URL", |k| {
+            k.key_data.as_mut().unwrap().type_url = "unknown type URL".to_string()
+        }),
+        ("garbage key value", |k| {
+            k.key_data.as_mut().unwrap().value = vec![0xff, 0xff, 0xff]
+        }),
+        ("truncated key value", |k| {
+            let value = &mut k.key_data.as_mut().unwrap().value;
+            value.truncate(value.len() / 2)
+        }),
+    ];
+    for (desc, mutate) in mutations {
+        let mut ks = tink_tests::new_test_aes_gcm_keyset(OutputPrefixType::Tink);
+        let primary_key_id = ks.primary_key_id;
+        let key = ks
+            .key
+            .iter_mut()
+            .find(|k| k.key_id == primary_key_id)
+            .unwrap();
+        mutate(key);
+        let result = insecure::new_handle(ks).and_then(|h| tink_aead::new(&h));
+        assert!(result.is_err(), "malformed {} unexpectedly accepted", desc);
+    }
+}
+
+#[test]
+fn test_malformed_enum_in_symmetric_key() {
+    init();
+
+    let mut key = tink_tests::new_hmac_key(HashType::Sha256, 32);
+    key.params.as_mut().unwrap().hash = INVALID_ENUM;
+    assert_primitive_err(tink_mac::HMAC_TYPE_URL, &key, "HMAC hash");
+
+    let mut key = tink_tests::new_hmac_prf_key(HashType::Sha256);
+    key.params.as_mut().unwrap().hash = INVALID_ENUM;
+    assert_primitive_err(tink_prf::HMAC_PRF_TYPE_URL, &key, "HMAC-PRF hash");
+
+    let mut key = tink_tests::new_hkdf_prf_key(HashType::Sha256, &[]);
+    key.params.as_mut().unwrap().hash = INVALID_ENUM;
+    assert_primitive_err(tink_prf::HKDF_PRF_TYPE_URL, &key, "HKDF-PRF hash");
+
+    let key = tink_tests::new_aes_gcm_hkdf_key(0, 16, 16, INVALID_ENUM, 4096);
+    assert_primitive_err(
+        tink_streaming_aead::AES_GCM_HKDF_TYPE_URL,
+        &key,
+        "AES-GCM-HKDF hash",
+    );
+
+    let mut key =
+        tink_tests::new_aes_ctr_hmac_key(0, 16, HashType::Sha256, 16, HashType::Sha256, 16, 4096);
+    key.params
+        .as_mut()
+        .unwrap()
+        .hmac_params
+        .as_mut()
+        .unwrap()
+        .hash = INVALID_ENUM;
+    assert_primitive_err(
+        tink_streaming_aead::AES_CTR_HMAC_TYPE_URL,
+        &key,
+        "AES-CTR-HMAC hash",
+    );
+}
+
+#[test]
+fn test_malformed_enum_in_ecdsa_key() {
+    init();
+    type Mutation = fn(&mut tink_proto::EcdsaParams);
+    let mutations: Vec<(&str, Mutation)> = vec![
+        ("ECDSA hash", |p| p.hash_type = INVALID_ENUM),
+        ("ECDSA curve", |p| p.curve = INVALID_ENUM),
+        ("ECDSA encoding", |p| p.encoding = INVALID_ENUM),
+    ];
+    for (desc, mutate) in mutations {
+        let mut key =
+            tink_tests::new_random_ecdsa_private_key(HashType::Sha256, EllipticCurveType::NistP256);
+        let pub_key = key.public_key.as_mut().unwrap();
+        mutate(pub_key.params.as_mut().unwrap());
+        assert_primitive_err(
+            tink_signature::ECDSA_VERIFIER_TYPE_URL,
+            &pub_key.clone(),
+            desc,
+        );
+        assert_primitive_err(tink_signature::ECDSA_SIGNER_TYPE_URL, &key, desc);
+    }
+}
+
+#[test]
+fn test_malformed_ecies_key() {
+    init();
+    type Mutation = fn(&mut tink_proto::EciesAeadHkdfParams);
+    let mutations: Vec<(&str, Mutation)> = vec![
+        ("ECIES curve", |p| {
+            p.kem_params.as_mut().unwrap().curve_type = INVALID_ENUM
+        }),
+        ("ECIES HKDF hash", |p| {
+            p.kem_params.as_mut().unwrap().hkdf_hash_type = INVALID_ENUM
+        }),
+        ("ECIES point format", |p| p.ec_point_format = INVALID_ENUM),
+        ("ECIES DEM template", |p| {
+            p.dem_params
+                .as_mut()
+                .unwrap()
+                .aead_dem
+                .as_mut()
+                .unwrap()
+                .value = vec![0xff, 0xff, 0xff]
+        }),
+        ("ECIES DEM type URL", |p| {
+            p.dem_params
+                .as_mut()
+                .unwrap()
+                .aead_dem
+                .as_mut()
+                .unwrap()
+                .type_url = tink_mac::HMAC_TYPE_URL.to_string()
+        }),
+    ];
+    let template = tink_hybrid::ecies_hkdf_aes128_gcm_key_template();
+    for (desc, mutate) in mutations {
+        let key_data = tink_core::registry::new_key_data(&template).unwrap();
+        let mut key = tink_proto::EciesAeadHkdfPrivateKey::decode(&key_data.value[..]).unwrap();
+        let pub_key = key.public_key.as_mut().unwrap();
+        mutate(pub_key.params.as_mut().unwrap());
+        assert_primitive_err(
+            tink_hybrid::ECIES_AEAD_HKDF_PUBLIC_KEY_TYPE_URL,
+            &pub_key.clone(),
+            desc,
+        );
+        assert_primitive_err(
+            tink_hybrid::ECIES_AEAD_HKDF_PRIVATE_KEY_TYPE_URL,
+            &key,
+            desc,
+        );
+    }
+}
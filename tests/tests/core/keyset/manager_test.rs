@@ -383,3 +383,87 @@ fn test_unknown_output_prefix_type_fails() {
     let result = ksm1.rotate(&kt);
     tink_tests::expect_err(result, "unknown output prefix type");
 }
+
+#[test]
+fn test_keyset_manager_set_key_status() {
+    use tink_proto::KeyStatusType;
+    tink_aead::init();
+    let key_template = tink_aead::aes128_gcm_key_template();
+    let mut km = tink_core::keyset::Manager::new();
+    let primary_id = km.rotate(&key_template).unwrap();
+    let other_id = km.add(&key_template, false).unwrap();
+    assert_eq!(km.key_status(other_id).unwrap(), KeyStatusType::Enabled);
+
+    km.set_key_status(other_id, KeyStatusType::Disabled)
+        .unwrap();
+    assert_eq!(km.key_status(other_id).unwrap(), KeyStatusType::Disabled);
+    km.set_key_status(other_id, KeyStatusType::Disabled)
+        .unwrap();
+    km.set_key_status(other_id, KeyStatusType::Enabled).unwrap();
+    assert_eq!(km.key_status(other_id).unwrap(), KeyStatusType::Enabled);
+
+    // The primary key must stay enabled.
+    tink_tests::expect_err(
+        km.set_key_status(primary_id, KeyStatusType::Disabled),
+        "Cannot disable primary",
+    );
+    tink_tests::expect_err(
+        km.set_key_status(primary_id, KeyStatusType::Destroyed),
+        "Cannot destroy primary",
+    );
+    km.set_key_status(primary_id, KeyStatusType::Enabled)
+        .unwrap();
+    assert_eq!(km.key_status(primary_id).unwrap(), KeyStatusType::Enabled);
+
+    // Destroyed is terminal.
+    km.set_key_status(other_id, KeyStatusType::Destroyed)
+        .unwrap();
+    assert_eq!(km.key_status(other_id).unwrap(), KeyStatusType::Destroyed);
+    km.set_key_status(other_id, KeyStatusType::Destroyed)
+        .unwrap();
+    tink_tests::expect_err(
+        km.set_key_status(other_id, KeyStatusType::Enabled),
+        "Cannot enable key with key_id",
+    );
+    tink_tests::expect_err(
+        km.set_key_status(other_id, KeyStatusType::Disabled),
+        "and status Destroyed",
+    );
+    tink_tests::expect_err(km.set_primary(other_id), "(status Destroyed)");
+    tink_tests::expect_err(
+        km.set_key_status(other_id, KeyStatusType::UnknownStatus),
+        "Cannot set status",
+    );
+
+    tink_tests::expect_err(km.key_status(12345), "Key 12345 not found");
+    tink_tests::expect_err(
+        km.set_key_status(12345, KeyStatusType::Enabled),
+        "Key 12345 not found",
+    );
+
+    // Every reachable keyset is valid.
+    let kh = km.handle().unwrap();
+    let ks = insecure::keyset_material(&kh);
+    assert_eq!(ks.primary_key_id, primary_id);
+    assert!(ks.key[1].key_data.is_none());
+}
+
+#[test]
+fn test_keyset_manager_key_status_invalid() {
+    tink_aead::init();
+    let mut km = tink_core::keyset::Manager::new();
+    km.rotate(&tink_aead::aes128_gcm_key_template()).unwrap();
+    let secondary_key_id = km
+        .add(&tink_aead::aes128_gcm_key_template(), false)
+        .unwrap();
+    let mut keyset = insecure::keyset_material(&km.handle().unwrap());
+    keyset.key[1].status = 999;
+    let kh = insecure::new_handle(keyset).unwrap();
+    let mut km = tink_core::keyset::Manager::new_from_handle(kh);
+
+    tink_tests::expect_err(km.key_status(secondary_key_id), "invalid status");
+    tink_tests::expect_err(
+        km.set_key_status(secondary_key_id, tink_proto::KeyStatusType::Enabled),
+        "status UnknownStatus (999)",
+    );
+}
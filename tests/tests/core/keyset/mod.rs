@@ -14,8 +14,19 @@
 //
 ////////////////////////////////////////////////////////////////////////////////
 
+mod audit_test;
 mod binary_io_test;
+mod builder_test;
+mod bundle_test;
+mod diff_test;
+mod gatekeeper_test;
 mod handle_test;
 mod json_io_test;
+mod malformed_test;
 mod manager_test;
+mod password_test;
+mod proptest_test;
+mod redact_test;
+mod reloading_test;
 mod validation_test;
+mod wrapped_key_test;
@@ -0,0 +1,171 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_core::keyset::{BinaryReader, BinaryWriter, Handle, PasswordKdfParams};
+use tink_tests::expect_err;
+
+const PASSWORD: &[u8] = b"hunter2";
+
+/// Parameters that keep the tests fast.
+const FAST_PARAMS: PasswordKdfParams = PasswordKdfParams {
+    m_cost_kib: 64,
+    t_cost: 1,
+    p_cost: 1,
+};
+
+/// Binary `EncryptedKeyset` holding a single HMAC-SHA256 key (id 42, TINK prefix, key bytes
+/// 0x00..0x1f), encrypted under `PASSWORD` with `FAST_PARAMS` by an independent implementation.
+const FIXTURE: &str = "12a2010101000000400000000100000001c92442efa8d149a683a200354ebbd028da605f45dbac549470334d9fc8a4adccac678aa6ffe4db21776c33297b2cde865ebc614b74fe9cc09cc3564317092a5881896966fb7bd5d5e27c0be4f4479d84e8ed8aae6c56cd6d025e70e6bb631a77b1adab696a67451b80829a758105280d88a8eb17582b7d8da0c5f0aed5a990d3740ae6908e814f30f584dcde221251f4d83bb0a7";
+/// HMAC tag for `MESSAGE` under the fixture key.
+const FIXTURE_TAG: &str = "010000002adfee8f2e3d54c458b1c59b1a71d06c5a";
+const MESSAGE: &[u8] = b"some data to authenticate";
+
+fn write(h: &Handle, params: &PasswordKdfParams) -> Vec<u8> {
+    let mut buf = Vec::new();
+    h.write_with_password_params(&mut BinaryWriter::new(&mut buf), PASSWORD, params)
+        .unwrap();
+    buf
+}
+
+fn read(buf: &[u8], password: &[u8]) -> Result<Handle, tink_core::TinkError> {
+    Handle::read_with_password(&mut BinaryReader::new(buf), password)
+}
+
+#[test]
+fn test_password_round_trip() {
+    tink_mac::init();
+    let h = Handle::new(&tink_mac::hmac_sha256_tag256_key_template()).unwrap();
+    let mut buf = Vec::new();
+    h.write_with_password(&mut BinaryWriter::new(&mut buf), PASSWORD)
+        .unwrap();
+
+    let h2 = read(&buf, PASSWORD).unwrap();
+    assert_eq!(
+        tink_core::keyset::insecure::keyset_material(&h),
+        tink_core::keyset::insecure::keyset_material(&h2)
+    );
+    expect_err(read(&buf, b"hunter3"), "decryption failed");
+}
+
+#[test]
+fn test_password_json_round_trip() {
+    tink_mac::init();
+    let h = Handle::new(&tink_mac::hmac_sha256_tag256_key_template()).unwrap();
+    let mut buf = Vec::new();
+    h.write_with_password_params(
+        &mut tink_core::keyset::JsonWriter::new(&mut buf),
+        PASSWORD,
+        &FAST_PARAMS,
+    )
+    .unwrap();
+    let h2 =
+        Handle::read_with_password(&mut tink_core::keyset::JsonReader::new(&buf[..]), PASSWORD)
+            .unwrap();
+    assert_eq!(
+        tink_core::keyset::insecure::keyset_material(&h),
+        tink_core::keyset::insecure::keyset_material(&h2)
+    );
+}
+
+#[test]
+fn test_password_fixture() {
+    tink_mac::init();
+    let h = read(&hex::decode(FIXTURE).unwrap(), PASSWORD).unwrap();
+    let info = h.keyset_info();
+    assert_eq!(info.primary_key_id, 42);
+    let mac = tink_mac::new(&h).unwrap();
+    assert_eq!(hex::encode(mac.compute_mac(MESSAGE).unwrap()), FIXTURE_TAG);
+    expect_err(
+        read(&hex::decode(FIXTURE).unwrap(), b"wrong"),
+        "decryption failed",
+    );
+}
+
+#[test]
+fn test_password_fresh_salt() {
+    tink_mac::init();
+    let h = Handle::new(&tink_mac::hmac_sha256_tag256_key_template()).unwrap();
+    assert_ne!(write(&h, &FAST_PARAMS), write(&h, &FAST_PARAMS));
+}
+
+#[test]
+fn test_password_tampered() {
+    tink_mac::init();
+    let fixture = hex::decode(FIXTURE).unwrap();
+    // The ciphertext starts after the 3-byte protobuf tag and length.
+    let start = 3;
+
+    // Changing the stored parameters (here, the iteration count) causes decryption to fail.
+    let mut bad = fixture.clone();
+    bad[start + 9] = 2;
+    expect_err(read(&bad, PASSWORD), "decryption failed");
+
+    let mut bad = fixture.clone();
+    bad[start] = 2;
+    expect_err(read(&bad, PASSWORD), "unsupported format version");
+
+    let mut bad = fixture.clone();
+    bad[start + 1] = 2;
+    expect_err(read(&bad, PASSWORD), "unsupported KDF");
+
+    // Excessive parameters are rejected before any key derivation is attempted.
+    let mut bad = fixture.clone();
+    bad[start + 2] = 0xff;
+    expect_err(read(&bad, PASSWORD), "exceed limits");
+
+    let mut bad = fixture;
+    let n = bad.len();
+    bad[n - 1] ^= 0x01;
+    expect_err(read(&bad, PASSWORD), "decryption failed");
+}
+
+#[test]
+fn test_password_invalid_params() {
+    tink_mac::init();
+    let h = Handle::new(&tink_mac::hmac_sha256_tag256_key_template()).unwrap();
+    let mut buf = Vec::new();
+    for params in &[
+        PasswordKdfParams {
+            p_cost: 0,
+            ..FAST_PARAMS
+        },
+        PasswordKdfParams {
+            t_cost: 0,
+            ..FAST_PARAMS
+        },
+        PasswordKdfParams {
+            t_cost: tink_core::keyset::PASSWORD_MAX_T_COST + 1,
+            ..FAST_PARAMS
+        },
+    ] {
+        assert!(h
+            .write_with_password_params(&mut BinaryWriter::new(&mut buf), PASSWORD, params)
+            .is_err());
+    }
+}
+
+#[test]
+fn test_password_not_password_encrypted() {
+    tink_mac::init();
+    let h = Handle::new(&tink_mac::hmac_sha256_tag256_key_template()).unwrap();
+    let mut buf = Vec::new();
+    h.write(
+        &mut BinaryWriter::new(&mut buf),
+        Box::new(tink_aead::subtle::ChaCha20Poly1305::new(&[1; 32]).unwrap()),
+    )
+    .unwrap();
+    assert!(read(&buf, PASSWORD).is_err());
+}
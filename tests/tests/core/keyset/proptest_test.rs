@@ -0,0 +1,62 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use proptest::prelude::*;
+use tink_core::keyset::{insecure, Reader, Writer};
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn test_arb_keyset_binary_roundtrip(ks in tink_tests::arb_keyset()) {
+        let mut buf = Vec::new();
+        tink_core::keyset::BinaryWriter::new(&mut buf).write(&ks).unwrap();
+        let ks2 = tink_core::keyset::BinaryReader::new(&buf[..]).read().unwrap();
+        prop_assert_eq!(&ks, &ks2);
+
+        let kh = insecure::new_handle(ks).unwrap();
+        prop_assert!(kh.primitives().is_ok());
+    }
+
+    #[test]
+    fn test_arb_keyset_json_roundtrip(ks in tink_tests::arb_keyset()) {
+        let mut buf = Vec::new();
+        tink_core::keyset::JsonWriter::new(&mut buf).write(&ks).unwrap();
+        let ks2 = tink_core::keyset::JsonReader::new(&buf[..]).read().unwrap();
+        prop_assert_eq!(ks, ks2);
+    }
+
+    #[test]
+    fn test_arbitrary_binary_keyset_no_panic(data in prop::collection::vec(any::<u8>(), 0..256)) {
+        let mut reader = tink_core::keyset::BinaryReader::new(&data[..]);
+        if let Ok(kh) = insecure::read(&mut reader) {
+            let _ = kh.primitives();
+            let _ = kh.public();
+        }
+    }
+
+    #[test]
+    fn test_mutated_keyset_no_panic(ks in tink_tests::arb_keyset(), idx in any::<prop::sample::Index>(), val in any::<u8>()) {
+        let mut buf = Vec::new();
+        tink_core::keyset::BinaryWriter::new(&mut buf).write(&ks).unwrap();
+        let i = idx.index(buf.len());
+        buf[i] = val;
+        let mut reader = tink_core::keyset::BinaryReader::new(&buf[..]);
+        if let Ok(kh) = insecure::read(&mut reader) {
+            let _ = kh.primitives();
+        }
+    }
+}
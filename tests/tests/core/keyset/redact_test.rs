@@ -0,0 +1,108 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_core::keyset::{insecure, RedactedDebug};
+use tink_proto::{key_data::KeyMaterialType, KeyStatusType, OutputPrefixType};
+
+#[test]
+fn test_redacted_keyset() {
+    let key_value = vec![0xab; 34];
+    let kd = tink_tests::new_key_data(
+        tink_tests::AES_GCM_TYPE_URL,
+        &key_value,
+        KeyMaterialType::Symmetric,
+    );
+    let key = tink_tests::new_key(&kd, KeyStatusType::Enabled, 42, OutputPrefixType::Tink);
+    let ks = tink_tests::new_keyset(42, vec![key]);
+
+    // The derived `Debug` output includes the key material.
+    assert!(format!("{:?}", ks).contains("171, 171"));
+
+    let got = format!("{:?}", ks.redacted());
+    assert_eq!(
+        got,
+        format!(
+            "Keyset {{ primary_key_id: 42, key: [Key {{ key_data: Some(KeyData {{ type_url: \
+             {:?}, key_material_type: Symmetric, value: <redacted, 34 bytes> }}), status: \
+             Enabled, key_id: 42, output_prefix_type: Tink }}] }}",
+            tink_tests::AES_GCM_TYPE_URL
+        )
+    );
+
+    // Pretty-printed output is redacted too.
+    let pretty = format!("{:#?}", ks.redacted());
+    assert!(pretty.contains("<redacted, 34 bytes>"));
+    assert!(!pretty.contains("171"));
+}
+
+#[test]
+fn test_redacted_generated_keyset() {
+    tink_aead::init();
+    let h = tink_core::keyset::Handle::new(&tink_aead::aes256_gcm_key_template()).unwrap();
+    let ks = insecure::keyset_material(&h);
+    let kd = ks.key[0].key_data.as_ref().unwrap();
+    let got = format!("{:?}", ks.redacted());
+    assert!(got.contains(&format!("<redacted, {} bytes>", kd.value.len())));
+    assert!(got.contains(&format!("key_id: {}", ks.primary_key_id)));
+    assert!(!got.contains(&format!("{:?}", kd.value)));
+}
+
+#[test]
+fn test_redacted_unknown_enums() {
+    let kd = tink_proto::KeyData {
+        type_url: "some type".to_string(),
+        value: vec![],
+        key_material_type: 99,
+    };
+    let key = tink_proto::keyset::Key {
+        key_data: None,
+        status: 98,
+        key_id: 1,
+        output_prefix_type: 97,
+    };
+    assert_eq!(
+        format!("{:?}", kd.redacted()),
+        "KeyData { type_url: \"some type\", key_material_type: 99, value: <redacted, 0 bytes> }"
+    );
+    assert_eq!(
+        format!("{:?}", key.redacted()),
+        "Key { key_data: None, status: 98, key_id: 1, output_prefix_type: 97 }"
+    );
+}
+
+#[test]
+fn test_redacted_key_messages() {
+    let key = tink_tests::new_aes_gcm_key(0, 16);
+    assert!(format!("{:?}", key).contains("key_value"));
+    let want = format!(
+        "AesGcmKey <redacted, {} bytes>",
+        tink_tests::proto_encode(&key).len()
+    );
+    assert_eq!(format!("{:?}", key.redacted()), want);
+
+    let key = tink_tests::new_hmac_key(tink_proto::HashType::Sha256, 32);
+    assert!(format!("{:?}", key.redacted()).starts_with("HmacKey <redacted, "));
+
+    let key = tink_proto::Ed25519PrivateKey {
+        version: 0,
+        key_value: vec![0xab; 32],
+        public_key: None,
+    };
+    assert_eq!(
+        format!("{:?}", key.redacted()),
+        "Ed25519PrivateKey <redacted, 34 bytes>"
+    );
+}
@@ -0,0 +1,253 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use std::{
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime},
+};
+use tink_core::{
+    keyset::{FixedClock, KeysetStore, Manager, MemKeysetStore, ReloadingHandle, StoredKeyset},
+    TinkError,
+};
+use tink_tests::expect_err;
+
+fn main_key() -> Box<tink_aead::subtle::AesGcm> {
+    Box::new(tink_aead::subtle::AesGcm::new(&[b'A'; 32]).unwrap())
+}
+
+/// A [`KeysetStore`] shared with the test, which can be made to fail.
+#[derive(Clone, Default)]
+struct SharedStore {
+    store: Arc<MemKeysetStore>,
+    loads: Arc<AtomicUsize>,
+    fail: Arc<AtomicBool>,
+}
+
+impl SharedStore {
+    fn loads(&self) -> usize {
+        self.loads.load(Ordering::SeqCst)
+    }
+
+    fn set_fail(&self, fail: bool) {
+        self.fail.store(fail, Ordering::SeqCst);
+    }
+}
+
+impl KeysetStore for SharedStore {
+    fn load(&self) -> Result<StoredKeyset, TinkError> {
+        self.loads.fetch_add(1, Ordering::SeqCst);
+        if self.fail.load(Ordering::SeqCst) {
+            return Err("store unavailable".into());
+        }
+        self.store.load()
+    }
+
+    fn save(&self, stored: &StoredKeyset) -> Result<(), TinkError> {
+        self.store.save(stored)
+    }
+}
+
+/// Rotate in a new primary key and save the keyset to `store`.
+fn rotate(ksm: &mut Manager, store: &SharedStore) {
+    ksm.rotate(&tink_aead::aes128_gcm_key_template()).unwrap();
+    ksm.save_to_store(store, main_key()).unwrap();
+}
+
+fn primary_key_id(rh: &ReloadingHandle) -> u32 {
+    rh.handle().unwrap().keyset_info().primary_key_id
+}
+
+fn setup() -> (SharedStore, Manager, Arc<FixedClock>) {
+    tink_aead::init();
+    let store = SharedStore::default();
+    let mut ksm = Manager::new();
+    rotate(&mut ksm, &store);
+    let clock = Arc::new(FixedClock::new(
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000),
+    ));
+    (store, ksm, clock)
+}
+
+#[test]
+fn test_reloading_handle_ttl() {
+    let (store, mut ksm, clock) = setup();
+    let rh = ReloadingHandle::new_with_clock(
+        Box::new(store.clone()),
+        main_key(),
+        Some(Duration::from_secs(60)),
+        clock.clone(),
+    )
+    .unwrap();
+    let first = primary_key_id(&rh);
+    assert_eq!(rh.generation().unwrap(), 0);
+
+    rotate(&mut ksm, &store);
+    let loads = store.loads();
+    // Not yet expired.
+    clock.advance(Duration::from_secs(59));
+    assert_eq!(primary_key_id(&rh), first);
+    assert_eq!(store.loads(), loads);
+
+    clock.advance(Duration::from_secs(1));
+    let second = primary_key_id(&rh);
+    assert_ne!(second, first);
+    assert_eq!(rh.generation().unwrap(), 1);
+    assert_eq!(store.loads(), loads + 1);
+
+    // Re-reading an unchanged keyset leaves the generation as it is.
+    clock.advance(Duration::from_secs(60));
+    assert_eq!(primary_key_id(&rh), second);
+    assert_eq!(rh.generation().unwrap(), 1);
+    assert_eq!(store.loads(), loads + 2);
+}
+
+#[test]
+fn test_reloading_handle_notification() {
+    let (store, mut ksm, clock) = setup();
+    let rh =
+        ReloadingHandle::new_with_clock(Box::new(store.clone()), main_key(), None, clock.clone())
+            .unwrap();
+    let first = primary_key_id(&rh);
+
+    rotate(&mut ksm, &store);
+    // Without a time-to-live, the keyset is only re-read on request.
+    clock.advance(Duration::from_secs(365 * 24 * 60 * 60));
+    assert_eq!(primary_key_id(&rh), first);
+
+    rh.notify_changed();
+    let second = primary_key_id(&rh);
+    assert_ne!(second, first);
+
+    rotate(&mut ksm, &store);
+    assert!(rh.reload().unwrap());
+    assert_ne!(primary_key_id(&rh), second);
+    assert!(!rh.reload().unwrap());
+    assert_eq!(rh.generation().unwrap(), 2);
+}
+
+#[test]
+fn test_reloading_handle_keeps_keyset_on_failure() {
+    let (store, mut ksm, clock) = setup();
+    let rh = ReloadingHandle::new_with_clock(
+        Box::new(store.clone()),
+        main_key(),
+        Some(Duration::from_secs(60)),
+        clock.clone(),
+    )
+    .unwrap();
+    let first = primary_key_id(&rh);
+    rotate(&mut ksm, &store);
+
+    store.set_fail(true);
+    clock.advance(Duration::from_secs(60));
+    assert_eq!(primary_key_id(&rh), first);
+    assert!(rh.last_error().unwrap().contains("store unavailable"));
+    // The failed re-read is not retried until the time-to-live elapses again.
+    let loads = store.loads();
+    assert_eq!(primary_key_id(&rh), first);
+    assert_eq!(store.loads(), loads);
+    expect_err(rh.reload(), "store unavailable");
+
+    store.set_fail(false);
+    clock.advance(Duration::from_secs(60));
+    assert_ne!(primary_key_id(&rh), first);
+    assert!(rh.last_error().is_none());
+
+    // A keyset that cannot be decrypted is also rejected.
+    let mut stored = store.load().unwrap();
+    stored.metadata = b"modified".to_vec();
+    store.save(&stored).unwrap();
+    expect_err(rh.reload(), "cannot decrypt keyset");
+    assert_eq!(rh.generation().unwrap(), 1);
+}
+
+#[test]
+fn test_reloading_handle_invalid_store() {
+    tink_aead::init();
+    let store = SharedStore::default();
+    assert!(ReloadingHandle::new(Box::new(store), main_key(), None).is_err());
+}
+
+#[test]
+fn test_reloading_primitive() {
+    let (store, mut ksm, clock) = setup();
+    let rh = ReloadingHandle::new_with_clock(
+        Box::new(store.clone()),
+        main_key(),
+        Some(Duration::from_secs(60)),
+        clock.clone(),
+    )
+    .unwrap();
+    let aead = rh.primitive(tink_aead::new);
+    let p1 = aead.get().unwrap();
+    let ct1 = p1.encrypt(b"plaintext", b"aad").unwrap();
+    assert!(Rc::ptr_eq(&p1, &aead.get().unwrap()));
+
+    rotate(&mut ksm, &store);
+    clock.advance(Duration::from_secs(60));
+    let p2 = aead.get().unwrap();
+    assert!(!Rc::ptr_eq(&p1, &p2));
+    let ct2 = p2.encrypt(b"plaintext", b"aad").unwrap();
+    assert_ne!(ct1[1..5], ct2[1..5]);
+    // The new primitive decrypts ciphertexts from the old keyset, which is still in the keyset.
+    assert_eq!(p2.decrypt(&ct1, b"aad").unwrap(), b"plaintext");
+    // The old primitive remains usable.
+    assert_eq!(p1.decrypt(&ct1, b"aad").unwrap(), b"plaintext");
+}
+
+#[test]
+fn test_reloading_handle_is_shareable() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<ReloadingHandle>();
+
+    let (store, mut ksm, _) = setup();
+    let rh = Arc::new(ReloadingHandle::new(Box::new(store.clone()), main_key(), None).unwrap());
+    let first = primary_key_id(&rh);
+
+    // Readers on other threads keep working while the keyset is replaced.
+    let readers: Vec<_> = (0..4)
+        .map(|_| {
+            let rh = rh.clone();
+            std::thread::spawn(move || {
+                for _ in 0..100 {
+                    let a = tink_aead::new(&rh.handle().unwrap()).unwrap();
+                    let ct = a.encrypt(b"plaintext", b"aad").unwrap();
+                    assert_eq!(a.decrypt(&ct, b"aad").unwrap(), b"plaintext");
+                }
+            })
+        })
+        .collect();
+
+    // A change notification from another thread is picked up on the next access.
+    rotate(&mut ksm, &store);
+    let notifier = rh.clone();
+    std::thread::spawn(move || notifier.notify_changed())
+        .join()
+        .unwrap();
+    let second = primary_key_id(&rh);
+    assert_ne!(second, first);
+    assert_eq!(rh.generation().unwrap(), 1);
+
+    for r in readers {
+        r.join().unwrap();
+    }
+    assert_eq!(primary_key_id(&rh), second);
+    assert!(rh.last_error().is_none());
+}
@@ -0,0 +1,159 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_aead::subtle::{AesKw, AesKwp};
+use tink_core::{
+    keyset::{Handle, KeyUsage},
+    KeyWrap,
+};
+use tink_tests::expect_err;
+
+#[test]
+fn test_export_import_wrapped_key() {
+    tink_aead::init();
+    tink_daead::init();
+    tink_mac::init();
+    let kek = AesKw::new(&[0x42; 32]).unwrap();
+    for kt in &[
+        tink_aead::aes128_gcm_key_template(),
+        tink_aead::aes256_gcm_key_template(),
+        tink_aead::aes256_gcm_siv_key_template(),
+        tink_aead::cha_cha20_poly1305_key_template(),
+        tink_aead::x_cha_cha20_poly1305_key_template(),
+        tink_daead::aes_siv_key_template(),
+        tink_mac::hmac_sha256_tag256_key_template(),
+    ] {
+        let h = Handle::new(kt).unwrap();
+        let key_id = h.keyset_info().primary_key_id;
+        let wrapped = h.export_wrapped_key(key_id, &kek).unwrap();
+
+        let imported = Handle::import_wrapped_key(&wrapped, &kek, kt).unwrap();
+        let info = imported.keyset_info();
+        assert_eq!(info.key_info.len(), 1);
+        assert_eq!(info.key_info[0].type_url, kt.type_url);
+        assert_eq!(info.key_info[0].output_prefix_type, kt.output_prefix_type);
+        // The same key material is exported again.
+        assert_eq!(
+            imported
+                .export_wrapped_key(info.primary_key_id, &kek)
+                .unwrap(),
+            wrapped
+        );
+    }
+}
+
+#[test]
+fn test_export_wrapped_key_restricted_handle() {
+    tink_aead::init();
+    tink_mac::init();
+    let kek = AesKw::new(&[0x42; 32]).unwrap();
+    for (kt, usage) in &[
+        (tink_aead::aes256_gcm_key_template(), KeyUsage::EncryptOnly),
+        (tink_aead::aes256_gcm_key_template(), KeyUsage::DecryptOnly),
+        (
+            tink_mac::hmac_sha256_tag256_key_template(),
+            KeyUsage::VerifyOnly,
+        ),
+    ] {
+        let h = Handle::new(kt).unwrap();
+        let key_id = h.keyset_info().primary_key_id;
+        let restricted = h.restrict(*usage).unwrap();
+        expect_err(
+            restricted.export_wrapped_key(key_id, &kek),
+            "usage-restricted",
+        );
+        // The unrestricted handle can still export.
+        assert!(h.export_wrapped_key(key_id, &kek).is_ok());
+    }
+}
+
+#[test]
+fn test_import_wrapped_key_interop() {
+    tink_aead::init();
+    // Key material wrapped by another party (for example, an HSM) using AES-KW.
+    let kek = AesKw::new(&[0x42; 16]).unwrap();
+    let raw_key = [0x24; 32];
+    let wrapped = kek.wrap_key(&raw_key).unwrap();
+
+    let kt = tink_aead::aes256_gcm_no_prefix_key_template();
+    let h = Handle::import_wrapped_key(&wrapped, &kek, &kt).unwrap();
+    let a = tink_aead::new(&h).unwrap();
+
+    // With a RAW prefix, ciphertexts are plain AES-GCM and decrypt with the raw key.
+    let ct = a.encrypt(b"plaintext", b"aad").unwrap();
+    let raw = tink_aead::subtle::AesGcm::new(&raw_key).unwrap();
+    assert_eq!(
+        tink_core::Aead::decrypt(&raw, &ct, b"aad").unwrap(),
+        b"plaintext"
+    );
+
+    // And the key can be exported back in the same form.
+    let exported = h
+        .export_wrapped_key(h.keyset_info().primary_key_id, &kek)
+        .unwrap();
+    assert_eq!(kek.unwrap_key(&exported).unwrap(), raw_key);
+}
+
+#[test]
+fn test_import_wrapped_key_kwp() {
+    tink_mac::init();
+    // HMAC keys need not be a multiple of 8 bytes.
+    let kek = AesKwp::new(&[0x42; 32]).unwrap();
+    let mut kt = tink_mac::hmac_sha256_tag256_key_template();
+    let mut format: tink_proto::HmacKeyFormat =
+        tink_proto::prost::Message::decode(kt.value.as_ref()).unwrap();
+    format.key_size = 20;
+    kt.value = tink_tests::proto_encode(&format);
+
+    let wrapped = kek.wrap_key(&[0x33; 20]).unwrap();
+    let h = Handle::import_wrapped_key(&wrapped, &kek, &kt).unwrap();
+    let m = tink_mac::new(&h).unwrap();
+    let tag = m.compute_mac(b"data").unwrap();
+    m.verify_mac(&tag, b"data").unwrap();
+}
+
+#[test]
+fn test_wrapped_key_errors() {
+    tink_aead::init();
+    tink_signature::init();
+    let kek = AesKw::new(&[0x42; 32]).unwrap();
+
+    let h = Handle::new(&tink_aead::aes256_gcm_key_template()).unwrap();
+    expect_err(h.export_wrapped_key(12345, &kek), "key 12345 not found");
+
+    // Only symmetric keys of known types can be exported.
+    let h = Handle::new(&tink_signature::ed25519_key_template()).unwrap();
+    let key_id = h.keyset_info().primary_key_id;
+    expect_err(h.export_wrapped_key(key_id, &kek), "unsupported key type");
+
+    // The unwrapped key material must match the template.
+    let wrapped = kek.wrap_key(&[0x11; 16]).unwrap();
+    expect_err(
+        Handle::import_wrapped_key(&wrapped, &kek, &tink_aead::aes256_gcm_key_template()),
+        "key material has 16 bytes, template requires 32",
+    );
+    expect_err(
+        Handle::import_wrapped_key(&wrapped, &kek, &tink_signature::ed25519_key_template()),
+        "unsupported key type",
+    );
+
+    // As must the KEK.
+    let other = AesKw::new(&[0x43; 32]).unwrap();
+    expect_err(
+        Handle::import_wrapped_key(&wrapped, &other, &tink_aead::aes128_gcm_key_template()),
+        "cannot unwrap key",
+    );
+}
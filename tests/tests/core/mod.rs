@@ -14,7 +14,13 @@
 //
 ////////////////////////////////////////////////////////////////////////////////
 
+mod associated_data_test;
+mod custom_output_prefix_test;
 mod keyset;
+mod pipeline_test;
+mod primitive_test;
 mod primitiveset;
+mod proto_enums_test;
 mod registry;
 mod subtle;
+mod trace_test;
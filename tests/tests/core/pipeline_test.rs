@@ -0,0 +1,133 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_core::{
+    pipeline::{Pipeline, Stage, Transform, PIPELINE_VERSION},
+    Aead, TinkError,
+};
+
+/// Toy reversible transform that stands in for compression.
+#[derive(Clone)]
+struct Reverse;
+
+impl Transform for Reverse {
+    fn forward(&self, data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        Ok(data.iter().rev().cloned().collect())
+    }
+    fn reverse(&self, data: &[u8]) -> Result<Vec<u8>, TinkError> {
+        self.forward(data)
+    }
+}
+
+fn new_aead() -> Box<dyn Aead> {
+    tink_aead::init();
+    let kh = tink_core::keyset::Handle::new(&tink_aead::aes128_gcm_key_template()).unwrap();
+    tink_aead::new(&kh).unwrap()
+}
+
+fn new_mac() -> Box<dyn tink_core::Mac> {
+    tink_mac::init();
+    let kh = tink_core::keyset::Handle::new(&tink_mac::hmac_sha256_tag256_key_template()).unwrap();
+    tink_mac::new(&kh).unwrap()
+}
+
+#[test]
+fn test_pipeline_encrypt_then_mac() {
+    let p = Pipeline::encrypt_then_mac(new_aead(), new_mac());
+    let pt = b"some data";
+    let aad = b"aad";
+    let ct = p.encrypt(pt, aad).unwrap();
+    assert_eq!(ct[0], PIPELINE_VERSION);
+    assert_eq!(p.decrypt(&ct, aad).unwrap(), pt);
+    assert_eq!(p.clone().decrypt(&ct, aad).unwrap(), pt);
+
+    tink_tests::expect_err(p.decrypt(&ct, b"other aad"), "invalid MAC");
+    for i in 0..ct.len() {
+        let mut modified = ct.clone();
+        modified[i] ^= 0x01;
+        assert!(p.decrypt(&modified, aad).is_err(), "modified byte {}", i);
+    }
+    for len in 0..ct.len() {
+        assert!(p.decrypt(&ct[..len], aad).is_err(), "truncated to {}", len);
+    }
+}
+
+#[test]
+fn test_pipeline_with_transform() {
+    let aead = new_aead();
+    let p = Pipeline::new(vec![
+        Stage::Transform(Box::new(Reverse)),
+        Stage::Aead(aead.box_clone()),
+    ])
+    .unwrap();
+    let ct = p.encrypt(b"abcdef", b"").unwrap();
+    assert_eq!(p.decrypt(&ct, b"").unwrap(), b"abcdef");
+
+    // The AEAD stage is bound to its position in the pipeline.
+    let direct = Pipeline::new(vec![Stage::Aead(aead)]).unwrap();
+    tink_tests::expect_err(direct.decrypt(&ct, b""), "decryption failed");
+}
+
+#[test]
+fn test_pipeline_stage_order_matters() {
+    let aead = new_aead();
+    let mac = new_mac();
+    let etm = Pipeline::encrypt_then_mac(aead.box_clone(), mac.box_clone());
+    let mte = Pipeline::new(vec![Stage::Mac(mac), Stage::Aead(aead)]).unwrap();
+    let ct = mte.encrypt(b"data", b"").unwrap();
+    assert_eq!(mte.decrypt(&ct, b"").unwrap(), b"data");
+    assert!(etm.decrypt(&ct, b"").is_err());
+}
+
+#[test]
+fn test_pipeline_invalid() {
+    tink_tests::expect_err(Pipeline::new(vec![]), "no AEAD stage");
+    tink_tests::expect_err(
+        Pipeline::new(vec![Stage::Transform(Box::new(Reverse))]),
+        "no AEAD stage",
+    );
+    // A MAC alone authenticates but does not encrypt.
+    tink_tests::expect_err(Pipeline::new(vec![Stage::Mac(new_mac())]), "no AEAD stage");
+    tink_tests::expect_err(
+        Pipeline::new(vec![
+            Stage::Transform(Box::new(Reverse)),
+            Stage::Mac(new_mac()),
+        ]),
+        "no AEAD stage",
+    );
+    // The output of a trailing transform would not be authenticated.
+    tink_tests::expect_err(
+        Pipeline::new(vec![
+            Stage::Aead(new_aead()),
+            Stage::Transform(Box::new(Reverse)),
+        ]),
+        "last stage is not an authenticating stage",
+    );
+    tink_tests::expect_err(
+        Pipeline::new(vec![
+            Stage::Aead(new_aead()),
+            Stage::Mac(new_mac()),
+            Stage::Transform(Box::new(Reverse)),
+        ]),
+        "last stage is not an authenticating stage",
+    );
+
+    let p = Pipeline::encrypt_then_mac(new_aead(), new_mac());
+    let mut ct = p.encrypt(b"data", b"").unwrap();
+    ct[0] = PIPELINE_VERSION + 1;
+    tink_tests::expect_err(p.decrypt(&ct, b""), "unsupported version");
+    tink_tests::expect_err(p.decrypt(&[], b""), "too short");
+}
@@ -0,0 +1,55 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_core::Primitive;
+
+fn aead_primitive() -> Primitive {
+    tink_aead::init();
+    let kt = tink_aead::aes128_gcm_key_template();
+    let key_data = tink_core::registry::new_key_data(&kt).unwrap();
+    tink_core::registry::primitive_from_key_data(&key_data).unwrap()
+}
+
+#[test]
+fn test_primitive_accessors() {
+    let p = aead_primitive();
+    assert_eq!(p.name(), "Aead");
+    assert!(p.as_aead().is_some());
+    assert!(p.as_mac().is_none());
+    assert!(p.as_signer().is_none());
+
+    let a = p.as_aead().unwrap();
+    let ct = a.encrypt(b"plaintext", b"aad").unwrap();
+    let a = p.clone().into_aead().unwrap();
+    assert_eq!(a.decrypt(&ct, b"aad").unwrap(), b"plaintext");
+
+    tink_tests::expect_err(p.clone().into_mac(), "expected Mac primitive, got Aead");
+    tink_tests::expect_err(
+        p.into_streaming_aead(),
+        "expected StreamingAead primitive, got Aead",
+    );
+}
+
+#[test]
+fn test_primitive_accessors_mac() {
+    tink_mac::init();
+    let kt = tink_mac::hmac_sha256_tag128_key_template();
+    let key_data = tink_core::registry::new_key_data(&kt).unwrap();
+    let p = tink_core::registry::primitive_from_key_data(&key_data).unwrap();
+    assert_eq!(p.name(), "Mac");
+    let tag = p.as_mac().unwrap().compute_mac(b"data").unwrap();
+    assert!(p.into_mac().unwrap().verify_mac(&tag, b"data").is_ok());
+}
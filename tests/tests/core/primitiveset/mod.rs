@@ -141,6 +141,32 @@ fn test_primitive_set_basic() {
     );
 }
 
+#[test]
+fn test_primitive_set_large_keyset() {
+    let n = 10_000;
+    let mut ps = tink_core::primitiveset::PrimitiveSet::new();
+    for key_id in 1..=n {
+        let key = new_dummy_key(key_id, KeyStatusType::Enabled, OutputPrefixType::Tink);
+        let mac = DummyMac {
+            name: format!("Mac#{}", key_id),
+        };
+        ps.add(Primitive::Mac(Box::new(mac)), &key).unwrap();
+    }
+    assert_eq!(ps.entries.len(), n as usize);
+    for key_id in &[1, n / 2, n] {
+        let key = new_dummy_key(*key_id, KeyStatusType::Enabled, OutputPrefixType::Tink);
+        let prefix = tink_core::cryptofmt::output_prefix(&key).unwrap();
+        let entries = ps.entries_for_prefix(&prefix);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].key_id, *key_id);
+    }
+    assert!(ps.raw_entries().is_empty());
+
+    let typed: tink_core::primitiveset::TypedPrimitiveSet<Box<dyn tink_core::Mac>> = ps.into();
+    assert_eq!(typed.entry_for_key_id(n / 2).unwrap().key_id, n / 2);
+    assert!(typed.entry_for_key_id(n + 1).is_none());
+}
+
 #[test]
 fn test_add_with_invalid_input() {
     let mut ps = tink_core::primitiveset::PrimitiveSet::new();
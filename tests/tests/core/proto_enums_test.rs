@@ -0,0 +1,101 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_core::TinkError;
+use tink_proto::{EcdsaParams, EcdsaSignatureEncoding, EllipticCurveType, HashType, HmacParams};
+
+#[test]
+fn test_try_enum_accessors() {
+    let params = EcdsaParams {
+        hash_type: HashType::Sha384 as i32,
+        curve: EllipticCurveType::NistP384 as i32,
+        encoding: EcdsaSignatureEncoding::Der as i32,
+    };
+    assert_eq!(params.try_hash_type(), Ok(HashType::Sha384));
+    assert_eq!(params.try_curve(), Ok(EllipticCurveType::NistP384));
+    assert_eq!(params.try_encoding(), Ok(EcdsaSignatureEncoding::Der));
+
+    let params = EcdsaParams {
+        hash_type: 9999,
+        ..params
+    };
+    let err = params.try_hash_type().unwrap_err();
+    assert_eq!(err.enum_name, "HashType");
+    assert_eq!(err.value, 9999);
+    assert_eq!(err.to_string(), "unknown HashType value 9999");
+    // The prost-generated accessor silently falls back to the default variant.
+    assert_eq!(params.hash_type(), HashType::UnknownHash);
+}
+
+#[test]
+fn test_unknown_enum_value_into_tink_error() {
+    let params = HmacParams {
+        hash: -1,
+        tag_size: 16,
+    };
+    let result: Result<HashType, TinkError> = params.try_hash().map_err(Into::into);
+    tink_tests::expect_err(result, "unknown HashType value -1");
+}
+
+#[test]
+fn test_key_manager_rejects_unknown_hash() {
+    tink_mac::init();
+    let format = tink_proto::HmacKeyFormat {
+        params: Some(HmacParams {
+            hash: 9999,
+            tag_size: 16,
+        }),
+        key_size: 32,
+        version: 0,
+    };
+    let km = tink_core::registry::get_key_manager(tink_tests::HMAC_TYPE_URL).unwrap();
+    assert!(km.new_key(&tink_tests::proto_encode(&format)).is_err());
+}
+
+#[test]
+fn test_hpke_messages() {
+    use tink_proto::{prost::Message, HpkeAead, HpkeKdf, HpkeKem, HpkeParams, HpkePrivateKey};
+    let key = HpkePrivateKey {
+        version: 0,
+        public_key: Some(tink_proto::HpkePublicKey {
+            version: 0,
+            params: Some(HpkeParams {
+                kem: HpkeKem::DhkemX25519HkdfSha256 as i32,
+                kdf: HpkeKdf::HkdfSha256 as i32,
+                aead: HpkeAead::Chacha20Poly1305 as i32,
+            }),
+            public_key: vec![0x01; 32],
+        }),
+        private_key: vec![0x02; 32],
+    };
+    let decoded = HpkePrivateKey::decode(tink_tests::proto_encode(&key).as_ref()).unwrap();
+    assert_eq!(decoded, key);
+    let params = decoded.public_key.unwrap().params.unwrap();
+    assert_eq!(params.try_kem(), Ok(HpkeKem::DhkemX25519HkdfSha256));
+    assert_eq!(params.try_aead(), Ok(HpkeAead::Chacha20Poly1305));
+}
+
+#[test]
+fn test_jwt_ecdsa_messages() {
+    use tink_proto::{prost::Message, JwtEcdsaAlgorithm, JwtEcdsaKeyFormat};
+    let format = JwtEcdsaKeyFormat {
+        version: 0,
+        algorithm: JwtEcdsaAlgorithm::Es384 as i32,
+    };
+    let decoded = JwtEcdsaKeyFormat::decode(tink_tests::proto_encode(&format).as_ref()).unwrap();
+    assert_eq!(decoded.try_algorithm(), Ok(JwtEcdsaAlgorithm::Es384));
+    assert_eq!(JwtEcdsaAlgorithm::Es384.as_str_name(), "ES384");
+}
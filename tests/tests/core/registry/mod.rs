@@ -37,6 +37,17 @@ fn test_register_key_manager() {
     assert!(km.public_key_data(&[]).is_err());
 }
 
+#[test]
+fn test_key_manager_type_urls() {
+    tink_mac::init();
+    tink_aead::init();
+    let type_urls = tink_core::registry::key_manager_type_urls();
+    assert!(type_urls.contains(&tink_tests::HMAC_TYPE_URL));
+    assert!(type_urls.contains(&tink_tests::AES_GCM_TYPE_URL));
+    assert!(!type_urls.contains(&"some url"));
+    assert!(type_urls.windows(2).all(|w| w[0] < w[1]));
+}
+
 #[test]
 fn test_register_key_manager_with_collision() {
     tink_aead::init();
@@ -61,6 +72,55 @@ fn test_register_key_manager_duplicate() {
     );
 }
 
+#[test]
+fn test_register_key_manager_concurrent() {
+    const TYPE_URLS: &[&str] = &[
+        "concurrent-0",
+        "concurrent-1",
+        "concurrent-2",
+        "concurrent-3",
+        "concurrent-4",
+        "concurrent-5",
+        "concurrent-6",
+        "concurrent-7",
+    ];
+    // Every type URL is registered from two threads at once, while other threads look them up.
+    let mut handles = Vec::new();
+    for type_url in TYPE_URLS.iter().chain(TYPE_URLS.iter()) {
+        let type_url: &'static str = *type_url;
+        handles.push(std::thread::spawn(move || {
+            let km = Arc::new(tink_tests::DummyAeadKeyManager { type_url });
+            tink_core::registry::register_key_manager(km).is_ok()
+        }));
+    }
+    let readers: Vec<_> = (0..4)
+        .map(|_| {
+            std::thread::spawn(|| {
+                for _ in 0..100 {
+                    for type_url in TYPE_URLS {
+                        if let Ok(km) = tink_core::registry::get_key_manager(type_url) {
+                            assert_eq!(km.type_url(), *type_url);
+                        }
+                    }
+                }
+            })
+        })
+        .collect();
+    let successes = handles
+        .into_iter()
+        .map(|h| h.join().unwrap())
+        .filter(|ok| *ok)
+        .count();
+    for reader in readers {
+        reader.join().unwrap();
+    }
+    // Exactly one registration per type URL succeeded, and none were lost.
+    assert_eq!(successes, TYPE_URLS.len());
+    for type_url in TYPE_URLS {
+        assert!(tink_core::registry::get_key_manager(type_url).is_ok());
+    }
+}
+
 #[test]
 fn test_new_key_data() {
     tink_mac::init();
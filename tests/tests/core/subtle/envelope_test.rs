@@ -0,0 +1,121 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_core::cryptofmt;
+
+fn new_aead() -> Box<dyn tink_core::Aead> {
+    tink_aead::init();
+    let kh = tink_core::keyset::Handle::new(&tink_aead::aes256_gcm_key_template()).unwrap();
+    tink_aead::new(&kh).unwrap()
+}
+
+#[test]
+fn test_envelope_encode_decode() {
+    let ct = b"some ciphertext";
+    let encoded = cryptofmt::encode_envelope("AES256_GCM", 7, ct).unwrap();
+    assert!(cryptofmt::is_envelope(&encoded));
+    assert_eq!(
+        encoded.len(),
+        cryptofmt::ENVELOPE_HEADER_SIZE + "AES256_GCM".len() + ct.len()
+    );
+    let envelope = cryptofmt::decode_envelope(&encoded).unwrap();
+    assert_eq!(
+        envelope,
+        cryptofmt::CiphertextEnvelope {
+            version: cryptofmt::ENVELOPE_VERSION,
+            template_hint: "AES256_GCM",
+            aad_len: 7,
+            ciphertext: &ct[..],
+        }
+    );
+
+    // Empty hint and ciphertext are allowed.
+    let encoded = cryptofmt::encode_envelope("", 0, &[]).unwrap();
+    let envelope = cryptofmt::decode_envelope(&encoded).unwrap();
+    assert_eq!(envelope.template_hint, "");
+    assert!(envelope.ciphertext.is_empty());
+}
+
+#[test]
+fn test_envelope_decode_invalid() {
+    let encoded = cryptofmt::encode_envelope("AES256_GCM", 0, b"ct").unwrap();
+
+    // Every truncation of the header is rejected.
+    let header_len = cryptofmt::ENVELOPE_HEADER_SIZE + "AES256_GCM".len();
+    for i in 0..header_len {
+        assert!(
+            cryptofmt::decode_envelope(&encoded[..i]).is_err(),
+            "expected error when truncated to {} bytes",
+            i
+        );
+    }
+
+    let mut bad_magic = encoded.clone();
+    bad_magic[0] ^= 0x01;
+    assert!(!cryptofmt::is_envelope(&bad_magic));
+    tink_tests::expect_err(
+        cryptofmt::decode_envelope(&bad_magic),
+        "missing envelope magic",
+    );
+
+    let mut bad_version = encoded.clone();
+    bad_version[cryptofmt::ENVELOPE_MAGIC.len()] = cryptofmt::ENVELOPE_VERSION + 1;
+    tink_tests::expect_err(
+        cryptofmt::decode_envelope(&bad_version),
+        "unsupported envelope version",
+    );
+
+    let mut bad_hint = encoded;
+    bad_hint[cryptofmt::ENVELOPE_MAGIC.len() + 3] = 0xff;
+    tink_tests::expect_err(
+        cryptofmt::decode_envelope(&bad_hint),
+        "invalid template hint",
+    );
+
+    let long_hint = "x".repeat(usize::from(u16::MAX) + 1);
+    tink_tests::expect_err(
+        cryptofmt::encode_envelope(&long_hint, 0, &[]),
+        "template hint too long",
+    );
+}
+
+#[test]
+fn test_envelope_aead_round_trip() {
+    let a = new_aead();
+    let pt = b"archived data";
+    let aad = b"context";
+    let encoded = cryptofmt::encrypt_with_envelope(&*a, "AES256_GCM", pt, aad).unwrap();
+    let decrypted = cryptofmt::decrypt_envelope(&*a, &encoded, aad).unwrap();
+    assert_eq!(decrypted, pt);
+
+    // The wrapped ciphertext is standard Tink output.
+    let envelope = cryptofmt::decode_envelope(&encoded).unwrap();
+    assert_eq!(envelope.aad_len as usize, aad.len());
+    assert_eq!(a.decrypt(envelope.ciphertext, aad).unwrap(), pt);
+
+    tink_tests::expect_err(
+        cryptofmt::decrypt_envelope(&*a, &encoded, b"other"),
+        "associated data length mismatch",
+    );
+    assert!(cryptofmt::decrypt_envelope(&*a, &encoded, b"CONTEXT").is_err());
+}
+
+#[test]
+fn test_envelope_not_produced_by_default() {
+    let a = new_aead();
+    let ct = a.encrypt(b"data", b"").unwrap();
+    assert!(!cryptofmt::is_envelope(&ct));
+}
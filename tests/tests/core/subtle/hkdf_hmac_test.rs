@@ -14,7 +14,9 @@
 //
 ////////////////////////////////////////////////////////////////////////////////
 
-use tink_core::subtle::{compute_hkdf, random::get_random_bytes};
+use tink_core::subtle::{
+    compute_hkdf, hkdf_expand, hkdf_extract, hkdf_max_output_size, random::get_random_bytes,
+};
 use tink_proto::HashType;
 
 // Tests the implementation against the test vectors from RFC 5869.
@@ -179,3 +181,79 @@ fn test_new_hmac_with_invalid_input() {
         panic!("expect an error when hash algorithm is invalid");
     }
 }
+
+// Test case 1 from RFC 5869, exercising the extract and expand steps separately.
+#[test]
+fn test_hkdf_extract_expand() {
+    let ikm = hex::decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap();
+    let salt = hex::decode("000102030405060708090a0b0c").unwrap();
+    let info = hex::decode("f0f1f2f3f4f5f6f7f8f9").unwrap();
+
+    let prk = hkdf_extract(HashType::Sha256, &ikm, &salt).unwrap();
+    assert_eq!(
+        hex::encode(&prk),
+        "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5"
+    );
+    let okm = hkdf_expand(HashType::Sha256, &prk, &info, 42).unwrap();
+    assert_eq!(
+        hex::encode(&okm),
+        "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"
+    );
+}
+
+#[test]
+fn test_hkdf_extract_expand_matches_compute_hkdf() {
+    let hashes = vec![
+        HashType::Sha1,
+        HashType::Sha224,
+        HashType::Sha256,
+        HashType::Sha384,
+        HashType::Sha512,
+    ];
+    for hash in hashes {
+        let ikm = get_random_bytes(32);
+        let salt = get_random_bytes(16);
+        let info = get_random_bytes(8);
+        let prk = hkdf_extract(hash, &ikm, &salt).unwrap();
+        assert_eq!(
+            prk.len(),
+            tink_core::subtle::get_hash_digest_size(hash).unwrap()
+        );
+        let max = hkdf_max_output_size(hash).unwrap();
+        for size in &[10, 64, max] {
+            let okm = hkdf_expand(hash, &prk, &info, *size).unwrap();
+            assert_eq!(okm.len(), *size);
+            assert_eq!(
+                okm,
+                compute_hkdf(hash, &ikm, &salt, &info, *size).unwrap(),
+                "mismatch for {:?} with output size {}",
+                hash,
+                size
+            );
+        }
+    }
+}
+
+#[test]
+fn test_hkdf_expand_with_invalid_input() {
+    let prk = hkdf_extract(HashType::Sha256, &get_random_bytes(16), &[]).unwrap();
+    let max = hkdf_max_output_size(HashType::Sha256).unwrap();
+    assert_eq!(max, 255 * 32);
+    assert!(hkdf_expand(HashType::Sha256, &prk, &[], max).is_ok());
+    tink_tests::expect_err(
+        hkdf_expand(HashType::Sha256, &prk, &[], max + 1),
+        "output size too big",
+    );
+    tink_tests::expect_err(
+        hkdf_expand(HashType::Sha256, &prk[..31], &[], 32),
+        "pseudorandom key too short",
+    );
+    tink_tests::expect_err(
+        hkdf_expand(HashType::UnknownHash, &prk, &[], 32),
+        "invalid hash algorithm",
+    );
+    tink_tests::expect_err(
+        hkdf_extract(HashType::UnknownHash, &prk, &[]),
+        "unsupported hash",
+    );
+}
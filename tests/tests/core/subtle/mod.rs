@@ -18,6 +18,7 @@ use tink_core::subtle;
 use tink_proto::HashType;
 
 mod cryptofmt_test;
+mod envelope_test;
 mod hkdf_hmac_test;
 mod random_test;
 
@@ -30,3 +30,115 @@ fn test_random_uint() {
     let v2 = random::get_random_uint32();
     assert_ne!(v1, v2, "Just unlucky?");
 }
+
+#[test]
+fn test_with_test_rng() {
+    let a = random::with_test_rng(1, || random::get_random_bytes(32));
+    let b = random::with_test_rng(1, || random::get_random_bytes(32));
+    let c = random::with_test_rng(2, || random::get_random_bytes(32));
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+
+    // Normal randomness is restored afterwards.
+    assert_ne!(random::get_random_bytes(32), a);
+
+    // Nested overrides restore the outer generator's state.
+    let (outer, inner) = random::with_test_rng(1, || {
+        let first = random::get_random_bytes(16);
+        let inner = random::with_test_rng(2, || random::get_random_bytes(32));
+        let second = random::get_random_bytes(16);
+        ([first, second].concat(), inner)
+    });
+    assert_eq!(outer, a);
+    assert_eq!(inner, c);
+}
+
+#[test]
+fn test_with_test_rng_restored_on_panic() {
+    let result = std::panic::catch_unwind(|| {
+        random::with_test_rng(1, || panic!("failed"));
+    });
+    assert!(result.is_err());
+    assert_ne!(
+        random::get_random_bytes(32),
+        random::with_test_rng(1, || random::get_random_bytes(32))
+    );
+}
+
+#[test]
+fn test_with_test_rng_other_threads() {
+    let (local, remote) = random::with_test_rng(1, || {
+        let remote = std::thread::spawn(|| random::get_random_bytes(32))
+            .join()
+            .unwrap();
+        (random::get_random_bytes(32), remote)
+    });
+    assert_ne!(local, remote);
+}
+
+#[test]
+fn test_tink_rng() {
+    use rand::RngCore;
+    let mut rng = random::TinkRng;
+    let mut a = [0u8; 32];
+    rng.fill_bytes(&mut a);
+    let b = random::with_test_rng(1, || {
+        let mut b = [0u8; 32];
+        random::TinkRng.fill_bytes(&mut b);
+        b
+    });
+    assert_ne!(a, b);
+    assert_eq!(
+        b.to_vec(),
+        random::with_test_rng(1, || random::get_random_bytes(32))
+    );
+}
+
+#[test]
+fn test_deterministic_key_generation() {
+    tink_aead::init();
+    tink_hybrid::init();
+    tink_signature::init();
+    let templates = vec![
+        ("AES128_GCM", tink_aead::aes128_gcm_key_template()),
+        ("ECDSA_P256", tink_signature::ecdsa_p256_key_template()),
+        ("ED25519", tink_signature::ed25519_key_template()),
+        (
+            "ECIES_P256",
+            tink_hybrid::ecies_hkdf_aes128_gcm_key_template(),
+        ),
+    ];
+    for (name, template) in templates {
+        let generate = || {
+            random::with_test_rng(42, || {
+                let mut ksm = tink_core::keyset::Manager::new();
+                ksm.rotate(&template).unwrap();
+                ksm.rotate(&template).unwrap();
+                tink_core::keyset::insecure::keyset_material(&ksm.handle().unwrap())
+            })
+        };
+        let ks1 = generate();
+        let ks2 = generate();
+        assert_eq!(ks1, ks2, "{}", name);
+        assert_eq!(ks1.key.len(), 2, "{}", name);
+        assert_ne!(ks1.key[0].key_data, ks1.key[1].key_data, "{}", name);
+
+        let ks3 = tink_core::keyset::insecure::keyset_material(
+            &tink_core::keyset::Handle::new(&template).unwrap(),
+        );
+        assert_ne!(ks1.key[0].key_data, ks3.key[0].key_data, "{}", name);
+    }
+}
+
+#[test]
+fn test_deterministic_ciphertext() {
+    tink_aead::init();
+    let encrypt = || {
+        random::with_test_rng(7, || {
+            let kh = tink_core::keyset::Handle::new(&tink_aead::aes256_gcm_key_template()).unwrap();
+            let a = tink_aead::new(&kh).unwrap();
+            a.encrypt(b"plaintext", b"aad").unwrap()
+        })
+    };
+    assert_eq!(encrypt(), encrypt());
+}
@@ -0,0 +1,128 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use std::sync::{Arc, Mutex};
+use tink_core::keyset::{insecure, Handle, MemReaderWriter};
+
+/// `MakeWriter` that captures all formatted log output in a shared buffer.
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for Capture {
+    type Writer = Capture;
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+/// Run `f` with a subscriber that captures all `tink` trace output, and return that output.
+fn capture_logs<F: FnOnce()>(f: F) -> String {
+    let capture = Capture::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::TRACE)
+        .with_ansi(false)
+        .with_writer(capture.clone())
+        .finish();
+    tracing::subscriber::with_default(subscriber, f);
+    let buf = capture.0.lock().unwrap().clone();
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn test_trace_aead_operations() {
+    tink_aead::init();
+    let kh = Handle::new(&tink_aead::aes128_gcm_key_template()).unwrap();
+    let key_id = kh.keyset_info().primary_key_id;
+    let a = tink_aead::new(&kh).unwrap();
+    let plaintext = b"TOP-SECRET-PLAINTEXT";
+    let aad = b"SECRET-ASSOCIATED-DATA";
+
+    let logs = capture_logs(|| {
+        let ct = a.encrypt(plaintext, aad).unwrap();
+        assert_eq!(a.decrypt(&ct, aad).unwrap(), plaintext);
+        tink_tests::expect_err(a.decrypt(&ct, b"wrong aad"), "decryption failed");
+    });
+
+    assert!(logs.contains("operation succeeded"), "logs: {}", logs);
+    assert!(logs.contains("operation failed"), "logs: {}", logs);
+    assert!(logs.contains("no key could decrypt"), "logs: {}", logs);
+    assert!(
+        logs.contains(&format!("key_id={}", key_id)),
+        "logs: {}",
+        logs
+    );
+    assert!(!logs.contains("TOP-SECRET"), "plaintext leaked: {}", logs);
+    assert!(!logs.contains("SECRET-ASSOCIATED"), "aad leaked: {}", logs);
+}
+
+#[test]
+fn test_trace_keyset_io_has_no_key_material() {
+    tink_mac::init();
+    let kh = Handle::new(&tink_mac::hmac_sha256_tag256_key_template()).unwrap();
+    let ks = insecure::keyset_material(&kh);
+    let key_value = ks.key[0].key_data.as_ref().unwrap().value.clone();
+    let type_url = ks.key[0].key_data.as_ref().unwrap().type_url.clone();
+
+    let logs = capture_logs(|| {
+        let mut mem = MemReaderWriter::default();
+        insecure::write(&kh, &mut mem).unwrap();
+        let kh2 = insecure::read(&mut mem).unwrap();
+        let m = tink_mac::new(&kh2).unwrap();
+        let tag = m.compute_mac(b"data").unwrap();
+        assert!(m.verify_mac(&tag, b"data").is_ok());
+    });
+
+    assert!(logs.contains("keyset I/O succeeded"), "logs: {}", logs);
+    assert!(logs.contains(&type_url), "logs: {}", logs);
+    assert!(logs.contains("primitive created"), "logs: {}", logs);
+    assert!(
+        !logs.contains(&hex::encode(&key_value)),
+        "key leaked: {}",
+        logs
+    );
+    assert!(
+        !logs.contains(&format!("{:?}", key_value)),
+        "key leaked: {}",
+        logs
+    );
+}
+
+#[test]
+fn test_trace_nothing_logged_without_subscriber_interest() {
+    tink_aead::init();
+    let kh = Handle::new(&tink_aead::aes128_gcm_key_template()).unwrap();
+    let a = tink_aead::new(&kh).unwrap();
+    let capture = Capture::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::ERROR)
+        .with_writer(capture.clone())
+        .finish();
+    tracing::subscriber::with_default(subscriber, || {
+        let ct = a.encrypt(b"pt", b"").unwrap();
+        assert!(a.decrypt(&ct, b"x").is_err());
+    });
+    assert!(capture.0.lock().unwrap().is_empty());
+}
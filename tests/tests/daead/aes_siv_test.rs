@@ -92,6 +92,36 @@ fn test_aes_siv_key_sizes() {
     }
 }
 
+#[test]
+fn test_aes_siv_compat_key_sizes() {
+    let key = get_random_bytes(128);
+    for i in 0..key.len() {
+        let result = tink_daead::subtle::AesSiv::new_compat(&key[..i]);
+        if tink_daead::subtle::AES_SIV_COMPAT_KEY_SIZES.contains(&i) {
+            let a = result.unwrap();
+            let ct = a.encrypt_deterministically(b"data", b"aad").unwrap();
+            assert_eq!(a.decrypt_deterministically(&ct, b"aad").unwrap(), b"data");
+        } else {
+            assert!(result.is_err(), "Allowed invalid key size: {}", i);
+        }
+    }
+}
+
+#[test]
+fn test_aes_siv_compat_rfc5297_vector() {
+    // RFC 5297 appendix A.1 (deterministic authenticated encryption with a 32-byte key).
+    let key =
+        hex::decode("fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff").unwrap();
+    let aad = hex::decode("101112131415161718191a1b1c1d1e1f2021222324252627").unwrap();
+    let pt = hex::decode("112233445566778899aabbccddee").unwrap();
+    let want = hex::decode("85632d07c6e8f37f950acd320a2ecc9340c02b9690c4dc04daef7f6afe5c").unwrap();
+
+    tink_tests::expect_err(tink_daead::subtle::AesSiv::new(&key), "invalid key size");
+    let a = tink_daead::subtle::AesSiv::new_compat(&key).unwrap();
+    assert_eq!(a.encrypt_deterministically(&pt, &aad).unwrap(), want);
+    assert_eq!(a.decrypt_deterministically(&want, &aad).unwrap(), pt);
+}
+
 #[test]
 fn test_aes_siv_message_sizes() {
     let key_str =
@@ -220,13 +250,28 @@ struct TestCase {
 
 #[test]
 fn test_aes_siv_wycheproof_vectors() {
+    wycheproof_test(false);
+}
+
+#[test]
+fn test_aes_siv_compat_wycheproof_vectors() {
+    wycheproof_test(true);
+}
+
+fn wycheproof_test(compat: bool) {
     let filename = "testvectors/aes_siv_cmac_test.json";
     println!("wycheproof file '{}'", filename);
     let bytes = tink_tests::wycheproof_data(filename);
     let data: TestData = serde_json::from_slice(&bytes).unwrap();
 
     for g in &data.test_groups {
-        if (g.key_size / 8) as usize != tink_daead::subtle::AES_SIV_KEY_SIZE {
+        let key_size = (g.key_size / 8) as usize;
+        let supported = if compat {
+            tink_daead::subtle::AES_SIV_COMPAT_KEY_SIZES.contains(&key_size)
+        } else {
+            key_size == tink_daead::subtle::AES_SIV_KEY_SIZE
+        };
+        if !supported {
             println!("   skipping tests for key_size={}", g.key_size);
             continue;
         }
@@ -236,7 +281,12 @@ fn test_aes_siv_wycheproof_vectors() {
                 "     case {} [{}] {}",
                 tc.case.case_id, tc.case.result, tc.case.comment
             );
-            let a = tink_daead::subtle::AesSiv::new(&tc.key).expect("AesSiv::new() failed");
+            let a = if compat {
+                tink_daead::subtle::AesSiv::new_compat(&tc.key)
+            } else {
+                tink_daead::subtle::AesSiv::new(&tc.key)
+            }
+            .expect("AesSiv::new() failed");
 
             // EncryptDeterministically should always succeed since msg and aad are valid inputs.
             let got_ct = a
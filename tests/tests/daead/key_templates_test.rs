@@ -45,3 +45,24 @@ fn test_encrypt_decrypt(template: &tink_proto::KeyTemplate) -> Result<(), TinkEr
     assert_eq!(&plaintext[..], decrypted);
     Ok(())
 }
+
+#[test]
+fn test_aes_siv_template_builder() {
+    tink_daead::init();
+    assert_eq!(
+        tink_daead::aes_siv_key_template(),
+        tink_daead::AesSivTemplateBuilder::new().build().unwrap()
+    );
+    let template = tink_daead::AesSivTemplateBuilder::new()
+        .prefix(tink_proto::OutputPrefixType::Raw)
+        .build()
+        .unwrap();
+    assert!(test_encrypt_decrypt(&template).is_ok());
+
+    tink_tests::expect_err(
+        tink_daead::AesSivTemplateBuilder::new()
+            .key_size(32)
+            .build(),
+        "key_size != 64",
+    );
+}
@@ -0,0 +1,82 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_daead::{unwrap_key, wrap_key};
+
+fn new_kek() -> Box<dyn tink_core::DeterministicAead> {
+    tink_daead::init();
+    let kh = tink_core::keyset::Handle::new(&tink_daead::aes_siv_key_template()).unwrap();
+    tink_daead::new(&kh).unwrap()
+}
+
+fn new_dek() -> tink_proto::KeyData {
+    tink_aead::init();
+    tink_core::registry::new_key_data(&tink_aead::aes256_gcm_key_template()).unwrap()
+}
+
+#[test]
+fn test_wrap_unwrap_key() {
+    let kek = new_kek();
+    let dek = new_dek();
+    let wrapped = wrap_key(&*kek, &dek).unwrap();
+    let unwrapped = unwrap_key(&*kek, &wrapped, tink_tests::AES_GCM_TYPE_URL).unwrap();
+    assert_eq!(unwrapped, dek);
+
+    // Wrapping is deterministic.
+    assert_eq!(wrap_key(&*kek, &dek).unwrap(), wrapped);
+}
+
+#[test]
+fn test_unwrap_key_with_wrong_type_url() {
+    let kek = new_kek();
+    let wrapped = wrap_key(&*kek, &new_dek()).unwrap();
+    tink_tests::expect_err(
+        unwrap_key(&*kek, &wrapped, tink_tests::AES_GCM_SIV_TYPE_URL),
+        "failed to unwrap key",
+    );
+}
+
+#[test]
+fn test_unwrap_key_with_wrong_kek() {
+    let wrapped = wrap_key(&*new_kek(), &new_dek()).unwrap();
+    tink_tests::expect_err(
+        unwrap_key(&*new_kek(), &wrapped, tink_tests::AES_GCM_TYPE_URL),
+        "failed to unwrap key",
+    );
+}
+
+#[test]
+fn test_unwrap_key_with_modified_input() {
+    let kek = new_kek();
+    let wrapped = wrap_key(&*kek, &new_dek()).unwrap();
+    for i in 0..wrapped.len() {
+        let mut modified = wrapped.clone();
+        modified[i] ^= 0x01;
+        assert!(
+            unwrap_key(&*kek, &modified, tink_tests::AES_GCM_TYPE_URL).is_err(),
+            "expected error when modifying byte {}",
+            i
+        );
+    }
+}
+
+#[test]
+fn test_wrap_key_without_type_url() {
+    let kek = new_kek();
+    let mut dek = new_dek();
+    dek.type_url = String::new();
+    tink_tests::expect_err(wrap_key(&*kek, &dek), "missing type URL");
+}
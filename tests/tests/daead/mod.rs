@@ -17,5 +17,7 @@
 mod aes_siv_key_manager_test;
 mod aes_siv_test;
 mod factory_test;
+mod fixtures_test;
 mod integration_test;
 mod key_templates_test;
+mod key_wrap_test;
@@ -69,3 +69,118 @@ fn test_get_aead_fails_with_bad_keyset_encoding() {
     let client = FakeClient::new("fake-kms://bad").unwrap();
     assert!(client.get_aead("fake-kms://badencoding").is_err());
 }
+
+#[test]
+fn test_get_aead_with_context() {
+    tink_aead::init();
+    let client = FakeClient::new(KEY_URI).unwrap();
+    let mut context = std::collections::BTreeMap::new();
+    context.insert("purpose".to_string(), "test".to_string());
+    let primitive = client.get_aead_with_context(KEY_URI, &context).unwrap();
+
+    let plaintext = b"some data to encrypt";
+    let ciphertext = primitive.encrypt(&plaintext[..], &[]).unwrap();
+    let decrypted = primitive.decrypt(&ciphertext, &[]).unwrap();
+    assert_eq!(&plaintext[..], decrypted);
+    assert!(primitive.encrypt(&plaintext[..], b"aad").is_err());
+
+    // The context is passed to the KMS as length-prefixed associated data.
+    let context_aad = b"\x00\x00\x00\x07purpose\x00\x00\x00\x04test";
+    let aead = client.get_aead(KEY_URI).unwrap();
+    assert_eq!(
+        &plaintext[..],
+        aead.decrypt(&ciphertext, &context_aad[..]).unwrap()
+    );
+
+    // A different context gives a primitive that cannot decrypt.
+    context.insert("purpose".to_string(), "other".to_string());
+    let other = client.get_aead_with_context(KEY_URI, &context).unwrap();
+    assert!(other.decrypt(&ciphertext, &[]).is_err());
+}
+
+#[test]
+fn test_capabilities() {
+    tink_aead::init();
+    tink_signature::init();
+    let client = FakeClient::new(KEY_URI).unwrap();
+    assert_eq!(
+        client.capabilities(KEY_URI),
+        tink_core::registry::KmsCapabilities {
+            associated_data: true,
+            generate_data_key: true,
+            sign: false,
+        }
+    );
+
+    let signer_uri =
+        fakekms::new_key_uri_with_template(&tink_signature::ecdsa_p256_key_template()).unwrap();
+    let client = FakeClient::new(&signer_uri).unwrap();
+    assert_eq!(
+        client.capabilities(&signer_uri),
+        tink_core::registry::KmsCapabilities {
+            associated_data: true,
+            generate_data_key: false,
+            sign: true,
+        }
+    );
+
+    let client = FakeClient::new("fake-kms://bad").unwrap();
+    assert!(!client.capabilities("fake-kms://badencoding").sign);
+}
+
+#[test]
+fn test_default_capabilities() {
+    let client = tink_tests::DummyKmsClient;
+    assert_eq!(
+        client.capabilities("dummy"),
+        tink_core::registry::KmsCapabilities::default()
+    );
+    tink_tests::expect_err(
+        client.generate_data_key("dummy", 16),
+        "does not support data key generation",
+    );
+    tink_tests::expect_err(client.get_signer("dummy"), "does not support signing");
+    tink_tests::expect_err(
+        client.get_public_key_data("dummy"),
+        "does not support signing",
+    );
+}
+
+#[test]
+fn test_generate_data_key() {
+    tink_aead::init();
+    let client = FakeClient::new(KEY_URI).unwrap();
+    let data_key = client.generate_data_key(KEY_URI, 32).unwrap();
+    assert_eq!(data_key.plaintext.len(), 32);
+    let decrypted = client
+        .get_aead(KEY_URI)
+        .unwrap()
+        .decrypt(&data_key.ciphertext, &[])
+        .unwrap();
+    assert_eq!(decrypted, data_key.plaintext);
+    assert!(!format!("{:?}", data_key).contains("plaintext"));
+
+    let other = client.generate_data_key(KEY_URI, 32).unwrap();
+    assert_ne!(other.plaintext, data_key.plaintext);
+}
+
+#[test]
+fn test_get_signer() {
+    tink_signature::init();
+    let signer_uri =
+        fakekms::new_key_uri_with_template(&tink_signature::ed25519_key_template()).unwrap();
+    let client = FakeClient::new(&signer_uri).unwrap();
+    let signer = client.get_signer(&signer_uri).unwrap();
+    let public_key_data = client.get_public_key_data(&signer_uri).unwrap();
+    assert_eq!(
+        public_key_data.type_url,
+        tink_signature::ED25519_VERIFIER_TYPE_URL
+    );
+    let verifier = tink_signature::unwrapped_verifier_from_key_data(&public_key_data).unwrap();
+    let signature = signer.sign(b"data").unwrap();
+    assert!(verifier.verify(&signature, b"data").is_ok());
+
+    // AEAD keys cannot sign.
+    let client = FakeClient::new(KEY_URI).unwrap();
+    assert!(client.get_signer(KEY_URI).is_err());
+}
@@ -0,0 +1,287 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+// Fixtures generated by an independent implementation of the age v1 format, using the X25519
+// private keys 0x01..0x20 and 0x65..0x84.
+
+use std::io::{Read, Write};
+use tink_core::{keyset::Handle, HybridDecrypt, HybridEncrypt};
+use tink_hybrid::{
+    age::{
+        age_identities, age_recipients, import_age_identity, import_age_recipient,
+        new_multi_recipient_encrypt,
+    },
+    subtle::{AgeDecrypt, AgeEncrypt, AgeIdentity, AgeRecipient},
+};
+use tink_tests::expect_err;
+
+const IDENTITY: &str = "AGE-SECRET-KEY-1QYPQXPQ9QCRSSZG2PVXQ6RS0ZQG3YYC5Z5TPWXQERGD3C8G7RUSQGPQYEE";
+const RECIPIENT: &str = "age1q73he0q5yzfu3d64msd3p6rvksnrwjk3d2598mgtmlqt9wrdr37q2vrn72";
+const IDENTITY2: &str =
+    "AGE-SECRET-KEY-1V4NXW6RFDF4KCMTWDAC8ZUNNW36HVAMC09A8KLRA0ELCPQVZSWZQA3Y2A3";
+const RECIPIENT2: &str = "age12u28d8g3d0mkgd4wwj78j0fvxzk3jq79ntzjwwq9cl3xnz6ppsmq4eu6rs";
+const PLAINTEXT: &[u8] = b"age fixture for tink-rust";
+/// Encrypted to `RECIPIENT`.
+const X25519_CT: &str = "6167652d656e6372797074696f6e2e6f72672f76310a2d3e20583235353139207274514d7441503047506d7037593830685750466a66616c7a4e6861733656326c4d5469314832794231670a7967794546504d374570666c364e635665382b55414d304d34626e2f356a5073733147374a4b44372f2b6b0a2d2d2d20776c7855766b2b4a305452564873506e59734362374f7a584279455868544a7275506b6736586f384a75730a1eb04c25173c52424861dc9d9b3e058579e83a4467aa842ca124c8fb1104d56aa6f66ef33e08d7d88824642065d681c23ac3c3ba9c6a428d41";
+/// Encrypted to an unknown stanza type, `RECIPIENT2` and `RECIPIENT`.
+const MULTI_CT: &str = "6167652d656e6372797074696f6e2e6f72672f76310a2d3e20756e6b6e6f776e2d67726561736520610a393958514a68776f34426e476875386a48364e684f2b61787271727452625668425368515651653679436933647a6d59787a3368716a487750773778513344660a4e486f5452492f4d305170516c5265560a2d3e20583235353139205a7a4f4b4756366652615330552b424f677853496f795470334d54716c745a5a31673539793658733648450a4e5a78572b614f494f35467366656f536475765443706d6531536738392f4a376e3061314a5433693954770a2d3e205832353531392046446c767856466d7277684d315a6d2b4c354b7253796a6834665a5276417148724b6c56684a485a7469730a3549476c524330306978354f513968616c4a59626e70774f7458385648415178425a6a62767a6e70486f730a2d2d2d206a653450384c496353326d70446a706e6171736e38536867494b417631524c67655a2b45463042704a59490ae8a366f6f878d7f60293cb5d7630a745186b7fa244efe03f40c45915061ec795fa1538e8ed60b48b0e34e94ecf32fb1e696c9cb17ce623ffa9";
+/// Encrypted to `PASSPHRASE` with a scrypt work factor of 10.
+const SCRYPT_CT: &str = "6167652d656e6372797074696f6e2e6f72672f76310a2d3e20736372797074204f344266615638772b6b44464a5049485a78585769412031300a326c416d634c384a42696c79466b4c432f4852316162653766315056787834364c3950702f34386f76706b0a2d2d2d20302b524131646b7a434f71647a3754456e58666b7134716d555757634a6b6667727859734c627a7672526f0a51695edd2b4f78ae1bab63b97350c94914e92e87061ababcc410fc3beeec42ff859c536a571591d15659d8bf2b90607000d4dddd398ec657f2";
+const PASSPHRASE: &[u8] = b"correct horse battery staple";
+/// Empty plaintext encrypted to `RECIPIENT`.
+const EMPTY_CT: &str = "6167652d656e6372797074696f6e2e6f72672f76310a2d3e205832353531392041436b4e4f3772646f4b4d746764636333306175784551314b46395574766e646a6d552f44526a5437534d0a35394963396d31636c696d37704e62692b32446a654f484b337169642b374932367548795563674b316e510a2d2d2d20314d532f4361792f2b694743486c7353485a3863796e686444577157716948674366542f6e76646a3551450ae3df804e8bb77a9b42edfc37f03b4bcd053a5236d1b5ad100236bcf80e6a9700";
+
+fn decrypt(identity: &str, ct: &str) -> Result<Vec<u8>, tink_core::TinkError> {
+    let h = import_age_identity(identity).unwrap();
+    let d = tink_hybrid::new_decrypt(&h).unwrap();
+    d.decrypt(&hex::decode(ct).unwrap(), b"")
+}
+
+#[test]
+fn test_age_rfc7748_key() {
+    tink_hybrid::init();
+    // Alice's key pair from RFC 7748 section 6.1.
+    let h = import_age_identity(
+        "AGE-SECRET-KEY-1WURK6ZNNRZJH60QKC9E9RVNXGH05CTU8A0QFJ243WLA628DE9S4QRFH26J",
+    )
+    .unwrap();
+    assert_eq!(
+        age_recipients(&h).unwrap(),
+        vec!["age1s5s0qzvfxzn4gayt0hwtg0hhtgxm7wsdycup4a8t5j5ca25mfe4qt4hs7q"]
+    );
+}
+
+#[test]
+fn test_age_import_export() {
+    tink_hybrid::init();
+    let h = import_age_identity(&format!(
+        "# created: today\n# public key: {}\n{}\n",
+        RECIPIENT, IDENTITY
+    ))
+    .unwrap();
+    assert_eq!(age_recipients(&h).unwrap(), vec![RECIPIENT]);
+    assert_eq!(
+        age_recipients(&h.public().unwrap()).unwrap(),
+        vec![RECIPIENT]
+    );
+    assert_eq!(age_identities(&h).unwrap(), vec![IDENTITY]);
+    // Identities are accepted in lower case too.
+    let h = import_age_identity(&IDENTITY.to_lowercase()).unwrap();
+    assert_eq!(age_identities(&h).unwrap(), vec![IDENTITY]);
+
+    let pub_h = import_age_recipient(RECIPIENT).unwrap();
+    assert_eq!(age_recipients(&pub_h).unwrap(), vec![RECIPIENT]);
+}
+
+#[test]
+fn test_age_import_invalid() {
+    tink_hybrid::init();
+    expect_err(
+        import_age_recipient(&IDENTITY.to_lowercase()),
+        "not an age recipient",
+    );
+    expect_err(import_age_identity(RECIPIENT), "not an age identity");
+    expect_err(
+        import_age_identity(&format!("{}\n{}\n", IDENTITY, IDENTITY2)),
+        "single identity",
+    );
+    expect_err(
+        import_age_identity("# just a comment\n"),
+        "no identity found",
+    );
+
+    let mut bad_checksum = RECIPIENT.to_string();
+    let last = if bad_checksum.ends_with('q') {
+        "p"
+    } else {
+        "q"
+    };
+    bad_checksum.replace_range(bad_checksum.len() - 1.., last);
+    expect_err(import_age_recipient(&bad_checksum), "invalid checksum");
+
+    let mixed_case = format!("AGE{}", &RECIPIENT[3..]);
+    expect_err(import_age_recipient(&mixed_case), "mixed case");
+
+    let ecies = Handle::new(&tink_hybrid::ecies_hkdf_aes128_gcm_key_template()).unwrap();
+    expect_err(
+        age_recipients(&ecies.public().unwrap()),
+        "unsupported key type",
+    );
+}
+
+#[test]
+fn test_age_decrypt_fixtures() {
+    tink_hybrid::init();
+    assert_eq!(decrypt(IDENTITY, X25519_CT).unwrap(), PLAINTEXT);
+    assert_eq!(decrypt(IDENTITY, MULTI_CT).unwrap(), PLAINTEXT);
+    assert_eq!(decrypt(IDENTITY2, MULTI_CT).unwrap(), PLAINTEXT);
+    assert!(decrypt(IDENTITY, EMPTY_CT).unwrap().is_empty());
+    expect_err(decrypt(IDENTITY2, X25519_CT), "decryption failed");
+
+    let d = AgeDecrypt::with_passphrase(PASSPHRASE);
+    assert_eq!(
+        d.decrypt(&hex::decode(SCRYPT_CT).unwrap(), b"").unwrap(),
+        PLAINTEXT
+    );
+    let d = AgeDecrypt::with_passphrase(b"wrong passphrase");
+    expect_err(
+        d.decrypt(&hex::decode(SCRYPT_CT).unwrap(), b""),
+        "no identity matches",
+    );
+    let d = AgeDecrypt::new(vec![AgeIdentity::Scrypt {
+        passphrase: PASSPHRASE.to_vec(),
+        max_log_n: 9,
+    }])
+    .unwrap();
+    expect_err(
+        d.decrypt(&hex::decode(SCRYPT_CT).unwrap(), b""),
+        "work factor too large",
+    );
+}
+
+#[test]
+fn test_age_decrypt_tampered() {
+    tink_hybrid::init();
+    let h = import_age_identity(IDENTITY).unwrap();
+    let d = tink_hybrid::new_decrypt(&h).unwrap();
+    let ct = hex::decode(X25519_CT).unwrap();
+    let header_len = ct.windows(4).position(|w| w == b"\n---").unwrap();
+
+    // Changing the version line, a stanza argument, or the payload all cause failure.
+    let mut bad = ct.clone();
+    bad[0] = b'A';
+    expect_err(d.decrypt(&bad, b""), "decryption failed");
+    let mut bad = ct.clone();
+    bad[header_len - 1] ^= 0x01;
+    expect_err(d.decrypt(&bad, b""), "decryption failed");
+    let mut bad = ct.clone();
+    let n = bad.len();
+    bad[n - 1] ^= 0x01;
+    expect_err(d.decrypt(&bad, b""), "decryption failed");
+    expect_err(d.decrypt(&ct[..ct.len() - 1], b""), "decryption failed");
+    expect_err(d.decrypt(&ct[..header_len], b""), "decryption failed");
+}
+
+#[test]
+fn test_age_keyset_round_trip() {
+    tink_hybrid::init();
+    let priv_h = Handle::new(&tink_hybrid::age_x25519_key_template()).unwrap();
+    let pub_h = priv_h.public().unwrap();
+    let e = tink_hybrid::new_encrypt(&pub_h).unwrap();
+    let d = tink_hybrid::new_decrypt(&priv_h).unwrap();
+
+    for size in &[0, 1, 64 * 1024 - 1, 64 * 1024, 64 * 1024 + 1, 200 * 1024] {
+        let pt = vec![0x42; *size];
+        let ct = e.encrypt(&pt, b"").unwrap();
+        assert!(ct.starts_with(b"age-encryption.org/v1\n"));
+        assert_eq!(d.decrypt(&ct, b"").unwrap(), pt, "size {}", size);
+    }
+    expect_err(
+        e.encrypt(PLAINTEXT, b"context"),
+        "context_info not supported",
+    );
+    let ct = e.encrypt(PLAINTEXT, b"").unwrap();
+    expect_err(d.decrypt(&ct, b"context"), "decryption failed");
+
+    // Keys generated by Tink can be exported for use with the age tool.
+    let identity = &age_identities(&priv_h).unwrap()[0];
+    assert_eq!(decrypt_str(identity, &ct), PLAINTEXT);
+}
+
+fn decrypt_str(identity: &str, ct: &[u8]) -> Vec<u8> {
+    decrypt(identity, &hex::encode(ct)).unwrap()
+}
+
+#[test]
+fn test_age_streaming_round_trip() {
+    tink_hybrid::init();
+    let priv_h = Handle::new(&tink_hybrid::age_x25519_key_template()).unwrap();
+    let e = tink_hybrid::new_encrypt(&priv_h.public().unwrap()).unwrap();
+    let d = tink_hybrid::new_decrypt(&priv_h).unwrap();
+
+    for size in &[0, 64 * 1024, 150 * 1024] {
+        let pt: Vec<u8> = (0..*size).map(|i| i as u8).collect();
+        let buf = tink_tests::SharedBuf::new();
+        let mut w = e.new_encrypting_writer(Box::new(buf.clone()), b"").unwrap();
+        for chunk in pt.chunks(1000) {
+            w.write_all(chunk).unwrap();
+        }
+        w.close().unwrap();
+        let ct = buf.contents();
+
+        // Streamed and one-shot ciphertexts are interchangeable.
+        assert_eq!(d.decrypt(&ct, b"").unwrap(), pt, "size {}", size);
+        let mut r = d
+            .new_decrypting_reader(Box::new(std::io::Cursor::new(ct)), b"")
+            .unwrap();
+        let mut got = Vec::new();
+        r.read_to_end(&mut got).unwrap();
+        assert_eq!(got, pt, "size {}", size);
+
+        let ct = e.encrypt(&pt, b"").unwrap();
+        let mut r = d
+            .new_decrypting_reader(Box::new(std::io::Cursor::new(ct)), b"")
+            .unwrap();
+        let mut got = Vec::new();
+        r.read_to_end(&mut got).unwrap();
+        assert_eq!(got, pt, "size {}", size);
+    }
+}
+
+#[test]
+fn test_age_multi_recipient() {
+    tink_hybrid::init();
+    let h1 = Handle::new(&tink_hybrid::age_x25519_key_template()).unwrap();
+    let h2 = import_age_identity(IDENTITY).unwrap();
+    let h3 = Handle::new(&tink_hybrid::age_x25519_key_template()).unwrap();
+    let e = new_multi_recipient_encrypt(&[
+        &h1.public().unwrap(),
+        &import_age_recipient(RECIPIENT).unwrap(),
+    ])
+    .unwrap();
+    let ct = e.encrypt(PLAINTEXT, b"").unwrap();
+    for h in &[&h1, &h2] {
+        let d = tink_hybrid::new_decrypt(h).unwrap();
+        assert_eq!(d.decrypt(&ct, b"").unwrap(), PLAINTEXT);
+    }
+    let d = tink_hybrid::new_decrypt(&h3).unwrap();
+    expect_err(d.decrypt(&ct, b""), "decryption failed");
+}
+
+#[test]
+fn test_age_passphrase_round_trip() {
+    let e = AgeEncrypt::new(vec![AgeRecipient::Scrypt {
+        passphrase: PASSPHRASE.to_vec(),
+        log_n: 10,
+    }])
+    .unwrap();
+    let ct = e.encrypt(PLAINTEXT, b"").unwrap();
+    let d = AgeDecrypt::with_passphrase(PASSPHRASE);
+    assert_eq!(d.decrypt(&ct, b"").unwrap(), PLAINTEXT);
+
+    let x25519 = AgeRecipient::X25519([9; 32]);
+    expect_err(
+        AgeEncrypt::new(vec![
+            x25519,
+            AgeRecipient::Scrypt {
+                passphrase: PASSPHRASE.to_vec(),
+                log_n: 10,
+            },
+        ]),
+        "must be the only recipient",
+    );
+    expect_err(AgeEncrypt::new(vec![]), "no recipients");
+}
@@ -0,0 +1,102 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use std::{
+    collections::HashSet,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tink_hybrid::subtle::{EphemeralKeyPool, EphemeralKeyPoolStats};
+use tink_proto::EllipticCurveType;
+use tink_tests::expect_err;
+
+/// Length of the TINK output prefix plus an uncompressed P-256 point.
+const HEADER_LEN: usize = 5 + 65;
+
+/// Wait for the background thread to fill the pool.
+fn wait_until_full(pool: &EphemeralKeyPool) {
+    let deadline = Instant::now() + Duration::from_secs(30);
+    while pool.available() < pool.capacity() {
+        assert!(Instant::now() < deadline, "pool not filled");
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
+
+#[test]
+fn test_ephemeral_key_pool_invalid() {
+    expect_err(
+        EphemeralKeyPool::new(EllipticCurveType::NistP256, 0),
+        "capacity must be positive",
+    );
+    assert!(EphemeralKeyPool::new(EllipticCurveType::UnknownCurve, 4).is_err());
+}
+
+#[test]
+fn test_ephemeral_key_pool_fills_to_capacity() {
+    let pool = EphemeralKeyPool::new(EllipticCurveType::NistP256, 8).unwrap();
+    assert_eq!(pool.curve(), EllipticCurveType::NistP256);
+    assert_eq!(pool.capacity(), 8);
+    wait_until_full(&pool);
+    std::thread::sleep(Duration::from_millis(20));
+    assert_eq!(pool.available(), 8);
+    assert_eq!(pool.stats(), EphemeralKeyPoolStats::default());
+}
+
+#[test]
+fn test_encrypt_with_ephemeral_key_pool() {
+    tink_hybrid::init();
+    let khpriv =
+        tink_core::keyset::Handle::new(&tink_hybrid::ecies_hkdf_aes128_gcm_key_template()).unwrap();
+    let khpub = khpriv.public().unwrap();
+    let pool = Arc::new(EphemeralKeyPool::new(EllipticCurveType::NistP256, 4).unwrap());
+    wait_until_full(&pool);
+
+    let e = tink_hybrid::new_encrypt_with_ephemeral_key_pool(&khpub, pool.clone()).unwrap();
+    let d = tink_hybrid::new_decrypt(&khpriv).unwrap();
+    let context_info = b"context info";
+    let mut headers = HashSet::new();
+    for i in 0..10u8 {
+        let pt = vec![i; 16];
+        let ct = e.encrypt(&pt, context_info).unwrap();
+        assert_eq!(d.decrypt(&ct, context_info).unwrap(), pt);
+        // Every message has its own ephemeral key.
+        assert!(headers.insert(ct[..HEADER_LEN].to_vec()));
+    }
+    let stats = pool.stats();
+    assert_eq!(stats.hits + stats.misses, 10);
+    assert!(stats.hits >= 4, "{:?}", stats);
+
+    // The pool is refilled after use.
+    wait_until_full(&pool);
+}
+
+#[test]
+fn test_encrypt_without_ephemeral_key_pool() {
+    tink_hybrid::init();
+    let khpriv =
+        tink_core::keyset::Handle::new(&tink_hybrid::ecies_hkdf_aes128_gcm_key_template()).unwrap();
+    let khpub = khpriv.public().unwrap();
+    let pool = Arc::new(EphemeralKeyPool::new(EllipticCurveType::NistP256, 2).unwrap());
+    wait_until_full(&pool);
+
+    // Encryption without the pool leaves the pool untouched.
+    let e = tink_hybrid::new_encrypt(&khpub).unwrap();
+    let d = tink_hybrid::new_decrypt(&khpriv).unwrap();
+    let ct = e.encrypt(b"plaintext", b"").unwrap();
+    assert_eq!(d.decrypt(&ct, b"").unwrap(), b"plaintext");
+    assert_eq!(pool.available(), 2);
+    assert_eq!(pool.stats(), EphemeralKeyPoolStats::default());
+}
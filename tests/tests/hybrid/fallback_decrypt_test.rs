@@ -0,0 +1,119 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use std::io::{Read, Write};
+use tink_core::{HybridDecrypt, HybridEncrypt};
+use tink_hybrid::FallbackHybridDecrypt;
+use tink_tests::expect_err;
+
+fn new_handle() -> tink_core::keyset::Handle {
+    tink_hybrid::init();
+    tink_core::keyset::Handle::new(&tink_hybrid::ecies_hkdf_aes128_gcm_key_template()).unwrap()
+}
+
+fn encrypt(kh: &tink_core::keyset::Handle, pt: &[u8], ci: &[u8]) -> Vec<u8> {
+    tink_hybrid::new_encrypt(&kh.public().unwrap())
+        .unwrap()
+        .encrypt(pt, ci)
+        .unwrap()
+}
+
+#[test]
+fn test_fallback_hybrid_decrypt_reports_keyset() {
+    let (current, previous, oldest) = (new_handle(), new_handle(), new_handle());
+    let d =
+        FallbackHybridDecrypt::from_handles(&current, &[previous.clone(), oldest.clone()]).unwrap();
+    assert_eq!(d.keyset_count(), 3);
+    let ci = b"context";
+
+    let ct = encrypt(&current, b"new", ci);
+    assert_eq!(
+        d.decrypt_with_keyset_index(&ct, ci).unwrap(),
+        (b"new".to_vec(), 0)
+    );
+    let ct = encrypt(&previous, b"old", ci);
+    assert_eq!(
+        d.decrypt_with_keyset_index(&ct, ci).unwrap(),
+        (b"old".to_vec(), 1)
+    );
+    let ct = encrypt(&oldest, b"oldest", ci);
+    assert_eq!(d.decrypt(&ct, ci).unwrap(), b"oldest");
+    assert_eq!(d.clone().decrypt_with_keyset_index(&ct, ci).unwrap().1, 2);
+}
+
+#[test]
+fn test_fallback_hybrid_decrypt_with_key_id() {
+    let (current, previous) = (new_handle(), new_handle());
+    let d = FallbackHybridDecrypt::from_handles(&current, &[previous.clone()]).unwrap();
+    let key_id = previous.keyset_info().primary_key_id;
+    let ct = encrypt(&previous, b"data", b"");
+    assert_eq!(d.decrypt_with_key_id(key_id, &ct, b"").unwrap(), b"data");
+    assert!(d.raw_key_ids().is_empty());
+}
+
+#[test]
+fn test_fallback_hybrid_decrypt_all_fail() {
+    let d = FallbackHybridDecrypt::from_handles(&new_handle(), &[new_handle()]).unwrap();
+    let ct = encrypt(&new_handle(), b"data", b"");
+    expect_err(d.decrypt(&ct, b""), "decryption failed with all 2 keysets");
+}
+
+#[test]
+fn test_fallback_hybrid_decrypt_invalid_keyset() {
+    let public = new_handle().public().unwrap();
+    expect_err(
+        FallbackHybridDecrypt::from_handles(&public, &[]),
+        "invalid primary keyset",
+    );
+}
+
+#[test]
+fn test_fallback_hybrid_decrypt_streaming_uses_primary() {
+    tink_hybrid::init();
+    let new_streaming_handle = || {
+        tink_core::keyset::Handle::new(&tink_hybrid::ecies_hkdf_aes128_gcm_hkdf_1mb_key_template())
+            .unwrap()
+    };
+    let (current, previous) = (new_streaming_handle(), new_streaming_handle());
+    let d = FallbackHybridDecrypt::from_handles(&current, &[previous.clone()]).unwrap();
+    let ci = b"context";
+    let encrypt_stream = |kh: &tink_core::keyset::Handle, pt: &[u8]| {
+        let buf = tink_tests::SharedBuf::new();
+        let mut w = tink_hybrid::new_encrypt(&kh.public().unwrap())
+            .unwrap()
+            .new_encrypting_writer(Box::new(buf.clone()), ci)
+            .unwrap();
+        w.write_all(pt).unwrap();
+        w.close().unwrap();
+        buf.contents()
+    };
+
+    let ct = encrypt_stream(&current, b"new");
+    let mut r = d
+        .new_decrypting_reader(Box::new(std::io::Cursor::new(ct)), ci)
+        .unwrap();
+    let mut pt = vec![];
+    r.read_to_end(&mut pt).unwrap();
+    assert_eq!(pt, b"new");
+
+    // Fallback keysets are not tried for streams.
+    let ct = encrypt_stream(&previous, b"old");
+    let mut r = d
+        .new_decrypting_reader(Box::new(std::io::Cursor::new(ct)), ci)
+        .unwrap();
+    let mut pt = vec![];
+    assert!(r.read_to_end(&mut pt).is_err());
+}
@@ -112,3 +112,50 @@ fn test_factory_with_valid_primitive_set_type() {
     let result = tink_hybrid::new_decrypt(&good_kh);
     assert!(result.is_ok(), "new_decrypt() failed: {:?}", result.err());
 }
+
+#[test]
+fn test_decrypt_with_key_id() {
+    tink_hybrid::init();
+    let mut raw_template = tink_hybrid::ecies_hkdf_aes128_gcm_key_template();
+    raw_template.output_prefix_type = tink_proto::OutputPrefixType::Raw as i32;
+    let tink_template = tink_hybrid::ecies_hkdf_aes128_gcm_key_template();
+
+    // Build a keyset with two RAW keys and a TINK key, keeping an encrypter for each key.
+    let mut ksm = tink_core::keyset::Manager::new();
+    let mut encrypters = Vec::new();
+    for template in &[&raw_template, &raw_template, &tink_template] {
+        let key_id = ksm.rotate(template).unwrap();
+        let kh_pub = ksm.handle().unwrap().public().unwrap();
+        encrypters.push((key_id, tink_hybrid::new_encrypt(&kh_pub).unwrap()));
+    }
+    let d = tink_hybrid::new_decrypt(&ksm.handle().unwrap()).unwrap();
+
+    let mut raw_key_ids = d.raw_key_ids();
+    raw_key_ids.sort_unstable();
+    let mut want = vec![encrypters[0].0, encrypters[1].0];
+    want.sort_unstable();
+    assert_eq!(raw_key_ids, want);
+
+    let pt = get_random_bytes(20);
+    let ci = get_random_bytes(20);
+    for (key_id, e) in &encrypters {
+        let ct = e.encrypt(&pt, &ci).unwrap();
+        assert_eq!(d.decrypt_with_key_id(*key_id, &ct, &ci).unwrap(), pt);
+        for (other_key_id, _) in &encrypters {
+            if other_key_id != key_id {
+                assert!(d.decrypt_with_key_id(*other_key_id, &ct, &ci).is_err());
+            }
+        }
+    }
+
+    let ct = encrypters[0].1.encrypt(&pt, &ci).unwrap();
+    tink_tests::expect_err(
+        d.decrypt_with_key_id(encrypters[0].0 ^ 0xffff_ffff, &ct, &ci),
+        "unknown key ID",
+    );
+    let ct = encrypters[2].1.encrypt(&pt, &ci).unwrap();
+    tink_tests::expect_err(
+        d.decrypt_with_key_id(encrypters[2].0, &ct[1..], &ci),
+        "prefix does not match",
+    );
+}
@@ -54,3 +54,41 @@ fn test_key_templates() {
         assert_eq!(registered, template);
     }
 }
+
+#[test]
+fn test_ecies_template_builder() {
+    assert_eq!(
+        tink_hybrid::ecies_hkdf_aes128_gcm_key_template(),
+        tink_hybrid::EciesAeadHkdfTemplateBuilder::new(tink_aead::aes128_gcm_key_template())
+            .build()
+            .unwrap()
+    );
+    assert!(
+        tink_hybrid::EciesAeadHkdfTemplateBuilder::new(tink_daead::aes_siv_key_template())
+            .hkdf_salt(b"salt")
+            .point_format(tink_proto::EcPointFormat::Compressed)
+            .prefix(tink_proto::OutputPrefixType::Raw)
+            .build()
+            .is_ok()
+    );
+
+    tink_tests::expect_err(
+        tink_hybrid::EciesAeadHkdfTemplateBuilder::new(tink_aead::aes128_gcm_key_template())
+            .curve(tink_proto::EllipticCurveType::NistP384)
+            .build(),
+        "unsupported curve",
+    );
+    tink_tests::expect_err(
+        tink_hybrid::EciesAeadHkdfTemplateBuilder::new(tink_aead::aes128_gcm_key_template())
+            .hkdf_hash(tink_proto::HashType::UnknownHash)
+            .build(),
+        "unsupported HKDF hash",
+    );
+    tink_tests::expect_err(
+        tink_hybrid::EciesAeadHkdfTemplateBuilder::new(
+            tink_aead::cha_cha20_poly1305_key_template(),
+        )
+        .build(),
+        "unsupported DEM",
+    );
+}
@@ -0,0 +1,147 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use std::collections::HashSet;
+use tink_core::{HybridDecrypt, HybridEncrypt};
+use tink_proto::{EcPointFormat, EllipticCurveType, HashType};
+use tink_tests::expect_err;
+
+/// Length of the TINK output prefix plus an uncompressed P-256 point.
+const HEADER_LEN: usize = 5 + 65;
+
+fn new_subtle_pair(
+    dem: tink_proto::KeyTemplate,
+) -> (
+    tink_hybrid::subtle::EciesAeadHkdfHybridEncrypt,
+    tink_hybrid::subtle::EciesAeadHkdfHybridDecrypt,
+) {
+    let pvt = tink_hybrid::subtle::generate_ecdh_key_pair(EllipticCurveType::NistP256).unwrap();
+    let salt = b"some salt";
+    let r_dem = tink_hybrid::EciesAeadHkdfDemHelper::new(&dem).unwrap();
+    let e = tink_hybrid::subtle::EciesAeadHkdfHybridEncrypt::new(
+        &pvt.public_key(),
+        salt,
+        HashType::Sha256,
+        EcPointFormat::Uncompressed,
+        r_dem.clone(),
+    )
+    .unwrap();
+    let d = tink_hybrid::subtle::EciesAeadHkdfHybridDecrypt::new(
+        pvt,
+        salt,
+        HashType::Sha256,
+        EcPointFormat::Uncompressed,
+        r_dem,
+    )
+    .unwrap();
+    (e, d)
+}
+
+#[test]
+fn test_encrypt_with_kem_reuse() {
+    tink_hybrid::init();
+    let khpriv =
+        tink_core::keyset::Handle::new(&tink_hybrid::ecies_hkdf_aes128_gcm_key_template()).unwrap();
+    let khpub = khpriv.public().unwrap();
+    let e = tink_hybrid::new_encrypt_with_kem_reuse(&khpub, 3).unwrap();
+    let d = tink_hybrid::new_decrypt(&khpriv).unwrap();
+
+    let mut headers = Vec::new();
+    let mut cts = HashSet::new();
+    for i in 0..7u8 {
+        let pt = vec![i % 2; 16];
+        let context_info = vec![i % 3];
+        let ct = e.encrypt(&pt, &context_info).unwrap();
+        assert_eq!(d.decrypt(&ct, &context_info).unwrap(), pt);
+        assert!(cts.insert(ct.clone()), "encryption is not randomized");
+        headers.push(ct[..HEADER_LEN].to_vec());
+    }
+    // Each ephemeral key exchange is used for 3 messages.
+    assert_eq!(headers[0], headers[1]);
+    assert_eq!(headers[0], headers[2]);
+    assert_ne!(headers[2], headers[3]);
+    assert_eq!(headers[3], headers[5]);
+    assert_ne!(headers[5], headers[6]);
+}
+
+#[test]
+fn test_encrypt_with_kem_reuse_invalid() {
+    tink_hybrid::init();
+    let khpriv =
+        tink_core::keyset::Handle::new(&tink_hybrid::ecies_hkdf_aes128_gcm_key_template()).unwrap();
+    let khpub = khpriv.public().unwrap();
+    expect_err(
+        tink_hybrid::new_encrypt_with_kem_reuse(&khpub, 0),
+        "KEM reuse must be between 1",
+    );
+    expect_err(
+        tink_hybrid::new_encrypt_with_kem_reuse(&khpub, tink_hybrid::subtle::MAX_KEM_REUSE + 1),
+        "KEM reuse must be between 1",
+    );
+    assert!(
+        tink_hybrid::new_encrypt_with_kem_reuse(&khpub, tink_hybrid::subtle::MAX_KEM_REUSE).is_ok()
+    );
+}
+
+#[test]
+fn test_kem_reuse_randomized_dems() {
+    tink_hybrid::init();
+    for dem in &[
+        tink_aead::aes128_gcm_key_template(),
+        tink_aead::aes128_ctr_hmac_sha256_key_template(),
+    ] {
+        let (e, d) = new_subtle_pair(dem.clone());
+        assert_eq!(e.kem_reuse(), None);
+        let e = e.with_kem_reuse(2).unwrap();
+        assert_eq!(e.kem_reuse(), Some(2));
+        let ct1 = e.encrypt(b"plaintext", b"info").unwrap();
+        let ct2 = e.encrypt(b"plaintext", b"info").unwrap();
+        assert_eq!(ct1[..65], ct2[..65]);
+        assert_ne!(ct1, ct2);
+        assert_eq!(d.decrypt(&ct1, b"info").unwrap(), b"plaintext");
+        assert_eq!(d.decrypt(&ct2, b"info").unwrap(), b"plaintext");
+    }
+}
+
+#[test]
+fn test_kem_reuse_not_applied_to_deterministic_dem() {
+    tink_hybrid::init();
+    tink_daead::init();
+    let (e, d) = new_subtle_pair(tink_daead::aes_siv_key_template());
+    let e = e.with_kem_reuse(10).unwrap();
+    assert_eq!(e.kem_reuse(), None);
+
+    // Each message still gets a fresh exchange, so equal plaintexts are not revealed.
+    let ct1 = e.encrypt(b"plaintext", b"info").unwrap();
+    let ct2 = e.encrypt(b"plaintext", b"info").unwrap();
+    assert_ne!(ct1[..65], ct2[..65]);
+    assert_ne!(ct1[65..], ct2[65..]);
+    assert_eq!(d.decrypt(&ct1, b"info").unwrap(), b"plaintext");
+}
+
+#[test]
+fn test_kem_reuse_shared_by_clones() {
+    tink_hybrid::init();
+    let (e, d) = new_subtle_pair(tink_aead::aes128_gcm_key_template());
+    let e = e.with_kem_reuse(2).unwrap();
+    let e2 = e.clone();
+    let ct1 = e.encrypt(b"one", b"").unwrap();
+    let ct2 = e2.encrypt(b"two", b"").unwrap();
+    let ct3 = e.encrypt(b"three", b"").unwrap();
+    assert_eq!(ct1[..65], ct2[..65]);
+    assert_ne!(ct2[..65], ct3[..65]);
+    assert_eq!(d.decrypt(&ct3, b"").unwrap(), b"three");
+}
@@ -636,3 +636,46 @@ fn test_primitive_with_invalid_key() {
         tink_tests::expect_err(result, err_msg);
     }
 }
+
+#[test]
+fn test_deterministic_aead_dem() {
+    tink_hybrid::init();
+    let template =
+        tink_hybrid::EciesAeadHkdfTemplateBuilder::new(tink_daead::aes_siv_key_template())
+            .build()
+            .unwrap();
+    let kh = tink_core::keyset::Handle::new(&template).unwrap();
+    let enc = tink_hybrid::new_encrypt(&kh.public().unwrap()).unwrap();
+    let dec = tink_hybrid::new_decrypt(&kh).unwrap();
+    let ct = enc.encrypt(b"plaintext", b"context").unwrap();
+    assert_eq!(dec.decrypt(&ct, b"context").unwrap(), b"plaintext");
+    assert!(dec.decrypt(&ct, b"other context").is_err());
+}
+
+#[test]
+fn test_new_key_with_unsupported_dem() {
+    tink_hybrid::init();
+    tink_mac::init();
+    let km = tink_core::registry::get_key_manager(tink_tests::ECIES_AEAD_HKDF_PRIVATE_KEY_TYPE_URL)
+        .unwrap();
+    for dem in &[
+        tink_mac::hmac_sha256_tag256_key_template(),
+        tink_aead::cha_cha20_poly1305_key_template(),
+    ] {
+        let format = EciesAeadHkdfKeyFormat {
+            params: Some(EciesAeadHkdfParams {
+                kem_params: Some(EciesHkdfKemParams {
+                    curve_type: EllipticCurveType::NistP256 as i32,
+                    hkdf_hash_type: HashType::Sha256 as i32,
+                    hkdf_salt: vec![],
+                }),
+                dem_params: Some(EciesAeadDemParams {
+                    aead_dem: Some(dem.clone()),
+                }),
+                ec_point_format: EcPointFormat::Uncompressed as i32,
+            }),
+        };
+        let result = km.new_key(&tink_tests::proto_encode(&format));
+        tink_tests::expect_err(result, "unsupported DEM key type");
+    }
+}
@@ -14,11 +14,17 @@
 //
 ////////////////////////////////////////////////////////////////////////////////
 
+mod age_test;
 mod ecies_aead_hkdf_dem_helper_test;
 mod ecies_aead_hkdf_hybrid_decrypt_test;
 mod ecies_aead_hkdf_hybrid_encrypt_test;
+mod ephemeral_key_pool_test;
+mod fallback_decrypt_test;
 mod hybrid_factory_test;
 mod hybrid_key_templates_test;
 mod integration_test;
+mod kem_reuse_test;
 mod key_manager_test;
+mod multi_recipient_test;
+mod streaming_test;
 mod subtle;
@@ -0,0 +1,78 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_core::keyset::Handle;
+use tink_hybrid::{MultiRecipientDecrypt, MultiRecipientEncrypt, MULTI_RECIPIENT_VERSION};
+
+fn new_recipient() -> Handle {
+    tink_hybrid::init();
+    Handle::new(&tink_hybrid::ecies_hkdf_aes128_gcm_key_template()).unwrap()
+}
+
+#[test]
+fn test_multi_recipient_round_trip() {
+    let recipients: Vec<Handle> = (0..3).map(|_| new_recipient()).collect();
+    let public: Vec<Handle> = recipients.iter().map(|h| h.public().unwrap()).collect();
+    let enc = MultiRecipientEncrypt::new(&public).unwrap();
+    let pt = b"shared document";
+    let ct = enc.encrypt(pt, b"context").unwrap();
+    assert_eq!(ct[0], MULTI_RECIPIENT_VERSION);
+    assert_eq!(&ct[1..5], &[0, 0, 0, 3]);
+
+    for h in &recipients {
+        let dec = MultiRecipientDecrypt::new(h).unwrap();
+        assert_eq!(dec.decrypt(&ct, b"context").unwrap(), pt);
+        assert!(dec.decrypt(&ct, b"other context").is_err());
+    }
+
+    let outsider = MultiRecipientDecrypt::new(&new_recipient()).unwrap();
+    tink_tests::expect_err(outsider.decrypt(&ct, b"context"), "not a recipient");
+}
+
+#[test]
+fn test_multi_recipient_tampering() {
+    let recipient = new_recipient();
+    let other = new_recipient();
+    let enc = MultiRecipientEncrypt::new(&[recipient.public().unwrap(), other.public().unwrap()])
+        .unwrap();
+    let dec = MultiRecipientDecrypt::new(&recipient).unwrap();
+    let ct = enc.encrypt(b"data", b"").unwrap();
+    for i in 0..ct.len() {
+        let mut modified = ct.clone();
+        modified[i] ^= 0x01;
+        assert!(dec.decrypt(&modified, b"").is_err(), "modified byte {}", i);
+    }
+    for len in 0..ct.len() {
+        assert!(
+            dec.decrypt(&ct[..len], b"").is_err(),
+            "truncated to {}",
+            len
+        );
+    }
+}
+
+#[test]
+fn test_multi_recipient_invalid() {
+    tink_tests::expect_err(MultiRecipientEncrypt::new(&[]), "no recipients");
+    // Recipients must be given as public keysets.
+    tink_aead::init();
+    let aead = Handle::new(&tink_aead::aes128_gcm_key_template()).unwrap();
+    tink_tests::expect_err(
+        MultiRecipientEncrypt::new(&[aead.clone()]),
+        "invalid recipient keyset",
+    );
+    tink_tests::expect_err(MultiRecipientDecrypt::new(&aead), "invalid keyset");
+}
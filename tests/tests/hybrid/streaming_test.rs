@@ -0,0 +1,130 @@
+// Copyright 2019-2021 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use std::io::{Cursor, Read, Write};
+use tink_core::{keyset::Handle, subtle::random::get_random_bytes, TinkError};
+use tink_proto::{prost::Message, KeyTemplate};
+use tink_tests::{proto_encode, SharedBuf};
+
+/// Return an ECIES P-256 template that uses the given streaming AEAD template as its DEM.
+fn ecies_template_with_dem(dem: KeyTemplate) -> KeyTemplate {
+    let mut kt = tink_hybrid::ecies_hkdf_aes128_gcm_hkdf_1mb_key_template();
+    let mut format = tink_proto::EciesAeadHkdfKeyFormat::decode(&kt.value[..]).unwrap();
+    format
+        .params
+        .as_mut()
+        .unwrap()
+        .dem_params
+        .as_mut()
+        .unwrap()
+        .aead_dem = Some(dem);
+    kt.value = proto_encode(&format);
+    kt
+}
+
+fn encrypt(kh: &Handle, pt: &[u8], context_info: &[u8]) -> Result<Vec<u8>, TinkError> {
+    let e = tink_hybrid::new_encrypt(&kh.public()?)?;
+    let buf = SharedBuf::new();
+    let mut w = e.new_encrypting_writer(Box::new(buf.clone()), context_info)?;
+    w.write_all(pt)
+        .map_err(|e| tink_core::utils::wrap_err("write failed", e))?;
+    w.close()?;
+    Ok(buf.contents())
+}
+
+fn decrypt(kh: &Handle, ct: &[u8], context_info: &[u8]) -> Result<Vec<u8>, TinkError> {
+    let d = tink_hybrid::new_decrypt(kh)?;
+    let mut r = d.new_decrypting_reader(Box::new(Cursor::new(ct.to_vec())), context_info)?;
+    let mut pt = vec![];
+    r.read_to_end(&mut pt)
+        .map_err(|e| tink_core::utils::wrap_err("read failed", e))?;
+    Ok(pt)
+}
+
+#[test]
+fn test_streaming_hybrid_encrypt_decrypt() {
+    tink_hybrid::init();
+    let templates = vec![
+        tink_hybrid::ecies_hkdf_aes128_gcm_hkdf_1mb_key_template(),
+        ecies_template_with_dem(tink_streaming_aead::aes256_gcm_hkdf_4kb_key_template()),
+        ecies_template_with_dem(
+            tink_streaming_aead::aes128_ctr_hmac_sha256_segment_4kb_key_template(),
+        ),
+    ];
+    for kt in templates {
+        let kh = Handle::new(&kt).unwrap();
+        for pt_size in &[0, 1, 4096, 10000, (1 << 20) + 17] {
+            let pt = get_random_bytes(*pt_size);
+            let ct = encrypt(&kh, &pt, b"context info").unwrap();
+            assert_eq!(decrypt(&kh, &ct, b"context info").unwrap(), pt);
+            assert!(decrypt(&kh, &ct, b"other context info").is_err());
+
+            let mut modified_ct = ct.clone();
+            let last = modified_ct.len() - 1;
+            modified_ct[last] ^= 0x01;
+            assert!(decrypt(&kh, &modified_ct, b"context info").is_err());
+        }
+    }
+}
+
+#[test]
+fn test_streaming_hybrid_key_rotation() {
+    tink_hybrid::init();
+    let kt = tink_hybrid::ecies_hkdf_aes128_gcm_hkdf_1mb_key_template();
+    let mut raw_kt = kt.clone();
+    raw_kt.output_prefix_type = tink_proto::OutputPrefixType::Raw as i32;
+
+    let mut ksm = tink_core::keyset::Manager::new();
+    ksm.rotate(&raw_kt).unwrap();
+    let kh_old = ksm.handle().unwrap();
+    ksm.rotate(&kt).unwrap();
+    let kh_new = ksm.handle().unwrap();
+    ksm.add(&raw_kt, /* primary= */ false).unwrap();
+    let kh_all = ksm.handle().unwrap();
+
+    let pt = get_random_bytes(5000);
+    for kh in &[&kh_old, &kh_new] {
+        let ct = encrypt(kh, &pt, b"").unwrap();
+        assert_eq!(decrypt(&kh_all, &ct, b"").unwrap(), pt);
+    }
+
+    // Ciphertext for a key outside the keyset cannot be decrypted.
+    let other_kh = Handle::new(&kt).unwrap();
+    let ct = encrypt(&other_kh, &pt, b"").unwrap();
+    tink_tests::expect_err(decrypt(&kh_all, &ct, b""), "decryption failed");
+}
+
+#[test]
+fn test_streaming_hybrid_dem_mismatch() {
+    tink_hybrid::init();
+
+    // A key with a streaming DEM does not support one-shot encryption.
+    let kh = Handle::new(&tink_hybrid::ecies_hkdf_aes128_gcm_hkdf_1mb_key_template()).unwrap();
+    let e = tink_hybrid::new_encrypt(&kh.public().unwrap()).unwrap();
+    tink_tests::expect_err(e.encrypt(b"plaintext", b""), "new_encrypting_writer");
+    let d = tink_hybrid::new_decrypt(&kh).unwrap();
+    assert!(d.decrypt(&get_random_bytes(100), b"").is_err());
+
+    // A key with an AEAD DEM does not support streaming encryption.
+    let kh = Handle::new(&tink_hybrid::ecies_hkdf_aes128_gcm_key_template()).unwrap();
+    tink_tests::expect_err(
+        encrypt(&kh, b"plaintext", b""),
+        "requires a streaming AEAD DEM",
+    );
+    let e = tink_hybrid::new_encrypt(&kh.public().unwrap()).unwrap();
+    let ct = e.encrypt(b"plaintext", b"").unwrap();
+    tink_tests::expect_err(decrypt(&kh, &ct, b""), "decryption failed");
+}
@@ -0,0 +1,166 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+// These tests install a global key template policy, which would interfere with key generation in
+// other tests, so they live in their own test binary and serialize via `registry::isolate()`.
+
+use tink_core::{
+    keyset::{Handle, Manager},
+    registry::{self, KeyTemplatePolicy},
+};
+use tink_proto::{
+    prost::Message, AesGcmKeyFormat, HashType, HmacKeyFormat, HmacParams, KeyTemplate,
+};
+use tink_tests::{expect_err, AES_GCM_TYPE_URL, HMAC_TYPE_URL};
+
+/// Policy forbidding AES-128 and SHA-1 HMAC keys.
+fn org_policy() -> KeyTemplatePolicy {
+    KeyTemplatePolicy::new("test-org")
+        .deny("AES-128 is forbidden", |kt| {
+            kt.type_url == AES_GCM_TYPE_URL
+                && AesGcmKeyFormat::decode(kt.value.as_ref()).map_or(true, |f| f.key_size < 32)
+        })
+        .deny("SHA-1 MACs are forbidden", |kt| {
+            kt.type_url == HMAC_TYPE_URL
+                && HmacKeyFormat::decode(kt.value.as_ref()).map_or(true, |f| {
+                    f.params
+                        .map_or(true, |p| p.try_hash() == Ok(HashType::Sha1))
+                })
+        })
+}
+
+fn hmac_sha1_key_template() -> KeyTemplate {
+    let format = HmacKeyFormat {
+        params: Some(HmacParams {
+            hash: HashType::Sha1 as i32,
+            tag_size: 16,
+        }),
+        key_size: 32,
+        version: 0,
+    };
+    KeyTemplate {
+        type_url: HMAC_TYPE_URL.to_string(),
+        value: tink_tests::proto_encode(&format),
+        output_prefix_type: tink_proto::OutputPrefixType::Tink as i32,
+    }
+}
+
+#[test]
+fn test_policy_deny_rules() {
+    let policy = org_policy();
+    assert!(policy.check(&tink_aead::aes256_gcm_key_template()).is_ok());
+    assert!(policy
+        .check(&tink_mac::hmac_sha256_tag256_key_template())
+        .is_ok());
+    expect_err(
+        policy.check(&tink_aead::aes128_gcm_key_template()),
+        "key template policy 'test-org': template for type.googleapis.com/google.crypto.tink.AesGcmKey rejected: AES-128 is forbidden",
+    );
+    expect_err(
+        policy.check(&hmac_sha1_key_template()),
+        "SHA-1 MACs are forbidden",
+    );
+    // Templates for other key types are unaffected by deny rules.
+    assert!(policy
+        .check(&tink_aead::cha_cha20_poly1305_key_template())
+        .is_ok());
+}
+
+#[test]
+fn test_policy_allowlist() {
+    tink_aead::init();
+    let policy = KeyTemplatePolicy::new("allowlist")
+        .allow_type_url(HMAC_TYPE_URL)
+        .allow_named_template("AES256_GCM")
+        .unwrap();
+    assert!(policy
+        .check(&tink_mac::hmac_sha512_tag512_key_template())
+        .is_ok());
+    assert!(policy.check(&tink_aead::aes256_gcm_key_template()).is_ok());
+    // The output prefix type is not part of the comparison.
+    assert!(policy
+        .check(&tink_aead::aes256_gcm_no_prefix_key_template())
+        .is_ok());
+    expect_err(
+        policy.check(&tink_aead::aes128_gcm_key_template()),
+        "not in the allowlist",
+    );
+    expect_err(
+        policy.check(&tink_aead::cha_cha20_poly1305_key_template()),
+        "not in the allowlist",
+    );
+    expect_err(
+        KeyTemplatePolicy::new("allowlist").allow_named_template("NO_SUCH_TEMPLATE"),
+        "unknown key template name",
+    );
+
+    // Deny rules apply to allowlisted templates too.
+    let policy = policy.deny("no MACs", |kt| kt.type_url == HMAC_TYPE_URL);
+    expect_err(
+        policy.check(&tink_mac::hmac_sha512_tag512_key_template()),
+        "no MACs",
+    );
+}
+
+#[test]
+fn test_installed_policy() {
+    tink_aead::init();
+    tink_mac::init();
+    let _guard = registry::isolate();
+    assert!(registry::key_template_policy().is_none());
+    assert!(Handle::new(&tink_aead::aes128_gcm_key_template()).is_ok());
+
+    registry::set_key_template_policy(org_policy());
+    assert_eq!(registry::key_template_policy().unwrap().name(), "test-org");
+
+    expect_err(
+        Handle::new(&tink_aead::aes128_gcm_key_template()),
+        "AES-128 is forbidden",
+    );
+    expect_err(
+        registry::new_key(&tink_aead::aes128_gcm_key_template()),
+        "AES-128 is forbidden",
+    );
+    expect_err(
+        registry::new_key_data(&hmac_sha1_key_template()),
+        "SHA-1 MACs are forbidden",
+    );
+    let mut pseudorandomness: &[u8] = &[0x01; 64];
+    expect_err(
+        registry::derive_key_data(&tink_aead::aes128_gcm_key_template(), &mut pseudorandomness),
+        "AES-128 is forbidden",
+    );
+
+    let mut manager = Manager::new();
+    manager
+        .rotate(&tink_aead::aes256_gcm_key_template())
+        .unwrap();
+    expect_err(
+        manager.rotate(&tink_aead::aes128_gcm_key_template()),
+        "AES-128 is forbidden",
+    );
+
+    // Keys generated before the policy was installed remain usable.
+    registry::clear_key_template_policy();
+    let handle = Handle::new(&tink_aead::aes128_gcm_key_template()).unwrap();
+    registry::set_key_template_policy(org_policy());
+    let a = tink_aead::new(&handle).unwrap();
+    let ct = a.encrypt(b"data", b"aad").unwrap();
+    assert_eq!(a.decrypt(&ct, b"aad").unwrap(), b"data");
+
+    registry::clear_key_template_policy();
+    assert!(Handle::new(&tink_aead::aes128_gcm_key_template()).is_ok());
+}
@@ -14,7 +14,7 @@
 //
 ////////////////////////////////////////////////////////////////////////////////
 
-use tink_core::{utils::wrap_err, TinkError};
+use tink_core::{utils::wrap_err, Mac, TinkError};
 
 #[test]
 fn test_factory_multiple_keys() {
@@ -67,6 +67,44 @@ fn test_factory_multiple_keys() {
     );
 }
 
+#[test]
+fn test_factory_verify_mac_with_key_id() {
+    tink_mac::init();
+    let tag_size = 16;
+    let keyset = tink_tests::new_test_hmac_keyset(tag_size, tink_proto::OutputPrefixType::Tink);
+    let primary_key = keyset.key[0].clone();
+    let raw_key = keyset.key[1].clone();
+    let p = tink_mac::new(&tink_core::keyset::insecure::new_handle(keyset).unwrap()).unwrap();
+
+    let data = b"data to authenticate";
+    let tag = p.compute_mac(data).unwrap();
+    assert_eq!(
+        p.verify_mac_with_key_id(&tag, data).unwrap(),
+        primary_key.key_id
+    );
+
+    // A tag from the RAW key in the keyset reports that key.
+    let raw_keyset = tink_tests::new_keyset(raw_key.key_id, vec![raw_key.clone()]);
+    let raw_p =
+        tink_mac::new(&tink_core::keyset::insecure::new_handle(raw_keyset).unwrap()).unwrap();
+    let tag = raw_p.compute_mac(data).unwrap();
+    assert_eq!(
+        p.verify_mac_with_key_id(&tag, data).unwrap(),
+        raw_key.key_id
+    );
+
+    assert!(p.verify_mac_with_key_id(&tag, b"other data").is_err());
+
+    // Primitives without a keyset cannot report a key ID.
+    let subtle_mac =
+        tink_mac::subtle::Hmac::new(tink_proto::HashType::Sha256, &[0; 32], 16).unwrap();
+    let tag = subtle_mac.compute_mac(data).unwrap();
+    tink_tests::expect_err(
+        subtle_mac.verify_mac_with_key_id(&tag, data),
+        "not supported",
+    );
+}
+
 #[test]
 fn test_factory_raw_key() {
     tink_mac::init();
@@ -150,3 +188,29 @@ fn test_factory_with_valid_primitive_set_type() {
 
     tink_mac::new(&good_kh).expect("calling new() with good keyset::Handle failed");
 }
+
+#[test]
+fn test_unwrapped_mac_from_key_data() {
+    tink_mac::init();
+    let kd = tink_tests::new_hmac_key_data(tink_proto::HashType::Sha256, 16);
+    let p = tink_mac::unwrapped_mac_from_key_data(&kd).unwrap();
+    let tag = p.compute_mac(b"data").unwrap();
+    assert_eq!(tag.len(), 16);
+    assert!(p.verify_mac(&tag, b"data").is_ok());
+
+    let key = tink_tests::new_key(
+        &kd,
+        tink_proto::KeyStatusType::Enabled,
+        1,
+        tink_proto::OutputPrefixType::Raw,
+    );
+    let kh = tink_core::keyset::insecure::new_handle(tink_tests::new_keyset(1, vec![key])).unwrap();
+    assert!(tink_mac::new(&kh)
+        .unwrap()
+        .verify_mac(&tag, b"data")
+        .is_ok());
+
+    let mut bad = kd;
+    bad.value = vec![];
+    assert!(tink_mac::unwrapped_mac_from_key_data(&bad).is_err());
+}
@@ -205,7 +205,9 @@ fn gen_invalid_hmac_keys() -> Vec<Vec<u8>> {
         proto_encode(&bad_version_key),
         // tag size too big
         proto_encode(&tink_tests::new_hmac_key(HashType::Sha1, 21)),
+        proto_encode(&tink_tests::new_hmac_key(HashType::Sha224, 29)),
         proto_encode(&tink_tests::new_hmac_key(HashType::Sha256, 33)),
+        proto_encode(&tink_tests::new_hmac_key(HashType::Sha384, 49)),
         proto_encode(&tink_tests::new_hmac_key(HashType::Sha512, 65)),
         // tag size too small
         proto_encode(&tink_tests::new_hmac_key(HashType::Sha256, 1)),
@@ -225,7 +227,9 @@ fn gen_invalid_hmac_key_formats() -> Vec<Vec<u8>> {
         proto_encode(&tink_tests::new_hmac_params(HashType::Sha256, 32)),
         // tag size too big
         proto_encode(&tink_tests::new_hmac_key_format(HashType::Sha1, 21)),
+        proto_encode(&tink_tests::new_hmac_key_format(HashType::Sha224, 29)),
         proto_encode(&tink_tests::new_hmac_key_format(HashType::Sha256, 33)),
+        proto_encode(&tink_tests::new_hmac_key_format(HashType::Sha384, 49)),
         proto_encode(&tink_tests::new_hmac_key_format(HashType::Sha512, 65)),
         // tag size too small
         proto_encode(&tink_tests::new_hmac_key_format(HashType::Sha256, 1)),
@@ -239,7 +243,9 @@ fn gen_invalid_hmac_key_formats() -> Vec<Vec<u8>> {
 fn gen_valid_hmac_key_formats() -> Vec<tink_proto::HmacKeyFormat> {
     vec![
         tink_tests::new_hmac_key_format(HashType::Sha1, 20),
+        tink_tests::new_hmac_key_format(HashType::Sha224, 28),
         tink_tests::new_hmac_key_format(HashType::Sha256, 32),
+        tink_tests::new_hmac_key_format(HashType::Sha384, 48),
         tink_tests::new_hmac_key_format(HashType::Sha512, 64),
     ]
 }
@@ -247,7 +253,9 @@ fn gen_valid_hmac_key_formats() -> Vec<tink_proto::HmacKeyFormat> {
 fn gen_valid_hmac_keys() -> Vec<tink_proto::HmacKey> {
     vec![
         tink_tests::new_hmac_key(HashType::Sha1, 20),
+        tink_tests::new_hmac_key(HashType::Sha224, 28),
         tink_tests::new_hmac_key(HashType::Sha256, 32),
+        tink_tests::new_hmac_key(HashType::Sha384, 48),
         tink_tests::new_hmac_key(HashType::Sha512, 64),
     ]
 }
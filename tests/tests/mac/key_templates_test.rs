@@ -127,3 +127,43 @@ fn check_cmac_template(
         .expect("AES CMAC key manager cannot create key");
     Ok(())
 }
+
+#[test]
+fn test_template_builders() {
+    assert_eq!(
+        tink_mac::hmac_sha512_tag256_key_template(),
+        tink_mac::HmacTemplateBuilder::new()
+            .key_size(64)
+            .hash(tink_proto::HashType::Sha512)
+            .build()
+            .unwrap()
+    );
+    assert_eq!(
+        tink_mac::aes_cmac_tag128_key_template(),
+        tink_mac::AesCmacTemplateBuilder::new().build().unwrap()
+    );
+
+    tink_tests::expect_err(
+        tink_mac::HmacTemplateBuilder::new().key_size(8).build(),
+        "key too short",
+    );
+    tink_tests::expect_err(
+        tink_mac::HmacTemplateBuilder::new().tag_size(8).build(),
+        "tag size too small",
+    );
+    tink_tests::expect_err(
+        tink_mac::HmacTemplateBuilder::new()
+            .hash(tink_proto::HashType::Sha1)
+            .tag_size(32)
+            .build(),
+        "tag size too big",
+    );
+    tink_tests::expect_err(
+        tink_mac::AesCmacTemplateBuilder::new().tag_size(17).build(),
+        "Tag size too long",
+    );
+    tink_tests::expect_err(
+        tink_mac::AesCmacTemplateBuilder::new().key_size(16).build(),
+        "",
+    );
+}
@@ -16,7 +16,10 @@
 
 mod aes_cmac_key_manager_test;
 mod factory_test;
+mod fixtures_test;
 mod hmac_key_manager_test;
 mod integration_test;
 mod key_templates_test;
+mod policy_test;
+mod prefix_interop_test;
 mod subtle;
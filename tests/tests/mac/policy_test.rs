@@ -0,0 +1,121 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_core::{keyset::insecure, Mac};
+use tink_proto::{key_data::KeyMaterialType, HashType, KeyStatusType, OutputPrefixType};
+use tink_tests::expect_err;
+
+fn handle_with_keys(keys: Vec<tink_proto::KeyData>) -> tink_core::keyset::Handle {
+    let keys = keys
+        .iter()
+        .enumerate()
+        .map(|(i, kd)| {
+            tink_tests::new_key(
+                kd,
+                KeyStatusType::Enabled,
+                (i + 1) as u32,
+                OutputPrefixType::Tink,
+            )
+        })
+        .collect();
+    insecure::new_handle(tink_tests::new_keyset(1, keys)).unwrap()
+}
+
+fn aes_cmac_key_data(tag_size: u32) -> tink_proto::KeyData {
+    tink_tests::new_key_data(
+        tink_mac::CMAC_TYPE_URL,
+        &tink_tests::proto_encode(&tink_tests::new_aes_cmac_key(tag_size)),
+        KeyMaterialType::Symmetric,
+    )
+}
+
+#[test]
+fn test_policy_default() {
+    tink_mac::init();
+    let policy = tink_mac::MacPolicy::default();
+    assert_eq!(policy.min_tag_size, tink_mac::DEFAULT_POLICY_MIN_TAG_SIZE);
+    assert!(!policy.allow_sha1);
+
+    let kh = tink_core::keyset::Handle::new(&tink_mac::hmac_sha256_tag128_key_template()).unwrap();
+    let p = tink_mac::new_with_policy(&kh, &policy).unwrap();
+    let tag = p.compute_mac(b"data").unwrap();
+    assert!(p.verify_mac(&tag, b"data").is_ok());
+    assert!(tink_mac::new(&kh)
+        .unwrap()
+        .verify_mac(&tag, b"data")
+        .is_ok());
+}
+
+#[test]
+fn test_policy_rejects_sha1() {
+    tink_mac::init();
+    // The SHA-1 key is not primary, but is still usable for verification.
+    let kh = handle_with_keys(vec![
+        tink_tests::new_hmac_key_data(HashType::Sha256, 16),
+        tink_tests::new_hmac_key_data(HashType::Sha1, 16),
+    ]);
+    assert!(tink_mac::new(&kh).is_ok());
+    expect_err(
+        tink_mac::new_with_policy(&kh, &tink_mac::MacPolicy::default()),
+        "SHA-1 not permitted",
+    );
+    let policy = tink_mac::MacPolicy {
+        allow_sha1: true,
+        ..Default::default()
+    };
+    assert!(tink_mac::new_with_policy(&kh, &policy).is_ok());
+}
+
+#[test]
+fn test_policy_rejects_short_hmac_tag() {
+    tink_mac::init();
+    let kh = handle_with_keys(vec![
+        tink_tests::new_hmac_key_data(HashType::Sha256, 32),
+        tink_tests::new_hmac_key_data(HashType::Sha512, 10),
+    ]);
+    expect_err(
+        tink_mac::new_with_policy(&kh, &tink_mac::MacPolicy::default()),
+        "tag size 10 below minimum 16",
+    );
+    let policy = tink_mac::MacPolicy {
+        min_tag_size: 10,
+        ..Default::default()
+    };
+    assert!(tink_mac::new_with_policy(&kh, &policy).is_ok());
+}
+
+#[test]
+fn test_policy_enforces_tag_size_at_runtime() {
+    tink_mac::init();
+    let kh = handle_with_keys(vec![aes_cmac_key_data(10)]);
+    let unchecked = tink_mac::new(&kh).unwrap();
+    let short_tag = unchecked.compute_mac(b"data").unwrap();
+    assert_eq!(
+        short_tag.len(),
+        tink_core::cryptofmt::NON_RAW_PREFIX_SIZE + 10
+    );
+
+    let p = tink_mac::new_with_policy(&kh, &tink_mac::MacPolicy::default()).unwrap();
+    expect_err(p.compute_mac(b"data"), "below minimum");
+    assert!(p.verify_mac(&short_tag, b"data").is_err());
+
+    let policy = tink_mac::MacPolicy {
+        min_tag_size: 10,
+        ..Default::default()
+    };
+    let p = tink_mac::new_with_policy(&kh, &policy).unwrap();
+    assert!(p.verify_mac(&short_tag, b"data").is_ok());
+}
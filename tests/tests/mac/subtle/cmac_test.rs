@@ -165,6 +165,49 @@ fn test_cmac_basic() {
     }
 }
 
+#[test]
+fn test_cmac_legacy_key_sizes() {
+    // Test vectors from NIST SP 800-38B, for a 16-byte message.
+    let test_cases = vec![
+        (
+            "2b7e151628aed2a6abf7158809cf4f3c",
+            "070a16b46b4d4144f79bdd9dd04a287c",
+        ),
+        (
+            "8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b",
+            "9e99a7bf31e710900662f65e617c5184",
+        ),
+        (
+            "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4",
+            "28a7023f452e8f82bd4bf28d8c37c35c",
+        ),
+    ];
+    for (key, tag) in test_cases {
+        let key = hex::decode(key).unwrap();
+        let tag = hex::decode(tag).unwrap();
+        let a = tink_mac::subtle::AesCmac::new_legacy(&key, 16).unwrap();
+        assert_eq!(a.compute_mac(&DATA_RFC4493[..16]).unwrap(), tag);
+        assert!(a.verify_mac(&tag, &DATA_RFC4493[..16]).is_ok());
+    }
+}
+
+#[test]
+fn test_validate_cmac_key_size() {
+    for key_size in &[16, 24, 32] {
+        assert!(tink_mac::subtle::validate_cmac_key_size(*key_size).is_ok());
+    }
+    for key_size in &[0, 1, 15, 17, 23, 25, 31, 33, 64] {
+        tink_tests::expect_err(
+            tink_mac::subtle::validate_cmac_key_size(*key_size),
+            "invalid AES-CMAC key size",
+        );
+        tink_tests::expect_err(
+            tink_mac::subtle::AesCmac::new_legacy(&get_random_bytes(*key_size), 16),
+            "invalid AES-CMAC key size",
+        );
+    }
+}
+
 #[test]
 fn test_new_cmac_with_invalid_input() {
     // key too short
@@ -14,6 +14,7 @@
 //
 ////////////////////////////////////////////////////////////////////////////////
 
+use super::cmac_test::TestData;
 use tink_core::{subtle::random::get_random_bytes, Mac};
 use tink_proto::HashType;
 
@@ -29,6 +30,20 @@ const KEY: &[u8] = b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b\x0c\x0d\x0
 const DATA: &[u8] = b"Hello";
 
 const HMAC_TESTS : &[TestCase] = &[
+    TestCase {
+        hash_alg:     HashType::Sha1,
+        tag_size:     20,
+        data:         DATA,
+        key:          KEY,
+        expected_mac: "c0efa6423230c55163e6c78c066903f59679ea3c",
+    },
+    TestCase {
+        hash_alg:     HashType::Sha224,
+        tag_size:     28,
+        data:         DATA,
+        key:          KEY,
+        expected_mac: "39f2ac028dec678b103dc25bcde4d1adba1f0d4bd43db98e44ebf356",
+    },
     TestCase {
         hash_alg:     HashType::Sha256,
         tag_size:     32,
@@ -36,6 +51,13 @@ const HMAC_TESTS : &[TestCase] = &[
         key:          KEY,
         expected_mac: "e0ff02553d9a619661026c7aa1ddf59b7b44eac06a9908ff9e19961d481935d4",
     },
+    TestCase {
+        hash_alg:     HashType::Sha384,
+        tag_size:     48,
+        data:         DATA,
+        key:          KEY,
+        expected_mac: "404593418e3ed5ce081381b562a276b03d70fb13568484634c986f67f1fd7637715b73c16709328ae6be50ef5f0be487",
+    },
     TestCase {
         hash_alg:     HashType::Sha512,
         tag_size:     64,
@@ -51,6 +73,13 @@ const HMAC_TESTS : &[TestCase] = &[
         key:          KEY,
         expected_mac: "07eff8b326b7798c9ccfcbdbe579489ac785a7995a04618b1a2813c26744777d",
     },
+    TestCase {
+        hash_alg:     HashType::Sha384,
+        tag_size:     24,
+        data:         &[],
+        key:          KEY,
+        expected_mac: "6a0fdc1c54c664ad91c7c157d2670c5d44e4d44ebad2359a0206974c7088b1a867f76971e6c240c33b33a66ba295bb56",
+    },
 ];
 
 #[test]
@@ -98,16 +127,125 @@ fn test_new_hmac_with_invalid_input() {
         tink_mac::subtle::Hmac::new(HashType::Sha1, &get_random_bytes(16), 21),
         "tag size too big",
     );
+    tink_tests::expect_err(
+        tink_mac::subtle::Hmac::new(HashType::Sha224, &get_random_bytes(16), 29),
+        "tag size too big",
+    );
     tink_tests::expect_err(
         tink_mac::subtle::Hmac::new(HashType::Sha256, &get_random_bytes(16), 33),
         "tag size too big",
     );
+    tink_tests::expect_err(
+        tink_mac::subtle::Hmac::new(HashType::Sha384, &get_random_bytes(16), 49),
+        "tag size too big",
+    );
     tink_tests::expect_err(
         tink_mac::subtle::Hmac::new(HashType::Sha512, &get_random_bytes(16), 65),
         "tag size too big",
     );
 }
 
+#[test]
+fn test_hmac_parameter_bounds() {
+    tink_mac::init();
+    for (hash, digest_size) in &[
+        (HashType::Sha1, 20),
+        (HashType::Sha224, 28),
+        (HashType::Sha256, 32),
+        (HashType::Sha384, 48),
+        (HashType::Sha512, 64),
+    ] {
+        // Smallest and largest permitted tag sizes, with the minimum key size.
+        for tag_size in &[10, *digest_size] {
+            let cipher = tink_mac::subtle::Hmac::new(*hash, &get_random_bytes(16), *tag_size)
+                .unwrap_or_else(|e| {
+                    panic!(
+                        "cannot create {:?} mac with tag size {}: {:?}",
+                        hash, tag_size, e
+                    )
+                });
+            let mac = cipher.compute_mac(DATA).unwrap();
+            assert_eq!(mac.len(), *tag_size);
+            assert!(cipher.verify_mac(&mac, DATA).is_ok());
+        }
+        tink_tests::expect_err(
+            tink_mac::subtle::Hmac::new(*hash, &get_random_bytes(15), *digest_size),
+            "key too short",
+        );
+        tink_tests::expect_err(
+            tink_mac::subtle::Hmac::new(*hash, &get_random_bytes(16), 9),
+            "tag size too small",
+        );
+        tink_tests::expect_err(
+            tink_mac::subtle::Hmac::new(*hash, &get_random_bytes(16), digest_size + 1),
+            "tag size too big",
+        );
+    }
+}
+
+#[test]
+fn test_hmac_wycheproof_cases() {
+    tink_mac::init();
+    for hash in &[
+        HashType::Sha1,
+        HashType::Sha224,
+        HashType::Sha256,
+        HashType::Sha384,
+        HashType::Sha512,
+    ] {
+        let hash_name = format!("{:?}", hash);
+        let filename = format!("testvectors/hmac_{}_test.json", hash_name.to_lowercase());
+        println!("wycheproof file '{}' hash {}", filename, hash_name);
+        let bytes = tink_tests::wycheproof_data(&filename);
+        let data: TestData = serde_json::from_slice(&bytes).unwrap();
+
+        for g in &data.test_groups {
+            println!(
+                "   key info: key_size={}, tag_size={}",
+                g.key_size, g.tag_size
+            );
+            assert_eq!(g.tag_size % 8, 0);
+            let tag_size = g.tag_size as usize / 8;
+            for tc in &g.tests {
+                println!(
+                    "     case {} [{}] {}",
+                    tc.case.case_id, tc.case.result, tc.case.comment
+                );
+                assert_eq!(tc.key.len() * 8, g.key_size as usize);
+
+                if tink_mac::subtle::validate_hmac_params(*hash, tc.key.len(), tag_size).is_err() {
+                    // Key or tag size is outside what Tink permits for HMAC.
+                    continue;
+                }
+                let valid = tc.case.result == tink_tests::WycheproofResult::Valid;
+                let cipher = tink_mac::subtle::Hmac::new(*hash, &tc.key, tag_size)
+                    .expect("cannot create new mac");
+                let mac = cipher.compute_mac(&tc.msg).unwrap();
+                if valid {
+                    assert_eq!(
+                        mac, tc.tag,
+                        "Computed HMAC and expected for test case {} ({}) do not match",
+                        tc.case.case_id, tc.case.comment
+                    );
+                } else {
+                    assert_ne!(
+                        mac, tc.tag,
+                        "Computed HMAC and invalid expected for test case {} ({}) match",
+                        tc.case.case_id, tc.case.comment
+                    );
+                }
+                assert_eq!(
+                    cipher.verify_mac(&tc.tag, &tc.msg).is_ok(),
+                    valid,
+                    "Unexpected verification result for test case {} ({})",
+                    tc.case.case_id,
+                    tc.case.comment
+                );
+            }
+        }
+    }
+}
+
 #[test]
 fn test_hmac_compute_verify_with_empty_input() {
     let cipher = tink_mac::subtle::Hmac::new(HashType::Sha256, &get_random_bytes(16), 32).unwrap();
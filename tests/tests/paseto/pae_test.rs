@@ -0,0 +1,60 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_paseto::{pre_auth_decode, pre_auth_encode};
+
+#[test]
+fn test_pre_auth_encode() {
+    // Test cases from the PASETO specification.
+    assert_eq!(
+        pre_auth_encode(&[]),
+        hex::decode("0000000000000000").unwrap()
+    );
+    assert_eq!(
+        pre_auth_encode(&[b""]),
+        hex::decode("01000000000000000000000000000000").unwrap()
+    );
+    assert_eq!(
+        pre_auth_encode(&[b"", b""]),
+        hex::decode("020000000000000000000000000000000000000000000000").unwrap()
+    );
+    assert_eq!(
+        pre_auth_encode(&[b"Paragon"]),
+        hex::decode("0100000000000000070000000000000050617261676f6e").unwrap()
+    );
+    assert_eq!(
+        pre_auth_encode(&[b"Paragon", b"Initiative"]),
+        hex::decode(
+            "0200000000000000070000000000000050617261676f6e0a00000000000000496e6974696174697665"
+        )
+        .unwrap()
+    );
+}
+
+#[test]
+fn test_pre_auth_decode() {
+    let pieces: &[&[u8]] = &[b"Paragon", b"", b"Initiative"];
+    let encoded = pre_auth_encode(pieces);
+    assert_eq!(pre_auth_decode(&encoded).unwrap(), pieces);
+    tink_tests::expect_err(pre_auth_decode(&[]), "truncated");
+
+    for n in 0..encoded.len() {
+        assert!(pre_auth_decode(&encoded[..n]).is_err());
+    }
+    let mut trailing = encoded;
+    trailing.push(0);
+    tink_tests::expect_err(pre_auth_decode(&trailing), "trailing data");
+}
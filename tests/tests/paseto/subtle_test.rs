@@ -0,0 +1,72 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_core::Aead;
+use tink_paseto::{pre_auth_encode, subtle::PasetoV4Local};
+use tink_tests::expect_err;
+
+const KEY: &str = "707172737475767778797a7b7c7d7e7f808182838485868788898a8b8c8d8e8f";
+
+#[test]
+fn test_v4_local_test_vector() {
+    // Test vector 4-E-1 from the PASETO specification.
+    let want = "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAQAr68PS4AXe7If_ZgesdkUMvSwscFlAl1pk5HC0e8kApeaqMfGo_7OpBnwJOAbY9V7WU6abu74MmcUE8YWAiaArVI8XJ5hOb_4v9RmDkneN0S92dx0OW4pgy7omxgf3S8c3LlQg";
+    let msg = br#"{"data":"this is a secret message","exp":"2022-01-01T00:00:00+00:00"}"#;
+    let p = PasetoV4Local::new(&hex::decode(KEY).unwrap()).unwrap();
+    let aad = pre_auth_encode(&[b"", b""]);
+    let ct = p.encrypt_with_nonce(msg, &aad, &[0u8; 32]).unwrap();
+    assert_eq!(base64::encode_config(&ct, base64::URL_SAFE_NO_PAD), want);
+    assert_eq!(p.decrypt(&ct, &aad).unwrap(), msg.to_vec());
+}
+
+#[test]
+fn test_v4_local_round_trip() {
+    let p = PasetoV4Local::new(&hex::decode(KEY).unwrap()).unwrap();
+    let aad = pre_auth_encode(&[b"footer", b"implicit"]);
+    for size in &[0usize, 1, 63, 64, 65, 1000] {
+        let pt = vec![0x42; *size];
+        let ct = p.encrypt(&pt, &aad).unwrap();
+        assert_eq!(ct.len(), 32 + size + 32);
+        assert_eq!(p.decrypt(&ct, &aad).unwrap(), pt);
+        // Nonces are random.
+        assert_ne!(ct, p.encrypt(&pt, &aad).unwrap());
+
+        let other_aad = pre_auth_encode(&[b"footer", b"other"]);
+        expect_err(p.decrypt(&ct, &other_aad), "invalid tag");
+        for i in 0..ct.len() {
+            let mut modified = ct.clone();
+            modified[i] ^= 0x01;
+            expect_err(p.decrypt(&modified, &aad), "invalid tag");
+        }
+    }
+}
+
+#[test]
+fn test_v4_local_invalid_inputs() {
+    expect_err(PasetoV4Local::new(&[0u8; 16]), "bad key length");
+    let p = PasetoV4Local::new(&hex::decode(KEY).unwrap()).unwrap();
+    let aad = pre_auth_encode(&[b"", b""]);
+    expect_err(p.encrypt(b"msg", b"not encoded"), "truncated");
+    expect_err(
+        p.encrypt(b"msg", &pre_auth_encode(&[b"footer"])),
+        "must encode footer and implicit assertion",
+    );
+    expect_err(
+        p.encrypt_with_nonce(b"msg", &aad, &[0u8; 24]),
+        "bad nonce length",
+    );
+    expect_err(p.decrypt(&[0u8; 63], &aad), "ciphertext too short");
+}
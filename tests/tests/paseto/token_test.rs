@@ -0,0 +1,252 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_core::keyset::{insecure, Handle, Manager};
+use tink_paseto::{
+    key_id_to_kid, kid_to_key_id, V4Local, V4PublicSigner, V4PublicVerifier,
+    PASETO_V4_LOCAL_TYPE_URL,
+};
+use tink_proto::{keyset::Key, KeyData, KeyStatusType, Keyset, OutputPrefixType};
+use tink_tests::{expect_err, proto_encode};
+
+// Fixtures computed independently of this library, following the PASETO v4 specification; the
+// keys are those of test vectors 4-E-1 and 4-S-1.
+const KEY_ID: tink_core::KeyId = 0x01020304;
+const LOCAL_KEY: &str = "707172737475767778797a7b7c7d7e7f808182838485868788898a8b8c8d8e8f";
+const LOCAL_TOKEN: &str = "v4.local.ICEiIyQlJicoKSorLC0uLzAxMjM0NTY3ODk6Ozw9Pj9DSuUjwMKwhG0uILFqzhYex0Odrrxa6BbPIPaGNXurB7T0y7WBnobFe8bgRlH91HQcGIH364eklfUOSvSSPNYu5rXWDVzplNCC9K5ddRECHnfOGLnkLC_3QKsONn3uzHIfVwEtiA.eyJraWQiOiJBUUlEQkEifQ";
+const LOCAL_MESSAGE: &[u8] =
+    br#"{"data":"this is a hidden message","exp":"2022-01-01T00:00:00+00:00"}"#;
+const PUBLIC_SEED: &str = "b4cbfb43df4ce210727d953e4a713307fa19bb7d9f85041438d9e11b942a3774";
+const PUBLIC_KEY: &str = "1eb9dbbbbc047c03fd70604e0071f0987e16b28b757225c11f00415d0e20b1a2";
+const PUBLIC_TOKEN: &str = "v4.public.eyJkYXRhIjoidGhpcyBpcyBhIHNpZ25lZCBtZXNzYWdlIiwiZXhwIjoiMjAyMi0wMS0wMVQwMDowMDowMCswMDowMCJ9t44hk7693U0RD39E7byudqX6iSXMTyrmaccfq2MPmss3gzTxscYRQp6D0BVrEpD0lbEDMzm06xYMz2i9sf67DA.eyJraWQiOiJBUUlEQkEifQ";
+// Test vector 4-S-1, which has no footer.
+const PUBLIC_TOKEN_NO_FOOTER: &str = "v4.public.eyJkYXRhIjoidGhpcyBpcyBhIHNpZ25lZCBtZXNzYWdlIiwiZXhwIjoiMjAyMi0wMS0wMVQwMDowMDowMCswMDowMCJ9bg_XBBzds8lTZShVlwwKSgeKpLT3yukTw6JUz3W4h_ExsQV-P0V54zemZDcAxFaSeef1QlXEFtkqxT1ciiQEDA";
+const PUBLIC_MESSAGE: &[u8] =
+    br#"{"data":"this is a signed message","exp":"2022-01-01T00:00:00+00:00"}"#;
+const FOOTER: &[u8] = br#"{"kid":"AQIDBA"}"#;
+const IMPLICIT: &[u8] = br#"{"test-vector":"4-E-3"}"#;
+
+fn fixture_handle(type_url: &str, value: Vec<u8>, material_type: i32) -> Handle {
+    insecure::new_handle(Keyset {
+        primary_key_id: KEY_ID,
+        key: vec![Key {
+            key_data: Some(KeyData {
+                type_url: type_url.to_string(),
+                value,
+                key_material_type: material_type,
+            }),
+            status: KeyStatusType::Enabled as i32,
+            key_id: KEY_ID,
+            output_prefix_type: OutputPrefixType::Raw as i32,
+        }],
+    })
+    .unwrap()
+}
+
+fn local_fixture_handle() -> Handle {
+    fixture_handle(
+        PASETO_V4_LOCAL_TYPE_URL,
+        proto_encode(&tink_proto::XChaCha20Poly1305Key {
+            version: 0,
+            key_value: hex::decode(LOCAL_KEY).unwrap(),
+        }),
+        tink_proto::key_data::KeyMaterialType::Symmetric as i32,
+    )
+}
+
+fn public_fixture_handles() -> (Handle, Handle) {
+    let public_key = tink_proto::Ed25519PublicKey {
+        version: 0,
+        key_value: hex::decode(PUBLIC_KEY).unwrap(),
+    };
+    let private_handle = fixture_handle(
+        tink_signature::ED25519_SIGNER_TYPE_URL,
+        proto_encode(&tink_proto::Ed25519PrivateKey {
+            version: 0,
+            public_key: Some(public_key.clone()),
+            key_value: hex::decode(PUBLIC_SEED).unwrap(),
+        }),
+        tink_proto::key_data::KeyMaterialType::AsymmetricPrivate as i32,
+    );
+    let public_handle = fixture_handle(
+        tink_signature::ED25519_VERIFIER_TYPE_URL,
+        proto_encode(&public_key),
+        tink_proto::key_data::KeyMaterialType::AsymmetricPublic as i32,
+    );
+    (private_handle, public_handle)
+}
+
+#[test]
+fn test_kid() {
+    assert_eq!(key_id_to_kid(KEY_ID), "AQIDBA");
+    assert_eq!(kid_to_key_id("AQIDBA"), Some(KEY_ID));
+    for key_id in &[0, 1, 0xffffffff, 0x80000000] {
+        assert_eq!(kid_to_key_id(&key_id_to_kid(*key_id)), Some(*key_id));
+    }
+    assert_eq!(kid_to_key_id("AQID"), None);
+    assert_eq!(kid_to_key_id("AQIDBAU"), None);
+    assert_eq!(kid_to_key_id("!!!"), None);
+}
+
+#[test]
+fn test_v4_local_fixture() {
+    tink_paseto::init();
+    let local = V4Local::new(&local_fixture_handle()).unwrap();
+    let decoded = local.decrypt(LOCAL_TOKEN, IMPLICIT).unwrap();
+    assert_eq!(decoded.payload, LOCAL_MESSAGE);
+    assert_eq!(decoded.footer, FOOTER);
+    assert_eq!(decoded.key_id, KEY_ID);
+
+    expect_err(local.decrypt(LOCAL_TOKEN, b""), "decryption failed");
+    let tampered_footer = format!(
+        "{}.{}",
+        LOCAL_TOKEN.rsplit_once('.').unwrap().0,
+        base64::encode_config(br#"{"kid":"AQIDBA","x":1}"#, base64::URL_SAFE_NO_PAD)
+    );
+    expect_err(
+        local.decrypt(&tampered_footer, IMPLICIT),
+        "decryption failed",
+    );
+}
+
+#[test]
+fn test_v4_public_fixture() {
+    tink_paseto::init();
+    let (private_handle, public_handle) = public_fixture_handles();
+    let verifier = V4PublicVerifier::new(&public_handle).unwrap();
+
+    // Ed25519 signatures are deterministic, so signing reproduces the fixture.
+    let signer = V4PublicSigner::new(&private_handle).unwrap();
+    assert_eq!(signer.sign(PUBLIC_MESSAGE, IMPLICIT).unwrap(), PUBLIC_TOKEN);
+
+    let decoded = verifier.verify(PUBLIC_TOKEN, IMPLICIT).unwrap();
+    assert_eq!(decoded.payload, PUBLIC_MESSAGE);
+    assert_eq!(decoded.footer, FOOTER);
+    assert_eq!(decoded.key_id, KEY_ID);
+    expect_err(verifier.verify(PUBLIC_TOKEN, b""), "verification failed");
+
+    // A token without a footer is tried against all keys.
+    let decoded = verifier.verify(PUBLIC_TOKEN_NO_FOOTER, b"").unwrap();
+    assert_eq!(decoded.payload, PUBLIC_MESSAGE);
+    assert!(decoded.footer.is_empty());
+    assert_eq!(decoded.key_id, KEY_ID);
+}
+
+#[test]
+fn test_v4_local_rotation() {
+    tink_paseto::init();
+    let mut ksm = Manager::new();
+    let old_key_id = ksm.rotate(&tink_paseto::v4_local_key_template()).unwrap();
+    let old_local = V4Local::new(&ksm.handle().unwrap()).unwrap();
+    let old_token = old_local.encrypt(b"old message", b"aud").unwrap();
+    assert!(old_token.starts_with("v4.local."));
+    assert!(old_token.ends_with(&base64::encode_config(
+        format!(r#"{{"kid":"{}"}}"#, key_id_to_kid(old_key_id)),
+        base64::URL_SAFE_NO_PAD
+    )));
+
+    let new_key_id = ksm.rotate(&tink_paseto::v4_local_key_template()).unwrap();
+    let local = V4Local::new(&ksm.handle().unwrap()).unwrap();
+    let new_token = local.encrypt(b"new message", b"aud").unwrap();
+
+    let decoded = local.decrypt(&old_token, b"aud").unwrap();
+    assert_eq!(decoded.payload, b"old message");
+    assert_eq!(decoded.key_id, old_key_id);
+    let decoded = local.decrypt(&new_token, b"aud").unwrap();
+    assert_eq!(decoded.payload, b"new message");
+    assert_eq!(decoded.key_id, new_key_id);
+    expect_err(old_local.decrypt(&new_token, b"aud"), "unknown kid");
+
+    // Once the old key is disabled, its tokens are rejected.
+    ksm.disable(old_key_id).unwrap();
+    let local = V4Local::new(&ksm.handle().unwrap()).unwrap();
+    expect_err(local.decrypt(&old_token, b"aud"), "unknown kid");
+    assert!(local.decrypt(&new_token, b"aud").is_ok());
+}
+
+#[test]
+fn test_v4_public_rotation() {
+    tink_paseto::init();
+    let mut ksm = Manager::new();
+    let old_key_id = ksm.rotate(&tink_paseto::v4_public_key_template()).unwrap();
+    let old_signer = V4PublicSigner::new(&ksm.handle().unwrap()).unwrap();
+    let old_token = old_signer.sign(b"old message", b"").unwrap();
+
+    let new_key_id = ksm.rotate(&tink_paseto::v4_public_key_template()).unwrap();
+    let h = ksm.handle().unwrap();
+    let new_token = V4PublicSigner::new(&h)
+        .unwrap()
+        .sign(b"new message", b"")
+        .unwrap();
+
+    let verifier = V4PublicVerifier::new(&h.public().unwrap()).unwrap();
+    let decoded = verifier.verify(&old_token, b"").unwrap();
+    assert_eq!(decoded.payload, b"old message");
+    assert_eq!(decoded.key_id, old_key_id);
+    let decoded = verifier.verify(&new_token, b"").unwrap();
+    assert_eq!(decoded.payload, b"new message");
+    assert_eq!(decoded.key_id, new_key_id);
+}
+
+#[test]
+fn test_invalid_tokens() {
+    tink_paseto::init();
+    let local = V4Local::new(&local_fixture_handle()).unwrap();
+    expect_err(
+        local.decrypt(PUBLIC_TOKEN, b""),
+        "does not start with v4.local.",
+    );
+    expect_err(
+        local.decrypt(&format!("{}.extra", LOCAL_TOKEN), IMPLICIT),
+        "too many token segments",
+    );
+    expect_err(local.decrypt("v4.local.!!!", b""), "invalid token payload");
+    let unknown_kid = format!(
+        "{}.{}",
+        LOCAL_TOKEN.rsplit_once('.').unwrap().0,
+        base64::encode_config(br#"{"kid":"AAAAAA"}"#, base64::URL_SAFE_NO_PAD)
+    );
+    expect_err(local.decrypt(&unknown_kid, IMPLICIT), "unknown kid");
+    let invalid_kid = format!(
+        "{}.{}",
+        LOCAL_TOKEN.rsplit_once('.').unwrap().0,
+        base64::encode_config(br#"{"kid":7}"#, base64::URL_SAFE_NO_PAD)
+    );
+    expect_err(local.decrypt(&invalid_kid, IMPLICIT), "invalid kid");
+
+    let (_, public_handle) = public_fixture_handles();
+    let verifier = V4PublicVerifier::new(&public_handle).unwrap();
+    expect_err(verifier.verify("v4.public.AAAA", b""), "token too short");
+}
+
+#[test]
+fn test_wrong_key_types() {
+    tink_paseto::init();
+    tink_aead::init();
+    let aead_handle = Handle::new(&tink_aead::x_cha_cha20_poly1305_key_template()).unwrap();
+    expect_err(V4Local::new(&aead_handle), "unsupported key type");
+
+    let ecdsa_handle = Handle::new(&tink_signature::ecdsa_p256_key_template()).unwrap();
+    expect_err(V4PublicSigner::new(&ecdsa_handle), "unsupported key type");
+
+    let (private_handle, public_handle) = public_fixture_handles();
+    expect_err(
+        V4PublicVerifier::new(&private_handle),
+        "unsupported key type",
+    );
+    expect_err(V4PublicSigner::new(&public_handle), "unsupported key type");
+    expect_err(V4Local::new(&public_handle), "unsupported key type");
+}
@@ -0,0 +1,103 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_core::keyset::{Handle, Manager};
+use tink_prf::{BlindIndex, MAX_BLIND_INDEX_TOKEN_LENGTH};
+
+fn new_prf_set(kt: &tink_proto::KeyTemplate) -> tink_prf::Set {
+    tink_prf::init();
+    tink_prf::Set::new(&Handle::new(kt).unwrap()).unwrap()
+}
+
+#[test]
+fn test_blind_index_tokens() {
+    let templates = vec![
+        tink_prf::hmac_sha256_prf_key_template(),
+        tink_prf::hkdf_sha256_prf_key_template(),
+        tink_prf::aes_cmac_prf_key_template(),
+    ];
+    for kt in &templates {
+        let prf_set = new_prf_set(kt);
+        let email = BlindIndex::new(&prf_set, b"email", 8).unwrap();
+        let phone = BlindIndex::new(&prf_set, b"phone", 8).unwrap();
+
+        let token = email.token(b"alice@example.com").unwrap();
+        assert_eq!(token.len(), 8);
+        assert_eq!(token, email.token(b"alice@example.com").unwrap());
+        assert_ne!(token, email.token(b"bob@example.com").unwrap());
+        assert_ne!(token, phone.token(b"alice@example.com").unwrap());
+
+        // Tokens of different lengths are consistent truncations.
+        let long = BlindIndex::new(&prf_set, b"email", MAX_BLIND_INDEX_TOKEN_LENGTH)
+            .unwrap()
+            .token(b"alice@example.com")
+            .unwrap();
+        assert_eq!(long.len(), MAX_BLIND_INDEX_TOKEN_LENGTH);
+        assert_eq!(&long[..8], &token[..]);
+
+        // The PRF input is the context, the length-prefixed field salt, and the value.
+        let mut input = b"tink-blind-index-v1\0".to_vec();
+        input.extend_from_slice(&[0, 0, 0, 5]);
+        input.extend_from_slice(b"email");
+        input.extend_from_slice(b"alice@example.com");
+        assert_eq!(prf_set.compute_primary_prf(&input, 8).unwrap(), token);
+    }
+}
+
+#[test]
+fn test_blind_index_salt_is_length_prefixed() {
+    let prf_set = new_prf_set(&tink_prf::hmac_sha256_prf_key_template());
+    let a = BlindIndex::new(&prf_set, b"ab", 16).unwrap();
+    let b = BlindIndex::new(&prf_set, b"a", 16).unwrap();
+    assert_ne!(a.token(b"c").unwrap(), b.token(b"bc").unwrap());
+}
+
+#[test]
+fn test_blind_index_invalid_length() {
+    let prf_set = new_prf_set(&tink_prf::hmac_sha256_prf_key_template());
+    tink_tests::expect_err(BlindIndex::new(&prf_set, b"field", 0), "token length");
+    tink_tests::expect_err(
+        BlindIndex::new(&prf_set, b"field", MAX_BLIND_INDEX_TOKEN_LENGTH + 1),
+        "token length",
+    );
+}
+
+#[test]
+fn test_blind_index_key_rotation() {
+    tink_prf::init();
+    let kt = tink_prf::hmac_sha256_prf_key_template();
+    let mut km = Manager::new_from_handle(Handle::new(&kt).unwrap());
+    let old_set = tink_prf::Set::new(&km.handle().unwrap()).unwrap();
+    let old_token = BlindIndex::new(&old_set, b"field", 8)
+        .unwrap()
+        .token(b"value")
+        .unwrap();
+    km.rotate(&kt).unwrap();
+    let new_set = tink_prf::Set::new(&km.handle().unwrap()).unwrap();
+    let index = BlindIndex::new(&new_set, b"field", 8).unwrap();
+    let new_token = index.token(b"value").unwrap();
+    assert_ne!(old_token, new_token);
+
+    let tokens = index.tokens_for_all_keys(b"value").unwrap();
+    assert_eq!(tokens.len(), 2);
+    assert!(tokens.windows(2).all(|w| w[0].0 < w[1].0));
+    assert!(tokens
+        .iter()
+        .any(|(id, t)| *id == old_set.primary_id && *t == old_token));
+    assert!(tokens
+        .iter()
+        .any(|(id, t)| *id == new_set.primary_id && *t == new_token));
+}
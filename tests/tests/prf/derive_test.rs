@@ -0,0 +1,170 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_core::keyset::Handle;
+use tink_prf::{derive_handle, derive_key_data};
+use tink_proto::prost::Message;
+
+fn init() {
+    tink_aead::init();
+    tink_mac::init();
+    tink_prf::init();
+    tink_signature::init();
+}
+
+fn new_prf_set(kt: &tink_proto::KeyTemplate) -> tink_prf::Set {
+    tink_prf::Set::new(&Handle::new(kt).unwrap()).unwrap()
+}
+
+#[test]
+fn test_derive_key_data_deterministic() {
+    init();
+    let prf_templates = vec![
+        tink_prf::hmac_sha256_prf_key_template(),
+        tink_prf::hmac_sha512_prf_key_template(),
+        tink_prf::hkdf_sha256_prf_key_template(),
+        tink_prf::aes_cmac_prf_key_template(),
+    ];
+    let key_templates = vec![
+        tink_aead::aes128_gcm_key_template(),
+        tink_aead::aes256_gcm_key_template(),
+        tink_aead::cha_cha20_poly1305_key_template(),
+        tink_mac::hmac_sha256_tag256_key_template(),
+        tink_mac::hmac_sha512_tag512_key_template(),
+        tink_signature::ed25519_key_template(),
+    ];
+    for prf_template in &prf_templates {
+        let prf_set = new_prf_set(prf_template);
+        let other_prf_set = new_prf_set(prf_template);
+        for kt in &key_templates {
+            let kd = derive_key_data(&prf_set, b"user-1", kt).unwrap();
+            assert_eq!(kt.type_url, kd.type_url);
+            let again = derive_key_data(&prf_set, b"user-1", kt).unwrap();
+            assert_eq!(
+                kd, again,
+                "derivation not deterministic for {}",
+                kt.type_url
+            );
+
+            let other_salt = derive_key_data(&prf_set, b"user-2", kt).unwrap();
+            assert_ne!(kd.value, other_salt.value);
+            let other_prf = derive_key_data(&other_prf_set, b"user-1", kt).unwrap();
+            assert_ne!(kd.value, other_prf.value);
+
+            // The derived key is usable.
+            tink_core::registry::primitive_from_key_data(&kd).unwrap();
+        }
+    }
+}
+
+#[test]
+fn test_derive_key_data_separates_templates() {
+    init();
+    let prf_set = new_prf_set(&tink_prf::hmac_sha256_prf_key_template());
+    let aes128 =
+        derive_key_data(&prf_set, b"user-1", &tink_aead::aes128_gcm_key_template()).unwrap();
+    let aes128 = tink_proto::AesGcmKey::decode(&*aes128.value).unwrap();
+    let aes256 =
+        derive_key_data(&prf_set, b"user-1", &tink_aead::aes256_gcm_key_template()).unwrap();
+    let aes256 = tink_proto::AesGcmKey::decode(&*aes256.value).unwrap();
+    let hmac = derive_key_data(
+        &prf_set,
+        b"user-1",
+        &tink_mac::hmac_sha256_tag256_key_template(),
+    )
+    .unwrap();
+    let hmac = tink_proto::HmacKey::decode(&*hmac.value).unwrap();
+
+    // Keys derived for different templates share no key material.
+    assert_eq!(aes256.key_value.len(), hmac.key_value.len());
+    assert_ne!(aes256.key_value, hmac.key_value);
+    assert_ne!(aes128.key_value[..], aes256.key_value[..16]);
+}
+
+#[test]
+fn test_derive_handle_aead() {
+    init();
+    let prf_set = new_prf_set(&tink_prf::hkdf_sha256_prf_key_template());
+    let kt = tink_aead::aes256_gcm_no_prefix_key_template();
+    let kh1 = derive_handle(&prf_set, b"user-1", &kt).unwrap();
+    let kh2 = derive_handle(&prf_set, b"user-1", &kt).unwrap();
+    let kh3 = derive_handle(&prf_set, b"user-2", &kt).unwrap();
+    let a1 = tink_aead::new(&kh1).unwrap();
+    let a2 = tink_aead::new(&kh2).unwrap();
+    let a3 = tink_aead::new(&kh3).unwrap();
+
+    let ct = a1.encrypt(b"plaintext", b"aad").unwrap();
+    assert_eq!(a2.decrypt(&ct, b"aad").unwrap(), b"plaintext");
+    assert!(a3.decrypt(&ct, b"aad").is_err());
+}
+
+#[test]
+fn test_derive_handle_signature() {
+    init();
+    let prf_set = new_prf_set(&tink_prf::hmac_sha256_prf_key_template());
+    let kt = tink_signature::ed25519_key_template();
+    let kh = derive_handle(&prf_set, b"user-1", &kt).unwrap();
+    let signer = tink_signature::new_signer(&kh).unwrap();
+    let verifier = tink_signature::new_verifier(&kh.public().unwrap()).unwrap();
+    let sig = signer.sign(b"data").unwrap();
+    assert!(verifier.verify(&sig, b"data").is_ok());
+}
+
+#[test]
+fn test_derive_unsupported_key_type() {
+    init();
+    let prf_set = new_prf_set(&tink_prf::hmac_sha256_prf_key_template());
+    let unsupported = vec![
+        tink_aead::aes128_ctr_hmac_sha256_key_template(),
+        tink_aead::x_cha_cha20_poly1305_key_template(),
+        tink_mac::aes_cmac_tag128_key_template(),
+        tink_signature::ecdsa_p256_key_template(),
+        tink_prf::hmac_sha256_prf_key_template(),
+    ];
+    for kt in &unsupported {
+        tink_tests::expect_err(
+            derive_key_data(&prf_set, b"salt", kt),
+            "key derivation not supported",
+        );
+        assert!(derive_handle(&prf_set, b"salt", kt).is_err());
+    }
+}
+
+#[test]
+fn test_supports_derivation() {
+    init();
+    for kt in &[
+        tink_aead::aes128_gcm_key_template(),
+        tink_aead::cha_cha20_poly1305_key_template(),
+        tink_mac::hmac_sha256_tag256_key_template(),
+        tink_signature::ed25519_key_template(),
+    ] {
+        let km = tink_core::registry::get_key_manager(&kt.type_url).unwrap();
+        assert!(km.supports_derivation(), "{}", kt.type_url);
+    }
+    for kt in &[
+        tink_aead::aes128_ctr_hmac_sha256_key_template(),
+        tink_signature::ecdsa_p256_key_template(),
+        tink_prf::hmac_sha256_prf_key_template(),
+    ] {
+        let km = tink_core::registry::get_key_manager(&kt.type_url).unwrap();
+        assert!(!km.supports_derivation(), "{}", kt.type_url);
+        tink_tests::expect_err(
+            tink_core::registry::derive_key_data(kt, &mut std::io::repeat(0)),
+            "key derivation not supported",
+        );
+    }
+}
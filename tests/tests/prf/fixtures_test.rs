@@ -0,0 +1,36 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn test_prf_fixtures() {
+    tink_prf::init();
+    tink_tests::check_prf_fixtures(|h, input, output_length| {
+        tink_prf::Set::new(h)?.compute_primary_prf(input, output_length)
+    });
+}
+
+#[test]
+fn test_prf_set_fixtures() {
+    tink_prf::init();
+    tink_tests::check_prf_set_fixtures(|h, key_id, input, output_length| {
+        let set = tink_prf::Set::new(h)?;
+        assert_eq!(set.prfs.len(), h.keyset_info().key_info.len());
+        set.prfs
+            .get(&key_id)
+            .ok_or_else(|| tink_core::TinkError::new("key not in prf::Set"))?
+            .compute_prf(input, output_length)
+    });
+}
@@ -15,9 +15,13 @@
 ////////////////////////////////////////////////////////////////////////////////
 
 mod aes_cmac_prf_key_manager_test;
+mod blind_index_test;
+mod derive_test;
+mod fixtures_test;
 mod hkdf_prf_key_manager_test;
 mod hmac_prf_key_manager_test;
 mod integration_test;
 mod prf_key_templates_test;
 mod set_factory_test;
+mod streaming_prf_test;
 mod subtle;
@@ -151,3 +151,45 @@ fn check_cmac_template(template: &tink_proto::KeyTemplate, key_size: u32) -> Res
     );
     Ok(())
 }
+
+#[test]
+fn test_template_builders() {
+    assert_eq!(
+        tink_prf::hmac_sha512_prf_key_template(),
+        tink_prf::HmacPrfTemplateBuilder::new()
+            .key_size(64)
+            .hash(tink_proto::HashType::Sha512)
+            .build()
+            .unwrap()
+    );
+    assert_eq!(
+        tink_prf::hkdf_sha256_prf_key_template(),
+        tink_prf::HkdfPrfTemplateBuilder::new().build().unwrap()
+    );
+    assert_eq!(
+        tink_prf::aes_cmac_prf_key_template(),
+        tink_prf::AesCmacPrfTemplateBuilder::new().build().unwrap()
+    );
+    assert!(tink_prf::HkdfPrfTemplateBuilder::new()
+        .salt(b"salt")
+        .hash(tink_proto::HashType::Sha512)
+        .build()
+        .is_ok());
+
+    tink_tests::expect_err(
+        tink_prf::HmacPrfTemplateBuilder::new().key_size(8).build(),
+        "key too short",
+    );
+    tink_tests::expect_err(
+        tink_prf::HkdfPrfTemplateBuilder::new()
+            .hash(tink_proto::HashType::Sha1)
+            .build(),
+        "",
+    );
+    tink_tests::expect_err(
+        tink_prf::AesCmacPrfTemplateBuilder::new()
+            .key_size(16)
+            .build(),
+        "",
+    );
+}
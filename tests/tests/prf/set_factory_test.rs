@@ -188,3 +188,17 @@ fn run_z_tests(results: Vec<Vec<u8>>) {
         }
     }
 }
+
+#[test]
+fn test_init_registers_prf_key_managers() {
+    tink_prf::init();
+    for type_url in &[
+        tink_prf::AES_CMAC_PRF_TYPE_URL,
+        tink_prf::HKDF_PRF_TYPE_URL,
+        tink_prf::HMAC_PRF_TYPE_URL,
+    ] {
+        let km = tink_core::registry::get_key_manager(type_url)
+            .unwrap_or_else(|e| panic!("no key manager for {}: {:?}", type_url, e));
+        assert_eq!(km.type_url(), *type_url);
+    }
+}
@@ -0,0 +1,125 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use std::io::Read;
+use tink_core::{Prf, StreamingPrf};
+use tink_proto::HashType;
+
+fn read_all(p: &dyn StreamingPrf, input: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    p.compute_prf(input).unwrap().read_to_end(&mut out).unwrap();
+    out
+}
+
+#[test]
+fn test_hkdf_streaming_prf_rfc5869_vector() {
+    // RFC 5869 Appendix A.2.
+    let ikm: Vec<u8> = (0x00..=0x4f).collect();
+    let salt: Vec<u8> = (0x60..=0xaf).collect();
+    let info: Vec<u8> = (0xb0..=0xff).collect();
+    let want = hex::decode(
+        "b11e398dc80327a1c8e7f78c596a49344f012eda2d4efad8a050cc4c19afa97c\
+         59045a99cac7827271cb41c65e590e09da3275600c2f09b8367793a9aca3db71\
+         cc30c58179ec3e87c14c01d5c1f3434f1d87",
+    )
+    .unwrap();
+    let p = tink_prf::subtle::HkdfStreamingPrf::new(HashType::Sha256, &ikm, &salt).unwrap();
+    let mut got = vec![0; want.len()];
+    p.compute_prf(&info).unwrap().read_exact(&mut got).unwrap();
+    assert_eq!(hex::encode(got), hex::encode(want));
+}
+
+#[test]
+fn test_hkdf_streaming_prf_matches_hkdf_prf() {
+    let key = vec![0x42; 32];
+    let salt = b"some salt";
+    for (hash, max_len) in &[(HashType::Sha256, 255 * 32), (HashType::Sha512, 255 * 64)] {
+        let sp = tink_prf::subtle::HkdfStreamingPrf::new(*hash, &key, salt).unwrap();
+        let p = tink_prf::subtle::HkdfPrf::new(*hash, &key, salt).unwrap();
+        let out = read_all(&sp, b"input");
+        assert_eq!(
+            out.len(),
+            *max_len,
+            "stream should end after {} bytes",
+            max_len
+        );
+        assert_eq!(out, p.compute_prf(b"input", *max_len).unwrap());
+    }
+}
+
+#[test]
+fn test_hkdf_streaming_prf_read_sizes() {
+    let p = tink_prf::subtle::HkdfStreamingPrf::new(HashType::Sha256, &[0x01; 32], &[]).unwrap();
+    let want = read_all(&p, b"input");
+    for chunk in &[1, 7, 31, 32, 33, 100] {
+        let mut r = p.compute_prf(b"input").unwrap();
+        let mut got = Vec::new();
+        let mut buf = vec![0; *chunk];
+        loop {
+            let n = r.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            got.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(got, want, "mismatch for chunk size {}", chunk);
+    }
+}
+
+#[test]
+fn test_hkdf_streaming_prf_invalid_params() {
+    assert!(tink_prf::subtle::HkdfStreamingPrf::new(HashType::Sha256, &[0x01; 31], &[]).is_err());
+    assert!(tink_prf::subtle::HkdfStreamingPrf::new(HashType::Sha1, &[0x01; 32], &[]).is_err());
+    assert!(
+        tink_prf::subtle::HkdfStreamingPrf::new(HashType::UnknownHash, &[0x01; 32], &[]).is_err()
+    );
+}
+
+#[test]
+fn test_new_streaming_prf() {
+    tink_prf::init();
+    let kh = tink_core::keyset::Handle::new(&tink_prf::hkdf_sha256_prf_key_template()).unwrap();
+    let p = tink_prf::new_streaming_prf(&kh).unwrap();
+    let out1 = read_all(p.as_ref(), b"input");
+    let out2 = read_all(p.as_ref(), b"input");
+    assert_eq!(out1, out2);
+    assert_eq!(out1.len(), 255 * 32);
+    assert_ne!(out1, read_all(p.as_ref(), b"other input"));
+
+    // The same HKDF PRF key also works as a (non-streaming) PRF, whose output is a prefix of the
+    // streaming output.
+    let prf_set = tink_prf::Set::new(&kh).unwrap();
+    assert_eq!(
+        prf_set.compute_primary_prf(b"input", 32).unwrap(),
+        out1[..32].to_vec()
+    );
+    assert!(kh.check_self_test().is_ok());
+}
+
+#[test]
+fn test_new_streaming_prf_rejects_other_keysets() {
+    tink_prf::init();
+    let kh = tink_core::keyset::Handle::new(&tink_prf::hmac_sha256_prf_key_template()).unwrap();
+    tink_tests::expect_err(tink_prf::new_streaming_prf(&kh), "not a StreamingPrf");
+
+    let mut km = tink_core::keyset::Manager::new_from_handle(
+        tink_core::keyset::Handle::new(&tink_prf::hkdf_sha256_prf_key_template()).unwrap(),
+    );
+    km.rotate(&tink_prf::hkdf_sha256_prf_key_template())
+        .unwrap();
+    let kh = km.handle().unwrap();
+    tink_tests::expect_err(tink_prf::new_streaming_prf(&kh), "single RAW key");
+}
@@ -328,6 +328,11 @@ fn test_hkdf_prf_output_length() {
                 i
             );
         }
+        assert!(
+            prf.compute_prf(&[0x01, 0x02], usize::MAX).is_err(),
+            "Expected HKDF {:?} PRF to reject huge output length without allocating",
+            hash
+        );
     }
 }
 
@@ -0,0 +1,123 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+// These tests replace the global registry state wholesale, so they live in their own test binary
+// and serialize with each other via `registry::isolate()`.
+
+use std::sync::Arc;
+use tink_core::{
+    keyset::{insecure, BinaryReader, BinaryWriter, Handle},
+    registry::{self, CleartextLoad, Gatekeeper},
+    TinkError,
+};
+use tink_tests::fakekms;
+
+const DUMMY_TYPE_URL: &str = "type.googleapis.com/snapshot.test.DummyKey";
+const FAKE_KMS_PREFIX: &str = "fake-kms://snapshot";
+const FAKE_KMS_URI: &str = "fake-kms://snapshot-key";
+
+struct DenyAll;
+
+impl Gatekeeper for DenyAll {
+    fn check(&self, _load: &CleartextLoad) -> Result<(), TinkError> {
+        Err("denied".into())
+    }
+}
+
+fn cleartext_keyset() -> Vec<u8> {
+    let h = Handle::new(&tink_mac::hmac_sha256_tag128_key_template()).unwrap();
+    let mut buf = Vec::new();
+    insecure::write(&h, &mut BinaryWriter::new(&mut buf)).unwrap();
+    buf
+}
+
+fn register_everything() {
+    registry::register_key_manager(Arc::new(tink_tests::DummyAeadKeyManager {
+        type_url: DUMMY_TYPE_URL,
+    }))
+    .unwrap();
+    registry::register_kms_client(fakekms::FakeClient::new(FAKE_KMS_PREFIX).unwrap());
+    registry::register_gatekeeper(DenyAll);
+    registry::register_template_generator("SNAPSHOT_TEST", tink_mac::aes_cmac_tag128_key_template);
+    registry::set_key_template_policy(
+        registry::KeyTemplatePolicy::new("snapshot").deny("everything", |_| true),
+    );
+}
+
+fn assert_clean(cleartext: &[u8]) {
+    assert!(registry::get_key_manager(DUMMY_TYPE_URL).is_err());
+    assert!(registry::get_kms_client(FAKE_KMS_URI).is_err());
+    assert!(registry::get_template_generator("SNAPSHOT_TEST").is_none());
+    assert!(registry::key_template_policy().is_none());
+    assert!(insecure::read(&mut BinaryReader::new(cleartext)).is_ok());
+    // State registered before the snapshot is retained.
+    assert!(registry::get_key_manager(tink_mac::HMAC_TYPE_URL).is_ok());
+    assert!(registry::get_template_generator("HMAC_SHA256_128BITTAG").is_some());
+}
+
+#[test]
+fn test_isolate_restores_on_drop() {
+    tink_mac::init();
+    let cleartext = cleartext_keyset();
+    {
+        let _guard = registry::isolate();
+        register_everything();
+        assert!(registry::get_key_manager(DUMMY_TYPE_URL).is_ok());
+        assert!(registry::get_kms_client(FAKE_KMS_URI).is_ok());
+        assert!(registry::get_template_generator("SNAPSHOT_TEST").is_some());
+        tink_tests::expect_err(
+            insecure::read(&mut BinaryReader::new(&cleartext[..])),
+            "denied",
+        );
+    }
+    assert_clean(&cleartext);
+}
+
+#[test]
+fn test_snapshot_restore() {
+    tink_mac::init();
+    let cleartext = cleartext_keyset();
+    let _guard = registry::isolate();
+    let snapshot = registry::snapshot();
+    register_everything();
+    // Re-registration is rejected until the snapshot is restored.
+    assert!(
+        registry::register_key_manager(Arc::new(tink_tests::DummyAeadKeyManager {
+            type_url: DUMMY_TYPE_URL,
+        }))
+        .is_err()
+    );
+
+    registry::restore(snapshot.clone());
+    assert_clean(&cleartext);
+    register_everything();
+    registry::restore(snapshot);
+    assert_clean(&cleartext);
+}
+
+#[test]
+fn test_isolate_restores_on_panic() {
+    tink_mac::init();
+    let cleartext = cleartext_keyset();
+    let result = std::panic::catch_unwind(|| {
+        let _guard = registry::isolate();
+        register_everything();
+        panic!("test failure");
+    });
+    assert!(result.is_err());
+    let _guard = registry::isolate();
+    assert_clean(&cleartext);
+}
@@ -0,0 +1,117 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use std::io::{Read, Write};
+use tink_tests::SharedBuf;
+
+#[test]
+fn test_shared_buf_round_trip() {
+    let buf = SharedBuf::new();
+    let mut w = buf.clone();
+    w.write_all(b"hello world").unwrap();
+    assert_eq!(buf.contents(), b"hello world");
+    let mut out = Vec::new();
+    buf.clone().read_to_end(&mut out).unwrap();
+    assert_eq!(out, b"hello world");
+    assert_eq!(buf.bytes_written(), 11);
+    assert_eq!(buf.bytes_read(), 11);
+    assert!(buf.contents().is_empty());
+}
+
+#[test]
+fn test_shared_buf_short_operations() {
+    let mut buf = SharedBuf::new().with_short_writes(3).with_short_reads(2);
+    assert_eq!(buf.write(b"hello").unwrap(), 3);
+    buf.write_all(b"lo").unwrap();
+    assert_eq!(buf.contents(), b"hello");
+
+    let mut out = [0u8; 8];
+    assert_eq!(buf.read(&mut out).unwrap(), 2);
+    assert_eq!(&out[..2], b"he");
+    let mut rest = Vec::new();
+    buf.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, b"llo");
+}
+
+#[test]
+fn test_shared_buf_injected_failures() {
+    let mut buf = SharedBuf::new().fail_writes_after(4);
+    let err = buf.write_all(b"hello").unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("injected write failure after 4 bytes"));
+    assert_eq!(buf.contents(), b"hell");
+
+    let mut buf = SharedBuf::with_contents(b"hello".to_vec()).fail_reads_after(3);
+    let mut out = Vec::new();
+    let err = buf.read_to_end(&mut out).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("injected read failure after 3 bytes"));
+    assert_eq!(out, b"hel");
+
+    // Failures only trigger once the threshold is reached.
+    let mut buf = SharedBuf::with_contents(b"hi".to_vec()).fail_reads_after(3);
+    let mut out = Vec::new();
+    buf.read_to_end(&mut out).unwrap();
+    assert_eq!(out, b"hi");
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn test_shared_buf_async() {
+    use futures_io::{AsyncRead, AsyncWrite};
+    use std::{
+        pin::Pin,
+        task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+    };
+
+    // The buffer never returns `Poll::Pending`, so a waker that does nothing suffices.
+    fn noop_raw_waker() -> RawWaker {
+        fn clone(_: *const ()) -> RawWaker {
+            noop_raw_waker()
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+    let waker = unsafe { Waker::from_raw(noop_raw_waker()) };
+    let mut cx = Context::from_waker(&waker);
+
+    let mut buf = SharedBuf::new().with_short_writes(2).fail_reads_after(3);
+    match Pin::new(&mut buf).poll_write(&mut cx, b"hello") {
+        Poll::Ready(Ok(2)) => {}
+        _ => panic!("unexpected poll_write result"),
+    }
+    match Pin::new(&mut buf).poll_write(&mut cx, b"llo") {
+        Poll::Ready(Ok(2)) => {}
+        _ => panic!("unexpected poll_write result"),
+    }
+    assert!(matches!(
+        Pin::new(&mut buf).poll_close(&mut cx),
+        Poll::Ready(Ok(()))
+    ));
+
+    let mut out = [0u8; 8];
+    match Pin::new(&mut buf).poll_read(&mut cx, &mut out) {
+        Poll::Ready(Ok(3)) => assert_eq!(&out[..3], b"hel"),
+        _ => panic!("unexpected poll_read result"),
+    }
+    match Pin::new(&mut buf).poll_read(&mut cx, &mut out) {
+        Poll::Ready(Err(_)) => {}
+        _ => panic!("expected injected failure"),
+    }
+}
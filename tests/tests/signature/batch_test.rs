@@ -0,0 +1,161 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_core::keyset::{insecure, Handle};
+use tink_proto::{prost::Message, OutputPrefixType};
+
+/// Create a signer for each of the given templates and output prefix types, together with a
+/// verifier for a keyset holding all of the corresponding public keys.
+fn new_signers(
+    keys: &[(tink_proto::KeyTemplate, OutputPrefixType)],
+) -> (
+    Vec<Box<dyn tink_core::Signer>>,
+    Box<dyn tink_core::Verifier>,
+) {
+    tink_signature::init();
+    let mut signers = Vec::new();
+    let mut pub_keys = Vec::new();
+    for (template, prefix_type) in keys {
+        let mut ks = insecure::keyset_material(&Handle::new(template).unwrap());
+        ks.key[0].output_prefix_type = *prefix_type as i32;
+        let kh = insecure::new_handle(ks).unwrap();
+        signers.push(tink_signature::new_signer(&kh).unwrap());
+        let pub_ks = insecure::keyset_material(&kh.public().unwrap());
+        pub_keys.extend(pub_ks.key);
+    }
+    let primary_key_id = pub_keys[0].key_id;
+    let pub_handle =
+        insecure::new_handle(tink_tests::new_keyset(primary_key_id, pub_keys)).unwrap();
+    (signers, tink_signature::new_verifier(&pub_handle).unwrap())
+}
+
+/// Sign `count` distinct messages with each of the `signers`, returning `(signature, message)`
+/// pairs.
+fn sign_messages(signers: &[Box<dyn tink_core::Signer>], count: usize) -> Vec<(Vec<u8>, Vec<u8>)> {
+    let mut ret = Vec::new();
+    for (i, signer) in signers.iter().enumerate() {
+        for j in 0..count {
+            let msg = format!("log entry {} from signer {}", j, i).into_bytes();
+            ret.push((signer.sign(&msg).unwrap(), msg));
+        }
+    }
+    ret
+}
+
+fn as_batch(items: &[(Vec<u8>, Vec<u8>)]) -> Vec<(&[u8], &[u8])> {
+    items
+        .iter()
+        .map(|(sig, msg)| (&sig[..], &msg[..]))
+        .collect()
+}
+
+#[test]
+fn test_verify_batch_ed25519() {
+    let (signers, verifier) = new_signers(&[
+        (
+            tink_signature::ed25519_key_template(),
+            OutputPrefixType::Tink,
+        ),
+        (
+            tink_signature::ed25519_key_template(),
+            OutputPrefixType::Legacy,
+        ),
+        (
+            tink_signature::ed25519_key_template(),
+            OutputPrefixType::Crunchy,
+        ),
+        (
+            tink_signature::ed25519_key_template(),
+            OutputPrefixType::Raw,
+        ),
+    ]);
+    let items = sign_messages(&signers, 10);
+    assert!(verifier.verify_batch(&as_batch(&items)).is_ok());
+    assert!(verifier.verify_batch(&[]).is_ok());
+
+    for i in [0, 15, 25, 39] {
+        let mut bad_items = items.clone();
+        bad_items[i].1.push(b'!');
+        assert!(
+            verifier.verify_batch(&as_batch(&bad_items)).is_err(),
+            "batch with modified message {} unexpectedly verified",
+            i
+        );
+        let mut bad_items = items.clone();
+        let last = bad_items[i].0.len() - 1;
+        bad_items[i].0[last] ^= 0x01;
+        assert!(
+            verifier.verify_batch(&as_batch(&bad_items)).is_err(),
+            "batch with modified signature {} unexpectedly verified",
+            i
+        );
+    }
+}
+
+#[test]
+fn test_verify_batch_mixed_key_types() {
+    let (signers, verifier) = new_signers(&[
+        (
+            tink_signature::ed25519_key_template(),
+            OutputPrefixType::Tink,
+        ),
+        (
+            tink_signature::ecdsa_p256_key_template(),
+            OutputPrefixType::Tink,
+        ),
+        (
+            tink_signature::ecdsa_p256_key_template(),
+            OutputPrefixType::Raw,
+        ),
+    ]);
+    let items = sign_messages(&signers, 3);
+    assert!(verifier.verify_batch(&as_batch(&items)).is_ok());
+
+    let mut bad_items = items;
+    bad_items[4].1.push(b'!');
+    assert!(verifier.verify_batch(&as_batch(&bad_items)).is_err());
+
+    // A signature from a key outside the keyset fails the batch.
+    let (other_signers, _) = new_signers(&[(
+        tink_signature::ed25519_key_template(),
+        OutputPrefixType::Tink,
+    )]);
+    let mut items = sign_messages(&signers, 1);
+    items.extend(sign_messages(&other_signers, 1));
+    assert!(verifier.verify_batch(&as_batch(&items)).is_err());
+}
+
+#[test]
+fn test_verify_batch_subtle_ed25519() {
+    tink_signature::init();
+    let kh = Handle::new(&tink_signature::ed25519_key_without_prefix_template()).unwrap();
+    let pub_ks = insecure::keyset_material(&kh.public().unwrap());
+    let pub_key =
+        tink_proto::Ed25519PublicKey::decode(&pub_ks.key[0].key_data.as_ref().unwrap().value[..])
+            .unwrap();
+    let verifier = tink_signature::subtle::Ed25519Verifier::new(&pub_key.key_value).unwrap();
+    let signers = vec![tink_signature::new_signer(&kh).unwrap()];
+    let items = sign_messages(&signers, 5);
+    assert!(tink_core::Verifier::verify_batch(&verifier, &as_batch(&items)).is_ok());
+
+    let mut bad_items = items.clone();
+    bad_items[2].1.push(b'!');
+    assert!(tink_core::Verifier::verify_batch(&verifier, &as_batch(&bad_items)).is_err());
+
+    let mut short_items = items;
+    short_items[3].0.pop();
+    assert!(tink_core::Verifier::verify_batch(&verifier, &as_batch(&short_items)).is_err());
+}
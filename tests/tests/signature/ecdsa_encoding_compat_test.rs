@@ -0,0 +1,189 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use std::sync::Arc;
+use tink_core::{subtle::random::get_random_bytes, Signer, Verifier};
+use tink_proto::{prost::Message, EcdsaSignatureEncoding, EllipticCurveType, HashType};
+use tink_signature::{
+    subtle,
+    subtle::{EcdsaEncodingCounters, EcdsaEncodingStats, EcdsaPrivateKey, EcdsaPublicKey},
+};
+
+/// Create signers and verifiers in both encodings for a single fresh P-256 key.
+fn new_key_pair() -> (
+    subtle::EcdsaSigner,
+    subtle::EcdsaSigner,
+    subtle::EcdsaVerifier,
+    subtle::EcdsaVerifier,
+) {
+    let mut csprng = p256::elliptic_curve::rand_core::OsRng {};
+    let secret_key = p256::ecdsa::SigningKey::random(&mut csprng);
+    let signer = |encoding| {
+        subtle::EcdsaSigner::new_from_private_key(
+            HashType::Sha256,
+            EllipticCurveType::NistP256,
+            encoding,
+            EcdsaPrivateKey::new::<p256::NistP256>(
+                p256::ecdsa::SigningKey::from_bytes(&secret_key.to_bytes()).unwrap(),
+            ),
+        )
+        .unwrap()
+    };
+    let verifier = |encoding| {
+        subtle::EcdsaVerifier::new_from_public_key(
+            HashType::Sha256,
+            EllipticCurveType::NistP256,
+            encoding,
+            EcdsaPublicKey::new::<p256::NistP256>(p256::ecdsa::VerifyingKey::from(&secret_key)),
+        )
+        .unwrap()
+    };
+    (
+        signer(EcdsaSignatureEncoding::Der),
+        signer(EcdsaSignatureEncoding::IeeeP1363),
+        verifier(EcdsaSignatureEncoding::Der),
+        verifier(EcdsaSignatureEncoding::IeeeP1363),
+    )
+}
+
+#[test]
+fn test_default_verifier_rejects_other_encoding() {
+    let (der_signer, p1363_signer, der_verifier, p1363_verifier) = new_key_pair();
+    let data = get_random_bytes(20);
+    let der_sig = der_signer.sign(&data).unwrap();
+    let p1363_sig = p1363_signer.sign(&data).unwrap();
+
+    assert!(der_verifier.verify(&der_sig, &data).is_ok());
+    assert!(p1363_verifier.verify(&p1363_sig, &data).is_ok());
+    assert!(der_verifier.verify(&p1363_sig, &data).is_err());
+    assert!(p1363_verifier.verify(&der_sig, &data).is_err());
+}
+
+#[test]
+fn test_any_encoding_verifier() {
+    let (der_signer, p1363_signer, der_verifier, p1363_verifier) = new_key_pair();
+    for verifier in vec![der_verifier, p1363_verifier] {
+        let counters = Arc::new(EcdsaEncodingCounters::default());
+        let verifier = verifier
+            .with_any_encoding(true)
+            .with_encoding_counters(counters.clone());
+        for _ in 0..3 {
+            let data = get_random_bytes(20);
+            let der_sig = der_signer.sign(&data).unwrap();
+            assert!(verifier.verify(&der_sig, &data).is_ok());
+        }
+        let data = get_random_bytes(20);
+        let p1363_sig = p1363_signer.sign(&data).unwrap();
+        assert!(verifier.verify(&p1363_sig, &data).is_ok());
+
+        // Failures are not counted.
+        assert!(verifier.verify(&p1363_sig, b"other data").is_err());
+        assert!(verifier.verify(&[0x30, 0x00], &data).is_err());
+
+        assert_eq!(
+            counters.stats(),
+            EcdsaEncodingStats {
+                der: 3,
+                ieee_p1363: 1,
+            }
+        );
+    }
+}
+
+#[test]
+fn test_counters_without_any_encoding() {
+    let (der_signer, p1363_signer, der_verifier, _) = new_key_pair();
+    let counters = Arc::new(EcdsaEncodingCounters::default());
+    let verifier = der_verifier.with_encoding_counters(counters.clone());
+    let data = get_random_bytes(20);
+    assert!(verifier
+        .verify(&der_signer.sign(&data).unwrap(), &data)
+        .is_ok());
+    assert!(verifier
+        .verify(&p1363_signer.sign(&data).unwrap(), &data)
+        .is_err());
+    assert_eq!(
+        counters.stats(),
+        EcdsaEncodingStats {
+            der: 1,
+            ieee_p1363: 0,
+        }
+    );
+}
+
+#[test]
+fn test_any_encoding_with_strict() {
+    let (_, p1363_signer, der_verifier, _) = new_key_pair();
+    let verifier = der_verifier.with_any_encoding(true).with_strict(true);
+    let p1363_signer = p1363_signer.with_low_s(true);
+    let data = get_random_bytes(20);
+    assert!(verifier
+        .verify(&p1363_signer.sign(&data).unwrap(), &data)
+        .is_ok());
+}
+
+#[test]
+fn test_wrapped_any_encoding_verifier() {
+    tink_signature::init();
+    let kh_priv =
+        tink_core::keyset::Handle::new(&tink_signature::ecdsa_p256_key_template()).unwrap();
+    let kh_pub = kh_priv.public().unwrap();
+
+    // Build an IEEE-P1363 signer from the same key material as the (DER) keyset.
+    let ks = tink_core::keyset::insecure::keyset_material(&kh_priv);
+    let key = &ks.key[0];
+    let priv_key =
+        tink_proto::EcdsaPrivateKey::decode(key.key_data.as_ref().unwrap().value.as_ref()).unwrap();
+    let p1363_signer = subtle::EcdsaSigner::new(
+        HashType::Sha256,
+        EllipticCurveType::NistP256,
+        EcdsaSignatureEncoding::IeeeP1363,
+        &priv_key.key_value,
+    )
+    .unwrap();
+    let prefix = tink_core::cryptofmt::output_prefix(key).unwrap();
+
+    let der_signer = tink_signature::new_signer(&kh_priv).unwrap();
+    let data = get_random_bytes(20);
+    let der_sig = der_signer.sign(&data).unwrap();
+    let mut p1363_sig = prefix.clone();
+    p1363_sig.extend_from_slice(&p1363_signer.sign(&data).unwrap());
+
+    let verifier = tink_signature::new_verifier(&kh_pub).unwrap();
+    assert!(verifier.verify(&der_sig, &data).is_ok());
+    assert!(verifier.verify(&p1363_sig, &data).is_err());
+
+    let counters = Arc::new(EcdsaEncodingCounters::default());
+    let verifier = tink_signature::new_verifier_with_ecdsa_options(
+        &kh_pub,
+        tink_signature::EcdsaVerifierOptions {
+            any_encoding: true,
+            encoding_counters: Some(counters.clone()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert!(verifier.verify(&der_sig, &data).is_ok());
+    assert!(verifier.verify(&p1363_sig, &data).is_ok());
+    assert!(verifier.verify(&p1363_sig[prefix.len()..], &data).is_err());
+    assert_eq!(
+        counters.stats(),
+        EcdsaEncodingStats {
+            der: 1,
+            ieee_p1363: 1,
+        }
+    );
+}
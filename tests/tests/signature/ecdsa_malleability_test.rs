@@ -0,0 +1,215 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use p256::ecdsa::signature::Signature as _;
+use tink_core::{subtle::random::get_random_bytes, Signer, Verifier};
+use tink_proto::{EcdsaSignatureEncoding, EllipticCurveType, HashType};
+use tink_signature::{
+    subtle,
+    subtle::{EcdsaPrivateKey, EcdsaPublicKey},
+};
+
+const NIST_P256_ORDER: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xbc, 0xe6, 0xfa, 0xad, 0xa7, 0x17, 0x9e, 0x84, 0xf3, 0xb9, 0xca, 0xc2, 0xfc, 0x63, 0x25, 0x51,
+];
+
+/// Replace `s` with `n - s` in an IEEE-P1363 P-256 signature, giving the other valid signature.
+fn negate_s(signature: &[u8]) -> Vec<u8> {
+    let mut out = signature.to_vec();
+    let mut borrow = 0u16;
+    for i in (0..32).rev() {
+        let diff = 0x100 + NIST_P256_ORDER[i] as u16 - signature[32 + i] as u16 - borrow;
+        out[32 + i] = diff as u8;
+        borrow = if diff < 0x100 { 1 } else { 0 };
+    }
+    out
+}
+
+fn p1363_to_der(signature: &[u8]) -> Vec<u8> {
+    p256::ecdsa::Signature::from_bytes(signature)
+        .unwrap()
+        .to_der()
+        .as_bytes()
+        .to_vec()
+}
+
+fn der_to_p1363(signature: &[u8]) -> Vec<u8> {
+    p256::ecdsa::Signature::from_der(signature)
+        .unwrap()
+        .as_bytes()
+        .to_vec()
+}
+
+fn new_signer_verifier(
+    encoding: EcdsaSignatureEncoding,
+) -> (subtle::EcdsaSigner, subtle::EcdsaVerifier) {
+    let mut csprng = p256::elliptic_curve::rand_core::OsRng {};
+    let secret_key = p256::ecdsa::SigningKey::random(&mut csprng);
+    let public_key = p256::ecdsa::VerifyingKey::from(&secret_key);
+    let signer = subtle::EcdsaSigner::new_from_private_key(
+        HashType::Sha256,
+        EllipticCurveType::NistP256,
+        encoding,
+        EcdsaPrivateKey::new::<p256::NistP256>(secret_key),
+    )
+    .unwrap();
+    let verifier = subtle::EcdsaVerifier::new_from_public_key(
+        HashType::Sha256,
+        EllipticCurveType::NistP256,
+        encoding,
+        EcdsaPublicKey::new::<p256::NistP256>(public_key),
+    )
+    .unwrap();
+    (signer, verifier)
+}
+
+#[test]
+fn test_normalize_s() {
+    let (signer, _) = new_signer_verifier(EcdsaSignatureEncoding::IeeeP1363);
+    let signer = signer.with_low_s(true);
+    let data = get_random_bytes(20);
+    let low = signer.sign(&data).unwrap();
+    assert!(subtle::nist_p256_is_low_s(&low));
+
+    let high = negate_s(&low);
+    assert!(!subtle::nist_p256_is_low_s(&high));
+    let mut normalized = high.clone();
+    subtle::nist_p256_normalize_s(&mut normalized).unwrap();
+    assert_eq!(normalized, low);
+
+    // Already-normalized signatures are left alone.
+    let mut unchanged = low.clone();
+    subtle::nist_p256_normalize_s(&mut unchanged).unwrap();
+    assert_eq!(unchanged, low);
+
+    assert!(subtle::nist_p256_normalize_s(&mut vec![0u8; 63]).is_err());
+    assert!(!subtle::nist_p256_is_low_s(&low[..63]));
+}
+
+#[test]
+fn test_low_s_signer() {
+    for encoding in &[
+        EcdsaSignatureEncoding::Der,
+        EcdsaSignatureEncoding::IeeeP1363,
+    ] {
+        let (signer, verifier) = new_signer_verifier(*encoding);
+        let signer = signer.with_low_s(true);
+        let strict_verifier = verifier.clone().with_strict(true);
+        for _ in 0..32 {
+            let data = get_random_bytes(20);
+            let signature = signer.sign(&data).unwrap();
+            let raw = match encoding {
+                EcdsaSignatureEncoding::Der => der_to_p1363(&signature),
+                _ => signature.clone(),
+            };
+            assert!(
+                subtle::nist_p256_is_low_s(&raw),
+                "{:?} signature not normalized",
+                encoding
+            );
+            assert!(verifier.verify(&signature, &data).is_ok());
+            assert!(strict_verifier.verify(&signature, &data).is_ok());
+        }
+    }
+}
+
+#[test]
+fn test_strict_verifier_rejects_high_s() {
+    for encoding in &[
+        EcdsaSignatureEncoding::Der,
+        EcdsaSignatureEncoding::IeeeP1363,
+    ] {
+        let (signer, verifier) = new_signer_verifier(*encoding);
+        let signer = signer.with_low_s(true);
+        let strict_verifier = verifier.clone().with_strict(true);
+        let data = get_random_bytes(20);
+        let signature = signer.sign(&data).unwrap();
+        let high = match encoding {
+            EcdsaSignatureEncoding::Der => p1363_to_der(&negate_s(&der_to_p1363(&signature))),
+            _ => negate_s(&signature),
+        };
+
+        assert!(
+            verifier.verify(&high, &data).is_ok(),
+            "{:?}: default verifier should accept high-S signature",
+            encoding
+        );
+        tink_tests::expect_err(strict_verifier.verify(&high, &data), "high S");
+        assert!(strict_verifier.verify(&signature, &data).is_ok());
+    }
+}
+
+#[test]
+fn test_strict_verifier_rejects_non_canonical_der() {
+    let (signer, verifier) = new_signer_verifier(EcdsaSignatureEncoding::Der);
+    let strict_verifier = verifier.with_strict(true);
+    let signer = signer.with_low_s(true);
+    let data = get_random_bytes(20);
+    let signature = signer.sign(&data).unwrap();
+    assert!(signature.len() < 0x80);
+
+    // Re-encode the outer SEQUENCE length in (non-minimal) long form.
+    let mut long_form = vec![signature[0], 0x81];
+    long_form.extend_from_slice(&signature[1..]);
+    assert!(strict_verifier.verify(&long_form, &data).is_err());
+
+    // Trailing data after the SEQUENCE.
+    let mut trailing = signature.clone();
+    trailing.push(0);
+    assert!(strict_verifier.verify(&trailing, &data).is_err());
+}
+
+#[test]
+fn test_wrapped_low_s_signer_and_strict_verifier() {
+    tink_signature::init();
+    let kh_priv =
+        tink_core::keyset::Handle::new(&tink_signature::ecdsa_p256_key_template()).unwrap();
+    let kh_pub = kh_priv.public().unwrap();
+
+    let signer = tink_signature::new_signer_with_low_s(&kh_priv).unwrap();
+    let verifier = tink_signature::new_verifier(&kh_pub).unwrap();
+    let strict_verifier = tink_signature::new_verifier_with_strict_ecdsa(&kh_pub).unwrap();
+
+    let prefix_len = tink_core::cryptofmt::NON_RAW_PREFIX_SIZE;
+    for _ in 0..16 {
+        let data = get_random_bytes(20);
+        let signature = signer.sign(&data).unwrap();
+        assert!(subtle::nist_p256_is_low_s(&der_to_p1363(
+            &signature[prefix_len..]
+        )));
+        assert!(strict_verifier.verify(&signature, &data).is_ok());
+
+        let mut high = signature[..prefix_len].to_vec();
+        high.extend_from_slice(&p1363_to_der(&negate_s(&der_to_p1363(
+            &signature[prefix_len..],
+        ))));
+        assert!(verifier.verify(&high, &data).is_ok());
+        assert!(strict_verifier.verify(&high, &data).is_err());
+    }
+}
+
+#[test]
+fn test_wrapped_strict_verifier_ignores_other_key_types() {
+    tink_signature::init();
+    let kh_priv = tink_core::keyset::Handle::new(&tink_signature::ed25519_key_template()).unwrap();
+    let kh_pub = kh_priv.public().unwrap();
+    let signer = tink_signature::new_signer_with_low_s(&kh_priv).unwrap();
+    let strict_verifier = tink_signature::new_verifier_with_strict_ecdsa(&kh_pub).unwrap();
+    let data = get_random_bytes(20);
+    let signature = signer.sign(&data).unwrap();
+    assert!(strict_verifier.verify(&signature, &data).is_ok());
+}
@@ -194,3 +194,38 @@ fn test_primitive_with_invalid_key() {
         );
     }
 }
+
+#[test]
+fn test_ecdsa_verify_get_primitive_repeated() {
+    tink_signature::init();
+    let signer_km = tink_core::registry::get_key_manager(tink_tests::ECDSA_SIGNER_TYPE_URL)
+        .expect("cannot obtain EcdsaSigner key manager");
+    let km = tink_core::registry::get_key_manager(tink_tests::ECDSA_VERIFIER_TYPE_URL)
+        .expect("cannot obtain EcdsaVerifier key manager");
+    let data = b"some data to sign";
+    let mut keys = Vec::new();
+    for _ in 0..2 {
+        let priv_key =
+            tink_tests::new_random_ecdsa_private_key(HashType::Sha256, EllipticCurveType::NistP256);
+        let signer = match signer_km.primitive(&tink_tests::proto_encode(&priv_key)) {
+            Ok(tink_core::Primitive::Signer(p)) => p,
+            _ => panic!("failed to create signer"),
+        };
+        let sig = signer.sign(data).unwrap();
+        let serialized_pub_key = tink_tests::proto_encode(priv_key.public_key.as_ref().unwrap());
+        keys.push((serialized_pub_key, sig));
+    }
+    // Obtaining the primitive repeatedly for the same key gives equivalent verifiers, and
+    // different keys give different verifiers.
+    for _ in 0..3 {
+        for (i, (serialized_pub_key, _)) in keys.iter().enumerate() {
+            let verifier = match km.primitive(serialized_pub_key) {
+                Ok(tink_core::Primitive::Verifier(p)) => p,
+                _ => panic!("failed to create verifier"),
+            };
+            for (j, (_, sig)) in keys.iter().enumerate() {
+                assert_eq!(i == j, verifier.verify(sig, data).is_ok());
+            }
+        }
+    }
+}
@@ -0,0 +1,120 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_signature::{parse_attached, sign_and_encode, verify_and_decode, SignatureEncoding};
+
+fn new_signer_verifier() -> (Box<dyn tink_core::Signer>, Box<dyn tink_core::Verifier>) {
+    tink_signature::init();
+    let kh = tink_core::keyset::Handle::new(&tink_signature::ed25519_key_template()).unwrap();
+    let signer = tink_signature::new_signer(&kh).unwrap();
+    let verifier = tink_signature::new_verifier(&kh.public().unwrap()).unwrap();
+    (signer, verifier)
+}
+
+#[test]
+fn test_detached_round_trip() {
+    let (signer, verifier) = new_signer_verifier();
+    let payload = b"this data needs to be signed";
+    let encoded = sign_and_encode(&*signer, payload, SignatureEncoding::Detached).unwrap();
+    // A detached signature is just the output of the signer.
+    assert!(verifier.verify(&encoded, payload).is_ok());
+
+    let decoded = verify_and_decode(
+        &*verifier,
+        &encoded,
+        SignatureEncoding::Detached,
+        Some(&payload[..]),
+    )
+    .unwrap();
+    assert_eq!(decoded, payload.to_vec());
+
+    let result = verify_and_decode(
+        &*verifier,
+        &encoded,
+        SignatureEncoding::Detached,
+        Some(&b"other data"[..]),
+    );
+    tink_tests::expect_err(result, "verification failed");
+    let result = verify_and_decode(&*verifier, &encoded, SignatureEncoding::Detached, None);
+    tink_tests::expect_err(result, "requires payload");
+}
+
+#[test]
+fn test_attached_round_trip() {
+    let (signer, verifier) = new_signer_verifier();
+    for payload in &[&b""[..], b"x", b"this data needs to be signed"] {
+        let encoded = sign_and_encode(&*signer, payload, SignatureEncoding::Attached).unwrap();
+        let parsed = parse_attached(&encoded).unwrap();
+        assert_eq!(parsed.payload, *payload);
+        assert!(verifier.verify(parsed.signature, payload).is_ok());
+        // The signature carries the Tink output prefix of the signing key.
+        assert_eq!(parsed.signature[0], tink_core::cryptofmt::TINK_START_BYTE);
+
+        let decoded =
+            verify_and_decode(&*verifier, &encoded, SignatureEncoding::Attached, None).unwrap();
+        assert_eq!(decoded, payload.to_vec());
+
+        let result = verify_and_decode(
+            &*verifier,
+            &encoded,
+            SignatureEncoding::Attached,
+            Some(*payload),
+        );
+        tink_tests::expect_err(result, "unexpected payload");
+    }
+}
+
+#[test]
+fn test_attached_modified() {
+    let (signer, verifier) = new_signer_verifier();
+    let encoded = sign_and_encode(&*signer, b"payload", SignatureEncoding::Attached).unwrap();
+
+    for i in 0..encoded.len() {
+        let mut modified = encoded.clone();
+        modified[i] ^= 0x01;
+        assert!(
+            verify_and_decode(&*verifier, &modified, SignatureEncoding::Attached, None).is_err(),
+            "modified byte {} accepted",
+            i
+        );
+    }
+    for len in 0..encoded.len() {
+        assert!(
+            verify_and_decode(
+                &*verifier,
+                &encoded[..len],
+                SignatureEncoding::Attached,
+                None
+            )
+            .is_err(),
+            "truncated signature of len {} accepted",
+            len
+        );
+    }
+}
+
+#[test]
+fn test_parse_attached_invalid() {
+    tink_tests::expect_err(parse_attached(&[0x01, 0x00]), "too short");
+    tink_tests::expect_err(
+        parse_attached(&[0x02, 0x00, 0x00, 0x00, 0x00]),
+        "unknown attached signature version",
+    );
+    tink_tests::expect_err(
+        parse_attached(&[0x01, 0x00, 0x00, 0x00, 0x02, 0xaa]),
+        "invalid payload length",
+    );
+}
@@ -0,0 +1,132 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_core::keyset::{insecure, public_key_fingerprint, Handle, FINGERPRINT_SIZE};
+use tink_proto::{prost::Message, KeyData};
+use tink_tests::{expect_err, proto_encode};
+
+fn first_key_data(h: &Handle) -> KeyData {
+    insecure::keyset_material(h).key[0]
+        .key_data
+        .clone()
+        .unwrap()
+}
+
+#[test]
+fn test_fingerprint_private_matches_public() {
+    tink_signature::init();
+    for template in &[
+        tink_signature::ecdsa_p256_key_template(),
+        tink_signature::ed25519_key_template(),
+    ] {
+        let priv_handle = Handle::new(template).unwrap();
+        let pub_handle = priv_handle.public().unwrap();
+
+        let priv_infos = priv_handle.keyset_info_with_fingerprints().unwrap();
+        let pub_infos = pub_handle.keyset_info_with_fingerprints().unwrap();
+        assert_eq!(priv_infos.len(), 1);
+        assert_eq!(pub_infos.len(), 1);
+        let fingerprint = priv_infos[0].fingerprint.clone().unwrap();
+        assert_eq!(fingerprint.len(), FINGERPRINT_SIZE);
+        assert_eq!(Some(fingerprint.clone()), pub_infos[0].fingerprint);
+        assert_eq!(priv_infos[0].info.key_id, pub_infos[0].info.key_id);
+        assert_eq!(
+            fingerprint,
+            public_key_fingerprint(&first_key_data(&pub_handle)).unwrap()
+        );
+
+        // A different key has a different fingerprint.
+        let other = Handle::new(template).unwrap();
+        let other_infos = other.keyset_info_with_fingerprints().unwrap();
+        assert_ne!(Some(fingerprint), other_infos[0].fingerprint);
+    }
+}
+
+#[test]
+fn test_fingerprint_independent_of_key_id() {
+    tink_signature::init();
+    let pub_handle = Handle::new(&tink_signature::ed25519_key_template())
+        .unwrap()
+        .public()
+        .unwrap();
+    let mut ks = insecure::keyset_material(&pub_handle);
+    let want = pub_handle.keyset_info_with_fingerprints().unwrap()[0]
+        .fingerprint
+        .clone();
+
+    ks.key[0].key_id ^= 0xffff;
+    ks.primary_key_id = ks.key[0].key_id;
+    ks.key[0].output_prefix_type = tink_proto::OutputPrefixType::Raw as i32;
+    let renumbered = insecure::new_handle(ks).unwrap();
+    let infos = renumbered.keyset_info_with_fingerprints().unwrap();
+    assert_ne!(
+        infos[0].info.key_id,
+        pub_handle.keyset_info().key_info[0].key_id
+    );
+    assert_eq!(want, infos[0].fingerprint);
+}
+
+#[test]
+fn test_fingerprint_ignores_coordinate_padding() {
+    tink_signature::init();
+    let pub_handle = Handle::new(&tink_signature::ecdsa_p256_key_template())
+        .unwrap()
+        .public()
+        .unwrap();
+    let key_data = first_key_data(&pub_handle);
+    let want = public_key_fingerprint(&key_data).unwrap();
+
+    let mut key = tink_proto::EcdsaPublicKey::decode(key_data.value.as_ref()).unwrap();
+    key.x.insert(0, 0);
+    key.y.insert(0, 0);
+    let padded = KeyData {
+        value: proto_encode(&key),
+        ..key_data.clone()
+    };
+    assert_eq!(want, public_key_fingerprint(&padded).unwrap());
+
+    // Changing the key parameters changes the fingerprint.
+    let mut key = tink_proto::EcdsaPublicKey::decode(key_data.value.as_ref()).unwrap();
+    key.params.as_mut().unwrap().hash_type = tink_proto::HashType::Sha512 as i32;
+    let other_params = KeyData {
+        value: proto_encode(&key),
+        ..key_data
+    };
+    assert_ne!(want, public_key_fingerprint(&other_params).unwrap());
+}
+
+#[test]
+fn test_fingerprint_unsupported_keys() {
+    tink_signature::init();
+    tink_mac::init();
+    let priv_handle = Handle::new(&tink_signature::ed25519_key_template()).unwrap();
+    expect_err(
+        public_key_fingerprint(&first_key_data(&priv_handle)),
+        "not a public key",
+    );
+
+    let mac_handle = Handle::new(&tink_mac::hmac_sha256_tag256_key_template()).unwrap();
+    let infos = mac_handle.keyset_info_with_fingerprints().unwrap();
+    assert_eq!(infos.len(), 1);
+    assert!(infos[0].fingerprint.is_none());
+
+    let unknown = KeyData {
+        type_url: "type.googleapis.com/google.crypto.tink.UnknownPublicKey".to_string(),
+        value: vec![],
+        key_material_type: tink_proto::key_data::KeyMaterialType::AsymmetricPublic as i32,
+    };
+    expect_err(public_key_fingerprint(&unknown), "unsupported key type");
+}
@@ -0,0 +1,121 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_core::{registry::KeyManager, subtle::random::get_random_bytes};
+use tink_proto::prost::Message;
+use tink_tests::fakekms;
+
+fn register_signing_key_uri(template: &tink_proto::KeyTemplate) -> String {
+    tink_signature::init();
+    let key_uri = fakekms::new_key_uri_with_template(template).unwrap();
+    tink_core::registry::register_kms_client(fakekms::FakeClient::new(&key_uri).unwrap());
+    key_uri
+}
+
+#[test]
+fn test_kms_signer_keyset() {
+    for template in &[
+        tink_signature::ecdsa_p256_key_template(),
+        tink_signature::ed25519_key_template(),
+    ] {
+        let key_uri = register_signing_key_uri(template);
+        let kh_priv =
+            tink_core::keyset::Handle::new(&tink_signature::kms_signer_key_template(&key_uri))
+                .unwrap();
+        let info = kh_priv.keyset_info();
+        assert_eq!(
+            info.key_info[0].type_url,
+            tink_signature::KMS_SIGNER_TYPE_URL
+        );
+        let kh_pub = kh_priv.public().unwrap();
+
+        let signer = tink_signature::new_signer(&kh_priv).unwrap();
+        let verifier = tink_signature::new_verifier(&kh_pub).unwrap();
+        let data = get_random_bytes(20);
+        let signature = signer.sign(&data).unwrap();
+        assert!(verifier.verify(&signature, &data).is_ok());
+        assert!(verifier.verify(&signature, b"other data").is_err());
+    }
+}
+
+#[test]
+fn test_kms_signer_key_manager_no_signing_support() {
+    tink_signature::init();
+    tink_aead::init();
+    let key_uri = fakekms::new_key_uri().unwrap();
+    tink_core::registry::register_kms_client(fakekms::FakeClient::new(&key_uri).unwrap());
+    tink_tests::expect_err(
+        tink_core::keyset::Handle::new(&tink_signature::kms_signer_key_template(&key_uri)),
+        "does not support signing",
+    );
+}
+
+#[test]
+fn test_kms_signer_key_manager_invalid_keys() {
+    let key_uri = register_signing_key_uri(&tink_signature::ecdsa_p256_key_template());
+    let km = tink_core::registry::get_key_manager(tink_signature::KMS_SIGNER_TYPE_URL).unwrap();
+    assert_eq!(
+        km.key_material_type(),
+        tink_proto::key_data::KeyMaterialType::Remote
+    );
+    assert!(km.supports_private_keys());
+
+    let format = tink_proto::KmsSignerKeyFormat {
+        key_uri: key_uri.clone(),
+    };
+    let serialized_key = km.new_key(&tink_tests::proto_encode(&format)).unwrap();
+    let key = tink_proto::KmsSignerPrivateKey::decode(serialized_key.as_ref()).unwrap();
+    assert!(km.primitive(&serialized_key).is_ok());
+
+    assert!(km.primitive(&[]).is_err());
+    assert!(km.new_key(&[]).is_err());
+    assert!(km
+        .new_key(&tink_tests::proto_encode(&tink_proto::KmsSignerKeyFormat {
+            key_uri: String::new(),
+        }))
+        .is_err());
+
+    let mut bad_version = key.clone();
+    bad_version.version += 1;
+    assert!(km
+        .primitive(&tink_tests::proto_encode(&bad_version))
+        .is_err());
+
+    let mut no_public_key = key.clone();
+    no_public_key.public_key_data = None;
+    tink_tests::expect_err(
+        km.primitive(&tink_tests::proto_encode(&no_public_key)),
+        "no public key",
+    );
+    tink_tests::expect_err(
+        km.public_key_data(&tink_tests::proto_encode(&no_public_key)),
+        "no public key",
+    );
+
+    let mut private_public_key = key.clone();
+    if let Some(kd) = private_public_key.public_key_data.as_mut() {
+        kd.key_material_type = tink_proto::key_data::KeyMaterialType::AsymmetricPrivate as i32;
+    }
+    assert!(km
+        .primitive(&tink_tests::proto_encode(&private_public_key))
+        .is_err());
+
+    let mut unknown_uri = key;
+    unknown_uri.key_uri = "unknown-kms://key".to_string();
+    assert!(km
+        .primitive(&tink_tests::proto_encode(&unknown_uri))
+        .is_err());
+}
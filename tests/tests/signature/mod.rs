@@ -16,11 +16,23 @@
 
 pub mod common;
 
+mod batch_test;
+mod ecdsa_encoding_compat_test;
+mod ecdsa_malleability_test;
 mod ecdsa_signer_key_manager_test;
 mod ecdsa_verifier_key_manager_test;
 mod ed25519_signer_key_manager_test;
 mod ed25519_verifier_key_manager_test;
+mod envelope_test;
+mod fingerprint_test;
+mod fixtures_test;
 mod integration_test;
+mod kms_signer_key_manager_test;
+mod multisig_test;
+mod openssh_test;
+mod pkcs8_test;
+mod prefix_interop_test;
 mod signature_factory_test;
 mod signature_key_templates_test;
+mod signed_keyset_test;
 mod subtle;
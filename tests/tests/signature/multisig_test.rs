@@ -0,0 +1,112 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_core::keyset::{insecure, Handle};
+use tink_signature::{new_threshold_verifier, parse_multi, sign_multi};
+
+/// Create `n` single-key signers, together with a handle for a keyset holding all of their public
+/// keys.
+fn new_signers(n: usize) -> (Vec<Box<dyn tink_core::Signer>>, Handle) {
+    tink_signature::init();
+    let mut signers = Vec::new();
+    let mut pub_keys = Vec::new();
+    for i in 0..n {
+        let template = if i % 2 == 0 {
+            tink_signature::ed25519_key_template()
+        } else {
+            tink_signature::ecdsa_p256_key_template()
+        };
+        let kh = Handle::new(&template).unwrap();
+        signers.push(tink_signature::new_signer(&kh).unwrap());
+        let pub_ks = insecure::keyset_material(&kh.public().unwrap());
+        pub_keys.extend(pub_ks.key);
+    }
+    let primary_key_id = pub_keys[0].key_id;
+    let pub_handle =
+        insecure::new_handle(tink_tests::new_keyset(primary_key_id, pub_keys)).unwrap();
+    (signers, pub_handle)
+}
+
+#[test]
+fn test_threshold_verify() {
+    let (signers, pub_handle) = new_signers(3);
+    let data = b"release artifact";
+    let verifier = new_threshold_verifier(&pub_handle, 2).unwrap();
+
+    let all: Vec<&dyn tink_core::Signer> = signers.iter().map(|s| &**s).collect();
+    let sig = sign_multi(&all, data).unwrap();
+    assert_eq!(3, parse_multi(&sig).unwrap().len());
+    assert!(verifier.verify(&sig, data).is_ok());
+    assert!(verifier.verify(&sig, b"other data").is_err());
+
+    let two = sign_multi(&[&*signers[0], &*signers[2]], data).unwrap();
+    assert!(verifier.verify(&two, data).is_ok());
+
+    let one = sign_multi(&[&*signers[1]], data).unwrap();
+    tink_tests::expect_err(verifier.verify(&one, data), "insufficient valid signatures");
+
+    // Repeated signatures from the same key only count once.
+    let repeated = sign_multi(&[&*signers[1], &*signers[1]], data).unwrap();
+    tink_tests::expect_err(
+        verifier.verify(&repeated, data),
+        "insufficient valid signatures",
+    );
+
+    // Signatures from unknown keys are ignored.
+    let (others, _) = new_signers(1);
+    let unknown = sign_multi(&[&*signers[0], &*others[0]], data).unwrap();
+    tink_tests::expect_err(
+        verifier.verify(&unknown, data),
+        "insufficient valid signatures",
+    );
+}
+
+#[test]
+fn test_threshold_verifier_invalid_threshold() {
+    let (_, pub_handle) = new_signers(2);
+    tink_tests::expect_err(new_threshold_verifier(&pub_handle, 0), "invalid threshold");
+    tink_tests::expect_err(new_threshold_verifier(&pub_handle, 3), "invalid threshold");
+    assert!(new_threshold_verifier(&pub_handle, 2).is_ok());
+}
+
+#[test]
+fn test_threshold_verifier_raw_keys() {
+    tink_signature::init();
+    let kh = Handle::new(&tink_signature::ed25519_key_without_prefix_template()).unwrap();
+    tink_tests::expect_err(
+        new_threshold_verifier(&kh.public().unwrap(), 1),
+        "RAW output prefix not supported",
+    );
+}
+
+#[test]
+fn test_parse_multi_invalid() {
+    let (signers, pub_handle) = new_signers(1);
+    let verifier = new_threshold_verifier(&pub_handle, 1).unwrap();
+    let sig = sign_multi(&[&*signers[0]], b"data").unwrap();
+    for len in 0..sig.len() {
+        assert!(parse_multi(&sig[..len]).is_err());
+        assert!(verifier.verify(&sig[..len], b"data").is_err());
+    }
+    let mut trailing = sig.clone();
+    trailing.push(0);
+    tink_tests::expect_err(parse_multi(&trailing), "trailing data");
+    tink_tests::expect_err(
+        parse_multi(&[0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00]),
+        "invalid signature count",
+    );
+    assert_eq!(0, parse_multi(&[0, 0, 0, 0]).unwrap().len());
+}
@@ -0,0 +1,59 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+// The LEGACY and CRUNCHY output prefixes are the same, but for LEGACY keys the signature covers
+// the message followed by a zero byte.  The fixtures (from testdata/fixtures/signature.json, which
+// records its generator) use the same key for both, so each signature must be rejected by the
+// other keyset.
+
+fn fixture(name: &str) -> tink_tests::Fixture {
+    tink_tests::fixtures("signature")
+        .into_iter()
+        .find(|f| f.name == name)
+        .unwrap_or_else(|| panic!("no fixture {}", name))
+}
+
+fn new_verifier(f: &tink_tests::Fixture) -> Box<dyn tink_core::Verifier> {
+    tink_signature::new_verifier(f.public_keyset.as_ref().unwrap()).unwrap()
+}
+
+#[test]
+fn test_signature_legacy_and_crunchy_are_distinct() {
+    tink_signature::init();
+    let legacy = fixture("ED25519/LEGACY");
+    let crunchy = fixture("ED25519/CRUNCHY");
+    assert_eq!(legacy.input, crunchy.input);
+    assert_eq!(
+        legacy.output[..tink_core::cryptofmt::NON_RAW_PREFIX_SIZE],
+        crunchy.output[..tink_core::cryptofmt::NON_RAW_PREFIX_SIZE]
+    );
+    assert_ne!(legacy.output, crunchy.output);
+
+    let legacy_verifier = new_verifier(&legacy);
+    let crunchy_verifier = new_verifier(&crunchy);
+    assert!(legacy_verifier
+        .verify(&legacy.output, &legacy.input)
+        .is_ok());
+    assert!(crunchy_verifier
+        .verify(&crunchy.output, &crunchy.input)
+        .is_ok());
+    assert!(crunchy_verifier
+        .verify(&legacy.output, &legacy.input)
+        .is_err());
+    assert!(legacy_verifier
+        .verify(&crunchy.output, &crunchy.input)
+        .is_err());
+}
@@ -247,3 +247,59 @@ fn test_factory_with_valid_primitive_set_type() {
         "calling new_verifier() with good keyset::handle failed"
     );
 }
+
+#[test]
+fn test_verify_with_key_id() {
+    tink_signature::init();
+    let mut ksm = tink_core::keyset::Manager::new();
+    let old_key_id = ksm.rotate(&tink_signature::ed25519_key_template()).unwrap();
+    let old_signer = tink_signature::new_signer(&ksm.handle().unwrap()).unwrap();
+    let new_key_id = ksm.rotate(&tink_signature::ed25519_key_template()).unwrap();
+    let priv_handle = ksm.handle().unwrap();
+    let signer = tink_signature::new_signer(&priv_handle).unwrap();
+    let verifier = tink_signature::new_verifier(&priv_handle.public().unwrap()).unwrap();
+
+    let data = get_random_bytes(20);
+    let sig = signer.sign(&data).unwrap();
+    assert_eq!(
+        verifier.verify_with_key_id(&sig, &data).unwrap(),
+        new_key_id
+    );
+    let old_sig = old_signer.sign(&data).unwrap();
+    assert_eq!(
+        verifier.verify_with_key_id(&old_sig, &data).unwrap(),
+        old_key_id
+    );
+    assert!(verifier.verify_with_key_id(&sig, b"other data").is_err());
+}
+
+#[test]
+fn test_unwrapped_signer_verifier_from_key_data() {
+    tink_signature::init();
+    let kh = tink_core::keyset::Handle::new(&tink_signature::ed25519_key_template()).unwrap();
+    let priv_kd = tink_core::keyset::insecure::keyset_material(&kh).key[0]
+        .key_data
+        .clone()
+        .unwrap();
+    let pub_kd = tink_core::keyset::insecure::keyset_material(&kh.public().unwrap()).key[0]
+        .key_data
+        .clone()
+        .unwrap();
+
+    let signer = tink_signature::unwrapped_signer_from_key_data(&priv_kd).unwrap();
+    let verifier = tink_signature::unwrapped_verifier_from_key_data(&pub_kd).unwrap();
+    let data = get_random_bytes(20);
+    let sig = signer.sign(&data).unwrap();
+    // No output prefix.
+    assert_eq!(sig.len(), 64);
+    assert!(verifier.verify(&sig, &data).is_ok());
+
+    tink_tests::expect_err(
+        tink_signature::unwrapped_signer_from_key_data(&pub_kd),
+        "not a Signer primitive",
+    );
+    tink_tests::expect_err(
+        tink_signature::unwrapped_verifier_from_key_data(&priv_kd),
+        "not a Verifier primitive",
+    );
+}
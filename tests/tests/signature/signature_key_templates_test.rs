@@ -29,12 +29,12 @@ fn test_key_templates() {
             "ECDSA_P384",
             #[allow(deprecated)]
             tink_signature::ecdsa_p384_key_template(),
-            false,
+            true,
         ),
         (
             "ECDSA_P384_SHA384",
             tink_signature::ecdsa_p384_sha384_key_template(),
-            false,
+            true,
         ),
         (
             "ECDSA_P521",
@@ -76,7 +76,7 @@ fn test_no_prefix_key_templates() {
         (
             "ECDSA_P384",
             tink_signature::ecdsa_p384_key_without_prefix_template(),
-            false,
+            true,
         ),
         (
             "ECDSA_P521",
@@ -111,3 +111,46 @@ fn test_sign_verify(template: &tink_proto::KeyTemplate) -> Result<(), TinkError>
     let verifier = tink_signature::new_verifier(&public_handle).unwrap();
     verifier.verify(&sig, &msg[..])
 }
+
+#[test]
+fn test_ecdsa_template_builder() {
+    assert_eq!(
+        tink_signature::ecdsa_p256_key_template(),
+        tink_signature::EcdsaTemplateBuilder::new().build().unwrap()
+    );
+    assert_eq!(
+        tink_signature::ecdsa_p384_sha384_key_template(),
+        tink_signature::EcdsaTemplateBuilder::new()
+            .curve(tink_proto::EllipticCurveType::NistP384)
+            .hash(tink_proto::HashType::Sha384)
+            .build()
+            .unwrap()
+    );
+    assert_eq!(
+        tink_signature::ecdsa_p256_raw_key_template(),
+        tink_signature::EcdsaTemplateBuilder::new()
+            .encoding(tink_proto::EcdsaSignatureEncoding::IeeeP1363)
+            .prefix(tink_proto::OutputPrefixType::Raw)
+            .build()
+            .unwrap()
+    );
+
+    tink_tests::expect_err(
+        tink_signature::EcdsaTemplateBuilder::new()
+            .hash(tink_proto::HashType::Sha512)
+            .build(),
+        "expect SHA-256",
+    );
+    tink_tests::expect_err(
+        tink_signature::EcdsaTemplateBuilder::new()
+            .curve(tink_proto::EllipticCurveType::Curve25519)
+            .build(),
+        "unsupported curve",
+    );
+    tink_tests::expect_err(
+        tink_signature::EcdsaTemplateBuilder::new()
+            .encoding(tink_proto::EcdsaSignatureEncoding::UnknownEncoding)
+            .build(),
+        "unsupported encoding",
+    );
+}
@@ -0,0 +1,199 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use tink_core::keyset::{FixedClock, Handle};
+use tink_signature::{
+    sign_and_encode, sign_public_keyset, sign_public_keyset_at, verify_signed_public_keyset,
+    verify_signed_public_keyset_chain, FreshnessPolicy, SignatureEncoding,
+};
+
+fn new_signature_handle() -> Handle {
+    tink_signature::init();
+    Handle::new(&tink_signature::ed25519_key_template()).unwrap()
+}
+
+#[test]
+fn test_signed_public_keyset_round_trip() {
+    tink_hybrid::init();
+    let distribution = new_signature_handle();
+    let signer = tink_signature::new_signer(&distribution).unwrap();
+    let verifier = tink_signature::new_verifier(&distribution.public().unwrap()).unwrap();
+
+    let hybrid = Handle::new(&tink_hybrid::ecies_hkdf_aes128_gcm_key_template()).unwrap();
+    let signed = sign_public_keyset(&hybrid.public().unwrap(), &*signer, 7).unwrap();
+
+    let verified =
+        verify_signed_public_keyset(&signed, &*verifier, &FreshnessPolicy::default()).unwrap();
+    assert_eq!(verified.serial(), 7);
+    let public = verified.into_handle();
+    assert_eq!(public.keyset_info(), hybrid.public().unwrap().keyset_info());
+    let enc = tink_hybrid::new_encrypt(&public).unwrap();
+    let dec = tink_hybrid::new_decrypt(&hybrid).unwrap();
+    let ct = enc.encrypt(b"plaintext", b"context").unwrap();
+    assert_eq!(dec.decrypt(&ct, b"context").unwrap(), b"plaintext");
+}
+
+#[test]
+fn test_signed_public_keyset_rejects_invalid() {
+    tink_hybrid::init();
+    let distribution = new_signature_handle();
+    let signer = tink_signature::new_signer(&distribution).unwrap();
+    let verifier = tink_signature::new_verifier(&distribution.public().unwrap()).unwrap();
+    let hybrid = Handle::new(&tink_hybrid::ecies_hkdf_aes128_gcm_key_template()).unwrap();
+
+    // Secret key material is never signed for distribution.
+    tink_tests::expect_err(sign_public_keyset(&hybrid, &*signer, 1), "cannot serialize");
+
+    let signed = sign_public_keyset(&hybrid.public().unwrap(), &*signer, 1).unwrap();
+    let policy = FreshnessPolicy::default();
+    for i in 0..signed.len() {
+        let mut modified = signed.clone();
+        modified[i] ^= 0x01;
+        assert!(
+            verify_signed_public_keyset(&modified, &*verifier, &policy).is_err(),
+            "modified byte {}",
+            i
+        );
+    }
+
+    // A different distribution key does not verify.
+    let other = new_signature_handle();
+    let other_verifier = tink_signature::new_verifier(&other.public().unwrap()).unwrap();
+    tink_tests::expect_err(
+        verify_signed_public_keyset(&signed, &*other_verifier, &policy),
+        "verification failed",
+    );
+
+    // Other data signed by the distribution key is not accepted as a keyset.
+    let mut ks = Vec::new();
+    hybrid
+        .public()
+        .unwrap()
+        .write_with_no_secrets(&mut tink_core::keyset::BinaryWriter::new(&mut ks))
+        .unwrap();
+    let bare = sign_and_encode(&*signer, &ks, SignatureEncoding::Attached).unwrap();
+    tink_tests::expect_err(
+        verify_signed_public_keyset(&bare, &*verifier, &policy),
+        "not a signed public keyset",
+    );
+}
+
+#[test]
+fn test_signed_public_keyset_chain() {
+    tink_hybrid::init();
+    let root = new_signature_handle();
+    let root_signer = tink_signature::new_signer(&root).unwrap();
+    let root_verifier = tink_signature::new_verifier(&root.public().unwrap()).unwrap();
+    let distribution = new_signature_handle();
+    let distribution_signer = tink_signature::new_signer(&distribution).unwrap();
+    let hybrid = Handle::new(&tink_hybrid::ecies_hkdf_aes128_gcm_key_template()).unwrap();
+
+    let signed_distribution =
+        sign_public_keyset(&distribution.public().unwrap(), &*root_signer, 1).unwrap();
+    let signed_hybrid =
+        sign_public_keyset(&hybrid.public().unwrap(), &*distribution_signer, 5).unwrap();
+
+    let policy = FreshnessPolicy {
+        min_serial: 5,
+        ..FreshnessPolicy::default()
+    };
+    let public = verify_signed_public_keyset_chain(
+        &[&signed_distribution[..], &signed_hybrid[..]],
+        &*root_verifier,
+        &policy,
+    )
+    .unwrap();
+    assert_eq!(public.serial(), 5);
+    assert_eq!(
+        public.handle().keyset_info(),
+        hybrid.public().unwrap().keyset_info()
+    );
+
+    tink_tests::expect_err(
+        verify_signed_public_keyset_chain(&[&signed_hybrid[..]], &*root_verifier, &policy),
+        "chain element 0",
+    );
+    tink_tests::expect_err(
+        verify_signed_public_keyset_chain(
+            &[&signed_distribution[..], &signed_distribution[..]],
+            &*root_verifier,
+            &policy,
+        ),
+        "chain element 1",
+    );
+    tink_tests::expect_err(
+        verify_signed_public_keyset_chain(&[], &*root_verifier, &policy),
+        "empty chain",
+    );
+}
+
+#[test]
+fn test_signed_public_keyset_freshness() {
+    tink_hybrid::init();
+    let distribution = new_signature_handle();
+    let signer = tink_signature::new_signer(&distribution).unwrap();
+    let verifier = tink_signature::new_verifier(&distribution.public().unwrap()).unwrap();
+    let hybrid = Handle::new(&tink_hybrid::ecies_hkdf_aes128_gcm_key_template()).unwrap();
+
+    let issued_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+    let signed = sign_public_keyset_at(&hybrid.public().unwrap(), &*signer, 3, issued_at).unwrap();
+    let clock = Arc::new(FixedClock::new(issued_at + Duration::from_secs(60)));
+
+    let verified = verify_signed_public_keyset(
+        &signed,
+        &*verifier,
+        &FreshnessPolicy {
+            min_serial: 3,
+            max_age: Some(Duration::from_secs(60)),
+            clock: Some(clock.clone()),
+        },
+    )
+    .unwrap();
+    assert_eq!(verified.serial(), 3);
+    assert_eq!(verified.issued_at(), issued_at);
+
+    // An older keyset is rejected once a newer serial has been seen.
+    tink_tests::expect_err(
+        verify_signed_public_keyset(
+            &signed,
+            &*verifier,
+            &FreshnessPolicy {
+                min_serial: 4,
+                ..FreshnessPolicy::default()
+            },
+        ),
+        "below the minimum",
+    );
+
+    // A keyset is rejected once it is older than the maximum age.
+    clock.set(issued_at + Duration::from_secs(61));
+    tink_tests::expect_err(
+        verify_signed_public_keyset(
+            &signed,
+            &*verifier,
+            &FreshnessPolicy {
+                min_serial: 0,
+                max_age: Some(Duration::from_secs(60)),
+                clock: Some(clock),
+            },
+        ),
+        "expired",
+    );
+}
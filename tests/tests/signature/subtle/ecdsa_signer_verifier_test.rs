@@ -37,35 +37,18 @@ fn test_sign_verify() {
         EcdsaSignatureEncoding::IeeeP1363,
     ];
     for encoding in encodings {
-        let (priv_key, pub_key) = match curve {
-            EllipticCurveType::NistP256 => {
-                let secret_key = p256::ecdsa::SigningKey::random(&mut csprng);
-                let public_key = p256::ecdsa::VerifyingKey::from(&secret_key);
-                (
-                    EcdsaPrivateKey::NistP256(secret_key),
-                    EcdsaPublicKey::NistP256(public_key),
-                )
-            }
-            _ => panic!("unsupported curve {:?}", curve),
-        };
-        let priv_key_bytes = match &priv_key {
-            EcdsaPrivateKey::NistP256(secret_key) => secret_key.to_bytes().to_vec(),
-        };
-        let (pub_x, pub_y) = match &pub_key {
-            EcdsaPublicKey::NistP256(public_key) => {
-                let point_len = elliptic_curve::FieldSize::<p256::NistP256>::to_usize();
-                let pub_key_point = public_key.to_encoded_point(/* compress= */ false);
-                let pub_key_data = pub_key_point.as_bytes();
-                assert_eq!(
-                    pub_key_data[0],
-                    tink_signature::ECDSA_UNCOMPRESSED_POINT_PREFIX
-                );
-                (
-                    pub_key_data[1..point_len + 1].to_vec(),
-                    pub_key_data[point_len + 1..].to_vec(),
-                )
-            }
-        };
+        let secret_key = p256::ecdsa::SigningKey::random(&mut csprng);
+        let public_key = p256::ecdsa::VerifyingKey::from(&secret_key);
+        let priv_key = EcdsaPrivateKey::new::<p256::NistP256>(secret_key);
+        let pub_key = EcdsaPublicKey::new::<p256::NistP256>(public_key);
+        assert_eq!(priv_key.curve(), curve);
+        assert_eq!(pub_key.curve(), curve);
+        let priv_key_bytes = priv_key.to_bytes();
+        let (pub_x, pub_y) = pub_key.coordinates().unwrap();
+        assert_eq!(
+            priv_key.public_key().coordinates().unwrap(),
+            (pub_x.clone(), pub_y.clone())
+        );
 
         // Use the private key and public key directly to create new instances
         let signer = tink_signature::subtle::EcdsaSigner::new_from_private_key(
@@ -97,6 +80,158 @@ fn test_sign_verify() {
     }
 }
 
+#[test]
+fn test_ecdsa_curve_generic_keys() {
+    let priv_key = EcdsaPrivateKey::generate(EllipticCurveType::NistP256).unwrap();
+    let priv_key2 =
+        EcdsaPrivateKey::from_bytes(EllipticCurveType::NistP256, &priv_key.to_bytes()).unwrap();
+    assert_eq!(priv_key.to_bytes(), priv_key2.to_bytes());
+    let (x, y) = priv_key.public_key().coordinates().unwrap();
+    let pub_key = EcdsaPublicKey::from_coordinates(EllipticCurveType::NistP256, &x, &y).unwrap();
+    assert_eq!(pub_key.curve(), EllipticCurveType::NistP256);
+
+    let signer = subtle::EcdsaSigner::new_from_private_key(
+        HashType::Sha256,
+        EllipticCurveType::NistP256,
+        EcdsaSignatureEncoding::Der,
+        priv_key2,
+    )
+    .unwrap();
+    let verifier = subtle::EcdsaVerifier::new_from_public_key(
+        HashType::Sha256,
+        EllipticCurveType::NistP256,
+        EcdsaSignatureEncoding::Der,
+        pub_key.clone(),
+    )
+    .unwrap();
+    let data = get_random_bytes(20);
+    verifier
+        .verify(&signer.sign(&data).unwrap(), &data)
+        .unwrap();
+
+    // Keys must match the curve of the parameters.
+    let result = subtle::EcdsaSigner::new_from_private_key(
+        HashType::Sha384,
+        EllipticCurveType::NistP384,
+        EcdsaSignatureEncoding::Der,
+        priv_key,
+    );
+    tink_tests::expect_err(result, "does not support");
+    let result = subtle::EcdsaVerifier::new_from_public_key(
+        HashType::Sha512,
+        EllipticCurveType::NistP521,
+        EcdsaSignatureEncoding::Der,
+        pub_key,
+    );
+    tink_tests::expect_err(result, "does not support");
+
+    tink_tests::expect_err(
+        EcdsaPrivateKey::generate(EllipticCurveType::NistP521),
+        "unsupported curve",
+    );
+    tink_tests::expect_err(
+        EcdsaPrivateKey::from_bytes(EllipticCurveType::UnknownCurve, &[1; 32]),
+        "unsupported curve",
+    );
+}
+
+#[test]
+fn test_ecdsa_p384_hashes() {
+    use sha2::Digest;
+    use subtle::{DigestSigner, DigestVerifier};
+    let curve = EllipticCurveType::NistP384;
+    let data = get_random_bytes(20);
+    for (hash, digest) in vec![
+        (HashType::Sha384, sha2::Sha384::digest(&data).to_vec()),
+        (HashType::Sha512, sha2::Sha512::digest(&data).to_vec()),
+    ] {
+        for encoding in vec![
+            EcdsaSignatureEncoding::Der,
+            EcdsaSignatureEncoding::IeeeP1363,
+        ] {
+            // The same key can be used with either hash function that the curve permits.
+            let priv_key = EcdsaPrivateKey::generate(curve).unwrap();
+            let pub_key = priv_key.public_key();
+            let signer =
+                subtle::EcdsaSigner::new_from_private_key(hash, curve, encoding, priv_key.clone())
+                    .unwrap();
+            let verifier =
+                subtle::EcdsaVerifier::new_from_public_key(hash, curve, encoding, pub_key.clone())
+                    .unwrap();
+
+            let signature = signer.sign(&data).unwrap();
+            verifier.verify(&signature, &data).unwrap();
+            verifier.verify_digest(&signature, hash, &digest).unwrap();
+            let signature = signer.sign_digest(hash, &digest).unwrap();
+            verifier.verify(&signature, &data).unwrap();
+
+            // A signature made with one hash function does not verify with the other.
+            let other_hash = match hash {
+                HashType::Sha384 => HashType::Sha512,
+                _ => HashType::Sha384,
+            };
+            let other_verifier =
+                subtle::EcdsaVerifier::new_from_public_key(other_hash, curve, encoding, pub_key)
+                    .unwrap();
+            assert!(other_verifier.verify(&signature, &data).is_err());
+        }
+    }
+}
+
+#[test]
+fn test_sign_verify_digest() {
+    use sha2::Digest;
+    use subtle::{DigestSigner, DigestVerifier};
+    let data = get_random_bytes(20);
+    let digest = sha2::Sha256::digest(&data).to_vec();
+    let hash = HashType::Sha256;
+    let curve = EllipticCurveType::NistP256;
+    for encoding in vec![
+        EcdsaSignatureEncoding::Der,
+        EcdsaSignatureEncoding::IeeeP1363,
+    ] {
+        let priv_key = EcdsaPrivateKey::generate(curve).unwrap();
+        let pub_key = priv_key.public_key();
+        let signer = subtle::EcdsaSigner::new_from_private_key(hash, curve, encoding, priv_key)
+            .unwrap()
+            .with_low_s(true);
+        let verifier =
+            subtle::EcdsaVerifier::new_from_public_key(hash, curve, encoding, pub_key).unwrap();
+
+        // Signatures over a digest verify against the full message, and vice versa.
+        let signature = signer.sign_digest(hash, &digest).unwrap();
+        verifier.verify(&signature, &data).unwrap();
+        verifier.verify_digest(&signature, hash, &digest).unwrap();
+        let signature = signer.sign(&data).unwrap();
+        verifier.verify_digest(&signature, hash, &digest).unwrap();
+
+        let mut bad_digest = digest.clone();
+        bad_digest[0] ^= 0x01;
+        assert!(verifier
+            .verify_digest(&signature, hash, &bad_digest)
+            .is_err());
+
+        // The hash type and digest length must match the key parameters.
+        let sha512_digest = sha2::Sha512::digest(&data).to_vec();
+        tink_tests::expect_err(
+            signer.sign_digest(HashType::Sha512, &sha512_digest),
+            "does not match key hash",
+        );
+        tink_tests::expect_err(
+            verifier.verify_digest(&signature, HashType::Sha512, &sha512_digest),
+            "does not match key hash",
+        );
+        tink_tests::expect_err(
+            signer.sign_digest(hash, &digest[..20]),
+            "invalid digest length",
+        );
+        tink_tests::expect_err(
+            verifier.verify_digest(&signature, hash, &sha512_digest),
+            "invalid digest length",
+        );
+    }
+}
+
 #[test]
 fn test_ecdsa_invalid_signer_params() {
     let mut csprng = p256::elliptic_curve::rand_core::OsRng {};
@@ -223,11 +358,11 @@ fn test_ecdsa_wycheproof_cases() {
             filename: "ecdsa_secp256r1_sha256_p1363_test.json",
             encoding: EcdsaSignatureEncoding::IeeeP1363,
         },
+        TestVector {
+            filename: "ecdsa_secp384r1_sha512_p1363_test.json",
+            encoding: EcdsaSignatureEncoding::IeeeP1363,
+        },
         /* TODO(#16): more ECDSA curves
-                TestVector {
-                    filename: "ecdsa_secp384r1_sha512_p1363_test.json",
-                    encoding: EcdsaSignatureEncoding::IeeeP1363,
-                },
                 TestVector {
                     filename: "ecdsa_secp521r1_sha512_p1363_test.json",
                     encoding: EcdsaSignatureEncoding::IeeeP1363,
@@ -260,13 +395,20 @@ fn wycheproof_test(filename: &str, encoding: EcdsaSignatureEncoding) {
         }
         // TODO(#16): more ECDSA curves
         // if curve == EllipticCurveType::UnknownCurve {
-        if curve != EllipticCurveType::NistP256 {
+        if curve != EllipticCurveType::NistP256 && curve != EllipticCurveType::NistP384 {
             if !skipped_curves.contains(&g.key.curve) {
                 println!("skipping tests for unsupported curve {}", g.key.curve);
                 skipped_curves.insert(g.key.curve.clone());
             }
             continue;
         }
+        if subtle::validate_ecdsa_params(hash, curve, encoding).is_err() {
+            println!(
+                "skipping tests for unsupported combination {:?} with {:?}",
+                curve, hash
+            );
+            continue;
+        }
         println!(
             "   key info: {:?}, {:?}, {:?}, {}, {}",
             hash,
@@ -0,0 +1,111 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use std::io::{Cursor, Write};
+use tink_core::keyset::Handle;
+use tink_proto::HashType;
+use tink_streaming_aead::{parse_header, read_header, StreamingKeyParameters};
+use tink_tests::SharedBuf;
+
+fn encrypt(kt: &tink_proto::KeyTemplate, pt: &[u8]) -> Vec<u8> {
+    tink_streaming_aead::init();
+    let h = Handle::new(kt).unwrap();
+    let s = tink_streaming_aead::new(&h).unwrap();
+    let buf = SharedBuf::new();
+    let mut w = s
+        .new_encrypting_writer(Box::new(buf.clone()), b"aad")
+        .unwrap();
+    w.write_all(pt).unwrap();
+    w.close().unwrap();
+    buf.contents()
+}
+
+#[test]
+fn test_parse_header() {
+    let templates = vec![
+        (tink_streaming_aead::aes128_gcm_hkdf_4kb_key_template(), 16),
+        (tink_streaming_aead::aes256_gcm_hkdf_1mb_key_template(), 32),
+        (
+            tink_streaming_aead::aes128_ctr_hmac_sha256_segment_4kb_key_template(),
+            16,
+        ),
+        (
+            tink_streaming_aead::aes256_ctr_hmac_sha256_segment_1mb_key_template(),
+            32,
+        ),
+    ];
+    for (kt, key_size) in templates {
+        let ct = encrypt(&kt, b"some data to encrypt");
+        let header = parse_header(&ct).unwrap();
+        assert_eq!(header.derived_key_size(), key_size);
+        assert_eq!(header.salt.len(), key_size);
+        assert_eq!(header.nonce_prefix.len(), 7);
+        assert_eq!(header.header_length(), ct[0] as usize);
+        assert_eq!(&ct[1..1 + key_size], &header.salt[..]);
+
+        // Only the header bytes are needed.
+        assert_eq!(parse_header(&ct[..header.header_length()]).unwrap(), header);
+
+        let mut r = Cursor::new(ct.clone());
+        assert_eq!(read_header(&mut r).unwrap(), header);
+        assert_eq!(r.position() as usize, header.header_length());
+
+        let params = StreamingKeyParameters::from_template(&kt).unwrap();
+        assert_eq!(params.type_url, kt.type_url);
+        assert_eq!(params.hkdf_hash_type, HashType::Sha256);
+        assert!(params.matches(&header));
+    }
+}
+
+#[test]
+fn test_parse_header_invalid() {
+    tink_tests::expect_err(parse_header(&[]), "empty ciphertext");
+    tink_tests::expect_err(parse_header(&[5, 0, 0, 0, 0]), "invalid header length");
+    tink_tests::expect_err(parse_header(&[24; 23]), "ciphertext too short");
+    tink_tests::expect_err(
+        read_header(&mut Cursor::new(vec![40; 20])),
+        "failed to read header",
+    );
+}
+
+#[test]
+fn test_key_parameters() {
+    let params = StreamingKeyParameters::from_template(
+        &tink_streaming_aead::aes256_gcm_hkdf_1mb_key_template(),
+    )
+    .unwrap();
+    assert_eq!(params.derived_key_size, 32);
+    assert_eq!(params.ciphertext_segment_size, 1024 * 1024);
+
+    let params = StreamingKeyParameters::from_template(
+        &tink_streaming_aead::aes128_ctr_hmac_sha256_segment_4kb_key_template(),
+    )
+    .unwrap();
+    assert_eq!(params.derived_key_size, 16);
+    assert_eq!(params.ciphertext_segment_size, 4096);
+
+    // A header for a 32-byte derived key does not match a 16-byte key type.
+    let ct = encrypt(
+        &tink_streaming_aead::aes256_gcm_hkdf_4kb_key_template(),
+        b"data",
+    );
+    assert!(!params.matches(&parse_header(&ct).unwrap()));
+
+    tink_tests::expect_err(
+        StreamingKeyParameters::from_template(&tink_aead::aes128_gcm_key_template()),
+        "unsupported key type",
+    );
+}
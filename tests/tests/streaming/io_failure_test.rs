@@ -0,0 +1,96 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use std::io::{Read, Write};
+use tink_core::subtle::random::get_random_bytes;
+use tink_tests::SharedBuf;
+
+fn primitives() -> Vec<Box<dyn tink_core::StreamingAead>> {
+    tink_streaming_aead::init();
+    vec![
+        tink_streaming_aead::aes128_gcm_hkdf_4kb_key_template(),
+        tink_streaming_aead::aes128_ctr_hmac_sha256_segment_4kb_key_template(),
+    ]
+    .iter()
+    .map(|kt| {
+        let kh = tink_core::keyset::Handle::new(kt).unwrap();
+        tink_streaming_aead::new(&kh).unwrap()
+    })
+    .collect()
+}
+
+fn encrypt(a: &dyn tink_core::StreamingAead, buf: SharedBuf, pt: &[u8]) {
+    let mut w = a.new_encrypting_writer(Box::new(buf), b"aad").unwrap();
+    w.write_all(pt).unwrap();
+    w.close().unwrap();
+}
+
+#[test]
+fn test_short_reads_and_writes() {
+    let pt = get_random_bytes(10_000);
+    for a in primitives() {
+        let buf = SharedBuf::new().with_short_writes(5);
+        encrypt(a.as_ref(), buf.clone(), &pt);
+        let ct = buf.contents();
+
+        let mut r = a
+            .new_decrypting_reader(
+                Box::new(SharedBuf::with_contents(ct).with_short_reads(7)),
+                b"aad",
+            )
+            .unwrap();
+        let mut got = Vec::new();
+        r.read_to_end(&mut got).unwrap();
+        assert_eq!(got, pt);
+    }
+}
+
+#[test]
+fn test_read_failure_mid_stream() {
+    let pt = get_random_bytes(10_000);
+    for a in primitives() {
+        let buf = SharedBuf::new();
+        encrypt(a.as_ref(), buf.clone(), &pt);
+        let ct = buf.contents();
+        let fail_at = ct.len() / 2;
+
+        let mut r = a
+            .new_decrypting_reader(
+                Box::new(SharedBuf::with_contents(ct).fail_reads_after(fail_at)),
+                b"aad",
+            )
+            .unwrap();
+        let mut got = Vec::new();
+        assert!(r.read_to_end(&mut got).is_err());
+        // Only authenticated plaintext from complete segments is returned.
+        assert!(got.len() < fail_at);
+        assert_eq!(got, pt[..got.len()]);
+    }
+}
+
+#[test]
+fn test_write_failure_mid_stream() {
+    let pt = get_random_bytes(10_000);
+    for a in primitives() {
+        let buf = SharedBuf::new().fail_writes_after(5_000);
+        let mut w = a
+            .new_encrypting_writer(Box::new(buf.clone()), b"aad")
+            .unwrap();
+        let failed = w.write_all(&pt).is_err() || w.close().is_err();
+        assert!(failed, "expected injected write failure to surface");
+        assert_eq!(buf.bytes_written(), 5_000);
+    }
+}
@@ -85,3 +85,47 @@ fn test_key_templates() {
         assert_eq!(decrypted, plaintext);
     }
 }
+
+#[test]
+fn test_template_builders() {
+    assert_eq!(
+        tink_streaming_aead::aes256_gcm_hkdf_1mb_key_template(),
+        tink_streaming_aead::AesGcmHkdfTemplateBuilder::new()
+            .key_size(32)
+            .derived_key_size(32)
+            .ciphertext_segment_size(1048576)
+            .build()
+            .unwrap()
+    );
+    assert_eq!(
+        tink_streaming_aead::aes128_ctr_hmac_sha256_segment_4kb_key_template(),
+        tink_streaming_aead::AesCtrHmacTemplateBuilder::new()
+            .build()
+            .unwrap()
+    );
+
+    tink_tests::expect_err(
+        tink_streaming_aead::AesGcmHkdfTemplateBuilder::new()
+            .ciphertext_segment_size(16)
+            .build(),
+        "segment_size must be at least",
+    );
+    tink_tests::expect_err(
+        tink_streaming_aead::AesGcmHkdfTemplateBuilder::new()
+            .hkdf_hash(tink_proto::HashType::UnknownHash)
+            .build(),
+        "unknown HKDF hash",
+    );
+    tink_tests::expect_err(
+        tink_streaming_aead::AesCtrHmacTemplateBuilder::new()
+            .tag_size(64)
+            .build(),
+        "tag size too big",
+    );
+    tink_tests::expect_err(
+        tink_streaming_aead::AesCtrHmacTemplateBuilder::new()
+            .key_size(20)
+            .build(),
+        "",
+    );
+}
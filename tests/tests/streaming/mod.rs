@@ -19,6 +19,11 @@ pub mod common;
 mod aes_ctr_hmac_key_manager_test;
 mod aes_gcm_hkdf_key_manager_test;
 mod factory_test;
+mod header_test;
 mod integration_test;
+mod io_failure_test;
 mod key_templates_test;
+mod read_ahead_test;
+mod reencrypt_test;
+mod segment_aad_test;
 mod subtle;
@@ -0,0 +1,87 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use std::io::{Read, Write};
+use tink_core::subtle::random::get_random_bytes;
+use tink_streaming_aead::{ReadAheadReader, DEFAULT_READ_AHEAD_SEGMENTS};
+use tink_tests::SharedBuf;
+
+const SEGMENT_SIZE: usize = 4096;
+
+fn primitives() -> Vec<Box<dyn tink_core::StreamingAead>> {
+    tink_streaming_aead::init();
+    vec![
+        tink_streaming_aead::aes128_gcm_hkdf_4kb_key_template(),
+        tink_streaming_aead::aes128_ctr_hmac_sha256_segment_4kb_key_template(),
+    ]
+    .iter()
+    .map(|kt| {
+        let kh = tink_core::keyset::Handle::new(kt).unwrap();
+        tink_streaming_aead::new(&kh).unwrap()
+    })
+    .collect()
+}
+
+fn encrypt(a: &dyn tink_core::StreamingAead, pt: &[u8]) -> Vec<u8> {
+    let buf = SharedBuf::new();
+    let mut w = a
+        .new_encrypting_writer(Box::new(buf.clone()), b"aad")
+        .unwrap();
+    w.write_all(pt).unwrap();
+    w.close().unwrap();
+    buf.contents()
+}
+
+#[test]
+fn test_read_ahead_decrypt() {
+    for size in &[0, 1, SEGMENT_SIZE, 10 * SEGMENT_SIZE + 17] {
+        let pt = get_random_bytes(*size);
+        for a in primitives() {
+            let ct = encrypt(a.as_ref(), &pt);
+            for segments in &[0, 1, DEFAULT_READ_AHEAD_SEGMENTS] {
+                let source = SharedBuf::with_contents(ct.clone()).with_short_reads(1000);
+                let reader = ReadAheadReader::new(source, SEGMENT_SIZE, *segments);
+                let mut r = a.new_decrypting_reader(Box::new(reader), b"aad").unwrap();
+                let mut got = Vec::new();
+                r.read_to_end(&mut got).unwrap();
+                assert_eq!(got, pt);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_read_ahead_chunk_boundaries() {
+    let data = get_random_bytes(3 * SEGMENT_SIZE);
+    let mut r = ReadAheadReader::new(SharedBuf::with_contents(data.clone()), SEGMENT_SIZE, 2);
+    let mut got = Vec::new();
+    r.read_to_end(&mut got).unwrap();
+    assert_eq!(got, data);
+    // Further reads after the end of the data return zero.
+    assert_eq!(r.read(&mut [0u8; 10]).unwrap(), 0);
+}
+
+#[test]
+fn test_read_ahead_failure() {
+    let data = get_random_bytes(3 * SEGMENT_SIZE);
+    let fail_at = SEGMENT_SIZE + 100;
+    let source = SharedBuf::with_contents(data.clone()).fail_reads_after(fail_at);
+    let mut r = ReadAheadReader::new(source, SEGMENT_SIZE, 2);
+    let mut got = Vec::new();
+    assert!(r.read_to_end(&mut got).is_err());
+    // Complete chunks read before the failure are delivered.
+    assert_eq!(got, data[..SEGMENT_SIZE]);
+}
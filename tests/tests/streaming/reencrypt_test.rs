@@ -0,0 +1,126 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use std::io::{Cursor, Read, Write};
+use tink_core::keyset::Handle;
+use tink_streaming_aead::{ReencryptProgress, Reencrypter};
+use tink_tests::SharedBuf;
+
+const AAD: &[u8] = b"associated data";
+
+fn new_handle(kt: &tink_proto::KeyTemplate) -> Handle {
+    tink_aead::init();
+    tink_streaming_aead::init();
+    Handle::new(kt).unwrap()
+}
+
+fn encrypt(h: &Handle, pt: &[u8]) -> Vec<u8> {
+    if let Ok(a) = tink_aead::new(h) {
+        return a.encrypt(pt, AAD).unwrap();
+    }
+    let s = tink_streaming_aead::new(h).unwrap();
+    let buf = SharedBuf::new();
+    let mut w = s.new_encrypting_writer(Box::new(buf.clone()), AAD).unwrap();
+    w.write_all(pt).unwrap();
+    w.close().unwrap();
+    buf.contents()
+}
+
+fn decrypt(h: &Handle, ct: &[u8]) -> Vec<u8> {
+    if let Ok(a) = tink_aead::new(h) {
+        return a.decrypt(ct, AAD).unwrap();
+    }
+    let s = tink_streaming_aead::new(h).unwrap();
+    let mut r = s
+        .new_decrypting_reader(Box::new(Cursor::new(ct.to_vec())), AAD)
+        .unwrap();
+    let mut pt = Vec::new();
+    r.read_to_end(&mut pt).unwrap();
+    pt
+}
+
+#[test]
+fn test_reencrypt_combinations() {
+    let aead = || new_handle(&tink_aead::aes128_gcm_key_template());
+    let streaming = || new_handle(&tink_streaming_aead::aes128_gcm_hkdf_4kb_key_template());
+    let pt: Vec<u8> = (0..20_000u32).map(|i| i as u8).collect();
+    let cases = vec![
+        ("aead->aead", aead(), aead()),
+        ("aead->streaming", aead(), streaming()),
+        ("streaming->aead", streaming(), aead()),
+        ("streaming->streaming", streaming(), streaming()),
+    ];
+    for (name, old, new) in cases {
+        let ct = encrypt(&old, &pt);
+        let out = SharedBuf::new();
+        let mut reports = Vec::new();
+        let stats = Reencrypter::new(&old, &new)
+            .unwrap()
+            .with_chunk_size(4096)
+            .reencrypt(
+                Box::new(Cursor::new(ct.clone())),
+                Box::new(out.clone()),
+                AAD,
+                &mut |p: &ReencryptProgress| reports.push(*p),
+            )
+            .unwrap();
+        assert_eq!(decrypt(&new, &out.contents()), pt, "{}", name);
+        assert_eq!(stats.plaintext_bytes, pt.len() as u64, "{}", name);
+        assert_eq!(stats.ciphertext_bytes_read, ct.len() as u64, "{}", name);
+        assert_eq!(reports.last(), Some(&stats), "{}", name);
+        assert!(reports.len() >= pt.len() / 4096, "{}", name);
+        assert!(
+            reports
+                .windows(2)
+                .all(|w| w[0].plaintext_bytes < w[1].plaintext_bytes),
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_reencrypt_failures() {
+    let old = new_handle(&tink_streaming_aead::aes128_ctr_hmac_sha256_segment_4kb_key_template());
+    let new = new_handle(&tink_streaming_aead::aes128_gcm_hkdf_4kb_key_template());
+    let ct = encrypt(&old, b"some data");
+    let re = Reencrypter::new(&old, &new).unwrap();
+
+    // Wrong associated data.
+    let result = re.reencrypt(
+        Box::new(Cursor::new(ct.clone())),
+        Box::new(SharedBuf::new()),
+        b"other data",
+        &mut |_| {},
+    );
+    assert!(result.is_err());
+
+    // Ciphertext for a different keyset.
+    let other = encrypt(&new, b"some data");
+    let result = re.reencrypt(
+        Box::new(Cursor::new(other)),
+        Box::new(SharedBuf::new()),
+        AAD,
+        &mut |_| {},
+    );
+    assert!(result.is_err());
+
+    // Keysets that are not for (streaming) AEAD.
+    tink_mac::init();
+    let mac = Handle::new(&tink_mac::hmac_sha256_tag256_key_template()).unwrap();
+    tink_tests::expect_err(Reencrypter::new(&mac, &new), "invalid old keyset");
+    tink_tests::expect_err(Reencrypter::new(&old, &mac), "invalid new keyset");
+}
@@ -0,0 +1,118 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use std::io::{Cursor, Read, Write};
+use tink_core::{subtle::random::get_random_bytes, StreamingAead};
+use tink_tests::SharedBuf;
+
+fn templates() -> Vec<tink_proto::KeyTemplate> {
+    vec![
+        tink_streaming_aead::aes128_gcm_hkdf_4kb_key_template(),
+        tink_streaming_aead::aes256_gcm_hkdf_4kb_key_template(),
+        tink_streaming_aead::aes128_ctr_hmac_sha256_segment_4kb_key_template(),
+        tink_streaming_aead::aes256_ctr_hmac_sha256_segment_4kb_key_template(),
+    ]
+}
+
+fn encrypt(a: &dyn StreamingAead, pt: &[u8], aad: &[u8], segment_aad: &[u8]) -> Vec<u8> {
+    let buf = SharedBuf::new();
+    let mut w = a
+        .new_encrypting_writer_with_segment_aad(Box::new(buf.clone()), aad, segment_aad)
+        .unwrap();
+    w.write_all(pt).unwrap();
+    w.close().unwrap();
+    buf.contents()
+}
+
+fn decrypt(
+    a: &dyn StreamingAead,
+    ct: &[u8],
+    aad: &[u8],
+    segment_aad: &[u8],
+) -> std::io::Result<Vec<u8>> {
+    let mut r = a
+        .new_decrypting_reader_with_segment_aad(
+            Box::new(Cursor::new(ct.to_vec())),
+            aad,
+            segment_aad,
+        )
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("{:?}", e)))?;
+    let mut pt = vec![];
+    r.read_to_end(&mut pt)?;
+    Ok(pt)
+}
+
+#[test]
+fn test_segment_aad_encrypt_decrypt() {
+    tink_streaming_aead::init();
+    for kt in templates() {
+        let kh = tink_core::keyset::Handle::new(&kt).unwrap();
+        let a = tink_streaming_aead::new(&kh).unwrap();
+        for pt_size in &[0, 1, 4095, 4096, 10000] {
+            let pt = get_random_bytes(*pt_size);
+            let ct = encrypt(a.as_ref(), &pt, b"aad", b"chunk 7");
+            assert_eq!(
+                decrypt(a.as_ref(), &ct, b"aad", b"chunk 7").unwrap(),
+                pt,
+                "template {}, pt_size {}",
+                kt.type_url,
+                pt_size
+            );
+
+            assert!(decrypt(a.as_ref(), &ct, b"aad", b"chunk 8").is_err());
+            assert!(decrypt(a.as_ref(), &ct, b"aad", b"").is_err());
+            assert!(a
+                .new_decrypting_reader(Box::new(Cursor::new(ct.clone())), b"aad")
+                .and_then(|mut r| {
+                    let mut pt = vec![];
+                    r.read_to_end(&mut pt)
+                        .map_err(|e| tink_core::utils::wrap_err("read failed", e))
+                })
+                .is_err());
+        }
+    }
+}
+
+#[test]
+fn test_segment_aad_empty_is_compatible() {
+    tink_streaming_aead::init();
+    for kt in templates() {
+        let kh = tink_core::keyset::Handle::new(&kt).unwrap();
+        let a = tink_streaming_aead::new(&kh).unwrap();
+        let pt = get_random_bytes(10000);
+
+        // Ciphertext from the segment AAD API with empty segment AAD decrypts normally.
+        let ct = encrypt(a.as_ref(), &pt, b"aad", b"");
+        let mut r = a
+            .new_decrypting_reader(Box::new(Cursor::new(ct)), b"aad")
+            .unwrap();
+        let mut got = vec![];
+        r.read_to_end(&mut got).unwrap();
+        assert_eq!(got, pt);
+
+        // And vice versa.
+        let buf = SharedBuf::new();
+        let mut w = a
+            .new_encrypting_writer(Box::new(buf.clone()), b"aad")
+            .unwrap();
+        w.write_all(&pt).unwrap();
+        w.close().unwrap();
+        assert_eq!(
+            decrypt(a.as_ref(), &buf.contents(), b"aad", b"").unwrap(),
+            pt
+        );
+    }
+}
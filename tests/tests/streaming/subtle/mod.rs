@@ -17,6 +17,8 @@
 mod aes_ctr_hmac_test;
 mod aes_gcm_hkdf_test;
 mod noncebased_test;
+mod segment_buffers_test;
+mod segment_layout_test;
 
 mod subtle_test;
 use subtle_test::*;
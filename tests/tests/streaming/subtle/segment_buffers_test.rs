@@ -0,0 +1,194 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use std::io::{Read, Write};
+use tink_core::{subtle::random::get_random_bytes, EncryptingWrite, StreamingAead};
+use tink_proto::HashType;
+use tink_streaming_aead::subtle::{self, noncebased::SegmentBuffers};
+use tink_tests::SharedBuf;
+
+const SEGMENT_SIZE: usize = 256;
+
+fn ciphers() -> Vec<(&'static str, Box<dyn BufferedCipher>)> {
+    let ikm = get_random_bytes(32);
+    vec![
+        (
+            "AES-GCM-HKDF",
+            Box::new(subtle::AesGcmHkdf::new(&ikm, HashType::Sha256, 16, SEGMENT_SIZE, 0).unwrap())
+                as Box<dyn BufferedCipher>,
+        ),
+        (
+            "AES-CTR-HMAC",
+            Box::new(
+                subtle::AesCtrHmac::new(
+                    &ikm,
+                    HashType::Sha256,
+                    16,
+                    HashType::Sha256,
+                    16,
+                    SEGMENT_SIZE,
+                    0,
+                )
+                .unwrap(),
+            ) as Box<dyn BufferedCipher>,
+        ),
+    ]
+}
+
+/// Common view of the buffer-accepting methods of the subtle streaming AEAD implementations.
+trait BufferedCipher: StreamingAead {
+    fn buffers(&self) -> SegmentBuffers;
+    fn writer(
+        &self,
+        w: Box<dyn Write>,
+        aad: &[u8],
+        buffers: SegmentBuffers,
+    ) -> Result<subtle::noncebased::Writer, tink_core::TinkError>;
+    fn reader(
+        &self,
+        r: Box<dyn Read>,
+        aad: &[u8],
+        buffers: SegmentBuffers,
+    ) -> Result<subtle::noncebased::Reader, tink_core::TinkError>;
+}
+
+macro_rules! impl_buffered_cipher {
+    { $t:ty } => {
+        impl BufferedCipher for $t {
+            fn buffers(&self) -> SegmentBuffers {
+                self.segment_buffers()
+            }
+            fn writer(
+                &self,
+                w: Box<dyn Write>,
+                aad: &[u8],
+                buffers: SegmentBuffers,
+            ) -> Result<subtle::noncebased::Writer, tink_core::TinkError> {
+                self.new_encrypting_writer_with_buffers(w, aad, buffers)
+            }
+            fn reader(
+                &self,
+                r: Box<dyn Read>,
+                aad: &[u8],
+                buffers: SegmentBuffers,
+            ) -> Result<subtle::noncebased::Reader, tink_core::TinkError> {
+                self.new_decrypting_reader_with_buffers(r, aad, buffers)
+            }
+        }
+    };
+}
+impl_buffered_cipher!(subtle::AesGcmHkdf);
+impl_buffered_cipher!(subtle::AesCtrHmac);
+
+#[test]
+fn test_segment_buffers_interoperate() {
+    let aad = b"aad";
+    for (name, cipher) in ciphers() {
+        for pt_size in &[0, 1, SEGMENT_SIZE - 50, SEGMENT_SIZE, 10 * SEGMENT_SIZE + 7] {
+            let pt = get_random_bytes(*pt_size);
+
+            // Encrypt with caller-provided buffers, decrypt normally.
+            let buf = SharedBuf::new();
+            let mut w = cipher
+                .writer(Box::new(buf.clone()), aad, cipher.buffers())
+                .unwrap();
+            w.write_all(&pt).unwrap();
+            w.close().unwrap();
+            let mut got = vec![];
+            cipher
+                .new_decrypting_reader(Box::new(buf), aad)
+                .unwrap()
+                .read_to_end(&mut got)
+                .unwrap_or_else(|e| panic!("{}: decryption failed: {:?}", name, e));
+            assert_eq!(got, pt, "{}", name);
+
+            // Encrypt normally, decrypt with caller-provided buffers.
+            let buf = SharedBuf::new();
+            let mut w = cipher
+                .new_encrypting_writer(Box::new(buf.clone()), aad)
+                .unwrap();
+            w.write_all(&pt).unwrap();
+            w.close().unwrap();
+            let mut got = vec![];
+            cipher
+                .reader(Box::new(buf), aad, cipher.buffers())
+                .unwrap()
+                .read_to_end(&mut got)
+                .unwrap_or_else(|e| panic!("{}: decryption failed: {:?}", name, e));
+            assert_eq!(got, pt, "{}", name);
+        }
+    }
+}
+
+#[test]
+fn test_segment_buffers_reuse() {
+    let aad = b"aad";
+    for (name, cipher) in ciphers() {
+        let mut buffers = cipher.buffers();
+        let original = buffers_ptrs(&mut buffers);
+        for i in 0..3 {
+            let pt = get_random_bytes(3 * SEGMENT_SIZE + i);
+            let buf = SharedBuf::new();
+            let mut w = cipher.writer(Box::new(buf.clone()), aad, buffers).unwrap();
+            w.write_all(&pt).unwrap();
+            buffers = w.into_buffers().unwrap();
+
+            let mut r = cipher.reader(Box::new(buf), aad, buffers).unwrap();
+            let mut got = vec![];
+            r.read_to_end(&mut got).unwrap();
+            assert_eq!(got, pt, "{}", name);
+            buffers = r.into_buffers();
+        }
+        // The same storage was used throughout.
+        assert_eq!(buffers_ptrs(&mut buffers), original, "{}", name);
+    }
+}
+
+fn buffers_ptrs(buffers: &mut SegmentBuffers) -> (*const u8, *const u8) {
+    let (pt, ct) = std::mem::take(buffers).into_vecs();
+    let ptrs = (pt.as_ptr(), ct.as_ptr());
+    *buffers = SegmentBuffers::from_vecs(pt, ct);
+    ptrs
+}
+
+#[test]
+fn test_segment_buffers_too_small() {
+    for (name, cipher) in ciphers() {
+        let small = SegmentBuffers::from_vecs(
+            Vec::with_capacity(SEGMENT_SIZE - 1),
+            Vec::with_capacity(SEGMENT_SIZE + 1),
+        );
+        tink_tests::expect_err(
+            cipher.writer(Box::new(SharedBuf::new()), b"", small),
+            "plaintext buffer too small",
+        );
+        let small = SegmentBuffers::from_vecs(
+            Vec::with_capacity(SEGMENT_SIZE),
+            Vec::with_capacity(SEGMENT_SIZE),
+        );
+        tink_tests::expect_err(
+            cipher.reader(Box::new(SharedBuf::new()), b"", small),
+            "ciphertext buffer too small",
+        );
+        assert!(
+            SegmentBuffers::new(SEGMENT_SIZE)
+                .check_capacity(SEGMENT_SIZE)
+                .is_ok(),
+            "{}",
+            name
+        );
+    }
+}
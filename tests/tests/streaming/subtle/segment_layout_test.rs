@@ -0,0 +1,138 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_streaming_aead::subtle;
+use tink_tests::expect_err;
+
+/// Check computed sizes against the ciphertexts actually produced for a range of plaintext
+/// lengths, including those around segment boundaries.
+fn check_layout<T: tink_core::StreamingAead>(cipher: &T, layout: subtle::SegmentLayout) {
+    let first = layout.ciphertext_segment_size
+        - layout.first_segment_offset
+        - layout.header_length
+        - layout.tag_size;
+    let later = layout.ciphertext_segment_size - layout.tag_size;
+    let mut lengths: Vec<usize> = (0..=3).collect();
+    for boundary in &[first, first + later, first + 2 * later] {
+        lengths.extend_from_slice(&[boundary - 1, *boundary, boundary + 1]);
+    }
+    lengths.push(first + 3 * later + 17);
+
+    for pt_len in lengths {
+        let (_pt, ct) = super::encrypt(cipher, super::AAD, pt_len).unwrap();
+        assert_eq!(
+            layout.ciphertext_size(pt_len as u64).unwrap(),
+            ct.len() as u64,
+            "plaintext length {}",
+            pt_len
+        );
+        assert_eq!(
+            layout.plaintext_size(ct.len() as u64).unwrap(),
+            pt_len as u64,
+            "ciphertext length {}",
+            ct.len()
+        );
+    }
+}
+
+#[test]
+fn test_segment_layout_aes_gcm_hkdf() {
+    for (key_size, segment_size, offset) in &[(16, 256, 0), (32, 256, 8), (16, 4096, 30)] {
+        let cipher = subtle::AesGcmHkdf::new(
+            super::IKM,
+            tink_proto::HashType::Sha256,
+            *key_size,
+            *segment_size,
+            *offset,
+        )
+        .unwrap();
+        let layout =
+            subtle::SegmentLayout::aes_gcm_hkdf(*key_size, *segment_size, *offset).unwrap();
+        assert_eq!(layout, cipher.segment_layout());
+        assert_eq!(layout.header_length, cipher.header_length());
+        check_layout(&cipher, layout);
+    }
+}
+
+#[test]
+fn test_segment_layout_aes_ctr_hmac() {
+    for (key_size, tag_size, segment_size, offset) in
+        &[(16, 16, 256, 0), (32, 32, 256, 8), (16, 12, 4096, 30)]
+    {
+        let cipher = subtle::AesCtrHmac::new(
+            super::IKM,
+            tink_proto::HashType::Sha256,
+            *key_size,
+            tink_proto::HashType::Sha256,
+            *tag_size,
+            *segment_size,
+            *offset,
+        )
+        .unwrap();
+        let layout =
+            subtle::SegmentLayout::aes_ctr_hmac(*key_size, *tag_size, *segment_size, *offset)
+                .unwrap();
+        assert_eq!(layout, cipher.segment_layout());
+        check_layout(&cipher, layout);
+    }
+}
+
+#[test]
+fn test_segment_layout_sizes() {
+    // Header of 24 bytes, first segment of 232 bytes, later segments of 256 bytes.
+    let layout = subtle::SegmentLayout::aes_gcm_hkdf(16, 256, 0).unwrap();
+    assert_eq!(layout.header_length, 24);
+    assert_eq!(layout.ciphertext_size(0).unwrap(), 24 + 16);
+    assert_eq!(layout.ciphertext_size(216).unwrap(), 24 + 232);
+    assert_eq!(layout.ciphertext_size(217).unwrap(), 24 + 232 + 17);
+    assert_eq!(layout.ciphertext_size(216 + 240).unwrap(), 24 + 232 + 256);
+
+    // Large plaintexts do not overflow.
+    let size = layout.ciphertext_size(1 << 40).unwrap();
+    assert_eq!(layout.plaintext_size(size).unwrap(), 1 << 40);
+    expect_err(layout.ciphertext_size(u64::MAX), "plaintext too long");
+
+    // Sizes that no ciphertext can have.
+    expect_err(layout.plaintext_size(0), "ciphertext too short");
+    expect_err(layout.plaintext_size(23), "ciphertext too short");
+    expect_err(layout.plaintext_size(24 + 15), "ciphertext too short");
+    // A final segment holding no plaintext only occurs for an empty plaintext.
+    expect_err(
+        layout.plaintext_size(24 + 232 + 16),
+        "invalid ciphertext size",
+    );
+    expect_err(
+        layout.plaintext_size(24 + 232 + 5),
+        "invalid ciphertext size",
+    );
+    assert_eq!(layout.plaintext_size(24 + 232 + 17).unwrap(), 217);
+}
+
+#[test]
+fn test_segment_layout_invalid() {
+    expect_err(
+        subtle::SegmentLayout::aes_gcm_hkdf(24, 256, 0),
+        "invalid AES key size",
+    );
+    expect_err(
+        subtle::SegmentLayout::aes_gcm_hkdf(16, 40, 0),
+        "ciphertext_segment_size too small",
+    );
+    expect_err(
+        subtle::SegmentLayout::aes_ctr_hmac(32, 32, 256, 200),
+        "ciphertext_segment_size too small",
+    );
+}
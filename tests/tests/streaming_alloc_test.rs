@@ -0,0 +1,147 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+// These tests install a counting global allocator, so they live in their own test binary.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    io::{Cursor, Read, Write},
+};
+use tink_core::EncryptingWrite;
+use tink_proto::HashType;
+use tink_streaming_aead::subtle::AesGcmHkdf;
+use tink_tests::SharedBuf;
+
+/// Allocator that counts the allocations made by threads that have opted in.
+struct CountingAllocator;
+
+thread_local! {
+    /// Number of allocations made by this thread, if counting is enabled.
+    static ALLOCATIONS: Cell<Option<usize>> = Cell::new(None);
+}
+
+fn record_allocation() {
+    let _ = ALLOCATIONS.try_with(|count| {
+        if let Some(n) = count.get() {
+            count.set(Some(n + 1));
+        }
+    });
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record_allocation();
+        System.alloc(layout)
+    }
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record_allocation();
+        System.alloc_zeroed(layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record_allocation();
+        System.realloc(ptr, layout, new_size)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Return the number of heap allocations made by the current thread while running `f`.
+fn allocations_during<F: FnOnce()>(f: F) -> usize {
+    ALLOCATIONS.with(|count| count.set(Some(0)));
+    f();
+    ALLOCATIONS.with(|count| count.replace(None)).unwrap()
+}
+
+const SEGMENT_SIZE: usize = 512;
+const SEGMENT_COUNT: usize = 50;
+const AAD: &[u8] = b"aad";
+
+fn new_cipher() -> AesGcmHkdf {
+    AesGcmHkdf::new(&[0x01; 32], HashType::Sha256, 16, SEGMENT_SIZE, 0).unwrap()
+}
+
+/// Writer that discards its input.
+struct NullWriter;
+
+impl Write for NullWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_encrypt_with_buffers_no_per_segment_allocation() {
+    let cipher = new_cipher();
+    let pt = vec![0x42; SEGMENT_COUNT * SEGMENT_SIZE];
+    let mut w = cipher
+        .new_encrypting_writer_with_buffers(Box::new(NullWriter), AAD, cipher.segment_buffers())
+        .unwrap();
+
+    let count = allocations_during(|| {
+        w.write_all(&pt).unwrap();
+        w.close().unwrap();
+    });
+    assert_eq!(
+        count, 0,
+        "allocations while encrypting {} segments",
+        SEGMENT_COUNT
+    );
+}
+
+#[test]
+fn test_decrypt_with_buffers_no_per_segment_allocation() {
+    let cipher = new_cipher();
+    let pt = vec![0x42; SEGMENT_COUNT * SEGMENT_SIZE];
+    let buf = SharedBuf::new();
+    let mut w = cipher
+        .new_encrypting_writer_with_buffers(Box::new(buf.clone()), AAD, cipher.segment_buffers())
+        .unwrap();
+    w.write_all(&pt).unwrap();
+    w.close().unwrap();
+    let mut ct = vec![];
+    buf.clone().read_to_end(&mut ct).unwrap();
+
+    let mut r = cipher
+        .new_decrypting_reader_with_buffers(
+            Box::new(Cursor::new(ct)),
+            AAD,
+            cipher.segment_buffers(),
+        )
+        .unwrap();
+    let mut got = vec![0; pt.len()];
+
+    let count = allocations_during(|| r.read_exact(&mut got).unwrap());
+    assert_eq!(
+        count, 0,
+        "allocations while decrypting {} segments",
+        SEGMENT_COUNT
+    );
+    assert_eq!(got, pt);
+}
+
+#[test]
+fn test_counting_allocator() {
+    let count = allocations_during(|| assert_eq!(format!("{}", SEGMENT_SIZE), "512"));
+    assert!(count > 0);
+}
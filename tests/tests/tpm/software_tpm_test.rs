@@ -0,0 +1,50 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use sha2::{Digest, Sha256};
+use tink_tests::expect_err;
+use tink_tpm::{SoftwareTpm, TpmDevice};
+
+#[test]
+fn test_software_tpm_extend() {
+    let tpm = SoftwareTpm::new();
+    assert_eq!(tpm.pcr_value(5).unwrap(), [0; 32]);
+    tpm.extend_pcr(5, b"measurement").unwrap();
+
+    let mut h = Sha256::new();
+    h.update([0; 32]);
+    h.update(Sha256::digest(b"measurement"));
+    assert_eq!(tpm.pcr_value(5).unwrap()[..], h.finalize()[..]);
+    assert_eq!(tpm.pcr_value(4).unwrap(), [0; 32]);
+
+    expect_err(tpm.extend_pcr(24, b"x"), "invalid PCR index");
+    expect_err(tpm.pcr_value(24), "invalid PCR index");
+}
+
+#[test]
+fn test_software_tpm_seal() {
+    let tpm = SoftwareTpm::new();
+    let blob = tpm.seal(b"secret", &[3, 4]).unwrap();
+    assert_eq!(tpm.unseal(&blob, &[3, 4]).unwrap(), b"secret");
+    expect_err(tpm.unseal(&blob, &[3]), "PCR policy check failed");
+    expect_err(tpm.seal(b"secret", &[30]), "invalid PCR index");
+
+    // Sealed data is bound to the storage key of the device that sealed it.
+    let tpm2 = SoftwareTpm::new();
+    let blob = tpm2.seal(b"secret", &[]).unwrap();
+    expect_err(tpm.unseal(&blob, &[]), "PCR policy check failed");
+    assert_eq!(tpm2.unseal(&blob, &[]).unwrap(), b"secret");
+}
@@ -0,0 +1,109 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use std::sync::Arc;
+use tink_core::{
+    keyset::{insecure, Handle, MemReaderWriter},
+    registry::KmsClient,
+};
+use tink_tests::expect_err;
+use tink_tpm::{SoftwareTpm, TpmClient};
+
+const KEY_URI: &str = "tpm://sha256:0,2,7";
+
+#[test]
+fn test_tpm_client_supported() {
+    let client = TpmClient::new("tpm://", Arc::new(SoftwareTpm::new())).unwrap();
+    assert!(client.supported(KEY_URI));
+    assert!(!client.supported("aws-kms://arn:aws:kms:us-east-2:123:key/1"));
+
+    let client = TpmClient::new("tpm://sha256:7", Arc::new(SoftwareTpm::new())).unwrap();
+    assert!(client.supported("tpm://sha256:7"));
+    assert!(!client.supported(KEY_URI));
+    expect_err(client.get_aead(KEY_URI), "must start with prefix");
+
+    expect_err(
+        TpmClient::new("gcp-kms://", Arc::new(SoftwareTpm::new())),
+        "must start with",
+    );
+}
+
+#[test]
+fn test_tpm_client_invalid_uri() {
+    let client = TpmClient::new("tpm://", Arc::new(SoftwareTpm::new())).unwrap();
+    for (uri, err) in &[
+        ("tpm://", "must have format"),
+        ("tpm://sha1:0", "must have format"),
+        ("tpm://sha256:24", "invalid PCR index"),
+        ("tpm://sha256:x", "invalid PCR index"),
+        ("tpm://sha256:0,", "invalid PCR index"),
+        ("tpm://sha256:3,3", "duplicate PCR index"),
+    ] {
+        tink_tests::expect_err_for_case(client.get_aead(uri), err, uri);
+    }
+    assert!(client.get_aead("tpm://sha256:").is_ok());
+}
+
+#[test]
+fn test_tpm_aead_round_trip() {
+    let tpm = Arc::new(SoftwareTpm::new());
+    let client = TpmClient::new("tpm://", tpm.clone()).unwrap();
+    let a = client.get_aead(KEY_URI).unwrap();
+    let pt = b"keyset-encryption key material";
+    let ct = a.encrypt(pt, b"aad").unwrap();
+    assert_eq!(a.decrypt(&ct, b"aad").unwrap(), pt);
+    expect_err(a.decrypt(&ct, b"other"), "");
+
+    // PCRs outside the selection do not matter.
+    tpm.extend_pcr(1, b"unmeasured component").unwrap();
+    assert_eq!(a.decrypt(&ct, b"aad").unwrap(), pt);
+
+    // A different selection, or a change to a selected PCR, prevents unsealing.
+    let other = client.get_aead("tpm://sha256:0,2").unwrap();
+    expect_err(other.decrypt(&ct, b"aad"), "PCR policy check failed");
+    tpm.extend_pcr(7, b"new secure boot policy").unwrap();
+    expect_err(a.decrypt(&ct, b"aad"), "PCR policy check failed");
+
+    // Data sealed by a different device cannot be unsealed.
+    let client2 = TpmClient::new("tpm://", Arc::new(SoftwareTpm::new())).unwrap();
+    let a2 = client2.get_aead(KEY_URI).unwrap();
+    expect_err(a2.decrypt(&ct, b"aad"), "failed to unseal key");
+
+    expect_err(a.decrypt(&ct[..3], b"aad"), "too short");
+    expect_err(a.decrypt(&ct[..10], b"aad"), "too short");
+}
+
+#[test]
+fn test_tpm_sealed_keyset() {
+    tink_aead::init();
+    let tpm = Arc::new(SoftwareTpm::new());
+    tpm.extend_pcr(0, b"firmware").unwrap();
+    let client = TpmClient::new("tpm://", tpm.clone()).unwrap();
+    let main_key = client.get_aead(KEY_URI).unwrap();
+
+    let kh1 = Handle::new(&tink_aead::aes256_gcm_key_template()).unwrap();
+    let mut mem_keyset = MemReaderWriter::default();
+    kh1.write(&mut mem_keyset, main_key.box_clone()).unwrap();
+    let kh2 = Handle::read(&mut mem_keyset, main_key.box_clone()).unwrap();
+    assert_eq!(
+        insecure::keyset_material(&kh1),
+        insecure::keyset_material(&kh2)
+    );
+
+    // After the boot chain changes, the keyset can no longer be loaded.
+    tpm.extend_pcr(2, b"option ROM").unwrap();
+    expect_err(Handle::read(&mut mem_keyset, main_key), "decryption failed");
+}
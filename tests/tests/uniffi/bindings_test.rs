@@ -0,0 +1,109 @@
+// Copyright 2020 The Tink-Rust Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+////////////////////////////////////////////////////////////////////////////////
+
+use tink_tests::{expect_err, fakekms};
+use tink_uniffi::{Aead, HybridDecrypt, HybridEncrypt, KeysetHandle};
+
+const HYBRID_TEMPLATE: &str = "ECIES_P256_HKDF_HMAC_SHA256_AES128_GCM";
+
+#[test]
+fn test_aead() {
+    let handle = KeysetHandle::generate_new("AES256_GCM".to_string()).unwrap();
+    let aead = Aead::new(handle).unwrap();
+    let ct = aead
+        .encrypt(b"plaintext".to_vec(), b"aad".to_vec())
+        .unwrap();
+    assert_eq!(
+        aead.decrypt(ct.clone(), b"aad".to_vec()).unwrap(),
+        b"plaintext"
+    );
+    expect_err(aead.decrypt(ct, b"other".to_vec()), "decryption failed");
+
+    expect_err(
+        KeysetHandle::generate_new("NOT_A_TEMPLATE".to_string()),
+        "unknown key template",
+    );
+    let hybrid = KeysetHandle::generate_new(HYBRID_TEMPLATE.to_string()).unwrap();
+    expect_err(Aead::new(hybrid), "not an AEAD primitive");
+}
+
+#[test]
+fn test_encrypted_keyset() {
+    let main_key =
+        Aead::new(KeysetHandle::generate_new("AES128_GCM".to_string()).unwrap()).unwrap();
+    let handle = KeysetHandle::generate_new("AES256_GCM".to_string()).unwrap();
+    let encrypted = handle
+        .write_encrypted(main_key.clone(), b"keyset ad".to_vec())
+        .unwrap();
+
+    let handle2 =
+        KeysetHandle::read_encrypted(encrypted.clone(), main_key.clone(), b"keyset ad".to_vec())
+            .unwrap();
+    assert_eq!(handle.primary_key_id(), handle2.primary_key_id());
+    let ct = Aead::new(handle)
+        .unwrap()
+        .encrypt(b"plaintext".to_vec(), vec![])
+        .unwrap();
+    assert_eq!(
+        Aead::new(handle2).unwrap().decrypt(ct, vec![]).unwrap(),
+        b"plaintext"
+    );
+
+    expect_err(
+        KeysetHandle::read_encrypted(encrypted, main_key, b"other ad".to_vec()),
+        "decryption failed",
+    );
+}
+
+#[test]
+fn test_kms_main_key() {
+    let key_uri = fakekms::new_key_uri().unwrap();
+    expect_err(Aead::from_kms_uri(key_uri.clone()), "not found");
+    tink_core::registry::register_kms_client(fakekms::FakeClient::new(&key_uri).unwrap());
+
+    let main_key = Aead::from_kms_uri(key_uri).unwrap();
+    let handle = KeysetHandle::generate_new("AES256_GCM".to_string()).unwrap();
+    let encrypted = handle.write_encrypted(main_key.clone(), vec![]).unwrap();
+    let handle2 = KeysetHandle::read_encrypted(encrypted, main_key, vec![]).unwrap();
+    assert_eq!(handle.primary_key_id(), handle2.primary_key_id());
+}
+
+#[test]
+fn test_hybrid() {
+    let private = KeysetHandle::generate_new(HYBRID_TEMPLATE.to_string()).unwrap();
+    expect_err(
+        private.write_no_secret(),
+        "secret key material is forbidden",
+    );
+
+    // Public keysets can be exported in the clear.
+    let public = private.public_handle().unwrap();
+    let public_keyset = public.write_no_secret().unwrap();
+    let public = KeysetHandle::read_no_secret(public_keyset).unwrap();
+
+    let enc = HybridEncrypt::new(public.clone()).unwrap();
+    let dec = HybridDecrypt::new(private).unwrap();
+    let ct = enc
+        .encrypt(b"plaintext".to_vec(), b"context".to_vec())
+        .unwrap();
+    assert_eq!(
+        dec.decrypt(ct.clone(), b"context".to_vec()).unwrap(),
+        b"plaintext"
+    );
+    expect_err(dec.decrypt(ct, b"other".to_vec()), "decryption failed");
+
+    expect_err(HybridDecrypt::new(public), "not a HybridDecrypt primitive");
+}